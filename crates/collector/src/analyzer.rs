@@ -14,8 +14,8 @@ use std::process::ExitStatus;
 use oghutils::version::OdooVersion;
 use sqlitedb::models::module;
 use sqlitedb::models::module::{CommitterActivity, ManifestInfo};
-use sqlitedb::models::module_code_analysis::ModuleAnalysisInfo;
-use sqlitedb::DbSqliteConnection;
+use sqlitedb::models::module_code_analysis::{ModuleAnalysisInfo, OwnershipAnalysisInfo};
+use sqlitedb::DbConnection;
 
 use crate::gitclient::RepoInfo;
 
@@ -656,15 +656,65 @@ pub struct GitInfo {
     pub last_commit_partof: String,
 }
 
+/// A read path or module of a repository that `get_module_info` had to skip.
+#[derive(Debug, Clone)]
+pub struct AnalysisErrorInfo {
+    pub repo_full_path: String,
+    /// `None` when the whole read path couldn't be read.
+    pub module_name: Option<String>,
+    pub message: String,
+}
+
+/// Which side of a commit `get_git_committers` credits. The author wrote
+/// the change; the committer is whoever applied it, which on OCA repos is
+/// usually the merge bot, so rankings are built from authors unless
+/// `OGHCOLLECTOR_COMMIT_IDENTITY=committer` asks otherwise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommitIdentity {
+    #[default]
+    Author,
+    Committer,
+}
+
+impl CommitIdentity {
+    fn from_env() -> CommitIdentity {
+        match std::env::var("OGHCOLLECTOR_COMMIT_IDENTITY") {
+            Ok(value) if value.eq_ignore_ascii_case("committer") => CommitIdentity::Committer,
+            _ => CommitIdentity::Author,
+        }
+    }
+
+    // %aN/%aE (%cN/%cE) are name/email after .mailmap resolution; %as/%cs
+    // the matching `YYYY-MM-DD` date.
+    fn log_format(&self) -> &'static str {
+        match self {
+            CommitIdentity::Author => "--pretty=%aN%x1f%aE%x1f%as",
+            CommitIdentity::Committer => "--pretty=%cN%x1f%cE%x1f%cs",
+        }
+    }
+}
+
+// Global alias file in git's .mailmap format, applied on top of each
+// repository's own `.mailmap`. Overridable with OGHCOLLECTOR_MAILMAP.
+const DEFAULT_MAILMAP_PATH: &str = "files/mailmap";
+
 #[derive(Debug)]
 pub struct OGHCollectorAnalyzer {
     version_odoo: u8,
+    commit_identity: CommitIdentity,
+    mailmap_file: Option<PathBuf>,
 }
 
 impl OGHCollectorAnalyzer {
     pub fn new(version_odoo: &u8) -> OGHCollectorAnalyzer {
+        let mailmap_file = std::env::var("OGHCOLLECTOR_MAILMAP")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_MAILMAP_PATH));
         OGHCollectorAnalyzer {
             version_odoo: *version_odoo,
+            commit_identity: CommitIdentity::from_env(),
+            // git resolves a relative mailmap.file against each repo, not our cwd
+            mailmap_file: fs::canonicalize(mailmap_file).ok(),
         }
     }
 
@@ -795,22 +845,25 @@ impl OGHCollectorAnalyzer {
             return Ok(HashMap::new());
         };
 
-        // %cs is the committer date in `YYYY-MM-DD` (no time/tz), so year/month can be
-        // sliced directly. Kept on the same log line as %cn so the per-committer total
-        // and the per-(year, month) breakdown always come from the same commit set.
-        // `--shortstat` adds one "N files changed, X insertions(+), Y deletions(-)"
-        // line after each commit (either half omitted when zero); since it never
-        // contains \x1f, the split_once branch below always skips it, so we only
-        // need a second pass to fold it into whichever committer we last saw.
-        let output = cmd!(
+        // The date is `YYYY-MM-DD` (no time/tz), so year/month can be sliced
+        // directly. Kept on the same log line as the name/email so the
+        // per-identity total and the per-(year, month) breakdown always come
+        // from the same commit set. `--shortstat` adds one "N files changed, X
+        // insertions(+), Y deletions(-)" line after each commit (either half
+        // omitted when zero); since it never contains \x1f, the split branch
+        // below always skips it, so we only need a second pass to fold it into
+        // whichever identity we last saw.
+        let output = cmd(
             "git",
-            "--no-pager",
-            "log",
-            &log_range,
-            "--shortstat",
-            "--pretty=%cn%x1f%cs",
-            "--",
-            "."
+            self.git_args(&[
+                "--no-pager",
+                "log",
+                log_range.as_str(),
+                "--shortstat",
+                self.commit_identity.log_format(),
+                "--",
+                ".",
+            ]),
         )
         .dir(folder_path)
         .stdin_null()
@@ -822,11 +875,16 @@ impl OGHCollectorAnalyzer {
         )
         .unwrap();
 
-        let mut committers: HashMap<String, CommitterActivity> = HashMap::new();
-        let mut current_committer: Option<String> = None;
+        // Keyed by (name, email) first; identities sharing an email are
+        // folded together below.
+        let mut identities: HashMap<(String, String), CommitterActivity> = HashMap::new();
+        let mut current_identity: Option<(String, String)> = None;
         for line in output.lines() {
-            if let Some((name, date)) = line.split_once('\u{1f}') {
-                current_committer = None;
+            let mut fields = line.splitn(3, '\u{1f}');
+            if let (Some(name), Some(email), Some(date)) =
+                (fields.next(), fields.next(), fields.next())
+            {
+                current_identity = None;
                 if date.len() < 7 {
                     continue;
                 }
@@ -834,14 +892,15 @@ impl OGHCollectorAnalyzer {
                 else {
                     continue;
                 };
-                let activity = committers.entry(name.to_string()).or_default();
+                let key = (name.to_string(), email.trim().to_lowercase());
+                let activity = identities.entry(key.clone()).or_default();
                 activity.total += 1;
                 *activity.periods.entry((year, month)).or_insert(0) += 1;
-                current_committer = Some(name.to_string());
+                current_identity = Some(key);
                 continue;
             }
-            if let (Some(name), Some(caps)) = (&current_committer, shortstat_re.captures(line)) {
-                let activity = committers.entry(name.clone()).or_default();
+            if let (Some(key), Some(caps)) = (&current_identity, shortstat_re.captures(line)) {
+                let activity = identities.entry(key.clone()).or_default();
                 activity.insertions += caps
                     .get(1)
                     .and_then(|m| m.as_str().parse::<u32>().ok())
@@ -852,7 +911,133 @@ impl OGHCollectorAnalyzer {
                     .unwrap_or(0);
             }
         }
-        Ok(committers)
+        Ok(Self::merge_identities(identities))
+    }
+
+    /// `args` with the global mailmap (if any) passed along, so every git
+    /// command reporting identities resolves them the same way.
+    fn git_args(&self, args: &[&str]) -> Vec<String> {
+        let mut git_args: Vec<String> = Vec::new();
+        if let Some(mailmap_file) = &self.mailmap_file {
+            git_args.push("-c".to_string());
+            git_args.push(format!("mailmap.file={}", mailmap_file.display()));
+        }
+        git_args.extend(args.iter().map(|a| a.to_string()));
+        git_args
+    }
+
+    /// Surviving-lines ownership of the module's current tree: `git blame`
+    /// over every tracked text file (translations excluded, they're authored
+    /// by whoever ran Weblate), counting the lines each identity last
+    /// touched. Only runs when the module is re-analyzed, so an unchanged
+    /// module never pays for it again.
+    fn get_git_ownership(&self, folder_path: &std::path::Path) -> Vec<OwnershipAnalysisInfo> {
+        log::info!("Get git ownership info...");
+        // -I skips binary files; an empty pattern matches every non-empty file.
+        let files = cmd(
+            "git",
+            self.git_args(&[
+                "grep",
+                "-I",
+                "-l",
+                "-e",
+                "",
+                "--",
+                ".",
+                ":(exclude)i18n",
+                ":(exclude)i18n_extra",
+            ]),
+        )
+        .dir(folder_path)
+        .stdin_null()
+        .stderr_null()
+        .read()
+        .unwrap_or_else(|_| String::new());
+
+        let (name_prefix, email_prefix) = match self.commit_identity {
+            CommitIdentity::Author => ("author ", "author-mail "),
+            CommitIdentity::Committer => ("committer ", "committer-mail "),
+        };
+        let mut identities: HashMap<(String, String), CommitterActivity> = HashMap::new();
+        for file in files.lines().filter(|f| !f.is_empty()) {
+            let Ok(output) = cmd(
+                "git",
+                self.git_args(&["blame", "--line-porcelain", "-w", "HEAD", "--", file]),
+            )
+            .dir(folder_path)
+            .stdin_null()
+            .stderr_null()
+            .read() else {
+                continue;
+            };
+            // --line-porcelain repeats the full header for every line, name
+            // always before email.
+            let mut current_name = String::new();
+            for line in output.lines() {
+                if let Some(name) = line.strip_prefix(name_prefix) {
+                    current_name = name.to_string();
+                } else if let Some(email) = line.strip_prefix(email_prefix) {
+                    let email = email.trim_start_matches('<').trim_end_matches('>');
+                    let key = (current_name.clone(), email.trim().to_lowercase());
+                    identities.entry(key).or_default().total += 1;
+                }
+            }
+        }
+
+        // `total` holds lines here, so identities fold exactly like commits.
+        let mut owners: Vec<OwnershipAnalysisInfo> = Self::merge_identities(identities)
+            .into_iter()
+            .map(|(name, activity)| OwnershipAnalysisInfo {
+                name,
+                emails: activity.emails,
+                lines: activity.total,
+            })
+            .collect();
+        owners.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.name.cmp(&b.name)));
+        owners
+    }
+
+    /// Folds (name, email) identities into one entry per person: names that
+    /// share an email are the same person (`Pedro M. Baeza`/`Pedro Baeza`),
+    /// credited under the name used on most of those commits. Identities
+    /// with no email are kept by name.
+    fn merge_identities(
+        identities: HashMap<(String, String), CommitterActivity>,
+    ) -> HashMap<String, CommitterActivity> {
+        let mut by_email: HashMap<String, Vec<(String, CommitterActivity)>> = HashMap::new();
+        let mut committers: HashMap<String, CommitterActivity> = HashMap::new();
+        for ((name, email), activity) in identities {
+            if email.is_empty() {
+                Self::fold_activity(committers.entry(name).or_default(), activity);
+            } else {
+                by_email.entry(email).or_default().push((name, activity));
+            }
+        }
+        for (email, mut names) in by_email {
+            names.sort_by(|(a_name, a), (b_name, b)| {
+                b.total.cmp(&a.total).then_with(|| a_name.cmp(b_name))
+            });
+            let canonical = names[0].0.clone();
+            let entry = committers.entry(canonical).or_default();
+            for (_, activity) in names {
+                Self::fold_activity(entry, activity);
+            }
+            entry.emails.push(email);
+        }
+        for activity in committers.values_mut() {
+            activity.emails.sort();
+        }
+        committers
+    }
+
+    fn fold_activity(target: &mut CommitterActivity, activity: CommitterActivity) {
+        target.total += activity.total;
+        target.insertions += activity.insertions;
+        target.deletions += activity.deletions;
+        for (period, commits) in activity.periods {
+            *target.periods.entry(period).or_insert(0) += commits;
+        }
+        target.emails.extend(activity.emails);
     }
 
     /// Best-effort read of `<module>/readme/<filename>` - not every module has
@@ -863,6 +1048,29 @@ impl OGHCollectorAnalyzer {
         (!text.is_empty()).then(|| text.to_string())
     }
 
+    /// `test_*.py` files under `<module>/tests`, subfolders included: what Odoo's
+    /// test runner picks up. No `tests` folder means no tests.
+    fn count_test_files(module_path: &std::path::Path) -> usize {
+        let Ok(entries) = fs::read_dir(module_path.join("tests")) else {
+            return 0;
+        };
+        let mut count = 0;
+        let mut pending: Vec<_> = entries.flatten().collect();
+        while let Some(entry) = pending.pop() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.extend(fs::read_dir(&path).into_iter().flatten().flatten());
+            } else if path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("test_") && name.ends_with(".py"))
+            {
+                count += 1;
+            }
+        }
+        count
+    }
+
     /// Best-effort read of `<module>/static/description/icon.png`, base64-encoded.
     fn read_icon(module_path: &std::path::Path) -> Option<String> {
         let bytes = fs::read(module_path.join("static/description/icon.png")).ok()?;
@@ -888,6 +1096,13 @@ impl OGHCollectorAnalyzer {
             } else {
                 String::new()
             };
+            // summary
+            let summary_opt = manifest.get_item("summary");
+            let summary: String = if let Some(summary_value) = summary_opt {
+                summary_value.downcast::<PyString>()?.extract::<String>()?
+            } else {
+                String::new()
+            };
             // description - readme/DESCRIPTION.md (OCA's rendered readme fragment) wins
             // over the manifest's `description` key when present, since the manifest
             // value is often stale or just a placeholder for the generated readme.
@@ -1074,6 +1289,7 @@ impl OGHCollectorAnalyzer {
                 version_odoo,
                 name,
                 version_module,
+                summary,
                 description,
                 installation,
                 usage,
@@ -1147,12 +1363,13 @@ impl OGHCollectorAnalyzer {
     // that case and make the env var unnecessary; add if this bites someone.
     pub fn get_module_info(
         &self,
-        conn: &mut DbSqliteConnection,
+        conn: &mut DbConnection,
         read_paths: &Vec<String>,
         repo_infos: &Vec<RepoInfo>,
-    ) -> Vec<ManifestInfo> {
+    ) -> (Vec<ManifestInfo>, Vec<AnalysisErrorInfo>) {
         let force_reanalyze = std::env::var("OGHCOLLECTOR_FORCE_REANALYZE").is_ok();
         let mut manifest_infos: Vec<ManifestInfo> = Vec::new();
+        let mut errors: Vec<AnalysisErrorInfo> = Vec::new();
         for repo_info in repo_infos {
             for read_path in read_paths {
                 let base_path =
@@ -1164,6 +1381,11 @@ impl OGHCollectorAnalyzer {
                     Ok(entries) => entries,
                     Err(err) => {
                         log::warn!("Can't read '{}': {err}. Skipping...", base_path.display());
+                        errors.push(AnalysisErrorInfo {
+                            repo_full_path: repo_info.get_full_path().to_string(),
+                            module_name: None,
+                            message: format!("Can't read '{read_path}': {err}"),
+                        });
                         continue;
                     }
                 };
@@ -1205,10 +1427,13 @@ impl OGHCollectorAnalyzer {
                             ModuleAnalysisInfo::default(),
                         )
                     } else {
+                        let mut analysis = self.analyze_module_source(&path);
+                        analysis.ownership = self.get_git_ownership(&path);
+                        analysis.test_files = Some(Self::count_test_files(&path));
                         (
                             get_size(&path).unwrap_or(0),
                             self.get_git_committers(&path).unwrap_or_default(),
-                            self.analyze_module_source(&path),
+                            analysis,
                         )
                     };
 
@@ -1224,6 +1449,11 @@ impl OGHCollectorAnalyzer {
                             log::warn!(
                                 "Can't read manifest '{manifest_path}': {err}. Skipping module..."
                             );
+                            errors.push(AnalysisErrorInfo {
+                                repo_full_path: repo_info.get_full_path().to_string(),
+                                module_name: Some(module_name.to_string()),
+                                message: format!("Can't read the manifest: {err}"),
+                            });
                             continue;
                         }
                     };
@@ -1240,7 +1470,7 @@ impl OGHCollectorAnalyzer {
                 }
             }
         }
-        manifest_infos
+        (manifest_infos, errors)
     }
 }
 
@@ -1288,6 +1518,27 @@ mod tests {
         fs::remove_dir_all(&dir).unwrap();
     }
 
+    #[test]
+    fn test_count_test_files() {
+        let dir = std::env::temp_dir().join(format!(
+            "oghcollector_analyzer_test_{}_{}",
+            std::process::id(),
+            "count_test_files"
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(OGHCollectorAnalyzer::count_test_files(&dir), 0);
+
+        fs::create_dir_all(dir.join("tests").join("common")).unwrap();
+        fs::write(dir.join("tests").join("__init__.py"), "").unwrap();
+        fs::write(dir.join("tests").join("test_sale.py"), "").unwrap();
+        fs::write(dir.join("tests").join("common.py"), "").unwrap();
+        fs::write(dir.join("tests").join("common").join("test_flow.py"), "").unwrap();
+        fs::write(dir.join("tests").join("test_data.xml"), "").unwrap();
+        assert_eq!(OGHCollectorAnalyzer::count_test_files(&dir), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_read_icon() {
         let dir = std::env::temp_dir().join(format!(
@@ -1749,8 +2000,8 @@ class PartnerKindController(http.Controller):
     // Exercises get_git_committers end to end against a real repo: two fake
     // `origin/X.Y` refs bound the log range, and two authors each contribute a
     // commit inside it, so this proves the --shortstat parsing added alongside
-    // the existing %cn/%cs line-splitting (insertions/deletions folded onto
-    // the last-seen committer) attributes lines to the right person.
+    // the name/email/date line-splitting (insertions/deletions folded onto
+    // the last-seen identity) attributes lines to the right person.
     #[test]
     fn test_get_git_committers_counts_lines_per_author() {
         let dir = std::env::temp_dir().join(format!(
@@ -1853,6 +2104,148 @@ class PartnerKindController(http.Controller):
         fs::remove_dir_all(&dir).unwrap();
     }
 
+    // Authors, not the committing bot, are credited by default; identities
+    // are resolved through the repo's .mailmap and the global alias file,
+    // then merged by email under the most used name.
+    #[test]
+    fn test_get_git_committers_resolves_identities() {
+        let dir = std::env::temp_dir().join(format!(
+            "oghcollector_analyzer_test_{}_{}",
+            std::process::id(),
+            "git_identities"
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let git = |args: &[&str]| {
+            cmd(
+                "git",
+                args.iter().map(|a| a.to_string()).collect::<Vec<_>>(),
+            )
+            .dir(&dir)
+            .stdin_null()
+            .stdout_null()
+            .stderr_null()
+            .run()
+            .unwrap();
+        };
+        let commit_as = |author: &str, n: usize| {
+            fs::write(dir.join("file.txt"), format!("{n}\n")).unwrap();
+            git(&["add", "-A"]);
+            git(&[
+                "-c",
+                "user.name=OCA-git-bot",
+                "-c",
+                "user.email=bot@test.com",
+                "commit",
+                "-q",
+                "--author",
+                author,
+                "-m",
+                "change",
+            ]);
+        };
+
+        git(&["init", "-q", "-b", "work"]);
+        commit_as("Base <base@test.com>", 0);
+        git(&["update-ref", "refs/remotes/origin/15.0", "HEAD"]);
+        commit_as("Pedro M. Baeza <pedro@test.com>", 1);
+        commit_as("Pedro Baeza <Pedro@test.com>", 2);
+        commit_as("pbaeza <old@test.com>", 3);
+        commit_as("Bob <bob@home.com>", 4);
+        git(&["update-ref", "refs/remotes/origin/16.0", "HEAD"]);
+
+        fs::write(
+            dir.join(".mailmap"),
+            "Pedro Baeza <pedro@test.com> <old@test.com>\n",
+        )
+        .unwrap();
+        let global_mailmap = dir.with_extension("mailmap");
+        fs::write(&global_mailmap, "Bob <bob@test.com> <bob@home.com>\n").unwrap();
+
+        let mut analyzer = OGHCollectorAnalyzer::new(&160u8);
+        analyzer.commit_identity = CommitIdentity::Author;
+        analyzer.mailmap_file = Some(global_mailmap.clone());
+        let committers = analyzer.get_git_committers(&dir).unwrap();
+        assert_eq!(committers.len(), 2);
+        let pedro = committers.get("Pedro Baeza").expect("merged by email");
+        assert_eq!(pedro.total, 3);
+        assert_eq!(pedro.emails, vec!["pedro@test.com".to_string()]);
+        let bob = committers.get("Bob").expect("Bob should have committed");
+        assert_eq!(bob.emails, vec!["bob@test.com".to_string()]);
+
+        analyzer.commit_identity = CommitIdentity::Committer;
+        let committers = analyzer.get_git_committers(&dir).unwrap();
+        assert_eq!(committers.len(), 1);
+        assert_eq!(committers.get("OCA-git-bot").unwrap().total, 4);
+
+        fs::remove_file(&global_mailmap).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // Ownership counts the lines each identity last touched in the current
+    // tree, mailmap-resolved like the commit stats, skipping translations
+    // and binary files.
+    #[test]
+    fn test_get_git_ownership_counts_surviving_lines() {
+        let dir = std::env::temp_dir().join(format!(
+            "oghcollector_analyzer_test_{}_{}",
+            std::process::id(),
+            "git_ownership"
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("i18n")).unwrap();
+
+        let git = |args: &[&str]| {
+            cmd(
+                "git",
+                args.iter().map(|a| a.to_string()).collect::<Vec<_>>(),
+            )
+            .dir(&dir)
+            .stdin_null()
+            .stdout_null()
+            .stderr_null()
+            .run()
+            .unwrap();
+        };
+        let commit_as = |author: &str| {
+            git(&["add", "-A"]);
+            git(&[
+                "-c",
+                "user.name=OCA-git-bot",
+                "-c",
+                "user.email=bot@test.com",
+                "commit",
+                "-q",
+                "--author",
+                author,
+                "-m",
+                "change",
+            ]);
+        };
+
+        git(&["init", "-q", "-b", "work"]);
+        fs::write(dir.join("models.py"), "a\nb\nc\nd\n").unwrap();
+        fs::write(dir.join("i18n/es.po"), "msgid \"a\"\nmsgstr \"a\"\n").unwrap();
+        fs::write(dir.join("icon.png"), [0u8, 159, 146, 150, 0, 10]).unwrap();
+        commit_as("Alice <alice@test.com>");
+        // Bob rewrites one of Alice's lines and adds two of his own.
+        fs::write(dir.join("models.py"), "a\nB\nc\nd\ne\nf\n").unwrap();
+        fs::write(dir.join("i18n/es.po"), "msgid \"a\"\nmsgstr \"b\"\n").unwrap();
+        commit_as("Bob B. <old@test.com>");
+        fs::write(dir.join(".mailmap"), "Bob <bob@test.com> <old@test.com>\n").unwrap();
+
+        let mut analyzer = OGHCollectorAnalyzer::new(&160u8);
+        analyzer.commit_identity = CommitIdentity::Author;
+        analyzer.mailmap_file = None;
+        let owners = analyzer.get_git_ownership(&dir);
+        let summary: Vec<(&str, u32)> = owners.iter().map(|o| (o.name.as_str(), o.lines)).collect();
+        assert_eq!(summary, vec![("Alice", 3), ("Bob", 3)]);
+        assert_eq!(owners[1].emails, vec!["bob@test.com".to_string()]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     // Proves the incremental-analysis short-circuit end to end: a module
     // analyzed once and persisted (like main.rs does after every run) must
     // be skipped on a re-run with no new commits, and picked back up as soon
@@ -1899,10 +2292,7 @@ class PartnerKindController(http.Controller):
             "init",
         ]);
 
-        let db_path = dir.join("data.db");
-        let pool = sqlitedb::new_write_pool(db_path.to_str().unwrap());
-        let mut conn = pool.get().unwrap();
-        sqlitedb::run_migrations(&mut conn).unwrap();
+        let mut conn = sqlitedb::establish_test_connection();
 
         let repo_infos = vec![RepoInfo {
             name: "test_repo".to_string(),
@@ -1914,7 +2304,7 @@ class PartnerKindController(http.Controller):
         let analyzer = OGHCollectorAnalyzer::new(&160u8);
 
         // First run: nothing stored yet, so it must analyze and report changed.
-        let first = analyzer.get_module_info(&mut conn, &read_paths, &repo_infos);
+        let (first, _) = analyzer.get_module_info(&mut conn, &read_paths, &repo_infos);
         assert_eq!(first.len(), 1);
         assert!(!first[0].source_unchanged);
         assert!(!first[0].last_commit_hash.is_empty());
@@ -1923,7 +2313,7 @@ class PartnerKindController(http.Controller):
         module::add(&mut conn, &first[0]).unwrap();
 
         // Second run, no new commits: must skip re-analysis.
-        let second = analyzer.get_module_info(&mut conn, &read_paths, &repo_infos);
+        let (second, _) = analyzer.get_module_info(&mut conn, &read_paths, &repo_infos);
         assert_eq!(second.len(), 1);
         assert!(second[0].source_unchanged);
         assert_eq!(second[0].last_commit_hash, first[0].last_commit_hash);
@@ -1941,7 +2331,7 @@ class PartnerKindController(http.Controller):
             "-m",
             "touch module",
         ]);
-        let third = analyzer.get_module_info(&mut conn, &read_paths, &repo_infos);
+        let (third, _) = analyzer.get_module_info(&mut conn, &read_paths, &repo_infos);
         assert_eq!(third.len(), 1);
         assert!(!third[0].source_unchanged);
         assert_ne!(third[0].last_commit_hash, first[0].last_commit_hash);
@@ -1951,7 +2341,7 @@ class PartnerKindController(http.Controller):
         // above) - otherwise the skip could never re-arm after a module's
         // first real change.
         module::add(&mut conn, &third[0]).unwrap();
-        let fourth = analyzer.get_module_info(&mut conn, &read_paths, &repo_infos);
+        let (fourth, _) = analyzer.get_module_info(&mut conn, &read_paths, &repo_infos);
         assert_eq!(fourth.len(), 1);
         assert!(fourth[0].source_unchanged);
         assert_eq!(fourth[0].last_commit_hash, third[0].last_commit_hash);
@@ -1,6 +1,8 @@
 use crate::clients::github::GithubClient;
 use crate::clients::gitlab::GitlabClient;
-use crate::gitclient::{GitClient, PullRequestInfo, RepoInfo};
+use crate::gitclient::{CloneReport, GitClient};
+use sqlitedb::models::open_pull_request::OpenPullRequestInfo;
+use sqlitedb::models::pull_request_history::PullRequestClosureInfo;
 
 pub enum AnyGitClient {
     Github(GithubClient),
@@ -19,6 +21,13 @@ impl GitClient for AnyGitClient {
         }
     }
 
+    fn get_api_calls(&self) -> u64 {
+        match self {
+            AnyGitClient::Github(c) => c.get_api_calls(),
+            AnyGitClient::Gitlab(c) => c.get_api_calls(),
+        }
+    }
+
     async fn request_json(&self, url: &str) -> Result<serde_json::Value, reqwest::Error> {
         match self {
             AnyGitClient::Github(c) => c.request_json(url).await,
@@ -38,7 +47,7 @@ impl GitClient for AnyGitClient {
         }
     }
 
-    async fn clone_org_repos(&self, org_name: &str, branch: &str, dest: &str) -> Vec<RepoInfo> {
+    async fn clone_org_repos(&self, org_name: &str, branch: &str, dest: &str) -> CloneReport {
         match self {
             AnyGitClient::Github(c) => c.clone_org_repos(org_name, branch, dest).await,
             AnyGitClient::Gitlab(c) => c.clone_org_repos(org_name, branch, dest).await,
@@ -64,21 +73,25 @@ impl GitClient for AnyGitClient {
         }
     }
 
-    async fn get_open_migration_pull_requests(
+    async fn get_open_pull_requests(
         &self,
         full_path: &str,
         branch: &str,
-    ) -> Vec<PullRequestInfo> {
+    ) -> Vec<OpenPullRequestInfo> {
         match self {
-            AnyGitClient::Github(c) => c.get_open_migration_pull_requests(full_path, branch).await,
-            AnyGitClient::Gitlab(c) => c.get_open_migration_pull_requests(full_path, branch).await,
+            AnyGitClient::Github(c) => c.get_open_pull_requests(full_path, branch).await,
+            AnyGitClient::Gitlab(c) => c.get_open_pull_requests(full_path, branch).await,
         }
     }
 
-    async fn is_pull_request_merged(&self, full_path: &str, number: &i64) -> Option<bool> {
+    async fn get_pull_request_closure(
+        &self,
+        full_path: &str,
+        number: &i64,
+    ) -> Option<PullRequestClosureInfo> {
         match self {
-            AnyGitClient::Github(c) => c.is_pull_request_merged(full_path, number).await,
-            AnyGitClient::Gitlab(c) => c.is_pull_request_merged(full_path, number).await,
+            AnyGitClient::Github(c) => c.get_pull_request_closure(full_path, number).await,
+            AnyGitClient::Gitlab(c) => c.get_pull_request_closure(full_path, number).await,
         }
     }
 }
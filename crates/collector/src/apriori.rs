@@ -0,0 +1,91 @@
+// Copyright Alexandre D. Díaz
+//! Reader for OpenUpgrade's `apriori.py` (one per Odoo version, in
+//! `openupgrade_scripts/apriori.py`), which lists the modules renamed or
+//! merged into another one when upgrading *to* that version.
+use regex::Regex;
+
+#[derive(Debug, Default)]
+pub struct AprioriInfo {
+    pub renamed_modules: Vec<(String, String)>,
+    pub merged_modules: Vec<(String, String)>,
+}
+
+/// `"old": "new"` pairs of the `name = {...}` dict literal, comments
+/// skipped. An absent dict yields no pairs.
+fn parse_dict(content: &str, name: &str) -> Vec<(String, String)> {
+    let start_re = Regex::new(&format!(r"(?m)^{name}\s*=\s*\{{")).unwrap();
+    let pair_re = Regex::new(r#"^\s*["']([\w.]+)["']\s*:\s*["']([\w.]+)["']"#).unwrap();
+    let Some(start) = start_re.find(content) else {
+        return Vec::new();
+    };
+    let mut pairs = Vec::new();
+    for line in content[start.end()..].lines() {
+        let line = line.split('#').next().unwrap_or("");
+        if line.trim_start().starts_with('}') {
+            break;
+        }
+        if let Some(caps) = pair_re.captures(line) {
+            pairs.push((caps[1].to_string(), caps[2].to_string()));
+        }
+    }
+    pairs
+}
+
+pub fn parse_apriori(content: &str) -> AprioriInfo {
+    AprioriInfo {
+        renamed_modules: parse_dict(content, "renamed_modules"),
+        merged_modules: parse_dict(content, "merged_modules"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_apriori;
+
+    #[test]
+    fn test_parse_apriori_reads_renamed_and_merged_modules() {
+        let content = r#"""" Encode any known changes to the database here
+to help the matching process
+"""
+
+renamed_modules = {
+    # Odoo
+    "website_sale_stock_product_configurator": "website_sale_stock",
+    # OCA/web
+    'web_widget_color': 'web_widget_colorpicker',  # moved too
+}
+
+merged_modules = {
+    "account_edi_ubl": "account_edi_ubl_cii",
+    # "commented_out": "ignored",
+}
+
+# only used here for upgrade_analysis
+renamed_models = {
+    "account.account.type": "account.account",
+}
+"#;
+        let info = parse_apriori(content);
+        assert_eq!(
+            info.renamed_modules,
+            vec![
+                (
+                    "website_sale_stock_product_configurator".to_string(),
+                    "website_sale_stock".to_string()
+                ),
+                (
+                    "web_widget_color".to_string(),
+                    "web_widget_colorpicker".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            info.merged_modules,
+            vec![(
+                "account_edi_ubl".to_string(),
+                "account_edi_ubl_cii".to_string()
+            )]
+        );
+        assert!(parse_apriori("").renamed_modules.is_empty());
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright Alexandre D. Díaz
+//! Decides which committers are bots/automation accounts, so rankings can
+//! skip them through `committer.is_bot`.
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+
+use sqlitedb::models;
+use sqlitedb::DbConnection;
+
+// Rules file, in the format documented at its top. Overridable with
+// OGHCOLLECTOR_BOTS; the shipped one is embedded as fallback so a run from
+// another working directory doesn't suddenly rank the OCA bots.
+const DEFAULT_BOTS_PATH: &str = "files/bots";
+const DEFAULT_BOTS_RULES: &str = include_str!("../../../files/bots");
+
+const REGEX_RULE_PREFIX: &str = "re:";
+const GITHUB_BOT_SUFFIX: &str = "[bot]";
+
+#[derive(Debug, Default)]
+pub struct BotRules {
+    names: HashSet<String>,
+    patterns: Vec<Regex>,
+}
+
+impl BotRules {
+    pub fn parse(content: &str) -> BotRules {
+        let mut rules = BotRules::default();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(pattern) = line.strip_prefix(REGEX_RULE_PREFIX) {
+                match Regex::new(pattern.trim()) {
+                    Ok(re) => rules.patterns.push(re),
+                    Err(err) => log::warn!("Ignoring invalid bot rule '{line}': {err}"),
+                }
+            } else {
+                rules.names.insert(line.to_string());
+            }
+        }
+        rules
+    }
+
+    pub fn load() -> BotRules {
+        let path = std::env::var("OGHCOLLECTOR_BOTS").unwrap_or(DEFAULT_BOTS_PATH.to_string());
+        match fs::read_to_string(&path) {
+            Ok(content) => BotRules::parse(&content),
+            Err(_) => BotRules::parse(DEFAULT_BOTS_RULES),
+        }
+    }
+
+    /// `admin_names` is the hand-edited `bot_account` list.
+    pub fn is_bot(&self, name: &str, admin_names: &HashSet<String>) -> bool {
+        name.ends_with(GITHUB_BOT_SUFFIX)
+            || self.names.contains(name)
+            || admin_names.contains(name)
+            || self.patterns.iter().any(|re| re.is_match(name))
+    }
+}
+
+/// Recomputes `committer.is_bot` for every committer. Returns how many
+/// flags changed.
+pub fn refresh_committer_flags(conn: &mut DbConnection, rules: &BotRules) -> usize {
+    let admin_names: HashSet<String> = models::bot_account::get_names(conn).into_iter().collect();
+    let bot_ids: Vec<i64> = models::committer::get_all(conn)
+        .into_iter()
+        .filter(|com| rules.is_bot(&com.name, &admin_names))
+        .map(|com| com.id)
+        .collect();
+    models::committer::set_bots(conn, &bot_ids).unwrap_or_else(|err| {
+        log::warn!("Can't update the committer bot flags: {err}");
+        0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BotRules, DEFAULT_BOTS_RULES};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_bot_rules() {
+        let rules =
+            BotRules::parse("# comment\nOCA-git-bot\nre:^ci-.*$\nre:([invalid\n\n  Weblate  \n");
+        let admin: HashSet<String> = ["Internal Deployer".to_string()].into_iter().collect();
+        assert!(rules.is_bot("OCA-git-bot", &admin));
+        assert!(rules.is_bot("Weblate", &admin));
+        assert!(rules.is_bot("ci-runner", &admin));
+        assert!(rules.is_bot("renovate[bot]", &admin));
+        assert!(rules.is_bot("Internal Deployer", &admin));
+        assert!(!rules.is_bot("oca-git-bot", &admin));
+        assert!(!rules.is_bot("Pedro Baeza", &admin));
+
+        let defaults = BotRules::parse(DEFAULT_BOTS_RULES);
+        assert!(defaults.is_bot("Renovate Bot", &HashSet::new()));
+        assert!(defaults.is_bot("pre-commit-ci", &HashSet::new()));
+        assert!(!defaults.is_bot("Renovated Man", &HashSet::new()));
+    }
+}
@@ -1,17 +1,50 @@
 // Copyright Alexandre D. Díaz
-use crate::gitclient::{
-    extract_migration_module_name, parse_created_at, GitClient, PullRequestInfo, RepoInfo,
-};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::gitclient::{extract_module_folders, parse_created_at, CloneReport, GitClient};
+use sqlitedb::models::open_pull_request::OpenPullRequestInfo;
+use sqlitedb::models::pull_request_history::{self, PullRequestClosureInfo};
+use sqlitedb::models::pull_request_review::{self, PullRequestReviewInfo};
 
 const GITHUB_API_VERSION: &str = "2022-11-28";
 const GITHUB_BASE_URL: &str = "https://api.github.com/";
 const GITHUB_LIMIT_PER_PAGE: usize = 50;
 const GITHUB_LIMIT_PAGES: usize = 255;
 
+/// GitHub's review state as one of `pull_request_review::STATE_*`; `None`
+/// for `PENDING` and `DISMISSED`.
+fn review_state(state: &str) -> Option<&'static str> {
+    match state {
+        "APPROVED" => Some(pull_request_review::STATE_APPROVED),
+        "CHANGES_REQUESTED" => Some(pull_request_review::STATE_CHANGES_REQUESTED),
+        "COMMENTED" => Some(pull_request_review::STATE_COMMENTED),
+        _ => None,
+    }
+}
+
+/// Submitted reviews and distinct approvers. A reviewer's approval only
+/// counts if it's their latest verdict - a later "changes requested"
+/// withdraws it, a plain comment doesn't.
+fn review_counts(reviews: &[PullRequestReviewInfo]) -> (i32, i32) {
+    let mut verdicts: HashMap<&str, &str> = HashMap::new();
+    for review in reviews {
+        if review.state != pull_request_review::STATE_COMMENTED {
+            verdicts.insert(&review.reviewer, &review.state);
+        }
+    }
+    let approval_count = verdicts
+        .values()
+        .filter(|state| **state == pull_request_review::STATE_APPROVED)
+        .count() as i32;
+    (reviews.len() as i32, approval_count)
+}
+
 #[derive(Debug)]
 pub struct GithubClient {
     token: String,
     client: reqwest::Client,
+    api_calls: AtomicU64,
 }
 
 impl GitClient for GithubClient {
@@ -24,11 +57,13 @@ impl GitClient for GithubClient {
         Self {
             token: token.into(),
             client,
+            api_calls: AtomicU64::new(0),
         }
     }
 
     async fn request(&self, url: &str) -> Result<reqwest::Response, reqwest::Error> {
         let full_url = format!("{GITHUB_BASE_URL}{url}");
+        self.api_calls.fetch_add(1, Ordering::Relaxed);
         let res = self
             .client
             .get(full_url)
@@ -44,6 +79,10 @@ impl GitClient for GithubClient {
         Ok(res)
     }
 
+    fn get_api_calls(&self) -> u64 {
+        self.api_calls.load(Ordering::Relaxed)
+    }
+
     async fn request_json(&self, url: &str) -> Result<serde_json::Value, reqwest::Error> {
         let req = self.request(url).await?;
         req.json().await
@@ -64,9 +103,9 @@ impl GitClient for GithubClient {
         Ok(res)
     }
 
-    async fn clone_org_repos(&self, org_name: &str, branch: &str, dest: &str) -> Vec<RepoInfo> {
+    async fn clone_org_repos(&self, org_name: &str, branch: &str, dest: &str) -> CloneReport {
         let mut page_count: usize = 1;
-        let mut repos: Vec<RepoInfo> = Vec::new();
+        let mut report = CloneReport::default();
         while page_count < GITHUB_LIMIT_PAGES {
             let org_repos = match self
                 .get_org_repos(org_name, &GITHUB_LIMIT_PER_PAGE, &page_count)
@@ -75,6 +114,7 @@ impl GitClient for GithubClient {
                 Ok(res) => res,
                 Err(err) => {
                     log::error!("Can't fetch repos of '{org_name}' (page {page_count}): {err}");
+                    report.error = Some(format!("Can't fetch repos (page {page_count}): {err}"));
                     break;
                 }
             };
@@ -83,10 +123,9 @@ impl GitClient for GithubClient {
             let org_repos_items = match org_repos.as_array() {
                 Some(arr) => arr,
                 _ => {
-                    log::error!(
-                        "Unexpected GitHub response for '{org_name}': {}",
-                        org_repos["message"].as_str().unwrap_or("unknown error")
-                    );
+                    let message = org_repos["message"].as_str().unwrap_or("unknown error");
+                    log::error!("Unexpected GitHub response for '{org_name}': {message}");
+                    report.error = Some(format!("Unexpected GitHub response: {message}"));
                     break;
                 }
             };
@@ -100,11 +139,13 @@ impl GitClient for GithubClient {
                 if repo_owner_login.is_empty() || repo_name.is_empty() || repo_url.is_empty() {
                     continue;
                 }
-                let repo_info_opt =
-                    self.clone_or_update_repo(repo_owner_login, repo_name, repo_url, branch, dest);
-                match repo_info_opt {
-                    Some(info) => repos.push(info),
-                    _ => log::info!("'{repo_url}' Is not a valid Odoo modules repository!"),
+                match self.clone_or_update_repo(repo_owner_login, repo_name, repo_url, branch, dest)
+                {
+                    Ok(info) => report.repos.push(info),
+                    Err(failure) => {
+                        log::info!("Can't clone/update '{repo_url}': {}", failure.error);
+                        report.failures.push(failure);
+                    }
                 }
             }
             if org_repos_items.len() < GITHUB_LIMIT_PER_PAGE {
@@ -112,7 +153,7 @@ impl GitClient for GithubClient {
             }
             page_count += 1;
         }
-        repos
+        report
     }
 
     async fn get_repo_pull_requests(
@@ -131,13 +172,13 @@ impl GitClient for GithubClient {
         .await
     }
 
-    async fn get_open_migration_pull_requests(
+    async fn get_open_pull_requests(
         &self,
         full_path: &str,
         branch: &str,
-    ) -> Vec<PullRequestInfo> {
+    ) -> Vec<OpenPullRequestInfo> {
         let mut page_count: usize = 1;
-        let mut prs: Vec<PullRequestInfo> = Vec::new();
+        let mut prs: Vec<OpenPullRequestInfo> = Vec::new();
         while page_count < GITHUB_LIMIT_PAGES {
             let pulls = match self
                 .get_repo_pull_requests(full_path, branch, &GITHUB_LIMIT_PER_PAGE, &page_count)
@@ -157,21 +198,38 @@ impl GitClient for GithubClient {
                 break;
             }
             for pull in pull_items {
-                let head_ref = pull["head"]["ref"].as_str().unwrap_or("");
-                if let Some(module_technical_name) = extract_migration_module_name(head_ref) {
-                    let created_at = pull["created_at"].as_str().and_then(parse_created_at);
-                    let last_message_at = pull["updated_at"].as_str().and_then(parse_created_at);
-                    let head_sha = pull["head"]["sha"].as_str().unwrap_or("");
-                    let ci_status = self.get_commit_ci_status(full_path, head_sha).await;
-                    prs.push(PullRequestInfo {
-                        number: pull["number"].as_i64().unwrap_or(0),
-                        title: pull["title"].as_str().unwrap_or("").to_string(),
-                        module_technical_name,
-                        created_at,
-                        last_message_at,
-                        ci_status,
-                    });
-                }
+                let number = pull["number"].as_i64().unwrap_or(0);
+                let head_sha = pull["head"]["sha"].as_str().unwrap_or("");
+                let author = pull["user"]["login"].as_str().unwrap_or("");
+                let (review_count, approval_count) = review_counts(
+                    &self
+                        .get_pull_request_reviews(full_path, &number, author)
+                        .await,
+                );
+                prs.push(OpenPullRequestInfo {
+                    number,
+                    title: pull["title"].as_str().unwrap_or("").to_string(),
+                    author: pull["user"]["login"].as_str().map(str::to_string),
+                    head_ref: pull["head"]["ref"].as_str().unwrap_or("").to_string(),
+                    created_at: pull["created_at"].as_str().and_then(parse_created_at),
+                    last_message_at: pull["updated_at"].as_str().and_then(parse_created_at),
+                    is_draft: pull["draft"].as_bool().unwrap_or(false),
+                    labels: pull["labels"]
+                        .as_array()
+                        .map(|labels| {
+                            labels
+                                .iter()
+                                .filter_map(|l| l["name"].as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    review_count,
+                    approval_count,
+                    ci_status: self.get_commit_ci_status(full_path, head_sha).await,
+                    module_technical_names: self
+                        .get_pull_request_module_folders(full_path, &number)
+                        .await,
+                });
             }
             if pull_items.len() < GITHUB_LIMIT_PER_PAGE {
                 break;
@@ -181,16 +239,108 @@ impl GitClient for GithubClient {
         prs
     }
 
-    async fn is_pull_request_merged(&self, full_path: &str, number: &i64) -> Option<bool> {
+    async fn get_pull_request_closure(
+        &self,
+        full_path: &str,
+        number: &i64,
+    ) -> Option<PullRequestClosureInfo> {
         let pull = self
             .request_json(&format!("repos/{full_path}/pulls/{number}"))
             .await
             .ok()?;
-        pull["merged"].as_bool()
+        let (close_reason, closed_at_key) = if pull["state"].as_str() == Some("open") {
+            (pull_request_history::CLOSE_REASON_RETARGETED, None)
+        } else if pull["merged"].as_bool().unwrap_or(false) {
+            (pull_request_history::CLOSE_REASON_MERGED, Some("merged_at"))
+        } else {
+            (pull_request_history::CLOSE_REASON_CLOSED, Some("closed_at"))
+        };
+        let author = pull["user"]["login"].as_str().unwrap_or("");
+        Some(PullRequestClosureInfo {
+            close_reason: close_reason.to_string(),
+            created_at: pull["created_at"].as_str().and_then(parse_created_at),
+            closed_at: closed_at_key.and_then(|key| pull[key].as_str().and_then(parse_created_at)),
+            reviews: self
+                .get_pull_request_reviews(full_path, number, author)
+                .await,
+        })
     }
 }
 
 impl GithubClient {
+    /// Module folders touched by a PR, from its files list (paginated, GitHub
+    /// caps it at 3000 files). Renamed files count on both sides.
+    async fn get_pull_request_module_folders(&self, full_path: &str, number: &i64) -> Vec<String> {
+        let mut paths: Vec<String> = Vec::new();
+        let mut page_count: usize = 1;
+        while page_count < GITHUB_LIMIT_PAGES {
+            let files = match self
+                .request_json(&format!(
+                    "repos/{full_path}/pulls/{number}/files?per_page=100&page={page_count}"
+                ))
+                .await
+            {
+                Ok(res) => res,
+                Err(err) => {
+                    log::warn!("Can't fetch files of '{full_path}' PR #{number}: {err}");
+                    break;
+                }
+            };
+            let Some(file_items) = files.as_array() else {
+                break;
+            };
+            for file in file_items {
+                for key in ["filename", "previous_filename"] {
+                    if let Some(path) = file[key].as_str() {
+                        paths.push(path.to_string());
+                    }
+                }
+            }
+            if file_items.len() < 100 {
+                break;
+            }
+            page_count += 1;
+        }
+        extract_module_folders(paths.iter().map(String::as_str))
+    }
+
+    /// Submitted reviews of a PR, oldest first (GitHub's order).
+    async fn get_pull_request_reviews(
+        &self,
+        full_path: &str,
+        number: &i64,
+        author: &str,
+    ) -> Vec<PullRequestReviewInfo> {
+        let Ok(reviews) = self
+            .request_json(&format!(
+                "repos/{full_path}/pulls/{number}/reviews?per_page=100"
+            ))
+            .await
+        else {
+            return Vec::new();
+        };
+        let Some(review_items) = reviews.as_array() else {
+            return Vec::new();
+        };
+        review_items
+            .iter()
+            .filter_map(|review| {
+                // Pending drafts and dismissed reviews aren't verdicts, and the
+                // author's own replies in review threads aren't reviews.
+                let state = review_state(review["state"].as_str()?)?;
+                let reviewer = review["user"]["login"].as_str()?;
+                if reviewer == author {
+                    return None;
+                }
+                Some(PullRequestReviewInfo {
+                    reviewer: reviewer.to_string(),
+                    state: state.to_string(),
+                    submitted_at: review["submitted_at"].as_str().and_then(parse_created_at)?,
+                })
+            })
+            .collect()
+    }
+
     /// Combines the classic combined-status API (used by e.g. OCA's
     /// runboat build) with the checks API (GitHub Actions runs, e.g. tests
     /// and pre-commit): on real OCA repos neither one alone reliably
@@ -250,3 +400,35 @@ impl GithubClient {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{review_counts, review_state};
+    use sqlitedb::models::pull_request_review::PullRequestReviewInfo;
+
+    #[test]
+    fn test_review_state_normalizes_verdicts_and_drops_the_rest() {
+        assert_eq!(review_state("APPROVED"), Some("approved"));
+        assert_eq!(review_state("CHANGES_REQUESTED"), Some("changes_requested"));
+        assert_eq!(review_state("COMMENTED"), Some("commented"));
+        assert_eq!(review_state("DISMISSED"), None);
+        assert_eq!(review_state("PENDING"), None);
+    }
+
+    #[test]
+    fn test_review_counts_keep_each_reviewers_latest_verdict() {
+        let review = |reviewer: &str, state: &str| PullRequestReviewInfo {
+            reviewer: reviewer.to_string(),
+            state: state.to_string(),
+            submitted_at: "2026-01-01 00:00:00".to_string(),
+        };
+        let reviews = [
+            review("alice", "approved"),
+            review("alice", "commented"),
+            review("bob", "approved"),
+            review("bob", "changes_requested"),
+        ];
+        assert_eq!(review_counts(&reviews), (4, 1));
+        assert_eq!(review_counts(&[]), (0, 0));
+    }
+}
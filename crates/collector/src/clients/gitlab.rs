@@ -1,8 +1,11 @@
 // Copyright Alexandre D. Díaz
 // Adapted for GitLab
-use crate::gitclient::{
-    extract_migration_module_name, parse_created_at, GitClient, PullRequestInfo, RepoInfo,
-};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::gitclient::{extract_module_folders, parse_created_at, CloneReport, GitClient};
+use sqlitedb::models::open_pull_request::OpenPullRequestInfo;
+use sqlitedb::models::pull_request_history::{self, PullRequestClosureInfo};
+use sqlitedb::models::pull_request_review::{self, PullRequestReviewInfo};
 
 const GITLAB_BASE_URL: &str = "https://gitlab.com/api/v4/";
 const GITLAB_LIMIT_PER_PAGE: usize = 100; // GitLab permite hasta 100
@@ -13,6 +16,7 @@ pub struct GitlabClient {
     token: String,
     base_url: String,
     client: reqwest::Client,
+    api_calls: AtomicU64,
 }
 
 impl GitClient for GitlabClient {
@@ -31,11 +35,13 @@ impl GitClient for GitlabClient {
             token: token.into(),
             base_url: base_url_san.into(),
             client,
+            api_calls: AtomicU64::new(0),
         }
     }
 
     async fn request(&self, url: &str) -> Result<reqwest::Response, reqwest::Error> {
         let full_url = format!("{}{url}", self.base_url);
+        self.api_calls.fetch_add(1, Ordering::Relaxed);
         let res = self
             .client
             .get(full_url)
@@ -50,6 +56,10 @@ impl GitClient for GitlabClient {
         Ok(res)
     }
 
+    fn get_api_calls(&self) -> u64 {
+        self.api_calls.load(Ordering::Relaxed)
+    }
+
     async fn request_json(&self, url: &str) -> Result<serde_json::Value, reqwest::Error> {
         let req = self.request(url).await?;
         req.json().await
@@ -75,9 +85,9 @@ impl GitClient for GitlabClient {
         Ok(res)
     }
 
-    async fn clone_org_repos(&self, org_name: &str, branch: &str, dest: &str) -> Vec<RepoInfo> {
+    async fn clone_org_repos(&self, org_name: &str, branch: &str, dest: &str) -> CloneReport {
         let mut page_count: usize = 1;
-        let mut repos: Vec<RepoInfo> = Vec::new();
+        let mut report = CloneReport::default();
 
         while page_count < GITLAB_LIMIT_PAGES {
             let group_repos = match self
@@ -87,13 +97,19 @@ impl GitClient for GitlabClient {
                 Ok(res) => res,
                 Err(err) => {
                     log::error!("Can't fetch repos of '{org_name}' (page {page_count}): {err}");
+                    report.error = Some(format!("Can't fetch repos (page {page_count}): {err}"));
                     break;
                 }
             };
 
             let group_repos_items = match group_repos.as_array() {
                 Some(arr) => arr,
-                _ => break,
+                _ => {
+                    let message = group_repos["message"].as_str().unwrap_or("unknown error");
+                    log::error!("Unexpected GitLab response for '{org_name}': {message}");
+                    report.error = Some(format!("Unexpected GitLab response: {message}"));
+                    break;
+                }
             };
 
             if group_repos_items.is_empty() {
@@ -115,7 +131,7 @@ impl GitClient for GitlabClient {
                     .next()
                     .unwrap_or(org_name);
 
-                let repo_info_opt = self.clone_or_update_repo(
+                let repo_info_res = self.clone_or_update_repo(
                     repo_owner_login,
                     repo_name,
                     repo_http_url,
@@ -123,14 +139,17 @@ impl GitClient for GitlabClient {
                     dest,
                 );
 
-                match repo_info_opt {
+                match repo_info_res {
                     // `repo_owner_login` is only the first segment of `path_with_namespace`,
                     // so it drops nested subgroups. Restore the real project path here.
-                    Some(mut info) => {
+                    Ok(mut info) => {
                         info.full_path = repo_path_with_namespace.to_string();
-                        repos.push(info);
+                        report.repos.push(info);
+                    }
+                    Err(failure) => {
+                        log::info!("Can't clone/update '{repo_http_url}': {}", failure.error);
+                        report.failures.push(failure);
                     }
-                    _ => log::info!("'{repo_http_url}' Is not a valid Odoo modules repository!"),
                 }
             }
 
@@ -141,7 +160,7 @@ impl GitClient for GitlabClient {
             page_count += 1;
         }
 
-        repos
+        report
     }
 
     async fn get_repo_pull_requests(
@@ -167,13 +186,13 @@ impl GitClient for GitlabClient {
         .await
     }
 
-    async fn get_open_migration_pull_requests(
+    async fn get_open_pull_requests(
         &self,
         full_path: &str,
         branch: &str,
-    ) -> Vec<PullRequestInfo> {
+    ) -> Vec<OpenPullRequestInfo> {
         let mut page_count: usize = 1;
-        let mut prs: Vec<PullRequestInfo> = Vec::new();
+        let mut prs: Vec<OpenPullRequestInfo> = Vec::new();
         while page_count < GITLAB_LIMIT_PAGES {
             let merge_requests = match self
                 .get_repo_pull_requests(full_path, branch, &GITLAB_LIMIT_PER_PAGE, &page_count)
@@ -193,21 +212,39 @@ impl GitClient for GitlabClient {
                 break;
             }
             for mr in mr_items {
-                let source_branch = mr["source_branch"].as_str().unwrap_or("");
-                if let Some(module_technical_name) = extract_migration_module_name(source_branch) {
-                    let created_at = mr["created_at"].as_str().and_then(parse_created_at);
-                    let last_message_at = mr["updated_at"].as_str().and_then(parse_created_at);
-                    let ci_status =
-                        detailed_merge_status_to_ci_status(mr["detailed_merge_status"].as_str());
-                    prs.push(PullRequestInfo {
-                        number: mr["iid"].as_i64().unwrap_or(0),
-                        title: mr["title"].as_str().unwrap_or("").to_string(),
-                        module_technical_name,
-                        created_at,
-                        last_message_at,
-                        ci_status,
-                    });
-                }
+                let iid = mr["iid"].as_i64().unwrap_or(0);
+                prs.push(OpenPullRequestInfo {
+                    number: iid,
+                    title: mr["title"].as_str().unwrap_or("").to_string(),
+                    author: mr["author"]["username"].as_str().map(str::to_string),
+                    head_ref: mr["source_branch"].as_str().unwrap_or("").to_string(),
+                    created_at: mr["created_at"].as_str().and_then(parse_created_at),
+                    last_message_at: mr["updated_at"].as_str().and_then(parse_created_at),
+                    // `work_in_progress` is the pre-15.0 name of `draft`.
+                    is_draft: mr["draft"]
+                        .as_bool()
+                        .or_else(|| mr["work_in_progress"].as_bool())
+                        .unwrap_or(false),
+                    labels: mr["labels"]
+                        .as_array()
+                        .map(|labels| {
+                            labels
+                                .iter()
+                                .filter_map(|l| l.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    // GitLab has no review objects: discussion notes are the
+                    // closest equivalent, and they come free in the list.
+                    review_count: mr["user_notes_count"].as_i64().unwrap_or(0) as i32,
+                    approval_count: self.get_merge_request_approval_count(full_path, &iid).await,
+                    ci_status: detailed_merge_status_to_ci_status(
+                        mr["detailed_merge_status"].as_str(),
+                    ),
+                    module_technical_names: self
+                        .get_merge_request_module_folders(full_path, &iid)
+                        .await,
+                });
             }
             if mr_items.len() < GITLAB_LIMIT_PER_PAGE {
                 break;
@@ -217,7 +254,11 @@ impl GitClient for GitlabClient {
         prs
     }
 
-    async fn is_pull_request_merged(&self, full_path: &str, number: &i64) -> Option<bool> {
+    async fn get_pull_request_closure(
+        &self,
+        full_path: &str,
+        number: &i64,
+    ) -> Option<PullRequestClosureInfo> {
         let mr = self
             .request_json(&format!(
                 "projects/{}/merge_requests/{number}",
@@ -225,7 +266,117 @@ impl GitClient for GitlabClient {
             ))
             .await
             .ok()?;
-        Some(mr["state"].as_str() == Some("merged"))
+        let (close_reason, closed_at_key) = match mr["state"].as_str() {
+            Some("merged") => (pull_request_history::CLOSE_REASON_MERGED, Some("merged_at")),
+            Some("closed") => (pull_request_history::CLOSE_REASON_CLOSED, Some("closed_at")),
+            Some("opened") => (pull_request_history::CLOSE_REASON_RETARGETED, None),
+            _ => (pull_request_history::CLOSE_REASON_UNKNOWN, None),
+        };
+        let author = mr["author"]["username"].as_str().unwrap_or("");
+        Some(PullRequestClosureInfo {
+            close_reason: close_reason.to_string(),
+            created_at: mr["created_at"].as_str().and_then(parse_created_at),
+            closed_at: closed_at_key.and_then(|key| mr[key].as_str().and_then(parse_created_at)),
+            reviews: self
+                .get_merge_request_reviews(full_path, number, author)
+                .await,
+        })
+    }
+}
+
+impl GitlabClient {
+    /// Module folders touched by an MR, from its paginated diffs list.
+    /// Renamed files count on both sides.
+    async fn get_merge_request_module_folders(&self, full_path: &str, iid: &i64) -> Vec<String> {
+        let mut paths: Vec<String> = Vec::new();
+        let mut page_count: usize = 1;
+        while page_count < GITLAB_LIMIT_PAGES {
+            let diffs = match self
+                .request_json(&format!(
+                    "projects/{}/merge_requests/{iid}/diffs?per_page={GITLAB_LIMIT_PER_PAGE}&page={page_count}",
+                    urlencoding::encode(full_path)
+                ))
+                .await
+            {
+                Ok(res) => res,
+                Err(err) => {
+                    log::warn!("Can't fetch diffs of '{full_path}' MR !{iid}: {err}");
+                    break;
+                }
+            };
+            let Some(diff_items) = diffs.as_array() else {
+                break;
+            };
+            for diff in diff_items {
+                for key in ["old_path", "new_path"] {
+                    if let Some(path) = diff[key].as_str() {
+                        paths.push(path.to_string());
+                    }
+                }
+            }
+            if diff_items.len() < GITLAB_LIMIT_PER_PAGE {
+                break;
+            }
+            page_count += 1;
+        }
+        extract_module_folders(paths.iter().map(String::as_str))
+    }
+
+    /// GitLab has no review objects: approvals only show up as system notes,
+    /// and any discussion note from someone other than the author is taken
+    /// as a commented review. Oldest first, first page only (100 notes).
+    async fn get_merge_request_reviews(
+        &self,
+        full_path: &str,
+        iid: &i64,
+        author: &str,
+    ) -> Vec<PullRequestReviewInfo> {
+        let Ok(notes) = self
+            .request_json(&format!(
+                "projects/{}/merge_requests/{iid}/notes?sort=asc&per_page=100",
+                urlencoding::encode(full_path)
+            ))
+            .await
+        else {
+            return Vec::new();
+        };
+        let Some(note_items) = notes.as_array() else {
+            return Vec::new();
+        };
+        note_items
+            .iter()
+            .filter_map(|note| {
+                let reviewer = note["author"]["username"].as_str()?;
+                if reviewer == author {
+                    return None;
+                }
+                let state = if note["system"].as_bool().unwrap_or(false) {
+                    let body = note["body"].as_str().unwrap_or("");
+                    if !body.starts_with("approved this merge request") {
+                        return None;
+                    }
+                    pull_request_review::STATE_APPROVED
+                } else {
+                    pull_request_review::STATE_COMMENTED
+                };
+                Some(PullRequestReviewInfo {
+                    reviewer: reviewer.to_string(),
+                    state: state.to_string(),
+                    submitted_at: note["created_at"].as_str().and_then(parse_created_at)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn get_merge_request_approval_count(&self, full_path: &str, iid: &i64) -> i32 {
+        self.request_json(&format!(
+            "projects/{}/merge_requests/{iid}/approvals",
+            urlencoding::encode(full_path)
+        ))
+        .await
+        .ok()
+        .and_then(|approvals| approvals["approved_by"].as_array().map(|a| a.len() as i32))
+        .unwrap_or(0)
     }
 }
 
@@ -10,6 +10,15 @@ pub enum GitType {
     Gitlab,
 }
 
+impl GitType {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            GitType::Github => "github",
+            GitType::Gitlab => "gitlab",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct OGHCollectorConfig {
     mode: String,
@@ -21,6 +30,7 @@ pub struct OGHCollectorConfig {
     git_type: GitType,
     version_odoo: u8,
     read_paths: Vec<String>,
+    repo_url: Option<String>,
 }
 
 impl OGHCollectorConfig {
@@ -79,6 +89,7 @@ impl OGHCollectorConfig {
             git_type,
             version_odoo,
             read_paths,
+            repo_url: None,
         }
     }
 
@@ -110,6 +121,11 @@ impl OGHCollectorConfig {
         &self.src
     }
 
+    // The organization the collector lock and the run history are kept for.
+    pub fn get_org(&self) -> &str {
+        self.src.split('/').next().unwrap_or_default()
+    }
+
     pub fn get_token(&self) -> &String {
         &self.token
     }
@@ -137,4 +153,27 @@ impl OGHCollectorConfig {
     pub fn get_git_type(&self) -> &GitType {
         &self.git_type
     }
+
+    // Queued jobs carry the clone URL from the webhook payload, which also
+    // covers self-hosted GitLab instances.
+    pub fn set_repo_url(&mut self, repo_url: Option<String>) {
+        self.repo_url = repo_url;
+    }
+
+    pub fn get_repo_url(&self) -> String {
+        if let Some(repo_url) = &self.repo_url {
+            return repo_url.clone();
+        }
+        match self.git_type {
+            GitType::Gitlab if !self.base_url.is_empty() => {
+                let host = self
+                    .base_url
+                    .trim_end_matches('/')
+                    .trim_end_matches("/api/v4");
+                format!("{host}/{}.git", self.src)
+            }
+            GitType::Gitlab => format!("https://gitlab.com/{}.git", self.src),
+            GitType::Github => format!("https://github.com/{}.git", self.src),
+        }
+    }
 }
@@ -0,0 +1,473 @@
+// Copyright Alexandre D. Díaz
+//! `oghcollector daemon`: runs the collections listed in the schedule file
+//! on their crontab expressions, replacing an external cron + `update_db.sh`.
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio_schedule::{every, Job};
+
+use sqlitedb::models;
+
+use crate::EXIT_LOCKED;
+
+// Schedule file, in the format documented at its top. Overridable with
+// OGHCOLLECTOR_SCHEDULE; the shipped one is embedded as fallback.
+const DEFAULT_SCHEDULE_PATH: &str = "files/schedule";
+const DEFAULT_SCHEDULE: &str = include_str!("../../../files/schedule");
+
+const DEFAULT_MAX_CONCURRENCY: usize = 2;
+// Upper bound, in seconds, of the random delay before each run, so entries
+// sharing an expression don't hit the forge APIs in the same second.
+const DEFAULT_MAX_JITTER: u64 = 300;
+
+/// A standard 5-field crontab expression (minute, hour, day of month, month,
+/// day of week). Fields take `*`, numbers, `a-b` ranges, `/step` and comma
+/// lists; day of week goes from 0 to 7, both 0 and 7 being Sunday.
+#[derive(Debug)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<CronSchedule, String> {
+        let fields = expression.split_whitespace().collect::<Vec<&str>>();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "'{expression}' must have 5 fields, found {}",
+                fields.len()
+            ));
+        };
+        let mut weekdays = parse_cron_field(weekday, 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(CronSchedule {
+            minutes: parse_cron_field(minute, 0, 59)?,
+            hours: parse_cron_field(hour, 0, 23)?,
+            days: parse_cron_field(day, 1, 31)?,
+            months: parse_cron_field(month, 1, 12)?,
+            weekdays,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+
+    pub fn matches(&self, at: &DateTime<Utc>) -> bool {
+        let has = |mask: u64, value: u32| mask & (1 << value) != 0;
+        let day = has(self.days, at.day());
+        let weekday = has(self.weekdays, at.weekday().num_days_from_sunday());
+        // As in crontab: when both day fields are restricted, either one is enough.
+        let day_matches = if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        };
+        day_matches
+            && has(self.minutes, at.minute())
+            && has(self.hours, at.hour())
+            && has(self.months, at.month())
+    }
+}
+
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let parse_value = |value: &str| {
+        value
+            .parse::<u32>()
+            .ok()
+            .filter(|value| (min..=max).contains(value))
+            .ok_or_else(|| format!("'{value}' is not a value between {min} and {max}"))
+    };
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<usize>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("'{part}' has an invalid step"))?,
+            ),
+            None => (part, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (parse_value(start)?, parse_value(end)?),
+            // "5/15" means every 15 starting at 5.
+            None if step > 1 => (parse_value(range)?, max),
+            None => {
+                let value = parse_value(range)?;
+                (value, value)
+            }
+        };
+        if start > end {
+            return Err(format!("'{part}' is an empty range"));
+        }
+        mask |= (start..=end)
+            .step_by(step)
+            .fold(0u64, |mask, value| mask | (1 << value));
+    }
+    Ok(mask)
+}
+
+#[derive(Debug)]
+pub struct ScheduleEntry {
+    pub cron: CronSchedule,
+    pub source: String,
+    pub versions: Vec<String>,
+    pub git_type: Option<String>,
+}
+
+impl ScheduleEntry {
+    // Same organization the collector takes its named lock for.
+    fn org(&self) -> &str {
+        self.source.split([':', '/']).next().unwrap_or_default()
+    }
+
+    fn collector_args<'a>(&'a self, version: &'a str) -> Vec<&'a str> {
+        let mut args = vec![self.source.as_str(), version];
+        args.extend(self.git_type.as_deref());
+        args
+    }
+}
+
+pub fn parse_schedule(content: &str) -> Result<Vec<ScheduleEntry>, String> {
+    let mut entries = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let tokens = line.split_whitespace().collect::<Vec<&str>>();
+        if !(7..=8).contains(&tokens.len()) {
+            return Err(format!(
+                "line {}: expected '<minute> <hour> <day of month> <month> <day of week> \
+                 <origin> <versions> [git_type]'",
+                index + 1
+            ));
+        }
+        let cron = CronSchedule::parse(&tokens[..5].join(" "))
+            .map_err(|err| format!("line {}: {err}", index + 1))?;
+        entries.push(ScheduleEntry {
+            cron,
+            source: tokens[5].to_string(),
+            versions: tokens[6]
+                .split(',')
+                .filter(|version| !version.is_empty())
+                .map(str::to_string)
+                .collect(),
+            git_type: tokens.get(7).map(|git_type| git_type.to_string()),
+        });
+    }
+    Ok(entries)
+}
+
+fn load_schedule() -> Result<Vec<ScheduleEntry>, String> {
+    match std::env::var("OGHCOLLECTOR_SCHEDULE") {
+        Ok(path) => fs::read_to_string(&path)
+            .map_err(|err| format!("Can't read '{path}': {err}"))
+            .and_then(|content| parse_schedule(&content)),
+        Err(_) => parse_schedule(
+            &fs::read_to_string(DEFAULT_SCHEDULE_PATH).unwrap_or(DEFAULT_SCHEDULE.to_string()),
+        ),
+    }
+}
+
+pub(crate) fn env_number<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+struct Daemon {
+    entries: Vec<ScheduleEntry>,
+    pool: sqlitedb::Pool,
+    max_jitter: u64,
+    slots: Semaphore,
+    // Runs of the same organization wait for their turn here instead of
+    // skipping each other on the named lock.
+    org_turns: HashMap<String, tokio::sync::Mutex<()>>,
+    // Entries with a run in progress (waiting or collecting).
+    running: Mutex<HashSet<usize>>,
+}
+
+// An entry's place in `Daemon::running`, given back when dropped: a run
+// that panics must not keep its entry from ever firing again.
+struct RunningEntry<'a> {
+    running: &'a Mutex<HashSet<usize>>,
+    index: usize,
+}
+
+impl<'a> RunningEntry<'a> {
+    /// `None` when the entry already has a run in progress.
+    fn claim(running: &'a Mutex<HashSet<usize>>, index: usize) -> Option<Self> {
+        let claimed = running.lock().unwrap().insert(index);
+        claimed.then(|| Self { running, index })
+    }
+}
+
+impl Drop for RunningEntry<'_> {
+    fn drop(&mut self) {
+        self.running
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&self.index);
+    }
+}
+
+impl Daemon {
+    fn tick(self: &Arc<Self>, now: DateTime<Utc>) {
+        for (index, entry) in self.entries.iter().enumerate() {
+            if entry.cron.matches(&now) {
+                tokio::spawn(self.clone().run_entry(index));
+            }
+        }
+    }
+
+    async fn run_entry(self: Arc<Self>, index: usize) {
+        let entry = &self.entries[index];
+        let Some(_running) = RunningEntry::claim(&self.running, index) else {
+            for version in &entry.versions {
+                self.record_skipped(entry, version, "its previous run is still in progress");
+            }
+            return;
+        };
+        let jitter = rand::thread_rng().gen_range(0..=self.max_jitter);
+        tokio::time::sleep(Duration::from_secs(jitter)).await;
+        {
+            let _org_turn = self.org_turns[entry.org()].lock().await;
+            let _slot = self
+                .slots
+                .acquire()
+                .await
+                .expect("Scheduler semaphore closed");
+            for version in &entry.versions {
+                log::info!("Scheduled scan of '{}' [{version}]...", entry.source);
+                let start_time = Instant::now();
+                let error = match run_collector(entry, version).await {
+                    Ok(()) => None,
+                    // Someone else (a manual run, `oghcollector queue`, another
+                    // daemon...) is collecting this organization.
+                    Err(CollectorError::Locked) => {
+                        self.record_skipped(
+                            entry,
+                            version,
+                            &format!("another collector holds the '{}' lock", entry.org()),
+                        );
+                        continue;
+                    }
+                    Err(CollectorError::Failed(error)) => {
+                        log::error!(
+                            "Scheduled scan of '{}' [{version}] failed: {error}",
+                            entry.source
+                        );
+                        Some(error)
+                    }
+                };
+                match self.pool.get() {
+                    Ok(mut conn) => {
+                        let _ = models::system_event::register_scheduled_task_collector(
+                            &mut conn,
+                            &entry.source,
+                            version,
+                            &start_time.elapsed().as_secs().to_string(),
+                            error.as_deref(),
+                        );
+                    }
+                    Err(err) => log::error!(
+                        "Can't record the scheduled scan of '{}' [{version}]: {err}",
+                        entry.source
+                    ),
+                }
+            }
+        }
+    }
+
+    fn record_skipped(&self, entry: &ScheduleEntry, version: &str, reason: &str) {
+        log::warn!(
+            "Scheduled scan of '{}' [{version}] skipped: {reason}",
+            entry.source
+        );
+        match self.pool.get() {
+            Ok(mut conn) => {
+                let _ = models::system_event::register_skipped_task_collector(
+                    &mut conn,
+                    &entry.source,
+                    version,
+                    reason,
+                );
+            }
+            Err(err) => log::error!(
+                "Can't record the skipped scan of '{}' [{version}]: {err}",
+                entry.source
+            ),
+        }
+    }
+}
+
+enum CollectorError {
+    /// The run found its organization's lock taken.
+    Locked,
+    Failed(String),
+}
+
+// Each run is its own `oghcollector` process: the collector panics on
+// unrecoverable errors, and a failed run must not take the daemon down. The
+// lock is left to the process itself, checking it here first would let
+// another collector take it in between.
+async fn run_collector(entry: &ScheduleEntry, version: &str) -> Result<(), CollectorError> {
+    let program = std::env::current_exe().map_err(|err| CollectorError::Failed(err.to_string()))?;
+    let status = Command::new(program)
+        .args(entry.collector_args(version))
+        .env(
+            "OGHCOLLECTOR_TRIGGER",
+            models::collection_run::TRIGGER_SCHEDULE,
+        )
+        .status()
+        .await
+        .map_err(|err| CollectorError::Failed(err.to_string()))?;
+    match status.code() {
+        _ if status.success() => Ok(()),
+        Some(EXIT_LOCKED) => Err(CollectorError::Locked),
+        _ => Err(CollectorError::Failed(format!(
+            "the collector exited with {status}"
+        ))),
+    }
+}
+
+pub async fn run(pool: sqlitedb::Pool) {
+    let entries = load_schedule().unwrap_or_else(|err| {
+        eprintln!("Invalid collection schedule: {err}");
+        std::process::exit(1);
+    });
+    let max_concurrency = env_number(
+        "OGHCOLLECTOR_DAEMON_MAX_CONCURRENCY",
+        DEFAULT_MAX_CONCURRENCY,
+    )
+    .max(1);
+    let max_jitter = env_number("OGHCOLLECTOR_DAEMON_JITTER", DEFAULT_MAX_JITTER);
+    log::info!(
+        "Scheduling {} collections (up to {max_concurrency} at once, {max_jitter}s of jitter)...",
+        entries.len()
+    );
+    let org_turns = entries
+        .iter()
+        .map(|entry| (entry.org().to_string(), tokio::sync::Mutex::new(())))
+        .collect();
+    let daemon = Arc::new(Daemon {
+        entries,
+        pool,
+        max_jitter,
+        slots: Semaphore::new(max_concurrency),
+        org_turns,
+        running: Mutex::new(HashSet::new()),
+    });
+    every(1)
+        .minute()
+        .in_timezone(&Utc)
+        .perform(move || {
+            let daemon = daemon.clone();
+            async move { daemon.tick(Utc::now()) }
+        })
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_cron_schedule() {
+        // 2025-06-02 was a Monday.
+        let at = |day, hour, minute| Utc.with_ymd_and_hms(2025, 6, day, hour, minute, 0).unwrap();
+
+        let every_six_hours = CronSchedule::parse("0 */6 * * *").unwrap();
+        assert!(every_six_hours.matches(&at(2, 0, 0)));
+        assert!(every_six_hours.matches(&at(2, 18, 0)));
+        assert!(!every_six_hours.matches(&at(2, 18, 1)));
+        assert!(!every_six_hours.matches(&at(2, 19, 0)));
+
+        let weekdays = CronSchedule::parse("30 2 * * 1-5").unwrap();
+        assert!(weekdays.matches(&at(2, 2, 30)));
+        assert!(!weekdays.matches(&at(1, 2, 30)));
+
+        let sundays = CronSchedule::parse("15,45 3 * * 7").unwrap();
+        assert!(sundays.matches(&at(1, 3, 45)));
+        assert!(!sundays.matches(&at(2, 3, 45)));
+
+        // Day of month OR day of week, as in crontab.
+        let first_or_monday = CronSchedule::parse("0 0 1 * 1").unwrap();
+        assert!(first_or_monday.matches(&at(1, 0, 0)));
+        assert!(first_or_monday.matches(&at(9, 0, 0)));
+        assert!(!first_or_monday.matches(&at(10, 0, 0)));
+
+        let offset_step = CronSchedule::parse("5/20 * * * *").unwrap();
+        assert!(offset_step.matches(&at(3, 7, 45)));
+        assert!(!offset_step.matches(&at(3, 7, 0)));
+
+        assert!(CronSchedule::parse("0 */6 * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("0 5-2 * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+    }
+
+    #[test]
+    fn test_running_entry_released_on_panic() {
+        let running = Mutex::new(HashSet::new());
+        let claimed = RunningEntry::claim(&running, 3).unwrap();
+        assert!(RunningEntry::claim(&running, 3).is_none());
+        assert!(RunningEntry::claim(&running, 4).is_some());
+        drop(claimed);
+
+        let panicked = std::panic::catch_unwind(|| {
+            let _running = RunningEntry::claim(&running, 3).unwrap();
+            panic!("collector blew up");
+        });
+        assert!(panicked.is_err());
+        assert!(running.lock().unwrap().is_empty());
+        assert!(RunningEntry::claim(&running, 3).is_some());
+    }
+
+    #[test]
+    fn test_parse_schedule() {
+        let entries = parse_schedule(
+            "# comment\n\
+             \n\
+             0 3 * * * OCA/web:/addons 16.0,17.0\n\
+             30 4 * * 0 MyGroup 18.0 GL:https://git.example.com/api/v4/\n",
+        )
+        .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].org(), "OCA");
+        assert_eq!(entries[0].versions, vec!["16.0", "17.0"]);
+        assert_eq!(
+            entries[0].collector_args("16.0"),
+            vec!["OCA/web:/addons", "16.0"]
+        );
+        assert_eq!(entries[1].org(), "MyGroup");
+        assert_eq!(
+            entries[1].collector_args("18.0"),
+            vec!["MyGroup", "18.0", "GL:https://git.example.com/api/v4/"]
+        );
+
+        let err = parse_schedule("0 3 * * * OCA\n").unwrap_err();
+        assert!(err.starts_with("line 1:"));
+        let err = parse_schedule("# ok\n0 25 * * * OCA 16.0\n").unwrap_err();
+        assert!(err.starts_with("line 2:"));
+
+        // The shipped schedule must stay valid.
+        assert_eq!(parse_schedule(DEFAULT_SCHEDULE).unwrap().len(), 3);
+    }
+}
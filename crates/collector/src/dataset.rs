@@ -0,0 +1,950 @@
+// Copyright Alexandre D. Díaz
+//! Portable dataset of what this instance collected, so another instance can
+//! merge it in without collecting it again (`oghcollector export|import`).
+//! JSON Lines: a header, then one self-contained record per line, keyed by
+//! organization/repository names (never by ids, which are local).
+//!
+//! Committer statistics and blame ownership aren't part of it: they identify
+//! people by email and stay with the instance that collected them.
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+use diesel::Connection;
+use serde::{Deserialize, Serialize};
+
+use sqlitedb::models;
+use sqlitedb::models::module::ManifestInfo;
+use sqlitedb::models::module_code_analysis::{
+    ControllerAnalysisInfo, FieldAnalysisInfo, MethodAnalysisInfo, ModelAnalysisInfo,
+    ModuleAnalysisInfo, RecordAnalysisInfo, ViewAnalysisInfo,
+};
+use sqlitedb::models::module_security_warning::SecurityWarningInfo;
+use sqlitedb::models::pull_request_review::PullRequestReviewInfo;
+use sqlitedb::utils::date::get_sqlite_utc_now;
+use sqlitedb::DbConnection;
+
+pub const FORMAT: &str = "oghcollector-dataset";
+/// Bumped on any change an older `import` couldn't read.
+pub const FORMAT_VERSION: i32 = 1;
+
+type DatasetError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DatasetRecord {
+    Header(HeaderRecord),
+    Module(Box<ModuleRecord>),
+    PullRequest(PullRequestRecord),
+    PullRequestHistory(PullRequestHistoryRecord),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct HeaderRecord {
+    pub format: String,
+    pub version: i32,
+    /// Name the importing instance records as the data's provenance.
+    pub source: String,
+    pub export_date: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ModuleRecord {
+    pub org: String,
+    pub repo: String,
+    pub technical_name: String,
+    pub version_odoo: i32,
+    pub name: String,
+    pub version_module: String,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub installation: Option<String>,
+    pub usage: Option<String>,
+    pub icon: Option<String>,
+    pub website: Option<String>,
+    pub license: Option<String>,
+    pub category: Option<String>,
+    pub auto_install: bool,
+    pub application: bool,
+    pub installable: bool,
+    pub folder_size: i64,
+    pub last_commit_hash: String,
+    pub last_commit_author: String,
+    pub last_commit_name: String,
+    pub last_commit_date: String,
+    pub last_commit_partof: Option<String>,
+    pub create_date: String,
+    pub update_date: String,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    #[serde(default)]
+    pub maintainers: Vec<String>,
+    #[serde(default)]
+    pub depends: Vec<String>,
+    #[serde(default)]
+    pub external_depends_python: Vec<String>,
+    #[serde(default)]
+    pub external_depends_bin: Vec<String>,
+    #[serde(default)]
+    pub osv: Vec<OsvRecord>,
+    #[serde(default)]
+    pub versions: Vec<VersionRecord>,
+}
+
+/// A known vulnerability of one of the module's Python dependencies.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct OsvRecord {
+    pub dependency: String,
+    pub osv_id: String,
+    pub details: String,
+    pub fixed_in: String,
+}
+
+/// Code analysis of one `version_module` snapshot.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct VersionRecord {
+    pub version_module: String,
+    pub analysis: ModuleAnalysisInfo,
+    #[serde(default)]
+    pub security_warnings: Vec<SecurityWarningInfo>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct PullRequestRecord {
+    pub org: String,
+    pub repo: String,
+    pub version_odoo: i32,
+    pub prid: i64,
+    pub name: String,
+    pub module_technical_name: String,
+    pub created_at: Option<String>,
+    pub ci_status: Option<String>,
+    pub last_message_at: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct PullRequestHistoryRecord {
+    pub org: String,
+    pub repo: String,
+    pub version_odoo: i32,
+    pub prid: i64,
+    pub module_technical_name: String,
+    pub created_at: String,
+    pub closed_at: String,
+    pub close_reason: String,
+    #[serde(default)]
+    pub reviews: Vec<PullRequestReviewInfo>,
+}
+
+#[derive(Debug, Default)]
+pub struct ExportSummary {
+    pub modules: usize,
+    pub pull_requests: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub source: String,
+    pub modules: usize,
+    /// Modules of repositories this instance collects itself, left alone.
+    pub skipped: usize,
+    /// Modules of imported repositories that the new export no longer has.
+    pub removed: usize,
+    pub pull_requests: usize,
+}
+
+fn write_record(out: &mut impl Write, record: &DatasetRecord) -> Result<(), DatasetError> {
+    serde_json::to_writer(&mut *out, record)?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+fn version_record(
+    conn: &mut DbConnection,
+    version: &models::module_version::Model,
+) -> VersionRecord {
+    let views = models::module_view::get_by_module_version_id(conn, &version.id)
+        .into_iter()
+        .map(|v| ViewAnalysisInfo {
+            xml_id: v.xml_id,
+            name: v.name,
+            model: v.model,
+            inherit_xml_id: v.inherit_xml_id,
+            view_type: v.view_type,
+        })
+        .collect();
+    let module_models = models::module_model::get_by_module_version_id(conn, &version.id)
+        .into_iter()
+        .map(|m| ModelAnalysisInfo {
+            fields: models::module_model_field::get_by_module_model_id(conn, &m.id)
+                .into_iter()
+                .map(|f| FieldAnalysisInfo {
+                    attrs: f.attrs_value(),
+                    name: f.name,
+                    field_type: f.field_type,
+                    relation: f.relation,
+                })
+                .collect(),
+            methods: models::module_model_method::get_by_module_model_id(conn, &m.id)
+                .into_iter()
+                .map(|method| MethodAnalysisInfo {
+                    decorators: method.decorators_vec(),
+                    name: method.name,
+                    signature: method.signature,
+                    docstring: method.docstring,
+                })
+                .collect(),
+            attrs: m.attrs_value(),
+            inherit_from: m
+                .inherit_from
+                .as_deref()
+                .unwrap_or("")
+                .split(',')
+                .filter(|x| !x.is_empty())
+                .map(str::to_string)
+                .collect(),
+            model_name: m.model_name,
+            class_name: m.class_name,
+            is_new_model: m.is_new_model,
+            docstring: m.docstring,
+        })
+        .collect();
+    let records = models::module_record::get_by_module_version_id(conn, &version.id)
+        .into_iter()
+        .map(|r| RecordAnalysisInfo {
+            fields: r.fields_value(),
+            xml_id: r.xml_id,
+            model: r.model,
+            noupdate: r.noupdate,
+        })
+        .collect();
+    let controllers = models::module_controller::get_by_module_version_id(conn, &version.id)
+        .into_iter()
+        .map(|c| ControllerAnalysisInfo {
+            routes: c.routes_vec(),
+            methods: c.methods_vec(),
+            class_name: c.class_name,
+            name: c.name,
+            auth: c.auth,
+            http_type: c.http_type,
+            csrf: c.csrf,
+            website: c.website,
+            uses_sudo: c.uses_sudo,
+            checks_token_access: c.checks_token_access,
+            signature: c.signature,
+            docstring: c.docstring,
+        })
+        .collect();
+    let security_warnings =
+        models::module_security_warning::get_by_module_version_id(conn, &version.id)
+            .into_iter()
+            .map(|w| SecurityWarningInfo {
+                severity: w.severity,
+                code: w.code,
+                message: w.message,
+                xml_id: w.xml_id,
+            })
+            .collect();
+    VersionRecord {
+        version_module: version.version_module.clone(),
+        analysis: ModuleAnalysisInfo {
+            views,
+            models: module_models,
+            records,
+            controllers,
+            ownership: Vec::new(),
+            test_files: version.test_files.map(|count| count as usize),
+        },
+        security_warnings,
+    }
+}
+
+/// Writes every repository this instance collects itself (imported ones are
+/// left to the instance they came from) as `source`'s dataset.
+pub fn export(
+    conn: &mut DbConnection,
+    source: &str,
+    out: &mut impl Write,
+) -> Result<ExportSummary, DatasetError> {
+    let mut summary = ExportSummary::default();
+    write_record(
+        out,
+        &DatasetRecord::Header(HeaderRecord {
+            format: FORMAT.to_string(),
+            version: FORMAT_VERSION,
+            source: source.to_string(),
+            export_date: get_sqlite_utc_now(),
+        }),
+    )?;
+
+    let dep_type_module = models::dependency_type::get_by_name(conn, "module").unwrap();
+    let dep_type_python = models::dependency_type::get_by_name(conn, "python").unwrap();
+    let dep_type_bin = models::dependency_type::get_by_name(conn, "bin").unwrap();
+    // gh_repository_id -> (org, repo), in the order modules come by.
+    let mut repos: Vec<(i64, String, String)> = Vec::new();
+    for module in models::module::get_collected(conn) {
+        if repos.last().map(|r| r.0) != Some(module.gh_repository_id) {
+            let repo = models::gh_repository::get_by_id(conn, &module.gh_repository_id).unwrap();
+            let org = models::gh_organization::get_by_id(conn, &repo.gh_organization_id).unwrap();
+            repos.push((repo.id, org.name, repo.name));
+        }
+        let (_, org, repo) = repos.last().unwrap();
+        let versions = models::module_version::get_by_module_id(conn, &module.id)
+            .iter()
+            .map(|v| version_record(conn, v))
+            .collect();
+        let record = ModuleRecord {
+            org: org.clone(),
+            repo: repo.clone(),
+            authors: models::module_author::get_names_by_module_id(conn, &module.id),
+            maintainers: models::module_maintainer::get_names_by_module_id(conn, &module.id),
+            depends: models::dependency_module::get_names(conn, &module.id, &dep_type_module.id),
+            external_depends_python: models::dependency_module::get_names(
+                conn,
+                &module.id,
+                &dep_type_python.id,
+            ),
+            external_depends_bin: models::dependency_module::get_names(
+                conn,
+                &module.id,
+                &dep_type_bin.id,
+            ),
+            osv: models::dependency_osv::get_by_module_id(conn, &module.id)
+                .into_iter()
+                .map(|o| OsvRecord {
+                    dependency: o.dependency_name,
+                    osv_id: o.osv_id,
+                    details: o.details,
+                    fixed_in: o.fixed_in,
+                })
+                .collect(),
+            versions,
+            technical_name: module.technical_name,
+            version_odoo: module.version_odoo,
+            name: module.name,
+            version_module: module.version_module,
+            summary: module.summary,
+            description: module.description,
+            installation: module.installation,
+            usage: module.usage,
+            icon: module.icon,
+            website: module.website,
+            license: module.license,
+            category: module.category,
+            auto_install: module.auto_install,
+            application: module.application,
+            installable: module.installable,
+            folder_size: module.folder_size,
+            last_commit_hash: module.last_commit_hash,
+            last_commit_author: module.last_commit_author,
+            last_commit_name: module.last_commit_name,
+            last_commit_date: module.last_commit_date,
+            last_commit_partof: module.last_commit_partof,
+            create_date: module.create_date,
+            update_date: module.update_date,
+        };
+        write_record(out, &DatasetRecord::Module(Box::new(record)))?;
+        summary.modules += 1;
+    }
+
+    for (repo_id, org, repo) in &repos {
+        for pr in models::pull_request::get_by_gh_repository_id(conn, repo_id) {
+            let record = PullRequestRecord {
+                org: org.clone(),
+                repo: repo.clone(),
+                version_odoo: pr.version_odoo,
+                prid: pr.prid,
+                name: pr.name,
+                module_technical_name: pr.module_technical_name,
+                created_at: pr.created_at,
+                ci_status: pr.ci_status,
+                last_message_at: pr.last_message_at,
+            };
+            write_record(out, &DatasetRecord::PullRequest(record))?;
+            summary.pull_requests += 1;
+        }
+        for history in models::pull_request_history::get_by_gh_repository_id(conn, repo_id) {
+            let reviews =
+                models::pull_request_review::get_by_pull_request_history_id(conn, &history.id)
+                    .into_iter()
+                    .map(|r| PullRequestReviewInfo {
+                        reviewer: r.reviewer,
+                        state: r.state,
+                        submitted_at: r.submitted_at,
+                    })
+                    .collect();
+            let record = PullRequestHistoryRecord {
+                org: org.clone(),
+                repo: repo.clone(),
+                version_odoo: history.version_odoo,
+                prid: history.prid,
+                module_technical_name: history.module_technical_name,
+                created_at: history.created_at,
+                closed_at: history.closed_at,
+                close_reason: history.close_reason,
+                reviews,
+            };
+            write_record(out, &DatasetRecord::PullRequestHistory(record))?;
+        }
+    }
+    out.flush()?;
+    Ok(summary)
+}
+
+/// The repository the records of `org`/`repo` go to, created if needed and
+/// marked as coming from `source_id`; `None` when this instance collects it
+/// itself, whose own data always wins over an import. A repository last
+/// imported from another source is taken over by this one.
+fn claim_repository(
+    conn: &mut DbConnection,
+    claimed: &mut HashMap<(String, String), Option<i64>>,
+    source_id: &i64,
+    org: &str,
+    repo: &str,
+) -> diesel::QueryResult<Option<i64>> {
+    let key = (org.to_string(), repo.to_string());
+    if let Some(repo_id) = claimed.get(&key) {
+        return Ok(*repo_id);
+    }
+    let existing = models::gh_organization::get_by_name(conn, org)
+        .and_then(|gh_org| models::gh_repository::get_by_name(conn, &gh_org.id, repo));
+    let repo_id = match existing {
+        Some(gh_repo) if gh_repo.dataset_source_id.is_none() => None,
+        _ => {
+            let gh_org = models::gh_organization::add(conn, org)?;
+            let gh_repo = models::gh_repository::add(conn, &gh_org.id, repo)?;
+            models::gh_repository::set_dataset_source(conn, &gh_repo.id, Some(*source_id))?;
+            Some(gh_repo.id)
+        }
+    };
+    claimed.insert(key, repo_id);
+    Ok(repo_id)
+}
+
+fn manifest_info(record: &ModuleRecord) -> ManifestInfo {
+    ManifestInfo {
+        technical_name: record.technical_name.clone(),
+        version_odoo: record.version_odoo as u8,
+        name: record.name.clone(),
+        version_module: record.version_module.clone(),
+        summary: record.summary.clone().unwrap_or_default(),
+        description: record.description.clone().unwrap_or_default(),
+        installation: record.installation.clone().unwrap_or_default(),
+        usage: record.usage.clone().unwrap_or_default(),
+        icon: record.icon.clone().unwrap_or_default(),
+        author: record.authors.join(","),
+        website: record.website.clone().unwrap_or_default(),
+        license: record.license.clone().unwrap_or_default(),
+        category: record.category.clone().unwrap_or_default(),
+        auto_install: record.auto_install,
+        application: record.application,
+        installable: record.installable,
+        maintainer: record.maintainers.join(","),
+        git_org: record.org.clone(),
+        git_repo: record.repo.clone(),
+        depends: record.depends.clone(),
+        external_depends_python: record.external_depends_python.clone(),
+        external_depends_bin: record.external_depends_bin.clone(),
+        folder_size: record.folder_size as u64,
+        last_commit_hash: record.last_commit_hash.clone(),
+        last_commit_author: record.last_commit_author.clone(),
+        last_commit_date: record.last_commit_date.clone(),
+        last_commit_name: record.last_commit_name.clone(),
+        last_commit_partof: record.last_commit_partof.clone().unwrap_or_default(),
+        ..Default::default()
+    }
+}
+
+/// Makes the module's dependencies of one type exactly `names`.
+fn sync_dependencies(
+    conn: &mut DbConnection,
+    module_id: &i64,
+    dep_type_id: &i64,
+    names: &[String],
+) -> diesel::QueryResult<()> {
+    let current = models::dependency_module::get_names(conn, module_id, dep_type_id);
+    for name in current.iter().filter(|name| !names.contains(name)) {
+        if let Some(dep) = models::dependency::get_by_name(conn, dep_type_id, name) {
+            models::dependency_module::delete_by_module_id_dependecy_id(conn, module_id, &dep.id)?;
+        }
+    }
+    for name in names.iter().filter(|name| !current.contains(name)) {
+        models::dependency_module::add(conn, dep_type_id, name, module_id)?;
+    }
+    Ok(())
+}
+
+fn import_module(
+    conn: &mut DbConnection,
+    record: &ModuleRecord,
+    dep_type_ids: &[i64; 3],
+) -> diesel::QueryResult<models::module::Model> {
+    let module = models::module::add(conn, &manifest_info(record))?;
+    models::module::set_dates(conn, &module.id, &record.create_date, &record.update_date)?;
+    for version in &record.versions {
+        let module_version =
+            models::module_version::get_or_create(conn, &module.id, &version.version_module)?;
+        let analysis = &version.analysis;
+        models::module_view::replace_for_module(
+            conn,
+            &module.id,
+            &module_version.id,
+            &analysis.views,
+        )?;
+        models::module_model::replace_for_module(
+            conn,
+            &module.id,
+            &module_version.id,
+            &analysis.models,
+        )?;
+        models::module_record::replace_for_module(
+            conn,
+            &module.id,
+            &module_version.id,
+            &analysis.records,
+        )?;
+        models::module_controller::replace_for_module(
+            conn,
+            &module.id,
+            &module_version.id,
+            &analysis.controllers,
+        )?;
+        models::module_security_warning::replace_for_module(
+            conn,
+            &module.id,
+            &module_version.id,
+            &version.security_warnings,
+        )?;
+        models::module_version::set_test_files(
+            conn,
+            &module_version.id,
+            analysis.test_files.map(|count| count as i32),
+        )?;
+    }
+    let [dep_type_module, dep_type_python, dep_type_bin] = dep_type_ids;
+    sync_dependencies(conn, &module.id, dep_type_module, &record.depends)?;
+    sync_dependencies(
+        conn,
+        &module.id,
+        dep_type_python,
+        &record.external_depends_python,
+    )?;
+    sync_dependencies(conn, &module.id, dep_type_bin, &record.external_depends_bin)?;
+    for osv in &record.osv {
+        models::dependency_module::add(conn, dep_type_python, &osv.dependency, &module.id)?;
+    }
+    // Each Python dependency gets exactly the source's findings.
+    for name in models::dependency_module::get_names(conn, &module.id, dep_type_python) {
+        let dep_mod = models::dependency_module::add(conn, dep_type_python, &name, &module.id)?;
+        let osvs: Vec<&OsvRecord> = record
+            .osv
+            .iter()
+            .filter(|osv| osv.dependency == name)
+            .collect();
+        let osv_ids: Vec<&str> = osvs.iter().map(|osv| osv.osv_id.as_str()).collect();
+        models::dependency_osv::delete_missing(conn, &dep_mod.id, &osv_ids)?;
+        for osv in osvs {
+            models::dependency_osv::add(
+                conn,
+                &dep_mod.id,
+                &osv.osv_id,
+                &osv.details,
+                &osv.fixed_in,
+            )?;
+        }
+    }
+    models::module_search::refresh(conn, &module.id)?;
+    Ok(module)
+}
+
+fn read_header(
+    line: Option<(usize, std::io::Result<String>)>,
+) -> Result<HeaderRecord, DatasetError> {
+    let Some((_, line)) = line else {
+        return Err("Empty dataset".into());
+    };
+    match serde_json::from_str::<DatasetRecord>(&line?) {
+        Ok(DatasetRecord::Header(header)) if header.format == FORMAT => {
+            if header.version > FORMAT_VERSION {
+                return Err(format!(
+                    "Dataset format version {} is newer than the supported {FORMAT_VERSION}",
+                    header.version
+                )
+                .into());
+            }
+            Ok(header)
+        }
+        _ => Err(format!("Not an {FORMAT} file: the first line must be its header").into()),
+    }
+}
+
+/// Upserts a dataset written by `export` into this instance, all or nothing.
+/// Records are matched by org/repo/technical_name/version_odoo; repositories
+/// collected here are skipped, and imported ones end up mirroring the export
+/// (modules and open migration PRs it no longer has are removed).
+pub fn import(conn: &mut DbConnection, input: impl BufRead) -> Result<ImportSummary, DatasetError> {
+    let mut lines = input.lines().enumerate();
+    let header = read_header(lines.next())?;
+    conn.transaction(|conn| {
+        let source =
+            models::dataset_source::add(conn, &header.source, header.version, &header.export_date)?;
+        let mut summary = ImportSummary {
+            source: source.name.clone(),
+            ..Default::default()
+        };
+        let dep_type_ids = ["module", "python", "bin"]
+            .map(|name| models::dependency_type::get_by_name(conn, name).unwrap().id);
+        let mut claimed: HashMap<(String, String), Option<i64>> = HashMap::new();
+        let mut module_ids: HashMap<i64, Vec<i64>> = HashMap::new();
+        let mut prids: HashMap<i64, Vec<i64>> = HashMap::new();
+        for (index, line) in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str::<DatasetRecord>(&line)
+                .map_err(|err| format!("Line {}: {err}", index + 1))?;
+            match record {
+                DatasetRecord::Header(_) => {
+                    return Err(format!("Line {}: unexpected header", index + 1).into());
+                }
+                DatasetRecord::Module(record) => {
+                    let Some(repo_id) = claim_repository(
+                        conn,
+                        &mut claimed,
+                        &source.id,
+                        &record.org,
+                        &record.repo,
+                    )?
+                    else {
+                        summary.skipped += 1;
+                        continue;
+                    };
+                    let module = import_module(conn, &record, &dep_type_ids)?;
+                    module_ids.entry(repo_id).or_default().push(module.id);
+                    summary.modules += 1;
+                }
+                DatasetRecord::PullRequest(pr) => {
+                    let Some(repo_id) =
+                        claim_repository(conn, &mut claimed, &source.id, &pr.org, &pr.repo)?
+                    else {
+                        continue;
+                    };
+                    models::pull_request::add(
+                        conn,
+                        &pr.name,
+                        &pr.module_technical_name,
+                        &pr.prid,
+                        &(pr.version_odoo as u8),
+                        &repo_id,
+                        pr.created_at.as_deref(),
+                        pr.ci_status.as_deref(),
+                        pr.last_message_at.as_deref(),
+                    )?;
+                    prids.entry(repo_id).or_default().push(pr.prid);
+                    summary.pull_requests += 1;
+                }
+                DatasetRecord::PullRequestHistory(history) => {
+                    let Some(repo_id) = claim_repository(
+                        conn,
+                        &mut claimed,
+                        &source.id,
+                        &history.org,
+                        &history.repo,
+                    )?
+                    else {
+                        continue;
+                    };
+                    if !models::pull_request_history::exists(
+                        conn,
+                        &repo_id,
+                        &history.prid,
+                        &history.closed_at,
+                    ) {
+                        models::pull_request_history::add(
+                            conn,
+                            &history.module_technical_name,
+                            history.version_odoo,
+                            repo_id,
+                            history.prid,
+                            &history.created_at,
+                            &history.closed_at,
+                            &history.close_reason,
+                            &history.reviews,
+                        )?;
+                    }
+                }
+            }
+        }
+
+        // The export is the source's whole dataset: what it no longer has,
+        // down to versions and repositories absent from the file, is gone.
+        for repo in models::gh_repository::get_by_dataset_source_id(conn, &source.id) {
+            let repo_ids = module_ids
+                .get(&repo.id)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            summary.removed += models::module::delete_missing(conn, &repo.id, repo_ids)?;
+            let repo_prids = prids.get(&repo.id).map(Vec::as_slice).unwrap_or_default();
+            models::pull_request::delete_missing(conn, &repo.id, repo_prids)?;
+        }
+        Ok(summary)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export, import};
+    use sqlitedb::models;
+    use sqlitedb::models::module::ManifestInfo;
+    use sqlitedb::models::module_code_analysis::{ModuleAnalysisInfo, ViewAnalysisInfo};
+
+    fn make_info(org: &str, technical_name: &str, name: &str) -> ManifestInfo {
+        ManifestInfo {
+            technical_name: technical_name.to_string(),
+            version_odoo: 17,
+            name: name.to_string(),
+            version_module: "17.0.1.0.0".to_string(),
+            author: "Odoo Community Association (OCA)".to_string(),
+            git_org: org.to_string(),
+            git_repo: "web".to_string(),
+            depends: vec!["web".to_string()],
+            external_depends_python: vec!["requests==2.0".to_string()],
+            last_commit_hash: "abc".to_string(),
+            installable: true,
+            analysis: ModuleAnalysisInfo {
+                views: vec![ViewAnalysisInfo {
+                    xml_id: "view_form".to_string(),
+                    model: Some("res.partner".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn collect(conn: &mut sqlitedb::DbConnection, info: &ManifestInfo) -> models::module::Model {
+        let module = models::module::add(conn, info).unwrap();
+        let version =
+            models::module_version::get_or_create(conn, &module.id, &info.version_module).unwrap();
+        models::module_view::replace_for_module(
+            conn,
+            &module.id,
+            &version.id,
+            &info.analysis.views,
+        )
+        .unwrap();
+        let python = models::dependency_type::get_by_name(conn, "python").unwrap();
+        for name in &info.external_depends_python {
+            let dep_mod =
+                models::dependency_module::add(conn, &python.id, name, &module.id).unwrap();
+            models::dependency_osv::add(conn, &dep_mod.id, "PYSEC-1", "details", "2.1").unwrap();
+        }
+        let module_type = models::dependency_type::get_by_name(conn, "module").unwrap();
+        for name in &info.depends {
+            models::dependency_module::add(conn, &module_type.id, name, &module.id).unwrap();
+        }
+        module
+    }
+
+    // The dataset of an instance that collected OCA's `web_tree`, with an
+    // open migration PR. The instance is gone when this returns: on
+    // PostgreSQL it shares the test database with the importing one.
+    fn public_dataset() -> Vec<u8> {
+        let mut public = sqlitedb::establish_test_connection();
+        let module = collect(&mut public, &make_info("OCA", "web_tree", "Web Tree"));
+        models::pull_request::add(
+            &mut public,
+            "[17.0][MIG] web_tree",
+            "web_tree",
+            &42,
+            &17,
+            &module.gh_repository_id,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let mut dataset = Vec::new();
+        let exported = export(&mut public, "public", &mut dataset).unwrap();
+        assert_eq!(exported.modules, 1);
+        assert_eq!(exported.pull_requests, 1);
+        dataset
+    }
+
+    #[test]
+    fn test_export_import_round_trip_keeps_local_data() {
+        let dataset = public_dataset();
+
+        // The private instance already collects its own `web` repository
+        // (same name, another organization) and a customized copy of OCA's.
+        let mut private = sqlitedb::establish_test_connection();
+        collect(
+            &mut private,
+            &make_info("customer", "web_tree", "Customer Tree"),
+        );
+        let mut local = make_info("OCA", "web_tree", "Local Tree");
+        local.git_repo = "web-local".to_string();
+        collect(&mut private, &local);
+
+        let summary = import(&mut private, dataset.as_slice()).unwrap();
+        assert_eq!(summary.source, "public");
+        assert_eq!(summary.modules, 1);
+        assert_eq!(summary.skipped, 0);
+        assert_eq!(summary.pull_requests, 1);
+
+        let oca =
+            models::module::get_by_technical_name_odoo_version_organization_name_repository_name(
+                &mut private,
+                "web_tree",
+                &17,
+                "OCA",
+                "web",
+            )
+            .remove(0);
+        assert_eq!(oca.name, "Web Tree");
+        let repo = models::gh_repository::get_by_id(&mut private, &oca.gh_repository_id).unwrap();
+        let source =
+            models::dataset_source::get_by_id(&mut private, &repo.dataset_source_id.unwrap())
+                .unwrap();
+        assert_eq!(source.name, "public");
+        let version = models::module_version::resolve_current(&mut private, &oca).unwrap();
+        assert_eq!(
+            models::module_view::get_by_module_version_id(&mut private, &version.id)[0].xml_id,
+            "view_form"
+        );
+        let osv = models::dependency_osv::get_by_module_id(&mut private, &oca.id);
+        assert_eq!(osv[0].dependency_name, "requests==2.0");
+        assert_eq!(osv[0].osv_id, "PYSEC-1");
+        assert_eq!(
+            models::pull_request::get_by_gh_repository_id(&mut private, &repo.id)[0].prid,
+            42
+        );
+
+        // Collected modules are untouched and not exported back.
+        let customer = models::module::get_by_technical_name_organization_name(
+            &mut private,
+            "web_tree",
+            "customer",
+        );
+        assert_eq!(customer[0].name, "Customer Tree");
+        assert_eq!(models::module::get_collected(&mut private).len(), 2);
+
+        // Importing the same export again changes nothing.
+        let again = import(&mut private, dataset.as_slice()).unwrap();
+        assert_eq!(again.modules, 1);
+        assert_eq!(again.removed, 0);
+        assert_eq!(
+            models::dependency_osv::get_by_module_id(&mut private, &oca.id).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_import_replaces_osv_findings() {
+        let dataset = public_dataset();
+        let mut private = sqlitedb::establish_test_connection();
+        import(&mut private, dataset.as_slice()).unwrap();
+
+        // The source fixed PYSEC-1 and found PYSEC-2 since.
+        let dataset = String::from_utf8(dataset)
+            .unwrap()
+            .replace("PYSEC-1", "PYSEC-2")
+            .into_bytes();
+        import(&mut private, dataset.as_slice()).unwrap();
+        let oca = models::module::get_by_technical_name_organization_name(
+            &mut private,
+            "web_tree",
+            "OCA",
+        );
+        let osv = models::dependency_osv::get_by_module_id(&mut private, &oca[0].id);
+        assert_eq!(osv.len(), 1);
+        assert_eq!(osv[0].osv_id, "PYSEC-2");
+    }
+
+    #[test]
+    fn test_import_drops_what_the_source_no_longer_exports() {
+        let mut public = sqlitedb::establish_test_connection();
+        let module = collect(&mut public, &make_info("OCA", "web_tree", "Web Tree"));
+        let mut older = make_info("OCA", "web_tree", "Web Tree");
+        older.version_odoo = 16;
+        older.version_module = "16.0.1.0.0".to_string();
+        collect(&mut public, &older);
+        let mut other = make_info("OCA", "base_tier", "Base Tier");
+        other.git_repo = "server-ux".to_string();
+        let other = collect(&mut public, &other);
+        for (prid, repo_id) in [(42, module.gh_repository_id), (43, other.gh_repository_id)] {
+            models::pull_request::add(
+                &mut public,
+                "[17.0][MIG]",
+                "x",
+                &prid,
+                &17,
+                &repo_id,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        }
+        let mut full = Vec::new();
+        export(&mut public, "public", &mut full).unwrap();
+        drop(public);
+
+        let mut private = sqlitedb::establish_test_connection();
+        import(&mut private, full.as_slice()).unwrap();
+
+        // The next export lost `server-ux` and the 16.0 branch of `web`.
+        let newer: String = String::from_utf8(full)
+            .unwrap()
+            .lines()
+            .filter(|line| !line.contains("\"server-ux\"") && !line.contains("16.0.1.0.0"))
+            .map(|line| format!("{line}\n"))
+            .collect();
+        let summary = import(&mut private, newer.as_bytes()).unwrap();
+        assert_eq!(summary.modules, 1);
+        assert_eq!(summary.removed, 2);
+        let web_tree = models::module::get_by_technical_name_organization_name(
+            &mut private,
+            "web_tree",
+            "OCA",
+        );
+        assert_eq!(web_tree.len(), 1);
+        assert_eq!(web_tree[0].version_odoo, 17);
+        assert!(models::module::get_by_technical_name_organization_name(
+            &mut private,
+            "base_tier",
+            "OCA"
+        )
+        .is_empty());
+        let ux = models::gh_organization::get_by_name(&mut private, "OCA")
+            .and_then(|org| models::gh_repository::get_by_name(&mut private, &org.id, "server-ux"))
+            .unwrap();
+        assert!(models::pull_request::get_by_gh_repository_id(&mut private, &ux.id).is_empty());
+    }
+
+    #[test]
+    fn test_import_skips_collected_repositories() {
+        let dataset = public_dataset();
+        let mut private = sqlitedb::establish_test_connection();
+        collect(&mut private, &make_info("OCA", "web_tree", "Local Tree"));
+        let summary = import(&mut private, dataset.as_slice()).unwrap();
+        assert_eq!(summary.modules, 0);
+        assert_eq!(summary.skipped, 1);
+        let modules = models::module::get_by_technical_name_organization_name(
+            &mut private,
+            "web_tree",
+            "OCA",
+        );
+        assert_eq!(modules[0].name, "Local Tree");
+    }
+
+    #[test]
+    fn test_import_rejects_unknown_files() {
+        let mut conn = sqlitedb::establish_test_connection();
+        assert!(import(&mut conn, "{\"type\":\"module\"}\n".as_bytes()).is_err());
+        let newer = "{\"type\":\"header\",\"format\":\"oghcollector-dataset\",\"version\":99,\
+                     \"source\":\"x\",\"export_date\":\"2026-01-01 00:00:00\"}\n";
+        assert!(import(&mut conn, newer.as_bytes()).is_err());
+    }
+}
@@ -4,6 +4,10 @@ use regex::Regex;
 use std::fs;
 use std::path::Path;
 
+use sqlitedb::models::open_pull_request::OpenPullRequestInfo;
+use sqlitedb::models::pull_request_history::PullRequestClosureInfo;
+
+#[derive(Debug)]
 pub struct RepoInfo {
     pub name: String,
     pub org: String,
@@ -26,6 +30,55 @@ impl RepoInfo {
     }
 }
 
+/// A repository that couldn't be cloned or updated.
+#[derive(Debug, Clone)]
+pub struct CloneFailureInfo {
+    pub org: String,
+    pub name: String,
+    /// The repository has no branch for the collected version.
+    pub missing_branch: bool,
+    pub error: String,
+}
+
+/// Outcome of cloning/updating every repository of an organization.
+#[derive(Debug, Default)]
+pub struct CloneReport {
+    pub repos: Vec<RepoInfo>,
+    pub failures: Vec<CloneFailureInfo>,
+    /// Set when listing the organization's repositories failed, so the
+    /// report may be missing some of them.
+    pub error: Option<String>,
+}
+
+// Runs git in `dir`, keeping the last line of its stderr as the error.
+fn run_git(dir: &str, args: &[&str]) -> Result<(), String> {
+    let output = cmd("git", args)
+        .dir(dir)
+        .stdin_null()
+        .stderr_capture()
+        .unchecked()
+        .run()
+        .map_err(|err| err.to_string())?;
+    if output.status.success() {
+        return Ok(());
+    }
+    // git follows its `fatal:` line with hints, which say nothing on their own.
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines = stderr.lines().map(str::trim).collect::<Vec<&str>>();
+    Err(lines
+        .iter()
+        .rfind(|line| line.starts_with("fatal:") || line.starts_with("error:"))
+        .or_else(|| lines.iter().rfind(|line| !line.is_empty()))
+        .unwrap_or(&"git exited with an error")
+        .to_string())
+}
+
+fn is_missing_branch_error(error: &str) -> bool {
+    error.contains("not found in upstream")
+        || error.contains("unknown revision")
+        || error.contains("invalid reference")
+}
+
 #[derive(Debug, Clone)]
 pub struct PullRequestInfo {
     pub number: i64,
@@ -80,6 +133,58 @@ pub fn extract_migration_module_name(head_ref: &str) -> Option<String> {
     })
 }
 
+/// Module folders changed by a PR/MR, from the file paths its files/diffs
+/// API reports (both old and new paths, so renames count on both sides).
+/// A module lives in a top-level folder of the repository; OCA's
+/// `setup/{module}/` packaging folders are mapped back to their module.
+/// Root files, dot-folders (`.github/`, ...) and `setup/_metapackage`
+/// aren't modules.
+pub fn extract_module_folders<'a>(paths: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut folders: Vec<String> = Vec::new();
+    for path in paths {
+        let mut segments = path.split('/');
+        let folder = match (segments.next(), segments.next(), segments.next()) {
+            (Some("setup"), Some(name), Some(_)) => name,
+            (Some(name), Some(_), _) => name,
+            _ => continue,
+        };
+        if folder.is_empty()
+            || folder == "setup"
+            || folder.starts_with('_')
+            || !folder
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            continue;
+        }
+        if !folders.iter().any(|f| f == folder) {
+            folders.push(folder.to_string());
+        }
+    }
+    folders.sort();
+    folders
+}
+
+/// The OCA migration PRs/MRs among all open ones, i.e. whose head branch
+/// follows the `{version}-mig-{module}` convention - these get their own
+/// lifecycle tracking in `pull_request`.
+pub fn filter_migration_pull_requests(prs: &[OpenPullRequestInfo]) -> Vec<PullRequestInfo> {
+    prs.iter()
+        .filter_map(|pr| {
+            extract_migration_module_name(&pr.head_ref).map(|module_technical_name| {
+                PullRequestInfo {
+                    number: pr.number,
+                    title: pr.title.clone(),
+                    module_technical_name,
+                    created_at: pr.created_at.clone(),
+                    last_message_at: pr.last_message_at.clone(),
+                    ci_status: pr.ci_status.clone(),
+                }
+            })
+        })
+        .collect()
+}
+
 pub trait GitClient {
     fn new(token: &str, base_url: &str) -> Self;
 
@@ -90,54 +195,45 @@ pub trait GitClient {
         repo_url: &str,
         branch: &str,
         dest: &str,
-    ) -> Option<RepoInfo> {
+    ) -> Result<RepoInfo, CloneFailureInfo> {
         let clone_path = format!("{dest}/{org_name}/{repo_name}");
         let clone_path_exists = Path::new(&clone_path).exists();
+        let fail = |error: String| CloneFailureInfo {
+            org: org_name.into(),
+            name: repo_name.into(),
+            missing_branch: is_missing_branch_error(&error),
+            error,
+        };
         if clone_path_exists {
             log::info!("Updating repo: {repo_name} @ {branch}");
-            cmd!("git", "fetch", "origin", "--prune")
-                .dir(&clone_path)
-                .stdin_null()
-                .run()
-                .ok()?;
-            cmd!("git", "reset", "--hard", &format!("origin/{branch}"))
-                .dir(&clone_path)
-                .stdin_null()
-                .run()
-                .ok()?;
-            cmd!("git", "clean", "-fdx")
-                .dir(&clone_path)
-                .stdin_null()
-                .run()
-                .ok()?;
-            cmd!("git", "switch", "-C", branch, &format!("origin/{branch}"))
-                .dir(&clone_path)
-                .stdin_null()
-                .run()
-                .ok()?;
+            let remote_branch = format!("origin/{branch}");
+            run_git(&clone_path, &["fetch", "origin", "--prune"]).map_err(fail)?;
+            run_git(&clone_path, &["reset", "--hard", &remote_branch]).map_err(fail)?;
+            run_git(&clone_path, &["clean", "-fdx"]).map_err(fail)?;
+            run_git(&clone_path, &["switch", "-C", branch, &remote_branch]).map_err(fail)?;
             log::info!("Repo updated & cleaned: {repo_name} @ {branch}");
         } else {
             log::info!("Cloning repo: {repo_name} @ {branch}");
             let base_dir = format!("{dest}/{org_name}");
             if fs::create_dir_all(&base_dir).is_err() {
                 log::error!("Cannot create directory: {base_dir}");
-                return None;
+                return Err(fail(format!("Cannot create directory: {base_dir}")));
             }
 
-            cmd!(
-                "git",
-                "clone",
-                "--no-single-branch",
-                "--branch",
-                branch,
-                repo_url,
+            run_git(
+                &base_dir,
+                &[
+                    "clone",
+                    "--no-single-branch",
+                    "--branch",
+                    branch,
+                    repo_url,
+                    repo_name,
+                ],
             )
-            .dir(&base_dir)
-            .stdin_null()
-            .run()
-            .ok()?;
+            .map_err(fail)?;
         }
-        Some(RepoInfo {
+        Ok(RepoInfo {
             name: repo_name.into(),
             org: org_name.into(),
             clone_path,
@@ -147,6 +243,9 @@ pub trait GitClient {
 
     async fn request(&self, url: &str) -> Result<reqwest::Response, reqwest::Error>;
 
+    /// API requests sent so far by this client.
+    fn get_api_calls(&self) -> u64;
+
     async fn request_json(&self, url: &str) -> Result<serde_json::Value, reqwest::Error> {
         let req = self.request(url).await?;
         req.json().await
@@ -159,7 +258,7 @@ pub trait GitClient {
         page: &usize,
     ) -> Result<serde_json::Value, reqwest::Error>;
 
-    async fn clone_org_repos(&self, org_name: &str, branch: &str, dest: &str) -> Vec<RepoInfo>;
+    async fn clone_org_repos(&self, org_name: &str, branch: &str, dest: &str) -> CloneReport;
 
     async fn get_repo_pull_requests(
         &self,
@@ -169,23 +268,36 @@ pub trait GitClient {
         page: &usize,
     ) -> Result<serde_json::Value, reqwest::Error>;
 
-    async fn get_open_migration_pull_requests(
+    /// Every open PR/MR against `branch`, with the module folders it changes,
+    /// labels, draft state, review/approval counts and CI status. Costs a few
+    /// extra API calls per PR (files, reviews/approvals, CI) on top of the
+    /// paginated list - use `filter_migration_pull_requests` on the result
+    /// instead of fetching the list again for migration PRs.
+    async fn get_open_pull_requests(
         &self,
         full_path: &str,
         branch: &str,
-    ) -> Vec<PullRequestInfo>;
-
-    /// Whether a PR/MR that's no longer open was actually merged - one extra
-    /// GET per newly-closed PR, called right before `delete_outdated` so the
-    /// "avg. time open before close" stat only counts merged PRs, not rejections.
-    /// `None` on a lookup failure (network/rate-limit/404): treated as "not merged"
-    /// by the caller, so a transient error undercounts rather than pollutes.
-    async fn is_pull_request_merged(&self, full_path: &str, number: &i64) -> Option<bool>;
+    ) -> Vec<OpenPullRequestInfo>;
+
+    /// How a PR/MR that's no longer in the open migration list ended: merged,
+    /// closed without merge, or still open but retargeted/renamed - with the
+    /// provider's real merge/close date and its reviews (PR author excluded),
+    /// for the latency stats. Two extra GETs per newly-closed PR, called right
+    /// before `delete_outdated`. `None` on a lookup failure
+    /// (network/rate-limit/404): the caller records it as `CLOSE_REASON_UNKNOWN`.
+    async fn get_pull_request_closure(
+        &self,
+        full_path: &str,
+        number: &i64,
+    ) -> Option<PullRequestClosureInfo>;
 }
 
 #[cfg(test)]
 mod tests {
-    use super::extract_migration_module_name;
+    use super::{extract_migration_module_name, extract_module_folders, GitClient};
+    use crate::clients::github::GithubClient;
+    use duct::cmd;
+    use std::fs;
 
     #[test]
     fn test_extract_migration_module_name_matches_oca_convention() {
@@ -230,4 +342,101 @@ mod tests {
             Some("nombre_modulo_algo_BAD".to_string())
         );
     }
+
+    #[test]
+    fn test_extract_module_folders_maps_paths_to_modules() {
+        assert_eq!(
+            extract_module_folders([
+                "sale_commission/models/sale.py",
+                "sale_commission/__manifest__.py",
+                "setup/sale_commission_product/odoo/addons/sale_commission_product",
+                "account_tag/i18n/es.po",
+            ]),
+            vec![
+                "account_tag".to_string(),
+                "sale_commission".to_string(),
+                "sale_commission_product".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_module_folders_skips_non_module_paths() {
+        assert!(extract_module_folders([
+            "README.md",
+            ".github/workflows/test.yml",
+            "setup/_metapackage/setup.py",
+            "setup/setup.py",
+        ])
+        .is_empty());
+    }
+
+    #[test]
+    fn test_clone_or_update_repo_reports_failures() {
+        let dir = std::env::temp_dir().join(format!(
+            "oghcollector_gitclient_test_{}_{}",
+            std::process::id(),
+            "clone_failures"
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        let origin_dir = dir.join("origin");
+        fs::create_dir_all(&origin_dir).unwrap();
+        fs::write(origin_dir.join("README.md"), "hello\n").unwrap();
+        let git = |args: &[&str]| {
+            cmd("git", args)
+                .dir(&origin_dir)
+                .stdin_null()
+                .stdout_null()
+                .stderr_null()
+                .run()
+                .unwrap();
+        };
+        git(&["init", "-q", "-b", "16.0"]);
+        git(&["add", "-A"]);
+        git(&[
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@test.com",
+            "commit",
+            "-q",
+            "-m",
+            "init",
+        ]);
+
+        let client = GithubClient::new("", "");
+        let dest = dir.join("repos").to_string_lossy().to_string();
+        let origin_url = origin_dir.to_string_lossy().to_string();
+        let repo = client
+            .clone_or_update_repo("test_org", "test_repo", &origin_url, "16.0", &dest)
+            .unwrap();
+        assert_eq!(repo.get_full_path(), "test_org/test_repo");
+        // Update path of an existing clone.
+        assert!(client
+            .clone_or_update_repo("test_org", "test_repo", &origin_url, "16.0", &dest)
+            .is_ok());
+
+        let failure = client
+            .clone_or_update_repo("test_org", "other_repo", &origin_url, "17.0", &dest)
+            .unwrap_err();
+        assert!(failure.missing_branch);
+        assert!(failure.error.contains("17.0"));
+        let failure = client
+            .clone_or_update_repo("test_org", "test_repo", &origin_url, "17.0", &dest)
+            .unwrap_err();
+        assert!(failure.missing_branch);
+        let failure = client
+            .clone_or_update_repo(
+                "test_org",
+                "missing_repo",
+                &dir.join("missing").to_string_lossy(),
+                "16.0",
+                &dest,
+            )
+            .unwrap_err();
+        assert!(!failure.missing_branch);
+        assert!(!failure.error.is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
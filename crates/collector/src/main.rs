@@ -1,9 +1,16 @@
 // Copyright Alexandre D. Díaz
 mod analyzer;
 mod anygitclient;
+mod apriori;
+mod bots;
 mod clients;
 mod config;
+mod daemon;
+mod dataset;
 mod gitclient;
+mod maintenance;
+mod metrics;
+mod openupgrade;
 mod pypi;
 mod security;
 
@@ -11,8 +18,9 @@ use named_lock::NamedLock;
 use regex::Regex;
 use std::collections::HashMap;
 use std::env;
-use std::fs::{self, File};
-use std::path::Path;
+use std::fs;
+use std::future::Future;
+use std::sync::Arc;
 use std::time::Instant;
 
 use analyzer::OGHCollectorAnalyzer;
@@ -20,58 +28,479 @@ use anygitclient::AnyGitClient;
 use clients::github::GithubClient;
 use clients::gitlab::GitlabClient;
 use config::{GitType, OGHCollectorConfig};
-use gitclient::{GitClient, RepoInfo};
-use oghutils::version::odoo_version_u8_to_string;
+
+use gitclient::{filter_migration_pull_requests, CloneFailureInfo, GitClient, RepoInfo};
+use oghutils::version::{odoo_version_string_to_u8, odoo_version_u8_to_string};
 use pypi::PypiClient;
 use sqlitedb::models;
+use sqlitedb::models::collection_run::RunTotalsInfo;
+use sqlitedb::models::collection_run_repo::RunRepoInfo;
+use sqlitedb::models::pull_request_history::PullRequestClosureInfo;
+use sqlitedb::DbConnection;
 
 // The guard must stay alive for the whole run: dropping it releases the lock,
 // which is why this returns it instead of letting it die inside the function.
-fn try_lock(config: &OGHCollectorConfig) -> named_lock::NamedLockGuard {
-    let source_info = config.get_source().split('/').collect::<Vec<&str>>();
-    let org = source_info[0];
+// `None` when another instance is already working with the same organization.
+fn acquire_lock(config: &OGHCollectorConfig) -> Option<named_lock::NamedLockGuard> {
+    acquire_org_lock(config.get_org())
+}
+
+fn acquire_org_lock(org: &str) -> Option<named_lock::NamedLockGuard> {
     let lock_name = format!("OGHCollector::{org}");
     let lock = NamedLock::create(lock_name.as_str()).expect("Can't create the collector lock");
-    match lock.try_lock() {
-        Ok(guard) => guard,
-        Err(_) => {
+    lock.try_lock().ok()
+}
+
+// Exit status of a run that found its organization locked by another
+// instance, so `oghcollector daemon` can tell it apart from a failure.
+pub(crate) const EXIT_LOCKED: i32 = 75;
+
+fn try_lock(config: &OGHCollectorConfig) -> named_lock::NamedLockGuard {
+    match acquire_lock(config) {
+        Some(guard) => guard,
+        None => {
             eprintln!(
-                "There is already an instance of OGHCollector working with '{org}'. Exiting..."
+                "There is already an instance of OGHCollector working with '{}'. Exiting...",
+                config.get_org()
             );
+            std::process::exit(EXIT_LOCKED);
+        }
+    }
+}
+
+fn open_write_pool() -> sqlitedb::Pool {
+    let database_url = sqlitedb::database_url("data/data.db").unwrap();
+    let pool = sqlitedb::new_write_pool(&database_url);
+    let mut conn = pool.get().unwrap();
+    sqlitedb::run_migrations(&mut conn).expect("Can't run migrations");
+    pool
+}
+
+// `oghcollector apriori <apriori.py> <version>`: records OpenUpgrade's module
+// renames/merges for that version in `module_lineage`.
+fn import_apriori(pool: &sqlitedb::Pool, args: &[String]) {
+    let (Some(file_path), Some(version)) = (args.get(2), args.get(3)) else {
+        eprintln!("Usage: oghcollector apriori <apriori.py> <version>");
+        std::process::exit(1);
+    };
+    let content = fs::read_to_string(file_path).unwrap_or_else(|err| {
+        eprintln!("Can't read '{file_path}': {err}");
+        std::process::exit(1);
+    });
+    let info = apriori::parse_apriori(&content);
+    let mut conn = pool.get().unwrap();
+    let inserted = models::module_lineage::import_apriori(
+        &mut conn,
+        &odoo_version_string_to_u8(version),
+        &info.renamed_modules,
+        &info.merged_modules,
+    )
+    .expect("Can't import the apriori file");
+    log::info!(
+        "Imported {} renamed and {} merged modules ({inserted} new links)",
+        info.renamed_modules.len(),
+        info.merged_modules.len()
+    );
+}
+
+// `oghcollector bots list|add <name>|remove <name>`: edits the admin list of
+// bot accounts (on top of the `files/bots` rules) and re-flags committers.
+fn manage_bots(pool: &sqlitedb::Pool, args: &[String]) {
+    let mut conn = pool.get().unwrap();
+    match (args.get(2).map(String::as_str), args.get(3)) {
+        (Some("list"), None) => {
+            for bot in models::bot_account::get_all(&mut conn) {
+                println!("{}", bot.name);
+            }
+            return;
+        }
+        (Some("add"), Some(name)) => {
+            models::bot_account::add(&mut conn, name).expect("Can't add the bot account");
+        }
+        (Some("remove"), Some(name)) => {
+            models::bot_account::remove(&mut conn, name).expect("Can't remove the bot account");
+        }
+        _ => {
+            eprintln!("Usage: oghcollector bots list|add <name>|remove <name>");
             std::process::exit(1);
         }
     }
+    let changed = bots::refresh_committer_flags(&mut conn, &bots::BotRules::load());
+    log::info!("Updated the bot flag of {changed} committers");
+}
+
+// `oghcollector export <dataset.jsonl> <source name>`: writes what this
+// instance collected as a dataset another instance can import, under the
+// given source name.
+fn export_dataset(pool: &sqlitedb::Pool, args: &[String]) {
+    let (Some(file_path), Some(source)) = (args.get(2), args.get(3)) else {
+        eprintln!("Usage: oghcollector export <dataset.jsonl> <source name>");
+        std::process::exit(1);
+    };
+    let file = fs::File::create(file_path).unwrap_or_else(|err| {
+        eprintln!("Can't create '{file_path}': {err}");
+        std::process::exit(1);
+    });
+    let mut conn = pool.get().unwrap();
+    let summary = dataset::export(&mut conn, source, &mut std::io::BufWriter::new(file))
+        .unwrap_or_else(|err| {
+            eprintln!("Can't export the dataset: {err}");
+            std::process::exit(1);
+        });
+    log::info!(
+        "Exported {} modules and {} migration PRs to '{file_path}'",
+        summary.modules,
+        summary.pull_requests
+    );
+}
+
+// `oghcollector import <dataset.jsonl>`: merges another instance's export
+// into this one (see dataset::import).
+fn import_dataset(pool: &sqlitedb::Pool, args: &[String]) {
+    let Some(file_path) = args.get(2) else {
+        eprintln!("Usage: oghcollector import <dataset.jsonl>");
+        std::process::exit(1);
+    };
+    let file = fs::File::open(file_path).unwrap_or_else(|err| {
+        eprintln!("Can't read '{file_path}': {err}");
+        std::process::exit(1);
+    });
+    let mut conn = pool.get().unwrap();
+    let summary = dataset::import(&mut conn, std::io::BufReader::new(file)).unwrap_or_else(|err| {
+        eprintln!("Can't import '{file_path}': {err}");
+        std::process::exit(1);
+    });
+    // A dataset can carry any Odoo version.
+    for version_odoo in models::module::get_odoo_versions(&mut conn) {
+        models::atlas_graph::refresh(&mut conn, &(version_odoo as u8)).unwrap();
+        models::module_health::refresh(&mut conn, &(version_odoo as u8)).unwrap();
+    }
+    log::info!(
+        "Imported {} modules and {} migration PRs from '{}' ({} removed, {} skipped: collected here)",
+        summary.modules,
+        summary.pull_requests,
+        summary.source,
+        summary.removed,
+        summary.skipped
+    );
+}
+
+// `oghcollector maintenance`: applies the retention policies set in the
+// environment and compacts the database (see maintenance::run).
+fn run_maintenance(pool: &sqlitedb::Pool) {
+    let policy = maintenance::RetentionPolicy::from_env();
+    let mut conn = pool.get().unwrap();
+    let summary = maintenance::run(&mut conn, &policy).unwrap_or_else(|err| {
+        eprintln!("Can't run the maintenance: {err}");
+        std::process::exit(1);
+    });
+    log::info!(
+        "Deleted {} info and {} warning events and {} analysis snapshots; reclaimed {} bytes ({} -> {})",
+        summary.info_events,
+        summary.warning_events,
+        summary.snapshots,
+        summary.reclaimed(),
+        summary.size_before,
+        summary.size_after
+    );
 }
 
 #[tokio::main]
 async fn main() {
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
     let args: Vec<String> = env::args().collect();
+    // Every command shares the one pool, migrated once here.
+    let pool = open_write_pool();
+    if args.get(1).map(String::as_str) == Some("apriori") {
+        import_apriori(&pool, &args);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("bots") {
+        manage_bots(&pool, &args);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("export") {
+        export_dataset(&pool, &args);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("import") {
+        import_dataset(&pool, &args);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("maintenance") {
+        run_maintenance(&pool);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("daemon") {
+        daemon::run(pool).await;
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("queue") {
+        drain_queue(&pool, &args[0]).await;
+        return;
+    }
     let config = OGHCollectorConfig::new(&args);
 
     let _lock_guard = try_lock(&config);
+    let trigger = env::var("OGHCOLLECTOR_TRIGGER")
+        .unwrap_or(models::collection_run::TRIGGER_MANUAL.to_string());
+    if run_collection(pool, Arc::new(config), &trigger)
+        .await
+        .is_err()
+    {
+        std::process::exit(1);
+    }
+    log::info!("All done. Bye!");
+}
 
-    let git_client = match config.get_git_type() {
+fn new_git_client(config: &OGHCollectorConfig) -> AnyGitClient {
+    match config.get_git_type() {
         GitType::Github => {
             AnyGitClient::Github(GithubClient::new(config.get_token(), config.get_base_url()))
         }
         GitType::Gitlab => {
             AnyGitClient::Gitlab(GitlabClient::new(config.get_token(), config.get_base_url()))
         }
+    }
+}
+
+// `oghcollector queue`: runs the jobs enqueued by the webhook receiver
+// (oghwebhook), oldest first. A job whose organization is locked by another
+// running instance stays pending for the next drain.
+async fn drain_queue(pool: &sqlitedb::Pool, prog: &str) {
+    let mut conn = pool.get().unwrap();
+    let jobs = models::collection_job::get_pending(&mut conn);
+    log::info!("Draining '{}' queued jobs...", jobs.len());
+    for job in jobs {
+        let mut config = OGHCollectorConfig::new(&[
+            prog.to_string(),
+            job.source.clone(),
+            job.branch.clone(),
+            job.git_type.clone(),
+        ]);
+        config.set_repo_url(job.repo_url.clone());
+        let Some(_lock_guard) = acquire_lock(&config) else {
+            log::info!(
+                "'{}' is locked by another instance. Leaving job #{} pending...",
+                job.source,
+                job.id
+            );
+            continue;
+        };
+        match models::collection_job::claim(&mut conn, &job.id) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(err) => {
+                log::error!("Can't claim job #{}: {err}", job.id);
+                let error = format!("Can't claim the job: {err}");
+                if let Err(err) = models::collection_job::finish(&mut conn, &job.id, Some(&error)) {
+                    log::error!("Can't record the failure of job #{}: {err}", job.id);
+                }
+                continue;
+            }
+        }
+        log::info!(
+            "Running job #{} ({} '{}' {})...",
+            job.id,
+            job.kind,
+            job.source,
+            job.branch
+        );
+        let config = Arc::new(config);
+        let kind = job.kind.clone();
+        let pool = pool.clone();
+        let res = run_job(&mut conn, &job.id, async move {
+            if kind == models::collection_job::KIND_PULL_REQUESTS {
+                refresh_pull_requests(&pool, &config).await
+            } else {
+                run_collection(pool, config, models::collection_run::TRIGGER_WEBHOOK).await
+            }
+        })
+        .await;
+        if let Err(err) = res {
+            log::error!("Can't record the end of job #{}: {err}", job.id);
+        }
+    }
+}
+
+// Runs a claimed job in its own task and records how it ended: a panic
+// fails the job with its message instead of leaving it "running" and taking
+// the rest of the queue down with the process.
+async fn run_job<F>(conn: &mut DbConnection, job_id: &i64, work: F) -> diesel::QueryResult<usize>
+where
+    F: Future<Output = Result<(), String>> + Send + 'static,
+{
+    let res = tokio::spawn(work)
+        .await
+        .unwrap_or_else(|err| Err(panic_message(err)));
+    if let Err(err) = &res {
+        log::warn!("Job #{job_id} failed: {err}");
+    }
+    models::collection_job::finish(conn, job_id, res.err().as_deref())
+}
+
+fn panic_message(err: tokio::task::JoinError) -> String {
+    match err.try_into_panic() {
+        Ok(payload) => payload
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| payload.downcast_ref::<&str>().map(|msg| msg.to_string()))
+            .unwrap_or("The collector panicked".to_string()),
+        Err(err) => err.to_string(),
+    }
+}
+
+// Pull request events only need the migration PR state of the repository,
+// which comes from the provider's API: no clone nor module analysis.
+async fn refresh_pull_requests(
+    pool: &sqlitedb::Pool,
+    config: &OGHCollectorConfig,
+) -> Result<(), String> {
+    let Some((org_name, repo_name)) = config.get_source().split_once('/') else {
+        return Err(format!(
+            "Invalid source '{}': expected '<user>/<repo>'",
+            config.get_source()
+        ));
     };
-    let pypi_client = PypiClient::new();
+    let git_client = new_git_client(config);
+    let mut conn = pool.get().unwrap();
+    let repo_infos = vec![RepoInfo {
+        name: repo_name.to_string(),
+        org: org_name.to_string(),
+        clone_path: String::new(),
+        full_path: config.get_source().clone(),
+    }];
+    scan_pull_requests(&mut conn, &git_client, config, &repo_infos).await;
+    // The CI of open PRs is part of the health score.
+    models::module_health::refresh(&mut conn, config.get_version_odoo())
+        .map_err(|err| format!("Can't refresh the health scores: {err}"))?;
+    Ok(())
+}
 
-    let db_path = "data/data.db";
-    if let Some(parent) = Path::new(db_path).parent() {
-        fs::create_dir_all(parent).unwrap();
+async fn scan_pull_requests(
+    conn: &mut DbConnection,
+    git_client: &AnyGitClient,
+    config: &OGHCollectorConfig,
+    repo_infos: &[RepoInfo],
+) {
+    let odoo_ver = config.get_version_odoo();
+    log::info!("Scanning '{}' repos for open PRs...", repo_infos.len());
+    for repo_info in repo_infos {
+        let gh_org = models::gh_organization::add(conn, repo_info.get_org()).unwrap();
+        let gh_repo = models::gh_repository::add(conn, &gh_org.id, repo_info.get_name()).unwrap();
+        let open_prs = git_client
+            .get_open_pull_requests(repo_info.get_full_path(), config.get_branch())
+            .await;
+        if let Err(err) = models::open_pull_request::replace_for_repository(
+            conn,
+            &gh_repo.id,
+            odoo_ver,
+            &open_prs,
+        ) {
+            log::error!(
+                "Can't store the open PRs of '{}': {err}",
+                repo_info.get_full_path()
+            );
+        }
+        let migration_prs = filter_migration_pull_requests(&open_prs);
+        let mut prids: Vec<i64> = Vec::with_capacity(migration_prs.len());
+        for pr in &migration_prs {
+            prids.push(pr.number);
+            models::pull_request::add(
+                conn,
+                &pr.title,
+                &pr.module_technical_name,
+                &pr.number,
+                odoo_ver,
+                &gh_repo.id,
+                pr.created_at.as_deref(),
+                pr.ci_status.as_deref(),
+                pr.last_message_at.as_deref(),
+            )
+            .unwrap();
+        }
+        let outdated = models::pull_request::find_outdated(conn, &gh_repo.id, odoo_ver, &prids)
+            .unwrap_or_default();
+        let mut closures: HashMap<i64, PullRequestClosureInfo> =
+            HashMap::with_capacity(outdated.len());
+        for pr in &outdated {
+            if let Some(closure) = git_client
+                .get_pull_request_closure(repo_info.get_full_path(), &pr.prid)
+                .await
+            {
+                closures.insert(pr.prid, closure);
+            }
+        }
+        let _ =
+            models::pull_request::delete_outdated(conn, &gh_repo.id, odoo_ver, &prids, &closures);
     }
-    if !Path::new(db_path).exists() {
-        File::create(db_path).unwrap();
+}
+// Records the run in `collection_run` and collects in its own task, so an
+// error or a panic fails the run with its message instead of leaving it
+// "running".
+async fn run_collection(
+    pool: sqlitedb::Pool,
+    config: Arc<OGHCollectorConfig>,
+    trigger: &str,
+) -> Result<(), String> {
+    let mut conn = pool.get().unwrap();
+    let run = models::collection_run::start(
+        &mut conn,
+        config.get_source(),
+        config.get_org(),
+        config.get_version_odoo(),
+        config.get_git_type().get_name(),
+        trigger,
+    )
+    .expect("Can't register the collection run");
+    let run_id = run.id;
+    let start_time = Instant::now();
+    let error = match tokio::spawn(async move { collect(&pool, &config, &run).await }).await {
+        Ok(Ok(())) => {
+            metrics::write_textfile(&mut conn);
+            return Ok(());
+        }
+        Ok(Err(error)) => error,
+        Err(err) => panic_message(err),
+    };
+    let _ = models::collection_run::finish(
+        &mut conn,
+        &run_id,
+        &(start_time.elapsed().as_secs() as i64),
+        &RunTotalsInfo::default(),
+        Some(&error),
+    );
+    metrics::write_textfile(&mut conn);
+    Err(error)
+}
+
+fn clone_failure_run_repo(failure: &CloneFailureInfo) -> RunRepoInfo {
+    RunRepoInfo {
+        org_name: failure.org.clone(),
+        repo_name: failure.name.clone(),
+        clone_status: if failure.missing_branch {
+            models::collection_run_repo::CLONE_MISSING_BRANCH
+        } else {
+            models::collection_run_repo::CLONE_FAILED
+        }
+        .to_string(),
+        analysis_status: models::collection_run_repo::ANALYSIS_SKIPPED.to_string(),
+        modules_added: 0,
+        modules_updated: 0,
+        modules_removed: 0,
+        error: Some(failure.error.clone()),
     }
+}
+
+async fn collect(
+    pool: &sqlitedb::Pool,
+    config: &OGHCollectorConfig,
+    run: &models::collection_run::Model,
+) -> Result<(), String> {
+    let git_client = new_git_client(config);
+    let pypi_client = PypiClient::new();
 
-    let pool = sqlitedb::new_write_pool(db_path);
     let mut conn = pool.get().unwrap();
-    sqlitedb::run_migrations(&mut conn).expect("Can't run migrations");
 
     let odoo_ver = config.get_version_odoo();
     let odoo_ver_str = odoo_version_u8_to_string(odoo_ver);
@@ -83,87 +512,126 @@ async fn main() {
     );
     log::info!("Cloning/Updating ({})...", odoo_ver_str);
     let mut repo_infos: Vec<RepoInfo> = Vec::new();
+    // Repos that couldn't be cloned; the rest get theirs after the analysis.
+    let mut run_repos: Vec<RunRepoInfo> = Vec::new();
+    let mut run_error: Option<String> = None;
     if config.get_mode() == "org" {
-        repo_infos = git_client
+        let report = git_client
             .clone_org_repos(
                 config.get_source(),
                 config.get_branch(),
                 config.get_repos_path(),
             )
             .await;
+        run_repos.extend(report.failures.iter().map(clone_failure_run_repo));
+        run_error = report.error;
+        repo_infos = report.repos;
     } else if config.get_mode() == "repo" {
         let Some((user_name, repo_name)) = config.get_source().split_once('/') else {
-            eprintln!(
+            return Err(format!(
                 "Invalid source '{}': repo mode expects '<user>/<repo>'",
                 config.get_source()
-            );
-            std::process::exit(1);
+            ));
         };
         let user_name = user_name.to_string();
         let repo_name = repo_name.to_string();
-        let repo_url = format!("https://github.com/{user_name}/{repo_name}.git");
-        let res_opt = git_client.clone_or_update_repo(
+        let repo_url = config.get_repo_url();
+        match git_client.clone_or_update_repo(
             &user_name,
             &repo_name,
             &repo_url,
             config.get_branch(),
             config.get_repos_path(),
-        );
-        if let Some(res) = res_opt {
-            repo_infos.push(res);
-        } else {
-            log::info!("'{repo_url}' Is not a valid Odoo modules repository!");
+        ) {
+            Ok(res) => repo_infos.push(res),
+            Err(failure) => {
+                log::info!("Can't clone/update '{repo_url}': {}", failure.error);
+                run_repos.push(clone_failure_run_repo(&failure));
+            }
         }
     }
 
-    log::info!("Scanning '{}' repos for migration PRs...", repo_infos.len());
-    for repo_info in &repo_infos {
-        let gh_org = models::gh_organization::add(&mut conn, repo_info.get_org()).unwrap();
-        let gh_repo =
-            models::gh_repository::add(&mut conn, &gh_org.id, repo_info.get_name()).unwrap();
-        let migration_prs = git_client
-            .get_open_migration_pull_requests(repo_info.get_full_path(), config.get_branch())
-            .await;
-        let mut prids: Vec<i64> = Vec::with_capacity(migration_prs.len());
-        for pr in &migration_prs {
-            prids.push(pr.number);
-            models::pull_request::add(
+    for repo_info in repo_infos
+        .iter()
+        .filter(|r| openupgrade::is_openupgrade_repo(r.get_name()))
+    {
+        for (version_odoo, rows) in openupgrade::read_coverage(repo_info.get_clone_path()) {
+            log::info!(
+                "Importing OpenUpgrade coverage for {} ({} modules)...",
+                odoo_version_u8_to_string(&version_odoo),
+                rows.len()
+            );
+            let _ = models::module_upgrade_coverage::replace_for_version(
                 &mut conn,
-                &pr.title,
-                &pr.module_technical_name,
-                &pr.number,
-                odoo_ver,
-                &gh_repo.id,
-                pr.created_at.as_deref(),
-                pr.ci_status.as_deref(),
-                pr.last_message_at.as_deref(),
-            )
-            .unwrap();
-        }
-        let outdated =
-            models::pull_request::find_outdated(&mut conn, &gh_repo.id, odoo_ver, &prids)
-                .unwrap_or_default();
-        let mut merged_prids: Vec<i64> = Vec::with_capacity(outdated.len());
-        for pr in &outdated {
-            if let Some(true) = git_client
-                .is_pull_request_merged(repo_info.get_full_path(), &pr.prid)
-                .await
-            {
-                merged_prids.push(pr.prid);
-            }
+                &version_odoo,
+                &rows,
+            );
         }
-        let _ = models::pull_request::delete_outdated(
-            &mut conn,
-            &gh_repo.id,
-            odoo_ver,
-            &prids,
-            &merged_prids,
-        );
     }
 
+    scan_pull_requests(&mut conn, &git_client, config, &repo_infos).await;
+
     log::info!("Analazyng '{}' repos...", repo_infos.len());
     let analyzer = OGHCollectorAnalyzer::new(odoo_ver);
-    let manifest_infos = analyzer.get_module_info(&mut conn, config.get_read_paths(), &repo_infos);
+    let (manifest_infos, analysis_errors) =
+        analyzer.get_module_info(&mut conn, config.get_read_paths(), &repo_infos);
+    let mut analyzed_repos: HashMap<(String, String), RunRepoInfo> = repo_infos
+        .iter()
+        .map(|repo_info| {
+            (
+                (
+                    repo_info.get_org().to_string(),
+                    repo_info.get_name().to_string(),
+                ),
+                RunRepoInfo {
+                    org_name: repo_info.get_org().to_string(),
+                    repo_name: repo_info.get_name().to_string(),
+                    clone_status: models::collection_run_repo::CLONE_OK.to_string(),
+                    analysis_status: models::collection_run_repo::ANALYSIS_OK.to_string(),
+                    modules_added: 0,
+                    modules_updated: 0,
+                    modules_removed: 0,
+                    error: None,
+                },
+            )
+        })
+        .collect();
+    for repo_info in &repo_infos {
+        let repo_errors = analysis_errors
+            .iter()
+            .filter(|err| err.repo_full_path == repo_info.get_full_path())
+            .collect::<Vec<_>>();
+        if repo_errors.is_empty() {
+            continue;
+        }
+        let unreadable_paths = repo_errors
+            .iter()
+            .filter(|err| err.module_name.is_none())
+            .count();
+        let run_repo = analyzed_repos
+            .get_mut(&(
+                repo_info.get_org().to_string(),
+                repo_info.get_name().to_string(),
+            ))
+            .unwrap();
+        run_repo.analysis_status = if unreadable_paths == config.get_read_paths().len() {
+            models::collection_run_repo::ANALYSIS_FAILED
+        } else {
+            models::collection_run_repo::ANALYSIS_PARTIAL
+        }
+        .to_string();
+        run_repo.error = Some(
+            repo_errors
+                .iter()
+                .map(|err| match &err.module_name {
+                    Some(module_name) => format!("{module_name}: {}", err.message),
+                    None => err.message.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+    let mut run_repo_keys: HashMap<i64, (String, String)> = HashMap::new();
     let manifest_count = &manifest_infos.len();
     if manifest_count.gt(&0) {
         log::info!("Saving '{}' repos info...", manifest_infos.len());
@@ -179,6 +647,16 @@ async fn main() {
             let mut new_module_info = manifest.clone();
             new_module_info.version_odoo = *odoo_ver; // It is forced because some modules do not have this data correctly.
             let new_module = models::module::add(&mut conn, &new_module_info).unwrap();
+            let run_repo_key = (manifest.git_org.clone(), manifest.git_repo.clone());
+            if let Some(run_repo) = analyzed_repos.get_mut(&run_repo_key) {
+                // Same clock and format: created during this run means new.
+                if new_module.create_date >= run.start_date {
+                    run_repo.modules_added += 1;
+                } else if !new_module_info.source_unchanged {
+                    run_repo.modules_updated += 1;
+                }
+            }
+            run_repo_keys.insert(new_module.gh_repository_id, run_repo_key);
             let module_ids = module_ids_by_repo
                 .entry(new_module.gh_repository_id)
                 .or_default();
@@ -193,8 +671,9 @@ async fn main() {
                 // then replace the module's code analysis (views touched, models
                 // defined/extended with their fields and public methods, and
                 // every other record it touches - access groups, record rules,
-                // access rights, ...) scoped to that version, on every run,
-                // independent of whether any manifest field changed. A prior
+                // access rights, ...) and its blame ownership, scoped to that
+                // version, on every run, independent of whether any manifest
+                // field changed. A prior
                 // version's snapshot is left untouched - only its own
                 // module_version_id gets wiped/rebuilt.
                 let module_version = models::module_version::get_or_create(
@@ -231,11 +710,27 @@ async fn main() {
                     &new_module_info.analysis.controllers,
                 )
                 .unwrap();
+                models::module_ownership::replace_for_module(
+                    &mut conn,
+                    &new_module.id,
+                    &module_version.id,
+                    &new_module_info.analysis.ownership,
+                )
+                .unwrap();
+                models::module_version::set_test_files(
+                    &mut conn,
+                    &module_version.id,
+                    new_module_info
+                        .analysis
+                        .test_files
+                        .map(|count| count as i32),
+                )
+                .unwrap();
 
                 // Static security checks over the records and HTTP controllers
-                // just analyzed: grave findings land in module_security_warning
-                // (shown on the module detail page), minor ones only leave a
-                // system_event log line.
+                // just analyzed. Every finding lands in module_security_warning
+                // (grave ones are shown on the module detail page, all of them
+                // on /security); minor ones also leave a system_event log line.
                 let mut sec_warnings = security::analyze_records(&new_module_info.analysis.records);
                 sec_warnings.extend(security::analyze_controllers(
                     &new_module_info.analysis.controllers,
@@ -262,6 +757,10 @@ async fn main() {
                 .unwrap();
             }
 
+            // Full-text index row, rebuilt from the manifest and analysis
+            // just stored.
+            models::module_search::refresh(&mut conn, &new_module.id).unwrap();
+
             // Check Odoo Version
             if manifest.version_odoo.ne(odoo_ver) && manifest.installable {
                 let repo_name =
@@ -502,11 +1001,31 @@ async fn main() {
 
         log::info!("Removing outdated modules info...");
         for (key, value) in module_ids_by_repo {
+            // Collected here from now on, even if it was imported before.
+            models::gh_repository::set_dataset_source(&mut conn, &key, None).unwrap();
             if !value.is_empty() {
-                models::module::delete_outdated(&mut conn, &key, config.get_version_odoo(), &value)
-                    .unwrap();
+                let removed = models::module::delete_outdated(
+                    &mut conn,
+                    &key,
+                    config.get_version_odoo(),
+                    &value,
+                )
+                .unwrap();
+                if let Some(run_repo) = run_repo_keys
+                    .get(&key)
+                    .and_then(|run_repo_key| analyzed_repos.get_mut(run_repo_key))
+                {
+                    run_repo.modules_removed += removed as i32;
+                }
             }
         }
+        if let Err(err) = models::module_lineage::detect_moves(&mut conn, config.get_version_odoo())
+        {
+            log::error!("Can't detect module moves: {err}");
+        }
+        models::atlas_graph::refresh(&mut conn, config.get_version_odoo()).unwrap();
+        models::module_health::refresh(&mut conn, config.get_version_odoo()).unwrap();
+        bots::refresh_committer_flags(&mut conn, &bots::BotRules::load());
         let _ = models::system_event::register_finished_task_collector(
             &mut conn,
             &start_time.elapsed().as_secs().to_string(),
@@ -518,5 +1037,100 @@ async fn main() {
         log::info!("Nothing to do!");
     }
 
-    log::info!("All done. Bye!");
+    run_repos.extend(analyzed_repos.into_values());
+    let mut totals = RunTotalsInfo {
+        repos_count: run_repos.len() as i32,
+        modules_scanned: *manifest_count as i32,
+        api_calls: git_client.get_api_calls() as i64,
+        ..Default::default()
+    };
+    for run_repo in &run_repos {
+        if run_repo.is_failed() {
+            totals.repos_failed += 1;
+        }
+        totals.modules_added += run_repo.modules_added;
+        totals.modules_updated += run_repo.modules_updated;
+        totals.modules_removed += run_repo.modules_removed;
+        let _ = models::collection_run_repo::add(&mut conn, &run.id, run_repo);
+    }
+    if run_error.is_none() {
+        let _ = models::collection_run_security::record(
+            &mut conn,
+            &run.id,
+            &run.org_name,
+            config.get_version_odoo(),
+        );
+    }
+    let _ = models::collection_run::finish(
+        &mut conn,
+        &run.id,
+        &(start_time.elapsed().as_secs() as i64),
+        &totals,
+        run_error.as_deref(),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_job;
+    use sqlitedb::models;
+    use sqlitedb::models::collection_job::JobRequestInfo;
+
+    #[tokio::test]
+    async fn test_run_job_records_failures() {
+        let mut conn = sqlitedb::establish_test_connection();
+        for source in ["OCA/web", "OCA/sale-workflow", "OCA/account-invoicing"] {
+            models::collection_job::enqueue(
+                &mut conn,
+                &JobRequestInfo {
+                    kind: models::collection_job::KIND_COLLECT.to_string(),
+                    source: source.to_string(),
+                    branch: "17.0".to_string(),
+                    git_type: "GH".to_string(),
+                    repo_url: None,
+                },
+            )
+            .unwrap();
+        }
+        let jobs = models::collection_job::get_pending(&mut conn);
+        for job in &jobs {
+            assert!(models::collection_job::claim(&mut conn, &job.id).unwrap());
+        }
+
+        run_job(&mut conn, &jobs[0].id, async { panic!("clone exploded") })
+            .await
+            .unwrap();
+        run_job(&mut conn, &jobs[1].id, async {
+            Err("Invalid source".to_string())
+        })
+        .await
+        .unwrap();
+        run_job(&mut conn, &jobs[2].id, async { Ok(()) })
+            .await
+            .unwrap();
+
+        let status = |conn: &mut sqlitedb::DbConnection, id: &i64| {
+            let job = models::collection_job::get_by_id(conn, id).unwrap();
+            (job.status, job.error)
+        };
+        assert_eq!(
+            status(&mut conn, &jobs[0].id),
+            (
+                models::collection_job::STATUS_FAILED.to_string(),
+                Some("clone exploded".to_string())
+            )
+        );
+        assert_eq!(
+            status(&mut conn, &jobs[1].id),
+            (
+                models::collection_job::STATUS_FAILED.to_string(),
+                Some("Invalid source".to_string())
+            )
+        );
+        assert_eq!(
+            status(&mut conn, &jobs[2].id),
+            (models::collection_job::STATUS_DONE.to_string(), None)
+        );
+    }
 }
@@ -0,0 +1,136 @@
+// Copyright Alexandre D. Díaz
+//! `oghcollector maintenance`: applies the retention policies, then compacts
+//! the database and reports what it gave back.
+use chrono::{Duration, Utc};
+use diesel::Connection;
+
+use sqlitedb::models;
+use sqlitedb::utils::date::to_sqlite_datetime;
+use sqlitedb::DbConnection;
+
+use crate::daemon::env_number;
+
+/// How long things are kept. A zero disables that policy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetentionPolicy {
+    /// Days of info and success system events.
+    pub info_days: i64,
+    /// Days of warning and error system events.
+    pub warning_days: i64,
+    /// Newest analysis snapshots kept per module (the current one always is).
+    pub snapshots: usize,
+}
+
+impl RetentionPolicy {
+    pub fn from_env() -> RetentionPolicy {
+        RetentionPolicy {
+            info_days: env_number("OGHCOLLECTOR_RETENTION_INFO_DAYS", 90),
+            warning_days: env_number("OGHCOLLECTOR_RETENTION_WARNING_DAYS", 365),
+            snapshots: env_number("OGHCOLLECTOR_RETENTION_SNAPSHOTS", 5),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MaintenanceSummary {
+    pub info_events: usize,
+    pub warning_events: usize,
+    pub snapshots: usize,
+    pub size_before: i64,
+    pub size_after: i64,
+}
+
+impl MaintenanceSummary {
+    pub fn reclaimed(&self) -> i64 {
+        (self.size_before - self.size_after).max(0)
+    }
+}
+
+fn days_ago(days: i64) -> String {
+    to_sqlite_datetime(Utc::now() - Duration::days(days))
+}
+
+/// Deletes what `policy` no longer keeps, in one transaction.
+pub fn apply_retention(
+    conn: &mut DbConnection,
+    policy: &RetentionPolicy,
+) -> diesel::QueryResult<MaintenanceSummary> {
+    conn.transaction(|conn| {
+        let mut summary = MaintenanceSummary::default();
+        if policy.info_days > 0 {
+            summary.info_events =
+                models::system_event::delete_info_before(conn, &days_ago(policy.info_days))?;
+        }
+        if policy.warning_days > 0 {
+            summary.warning_events =
+                models::system_event::delete_warnings_before(conn, &days_ago(policy.warning_days))?;
+        }
+        if policy.snapshots > 0 {
+            summary.snapshots = models::module_version::prune_snapshots(conn, policy.snapshots)?;
+        }
+        Ok(summary)
+    })
+}
+
+/// `apply_retention`, then a vacuum, measuring the database around both.
+/// Needs a connection outside any transaction, for the vacuum.
+pub fn run(
+    conn: &mut DbConnection,
+    policy: &RetentionPolicy,
+) -> diesel::QueryResult<MaintenanceSummary> {
+    let size_before = sqlitedb::maintenance::database_size(conn)?;
+    let summary = apply_retention(conn, policy)?;
+    sqlitedb::maintenance::vacuum(conn)?;
+    Ok(MaintenanceSummary {
+        size_before,
+        size_after: sqlitedb::maintenance::database_size(conn)?,
+        ..summary
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_retention, days_ago, RetentionPolicy};
+    use diesel::prelude::*;
+    use sqlitedb::models;
+    use sqlitedb::schema::system_event;
+
+    fn add_event(conn: &mut sqlitedb::DbConnection, severity: &str, age_days: i64) {
+        let event = models::system_event::add(conn, "internal", severity, "event").unwrap();
+        diesel::update(system_event::table.filter(system_event::id.eq(event.id)))
+            .set(system_event::date.eq(days_ago(age_days)))
+            .execute(conn)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_apply_retention() {
+        let mut conn = sqlitedb::establish_test_connection();
+        add_event(&mut conn, "info", 10);
+        add_event(&mut conn, "info", 100);
+        add_event(&mut conn, "error", 100);
+        add_event(&mut conn, "error", 400);
+
+        let policy = RetentionPolicy {
+            info_days: 90,
+            warning_days: 365,
+            snapshots: 5,
+        };
+        let summary = apply_retention(&mut conn, &policy).unwrap();
+        assert_eq!((summary.info_events, summary.warning_events), (1, 1));
+        assert_eq!(
+            models::system_event::get_messages_page(&mut conn, i64::MAX, None, None, 10).len(),
+            2
+        );
+
+        // Zero keeps everything.
+        let keep_all = RetentionPolicy {
+            info_days: 0,
+            warning_days: 0,
+            snapshots: 0,
+        };
+        add_event(&mut conn, "info", 1000);
+        let summary = apply_retention(&mut conn, &keep_all).unwrap();
+        assert_eq!((summary.info_events, summary.warning_events), (0, 0));
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright Alexandre D. Díaz
+use prometheus::{Encoder, IntGaugeVec, Opts, Registry, TextEncoder};
+use std::env;
+use std::fs;
+
+use sqlitedb::models;
+use sqlitedb::DbConnection;
+
+const LABELS: &[&str] = &["org", "version", "forge"];
+
+fn gauge(registry: &Registry, name: &str, help: &str, labels: &[&str]) -> IntGaugeVec {
+    let gauge = IntGaugeVec::new(Opts::new(name, help).namespace("oghcollector"), labels)
+        .expect("Invalid collector metric");
+    registry
+        .register(Box::new(gauge.clone()))
+        .expect("Duplicated collector metric");
+    gauge
+}
+
+fn end_timestamp(run: &models::collection_run::Model) -> i64 {
+    run.end_date
+        .as_deref()
+        .and_then(|date| chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S").ok())
+        .map(|date| date.and_utc().timestamp())
+        .unwrap_or_default()
+}
+
+/// Renders the last finished run of every organization and version in the
+/// Prometheus text format. Built from `collection_run` rather than from the
+/// current process, so each collector rewrites the whole picture and runs of
+/// other organizations don't vanish from the file.
+pub fn render(runs: &[models::collection_run::Model]) -> String {
+    let registry = Registry::new();
+    let timestamp = gauge(
+        &registry,
+        "run_timestamp_seconds",
+        "When the last run finished, as a Unix timestamp.",
+        LABELS,
+    );
+    let success = gauge(
+        &registry,
+        "run_success",
+        "Whether the last run finished without error.",
+        LABELS,
+    );
+    let duration = gauge(
+        &registry,
+        "run_duration_seconds",
+        "Duration of the last run.",
+        LABELS,
+    );
+    let repos = gauge(
+        &registry,
+        "run_repos",
+        "Repositories handled by the last run.",
+        LABELS,
+    );
+    let repos_failed = gauge(
+        &registry,
+        "run_repos_failed",
+        "Repositories the last run couldn't clone or analyze.",
+        LABELS,
+    );
+    let modules_scanned = gauge(
+        &registry,
+        "run_modules_scanned",
+        "Module manifests analyzed by the last run.",
+        LABELS,
+    );
+    let modules_changed = gauge(
+        &registry,
+        "run_modules_changed",
+        "Modules added, updated or removed by the last run.",
+        &["org", "version", "forge", "change"],
+    );
+    let api_calls = gauge(
+        &registry,
+        "run_api_calls",
+        "Forge API calls made by the last run.",
+        LABELS,
+    );
+    for run in runs {
+        let version = u8::try_from(run.version_odoo)
+            .map(|v| oghutils::version::odoo_version_u8_to_string(&v))
+            .unwrap_or_default();
+        let labels = [run.org_name.as_str(), version.as_str(), run.forge.as_str()];
+        timestamp.with_label_values(&labels).set(end_timestamp(run));
+        success
+            .with_label_values(&labels)
+            .set((run.status == models::collection_run::STATUS_DONE) as i64);
+        duration
+            .with_label_values(&labels)
+            .set(run.duration.unwrap_or_default());
+        repos.with_label_values(&labels).set(run.repos_count as i64);
+        repos_failed
+            .with_label_values(&labels)
+            .set(run.repos_failed as i64);
+        modules_scanned
+            .with_label_values(&labels)
+            .set(run.modules_scanned as i64);
+        for (change, count) in [
+            ("added", run.modules_added),
+            ("updated", run.modules_updated),
+            ("removed", run.modules_removed),
+        ] {
+            modules_changed
+                .with_label_values(&[labels[0], labels[1], labels[2], change])
+                .set(count as i64);
+        }
+        api_calls.with_label_values(&labels).set(run.api_calls);
+    }
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&registry.gather(), &mut buffer)
+        .expect("Can't encode the collector metrics");
+    String::from_utf8(buffer).unwrap_or_default()
+}
+
+/// Writes the metrics for node_exporter's textfile collector when
+/// `OGHCOLLECTOR_METRICS_TEXTFILE` is set. Written aside and renamed, so the
+/// exporter never reads half a file.
+pub fn write_textfile(conn: &mut DbConnection) {
+    let Ok(path) = env::var("OGHCOLLECTOR_METRICS_TEXTFILE") else {
+        return;
+    };
+    let metrics = render(&models::collection_run::get_last_finished(conn));
+    let tmp_path = format!("{path}.{}.tmp", std::process::id());
+    if let Err(err) = fs::write(&tmp_path, metrics).and_then(|_| fs::rename(&tmp_path, &path)) {
+        log::warn!("Can't write the metrics textfile '{path}': {err}");
+        let _ = fs::remove_file(&tmp_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let run = models::collection_run::Model {
+            id: 1,
+            source: "OCA".to_string(),
+            org_name: "OCA".to_string(),
+            version_odoo: 180,
+            forge: "github".to_string(),
+            trigger: models::collection_run::TRIGGER_SCHEDULE.to_string(),
+            status: models::collection_run::STATUS_DONE.to_string(),
+            start_date: "2026-01-01 00:00:00".to_string(),
+            end_date: Some("2026-01-01 00:01:40".to_string()),
+            duration: Some(100),
+            repos_count: 3,
+            repos_failed: 1,
+            modules_added: 2,
+            modules_updated: 5,
+            modules_removed: 0,
+            api_calls: 42,
+            error: None,
+            modules_scanned: 30,
+            security_errors: Some(2),
+            security_warnings: Some(7),
+        };
+        let metrics = render(&[run]);
+        let labels = r#"forge="github",org="OCA",version="18.0""#;
+        for line in [
+            format!("oghcollector_run_timestamp_seconds{{{labels}}} 1767225700"),
+            format!("oghcollector_run_success{{{labels}}} 1"),
+            format!("oghcollector_run_duration_seconds{{{labels}}} 100"),
+            format!("oghcollector_run_repos_failed{{{labels}}} 1"),
+            format!("oghcollector_run_modules_scanned{{{labels}}} 30"),
+            format!(r#"oghcollector_run_modules_changed{{change="updated",{labels}}} 5"#),
+            format!("oghcollector_run_api_calls{{{labels}}} 42"),
+        ] {
+            assert!(metrics.lines().any(|l| l == line), "missing {line}");
+        }
+    }
+}
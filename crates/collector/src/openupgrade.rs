@@ -0,0 +1,168 @@
+// Copyright Alexandre D. Díaz
+//! Reader for OpenUpgrade's module coverage tables
+//! (`docsource/modules<from>-<to>.rst` in an OCA/OpenUpgrade checkout), RST
+//! grid tables with one row per module of the target Odoo version.
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+use sqlitedb::models::module_upgrade_coverage::UpgradeCoverageInfo;
+
+const OPENUPGRADE_REPO_NAME: &str = "OpenUpgrade";
+
+pub fn is_openupgrade_repo(repo_name: &str) -> bool {
+    repo_name == OPENUPGRADE_REPO_NAME
+}
+
+/// Target version of a coverage file, in the same u8 format as
+/// `version_odoo` (`modules160-170.rst` -> 170).
+fn coverage_target_version(file_name: &str) -> Option<u8> {
+    let re = Regex::new(r"^modules_?\d+-(\d+)\.rst$").unwrap();
+    re.captures(file_name)?[1].parse::<u8>().ok()
+}
+
+fn append_cell(target: &mut String, text: &str) {
+    let text = text.trim();
+    if text.is_empty() {
+        return;
+    }
+    if !target.is_empty() {
+        target.push(' ');
+    }
+    target.push_str(text);
+}
+
+/// Rows of a coverage table. A row can span several lines until the next
+/// `+---` separator, its cells continuing on each line; the `|new|`/`|del|`
+/// substitutions in front of the module name flag modules added/removed in
+/// the target version.
+pub fn parse_coverage_rst(content: &str) -> Vec<UpgradeCoverageInfo> {
+    let mut rows: Vec<UpgradeCoverageInfo> = Vec::new();
+    let mut in_body = false;
+    let mut row_open = false;
+    for line in content.lines() {
+        let line = line.trim_end();
+        if line.starts_with("+=") {
+            in_body = true;
+            continue;
+        }
+        if !in_body {
+            continue;
+        }
+        if line.starts_with('+') {
+            row_open = false;
+            continue;
+        }
+        let Some(mut rest) = line.strip_prefix('|') else {
+            continue;
+        };
+        let mut is_new = false;
+        let mut is_removed = false;
+        if let Some(r) = rest.trim_start().strip_prefix("|new|") {
+            is_new = true;
+            rest = r;
+        } else if let Some(r) = rest.trim_start().strip_prefix("|del|") {
+            is_removed = true;
+            rest = r;
+        }
+        let cells: Vec<&str> = rest.split('|').collect();
+        let module_cell = cells.first().map_or("", |c| c.trim());
+        let status_cell = cells.get(1).copied().unwrap_or("");
+        let note_cell = cells.get(2).copied().unwrap_or("");
+        if row_open {
+            if let Some(last) = rows.last_mut() {
+                append_cell(&mut last.status, status_cell);
+                let mut note = last.note.take().unwrap_or_default();
+                append_cell(&mut note, note_cell);
+                last.note = (!note.is_empty()).then_some(note);
+            }
+            continue;
+        }
+        let mut note = String::new();
+        append_cell(&mut note, note_cell);
+        rows.push(UpgradeCoverageInfo {
+            technical_name: module_cell.to_string(),
+            status: status_cell.trim().to_string(),
+            note: (!note.is_empty()).then_some(note),
+            is_new,
+            is_removed,
+        });
+        row_open = true;
+    }
+    rows
+}
+
+/// Every coverage table found in an OpenUpgrade checkout, by target version.
+pub fn read_coverage(clone_path: &str) -> Vec<(u8, Vec<UpgradeCoverageInfo>)> {
+    let Ok(entries) = fs::read_dir(Path::new(clone_path).join("docsource")) else {
+        return Vec::new();
+    };
+    let mut coverage = Vec::new();
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some(version_odoo) = coverage_target_version(&file_name) else {
+            continue;
+        };
+        match fs::read_to_string(entry.path()) {
+            Ok(content) => coverage.push((version_odoo, parse_coverage_rst(&content))),
+            Err(err) => log::warn!("Can't read '{file_name}': {err}"),
+        }
+    }
+    coverage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{coverage_target_version, parse_coverage_rst};
+
+    #[test]
+    fn test_coverage_target_version() {
+        assert_eq!(coverage_target_version("modules160-170.rst"), Some(170));
+        assert_eq!(coverage_target_version("modules90-100.rst"), Some(100));
+        assert_eq!(coverage_target_version("modules_80-90.rst"), Some(90));
+        assert_eq!(coverage_target_version("coverage_legend.rst"), None);
+    }
+
+    #[test]
+    fn test_parse_coverage_rst() {
+        let content = "\
+Module coverage 16.0 -> 17.0
+============================
+
+.. include:: coverage_legend.rst
+
++-----------------------------+----------------------+-----------------------------------+
+| Module                      | Status               + Extra Information                 |
++=============================+======================+===================================+
+| account                     | Done                 |                                   |
++-----------------------------+----------------------+-----------------------------------+
+| |new| account_audit_trail   |                      |                                   |
++-----------------------------+----------------------+-----------------------------------+
+| |del| account_edi_facturx   |                      |Merged into account_edi_ubl_cii.   |
++-----------------------------+----------------------+-----------------------------------+
+| sale                        |Partial               |pricelists still                   |
+|                             |                      |pending                            |
++-----------------------------+----------------------+-----------------------------------+
+| website_sale                |                      |                                   |
++-----------------------------+----------------------+-----------------------------------+
+";
+        let rows = parse_coverage_rst(content);
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[0].technical_name, "account");
+        assert_eq!(rows[0].status, "Done");
+        assert!(!rows[0].is_new && !rows[0].is_removed);
+        assert_eq!(rows[1].technical_name, "account_audit_trail");
+        assert!(rows[1].is_new);
+        assert_eq!(rows[2].technical_name, "account_edi_facturx");
+        assert!(rows[2].is_removed);
+        assert_eq!(rows[2].status, "");
+        assert_eq!(
+            rows[2].note.as_deref(),
+            Some("Merged into account_edi_ubl_cii.")
+        );
+        assert_eq!(rows[3].status, "Partial");
+        assert_eq!(rows[3].note.as_deref(), Some("pricelists still pending"));
+        assert_eq!(rows[4].status, "");
+        assert_eq!(rows[4].note, None);
+    }
+}
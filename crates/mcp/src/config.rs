@@ -9,6 +9,7 @@ use lazy_static::lazy_static;
 #[derive(Debug)]
 pub struct OGHMcpConfig {
     cache_ttl: u64,
+    metrics_enabled: bool,
 }
 
 impl OGHMcpConfig {
@@ -20,12 +21,20 @@ impl OGHMcpConfig {
             .unwrap();
 
         let cache_ttl = settings.get_int("cache_ttl").unwrap_or(3600) as u64;
-        OGHMcpConfig { cache_ttl }
+        let metrics_enabled = settings.get_bool("metrics_enabled").unwrap_or(false);
+        OGHMcpConfig {
+            cache_ttl,
+            metrics_enabled,
+        }
     }
 
     pub fn get_cache_ttl(&self) -> &u64 {
         &self.cache_ttl
     }
+
+    pub fn get_metrics_enabled(&self) -> bool {
+        self.metrics_enabled
+    }
 }
 
 lazy_static! {
@@ -1,10 +1,11 @@
 // Copyright Alexandre D. Díaz
 mod config;
+mod metrics;
 mod tools;
 
 use std::path::PathBuf;
 
-use axum::Router;
+use axum::{routing::get, Router};
 use rmcp::transport::streamable_http_server::{
     session::local::LocalSessionManager, StreamableHttpServerConfig, StreamableHttpService,
 };
@@ -64,9 +65,15 @@ fn resolve_allowed_hosts() -> Vec<String> {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
 
-    let db_path = resolve_db_path();
-    log::info!("using SQLite DB at {}", db_path.display());
-    let pool = sqlitedb::new_read_pool(&db_path.to_string_lossy(), 4);
+    let database_url = if cfg!(feature = "postgres") {
+        log::info!("using PostgreSQL DB from OGHCOLLECTOR_DATABASE_URL");
+        sqlitedb::database_url("")?
+    } else {
+        let db_path = resolve_db_path();
+        log::info!("using SQLite DB at {}", db_path.display());
+        db_path.to_string_lossy().into_owned()
+    };
+    let pool = sqlitedb::new_read_pool(&database_url, 4);
 
     // Touch MCP_CONFIG now so a malformed mcp.yaml fails at startup rather than on the
     // first get_module call, and so the effective TTL is visible in the logs.
@@ -82,7 +89,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         StreamableHttpServerConfig::default().with_allowed_hosts(allowed_hosts),
     );
 
-    let router = Router::new().nest_service("/mcp", service);
+    let mut router = Router::new().nest_service("/mcp", service);
+    if config::MCP_CONFIG.get_metrics_enabled() {
+        router = router.route("/metrics", get(metrics::route));
+    }
     let bind_addr = resolve_bind_addr();
     let listener = tokio::net::TcpListener::bind(&bind_addr).await?;
     log::info!("MCP endpoint listening on http://{bind_addr}/mcp");
@@ -0,0 +1,60 @@
+// Copyright Alexandre D. Díaz
+use axum::http::header;
+use axum::response::IntoResponse;
+use lazy_static::lazy_static;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
+};
+use std::time::Duration;
+
+lazy_static! {
+    static ref REGISTRY: Registry = Registry::new_custom(Some("oghmcp".to_string()), None).unwrap();
+    static ref TOOL_CALLS: IntCounterVec = register(
+        IntCounterVec::new(
+            Opts::new("tool_calls_total", "Tool calls by tool name and outcome."),
+            &["tool", "outcome"],
+        )
+        .unwrap()
+    );
+    static ref TOOL_CALL_DURATION: HistogramVec = register(
+        HistogramVec::new(
+            HistogramOpts::new(
+                "tool_call_duration_seconds",
+                "Tool call latency by tool name."
+            ),
+            &["tool"],
+        )
+        .unwrap()
+    );
+}
+
+fn register<T: prometheus::core::Collector + Clone + 'static>(collector: T) -> T {
+    REGISTRY
+        .register(Box::new(collector.clone()))
+        .expect("Duplicated MCP metric");
+    collector
+}
+
+/// `failed` covers both protocol errors (unknown tool, bad arguments) and
+/// tool results flagged `is_error`.
+pub fn observe_tool_call(tool: &str, failed: bool, elapsed: Duration) {
+    let outcome = if failed { "error" } else { "ok" };
+    TOOL_CALLS.with_label_values(&[tool, outcome]).inc();
+    TOOL_CALL_DURATION
+        .with_label_values(&[tool])
+        .observe(elapsed.as_secs_f64());
+}
+
+pub async fn route() -> impl IntoResponse {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&REGISTRY.gather(), &mut buffer)
+        .expect("Can't encode the MCP metrics");
+    (
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        buffer,
+    )
+}
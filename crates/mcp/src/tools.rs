@@ -1,8 +1,8 @@
 // Copyright Alexandre D. Díaz
 use std::collections::HashMap;
+use std::time::Instant;
 
 use cached::{proc_macro::cached, stores::TimedSizedCache};
-use diesel::sqlite::SqliteConnection;
 use rmcp::{
     handler::server::wrapper::Parameters,
     model::{
@@ -12,13 +12,16 @@ use rmcp::{
     schemars, tool, tool_handler, tool_router, ErrorData as McpError, ServerHandler,
 };
 use serde::{Deserialize, Serialize};
+use sqlitedb::DbConnection;
 
 use oghutils::version::{odoo_version_string_to_u8, odoo_version_u8_to_string};
 use sqlitedb::{models, Pool};
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct SearchModulesParams {
-    /// Substring to match against a module's technical name.
+    /// Words matched against a module's technical name, each as the start of
+    /// a name part and case-insensitively: "sale type" finds
+    /// sale_order_type. Best matches come first.
     pub name: String,
     /// Restrict to a specific Odoo version, e.g. "17.0".
     pub odoo_version: Option<String>,
@@ -36,8 +39,10 @@ pub struct ModuleSearchResult {
 pub struct ListModulesByCriteriaParams {
     /// Odoo version, e.g. "17.0".
     pub odoo_version: String,
-    /// Substring matched against technical name, display name, or
-    /// description (SQL LIKE, case-sensitive).
+    /// Full-text search, case-insensitive, over technical name, display
+    /// name, summary, description, install/usage docs and the names of the
+    /// module's models, fields and views. Every word must match (as a word
+    /// prefix); results are ranked best match first.
     pub search_term: Option<String>,
     /// Exact manifest `category`, e.g. "Sales", "Accounting", "Localization".
     pub category: Option<String>,
@@ -45,6 +50,11 @@ pub struct ListModulesByCriteriaParams {
     /// declare it as an Odoo dependency. Reverse lookup - answers "what \
     /// depends on X" / "what would break if X were removed".
     pub depends_on: Option<String>,
+    /// Only modules whose health score (0-100, see get_module_health) is at
+    /// least this.
+    pub min_health: Option<i32>,
+    /// Healthiest first instead of best match first.
+    pub sort_by_health: Option<bool>,
     /// Max rows to return. Defaults to 50, capped at 200.
     pub limit: Option<u32>,
 }
@@ -59,6 +69,11 @@ pub struct ModuleCriteriaResult {
     pub application: bool,
     pub organization: String,
     pub repository: String,
+    /// What matched `search_term`, as HTML with the words in `<mark>`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    /// Composite health score out of 100; get_module_health breaks it down.
+    pub health: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -142,7 +157,8 @@ pub struct ModuleRecord {
 /// for website routes), or None for a pure override of an inherited route.
 /// `csrf: None` means the framework default (enabled). `uses_sudo` flags any
 /// `.sudo()` call inside the handler - on a public/none route that means
-/// privileged code reachable without login.
+/// privileged code reachable without login, unless `checks_token_access`
+/// (the handler validates a portal access token itself).
 #[derive(Debug, Clone, Serialize)]
 pub struct ModuleController {
     pub class_name: String,
@@ -154,6 +170,7 @@ pub struct ModuleController {
     pub csrf: Option<bool>,
     pub website: bool,
     pub uses_sudo: bool,
+    pub checks_token_access: bool,
     pub signature: String,
     pub docstring: Option<String>,
 }
@@ -310,11 +327,42 @@ pub struct ModuleSummary {
     pub depends: ModuleDependencies,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetDependencyDiagramParams {
+    /// Module technical names, e.g. ["sale_order_type"]; several draw the
+    /// closure of the whole set in one diagram.
+    pub technical_names: Vec<String>,
+    /// Odoo version, e.g. "17.0".
+    pub odoo_version: String,
+    /// Also draw the Python packages and system binaries the modules need.
+    /// Defaults to false.
+    pub include_external: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetModelUsageParams {
+    /// Odoo model name (its `_name`), e.g. "res.partner".
+    pub model: String,
+    /// Odoo version, e.g. "17.0".
+    pub odoo_version: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SearchModelFieldsParams {
+    /// Field name or part of it, case-insensitive, e.g. "vat_number".
+    pub name: String,
+    /// Restrict to a specific Odoo version, e.g. "17.0"; every version
+    /// otherwise.
+    pub odoo_version: Option<String>,
+    /// Maximum number of fields returned (default 50, capped at 200).
+    pub limit: Option<u32>,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct GetCommitterActivityParams {
-    /// Exact committer (git author) name, e.g. "Jane Doe". This project only
-    /// tracks git commit author names, not emails or GitHub logins - get real
-    /// names from the `committers` field of list_repository_modules.
+    /// Exact committer (git author) name, e.g. "Jane Doe", as resolved
+    /// through .mailmap; not an email or GitHub login - get real names from
+    /// the `committers` field of list_repository_modules.
     pub name: String,
 }
 
@@ -328,6 +376,39 @@ pub struct CommitterActivityEntry {
     pub commits: i32,
     pub insertions: i32,
     pub deletions: i32,
+    /// Lines of the module's current tree this committer last touched (git
+    /// blame) and their fraction of all its lines; `None` when they own none.
+    pub owned_lines: Option<i32>,
+    pub ownership_share: Option<f64>,
+}
+
+/// An open PR/MR changing a module's folder - not only migration PRs, but
+/// fixes and improvements too. `review_count` is the number of submitted
+/// reviews (GitLab: discussion notes), `approval_count` the number of
+/// distinct approvers; `modules` lists every module folder the PR touches.
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenPullRequestEntry {
+    pub prid: i64,
+    pub title: String,
+    pub url: String,
+    pub author: Option<String>,
+    pub is_draft: bool,
+    pub labels: Vec<String>,
+    pub review_count: i32,
+    pub approval_count: i32,
+    pub ci_status: Option<String>,
+    pub created_at: Option<String>,
+    pub last_activity_at: Option<String>,
+    pub modules: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleOpenPullRequests {
+    pub technical_name: String,
+    pub odoo_version: String,
+    pub organization: String,
+    pub repository: String,
+    pub pull_requests: Vec<OpenPullRequestEntry>,
 }
 
 fn build_search_results(rows: Vec<models::module::ModuleGenericInfo>) -> Vec<ModuleSearchResult> {
@@ -349,7 +430,7 @@ fn build_search_results(rows: Vec<models::module::ModuleGenericInfo>) -> Vec<Mod
         .collect()
 }
 
-fn get_module_views(conn: &mut SqliteConnection, module_version_id: &i64) -> Vec<ModuleView> {
+fn get_module_views(conn: &mut DbConnection, module_version_id: &i64) -> Vec<ModuleView> {
     models::module_view::get_by_module_version_id(conn, module_version_id)
         .into_iter()
         .map(|v| ModuleView {
@@ -363,7 +444,7 @@ fn get_module_views(conn: &mut SqliteConnection, module_version_id: &i64) -> Vec
         .collect()
 }
 
-fn get_module_records(conn: &mut SqliteConnection, module_version_id: &i64) -> Vec<ModuleRecord> {
+fn get_module_records(conn: &mut DbConnection, module_version_id: &i64) -> Vec<ModuleRecord> {
     models::module_record::get_by_module_version_id(conn, module_version_id)
         .into_iter()
         .map(|r| {
@@ -379,7 +460,7 @@ fn get_module_records(conn: &mut SqliteConnection, module_version_id: &i64) -> V
 }
 
 fn get_module_controllers(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_version_id: &i64,
 ) -> Vec<ModuleController> {
     models::module_controller::get_by_module_version_id(conn, module_version_id)
@@ -394,13 +475,14 @@ fn get_module_controllers(
             csrf: c.csrf,
             website: c.website,
             uses_sudo: c.uses_sudo,
+            checks_token_access: c.checks_token_access,
             signature: c.signature,
             docstring: c.docstring,
         })
         .collect()
 }
 
-fn get_module_models(conn: &mut SqliteConnection, module_version_id: &i64) -> Vec<ModuleModel> {
+fn get_module_models(conn: &mut DbConnection, module_version_id: &i64) -> Vec<ModuleModel> {
     models::module_model::get_by_module_version_id(conn, module_version_id)
         .into_iter()
         .map(|m| {
@@ -441,7 +523,7 @@ fn get_module_models(conn: &mut SqliteConnection, module_version_id: &i64) -> Ve
 }
 
 fn get_org_repo(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module: &models::module::Model,
 ) -> (models::gh_organization::Model, models::gh_repository::Model) {
     let repo = models::gh_repository::get_by_id(conn, &module.gh_repository_id)
@@ -451,7 +533,7 @@ fn get_org_repo(
     (org, repo)
 }
 
-fn build_module_info(conn: &mut SqliteConnection, module: &models::module::Model) -> ModuleInfo {
+fn build_module_info(conn: &mut DbConnection, module: &models::module::Model) -> ModuleInfo {
     let (org, repo) = get_org_repo(conn, module);
     ModuleInfo {
         technical_name: module.technical_name.clone(),
@@ -474,7 +556,7 @@ fn build_module_info(conn: &mut SqliteConnection, module: &models::module::Model
     }
 }
 
-fn build_module_docs(conn: &mut SqliteConnection, module: &models::module::Model) -> ModuleDocs {
+fn build_module_docs(conn: &mut DbConnection, module: &models::module::Model) -> ModuleDocs {
     let (org, repo) = get_org_repo(conn, module);
     ModuleDocs {
         technical_name: module.technical_name.clone(),
@@ -487,7 +569,7 @@ fn build_module_docs(conn: &mut SqliteConnection, module: &models::module::Model
 }
 
 fn build_module_dependency_info(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module: &models::module::Model,
 ) -> ModuleDependencyInfo {
     let (org, repo) = get_org_repo(conn, module);
@@ -506,7 +588,7 @@ fn build_module_dependency_info(
 }
 
 fn build_module_code_analysis(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module: &models::module::Model,
     version_module: Option<&str>,
 ) -> ModuleCodeAnalysis {
@@ -550,8 +632,47 @@ fn build_module_code_analysis(
     }
 }
 
+fn build_module_open_pull_requests(
+    conn: &mut DbConnection,
+    module: &models::module::Model,
+) -> ModuleOpenPullRequests {
+    let (org, repo) = get_org_repo(conn, module);
+    let pull_requests = models::open_pull_request::get_by_technical_name_organization_name(
+        conn,
+        &module.technical_name,
+        &org.name,
+        Some(&(module.version_odoo as u8)),
+    )
+    .into_iter()
+    .map(|pr| OpenPullRequestEntry {
+        url: format!(
+            "https://github.com/{}/{}/pull/{}",
+            pr.org_name, pr.repository_name, pr.prid
+        ),
+        labels: pr.labels_vec(),
+        modules: models::open_pull_request::get_module_names(conn, &pr.id),
+        prid: pr.prid,
+        title: pr.name,
+        author: pr.author,
+        is_draft: pr.is_draft,
+        review_count: pr.review_count,
+        approval_count: pr.approval_count,
+        ci_status: pr.ci_status,
+        created_at: pr.created_at,
+        last_activity_at: pr.last_message_at,
+    })
+    .collect();
+    ModuleOpenPullRequests {
+        technical_name: module.technical_name.clone(),
+        odoo_version: odoo_version_u8_to_string(&(module.version_odoo as u8)),
+        organization: org.name,
+        repository: repo.name,
+        pull_requests,
+    }
+}
+
 fn find_modules(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     technical_name: &str,
     version_odoo: &u8,
     org: &Option<String>,
@@ -618,7 +739,7 @@ fn build_repository_search_results(
 }
 
 fn build_module_direct_dependencies(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_id: &i64,
 ) -> ModuleDependencies {
     let mut odoo: HashMap<String, Vec<String>> = HashMap::new();
@@ -634,10 +755,7 @@ fn build_module_direct_dependencies(
     }
 }
 
-fn build_module_summary(
-    conn: &mut SqliteConnection,
-    module: &models::module::Model,
-) -> ModuleSummary {
+fn build_module_summary(conn: &mut DbConnection, module: &models::module::Model) -> ModuleSummary {
     ModuleSummary {
         technical_name: module.technical_name.clone(),
         name: module.name.clone(),
@@ -814,42 +932,129 @@ fn get_module_code_analysis_cached(
             TimedSizedCache::with_size_and_lifespan_and_refresh(500, ttl_secs, true)
         }
     "#,
-    convert = r#"{ format!("{odoo_version}|{search_term:?}|{category:?}|{depends_on:?}|{limit}") }"#
+    convert = r#"{ format!("{params:?}|{limit}") }"#
 )]
 fn list_modules_by_criteria_cached(
     pool: Pool,
-    odoo_version: String,
-    search_term: Option<String>,
-    category: Option<String>,
-    depends_on: Option<String>,
+    params: ListModulesByCriteriaParams,
     limit: i64,
 ) -> Vec<ModuleCriteriaResult> {
     let mut conn = pool
         .get()
         .expect("failed to get a DB connection from the pool");
-    let version_odoo = odoo_version_string_to_u8(&odoo_version);
+    let version_odoo = odoo_version_string_to_u8(&params.odoo_version);
+    let sort = if params.sort_by_health.unwrap_or(false) {
+        models::module_health::HealthSort::Health
+    } else {
+        models::module_health::HealthSort::Relevance
+    };
     models::module::search_by_criteria(
         &mut conn,
         &version_odoo,
-        search_term.as_deref(),
-        category.as_deref(),
-        depends_on.as_deref(),
+        params.search_term.as_deref(),
+        params.category.as_deref(),
+        params.depends_on.as_deref(),
+        params.min_health,
+        sort,
         limit,
     )
     .into_iter()
     .map(|m| ModuleCriteriaResult {
+        health: m.health,
         technical_name: m.technical_name,
         name: m.name,
-        odoo_version: odoo_version.clone(),
+        odoo_version: params.odoo_version.clone(),
         category: m.category.unwrap_or_default(),
         installable: m.installable,
         application: m.application,
         organization: m.organization,
         repository: m.repository,
+        snippet: m.snippet,
     })
     .collect()
 }
 
+#[cached(
+    type = "TimedSizedCache<String, Option<String>>",
+    key = "String",
+    create = r#"
+        {
+            let ttl_secs = *crate::config::MCP_CONFIG.get_cache_ttl();
+            TimedSizedCache::with_size_and_lifespan_and_refresh(200, ttl_secs, true)
+        }
+    "#,
+    convert = r#"{ format!("{technical_names:?}|{odoo_version}|{include_external}") }"#
+)]
+fn get_dependency_diagram_cached(
+    pool: Pool,
+    technical_names: Vec<String>,
+    odoo_version: String,
+    include_external: bool,
+) -> Option<String> {
+    let mut conn = pool
+        .get()
+        .expect("failed to get a DB connection from the pool");
+    let version_odoo = odoo_version_string_to_u8(&odoo_version);
+    let graph = models::atlas_graph::closure(&mut conn, &version_odoo, &technical_names);
+    if graph.nodes.is_empty() {
+        return None;
+    }
+    let graph = if include_external {
+        graph
+    } else {
+        graph.without_external()
+    };
+    let title = format!("{} (Odoo {odoo_version})", technical_names.join(", "));
+    Some(models::atlas_export::to_mermaid(&graph, &title))
+}
+
+#[cached(
+    type = "TimedSizedCache<String, Option<models::model_explorer::ModelExplorerInfo>>",
+    key = "String",
+    create = r#"
+        {
+            let ttl_secs = *crate::config::MCP_CONFIG.get_cache_ttl();
+            TimedSizedCache::with_size_and_lifespan_and_refresh(200, ttl_secs, true)
+        }
+    "#,
+    convert = r#"{ format!("{model}|{odoo_version}") }"#
+)]
+fn get_model_usage_cached(
+    pool: Pool,
+    model: String,
+    odoo_version: String,
+) -> Option<models::model_explorer::ModelExplorerInfo> {
+    let mut conn = pool
+        .get()
+        .expect("failed to get a DB connection from the pool");
+    let version_odoo = odoo_version_string_to_u8(&odoo_version);
+    models::model_explorer::get(&mut conn, &version_odoo, &model)
+}
+
+#[cached(
+    type = "TimedSizedCache<String, Vec<models::model_explorer::FieldMatchInfo>>",
+    key = "String",
+    create = r#"
+        {
+            let ttl_secs = *crate::config::MCP_CONFIG.get_cache_ttl();
+            TimedSizedCache::with_size_and_lifespan_and_refresh(200, ttl_secs, true)
+        }
+    "#,
+    convert = r#"{ format!("{name}|{odoo_version:?}|{limit}") }"#
+)]
+fn search_model_fields_cached(
+    pool: Pool,
+    name: String,
+    odoo_version: Option<String>,
+    limit: i64,
+) -> Vec<models::model_explorer::FieldMatchInfo> {
+    let mut conn = pool
+        .get()
+        .expect("failed to get a DB connection from the pool");
+    let version_odoo = odoo_version.as_deref().map(odoo_version_string_to_u8);
+    models::model_explorer::search_fields(&mut conn, &name, version_odoo.as_ref(), limit)
+}
+
 fn json_result<T: Serialize>(value: &T) -> Result<CallToolResult, McpError> {
     let text = serde_json::to_string_pretty(value)
         .map_err(|e| McpError::internal_error(format!("failed to serialize result: {e}"), None))?;
@@ -868,8 +1073,9 @@ impl OghMcp {
     }
 
     #[tool(
-        description = "Search Odoo modules by technical name substring, optionally filtered by \
-                        Odoo version and/or installable flag. Returns, per matching repository, \
+        description = "Search Odoo modules by technical name (case-insensitive words, each \
+                        matching the start of a name part, best matches first), optionally \
+                        filtered by Odoo version and/or installable flag. Returns, per matching repository, \
                         the Odoo versions in which the module was found. Good when you already \
                         know (part of) a module's technical name; if instead you're building a \
                         module pack for a country/topic and don't know exact names, use \
@@ -915,16 +1121,20 @@ impl OghMcp {
     }
 
     #[tool(
-        description = "Discover modules across every repository by category, a free-text term \
-                        (matched against technical name, display name, and description), and/or \
+        description = "Discover modules across every repository by category, a full-text term \
+                        (ranked, case-insensitive; matched against technical name, display name, \
+                        summary, description, install/usage docs and model, field and view \
+                        names, with a highlighted snippet of what matched), and/or \
                         reverse Odoo dependency (which modules depend on a given module - \
                         useful for \"what would break if I removed X\"). Unlike search_modules \
-                        (technical_name substring only) or list_repository_modules (needs a known \
+                        (technical_name only) or list_repository_modules (needs a known \
                         repository first), this is the entry point when you know a topic/category \
                         or a dependency but not which repository carries the modules. All filters \
                         are optional and ANDed together; at least one should be set or you'll get \
-                        an unfiltered (but still limited) listing. Returns lightweight rows - call \
-                        get_module for full manifest detail on matches of interest."
+                        an unfiltered (but still limited) listing. Each row carries its health \
+                        score (0-100): pass min_health to drop weak candidates or \
+                        sort_by_health=true to list the healthiest first. Returns lightweight \
+                        rows - call get_module for full manifest detail on matches of interest."
     )]
     async fn list_modules_by_criteria(
         &self,
@@ -933,14 +1143,7 @@ impl OghMcp {
         let pool = self.pool.clone();
         let limit = params.limit.unwrap_or(50).min(200) as i64;
         let results = tokio::task::spawn_blocking(move || {
-            list_modules_by_criteria_cached(
-                pool,
-                params.odoo_version,
-                params.search_term,
-                params.category,
-                params.depends_on,
-                limit,
-            )
+            list_modules_by_criteria_cached(pool, params, limit)
         })
         .await
         .map_err(|e| McpError::internal_error(e.to_string(), None))?;
@@ -1028,6 +1231,101 @@ impl OghMcp {
         json_result(&deps)
     }
 
+    #[tool(
+        description = "Draw the dependency closure of one or more modules at one Odoo version \
+                        as a Mermaid flowchart, ready to embed in Markdown documentation: \
+                        every Odoo module they need directly or transitively, grouped by \
+                        \"organization/repository\", with an arrow from each module to what it \
+                        depends on (`base` is left out, everything depends on it). Set \
+                        include_external to also draw Python packages and system binaries. \
+                        Use get_module_dependencies instead for the closure as data."
+    )]
+    async fn get_dependency_diagram(
+        &self,
+        Parameters(params): Parameters<GetDependencyDiagramParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let pool = self.pool.clone();
+        let mut technical_names = params.technical_names;
+        technical_names.sort();
+        technical_names.dedup();
+        let names = technical_names.join(", ");
+        let odoo_version = params.odoo_version.clone();
+        let diagram = tokio::task::spawn_blocking(move || {
+            get_dependency_diagram_cached(
+                pool,
+                technical_names,
+                params.odoo_version,
+                params.include_external.unwrap_or(false),
+            )
+        })
+        .await
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        match diagram {
+            Some(diagram) => Ok(CallToolResult::success(vec![ContentBlock::text(diagram)])),
+            None => Err(McpError::invalid_params(
+                format!("no module {names} collected for Odoo {odoo_version}"),
+                None,
+            )),
+        }
+    }
+
+    #[tool(
+        description = "List every collected module that defines (_name) or extends (_inherit) \
+                        one Odoo model at one Odoo version, definers first, with the fields \
+                        each adds (type, relation, attrs) and the public methods it overrides \
+                        (name, signature, decorators). Answers \"which modules extend \
+                        res.partner in 17.0, and which fields do they add?\". Uses each \
+                        module's latest collected version. Use search_model_fields instead \
+                        when you know a field name but not the model."
+    )]
+    async fn get_model_usage(
+        &self,
+        Parameters(params): Parameters<GetModelUsageParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let pool = self.pool.clone();
+        let model = params.model.trim().to_string();
+        let odoo_version = params.odoo_version.clone();
+        let model_name = model.clone();
+        let usage = tokio::task::spawn_blocking(move || {
+            get_model_usage_cached(pool, model_name, params.odoo_version)
+        })
+        .await
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        match usage {
+            Some(usage) => json_result(&usage),
+            None => Err(McpError::invalid_params(
+                format!("no collected module defines or extends {model} in Odoo {odoo_version}"),
+                None,
+            )),
+        }
+    }
+
+    #[tool(
+        description = "Find fields by name (case-insensitive substring, exact matches first) \
+                        across every collected module, e.g. who adds `vat_number` anywhere. \
+                        Returns the field (type, relation, attrs) with the model it is added \
+                        to, the module, its organization/repository and the Odoo version, \
+                        newest versions first. Follow up with get_model_usage to see \
+                        everything else touching that model."
+    )]
+    async fn search_model_fields(
+        &self,
+        Parameters(params): Parameters<SearchModelFieldsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let name = params.name.trim().to_string();
+        if name.is_empty() {
+            return Err(McpError::invalid_params("empty field name", None));
+        }
+        let pool = self.pool.clone();
+        let limit = params.limit.unwrap_or(50).min(200) as i64;
+        let fields = tokio::task::spawn_blocking(move || {
+            search_model_fields_cached(pool, name, params.odoo_version, limit)
+        })
+        .await
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        json_result(&fields)
+    }
+
     #[tool(
         description = "Get the code analysis for one module at one Odoo version: XML views it \
                         defines or inherits, the Odoo models it defines or extends with their \
@@ -1123,6 +1421,78 @@ impl OghMcp {
         json_result(&history)
     }
 
+    #[tool(
+        description = "List every open pull/merge request changing one module's folder on its \
+                        Odoo version branch - fixes, improvements and migrations alike - with \
+                        author, labels, draft state, review/approval counts, CI status and the \
+                        other module folders each PR touches. Check this before recommending a \
+                        local patch to a module: someone may already be fixing it upstream. \
+                        Reflects the last collector run, not live provider state."
+    )]
+    async fn get_module_open_pull_requests(
+        &self,
+        Parameters(params): Parameters<GetModuleParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let pool = self.pool.clone();
+        let results = tokio::task::spawn_blocking(move || {
+            let mut conn = pool
+                .get()
+                .expect("failed to get a DB connection from the pool");
+            let version_odoo = odoo_version_string_to_u8(&params.odoo_version);
+            let modules = find_modules(
+                &mut conn,
+                &params.technical_name,
+                &version_odoo,
+                &params.org,
+                &params.repo,
+            );
+            modules
+                .iter()
+                .map(|m| build_module_open_pull_requests(&mut conn, m))
+                .collect::<Vec<_>>()
+        })
+        .await
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        json_result(&results)
+    }
+
+    #[tool(
+        description = "Get the composite health score (0-100) of one module at one Odoo \
+                        version, with its breakdown: recent commits (20 points), maintainers \
+                        (15), test files (15), CI of its open pull requests (10), static \
+                        security checks (15), known vulnerabilities in its Python dependencies \
+                        (10) and whether its organization has it for the newest collected Odoo \
+                        version (15). Each component tells the points it got and why. Use it to \
+                        answer \"should we depend on this module?\" or to compare \
+                        alternatives; list_modules_by_criteria already returns the bare score \
+                        for many modules at once."
+    )]
+    async fn get_module_health(
+        &self,
+        Parameters(params): Parameters<GetModuleParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let pool = self.pool.clone();
+        let results = tokio::task::spawn_blocking(move || {
+            let mut conn = pool
+                .get()
+                .expect("failed to get a DB connection from the pool");
+            let version_odoo = odoo_version_string_to_u8(&params.odoo_version);
+            find_modules(
+                &mut conn,
+                &params.technical_name,
+                &version_odoo,
+                &params.org,
+                &params.repo,
+            )
+            .iter()
+            .filter_map(|m| models::module_health::get_by_module_id(&mut conn, &m.id))
+            .collect::<Vec<_>>()
+        })
+        .await
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        json_result(&results)
+    }
+
     #[tool(
         description = "Search GitHub/GitLab repositories by name substring (optionally scoped to \
                         one organization), e.g. name=\"spain\" finds organization \"OCA\" \
@@ -1188,7 +1558,8 @@ impl OghMcp {
     #[tool(
         description = "Given an exact committer (git author) name, list every module they've \
                         committed to across all Odoo versions and repositories, with commit \
-                        counts and lines inserted/deleted, ordered by Odoo version then commit \
+                        counts, lines inserted/deleted and the share of the module's current \
+                        code they still own (git blame), ordered by Odoo version then commit \
                         count. Use this to check who \
                         is actually maintaining a module in practice (as opposed to the nominal \
                         manifest authors/maintainers) - e.g. before recommending a module for a \
@@ -1205,17 +1576,46 @@ impl OghMcp {
             let mut conn = pool
                 .get()
                 .expect("failed to get a DB connection from the pool");
+            let ownership: HashMap<(String, i32, String, String), (i32, i64)> =
+                models::module_ownership::get_current_by_committer_name(&mut conn, &params.name)
+                    .into_iter()
+                    .map(|o| {
+                        (
+                            (
+                                o.technical_name,
+                                o.version_odoo,
+                                o.organization,
+                                o.repository,
+                            ),
+                            (o.lines, o.total_lines),
+                        )
+                    })
+                    .collect();
             models::module_committer::get_activity_by_committer_name(&mut conn, &params.name)
                 .into_iter()
-                .map(|a| CommitterActivityEntry {
-                    technical_name: a.technical_name,
-                    name: a.name,
-                    odoo_version: odoo_version_u8_to_string(&(a.version_odoo as u8)),
-                    organization: a.organization,
-                    repository: a.repository,
-                    commits: a.commits,
-                    insertions: a.insertions,
-                    deletions: a.deletions,
+                .map(|a| {
+                    let owned = ownership
+                        .get(&(
+                            a.technical_name.clone(),
+                            a.version_odoo,
+                            a.organization.clone(),
+                            a.repository.clone(),
+                        ))
+                        .copied();
+                    CommitterActivityEntry {
+                        technical_name: a.technical_name,
+                        name: a.name,
+                        odoo_version: odoo_version_u8_to_string(&(a.version_odoo as u8)),
+                        organization: a.organization,
+                        repository: a.repository,
+                        commits: a.commits,
+                        insertions: a.insertions,
+                        deletions: a.deletions,
+                        owned_lines: owned.map(|(lines, _)| lines),
+                        ownership_share: owned
+                            .filter(|(_, total)| *total > 0)
+                            .map(|(lines, total)| lines as f64 / total as f64),
+                    }
                 })
                 .collect::<Vec<_>>()
         })
@@ -1227,6 +1627,27 @@ impl OghMcp {
 
 #[tool_handler]
 impl ServerHandler for OghMcp {
+    // Same dispatch `#[tool_handler]` would generate, timed per tool.
+    async fn call_tool(
+        &self,
+        request: rmcp::model::CallToolRequestParams,
+        context: rmcp::service::RequestContext<rmcp::RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        // Names come from the client: only known tools get their own series.
+        let tool = match Self::tool_router().get(&request.name) {
+            Some(_) => request.name.to_string(),
+            None => "unknown".to_string(),
+        };
+        let start = Instant::now();
+        let tcc = rmcp::handler::server::tool::ToolCallContext::new(self, request, context);
+        let result = Self::tool_router().call(tcc).await;
+        let failed = result
+            .as_ref()
+            .map_or(true, |res| res.is_error == Some(true));
+        crate::metrics::observe_tool_call(&tool, failed, start.elapsed());
+        result
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo::new(ServerCapabilities::builder().enable_tools().build())
             .with_server_info(Implementation::new(
@@ -1247,10 +1668,14 @@ impl ServerHandler for OghMcp {
                  anywhere\")? Use list_modules_by_criteria. Either way, get_module's response is \
                  intentionally light - \
                  call get_module_docs (install/usage instructions), get_module_dependencies \
-                 (full transitive closure) or get_module_code_analysis (views/models/fields/\
-                 methods) on individual modules only when you actually need that detail, since \
-                 code analysis in particular can be large. Use list_module_versions to see a \
-                 module's recorded version history, and get_committer_activity to check what \
+                 (full transitive closure, or get_dependency_diagram to draw it) or \
+                 get_module_code_analysis (views/models/fields/methods) on individual modules \
+                 only when you actually need that detail, since code analysis in particular can be large. \
+                 Model-centric questions (\"who extends res.partner\", \"who adds vat_number\") \
+                 go to get_model_usage and search_model_fields instead. Use list_module_versions to see a \
+                 module's recorded version history, get_module_open_pull_requests to see \
+                 pending upstream work on a module, get_module_health for a scored answer to \
+                 \"should we depend on it?\", and get_committer_activity to check what \
                  else a specific person has committed to, e.g. to gauge whether they're still \
                  active."
                     .to_string(),
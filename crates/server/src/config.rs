@@ -21,6 +21,7 @@ pub struct OGHServerConfig {
     mcp_url: String,
     trusted_proxies: Vec<IpNet>,
     seo_enabled: bool,
+    metrics_enabled: bool,
 }
 
 impl OGHServerConfig {
@@ -66,6 +67,7 @@ impl OGHServerConfig {
             })
             .collect::<Vec<IpNet>>();
         let seo_enabled = settings.get_bool("seo_enabled").unwrap_or(false);
+        let metrics_enabled = settings.get_bool("metrics_enabled").unwrap_or(false);
         OGHServerConfig {
             bind_address,
             port,
@@ -81,6 +83,7 @@ impl OGHServerConfig {
             mcp_url,
             trusted_proxies,
             seo_enabled,
+            metrics_enabled,
         }
     }
 
@@ -160,6 +163,10 @@ impl OGHServerConfig {
     pub fn get_seo_enabled(&self) -> bool {
         self.seo_enabled
     }
+
+    pub fn get_metrics_enabled(&self) -> bool {
+        self.metrics_enabled
+    }
 }
 
 lazy_static! {
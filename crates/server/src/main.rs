@@ -1,5 +1,6 @@
 // Copyright Alexandre D. Díaz
 mod config;
+mod metrics;
 mod middlewares;
 mod minijinja_renderer;
 mod routes;
@@ -12,15 +13,15 @@ use actix_session::{storage::CookieSessionStore, SessionMiddleware};
 use actix_web::cookie::Key;
 use actix_web::{
     http::{header, StatusCode},
-    middleware::{self, DefaultHeaders, ErrorHandlers, Logger},
+    middleware::{self, Condition, DefaultHeaders, ErrorHandlers, Logger},
     web, App, HttpServer,
 };
 use minijinja::path_loader;
 use minijinja_autoreload::AutoReloader;
-use std::fs::{self, File};
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 use config::SERVER_CONFIG;
+use middlewares::metrics::track_requests;
 use middlewares::not_found;
 use middlewares::trusted_proxy::strip_untrusted_forwarded_headers;
 use sqlitedb::Pool;
@@ -72,15 +73,9 @@ async fn main() -> std::io::Result<()> {
     });
     let tmpl_reloader = web::Data::new(tmpl_reloader);
 
-    // connect to SQLite DB (read-only)
-    let db_path = "data/data.db";
-    if let Some(parent) = Path::new(db_path).parent() {
-        fs::create_dir_all(parent)?;
-    }
-    if !Path::new(db_path).exists() {
-        File::create(db_path)?;
-    }
-    let pool: Pool = sqlitedb::new_read_pool(db_path, *SERVER_CONFIG.get_db_pool_max_size());
+    // connect to the DB (read-only)
+    let database_url = sqlitedb::database_url("data/data.db")?;
+    let pool: Pool = sqlitedb::new_read_pool(&database_url, *SERVER_CONFIG.get_db_pool_max_size());
 
     log::info!(
         "starting HTTP server at http://{}:{}",
@@ -140,6 +135,7 @@ async fn main() -> std::io::Result<()> {
             .service(routes::common::route_odoo_versions)
             .service(routes::common::route_odoo_module_count)
             .service(routes::common::route_odoo_module_list)
+            .service(routes::common::route_odoo_module_search)
             .service(routes::common::route_odoo_module_icon)
             .service(routes::common::route_odoo_contributor_rank)
             .service(routes::common::route_odoo_committer_rank)
@@ -155,6 +151,8 @@ async fn main() -> std::io::Result<()> {
             .service(routes::api_doc::route)
             .service(routes::logs::route)
             .service(routes::logs::route_data)
+            .service(routes::runs::route)
+            .service(routes::runs::route_run)
             .service(routes::osv::route)
             .service(routes::osv::route_tab)
             .service(routes::pack::route)
@@ -167,19 +165,39 @@ async fn main() -> std::io::Result<()> {
             .service(routes::doodba_tools::route_doodba_migration_plan_addons)
             .service(routes::atlas::route)
             .service(routes::atlas::route_atlas_data)
+            .service(routes::atlas::route_atlas_facets)
+            .service(routes::atlas::route_atlas_export)
+            .service(routes::erd::route)
+            .service(routes::model_explorer::route)
+            .service(routes::model_explorer::route_model)
+            .service(routes::route_catalog::route_index)
+            .service(routes::route_catalog::route)
+            .service(routes::route_catalog::route_export)
+            .service(routes::security::route)
+            .service(routes::repository::route)
+            .service(routes::migration_status::route)
             .configure(|cfg| {
                 if SERVER_CONFIG.get_mcp_info_enabled() {
                     cfg.service(routes::mcp_info::route);
                 }
+                if SERVER_CONFIG.get_metrics_enabled() {
+                    cfg.service(routes::metrics::route);
+                }
             })
             .service(
                 web::scope(routes::api::v1::PATH)
+                    .service(routes::api::v1::migration_status::route)
+                    .service(routes::api::v1::model::route)
+                    .service(routes::api::v1::model::route_field_search)
                     .service(routes::api::v1::module::route)
                     .service(routes::api::v1::module::route_odoo_version)
                     .service(routes::api::v1::module::route_versions)
+                    .service(routes::api::v1::module::route_health)
+                    .service(routes::api::v1::pull_request::route_latency)
                     .service(routes::api::v1::repository::route)
                     .service(routes::api::v1::search::route_criteria)
-                    .service(routes::api::v1::search::route),
+                    .service(routes::api::v1::search::route)
+                    .service(routes::api::v1::security::route),
             )
             .wrap(DefaultHeaders::new().add((
                 "Cache-Control",
@@ -195,6 +213,10 @@ async fn main() -> std::io::Result<()> {
                 r#"%{r}a "%r" %s %b "%{Referer}i" "%{User-Agent}i" %T"#,
             ))
             .wrap(middleware::from_fn(strip_untrusted_forwarded_headers))
+            .wrap(Condition::new(
+                SERVER_CONFIG.get_metrics_enabled(),
+                middleware::from_fn(track_requests),
+            ))
             .wrap(middleware::Compress::default())
     })
     .bind((
@@ -0,0 +1,94 @@
+// Copyright Alexandre D. Díaz
+use cached::Cached;
+use lazy_static::lazy_static;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::sync::Mutex;
+
+use sqlitedb::Pool;
+
+lazy_static! {
+    static ref REGISTRY: Registry =
+        Registry::new_custom(Some("oghserver".to_string()), None).unwrap();
+    pub static ref HTTP_REQUESTS: IntCounterVec = register(
+        IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route and status."),
+            &["method", "route", "status"],
+        )
+        .unwrap()
+    );
+    pub static ref HTTP_REQUEST_DURATION: HistogramVec = register(
+        HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency by route."
+            ),
+            &["method", "route"],
+        )
+        .unwrap()
+    );
+    static ref DB_POOL: IntGaugeVec = register(
+        IntGaugeVec::new(
+            Opts::new(
+                "db_pool_connections",
+                "DB pool connections: open, idle and the configured maximum."
+            ),
+            &["state"],
+        )
+        .unwrap()
+    );
+    static ref CACHE_LOOKUPS: IntCounterVec = register(
+        IntCounterVec::new(
+            Opts::new("cache_lookups_total", "Lookups of the cached functions."),
+            &["cache", "result"],
+        )
+        .unwrap()
+    );
+}
+
+fn register<T: prometheus::core::Collector + Clone + 'static>(collector: T) -> T {
+    REGISTRY
+        .register(Box::new(collector.clone()))
+        .expect("Duplicated server metric");
+    collector
+}
+
+/// Hits and misses of a `#[cached]` function's static, since it started.
+pub fn cache_stats<K, V>(cache: &Mutex<impl Cached<K, V>>) -> (u64, u64) {
+    let cache = cache.lock().unwrap();
+    (
+        cache.cache_hits().unwrap_or_default(),
+        cache.cache_misses().unwrap_or_default(),
+    )
+}
+
+// The caches count on their own, the counters just catch up on every scrape.
+fn observe_cache(name: &str, (hits, misses): (u64, u64)) {
+    for (result, total) in [("hit", hits), ("miss", misses)] {
+        let counter = CACHE_LOOKUPS.with_label_values(&[name, result]);
+        counter.inc_by(total.saturating_sub(counter.get()));
+    }
+}
+
+/// Samples the gauges only known at scrape time and encodes every metric.
+pub fn gather(pool: &Pool) -> String {
+    let state = pool.state();
+    DB_POOL
+        .with_label_values(&["open"])
+        .set(state.connections as i64);
+    DB_POOL
+        .with_label_values(&["idle"])
+        .set(state.idle_connections as i64);
+    DB_POOL
+        .with_label_values(&["max"])
+        .set(pool.max_size() as i64);
+    observe_cache("modules_page", crate::routes::modules::cache_stats());
+    observe_cache("sitemap", crate::routes::sitemap::cache_stats());
+
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&REGISTRY.gather(), &mut buffer)
+        .expect("Can't encode the server metrics");
+    String::from_utf8(buffer).unwrap_or_default()
+}
@@ -1,3 +1,4 @@
 // Copyright Alexandre D. Díaz
+pub mod metrics;
 pub mod not_found;
 pub mod trusted_proxy;
@@ -0,0 +1,16 @@
+// Copyright Alexandre D. Díaz
+use actix_web::{get, web, Error as AWError, HttpResponse};
+
+use crate::routes::migration_status::MigrationStatusQuery;
+use crate::routes::report;
+use sqlitedb::Pool;
+
+/// The /migration-status report: per repository, the modules not ported yet
+/// from the previous Odoo version, most depended on first.
+#[get("/migration-status")]
+pub async fn route(
+    pool: web::Data<Pool>,
+    query: web::Query<MigrationStatusQuery>,
+) -> Result<HttpResponse, AWError> {
+    report::json(pool, query).await
+}
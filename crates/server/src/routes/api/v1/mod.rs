@@ -1,6 +1,10 @@
 // Copyright Alexandre D. Díaz
+pub mod migration_status;
+pub mod model;
 pub mod module;
+pub mod pull_request;
 pub mod repository;
 pub mod search;
+pub mod security;
 
 pub static PATH: &str = "/v1";
@@ -0,0 +1,56 @@
+// Copyright Alexandre D. Díaz
+use actix_web::{get, web, Error as AWError, HttpResponse};
+use serde::Deserialize;
+
+use oghutils::version::odoo_version_string_to_u8;
+use sqlitedb::{models, Pool};
+
+#[derive(Debug, Deserialize)]
+pub struct RouteFieldSearchRequest {
+    name: String,
+    odoo_version: Option<String>,
+    limit: Option<u32>,
+}
+
+/// Every module defining or extending a model, with the fields it adds and
+/// the public methods it overrides.
+#[get("/model/{odoo_version}/{model_name}")]
+pub async fn route(
+    pool: web::Data<Pool>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, AWError> {
+    let (odoo_version, model_name) = path.into_inner();
+    let version_odoo = odoo_version_string_to_u8(&odoo_version);
+    let result = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        models::model_explorer::get(&mut conn, &version_odoo, &model_name)
+    })
+    .await?;
+    match result {
+        Some(info) => Ok(HttpResponse::Ok().json(info)),
+        None => Ok(HttpResponse::NotFound().finish()),
+    }
+}
+
+#[get("/field/search")]
+pub async fn route_field_search(
+    pool: web::Data<Pool>,
+    info: web::Query<RouteFieldSearchRequest>,
+) -> Result<HttpResponse, AWError> {
+    let params = info.into_inner();
+    let term = params.name.trim().to_string();
+    if term.is_empty() {
+        return Err(actix_web::error::ErrorBadRequest("empty field name"));
+    }
+    let version_odoo = params
+        .odoo_version
+        .as_deref()
+        .map(odoo_version_string_to_u8);
+    let limit = params.limit.unwrap_or(50).min(200) as i64;
+    let result = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        models::model_explorer::search_fields(&mut conn, &term, version_odoo.as_ref(), limit)
+    })
+    .await?;
+    Ok(HttpResponse::Ok().json(result))
+}
@@ -5,8 +5,8 @@ use std::collections::HashMap;
 use actix_web::{get, web, Error as AWError, HttpResponse};
 use serde::{Deserialize, Serialize};
 
-use diesel::sqlite::SqliteConnection;
 use oghutils::version::{odoo_version_string_to_u8, odoo_version_u8_to_string};
+use sqlitedb::DbConnection;
 use sqlitedb::{models, Pool};
 
 use crate::utils::normalize_python_dep;
@@ -70,6 +70,7 @@ pub struct ModuleControllerResponse {
     pub csrf: Option<bool>,
     pub website: bool,
     pub uses_sudo: bool,
+    pub checks_token_access: bool,
     pub signature: String,
     pub docstring: Option<String>,
 }
@@ -83,6 +84,46 @@ pub struct ModuleSecurityWarningResponse {
     pub xml_id: Option<String>,
 }
 
+// Any open PR/MR changing this module's folder on its version branch (fixes,
+// improvements, migrations...): what to check before patching a module
+// yourself. `review_count`/`approval_count` come from the provider (GitLab
+// counts discussion notes as reviews); `modules` lists every module folder
+// the PR touches, this one included.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ModuleOpenPullRequestResponse {
+    pub prid: i64,
+    pub title: String,
+    pub url: String,
+    pub author: Option<String>,
+    pub is_draft: bool,
+    pub labels: Vec<String>,
+    pub review_count: i32,
+    pub approval_count: i32,
+    pub ci_status: Option<String>,
+    pub age_days: Option<i64>,
+    pub last_message_days: Option<i64>,
+    pub modules: Vec<String>,
+}
+
+/// OpenUpgrade's coverage of the jump from the previous Odoo version into
+/// this one; only core Odoo modules are listed in its tables.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ModuleUpgradeCoverageResponse {
+    pub status: String,
+    pub note: Option<String>,
+    pub covered: bool,
+    pub is_new: bool,
+}
+
+/// Surviving lines of the module version's tree last touched by a committer
+/// (git blame); `share` is the fraction of all owned lines.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ModuleOwnerResponse {
+    pub name: String,
+    pub lines: i32,
+    pub share: f64,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ModuleFullInfoResponse {
     pub technical_name: String,
@@ -106,6 +147,9 @@ pub struct ModuleFullInfoResponse {
     pub folder_size: u64,
     pub repository: String,
     pub organization: String,
+    /// Instance whose dataset the module was imported from (`oghcollector
+    /// import`); `None` when this instance collects it.
+    pub dataset_source: Option<String>,
     pub odoo_version: String,
     pub views: Vec<ModuleViewResponse>,
     pub models: Vec<ModuleModelResponse>,
@@ -114,6 +158,15 @@ pub struct ModuleFullInfoResponse {
     /// Modules (same Odoo version, any repository) that declare this module
     /// as an Odoo dependency.
     pub required_by: Vec<models::module::ModuleCriteriaInfo>,
+    pub open_pull_requests: Vec<ModuleOpenPullRequestResponse>,
+    pub upgrade_coverage: Option<ModuleUpgradeCoverageResponse>,
+    pub ownership: Vec<ModuleOwnerResponse>,
+    /// Fewest owners holding more than half of the live lines; `None` when
+    /// no ownership was collected for this version.
+    pub bus_factor: Option<usize>,
+    /// Composite score of the module in this Odoo version, with the points
+    /// each of its components got.
+    pub health: Option<models::module_health::ModuleHealth>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -146,16 +199,13 @@ pub struct ModuleVersionHistoryResponse {
     pub versions: Vec<ModuleVersionInfoResponse>,
 }
 
-fn get_module_git(conn: &mut SqliteConnection, module: &models::module::Model) -> String {
+fn get_module_git(conn: &mut DbConnection, module: &models::module::Model) -> String {
     let repo = models::gh_repository::get_by_id(conn, &module.gh_repository_id).unwrap();
     let org = models::gh_organization::get_by_id(conn, &repo.gh_organization_id).unwrap();
     format!("https://github.com/{}/{}.git", org.name, repo.name)
 }
 
-fn get_module_views(
-    conn: &mut SqliteConnection,
-    module_version_id: &i64,
-) -> Vec<ModuleViewResponse> {
+fn get_module_views(conn: &mut DbConnection, module_version_id: &i64) -> Vec<ModuleViewResponse> {
     models::module_view::get_by_module_version_id(conn, module_version_id)
         .into_iter()
         .map(|v| ModuleViewResponse {
@@ -170,7 +220,7 @@ fn get_module_views(
 }
 
 fn get_module_controllers(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_version_id: &i64,
 ) -> Vec<ModuleControllerResponse> {
     models::module_controller::get_by_module_version_id(conn, module_version_id)
@@ -185,14 +235,33 @@ fn get_module_controllers(
             csrf: c.csrf,
             website: c.website,
             uses_sudo: c.uses_sudo,
+            checks_token_access: c.checks_token_access,
             signature: c.signature,
             docstring: c.docstring,
         })
         .collect()
 }
 
+fn get_module_ownership(
+    conn: &mut DbConnection,
+    module_version_id: &i64,
+) -> (Vec<ModuleOwnerResponse>, Option<usize>) {
+    let owners = models::module_ownership::get_by_module_version_id(conn, module_version_id);
+    let lines: Vec<i32> = owners.iter().map(|o| o.lines).collect();
+    let total: i64 = lines.iter().map(|l| *l as i64).sum();
+    let ownership = owners
+        .into_iter()
+        .map(|o| ModuleOwnerResponse {
+            share: o.lines as f64 / total as f64,
+            name: o.name,
+            lines: o.lines,
+        })
+        .collect();
+    (ownership, models::module_ownership::bus_factor(&lines))
+}
+
 fn get_module_security_warnings(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_version_id: &i64,
 ) -> Vec<ModuleSecurityWarningResponse> {
     models::module_security_warning::get_by_module_version_id(conn, module_version_id)
@@ -206,10 +275,39 @@ fn get_module_security_warnings(
         .collect()
 }
 
-fn get_module_models(
-    conn: &mut SqliteConnection,
-    module_version_id: &i64,
-) -> Vec<ModuleModelResponse> {
+fn get_module_open_pull_requests(
+    conn: &mut DbConnection,
+    org_name: &str,
+    module: &models::module::Model,
+) -> Vec<ModuleOpenPullRequestResponse> {
+    models::open_pull_request::get_by_technical_name_organization_name(
+        conn,
+        &module.technical_name,
+        org_name,
+        Some(&(module.version_odoo as u8)),
+    )
+    .into_iter()
+    .map(|pr| ModuleOpenPullRequestResponse {
+        url: format!(
+            "https://github.com/{}/{}/pull/{}",
+            pr.org_name, pr.repository_name, pr.prid
+        ),
+        labels: pr.labels_vec(),
+        modules: models::open_pull_request::get_module_names(conn, &pr.id),
+        age_days: models::pull_request::days_since(pr.created_at.as_deref()),
+        last_message_days: models::pull_request::days_since(pr.last_message_at.as_deref()),
+        prid: pr.prid,
+        title: pr.name,
+        author: pr.author,
+        is_draft: pr.is_draft,
+        review_count: pr.review_count,
+        approval_count: pr.approval_count,
+        ci_status: pr.ci_status,
+    })
+    .collect()
+}
+
+fn get_module_models(conn: &mut DbConnection, module_version_id: &i64) -> Vec<ModuleModelResponse> {
     models::module_model::get_by_module_version_id(conn, module_version_id)
         .into_iter()
         .map(|m| {
@@ -253,7 +351,7 @@ fn get_module_models(
 }
 
 pub fn process_modules_db(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     modules: &[models::module::Model],
     version_module: Option<&str>,
 ) -> Vec<ModuleFullInfoResponse> {
@@ -278,6 +376,8 @@ pub fn process_modules_db(
             None,
             None,
             Some(&module.technical_name),
+            None,
+            models::module_health::HealthSort::Relevance,
             500,
         );
         let authors = models::module_author::get_names_by_module_id(conn, &module.id);
@@ -285,7 +385,23 @@ pub fn process_modules_db(
         let committers = models::module_committer::get_names_by_module_id(conn, &module.id);
         let repo = models::gh_repository::get_by_id(conn, &module.gh_repository_id).unwrap();
         let org = models::gh_organization::get_by_id(conn, &repo.gh_organization_id).unwrap();
+        let dataset_source = repo
+            .dataset_source_id
+            .and_then(|id| models::dataset_source::get_by_id(conn, &id))
+            .map(|source| source.name);
         let git = get_module_git(conn, module);
+        let open_pull_requests = get_module_open_pull_requests(conn, &org.name, module);
+        let upgrade_coverage = models::module_upgrade_coverage::get_by_technical_name_version(
+            conn,
+            &module.technical_name,
+            &(module.version_odoo as u8),
+        )
+        .map(|coverage| ModuleUpgradeCoverageResponse {
+            covered: coverage.is_covered(),
+            status: coverage.status,
+            note: coverage.note,
+            is_new: coverage.is_new,
+        });
         // None (default) resolves to the latest version; an explicit request
         // for a version that doesn't exist for this module comes back with
         // empty views/models rather than silently falling back to "latest".
@@ -293,25 +409,33 @@ pub fn process_modules_db(
             Some(v) => models::module_version::get_by_module_id_version_module(conn, &module.id, v),
             None => models::module_version::resolve_current(conn, module),
         };
-        let (views, module_models, controllers, security_warnings, version) =
-            match &resolved_version {
-                Some(mv) => (
-                    get_module_views(conn, &mv.id),
-                    get_module_models(conn, &mv.id),
-                    get_module_controllers(conn, &mv.id),
-                    get_module_security_warnings(conn, &mv.id),
-                    mv.version_module.clone(),
-                ),
-                None => (
-                    Vec::new(),
-                    Vec::new(),
-                    Vec::new(),
-                    Vec::new(),
-                    version_module
-                        .map(|v| v.to_string())
-                        .unwrap_or_else(|| module.version_module.clone()),
-                ),
-            };
+        let (
+            views,
+            module_models,
+            controllers,
+            security_warnings,
+            (ownership, bus_factor),
+            version,
+        ) = match &resolved_version {
+            Some(mv) => (
+                get_module_views(conn, &mv.id),
+                get_module_models(conn, &mv.id),
+                get_module_controllers(conn, &mv.id),
+                get_module_security_warnings(conn, &mv.id),
+                get_module_ownership(conn, &mv.id),
+                mv.version_module.clone(),
+            ),
+            None => (
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                (Vec::new(), None),
+                version_module
+                    .map(|v| v.to_string())
+                    .unwrap_or_else(|| module.version_module.clone()),
+            ),
+        };
         res.push(ModuleFullInfoResponse {
             name: module.name.clone(),
             version,
@@ -333,19 +457,25 @@ pub fn process_modules_db(
             folder_size: module.folder_size as u64,
             repository: repo.name.clone(),
             organization: org.name.clone(),
+            dataset_source,
             odoo_version: odoo_version_u8_to_string(&(module.version_odoo as u8)),
             views,
             models: module_models,
             controllers,
             security_warnings,
             required_by,
+            open_pull_requests,
+            upgrade_coverage,
+            ownership,
+            bus_factor,
+            health: models::module_health::get_by_module_id(conn, &module.id),
         });
     }
     res
 }
 
 fn get_module_generic_info(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_name: &str,
 ) -> Option<ModuleGenericInfoResponse> {
     let modules = models::module::get_info(conn, module_name);
@@ -370,6 +500,43 @@ fn get_module_generic_info(
     })
 }
 
+fn find_modules(
+    conn: &mut DbConnection,
+    module_name: &str,
+    version_odoo: &u8,
+    org: &Option<String>,
+    repo: &Option<String>,
+) -> Vec<models::module::Model> {
+    match (org, repo) {
+        (Some(org), Some(repo)) => {
+            models::module::get_by_technical_name_odoo_version_organization_name_repository_name(
+                conn,
+                module_name,
+                version_odoo,
+                org,
+                repo,
+            )
+        }
+        (Some(org), None) => models::module::get_by_technical_name_odoo_version_organization_name(
+            conn,
+            module_name,
+            version_odoo,
+            org,
+        ),
+        (None, Some(repo)) => models::module::get_by_technical_name_odoo_version_repository_name(
+            conn,
+            module_name,
+            version_odoo,
+            repo,
+        ),
+        (None, None) => models::module::get_by_technical_name_odoo_version(
+            conn,
+            &[module_name.to_string()],
+            version_odoo,
+        ),
+    }
+}
+
 #[get("/module/{module_name}")]
 pub async fn route(
     pool: web::Data<Pool>,
@@ -461,34 +628,12 @@ pub async fn route_versions(
 
     let result = web::block(move || {
         let mut conn = pool.get().unwrap();
-        let modules = match (&org, &repo) {
-            (Some(org), Some(repo)) => {
-                models::module::get_by_technical_name_odoo_version_organization_name_repository_name(
-                    &mut conn, &module_name, &version_odoo, org, repo,
-                )
-            }
-            (Some(org), None) => models::module::get_by_technical_name_odoo_version_organization_name(
-                &mut conn,
-                &module_name,
-                &version_odoo,
-                org,
-            ),
-            (None, Some(repo)) => models::module::get_by_technical_name_odoo_version_repository_name(
-                &mut conn,
-                &module_name,
-                &version_odoo,
-                repo,
-            ),
-            (None, None) => models::module::get_by_technical_name_odoo_version(
-                &mut conn,
-                std::slice::from_ref(&module_name),
-                &version_odoo,
-            ),
-        };
+        let modules = find_modules(&mut conn, &module_name, &version_odoo, &org, &repo);
         modules
             .iter()
             .map(|m| {
-                let repo_model = models::gh_repository::get_by_id(&mut conn, &m.gh_repository_id).unwrap();
+                let repo_model =
+                    models::gh_repository::get_by_id(&mut conn, &m.gh_repository_id).unwrap();
                 let org_model =
                     models::gh_organization::get_by_id(&mut conn, &repo_model.gh_organization_id)
                         .unwrap();
@@ -512,3 +657,25 @@ pub async fn route_versions(
     .await?;
     Ok(HttpResponse::Ok().json(result))
 }
+
+#[get("/module/{module_name}/{odoo_version}/health")]
+pub async fn route_health(
+    pool: web::Data<Pool>,
+    path: web::Path<(String, String)>,
+    info: web::Query<RouteModuleRequest>,
+) -> Result<HttpResponse, AWError> {
+    let (module_name, odoo_version) = path.into_inner();
+    let version_odoo = odoo_version_string_to_u8(&odoo_version);
+    let org = info.org.clone();
+    let repo = info.repo.clone();
+
+    let result = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        find_modules(&mut conn, &module_name, &version_odoo, &org, &repo)
+            .iter()
+            .filter_map(|m| models::module_health::get_by_module_id(&mut conn, &m.id))
+            .collect::<Vec<_>>()
+    })
+    .await?;
+    Ok(HttpResponse::Ok().json(result))
+}
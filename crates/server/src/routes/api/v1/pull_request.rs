@@ -0,0 +1,33 @@
+// Copyright Alexandre D. Díaz
+use actix_web::{get, web, Error as AWError, HttpResponse};
+use serde::Deserialize;
+
+use sqlitedb::models::pull_request_history::{self, LatencyGroup};
+use sqlitedb::Pool;
+
+#[derive(Debug, Deserialize)]
+pub struct RouteLatencyRequest {
+    group_by: Option<String>,
+}
+
+/// p50/p90 time to first review, to approval and to merge over every
+/// recorded migration PR closure, grouped by `repository` (default),
+/// `version` or `reviewer`.
+#[get("/pull_requests/latency")]
+pub async fn route_latency(
+    pool: web::Data<Pool>,
+    info: web::Query<RouteLatencyRequest>,
+) -> Result<HttpResponse, AWError> {
+    let group_by = info.group_by.as_deref().unwrap_or("repository");
+    let Some(group) = LatencyGroup::from_name(group_by) else {
+        return Err(actix_web::error::ErrorBadRequest(format!(
+            "Invalid group_by '{group_by}', expected repository, version or reviewer"
+        )));
+    };
+    let result = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        pull_request_history::latency_stats(&mut conn, group)
+    })
+    .await?;
+    Ok(HttpResponse::Ok().json(result))
+}
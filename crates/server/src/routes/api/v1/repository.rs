@@ -4,8 +4,8 @@ use std::collections::HashMap;
 use actix_web::{get, web, Error as AWError, HttpResponse};
 use serde::{Deserialize, Serialize};
 
-use diesel::sqlite::SqliteConnection;
 use oghutils::version::odoo_version_u8_to_string;
+use sqlitedb::DbConnection;
 use sqlitedb::{models, Pool};
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -15,7 +15,7 @@ pub struct RepositoryGenericInfoResponse {
 }
 
 fn get_repository_generic_info(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     repo_name: &str,
 ) -> Option<RepositoryGenericInfoResponse> {
     let repos = models::gh_repository::get_info_by_name(conn, repo_name);
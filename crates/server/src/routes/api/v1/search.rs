@@ -4,8 +4,8 @@ use std::collections::HashMap;
 use actix_web::{get, web, Error as AWError, HttpResponse};
 use serde::{Deserialize, Serialize};
 
-use diesel::sqlite::SqliteConnection;
 use oghutils::version::{odoo_version_string_to_u8, odoo_version_u8_to_string};
+use sqlitedb::DbConnection;
 use sqlitedb::{models, Pool};
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -26,6 +26,10 @@ pub struct RouteSearchCriteriaRequest {
     term: Option<String>,
     category: Option<String>,
     depends_on: Option<String>,
+    /// Lowest health score to list.
+    min_health: Option<i32>,
+    #[serde(default)]
+    sort: models::module_health::HealthSort,
     limit: Option<u32>,
 }
 
@@ -51,12 +55,12 @@ fn build_response(
     res
 }
 
-fn get_modules(conn: &mut SqliteConnection, module_name: &str) -> Vec<SearchGenericInfoResponse> {
+fn get_modules(conn: &mut DbConnection, module_name: &str) -> Vec<SearchGenericInfoResponse> {
     build_response(models::module::get_generic_info(conn, module_name))
 }
 
 fn get_modules_by_odoo_version_installable(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_name: &str,
     odoo_version: &u8,
     installable: &bool,
@@ -72,7 +76,7 @@ fn get_modules_by_odoo_version_installable(
 }
 
 fn get_modules_by_odoo_version(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_name: &str,
     odoo_version: &u8,
 ) -> Vec<SearchGenericInfoResponse> {
@@ -84,7 +88,7 @@ fn get_modules_by_odoo_version(
 }
 
 fn get_modules_by_installable(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_name: &str,
     installable: &bool,
 ) -> Vec<SearchGenericInfoResponse> {
@@ -96,8 +100,9 @@ fn get_modules_by_installable(
 }
 
 /// Cross-repository discovery by free-text term, category and/or reverse
-/// Odoo dependency ("which modules depend on X"); same query (and limit cap)
-/// as the MCP `list_modules_by_criteria` tool.
+/// Odoo dependency ("which modules depend on X"), each result with its health
+/// score; same query (and limit cap) as the MCP `list_modules_by_criteria`
+/// tool.
 #[get("/search")]
 pub async fn route_criteria(
     pool: web::Data<Pool>,
@@ -114,6 +119,8 @@ pub async fn route_criteria(
             params.term.as_deref(),
             params.category.as_deref(),
             params.depends_on.as_deref(),
+            params.min_health,
+            params.sort,
             limit,
         )
     })
@@ -0,0 +1,16 @@
+// Copyright Alexandre D. Díaz
+use actix_web::{get, web, Error as AWError, HttpResponse};
+
+use crate::routes::report;
+use crate::routes::security::SecurityQuery;
+use sqlitedb::Pool;
+
+/// The /security overview: current findings grouped by Odoo version,
+/// organization and rule, plus their counts over the last runs.
+#[get("/security")]
+pub async fn route(
+    pool: web::Data<Pool>,
+    query: web::Query<SecurityQuery>,
+) -> Result<HttpResponse, AWError> {
+    report::json(pool, query).await
+}
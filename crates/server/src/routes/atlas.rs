@@ -1,239 +1,276 @@
 // Copyright Alexandre D. Díaz
-use actix_web::{get, web, Error as AWError, HttpRequest, HttpResponse, Responder, Result};
-use cached::{proc_macro::cached, stores::TimedSizedCache};
-use diesel::sqlite::SqliteConnection;
+use actix_web::http::header::{ETag, EntityTag, IfNoneMatch};
+use actix_web::{
+    get, web, Error as AWError, HttpMessage, HttpRequest, HttpResponse, Responder, Result,
+};
 use minijinja::context;
 use oghutils::version::odoo_version_string_to_u8;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sqlitedb::models::atlas_export::{self, ExportFormat};
+use sqlitedb::models::atlas_graph::{GraphFilter, GraphInfo};
+use sqlitedb::DbConnection;
+use std::collections::BTreeSet;
 
-use crate::config::SERVER_CONFIG;
 use crate::minijinja_renderer::MiniJinjaRenderer;
-use crate::utils::get_minijinja_context;
+use crate::utils::{get_minijinja_context, technical_names};
 
 use sqlitedb::{models, Pool};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct GraphNodeInfo {
-    pub key: String,
-    pub attributes: HashMap<String, String>,
+// Ego graphs deeper than this are the whole ecosystem anyway.
+const MAX_EGO_DEPTH: usize = 10;
+// Each module of an exported set walks its own dependency closure.
+const MAX_EXPORT_MODULES: usize = 200;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AtlasDataQuery {
+    pub org: Option<String>,
+    pub repo: Option<String>,
+    pub category: Option<String>,
+    /// Comma-separated technical names.
+    pub modules: Option<String>,
+    /// Technical name of the module the ego graph is centered on.
+    pub focus: Option<String>,
+    pub depth: Option<usize>,
+    pub reverse_depth: Option<usize>,
 }
-impl GraphNodeInfo {
-    fn update(&mut self, key: &str, value: &str) {
-        self.attributes.insert(key.to_string(), value.to_string());
+
+impl AtlasDataQuery {
+    fn graph_filter(&self) -> GraphFilter {
+        let non_empty = |value: &Option<String>| value.clone().filter(|v| !v.is_empty());
+        GraphFilter {
+            organization: non_empty(&self.org),
+            repository: non_empty(&self.repo),
+            category: non_empty(&self.category),
+            modules: non_empty(&self.modules).map(|modules| {
+                modules
+                    .split(',')
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .collect()
+            }),
+        }
+    }
+
+    fn focus(&self) -> Option<&str> {
+        self.focus.as_deref().filter(|focus| !focus.is_empty())
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct GraphEdgeInfo {
-    pub key: String,
-    pub source: String,
-    pub target: String,
-    pub undirected: bool,
-    pub attributes: HashMap<String, String>,
+// The stored graph (see models::atlas_graph::refresh), or one built on the
+// fly when the collector hasn't stored it yet.
+fn get_graph(conn: &mut DbConnection, odoo_version: &u8) -> (String, String) {
+    match models::atlas_graph::get_by_version_odoo(conn, odoo_version) {
+        Some(graph) => (graph.data, graph.etag),
+        None => models::atlas_graph::render(conn, odoo_version),
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct GraphInfo {
-    pub attributes: HashMap<String, String>,
-    pub nodes: Vec<GraphNodeInfo>,
-    pub edges: Vec<GraphEdgeInfo>,
+fn parse_graph(data: &str) -> GraphInfo {
+    serde_json::from_str(data).unwrap_or_default()
 }
 
-fn set_main_node_attributes(
-    module: &models::module::Model,
-    gh_repo_odoo_id: &i64,
-    repo_name: &str,
-    node_info: &mut GraphNodeInfo,
-) {
-    if module.gh_repository_id.eq(gh_repo_odoo_id) {
-        if module.application {
-            node_info.update("size", "12");
-            node_info.update("color", "#21B799");
-        } else {
-            node_info.update("size", "10");
-            node_info.update("color", "#017E84");
-        }
-    } else if module.application {
-        node_info.update("size", "9");
-        node_info.update("color", "#21B799");
-    } else {
-        node_info.update("size", "8");
-        node_info.update("color", "#E46E78");
+/// The graph as JSON with its ETag, restricted by `query`. The unrestricted
+/// one is passed through as stored.
+fn get_graph_data(
+    conn: &mut DbConnection,
+    odoo_version: &u8,
+    query: &AtlasDataQuery,
+) -> (String, String) {
+    let (data, etag) = get_graph(conn, odoo_version);
+    let filter = query.graph_filter();
+    if filter.is_empty() && query.focus().is_none() {
+        return (data, etag);
     }
-    node_info.update("label", &module.technical_name);
-    node_info.update("repository", repo_name);
+    let mut graph = parse_graph(&data);
+    if !filter.is_empty() {
+        graph = graph.filter(&filter);
+    }
+    if let Some(focus) = query.focus() {
+        graph = graph.ego(
+            focus,
+            query.depth.unwrap_or(1).min(MAX_EGO_DEPTH),
+            query.reverse_depth.unwrap_or(1).min(MAX_EGO_DEPTH),
+        );
+    }
+    let data = serde_json::to_string(&graph).unwrap_or_default();
+    let etag = models::atlas_graph::etag_for(&data);
+    (data, etag)
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct AtlasFacetsResponse {
+    pub organizations: BTreeSet<String>,
+    pub repositories: BTreeSet<String>,
+    pub categories: BTreeSet<String>,
 }
 
-#[cached(
-    type = "TimedSizedCache<String, GraphInfo>",
-    key = "String",
-    create = r#"
-        {
-            let ttl_secs = *SERVER_CONFIG.get_cache_ttl();
-            TimedSizedCache::with_size_and_lifespan_and_refresh(50, ttl_secs, true)
+fn get_graph_facets(conn: &mut DbConnection, odoo_version: &u8) -> AtlasFacetsResponse {
+    let mut facets = AtlasFacetsResponse::default();
+    for node in parse_graph(&get_graph(conn, odoo_version).0).nodes {
+        let mut attributes = node.attributes;
+        for (name, values) in [
+            ("organization", &mut facets.organizations),
+            ("repository", &mut facets.repositories),
+            ("category", &mut facets.categories),
+        ] {
+            if let Some(value) = attributes.remove(name).filter(|v| !v.is_empty()) {
+                values.insert(value);
+            }
         }
-    "#,
-    convert = r#"{ format!("{}", odoo_version) }"#
-)]
-fn get_graph_data(conn: &mut SqliteConnection, odoo_version: &u8) -> GraphInfo {
-    let mut graph_info = GraphInfo {
-        attributes: HashMap::new(),
-        nodes: Vec::new(),
-        edges: Vec::new(),
+    }
+    facets
+}
+
+// Either way the browser revalidates with the ETag and gets a 304 until the
+// next collection.
+#[get("/atlas/data/{odoo_version}")]
+pub async fn route_atlas_data(
+    pool: web::Data<Pool>,
+    path: web::Path<String>,
+    query: web::Query<AtlasDataQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AWError> {
+    let odoo_version = odoo_version_string_to_u8(&path.into_inner());
+    let (data, etag) = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        get_graph_data(&mut conn, &odoo_version, &query)
+    })
+    .await?;
+    let etag = EntityTag::new_strong(etag);
+    let cached = req
+        .get_header::<IfNoneMatch>()
+        .is_some_and(|if_none_match| match if_none_match {
+            IfNoneMatch::Any => true,
+            IfNoneMatch::Items(items) => items.iter().any(|item| item.weak_eq(&etag)),
+        });
+    let mut response = if cached {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
     };
-    let mut gh_repo_odoo_id = 0i64;
-    let gh_org_odoo_opt = models::gh_organization::get_by_name(conn, "odoo");
-    if let Some(gh_org_odoo) = gh_org_odoo_opt {
-        let gh_repo_odoo_opt = models::gh_repository::get_by_name(conn, &gh_org_odoo.id, "odoo");
-        if let Some(gh_repo_odoo) = gh_repo_odoo_opt {
-            gh_repo_odoo_id = gh_repo_odoo.id;
-        }
+    response
+        .insert_header(ETag(etag))
+        .insert_header(("Cache-Control", "no-cache"));
+    if cached {
+        return Ok(response.finish());
     }
-    // A technical_name can exist in more than one org for the same Odoo
-    // version (e.g. a module donated from OCA into Odoo core keeps living in
-    // both repos for a while) - keep the odoo/odoo row when there is one so
-    // the graph node reflects that it's core, instead of whichever org's row
-    // SQLite happens to return last silently overwriting the node.
-    let mut main_modules_by_name: HashMap<String, models::module::Model> = HashMap::new();
-    for module in models::module::get_by_odoo_version(conn, odoo_version) {
-        let is_odoo_core = module.gh_repository_id == gh_repo_odoo_id;
-        let already_odoo_core = main_modules_by_name
-            .get(&module.technical_name)
-            .is_some_and(|existing| existing.gh_repository_id == gh_repo_odoo_id);
-        if is_odoo_core || !already_odoo_core {
-            main_modules_by_name.insert(module.technical_name.clone(), module);
-        }
+    Ok(response.content_type("application/json").body(data))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AtlasExportQuery {
+    /// Technical name of the module whose dependency closure is exported.
+    pub module: Option<String>,
+    /// Comma-separated technical names, exported with their closure.
+    pub modules: Option<String>,
+    /// Include Python packages and binaries (always in GraphML).
+    #[serde(default)]
+    pub external: bool,
+}
+
+impl AtlasExportQuery {
+    fn technical_names(&self) -> Vec<String> {
+        let mut names = technical_names([&self.module, &self.modules]);
+        names.sort();
+        names.dedup();
+        names
     }
-    let main_modules: Vec<_> = main_modules_by_name.into_values().collect();
-    let main_modules_names: Vec<String> = main_modules
-        .iter()
-        .map(|item| item.technical_name.clone())
-        .collect();
-    for module in main_modules {
-        if module.technical_name == "base" {
-            continue;
-        }
-        let module_depends_list =
-            models::dependency::get_module_external_dependency_names(conn, &module.id, "module");
-        for mod_dep_name in module_depends_list {
-            if mod_dep_name == "base" {
-                continue;
-            }
-            let node_key: String = format!("o_{}", mod_dep_name);
-            if !main_modules_names.contains(&mod_dep_name)
-                && !graph_info.nodes.iter().any(|x| x.key.eq(&node_key))
-            {
-                let mut node_info = GraphNodeInfo {
-                    key: node_key.clone(),
-                    attributes: HashMap::new(),
-                };
-                node_info.attributes.insert("size".into(), "8".into());
-                node_info
-                    .attributes
-                    .insert("color".into(), "#E46E78".into());
-                node_info
-                    .attributes
-                    .insert("label".into(), mod_dep_name.clone());
-                graph_info.nodes.push(node_info);
-            }
-            let edge_key = format!("o_{}__{}", module.technical_name, mod_dep_name);
-            if !graph_info.edges.iter().any(|x| x.key.eq(&edge_key)) {
-                let mut edge_info = GraphEdgeInfo {
-                    key: edge_key,
-                    source: format!("o_{}", module.technical_name),
-                    target: node_key.clone(),
-                    undirected: false,
-                    attributes: HashMap::new(),
-                };
-                edge_info.attributes.insert("size".into(), "2".into());
-                graph_info.edges.push(edge_info);
-            }
-        }
-        let pip_depends_list =
-            models::dependency::get_module_external_dependency_names(conn, &module.id, "python");
-        for pip_dep_name in pip_depends_list {
-            let node_key: String = format!("p_{}", pip_dep_name);
-            if !graph_info.nodes.iter().any(|x| x.key.eq(&node_key)) {
-                let mut node_info = GraphNodeInfo {
-                    key: node_key.clone(),
-                    attributes: HashMap::new(),
-                };
-                node_info.attributes.insert("size".into(), "5".into());
-                node_info
-                    .attributes
-                    .insert("color".into(), "#6c5148".into());
-                node_info
-                    .attributes
-                    .insert("label".into(), pip_dep_name.clone());
-                graph_info.nodes.push(node_info);
-            }
-            let mut edge_info = GraphEdgeInfo {
-                key: format!("p_{}__{}", module.technical_name, pip_dep_name),
-                source: format!("o_{}", module.technical_name),
-                target: node_key.clone(),
-                undirected: false,
-                attributes: HashMap::new(),
-            };
-            edge_info.attributes.insert("size".into(), "2".into());
-            graph_info.edges.push(edge_info);
-        }
-        let bin_depends_list =
-            models::dependency::get_module_external_dependency_names(conn, &module.id, "bin");
-        for bin_dep_name in bin_depends_list {
-            let node_key: String = format!("b_{}", bin_dep_name);
-            if !graph_info.nodes.iter().any(|x| x.key.eq(&node_key)) {
-                let mut node_info = GraphNodeInfo {
-                    key: node_key.clone(),
-                    attributes: HashMap::new(),
-                };
-                node_info.attributes.insert("size".into(), "5".into());
-                node_info
-                    .attributes
-                    .insert("color".into(), "#335548".into());
-                node_info
-                    .attributes
-                    .insert("label".into(), bin_dep_name.clone());
-                graph_info.nodes.push(node_info);
-            }
-            let mut edge_info = GraphEdgeInfo {
-                key: format!("b_{}__{}", module.technical_name, bin_dep_name),
-                source: format!("o_{}", module.technical_name),
-                target: node_key.clone(),
-                undirected: false,
-                attributes: HashMap::new(),
-            };
-            edge_info.attributes.insert("size".into(), "2".into());
-            graph_info.edges.push(edge_info);
-        }
+}
 
-        let node_key = format!("o_{}", module.technical_name);
-        let cur_node_info_opt = graph_info.nodes.iter().position(|x| x.key.eq(&node_key));
-        let repo_name = models::gh_repository::get_by_id(conn, &module.gh_repository_id)
-            .map(|r| r.name)
-            .unwrap_or_default();
-        let mut node_info = GraphNodeInfo {
-            key: node_key,
-            attributes: HashMap::new(),
-        };
-        set_main_node_attributes(&module, &gh_repo_odoo_id, &repo_name, &mut node_info);
-        if let Some(idx) = cur_node_info_opt {
-            graph_info.nodes.remove(idx);
-        }
-        graph_info.nodes.push(node_info);
+// Names come straight from the query string and end up in a header.
+pub(crate) fn file_name_safe(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        .collect()
+}
+
+/// The closure of the requested modules, or the whole version when none
+/// is, with the title and file name it's exported under.
+fn get_export_graph(
+    conn: &mut DbConnection,
+    odoo_version: &u8,
+    version_name: &str,
+    names: &[String],
+) -> (GraphInfo, String, String) {
+    match names {
+        [] => (
+            models::atlas_graph::load(conn, odoo_version),
+            format!("Odoo {version_name}"),
+            format!("odoo-{version_name}"),
+        ),
+        [name] => (
+            models::atlas_graph::closure(conn, odoo_version, names),
+            format!("{name} (Odoo {version_name})"),
+            format!("odoo-{version_name}-{name}"),
+        ),
+        _ => (
+            models::atlas_graph::closure(conn, odoo_version, names),
+            format!("{} modules (Odoo {version_name})", names.len()),
+            format!("odoo-{version_name}-modules"),
+        ),
     }
-    graph_info
 }
 
-#[get("/atlas/data/{odoo_version}")]
-pub async fn route_atlas_data(
+/// The dependency graph as DOT, GraphML or Mermaid: of a module, of a
+/// module set or of the whole version.
+#[get("/atlas/export/{odoo_version}/{format}")]
+pub async fn route_atlas_export(
+    pool: web::Data<Pool>,
+    path: web::Path<(String, String)>,
+    query: web::Query<AtlasExportQuery>,
+) -> Result<HttpResponse, AWError> {
+    let (version_name, format_name) = path.into_inner();
+    let Some(format) = ExportFormat::from_name(&format_name) else {
+        return Err(actix_web::error::ErrorBadRequest(format!(
+            "unknown export format: {format_name:?} (dot, graphml or mermaid)"
+        )));
+    };
+    let names = query.technical_names();
+    if names.len() > MAX_EXPORT_MODULES {
+        return Err(actix_web::error::ErrorBadRequest(format!(
+            "too many modules: {} (max {MAX_EXPORT_MODULES})",
+            names.len()
+        )));
+    }
+    let odoo_version = odoo_version_string_to_u8(&version_name);
+    let closure_requested = !names.is_empty();
+    let (graph, title, file_name) = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        get_export_graph(&mut conn, &odoo_version, &version_name, &names)
+    })
+    .await?;
+    if closure_requested && graph.nodes.is_empty() {
+        return Ok(HttpResponse::NotFound().finish());
+    }
+    let graph = if query.external || format == ExportFormat::GraphMl {
+        graph
+    } else {
+        graph.without_external()
+    };
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            "Content-Disposition",
+            format!(
+                "inline; filename=\"{}.{}\"",
+                file_name_safe(&file_name),
+                format.extension()
+            ),
+        ))
+        .body(atlas_export::render(&graph, &title, format)))
+}
+
+/// Organizations, repositories and categories of the graph, for the
+/// page's filters.
+#[get("/atlas/facets/{odoo_version}")]
+pub async fn route_atlas_facets(
     pool: web::Data<Pool>,
     path: web::Path<String>,
 ) -> Result<HttpResponse, AWError> {
-    let odoo_version = path.into_inner();
+    let odoo_version = odoo_version_string_to_u8(&path.into_inner());
     let result = web::block(move || {
         let mut conn = pool.get().unwrap();
-        get_graph_data(&mut conn, &odoo_version_string_to_u8(&odoo_version))
+        get_graph_facets(&mut conn, &odoo_version)
     })
     .await?;
     Ok(HttpResponse::Ok().json(result))
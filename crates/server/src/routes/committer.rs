@@ -2,9 +2,9 @@
 use std::collections::{HashMap, HashSet};
 
 use actix_web::{get, web, HttpRequest, Responder, Result};
-use diesel::sqlite::SqliteConnection;
 use minijinja::context;
 use serde::{Deserialize, Serialize};
+use sqlitedb::DbConnection;
 
 use crate::minijinja_renderer::MiniJinjaRenderer;
 use crate::utils::get_minijinja_context;
@@ -142,7 +142,7 @@ pub struct CommitterStats {
     pub quijote_fun_fact_chars: Option<i64>,
 }
 
-fn build_fun_facts(conn: &mut SqliteConnection, name: &str) -> Option<CommitterFunFacts> {
+fn build_fun_facts(conn: &mut DbConnection, name: &str) -> Option<CommitterFunFacts> {
     let periods = models::module_committer_period::get_activity_by_committer_name(conn, name);
     let first = periods.first()?;
     let last = periods.last()?;
@@ -177,7 +177,7 @@ fn build_fun_facts(conn: &mut SqliteConnection, name: &str) -> Option<CommitterF
     })
 }
 
-fn build_committer_stats(conn: &mut SqliteConnection, name: &str) -> CommitterStats {
+fn build_committer_stats(conn: &mut DbConnection, name: &str) -> CommitterStats {
     let rows = models::module_committer::get_activity_by_committer_name(conn, name);
 
     let mut versions: HashMap<i32, CommitterVersionGroup> = HashMap::new();
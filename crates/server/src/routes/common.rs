@@ -1,9 +1,11 @@
 // Copyright Alexandre D. Díaz
+use std::collections::HashMap;
+
 use actix_web::{get, web, Error as AWError, HttpResponse, Result};
 use base64::Engine;
-use diesel::sqlite::SqliteConnection;
-use oghutils::version::odoo_version_u8_to_string;
+use oghutils::version::{odoo_version_string_to_u8, odoo_version_u8_to_string};
 use serde::{Deserialize, Serialize};
+use sqlitedb::DbConnection;
 
 use sqlitedb::{models, Pool};
 
@@ -28,6 +30,26 @@ pub struct ModuleListInfo {
     pub description: Option<String>,
     pub category: Option<String>,
     pub org_name: String,
+    pub repository: String,
+    /// Health score by Odoo version.
+    pub health: HashMap<String, i32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ModuleSearchInfo {
+    pub versions: Vec<String>,
+    pub technical_name: String,
+    pub name: String,
+    pub org_name: String,
+    pub repository: String,
+    /// HTML-escaped, with the matched words in `<mark>`.
+    pub snippet: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModuleSearchRequest {
+    term: String,
+    odoo_version: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -53,7 +75,7 @@ pub struct CommitterListInfo {
     pub modules_touched: i64,
 }
 
-fn get_odoo_versions(conn: &mut SqliteConnection) -> Vec<OdooVersionInfo> {
+fn get_odoo_versions(conn: &mut DbConnection) -> Vec<OdooVersionInfo> {
     models::module::get_odoo_versions(conn)
         .into_iter()
         .map(|x| OdooVersionInfo {
@@ -63,7 +85,7 @@ fn get_odoo_versions(conn: &mut SqliteConnection) -> Vec<OdooVersionInfo> {
         .collect()
 }
 
-fn get_odoo_module_count(conn: &mut SqliteConnection) -> Vec<ModuleCountInfo> {
+fn get_odoo_module_count(conn: &mut DbConnection) -> Vec<ModuleCountInfo> {
     models::module::count_organization(conn)
         .into_iter()
         .map(|x| ModuleCountInfo {
@@ -74,10 +96,23 @@ fn get_odoo_module_count(conn: &mut SqliteConnection) -> Vec<ModuleCountInfo> {
         .collect()
 }
 
-fn get_odoo_module_list(conn: &mut SqliteConnection) -> Vec<ModuleListInfo> {
+fn get_odoo_module_list(conn: &mut DbConnection) -> Vec<ModuleListInfo> {
+    let mut health: HashMap<(String, String), HashMap<String, i32>> = HashMap::new();
+    for module_score in models::module_health::get_scores(conn) {
+        health
+            .entry((module_score.organization, module_score.technical_name))
+            .or_default()
+            .insert(
+                odoo_version_u8_to_string(&(module_score.version_odoo as u8)),
+                module_score.score,
+            );
+    }
     models::module::list(conn)
         .into_iter()
         .map(|x| ModuleListInfo {
+            health: health
+                .remove(&(x.org_name.clone(), x.technical_name.clone()))
+                .unwrap_or_default(),
             versions: x
                 .versions_odoo
                 .iter()
@@ -88,11 +123,45 @@ fn get_odoo_module_list(conn: &mut SqliteConnection) -> Vec<ModuleListInfo> {
             description: x.description,
             category: x.category,
             org_name: x.org_name,
+            repository: x.repository,
         })
         .collect()
 }
 
-fn get_odoo_contributor_rank(conn: &mut SqliteConnection) -> Vec<ContribRankInfo> {
+// One entry per organization and module, in the order of its best-ranked
+// version; the snippet comes from that version too.
+fn search_odoo_modules(
+    conn: &mut DbConnection,
+    term: &str,
+    version_odoo: Option<&u8>,
+) -> Vec<ModuleSearchInfo> {
+    let mut results: Vec<ModuleSearchInfo> = Vec::new();
+    for hit in models::module_search::search(conn, term, version_odoo, 200) {
+        let version = odoo_version_u8_to_string(&(hit.version_odoo as u8));
+        match results
+            .iter_mut()
+            .find(|r| r.org_name == hit.organization && r.technical_name == hit.technical_name)
+        {
+            Some(result) => {
+                if !result.versions.contains(&version) {
+                    result.versions.push(version);
+                }
+            }
+            None => results.push(ModuleSearchInfo {
+                versions: vec![version],
+                technical_name: hit.technical_name,
+                name: hit.name,
+                org_name: hit.organization,
+                repository: hit.repository,
+                snippet: hit.snippet,
+            }),
+        }
+    }
+    results.truncate(50);
+    results
+}
+
+fn get_odoo_contributor_rank(conn: &mut DbConnection) -> Vec<ContribRankInfo> {
     models::module::rank_contributor(conn)
         .into_iter()
         .map(|x| ContribRankInfo {
@@ -104,7 +173,7 @@ fn get_odoo_contributor_rank(conn: &mut SqliteConnection) -> Vec<ContribRankInfo
         .collect()
 }
 
-fn get_odoo_committer_rank(conn: &mut SqliteConnection) -> Vec<CommitterRankInfo> {
+fn get_odoo_committer_rank(conn: &mut DbConnection) -> Vec<CommitterRankInfo> {
     models::module::rank_committer(conn)
         .into_iter()
         .map(|x| CommitterRankInfo {
@@ -116,7 +185,7 @@ fn get_odoo_committer_rank(conn: &mut SqliteConnection) -> Vec<CommitterRankInfo
         .collect()
 }
 
-fn get_odoo_committer_list(conn: &mut SqliteConnection) -> Vec<CommitterListInfo> {
+fn get_odoo_committer_list(conn: &mut DbConnection) -> Vec<CommitterListInfo> {
     models::committer::list(conn)
         .into_iter()
         .map(|x| CommitterListInfo {
@@ -147,6 +216,26 @@ pub async fn route_odoo_module_list(pool: web::Data<Pool>) -> Result<HttpRespons
     Ok(HttpResponse::Ok().json(result))
 }
 
+/// Ranked full-text search for the module search box, which otherwise
+/// filters `/common/odoo/module/list` client-side.
+#[get("/common/odoo/module/search")]
+pub async fn route_odoo_module_search(
+    pool: web::Data<Pool>,
+    info: web::Query<ModuleSearchRequest>,
+) -> Result<HttpResponse, AWError> {
+    let params = info.into_inner();
+    let version_odoo = params
+        .odoo_version
+        .filter(|v| !v.is_empty())
+        .map(|v| odoo_version_string_to_u8(&v));
+    let result = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        search_odoo_modules(&mut conn, &params.term, version_odoo.as_ref())
+    })
+    .await?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
 // Served as an actual image, not embedded as base64 in the bulk module list
 // above - that list can hold thousands of entries, and only the handful of
 // results rendered in the search dropdown ever need their icon loaded.
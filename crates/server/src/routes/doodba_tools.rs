@@ -2,10 +2,10 @@
 use crate::routes::api::v1::module::process_modules_db;
 use actix_multipart::form::{text::Text, MultipartForm};
 use actix_web::{get, post, web, Error as AWError, HttpRequest, HttpResponse, Responder, Result};
-use diesel::sqlite::SqliteConnection;
 use minijinja::context;
 use oghutils::version::{odoo_version_string_to_u8, odoo_version_u8_to_string};
 use serde::Serialize;
+use sqlitedb::DbConnection;
 use std::collections::{HashMap, HashSet};
 
 use sqlitedb::{models, Pool};
@@ -69,7 +69,7 @@ pub async fn route_doodba_converter(
 }
 
 fn get_doodba_addons(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     mods: &[Text<String>],
     odoo_version: &str,
 ) -> Vec<sqlitedb::models::module::ModuleRepositoryInfo> {
@@ -122,7 +122,7 @@ pub struct DoodbaAddonsResponse {
 }
 
 fn get_doodba_addons_full(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     mods: &[Text<String>],
     odoo_version: &str,
 ) -> DoodbaAddonsResponse {
@@ -224,12 +224,35 @@ pub struct MigrationMissingModuleInfo {
     pub organization: String,
 }
 
+// A requested module that OpenUpgrade renamed or merged into another one by
+// this version (see [[module_lineage]] model); `successor` is what the step
+// classifies (and puts in addons.yaml) in its place.
+#[derive(Debug, Serialize, Clone)]
+pub struct MigrationRenamedModuleInfo {
+    pub technical_name: String,
+    pub successor: String,
+    pub kind: String,
+}
+
+// OpenUpgrade's coverage of a step's module for the jump into this step's
+// version (see [[module_upgrade_coverage]] model) - only core Odoo modules
+// are listed in OpenUpgrade's tables, so most OCA modules have none.
+#[derive(Debug, Serialize, Clone)]
+pub struct MigrationUpgradeCoverageInfo {
+    pub technical_name: String,
+    pub status: String,
+    pub note: Option<String>,
+    pub covered: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct MigrationPlanStepResponse {
     pub version: String,
     pub merged: Vec<models::module::ModuleRepositoryInfo>,
     pub pending: Vec<MigrationPendingModuleInfo>,
     pub missing: Vec<MigrationMissingModuleInfo>,
+    pub renamed: Vec<MigrationRenamedModuleInfo>,
+    pub upgrade_coverage: Vec<MigrationUpgradeCoverageInfo>,
     // Requested modules the system has never tracked anywhere (no merged row,
     // no PR/MR, in any version) - reported separately from `missing` since we
     // genuinely don't know whether they exist for this version or not.
@@ -254,7 +277,7 @@ pub async fn route_doodba_migration_plan(
 }
 
 fn get_pending_modules(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     technical_names: &[String],
     version_odoo: &u8,
 ) -> Vec<MigrationPendingModuleInfo> {
@@ -286,6 +309,50 @@ fn get_pending_modules(
         .collect()
 }
 
+/// "Registered" = the system has seen this technical_name somewhere before
+/// (merged in any version, or PR'd for any version) - regardless of the step
+/// being evaluated. Modules outside this map are never flagged as missing: we
+/// have no data on them (private/unindexed repo, typo, ...), so a version
+/// comparison can't say whether they exist there or not. A module that moved
+/// to another repository by `version_odoo` is reported under the new one.
+fn get_registered_modules(
+    conn: &mut DbConnection,
+    technical_names: &[String],
+    version_odoo: &u8,
+) -> HashMap<String, (String, String)> {
+    let mut registered: HashMap<String, (String, String)> =
+        models::module::get_repository_org_by_technical_names(conn, technical_names)
+            .into_iter()
+            .map(|info| {
+                (
+                    info.technical_name,
+                    (info.repository_name, info.organization),
+                )
+            })
+            .collect();
+    for (technical_name, repository_name, organization) in
+        models::pull_request::get_repository_org_by_technical_names(conn, technical_names)
+    {
+        registered
+            .entry(technical_name)
+            .or_insert((repository_name, organization));
+    }
+    for (technical_name, repo_org) in registered.iter_mut() {
+        let Some(gh_repo_id) =
+            models::module_lineage::get_latest_move(conn, technical_name, version_odoo)
+                .and_then(|lineage| lineage.successor_gh_repository_id)
+        else {
+            continue;
+        };
+        if let Some(repo) = models::gh_repository::get_by_id(conn, &gh_repo_id) {
+            if let Some(org) = models::gh_organization::get_by_id(conn, &repo.gh_organization_id) {
+                *repo_org = (repo.name, org.name);
+            }
+        }
+    }
+    registered
+}
+
 /// For each Odoo version between `from_version` and `to_version` (inclusive,
 /// only versions actually present in the DB), classifies every requested module
 /// as merged / pending-PR / missing / unknown. `from_version` itself is included
@@ -300,8 +367,14 @@ fn get_pending_modules(
 /// the same `version_odoo`), so they only ever land in `merged`, feeding
 /// into that step's `addons.yaml`; only the originally requested modules can
 /// end up `pending`/`missing`/`unknown`.
+///
+/// Requested modules are followed through their rename/merge lineage first:
+/// at a step where OpenUpgrade renamed `a` into `b`, `b` is what gets
+/// classified, and `a` is listed in `renamed` instead of showing up missing.
+/// Each step also carries OpenUpgrade's coverage of its modules for the jump
+/// into that version, the real question for core Odoo modules.
 fn get_migration_plan(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     mods: &[Text<String>],
     from_version: &str,
     to_version: Option<&str>,
@@ -318,29 +391,6 @@ fn get_migration_plan(
         .collect();
     steps.sort_unstable();
 
-    // "Registered" = the system has seen this technical_name somewhere before
-    // (merged in any version, or PR'd for any version) - regardless of the
-    // step being evaluated. Modules outside this set are never flagged as
-    // missing: we have no data on them (private/unindexed repo, typo, ...),
-    // so a version comparison can't say whether they exist there or not.
-    let mut registered: HashMap<String, (String, String)> =
-        models::module::get_repository_org_by_technical_names(conn, &modules)
-            .into_iter()
-            .map(|info| {
-                (
-                    info.technical_name,
-                    (info.repository_name, info.organization),
-                )
-            })
-            .collect();
-    for (technical_name, repository_name, organization) in
-        models::pull_request::get_repository_org_by_technical_names(conn, &modules)
-    {
-        registered
-            .entry(technical_name)
-            .or_insert((repository_name, organization));
-    }
-
     // Seeded once from migrations (see sqlitedb::models::mod::tests::
     // test_dependency_type_seeded) - always present.
     let module_dep_type_id = models::dependency_type::get_by_name(conn, "module")
@@ -352,6 +402,30 @@ fn get_migration_plan(
         .map(|version_odoo| {
             let version_u8 = version_odoo as u8;
 
+            let mut renamed = Vec::new();
+            let mut step_modules: Vec<String> = Vec::with_capacity(modules.len());
+            for technical_name in &modules {
+                let step_name = match models::module_lineage::resolve_successor(
+                    conn,
+                    technical_name,
+                    &version_u8,
+                ) {
+                    Some(lineage) => {
+                        renamed.push(MigrationRenamedModuleInfo {
+                            technical_name: technical_name.clone(),
+                            successor: lineage.successor_technical_name.clone(),
+                            kind: lineage.kind,
+                        });
+                        lineage.successor_technical_name
+                    }
+                    None => technical_name.clone(),
+                };
+                if !step_modules.contains(&step_name) {
+                    step_modules.push(step_name);
+                }
+            }
+            let registered = get_registered_modules(conn, &step_modules, &version_u8);
+
             // Requested modules actually merged at this version seed the
             // dependency walk; their transitive Odoo deps at this version
             // join the set to classify, so a required-but-unlisted module
@@ -360,9 +434,12 @@ fn get_migration_plan(
             // migration blocker - collected separately since it can't be
             // recursed into, and isn't in `registered` (built from the
             // originally requested list only).
-            let seed_modules =
-                models::module::get_by_technical_name_odoo_version(conn, &modules, &version_u8);
-            let mut expanded_modules = modules.clone();
+            let seed_modules = models::module::get_by_technical_name_odoo_version(
+                conn,
+                &step_modules,
+                &version_u8,
+            );
+            let mut expanded_modules = step_modules.clone();
             let mut dep_blocker_names: Vec<String> = Vec::new();
             for seed in &seed_modules {
                 let deps = models::dependency::get_full_dependency_info_with_unresolved(
@@ -392,9 +469,22 @@ fn get_migration_plan(
 
             let merged =
                 models::module::get_module_repository(conn, &version_u8, &expanded_modules);
+            let upgrade_coverage = models::module_upgrade_coverage::get_by_technical_names_version(
+                conn,
+                &expanded_modules,
+                &version_u8,
+            )
+            .into_iter()
+            .map(|coverage| MigrationUpgradeCoverageInfo {
+                covered: coverage.is_covered(),
+                technical_name: coverage.technical_name,
+                status: coverage.status,
+                note: coverage.note,
+            })
+            .collect();
             let merged_names: HashSet<&str> =
                 merged.iter().map(|m| m.technical_name.as_str()).collect();
-            let remaining: Vec<String> = modules
+            let remaining: Vec<String> = step_modules
                 .iter()
                 .filter(|m| !merged_names.contains(m.as_str()))
                 .cloned()
@@ -429,26 +519,7 @@ fn get_migration_plan(
                 dep_blocker_names.retain(|n| !dep_pending_names.contains(n.as_str()));
                 pending.extend(dep_pending);
 
-                let mut dep_registered: HashMap<String, (String, String)> =
-                    models::module::get_repository_org_by_technical_names(conn, &dep_blocker_names)
-                        .into_iter()
-                        .map(|info| {
-                            (
-                                info.technical_name,
-                                (info.repository_name, info.organization),
-                            )
-                        })
-                        .collect();
-                for (technical_name, repository_name, organization) in
-                    models::pull_request::get_repository_org_by_technical_names(
-                        conn,
-                        &dep_blocker_names,
-                    )
-                {
-                    dep_registered
-                        .entry(technical_name)
-                        .or_insert((repository_name, organization));
-                }
+                let dep_registered = get_registered_modules(conn, &dep_blocker_names, &version_u8);
                 for technical_name in dep_blocker_names {
                     match dep_registered.get(&technical_name) {
                         Some((repository_name, organization)) => {
@@ -468,6 +539,8 @@ fn get_migration_plan(
                 merged,
                 pending,
                 missing,
+                renamed,
+                upgrade_coverage,
                 unknown,
             }
         })
@@ -500,14 +573,10 @@ pub async fn route_doodba_migration_plan_addons(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use diesel::Connection;
-    use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
     use sqlitedb::models::{
         dependency_module, dependency_type, gh_organization, gh_repository, module, pull_request,
     };
 
-    const MIGRATIONS: EmbeddedMigrations = embed_migrations!("../../migrations");
-
     #[test]
     fn test_validate_request_caps_modules_and_rejects_bad_version() {
         let max = *SERVER_CONFIG.get_doodba_max_modules();
@@ -523,14 +592,12 @@ mod tests {
         assert!(validate_request(&ok, &["17.0", "not-a-version"]).is_err());
     }
 
-    fn setup_db() -> SqliteConnection {
-        let mut conn = SqliteConnection::establish(":memory:").unwrap();
-        conn.run_pending_migrations(MIGRATIONS).unwrap();
-        conn
+    fn setup_db() -> DbConnection {
+        sqlitedb::establish_test_connection()
     }
 
     fn make_module(
-        conn: &mut SqliteConnection,
+        conn: &mut DbConnection,
         tech_name: &str,
         git_org: &str,
         git_repo: &str,
@@ -543,6 +610,7 @@ mod tests {
                 version_odoo,
                 name: tech_name.to_string(),
                 version_module: "1.0.0".to_string(),
+                summary: String::new(),
                 description: String::new(),
                 installation: String::new(),
                 usage: String::new(),
@@ -575,7 +643,7 @@ mod tests {
     }
 
     // Records that `from` depends on `to` (both at the same version_odoo).
-    fn link_module_dep(conn: &mut SqliteConnection, from: &module::Model, to: &module::Model) {
+    fn link_module_dep(conn: &mut DbConnection, from: &module::Model, to: &module::Model) {
         let dep_type = dependency_type::get_by_name(conn, "module").unwrap();
         dependency_module::add(conn, &dep_type.id, &to.technical_name, &from.id).unwrap();
     }
@@ -584,13 +652,13 @@ mod tests {
     // for it exists at `from`'s version - covers the "unported dependency"
     // blocker case, which `link_module_dep` can't express (it requires an
     // actual `module::Model` at the same version).
-    fn declare_dep_by_name(conn: &mut SqliteConnection, from: &module::Model, dep_name: &str) {
+    fn declare_dep_by_name(conn: &mut DbConnection, from: &module::Model, dep_name: &str) {
         let dep_type = dependency_type::get_by_name(conn, "module").unwrap();
         dependency_module::add(conn, &dep_type.id, dep_name, &from.id).unwrap();
     }
 
     fn make_pending_pr(
-        conn: &mut SqliteConnection,
+        conn: &mut DbConnection,
         tech_name: &str,
         git_org: &str,
         git_repo: &str,
@@ -658,6 +726,92 @@ mod tests {
         assert_eq!(step.unknown, vec!["mod_never_seen".to_string()]);
     }
 
+    // A module OpenUpgrade renamed must be followed to its successor instead
+    // of being reported missing, and a module moved to another repository
+    // must be reported missing under the new one.
+    #[test]
+    fn test_migration_plan_follows_module_lineage() {
+        let mut conn = setup_db();
+        make_module(&mut conn, "old_name", "OCA", "repoA", 160);
+        make_module(&mut conn, "new_name", "OCA", "repoA", 170);
+        make_module(&mut conn, "mod_moved", "OCA", "repoA", 160);
+        let moved_v18 = make_module(&mut conn, "mod_moved", "OCA", "repoB", 180);
+        models::module_lineage::import_apriori(
+            &mut conn,
+            &170u8,
+            &[("old_name".to_string(), "new_name".to_string())],
+            &[],
+        )
+        .unwrap();
+        models::module_lineage::add(
+            &mut conn,
+            models::module_lineage::KIND_MOVED,
+            "mod_moved",
+            None,
+            "mod_moved",
+            Some(&moved_v18.gh_repository_id),
+            &170u8,
+            models::module_lineage::SOURCE_COLLECTOR,
+        )
+        .unwrap();
+
+        let modules = vec![Text("old_name".to_string()), Text("mod_moved".to_string())];
+        let steps = get_migration_plan(&mut conn, &modules, "16.0", Some("17.0"));
+        assert_eq!(steps.len(), 2);
+
+        assert!(steps[0].renamed.is_empty());
+        assert_eq!(steps[0].merged.len(), 2);
+
+        let step = &steps[1];
+        assert_eq!(
+            step.merged
+                .iter()
+                .map(|m| m.technical_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["new_name"]
+        );
+        assert_eq!(step.renamed.len(), 1);
+        assert_eq!(step.renamed[0].technical_name, "old_name");
+        assert_eq!(step.renamed[0].successor, "new_name");
+        assert_eq!(step.missing.len(), 1);
+        assert_eq!(step.missing[0].technical_name, "mod_moved");
+        assert_eq!(step.missing[0].repository_name, "repoB");
+        assert!(step.unknown.is_empty());
+    }
+
+    #[test]
+    fn test_migration_plan_reports_upgrade_coverage() {
+        let mut conn = setup_db();
+        make_module(&mut conn, "account", "odoo", "odoo", 170);
+        make_module(&mut conn, "sale", "odoo", "odoo", 170);
+        models::module_upgrade_coverage::replace_for_version(
+            &mut conn,
+            &170u8,
+            &[
+                models::module_upgrade_coverage::UpgradeCoverageInfo {
+                    technical_name: "account".to_string(),
+                    status: "Done".to_string(),
+                    ..Default::default()
+                },
+                models::module_upgrade_coverage::UpgradeCoverageInfo {
+                    technical_name: "sale".to_string(),
+                    status: "Partial".to_string(),
+                    ..Default::default()
+                },
+            ],
+        )
+        .unwrap();
+
+        let modules = vec![Text("account".to_string()), Text("sale".to_string())];
+        let steps = get_migration_plan(&mut conn, &modules, "17.0", Some("17.0"));
+        let coverage = &steps[0].upgrade_coverage;
+        assert_eq!(coverage.len(), 2);
+        assert_eq!(coverage[0].technical_name, "account");
+        assert!(coverage[0].covered);
+        assert_eq!(coverage[1].technical_name, "sale");
+        assert!(!coverage[1].covered);
+    }
+
     // Proves dependencies are re-resolved fresh per step, not accumulated
     // from a previous step: "dep" is a real dependency of "root" at 16.0
     // only. Both module rows also exist at 17.0, but the dependency link
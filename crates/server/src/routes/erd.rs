@@ -0,0 +1,106 @@
+// Copyright Alexandre D. Díaz
+use actix_web::{get, web, Error as AWError, HttpResponse, Result};
+use oghutils::version::odoo_version_string_to_u8;
+use serde::Deserialize;
+use sqlitedb::models::module_erd::{self, ErdInfo};
+use sqlitedb::DbConnection;
+
+use crate::utils::technical_names;
+
+use sqlitedb::{models, Pool};
+
+// Every module of the set costs a query per model it touches.
+const MAX_ERD_MODULES: usize = 200;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ErdQuery {
+    /// Comma-separated technical names.
+    pub modules: Option<String>,
+    /// Only the modules of this organization.
+    pub org: Option<String>,
+    /// Module version to draw instead of the latest one.
+    pub version: Option<String>,
+    /// One entity per model, with the fields of every module of the set.
+    #[serde(default)]
+    pub merge: bool,
+}
+
+impl ErdQuery {
+    fn technical_names(&self) -> Vec<String> {
+        technical_names([&self.modules])
+    }
+}
+
+fn get_erd(
+    conn: &mut DbConnection,
+    odoo_version: &u8,
+    names: &[String],
+    query: &ErdQuery,
+) -> Option<ErdInfo> {
+    let mut modules = match query.org.as_deref().filter(|org| !org.is_empty()) {
+        Some(org) => names
+            .iter()
+            .flat_map(|name| {
+                models::module::get_by_technical_name_odoo_version_organization_name(
+                    conn,
+                    name,
+                    odoo_version,
+                    org,
+                )
+            })
+            .collect(),
+        None => models::module::get_by_technical_name_odoo_version(conn, names, odoo_version),
+    };
+    if modules.is_empty() {
+        return None;
+    }
+    // In the order asked for, which is the order the entities are laid out.
+    modules.sort_by_key(|m| names.iter().position(|name| *name == m.technical_name));
+    Some(module_erd::build(
+        conn,
+        &modules,
+        query.version.as_deref().filter(|v| !v.is_empty()),
+        query.merge,
+    ))
+}
+
+/// The entity-relationship diagram of the models of a module set, as SVG,
+/// Mermaid or JSON.
+#[get("/erd/{odoo_version}/{format}")]
+pub async fn route(
+    pool: web::Data<Pool>,
+    path: web::Path<(String, String)>,
+    query: web::Query<ErdQuery>,
+) -> Result<HttpResponse, AWError> {
+    let (odoo_version, format) = path.into_inner();
+    if !matches!(format.as_str(), "svg" | "mermaid" | "mmd" | "json") {
+        return Err(actix_web::error::ErrorBadRequest(format!(
+            "unknown ERD format: {format:?} (svg, mermaid or json)"
+        )));
+    }
+    let names = query.technical_names();
+    if names.is_empty() || names.len() > MAX_ERD_MODULES {
+        return Err(actix_web::error::ErrorBadRequest(format!(
+            "expected between 1 and {MAX_ERD_MODULES} modules, got {}",
+            names.len()
+        )));
+    }
+    let odoo_version = odoo_version_string_to_u8(&odoo_version);
+    let erd = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        get_erd(&mut conn, &odoo_version, &names, &query)
+    })
+    .await?;
+    let Some(erd) = erd else {
+        return Ok(HttpResponse::NotFound().finish());
+    };
+    Ok(match format.as_str() {
+        "svg" => HttpResponse::Ok()
+            .content_type("image/svg+xml")
+            .body(module_erd::to_svg(&erd)),
+        "json" => HttpResponse::Ok().json(erd),
+        _ => HttpResponse::Ok()
+            .content_type("text/plain; charset=utf-8")
+            .body(module_erd::to_mermaid(&erd)),
+    })
+}
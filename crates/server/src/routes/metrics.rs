@@ -0,0 +1,14 @@
+// Copyright Alexandre D. Díaz
+use actix_web::{get, web, HttpResponse, Responder, Result};
+
+use crate::metrics;
+use sqlitedb::Pool;
+
+#[get("/metrics")]
+pub async fn route(pool: web::Data<Pool>) -> Result<impl Responder> {
+    let body = web::block(move || metrics::gather(&pool)).await?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .insert_header(("Cache-Control", "no-store"))
+        .body(body))
+}
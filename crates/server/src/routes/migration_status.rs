@@ -0,0 +1,66 @@
+// Copyright Alexandre D. Díaz
+use actix_web::{get, web, Error as AWError, HttpRequest, HttpResponse, Result};
+use minijinja::{context, Value};
+use serde::Deserialize;
+use sqlitedb::models::migration_status::{
+    MigrationStatus, MigrationStatusFilter, PortGapStatusFilter,
+};
+
+use crate::minijinja_renderer::MiniJinjaRenderer;
+use crate::routes::report::{self, ReportQuery};
+use crate::utils::non_empty;
+
+use oghutils::version::odoo_version_string_to_u8;
+use sqlitedb::{models, DbConnection, Pool};
+
+/// Filters of the /migration-status page and its API. `odoo_version` is the
+/// version ported to; empty values are the same as missing ones.
+#[derive(Debug, Default, Deserialize)]
+pub struct MigrationStatusQuery {
+    pub odoo_version: Option<String>,
+    pub org: Option<String>,
+    pub repo: Option<String>,
+    #[serde(default)]
+    pub status: PortGapStatusFilter,
+}
+
+impl ReportQuery for MigrationStatusQuery {
+    type Filter = MigrationStatusFilter;
+    type Report = MigrationStatus;
+
+    const PAGE_NAME: &'static str = "migration_status";
+
+    fn filter(&self) -> MigrationStatusFilter {
+        MigrationStatusFilter {
+            version_odoo: non_empty(&self.odoo_version)
+                .map(|version| odoo_version_string_to_u8(&version)),
+            org: non_empty(&self.org),
+            repo: non_empty(&self.repo),
+            status: self.status,
+        }
+    }
+
+    fn build(conn: &mut DbConnection, filter: &MigrationStatusFilter) -> MigrationStatus {
+        models::migration_status::build(conn, filter)
+    }
+
+    fn page_context(&self, status: MigrationStatus) -> Value {
+        context!(
+            filter_org => non_empty(&self.org).unwrap_or_default(),
+            filter_repo => non_empty(&self.repo).unwrap_or_default(),
+            filter_status => self.status,
+            status => status,
+        )
+    }
+}
+
+/// What each repository still has to port to the next Odoo version.
+#[get("/migration-status")]
+pub async fn route(
+    pool: web::Data<Pool>,
+    tmpl_env: MiniJinjaRenderer,
+    req: HttpRequest,
+    query: web::Query<MigrationStatusQuery>,
+) -> Result<HttpResponse, AWError> {
+    report::page(pool, tmpl_env, req, query).await
+}
@@ -7,12 +7,21 @@ pub mod committers;
 pub mod common;
 pub mod dashboard;
 pub mod doodba_tools;
+pub mod erd;
 pub mod favorites;
 pub mod logs;
 pub mod mcp_info;
+pub mod metrics;
+pub mod migration_status;
+pub mod model_explorer;
 pub mod module;
 pub mod modules;
 pub mod osv;
 pub mod pack;
+pub mod report;
+pub mod repository;
 pub mod robots;
+pub mod route_catalog;
+pub mod runs;
+pub mod security;
 pub mod sitemap;
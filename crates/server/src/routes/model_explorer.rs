@@ -0,0 +1,125 @@
+// Copyright Alexandre D. Díaz
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder, Result};
+use minijinja::context;
+use serde::Deserialize;
+
+use crate::minijinja_renderer::MiniJinjaRenderer;
+use crate::utils::{get_minijinja_context, non_empty};
+
+use oghutils::version::{odoo_version_string_to_u8, odoo_version_u8_to_string};
+use sqlitedb::{models, Pool};
+
+const MAX_MODEL_RESULTS: i64 = 100;
+const MAX_FIELD_RESULTS: i64 = 200;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ModelExplorerQuery {
+    /// Empty means the newest version for models and every version for
+    /// fields.
+    pub odoo_version: Option<String>,
+    pub model: Option<String>,
+    pub field: Option<String>,
+}
+
+/// Model and field search across every collected module.
+#[get("/models")]
+pub async fn route(
+    pool: web::Data<Pool>,
+    tmpl_env: MiniJinjaRenderer,
+    req: HttpRequest,
+    query: web::Query<ModelExplorerQuery>,
+) -> Result<impl Responder> {
+    let odoo_version = non_empty(&query.odoo_version);
+    let model_term = non_empty(&query.model);
+    let field_term = non_empty(&query.field);
+    let (odoo_versions, model_version, found_models, found_fields) = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        let mut odoo_versions = models::module::get_odoo_versions(&mut conn);
+        odoo_versions.sort_unstable_by(|a, b| b.cmp(a));
+        let version_odoo = odoo_version.as_deref().map(odoo_version_string_to_u8);
+        let model_version = version_odoo.or(odoo_versions.first().map(|v| *v as u8));
+        let found_models = match (&model_term, model_version) {
+            (Some(term), Some(version_odoo)) => models::module_model::search_current_model_names(
+                &mut conn,
+                &version_odoo,
+                term,
+                MAX_MODEL_RESULTS,
+            ),
+            _ => Vec::new(),
+        };
+        let found_fields = match &field_term {
+            Some(term) => models::model_explorer::search_fields(
+                &mut conn,
+                term,
+                version_odoo.as_ref(),
+                MAX_FIELD_RESULTS,
+            ),
+            None => Vec::new(),
+        };
+        let odoo_versions: Vec<String> = odoo_versions
+            .into_iter()
+            .map(|v| odoo_version_u8_to_string(&(v as u8)))
+            .collect();
+        (
+            odoo_versions,
+            model_version.map(|v| odoo_version_u8_to_string(&v)),
+            found_models,
+            found_fields,
+        )
+    })
+    .await?;
+
+    tmpl_env.render(
+        "pages/models.html",
+        context!(
+            ..get_minijinja_context(&req),
+            ..context!(
+                page_name => "models",
+                odoo_versions => odoo_versions,
+                odoo_version => non_empty(&query.odoo_version),
+                model_version => model_version,
+                model_term => non_empty(&query.model),
+                field_term => non_empty(&query.field),
+                found_models => found_models,
+                found_fields => found_fields,
+                max_field_results => MAX_FIELD_RESULTS,
+            )
+        ),
+    )
+}
+
+/// Every module defining or extending one model at one Odoo version.
+#[get("/models/{odoo_version}/{model_name}")]
+pub async fn route_model(
+    pool: web::Data<Pool>,
+    tmpl_env: MiniJinjaRenderer,
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse> {
+    let (odoo_version, model_name) = path.into_inner();
+    let version_odoo = odoo_version_string_to_u8(&odoo_version);
+    let info = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        models::model_explorer::get(&mut conn, &version_odoo, &model_name)
+    })
+    .await?;
+    let Some(info) = info else {
+        return Ok(HttpResponse::NotFound().finish());
+    };
+    let num_definers = info.modules.iter().filter(|m| m.is_new_model).count();
+    let html = tmpl_env.render(
+        "pages/model.html",
+        context!(
+            ..get_minijinja_context(&req),
+            ..context!(
+                page_name => "models",
+                odoo_version => odoo_version_u8_to_string(&version_odoo),
+                num_definers => num_definers,
+                model => info,
+            )
+        ),
+    )?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(html.0))
+}
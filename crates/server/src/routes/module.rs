@@ -2,9 +2,9 @@
 use std::collections::{HashMap, HashSet};
 
 use actix_web::{get, web, HttpRequest, HttpResponse, Responder, Result};
-use diesel::sqlite::SqliteConnection;
 use minijinja::context;
 use serde::{Deserialize, Serialize};
+use sqlitedb::DbConnection;
 
 use crate::minijinja_renderer::MiniJinjaRenderer;
 use crate::utils::get_minijinja_context;
@@ -45,7 +45,7 @@ pub struct ModulePullRequestInfo {
 }
 
 fn get_module_pull_requests(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     org: &str,
     module_technical_name: &str,
     merged_versions: &HashSet<i32>,
@@ -79,7 +79,7 @@ fn get_module_pull_requests(
 // version-history dropdown; shared by `route` (active tab, eager) and
 // `route_tab` (one lazily-loaded tab).
 fn build_module_context(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     modules: &[models::module::Model],
     version_module: Option<&str>,
 ) -> (
@@ -3,15 +3,15 @@ use std::collections::{BTreeMap, HashMap};
 
 use actix_web::{get, web, HttpRequest, HttpResponse, Responder, Result};
 use cached::{proc_macro::cached, stores::TimedSizedCache};
-use diesel::sqlite::SqliteConnection;
 use minijinja::context;
 use serde::{Deserialize, Serialize};
+use sqlitedb::DbConnection;
 
 use crate::config::SERVER_CONFIG;
 use crate::minijinja_renderer::MiniJinjaRenderer;
 use crate::utils::get_minijinja_context;
 
-use oghutils::version::odoo_version_u8_to_string;
+use oghutils::version::{odoo_version_string_to_u8, odoo_version_u8_to_string};
 use sqlitedb::{models, Pool};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -52,6 +52,7 @@ pub struct ModulesVersionGroup {
     pub security_warnings: Vec<ModuleSecurityFindingInfo>,
     pub avg_days_open: Option<f64>,
     pub closed_count: i64,
+    pub latency: Option<models::pull_request_history::LatencyStatsInfo>,
     pub most_changed: Option<models::module::ModuleFunFactInfo>,
     pub largest_module: Option<models::module::ModuleFunFactInfo>,
     pub newest_module: Option<models::module::ModuleLastCreatedInfo>,
@@ -96,7 +97,7 @@ fn get_group(
 }
 
 // The whole page is the same for every visitor (no query params), so it's
-// cached whole instead of per-query-param like `atlas::get_graph_data` -
+// cached whole instead of per-query-param -
 // `convert` ignores `conn` and always returns the same key, making this a
 // single-entry cache refreshed every `cache_ttl` seconds.
 #[cached(
@@ -110,7 +111,7 @@ fn get_group(
     "#,
     convert = r#"{ 0u8 }"#
 )]
-fn compute_modules_page_data(conn: &mut SqliteConnection) -> (i64, Vec<ModulesVersionGroup>) {
+fn compute_modules_page_data(conn: &mut DbConnection) -> (i64, Vec<ModulesVersionGroup>) {
     let modules_total = models::module::count_distinct(conn);
     let mut by_version: BTreeMap<i32, ModulesVersionGroup> = BTreeMap::new();
 
@@ -123,6 +124,13 @@ fn compute_modules_page_data(conn: &mut SqliteConnection) -> (i64, Vec<ModulesVe
         group.closed_count = stat.closed_count;
         avg_days_open_by_version.insert(stat.version_odoo, stat.avg_days);
     }
+    for stat in models::pull_request_history::latency_stats(
+        conn,
+        models::pull_request_history::LatencyGroup::Version,
+    ) {
+        let version_odoo = odoo_version_string_to_u8(&stat.key) as i32;
+        get_group(&mut by_version, version_odoo).latency = Some(stat);
+    }
 
     for pr in models::pull_request::get_all(conn) {
         let last_message_days = models::pull_request::days_since(pr.last_message_at.as_deref());
@@ -206,6 +214,11 @@ fn compute_modules_page_data(conn: &mut SqliteConnection) -> (i64, Vec<ModulesVe
     (modules_total, version_groups)
 }
 
+/// Hits and misses of `compute_modules_page_data`'s cache, for `/metrics`.
+pub fn cache_stats() -> (u64, u64) {
+    crate::metrics::cache_stats(&COMPUTE_MODULES_PAGE_DATA)
+}
+
 #[get("/modules")]
 pub async fn route(
     pool: web::Data<Pool>,
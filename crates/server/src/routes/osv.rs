@@ -1,8 +1,8 @@
 // Copyright Alexandre D. Díaz
 use actix_web::{get, web, HttpRequest, HttpResponse, Responder, Result};
-use diesel::sqlite::SqliteConnection;
 use minijinja::context;
 use serde::{Deserialize, Serialize};
+use sqlitedb::DbConnection;
 use std::collections::{BTreeMap, HashMap, HashSet};
 
 use crate::minijinja_renderer::MiniJinjaRenderer;
@@ -37,7 +37,7 @@ pub struct OSVVersionGroup {
     pub module_count: usize,
 }
 
-fn compute_osv_groups(conn: &mut SqliteConnection) -> Vec<OSVVersionGroup> {
+fn compute_osv_groups(conn: &mut DbConnection) -> Vec<OSVVersionGroup> {
     let osv_infos = models::dependency_osv::get_osv_info(conn);
     // Group package-first (not module-first): the same vulnerability is
     // shared by every module depending on the vulnerable package, so
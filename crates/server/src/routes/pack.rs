@@ -1,9 +1,9 @@
 // Copyright Alexandre D. Díaz
 use actix_web::{get, post, web, Error as AWError, HttpRequest, HttpResponse, Responder, Result};
-use diesel::sqlite::SqliteConnection;
 use minijinja::context;
 use oghutils::version::{odoo_version_string_to_u8, odoo_version_u8_to_string};
 use serde::{Deserialize, Serialize};
+use sqlitedb::DbConnection;
 
 use crate::minijinja_renderer::MiniJinjaRenderer;
 use crate::utils::get_minijinja_context;
@@ -51,7 +51,7 @@ pub struct PackModuleInfoResponse {
 // of dozens of modules would multiply badly. A pack detail page only needs
 // each listed module's own size at one version, nothing recursive.
 fn get_pack_module_info(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     refs: &[PackModuleRef],
     target_version: Option<u8>,
 ) -> Vec<PackModuleInfoResponse> {
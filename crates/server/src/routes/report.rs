@@ -0,0 +1,66 @@
+// Copyright Alexandre D. Díaz
+use actix_web::{web, Error as AWError, HttpRequest, HttpResponse, Result};
+use minijinja::{context, Value};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlitedb::{DbConnection, Pool};
+
+use crate::minijinja_renderer::MiniJinjaRenderer;
+use crate::utils::get_minijinja_context;
+
+/// The query string of a filtered report, served both as a page and through
+/// the API.
+pub trait ReportQuery: DeserializeOwned {
+    type Filter: Send + 'static;
+    type Report: Serialize + Send + 'static;
+
+    /// Name of the page, and of its template under `pages/`.
+    const PAGE_NAME: &'static str;
+
+    fn filter(&self) -> Self::Filter;
+
+    fn build(conn: &mut DbConnection, filter: &Self::Filter) -> Self::Report;
+
+    /// The page's template variables: the filters as selected and the report.
+    fn page_context(&self, report: Self::Report) -> Value;
+}
+
+pub async fn get_report<Q: ReportQuery>(
+    pool: web::Data<Pool>,
+    query: &Q,
+) -> Result<Q::Report, AWError> {
+    let filter = query.filter();
+    Ok(web::block(move || {
+        let mut conn = pool.get().unwrap();
+        Q::build(&mut conn, &filter)
+    })
+    .await?)
+}
+
+pub async fn page<Q: ReportQuery>(
+    pool: web::Data<Pool>,
+    tmpl_env: MiniJinjaRenderer,
+    req: HttpRequest,
+    query: web::Query<Q>,
+) -> Result<HttpResponse, AWError> {
+    let report = get_report(pool, &*query).await?;
+    let html = tmpl_env.render(
+        &format!("pages/{}.html", Q::PAGE_NAME),
+        context!(
+            ..get_minijinja_context(&req),
+            ..context!(page_name => Q::PAGE_NAME),
+            ..query.page_context(report)
+        ),
+    )?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(html.0))
+}
+
+pub async fn json<Q: ReportQuery>(
+    pool: web::Data<Pool>,
+    query: web::Query<Q>,
+) -> Result<HttpResponse, AWError> {
+    let report = get_report(pool, &*query).await?;
+    Ok(HttpResponse::Ok().json(report))
+}
@@ -0,0 +1,72 @@
+// Copyright Alexandre D. Díaz
+use actix_web::{get, web, Error as AWError, HttpRequest, HttpResponse, Result};
+use minijinja::context;
+use serde::{Deserialize, Serialize};
+
+use crate::minijinja_renderer::MiniJinjaRenderer;
+use crate::utils::get_minijinja_context;
+
+use oghutils::version::odoo_version_u8_to_string;
+use sqlitedb::{models, Pool};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RepositoryPullRequestInfo {
+    pub title: String,
+    pub prid: i64,
+    pub odoo_version: String,
+    pub module_technical_name: String,
+    pub url: String,
+    pub age_days: Option<i64>,
+    pub last_message_days: Option<i64>,
+    pub ci_status: Option<String>,
+}
+
+/// Modules, migration PRs, committers and findings of one repository.
+#[get("/repo/{org}/{repo}")]
+pub async fn route(
+    pool: web::Data<Pool>,
+    tmpl_env: MiniJinjaRenderer,
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, AWError> {
+    let (org, repo) = path.into_inner();
+    let overview = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        models::repository_overview::get(&mut conn, &org, &repo)
+    })
+    .await?;
+    let Some(overview) = overview else {
+        return Ok(HttpResponse::NotFound().finish());
+    };
+    let pull_requests: Vec<RepositoryPullRequestInfo> = overview
+        .pull_requests
+        .iter()
+        .map(|pr| RepositoryPullRequestInfo {
+            url: format!(
+                "https://github.com/{}/{}/pull/{}",
+                overview.organization, overview.name, pr.prid
+            ),
+            age_days: models::pull_request::days_since(pr.created_at.as_deref()),
+            last_message_days: models::pull_request::days_since(pr.last_message_at.as_deref()),
+            ci_status: pr.ci_status.clone(),
+            title: pr.name.clone(),
+            prid: pr.prid,
+            odoo_version: odoo_version_u8_to_string(&(pr.version_odoo as u8)),
+            module_technical_name: pr.module_technical_name.clone(),
+        })
+        .collect();
+    let html = tmpl_env.render(
+        "pages/repository.html",
+        context!(
+            ..get_minijinja_context(&req),
+            ..context!(
+                page_name => "repository",
+                pull_requests => pull_requests,
+                overview => overview,
+            )
+        ),
+    )?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(html.0))
+}
@@ -0,0 +1,165 @@
+// Copyright Alexandre D. Díaz
+use actix_web::http::header;
+use actix_web::{get, web, Error as AWError, HttpRequest, HttpResponse, Result};
+use minijinja::context;
+use serde::Deserialize;
+use sqlitedb::models::route_catalog::{RouteCatalog, RouteFilter};
+
+use crate::minijinja_renderer::MiniJinjaRenderer;
+use crate::routes::atlas::file_name_safe;
+use crate::utils::{get_minijinja_context, technical_names};
+
+use oghutils::version::{odoo_version_string_to_u8, odoo_version_u8_to_string};
+use sqlitedb::{models, Pool};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RouteCatalogQuery {
+    #[serde(default)]
+    pub public: bool,
+    #[serde(default)]
+    pub csrf_off: bool,
+    #[serde(default)]
+    pub sudo: bool,
+    #[serde(default)]
+    pub json: bool,
+    /// Comma-separated technical names.
+    pub modules: Option<String>,
+    pub org: Option<String>,
+}
+
+impl RouteCatalogQuery {
+    fn technical_names(&self) -> Vec<String> {
+        technical_names([&self.modules])
+    }
+
+    fn route_filter(&self) -> RouteFilter {
+        let names = self.technical_names();
+        RouteFilter {
+            public: self.public,
+            csrf_off: self.csrf_off,
+            sudo: self.sudo,
+            json: self.json,
+            modules: (!names.is_empty()).then_some(names),
+            org: self.org.clone().filter(|org| !org.is_empty()),
+        }
+    }
+}
+
+async fn get_catalog(
+    pool: web::Data<Pool>,
+    odoo_version: u8,
+    filter: RouteFilter,
+) -> Result<RouteCatalog, AWError> {
+    Ok(web::block(move || {
+        let mut conn = pool.get().unwrap();
+        models::route_catalog::build(&mut conn, &odoo_version, &filter)
+    })
+    .await?)
+}
+
+/// The catalog of the newest Odoo version.
+#[get("/routes")]
+pub async fn route_index(pool: web::Data<Pool>) -> Result<HttpResponse, AWError> {
+    let latest = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        models::module::get_odoo_versions(&mut conn)
+            .into_iter()
+            .max()
+    })
+    .await?;
+    let Some(latest) = latest else {
+        return Ok(HttpResponse::NotFound().finish());
+    };
+    Ok(HttpResponse::Found()
+        .insert_header((
+            header::LOCATION,
+            format!("/routes/{}", odoo_version_u8_to_string(&(latest as u8))),
+        ))
+        .finish())
+}
+
+/// Every HTTP endpoint the modules of one Odoo version expose.
+#[get("/routes/{odoo_version}")]
+pub async fn route(
+    pool: web::Data<Pool>,
+    tmpl_env: MiniJinjaRenderer,
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<RouteCatalogQuery>,
+) -> Result<HttpResponse, AWError> {
+    let odoo_version = odoo_version_string_to_u8(&path.into_inner());
+    let odoo_versions = {
+        let pool = pool.clone();
+        web::block(move || {
+            let mut conn = pool.get().unwrap();
+            let mut versions = models::module::get_odoo_versions(&mut conn);
+            versions.sort_unstable_by(|a, b| b.cmp(a));
+            versions
+                .into_iter()
+                .map(|v| odoo_version_u8_to_string(&(v as u8)))
+                .collect::<Vec<String>>()
+        })
+        .await?
+    };
+    let catalog = get_catalog(pool, odoo_version, query.route_filter()).await?;
+    let export_query = req.query_string();
+    let html = tmpl_env.render(
+        "pages/route_catalog.html",
+        context!(
+            ..get_minijinja_context(&req),
+            ..context!(
+                page_name => "routes",
+                odoo_version => odoo_version_u8_to_string(&odoo_version),
+                odoo_versions => odoo_versions,
+                filter_public => query.public,
+                filter_csrf_off => query.csrf_off,
+                filter_sudo => query.sudo,
+                filter_json => query.json,
+                filter_modules => query.modules.clone().unwrap_or_default(),
+                filter_org => query.org.clone().unwrap_or_default(),
+                export_query => export_query,
+                catalog => catalog,
+            )
+        ),
+    )?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(html.0))
+}
+
+/// The same catalog, filters included, as CSV or JSON.
+#[get("/routes/{odoo_version}/export/{format}")]
+pub async fn route_export(
+    pool: web::Data<Pool>,
+    path: web::Path<(String, String)>,
+    query: web::Query<RouteCatalogQuery>,
+) -> Result<HttpResponse, AWError> {
+    let (version_name, format) = path.into_inner();
+    if !matches!(format.as_str(), "csv" | "json") {
+        return Err(actix_web::error::ErrorBadRequest(format!(
+            "unknown export format: {format:?} (csv or json)"
+        )));
+    }
+    let odoo_version = odoo_version_string_to_u8(&version_name);
+    let catalog = get_catalog(pool, odoo_version, query.route_filter()).await?;
+    let file_name = match (query.technical_names().as_slice(), query.org.as_deref()) {
+        ([name], _) => format!("routes-{version_name}-{name}"),
+        ([], Some(org)) if !org.is_empty() => format!("routes-{version_name}-{org}"),
+        ([], _) => format!("routes-{version_name}"),
+        _ => format!("routes-{version_name}-modules"),
+    };
+    let disposition = format!(
+        "attachment; filename=\"{}.{format}\"",
+        file_name_safe(&file_name)
+    );
+    Ok(if format == "csv" {
+        HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header(("Content-Disposition", disposition))
+            .body(models::route_catalog::to_csv(&catalog))
+    } else {
+        HttpResponse::Ok()
+            .insert_header(("Content-Disposition", disposition))
+            .json(catalog)
+    })
+}
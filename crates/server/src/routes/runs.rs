@@ -0,0 +1,96 @@
+// Copyright Alexandre D. Díaz
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder, Result};
+use minijinja::context;
+use serde::{Deserialize, Serialize};
+
+use crate::minijinja_renderer::MiniJinjaRenderer;
+use crate::utils::get_minijinja_context;
+
+use oghutils::version::odoo_version_u8_to_string;
+use sqlitedb::{models, Pool};
+
+const RUNS_LIMIT: i64 = 200;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RunRow {
+    #[serde(flatten)]
+    pub run: models::collection_run::Model,
+    pub odoo_version: String,
+}
+
+impl From<models::collection_run::Model> for RunRow {
+    fn from(run: models::collection_run::Model) -> Self {
+        let odoo_version = u8::try_from(run.version_odoo)
+            .map(|v| odoo_version_u8_to_string(&v))
+            .unwrap_or_default();
+        Self { run, odoo_version }
+    }
+}
+
+// Both pages show live collector state: keep the site-wide cache default
+// away from them, same as /logs.
+fn no_store(html: String) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .insert_header(("Cache-Control", "no-store"))
+        .body(html)
+}
+
+#[get("/runs")]
+pub async fn route(
+    tmpl_env: MiniJinjaRenderer,
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+) -> Result<impl Responder> {
+    let runs = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        models::collection_run::get_latest(&mut conn, RUNS_LIMIT)
+    })
+    .await?
+    .into_iter()
+    .map(RunRow::from)
+    .collect::<Vec<RunRow>>();
+
+    let html = tmpl_env.render(
+        "pages/runs.html",
+        context!(
+            ..get_minijinja_context(&req),
+            ..context!(page_name => "runs", runs => runs)
+        ),
+    )?;
+    Ok(no_store(html.0))
+}
+
+#[get("/runs/{id}")]
+pub async fn route_run(
+    tmpl_env: MiniJinjaRenderer,
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i64>,
+) -> Result<HttpResponse> {
+    let run_id = path.into_inner();
+    let result = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        models::collection_run::get_by_id(&mut conn, &run_id).map(|run| {
+            let repos = models::collection_run_repo::get_by_run_id(&mut conn, &run.id);
+            (run, repos)
+        })
+    })
+    .await?;
+
+    let Some((run, repos)) = result else {
+        return Ok(HttpResponse::NotFound().finish());
+    };
+    let html = tmpl_env.render(
+        "pages/run.html",
+        context!(
+            ..get_minijinja_context(&req),
+            ..context!(
+                page_name => "runs",
+                run => RunRow::from(run),
+                repos => repos,
+            )
+        ),
+    )?;
+    Ok(no_store(html.0))
+}
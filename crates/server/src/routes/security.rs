@@ -0,0 +1,66 @@
+// Copyright Alexandre D. Díaz
+use actix_web::{get, web, Error as AWError, HttpRequest, HttpResponse, Result};
+use minijinja::{context, Value};
+use serde::Deserialize;
+use sqlitedb::models::security_overview::{SecurityFilter, SecurityOverview, SeverityFilter};
+
+use crate::minijinja_renderer::MiniJinjaRenderer;
+use crate::routes::report::{self, ReportQuery};
+use crate::utils::non_empty;
+
+use oghutils::version::odoo_version_string_to_u8;
+use sqlitedb::{models, DbConnection, Pool};
+
+/// Filters shared by the /security page and its API. Empty values (the
+/// page's "any" options) are the same as missing ones.
+#[derive(Debug, Default, Deserialize)]
+pub struct SecurityQuery {
+    pub odoo_version: Option<String>,
+    pub org: Option<String>,
+    pub code: Option<String>,
+    #[serde(default)]
+    pub severity: SeverityFilter,
+}
+
+impl ReportQuery for SecurityQuery {
+    type Filter = SecurityFilter;
+    type Report = SecurityOverview;
+
+    const PAGE_NAME: &'static str = "security";
+
+    fn filter(&self) -> SecurityFilter {
+        SecurityFilter {
+            version_odoo: non_empty(&self.odoo_version)
+                .map(|version| odoo_version_string_to_u8(&version)),
+            org: non_empty(&self.org),
+            code: non_empty(&self.code),
+            severity: self.severity,
+        }
+    }
+
+    fn build(conn: &mut DbConnection, filter: &SecurityFilter) -> SecurityOverview {
+        models::security_overview::build(conn, filter)
+    }
+
+    fn page_context(&self, overview: SecurityOverview) -> Value {
+        context!(
+            filter_odoo_version => non_empty(&self.odoo_version).unwrap_or_default(),
+            filter_org => non_empty(&self.org).unwrap_or_default(),
+            filter_code => non_empty(&self.code).unwrap_or_default(),
+            filter_severity => self.severity,
+            overview => overview,
+        )
+    }
+}
+
+/// Security findings across the ecosystem, by Odoo version, organization and
+/// rule.
+#[get("/security")]
+pub async fn route(
+    pool: web::Data<Pool>,
+    tmpl_env: MiniJinjaRenderer,
+    req: HttpRequest,
+    query: web::Query<SecurityQuery>,
+) -> Result<HttpResponse, AWError> {
+    report::page(pool, tmpl_env, req, query).await
+}
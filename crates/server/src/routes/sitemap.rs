@@ -1,7 +1,9 @@
 // Copyright Alexandre D. Díaz
+use std::collections::BTreeSet;
+
 use actix_web::{get, web, HttpRequest, HttpResponse, Result};
 use cached::{proc_macro::cached, stores::TimedSizedCache};
-use diesel::sqlite::SqliteConnection;
+use sqlitedb::DbConnection;
 use url::Url;
 
 use crate::config::SERVER_CONFIG;
@@ -51,7 +53,7 @@ fn xml_escape(s: &str) -> String {
     "#,
     convert = r#"{ base.to_string() }"#
 )]
-fn build_sitemap(base: &Url, conn: &mut SqliteConnection) -> String {
+fn build_sitemap(base: &Url, conn: &mut DbConnection) -> String {
     let mut body = String::from(
         "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
          <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
@@ -62,10 +64,19 @@ fn build_sitemap(base: &Url, conn: &mut SqliteConnection) -> String {
             page_url(base, segments)
         ));
     }
-    for m in models::module::list(conn) {
+    let modules = models::module::list(conn);
+    for m in &modules {
         let loc = page_url(base, &["module", &m.org_name, &m.technical_name]);
         body.push_str(&format!("  <url><loc>{loc}</loc></url>\n"));
     }
+    let repositories: BTreeSet<(&str, &str)> = modules
+        .iter()
+        .map(|m| (m.org_name.as_str(), m.repository.as_str()))
+        .collect();
+    for (org, repo) in repositories {
+        let loc = page_url(base, &["repo", org, repo]);
+        body.push_str(&format!("  <url><loc>{loc}</loc></url>\n"));
+    }
     for c in models::committer::list(conn) {
         let loc = page_url(base, &["committer", &c.name]);
         body.push_str(&format!("  <url><loc>{loc}</loc></url>\n"));
@@ -74,6 +85,11 @@ fn build_sitemap(base: &Url, conn: &mut SqliteConnection) -> String {
     body
 }
 
+/// Hits and misses of `build_sitemap`'s cache, for `/metrics`.
+pub fn cache_stats() -> (u64, u64) {
+    crate::metrics::cache_stats(&BUILD_SITEMAP)
+}
+
 #[get("/sitemap.xml")]
 pub async fn route(pool: web::Data<Pool>, req: HttpRequest) -> Result<HttpResponse> {
     // Mirrors robots.txt's seo_enabled gate: a site that tells crawlers to
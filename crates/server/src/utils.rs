@@ -53,6 +53,27 @@ pub fn normalize_python_dep(name: String) -> String {
     PIP_NAMES_MAP.get(&lower).cloned().unwrap_or(name)
 }
 
+/// A query parameter's trimmed value, with blank the same as missing.
+pub fn non_empty(value: &Option<String>) -> Option<String> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// Technical names from comma-separated query parameters, in order, blanks
+/// left out.
+pub fn technical_names<'a>(values: impl IntoIterator<Item = &'a Option<String>>) -> Vec<String> {
+    values
+        .into_iter()
+        .flatten()
+        .flat_map(|names| names.split(','))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
 pub fn get_base_url(req: &HttpRequest) -> String {
     let conn_info = req.connection_info();
     format!("{}://{}", conn_info.scheme(), conn_info.host())
@@ -0,0 +1,97 @@
+// Copyright Alexandre D. Díaz
+//! The few bits of raw SQL that SQLite and PostgreSQL spell differently.
+//! Queries are written with SQLite's `?`/`?NNN` placeholders and portable
+//! SQL otherwise; whatever isn't portable goes through here.
+use diesel::query_builder::SqlQuery;
+
+/// Case-insensitive `LIKE` (SQLite's `LIKE` already is, for ASCII).
+#[cfg(not(feature = "postgres"))]
+pub const ILIKE: &str = "LIKE";
+#[cfg(feature = "postgres")]
+pub const ILIKE: &str = "ILIKE";
+
+/// `diesel::sql_query` taking SQLite placeholders. On PostgreSQL they're
+/// rewritten to `$N`, numbered as SQLite does: `?NNN` is parameter NNN and a
+/// bare `?` is one past the largest so far.
+pub fn sql_query(query: impl Into<String>) -> SqlQuery {
+    #[cfg(not(feature = "postgres"))]
+    {
+        diesel::sql_query(query)
+    }
+    #[cfg(feature = "postgres")]
+    {
+        diesel::sql_query(numbered_placeholders(&query.into()))
+    }
+}
+
+/// Joins the non-NULL values of `expr` (any type) with `sep`.
+pub fn group_concat(expr: &str, sep: &str) -> String {
+    if cfg!(feature = "postgres") {
+        format!("STRING_AGG(CAST({expr} AS TEXT), '{sep}')")
+    } else {
+        format!("GROUP_CONCAT({expr}, '{sep}')")
+    }
+}
+
+/// Days (fractional) from `start` to `end`, both dates in the sqlite text
+/// format (see `utils::date`).
+pub fn days_between(start: &str, end: &str) -> String {
+    if cfg!(feature = "postgres") {
+        format!(
+            "CAST(EXTRACT(EPOCH FROM (CAST({end} AS TIMESTAMP) - CAST({start} AS TIMESTAMP))) \
+             / 86400 AS DOUBLE PRECISION)"
+        )
+    } else {
+        format!("(julianday({end}) - julianday({start}))")
+    }
+}
+
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+fn numbered_placeholders(query: &str) -> String {
+    let mut out = String::with_capacity(query.len() + 8);
+    let mut chars = query.chars().peekable();
+    let mut quote = None;
+    let mut last = 0;
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '?') => {
+                let mut digits = String::new();
+                while let Some(d) = chars.next_if(char::is_ascii_digit) {
+                    digits.push(d);
+                }
+                let n = digits.parse().unwrap_or(last + 1);
+                last = last.max(n);
+                out.push('$');
+                out.push_str(&n.to_string());
+                continue;
+            }
+            _ => {}
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::numbered_placeholders;
+
+    #[test]
+    fn test_numbered_placeholders() {
+        assert_eq!(
+            numbered_placeholders("SELECT 1 WHERE a = ? AND b = ? LIMIT ?"),
+            "SELECT 1 WHERE a = $1 AND b = $2 LIMIT $3"
+        );
+        assert_eq!(
+            numbered_placeholders("WHERE (?1 IS NULL OR a = ?1) AND b = ?"),
+            "WHERE ($1 IS NULL OR a = $1) AND b = $2"
+        );
+        assert_eq!(
+            numbered_placeholders("WHERE a = '?' AND \"b?\" = ? AND c LIKE 'it''s?'"),
+            "WHERE a = '?' AND \"b?\" = $1 AND c LIKE 'it''s?'"
+        );
+    }
+}
@@ -1,38 +1,132 @@
 // Copyright Alexandre D. Díaz
+pub mod maintenance;
 pub mod models;
 pub mod schema;
 pub mod utils;
 
+mod dialect;
+
 use diesel::r2d2::{ConnectionManager, CustomizeConnection, Error as R2d2Error, Pool as R2d2Pool};
-use diesel::sqlite::SqliteConnection;
+use diesel::Connection as _;
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 
 pub use diesel::r2d2::PooledConnection;
-pub use diesel::sqlite::SqliteConnection as DbSqliteConnection;
 
-pub type Pool = R2d2Pool<ConnectionManager<SqliteConnection>>;
-pub type Connection = PooledConnection<ConnectionManager<SqliteConnection>>;
+/// The connection every model function takes. SQLite by default; the
+/// `postgres` feature swaps it for PostgreSQL, so a build talks to one
+/// backend only.
+#[cfg(not(feature = "postgres"))]
+pub type DbConnection = diesel::sqlite::SqliteConnection;
+#[cfg(feature = "postgres")]
+pub type DbConnection = diesel::pg::PgConnection;
+
+pub type DbBackend = <DbConnection as diesel::Connection>::Backend;
 
+pub type Pool = R2d2Pool<ConnectionManager<DbConnection>>;
+pub type Connection = PooledConnection<ConnectionManager<DbConnection>>;
+
+#[cfg(not(feature = "postgres"))]
 const MIGRATIONS: EmbeddedMigrations = embed_migrations!("../../migrations");
+#[cfg(feature = "postgres")]
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("../../migrations_postgres");
+
+// Key of the advisory lock held while migrating, so services starting
+// together against the same PostgreSQL don't race each other.
+#[cfg(feature = "postgres")]
+const MIGRATIONS_LOCK_KEY: i64 = 0x4f4748; // "OGH"
 
+#[cfg(not(feature = "postgres"))]
 pub fn run_migrations(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
     conn.run_pending_migrations(MIGRATIONS)?;
     Ok(())
 }
 
-// Customizes connections for write access (WAL mode, timeouts).
+#[cfg(feature = "postgres")]
+pub fn run_migrations(
+    conn: &mut DbConnection,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+    use diesel::RunQueryDsl;
+    diesel::sql_query(format!("SELECT pg_advisory_lock({MIGRATIONS_LOCK_KEY})")).execute(conn)?;
+    let res = conn.run_pending_migrations(MIGRATIONS).map(|_| ());
+    diesel::sql_query(format!("SELECT pg_advisory_unlock({MIGRATIONS_LOCK_KEY})")).execute(conn)?;
+    res
+}
+
+/// What the pools connect to. On SQLite that's `db_path`, created (with its
+/// parent directories) when missing. On PostgreSQL it's the
+/// `OGHCOLLECTOR_DATABASE_URL` environment variable and `db_path` is unused.
+#[cfg(not(feature = "postgres"))]
+pub fn database_url(db_path: &str) -> std::io::Result<String> {
+    use std::fs::{self, File};
+    use std::path::Path;
+
+    if let Some(parent) = Path::new(db_path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if !Path::new(db_path).exists() {
+        File::create(db_path)?;
+    }
+    Ok(db_path.to_string())
+}
+
+#[cfg(feature = "postgres")]
+pub fn database_url(_db_path: &str) -> std::io::Result<String> {
+    std::env::var("OGHCOLLECTOR_DATABASE_URL").map_err(|_| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "OGHCOLLECTOR_DATABASE_URL must be set to a postgres:// URL",
+        )
+    })
+}
+
+/// A migrated connection for tests. SQLite: a private in-memory database.
+/// PostgreSQL: `OGHCOLLECTOR_TEST_DATABASE_URL` inside a transaction that is
+/// never committed, so tests leave nothing behind (run them with
+/// `--test-threads=1`, they share the database).
+#[cfg(not(feature = "postgres"))]
+pub fn establish_test_connection() -> DbConnection {
+    let mut conn = DbConnection::establish(":memory:").unwrap();
+    run_migrations(&mut conn).unwrap();
+    conn
+}
+
+#[cfg(feature = "postgres")]
+pub fn establish_test_connection() -> DbConnection {
+    static MIGRATED: std::sync::Once = std::sync::Once::new();
+
+    let url = std::env::var("OGHCOLLECTOR_TEST_DATABASE_URL")
+        .expect("OGHCOLLECTOR_TEST_DATABASE_URL must be set to run the tests on PostgreSQL");
+    let mut conn = DbConnection::establish(&url).unwrap();
+    MIGRATED.call_once(|| run_migrations(&mut conn).unwrap());
+    conn.begin_test_transaction().unwrap();
+    conn
+}
+
+#[cfg(not(feature = "postgres"))]
+const WRITE_SETUP: &str =
+    "PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL; PRAGMA busy_timeout = 5000;";
+#[cfg(feature = "postgres")]
+const WRITE_SETUP: &str = "";
+
+#[cfg(not(feature = "postgres"))]
+const READ_SETUP: &str = "PRAGMA query_only = ON; PRAGMA busy_timeout = 5000;";
+#[cfg(feature = "postgres")]
+const READ_SETUP: &str = "SET default_transaction_read_only = on;";
+
+// Customizes connections for write access (WAL mode, timeouts on SQLite).
 #[derive(Debug)]
 struct WriteCustomizer;
 
-impl CustomizeConnection<SqliteConnection, R2d2Error> for WriteCustomizer {
-    fn on_acquire(&self, conn: &mut SqliteConnection) -> Result<(), R2d2Error> {
+impl CustomizeConnection<DbConnection, R2d2Error> for WriteCustomizer {
+    fn on_acquire(&self, conn: &mut DbConnection) -> Result<(), R2d2Error> {
         use diesel::connection::SimpleConnection;
-        conn.batch_execute(
-            "PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL; PRAGMA busy_timeout = 5000;",
-        )
-        .map_err(R2d2Error::QueryError)
+        if WRITE_SETUP.is_empty() {
+            return Ok(());
+        }
+        conn.batch_execute(WRITE_SETUP)
+            .map_err(R2d2Error::QueryError)
     }
 }
 
@@ -40,27 +134,27 @@ impl CustomizeConnection<SqliteConnection, R2d2Error> for WriteCustomizer {
 #[derive(Debug)]
 struct ReadCustomizer;
 
-impl CustomizeConnection<SqliteConnection, R2d2Error> for ReadCustomizer {
-    fn on_acquire(&self, conn: &mut SqliteConnection) -> Result<(), R2d2Error> {
+impl CustomizeConnection<DbConnection, R2d2Error> for ReadCustomizer {
+    fn on_acquire(&self, conn: &mut DbConnection) -> Result<(), R2d2Error> {
         use diesel::connection::SimpleConnection;
-        conn.batch_execute("PRAGMA query_only = ON; PRAGMA busy_timeout = 5000;")
+        conn.batch_execute(READ_SETUP)
             .map_err(R2d2Error::QueryError)
     }
 }
 
-pub fn new_write_pool(db_path: &str) -> Pool {
-    let manager = ConnectionManager::<SqliteConnection>::new(db_path);
+pub fn new_write_pool(database_url: &str) -> Pool {
+    let manager = ConnectionManager::<DbConnection>::new(database_url);
     Pool::builder()
         .connection_customizer(Box::new(WriteCustomizer))
         .build(manager)
-        .unwrap_or_else(|e| panic!("Failed to create write pool for {db_path}: {e}"))
+        .unwrap_or_else(|e| panic!("Failed to create write pool: {e}"))
 }
 
-pub fn new_read_pool(db_path: &str, max_size: u32) -> Pool {
-    let manager = ConnectionManager::<SqliteConnection>::new(db_path);
+pub fn new_read_pool(database_url: &str, max_size: u32) -> Pool {
+    let manager = ConnectionManager::<DbConnection>::new(database_url);
     Pool::builder()
         .max_size(max_size)
         .connection_customizer(Box::new(ReadCustomizer))
         .build(manager)
-        .unwrap_or_else(|e| panic!("Failed to create read pool for {db_path}: {e}"))
+        .unwrap_or_else(|e| panic!("Failed to create read pool: {e}"))
 }
@@ -0,0 +1,58 @@
+// Copyright Alexandre D. Díaz
+//! Storage upkeep run by `oghcollector maintenance` once retention policies
+//! have deleted what they had to.
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+
+use crate::DbConnection;
+
+#[derive(QueryableByName)]
+struct Size {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    size: i64,
+}
+
+/// Bytes the database takes. SQLite: its pages, free ones included, so it
+/// only shrinks after `vacuum`. PostgreSQL: `pg_database_size`.
+pub fn database_size(conn: &mut DbConnection) -> QueryResult<i64> {
+    let query = if cfg!(feature = "postgres") {
+        "SELECT CAST(pg_database_size(current_database()) AS BIGINT) AS size"
+    } else {
+        "SELECT page_count * page_size AS size FROM pragma_page_count(), pragma_page_size()"
+    };
+    diesel::sql_query(query)
+        .get_result::<Size>(conn)
+        .map(|row| row.size)
+}
+
+/// Rewrites the database to give freed pages back and refreshes the planner
+/// statistics. Can't run inside a transaction on either backend.
+pub fn vacuum(conn: &mut DbConnection) -> QueryResult<()> {
+    if cfg!(feature = "postgres") {
+        conn.batch_execute("VACUUM ANALYZE")
+    } else {
+        conn.batch_execute("VACUUM; ANALYZE;")
+    }
+}
+
+// VACUUM can't run inside the transaction PostgreSQL tests are wrapped in.
+#[cfg(all(test, not(feature = "postgres")))]
+mod tests {
+    use super::{database_size, vacuum};
+
+    #[test]
+    fn test_vacuum_reclaims_space() {
+        use diesel::connection::SimpleConnection;
+        let mut conn = crate::establish_test_connection();
+        conn.batch_execute(
+            "CREATE TABLE filler (data TEXT);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 2000)
+             INSERT INTO filler SELECT hex(randomblob(256)) FROM n;
+             DELETE FROM filler;",
+        )
+        .unwrap();
+        let before = database_size(&mut conn).unwrap();
+        vacuum(&mut conn).unwrap();
+        assert!(database_size(&mut conn).unwrap() < before);
+    }
+}
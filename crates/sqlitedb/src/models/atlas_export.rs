@@ -0,0 +1,272 @@
+// Copyright Alexandre D. Díaz
+//! Renders an Atlas graph (see `atlas_graph`) as a diagram other tools
+//! understand: Graphviz DOT, GraphML or Mermaid. DOT and Mermaid group the
+//! modules of each repository together, GraphML keeps every attribute of
+//! the Atlas JSON as-is.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::utils::escape::{mermaid_label, xml_escape};
+
+use super::atlas_graph::{GraphInfo, GraphNodeInfo};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Dot,
+    GraphMl,
+    Mermaid,
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> Option<ExportFormat> {
+        match name {
+            "dot" | "gv" => Some(ExportFormat::Dot),
+            "graphml" => Some(ExportFormat::GraphMl),
+            "mermaid" | "mmd" => Some(ExportFormat::Mermaid),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Dot => "dot",
+            ExportFormat::GraphMl => "graphml",
+            ExportFormat::Mermaid => "mmd",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Dot => "text/vnd.graphviz; charset=utf-8",
+            ExportFormat::GraphMl => "application/graphml+xml; charset=utf-8",
+            ExportFormat::Mermaid => "text/plain; charset=utf-8",
+        }
+    }
+}
+
+pub fn render(graph: &GraphInfo, title: &str, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Dot => to_dot(graph, title),
+        ExportFormat::GraphMl => to_graphml(graph, title),
+        ExportFormat::Mermaid => to_mermaid(graph, title),
+    }
+}
+
+// Python packages and binaries; every Odoo module node is keyed `o_<name>`.
+fn is_external(node: &GraphNodeInfo) -> bool {
+    !node.key.starts_with("o_")
+}
+
+fn attribute<'a>(node: &'a GraphNodeInfo, name: &str) -> &'a str {
+    node.attributes
+        .get(name)
+        .map(String::as_str)
+        .unwrap_or_default()
+}
+
+// Collected modules by "organization/repository", in graph order, and the
+// rest (dependencies on modules not collected, Python packages, binaries).
+fn group_by_repository(graph: &GraphInfo) -> (BTreeMap<String, Vec<usize>>, Vec<usize>) {
+    let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    let mut ungrouped = Vec::new();
+    for (idx, node) in graph.nodes.iter().enumerate() {
+        match node.attributes.get("repository") {
+            Some(repository) => groups
+                .entry(format!(
+                    "{}/{}",
+                    attribute(node, "organization"),
+                    repository
+                ))
+                .or_default()
+                .push(idx),
+            None => ungrouped.push(idx),
+        }
+    }
+    (groups, ungrouped)
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn dot_node(out: &mut String, indent: &str, node: &GraphNodeInfo) {
+    let shape = if is_external(node) { "ellipse" } else { "box" };
+    let _ = writeln!(
+        out,
+        "{indent}\"{}\" [label=\"{}\", shape={shape}, fillcolor=\"{}\"];",
+        dot_escape(&node.key),
+        dot_escape(attribute(node, "label")),
+        dot_escape(attribute(node, "color")),
+    );
+}
+
+/// Graphviz DOT, one cluster per repository.
+pub fn to_dot(graph: &GraphInfo, title: &str) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "digraph \"{}\" {{", dot_escape(title));
+    out.push_str("    rankdir=LR;\n");
+    out.push_str("    node [style=\"rounded,filled\", fontcolor=\"#ffffff\"];\n");
+    let (groups, ungrouped) = group_by_repository(graph);
+    for (cluster, (repository, nodes)) in groups.iter().enumerate() {
+        let _ = writeln!(out, "    subgraph \"cluster_{cluster}\" {{");
+        let _ = writeln!(out, "        label=\"{}\";", dot_escape(repository));
+        for idx in nodes {
+            dot_node(&mut out, "        ", &graph.nodes[*idx]);
+        }
+        out.push_str("    }\n");
+    }
+    for idx in ungrouped {
+        dot_node(&mut out, "    ", &graph.nodes[idx]);
+    }
+    for edge in &graph.edges {
+        let _ = writeln!(
+            out,
+            "    \"{}\" -> \"{}\";",
+            dot_escape(&edge.source),
+            dot_escape(&edge.target)
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// A Mermaid flowchart, one subgraph per repository. Node keys can hold
+/// characters Mermaid ids can't (e.g. the dots of a Python package), so
+/// nodes get positional ids and carry their name as the label.
+pub fn to_mermaid(graph: &GraphInfo, title: &str) -> String {
+    let ids: BTreeMap<&str, String> = graph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(idx, node)| (node.key.as_str(), format!("n{idx}")))
+        .collect();
+    let node_line = |idx: usize| {
+        let node = &graph.nodes[idx];
+        let label = mermaid_label(attribute(node, "label"));
+        if is_external(node) {
+            format!("n{idx}([\"{label}\"])")
+        } else {
+            format!("n{idx}[\"{label}\"]")
+        }
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "---\ntitle: \"{}\"\n---", mermaid_label(title));
+    out.push_str("flowchart LR\n");
+    let (groups, ungrouped) = group_by_repository(graph);
+    for (group, (repository, nodes)) in groups.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "    subgraph g{group}[\"{}\"]",
+            mermaid_label(repository)
+        );
+        for idx in nodes {
+            let _ = writeln!(out, "        {}", node_line(*idx));
+        }
+        out.push_str("    end\n");
+    }
+    for idx in ungrouped {
+        let _ = writeln!(out, "    {}", node_line(idx));
+    }
+    for edge in &graph.edges {
+        if let (Some(source), Some(target)) =
+            (ids.get(edge.source.as_str()), ids.get(edge.target.as_str()))
+        {
+            let _ = writeln!(out, "    {source} --> {target}");
+        }
+    }
+    // One class per node color, so the diagram reads like the Atlas.
+    let mut by_color: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (idx, node) in graph.nodes.iter().enumerate() {
+        let color = attribute(node, "color");
+        if !color.is_empty() {
+            by_color.entry(color).or_default().push(format!("n{idx}"));
+        }
+    }
+    for (class, (color, nodes)) in by_color.iter().enumerate() {
+        let _ = writeln!(out, "    classDef c{class} fill:{color},color:#ffffff");
+        let _ = writeln!(out, "    class {} c{class}", nodes.join(","));
+    }
+    out
+}
+
+fn graphml_keys<'a>(
+    out: &mut String,
+    prefix: &str,
+    domain: &str,
+    names: impl Iterator<Item = &'a String>,
+) {
+    for name in names.collect::<BTreeSet<_>>() {
+        let name = xml_escape(name);
+        let _ = writeln!(
+            out,
+            "  <key id=\"{prefix}_{name}\" for=\"{domain}\" attr.name=\"{name}\" attr.type=\"string\"/>"
+        );
+    }
+}
+
+fn graphml_data(
+    out: &mut String,
+    indent: &str,
+    prefix: &str,
+    attributes: &BTreeMap<String, String>,
+) {
+    for (name, value) in attributes {
+        let _ = writeln!(
+            out,
+            "{indent}<data key=\"{prefix}_{}\">{}</data>",
+            xml_escape(name),
+            xml_escape(value)
+        );
+    }
+}
+
+/// GraphML with every graph, node and edge attribute of the Atlas JSON
+/// declared as a string key.
+pub fn to_graphml(graph: &GraphInfo, title: &str) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    graphml_keys(&mut out, "g", "graph", graph.attributes.keys());
+    graphml_keys(
+        &mut out,
+        "n",
+        "node",
+        graph.nodes.iter().flat_map(|node| node.attributes.keys()),
+    );
+    graphml_keys(
+        &mut out,
+        "e",
+        "edge",
+        graph.edges.iter().flat_map(|edge| edge.attributes.keys()),
+    );
+    let _ = writeln!(
+        out,
+        "  <graph id=\"{}\" edgedefault=\"directed\">",
+        xml_escape(title)
+    );
+    graphml_data(&mut out, "    ", "g", &graph.attributes);
+    for node in &graph.nodes {
+        let _ = writeln!(out, "    <node id=\"{}\">", xml_escape(&node.key));
+        graphml_data(&mut out, "      ", "n", &node.attributes);
+        out.push_str("    </node>\n");
+    }
+    for edge in &graph.edges {
+        let _ = writeln!(
+            out,
+            "    <edge id=\"{}\" source=\"{}\" target=\"{}\"{}>",
+            xml_escape(&edge.key),
+            xml_escape(&edge.source),
+            xml_escape(&edge.target),
+            if edge.undirected {
+                " directed=\"false\""
+            } else {
+                ""
+            }
+        );
+        graphml_data(&mut out, "      ", "e", &edge.attributes);
+        out.push_str("    </edge>\n");
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
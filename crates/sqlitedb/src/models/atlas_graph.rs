@@ -0,0 +1,464 @@
+// Copyright Alexandre D. Díaz
+//! The Atlas dependency graph of an Odoo version, in the shape the page's
+//! graph library loads. Built from two bulk queries and stored per version by
+//! the collector (see `refresh`), so `/atlas/data` only reads a row.
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::dialect;
+use crate::schema::atlas_graph;
+use crate::utils::date::get_sqlite_utc_now;
+use crate::DbConnection;
+
+use super::{dependency, module};
+
+#[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
+#[diesel(table_name = atlas_graph, check_for_backend(crate::DbBackend))]
+pub struct Model {
+    pub id: i64,
+    pub version_odoo: i32,
+    pub data: String,
+    pub etag: String,
+    pub update_date: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = atlas_graph)]
+struct NewAtlasGraph<'a> {
+    version_odoo: i32,
+    data: &'a str,
+    etag: &'a str,
+    update_date: &'a str,
+}
+
+// Attributes are sorted so the same graph always serializes to the same
+// JSON, and so keeps its ETag across rebuilds.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct GraphNodeInfo {
+    pub key: String,
+    pub attributes: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct GraphEdgeInfo {
+    pub key: String,
+    pub source: String,
+    pub target: String,
+    pub undirected: bool,
+    pub attributes: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+pub struct GraphInfo {
+    pub attributes: BTreeMap<String, String>,
+    pub nodes: Vec<GraphNodeInfo>,
+    pub edges: Vec<GraphEdgeInfo>,
+}
+
+#[derive(QueryableByName)]
+struct GraphModuleRow {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    id: i64,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    technical_name: String,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    application: bool,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    category: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    repo_name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    org_name: String,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    is_odoo_core: bool,
+}
+
+#[derive(QueryableByName)]
+struct GraphDependencyRow {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    module_id: i64,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    dep_type: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    name: String,
+}
+
+// Nodes and edges indexed by key, so adding one is a hash lookup instead of
+// a scan of everything added so far.
+#[derive(Default)]
+struct GraphBuilder {
+    graph: GraphInfo,
+    node_index: HashMap<String, usize>,
+    edge_keys: HashSet<String>,
+}
+
+impl GraphBuilder {
+    /// Adds the node unless there's one with that key already.
+    fn add_node(&mut self, key: String, attributes: &[(&str, &str)]) {
+        if !self.node_index.contains_key(&key) {
+            self.set_node(key, attributes);
+        }
+    }
+
+    /// Adds the node, replacing the attributes of an existing one.
+    fn set_node(&mut self, key: String, attributes: &[(&str, &str)]) {
+        let attributes = attributes
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        match self.node_index.get(&key) {
+            Some(idx) => self.graph.nodes[*idx].attributes = attributes,
+            None => {
+                self.node_index.insert(key.clone(), self.graph.nodes.len());
+                self.graph.nodes.push(GraphNodeInfo { key, attributes });
+            }
+        }
+    }
+
+    fn add_edge(&mut self, key: String, source: &str, target: &str) {
+        if self.edge_keys.insert(key.clone()) {
+            self.graph.edges.push(GraphEdgeInfo {
+                key,
+                source: source.to_string(),
+                target: target.to_string(),
+                undirected: false,
+                attributes: BTreeMap::from([("size".to_string(), "2".to_string())]),
+            });
+        }
+    }
+}
+
+fn main_node_attributes(module: &GraphModuleRow) -> [(&str, &str); 6] {
+    let (size, color) = match (module.is_odoo_core, module.application) {
+        (true, true) => ("12", "#21B799"),
+        (true, false) => ("10", "#017E84"),
+        (false, true) => ("9", "#21B799"),
+        (false, false) => ("8", "#E46E78"),
+    };
+    [
+        ("size", size),
+        ("color", color),
+        ("label", &module.technical_name),
+        ("repository", &module.repo_name),
+        ("organization", &module.org_name),
+        ("category", module.category.as_deref().unwrap_or_default()),
+    ]
+}
+
+/// Builds the graph of `version_odoo`: one node per module (`base` left out,
+/// everything depends on it) plus one per dependency on a module not
+/// collected here or on a Python package or binary, and an edge per
+/// dependency.
+pub fn build(conn: &mut DbConnection, version_odoo: &u8) -> GraphInfo {
+    let modules = dialect::sql_query(
+        "SELECT mod.id, mod.technical_name, mod.application, mod.category, \
+         repo.name as repo_name, org.name as org_name, \
+         (org.name = 'odoo' AND repo.name = 'odoo') as is_odoo_core \
+         FROM module as mod \
+         INNER JOIN gh_repository as repo ON repo.id = mod.gh_repository_id \
+         INNER JOIN gh_organization as org ON org.id = repo.gh_organization_id \
+         WHERE mod.version_odoo = ? \
+         ORDER BY mod.technical_name, mod.id",
+    )
+    .bind::<diesel::sql_types::Integer, _>(*version_odoo as i32)
+    .load::<GraphModuleRow>(conn)
+    .expect("DB error in atlas_graph::build");
+    let dependencies = dialect::sql_query(
+        "SELECT dep_mod.module_id, dep_type.name as dep_type, dep.name \
+         FROM dependency_module as dep_mod \
+         INNER JOIN dependency as dep ON dep.id = dep_mod.dependency_id \
+         INNER JOIN dependency_type as dep_type ON dep_type.id = dep.dependency_type_id \
+         INNER JOIN module as mod ON mod.id = dep_mod.module_id \
+         WHERE mod.version_odoo = ? \
+         ORDER BY dep.name",
+    )
+    .bind::<diesel::sql_types::Integer, _>(*version_odoo as i32)
+    .load::<GraphDependencyRow>(conn)
+    .expect("DB error in atlas_graph::build");
+
+    // A technical_name can exist in more than one org for the same Odoo
+    // version (e.g. a module donated from OCA into Odoo core keeps living in
+    // both repos for a while) - keep the odoo/odoo row when there is one so
+    // the graph node reflects that it's core.
+    let mut main_modules: BTreeMap<&str, &GraphModuleRow> = BTreeMap::new();
+    for module in &modules {
+        let already_odoo_core = main_modules
+            .get(module.technical_name.as_str())
+            .is_some_and(|existing| existing.is_odoo_core);
+        if module.is_odoo_core || !already_odoo_core {
+            main_modules.insert(&module.technical_name, module);
+        }
+    }
+    let mut depends_by_module: HashMap<i64, Vec<&GraphDependencyRow>> = HashMap::new();
+    for dependency in &dependencies {
+        depends_by_module
+            .entry(dependency.module_id)
+            .or_default()
+            .push(dependency);
+    }
+
+    let mut builder = GraphBuilder::default();
+    for (technical_name, module) in &main_modules {
+        if *technical_name == "base" {
+            continue;
+        }
+        let source = format!("o_{technical_name}");
+        for dependency in depends_by_module.get(&module.id).into_iter().flatten() {
+            let name = dependency.name.as_str();
+            let (prefix, size, color) = match dependency.dep_type.as_str() {
+                "module" => ("o", "8", "#E46E78"),
+                "python" => ("p", "5", "#6c5148"),
+                "bin" => ("b", "5", "#335548"),
+                _ => continue,
+            };
+            if prefix == "o" && name == "base" {
+                continue;
+            }
+            let target = format!("{prefix}_{name}");
+            if prefix != "o" || !main_modules.contains_key(name) {
+                builder.add_node(
+                    target.clone(),
+                    &[("size", size), ("color", color), ("label", name)],
+                );
+            }
+            builder.add_edge(
+                format!("{prefix}_{technical_name}__{name}"),
+                &source,
+                &target,
+            );
+        }
+        builder.set_node(source, &main_node_attributes(module));
+    }
+    builder.graph
+}
+
+/// The modules `GraphInfo::filter` keeps: those collected here matching
+/// every criterion given.
+#[derive(Debug, Default, Clone)]
+pub struct GraphFilter {
+    pub organization: Option<String>,
+    pub repository: Option<String>,
+    pub category: Option<String>,
+    /// Technical names, e.g. a pack or the favorites.
+    pub modules: Option<HashSet<String>>,
+}
+
+impl GraphFilter {
+    pub fn is_empty(&self) -> bool {
+        self.organization.is_none()
+            && self.repository.is_none()
+            && self.category.is_none()
+            && self.modules.is_none()
+    }
+
+    fn matches(&self, node: &GraphNodeInfo) -> bool {
+        let attribute = |name: &str| node.attributes.get(name).map(String::as_str);
+        // Only collected modules have a repository; dependencies on
+        // anything else never match on their own.
+        attribute("repository").is_some()
+            && self
+                .organization
+                .as_deref()
+                .is_none_or(|org| attribute("organization") == Some(org))
+            && self
+                .repository
+                .as_deref()
+                .is_none_or(|repo| attribute("repository") == Some(repo))
+            && self
+                .category
+                .as_deref()
+                .is_none_or(|category| attribute("category") == Some(category))
+            && self
+                .modules
+                .as_ref()
+                .is_none_or(|modules| attribute("label").is_some_and(|name| modules.contains(name)))
+    }
+}
+
+// Nodes reachable from `start` following `adjacency`, up to `depth` hops away.
+fn reach<'a>(
+    adjacency: &HashMap<&'a str, Vec<&'a str>>,
+    start: &'a str,
+    depth: usize,
+) -> HashSet<&'a str> {
+    let mut reached = HashSet::from([start]);
+    let mut frontier = vec![start];
+    for _ in 0..depth {
+        let mut next = Vec::new();
+        for key in frontier {
+            for neighbor in adjacency.get(key).into_iter().flatten() {
+                if reached.insert(neighbor) {
+                    next.push(*neighbor);
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+    reached
+}
+
+impl GraphInfo {
+    /// The modules matching `filter` and what they depend on, with their
+    /// dependency edges.
+    pub fn filter(self, filter: &GraphFilter) -> GraphInfo {
+        let selected: HashSet<&str> = self
+            .nodes
+            .iter()
+            .filter(|node| filter.matches(node))
+            .map(|node| node.key.as_str())
+            .collect();
+        let edges: Vec<GraphEdgeInfo> = self
+            .edges
+            .iter()
+            .filter(|edge| selected.contains(edge.source.as_str()))
+            .cloned()
+            .collect();
+        let mut keep: HashSet<String> = selected.iter().map(|key| key.to_string()).collect();
+        keep.extend(edges.iter().map(|edge| edge.target.clone()));
+        GraphInfo {
+            attributes: self.attributes,
+            nodes: self
+                .nodes
+                .into_iter()
+                .filter(|node| keep.contains(&node.key))
+                .collect(),
+            edges,
+        }
+    }
+
+    /// The ego graph of module `technical_name`: what it depends on up to
+    /// `depth` hops away, what depends on it up to `reverse_depth`, and the
+    /// edges among them. Empty when the module isn't in the graph.
+    pub fn ego(self, technical_name: &str, depth: usize, reverse_depth: usize) -> GraphInfo {
+        let center = format!("o_{technical_name}");
+        let mut depends: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut required_by: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &self.edges {
+            depends.entry(&edge.source).or_default().push(&edge.target);
+            required_by
+                .entry(&edge.target)
+                .or_default()
+                .push(&edge.source);
+        }
+        let keep: HashSet<String> = if self.nodes.iter().any(|node| node.key == center) {
+            reach(&depends, &center, depth)
+                .union(&reach(&required_by, &center, reverse_depth))
+                .map(|key| key.to_string())
+                .collect()
+        } else {
+            HashSet::new()
+        };
+        GraphInfo {
+            attributes: self.attributes,
+            nodes: self
+                .nodes
+                .into_iter()
+                .filter(|node| keep.contains(&node.key))
+                .collect(),
+            edges: self
+                .edges
+                .into_iter()
+                .filter(|edge| keep.contains(&edge.source) && keep.contains(&edge.target))
+                .collect(),
+        }
+    }
+
+    /// The graph without its Python package and binary nodes, leaving only
+    /// Odoo modules.
+    pub fn without_external(self) -> GraphInfo {
+        let is_module = |key: &str| key.starts_with("o_");
+        GraphInfo {
+            attributes: self.attributes,
+            nodes: self
+                .nodes
+                .into_iter()
+                .filter(|node| is_module(&node.key))
+                .collect(),
+            edges: self
+                .edges
+                .into_iter()
+                .filter(|edge| is_module(&edge.target))
+                .collect(),
+        }
+    }
+}
+
+/// The hash `/atlas/data` answers conditional requests with.
+pub fn etag_for(data: &str) -> String {
+    Sha256::digest(data.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// The graph of `version_odoo` as JSON with its ETag, freshly built.
+pub fn render(conn: &mut DbConnection, version_odoo: &u8) -> (String, String) {
+    let data = serde_json::to_string(&build(conn, version_odoo)).unwrap_or_default();
+    let etag = etag_for(&data);
+    (data, etag)
+}
+
+/// The stored graph of `version_odoo`, or one built on the spot when the
+/// collector hasn't stored it yet.
+pub fn load(conn: &mut DbConnection, version_odoo: &u8) -> GraphInfo {
+    match get_by_version_odoo(conn, version_odoo) {
+        Some(graph) => serde_json::from_str(&graph.data).unwrap_or_default(),
+        None => build(conn, version_odoo),
+    }
+}
+
+/// The full dependency closure of `technical_names`: those modules, every
+/// module they need directly or transitively (as resolved by
+/// `dependency::get_full_dependency_info`) and the Python packages and
+/// binaries all of them declare. Empty when none of them is collected here.
+pub fn closure(
+    conn: &mut DbConnection,
+    version_odoo: &u8,
+    technical_names: &[String],
+) -> GraphInfo {
+    let mut names: HashSet<String> = technical_names.iter().cloned().collect();
+    for module in module::get_by_technical_name_odoo_version(conn, technical_names, version_odoo) {
+        let info = dependency::get_full_dependency_info(conn, &module);
+        names.extend(info.odoo.into_values().flatten());
+    }
+    load(conn, version_odoo).filter(&GraphFilter {
+        modules: Some(names),
+        ..Default::default()
+    })
+}
+
+pub fn get_by_version_odoo(conn: &mut DbConnection, version_odoo: &u8) -> Option<Model> {
+    atlas_graph::table
+        .filter(atlas_graph::version_odoo.eq(*version_odoo as i32))
+        .select(Model::as_select())
+        .first(conn)
+        .optional()
+        .expect("DB error in atlas_graph::get_by_version_odoo")
+}
+
+/// Rebuilds and stores the graph of `version_odoo`. The collector calls it
+/// at the end of every run of that version.
+pub fn refresh(conn: &mut DbConnection, version_odoo: &u8) -> QueryResult<usize> {
+    let (data, etag) = render(conn, version_odoo);
+    let update_date = get_sqlite_utc_now();
+    diesel::insert_into(atlas_graph::table)
+        .values(NewAtlasGraph {
+            version_odoo: *version_odoo as i32,
+            data: &data,
+            etag: &etag,
+            update_date: &update_date,
+        })
+        .on_conflict(atlas_graph::version_odoo)
+        .do_update()
+        .set((
+            atlas_graph::data.eq(&data),
+            atlas_graph::etag.eq(&etag),
+            atlas_graph::update_date.eq(&update_date),
+        ))
+        .execute(conn)
+}
@@ -0,0 +1,54 @@
+// Copyright Alexandre D. Díaz
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::bot_account;
+use crate::utils::date::get_sqlite_utc_now;
+use crate::DbConnection;
+
+/// Committer names an admin marked as bots by hand (`oghcollector bots
+/// add`), on top of the collector's rules file.
+#[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
+#[diesel(table_name = bot_account, check_for_backend(crate::DbBackend))]
+pub struct Model {
+    pub id: i64,
+    pub name: String,
+    pub create_date: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = bot_account)]
+struct NewBotAccount<'a> {
+    name: &'a str,
+    create_date: &'a str,
+}
+
+pub fn get_all(conn: &mut DbConnection) -> Vec<Model> {
+    bot_account::table
+        .order(bot_account::name.asc())
+        .load::<Model>(conn)
+        .expect("DB error in bot_account::get_all")
+}
+
+pub fn get_names(conn: &mut DbConnection) -> Vec<String> {
+    bot_account::table
+        .select(bot_account::name)
+        .load::<String>(conn)
+        .expect("DB error in bot_account::get_names")
+}
+
+/// Returns the number of inserted rows (0 if the name was already listed).
+pub fn add(conn: &mut DbConnection, name: &str) -> QueryResult<usize> {
+    diesel::insert_into(bot_account::table)
+        .values(NewBotAccount {
+            name,
+            create_date: &get_sqlite_utc_now(),
+        })
+        .on_conflict(bot_account::name)
+        .do_nothing()
+        .execute(conn)
+}
+
+pub fn remove(conn: &mut DbConnection, name: &str) -> QueryResult<usize> {
+    diesel::delete(bot_account::table.filter(bot_account::name.eq(name))).execute(conn)
+}
@@ -0,0 +1,119 @@
+// Copyright Alexandre D. Díaz
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::collection_job;
+use crate::utils::date::get_sqlite_utc_now;
+use crate::DbConnection;
+
+pub const KIND_COLLECT: &str = "collect";
+pub const KIND_PULL_REQUESTS: &str = "pull_requests";
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_RUNNING: &str = "running";
+pub const STATUS_DONE: &str = "done";
+pub const STATUS_FAILED: &str = "failed";
+
+#[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
+#[diesel(table_name = collection_job, check_for_backend(crate::DbBackend))]
+pub struct Model {
+    pub id: i64,
+    pub kind: String,
+    pub source: String,
+    pub branch: String,
+    pub git_type: String,
+    pub repo_url: Option<String>,
+    pub status: String,
+    pub error: Option<String>,
+    pub create_date: String,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = collection_job)]
+struct NewCollectionJob<'a> {
+    kind: &'a str,
+    source: &'a str,
+    branch: &'a str,
+    git_type: &'a str,
+    repo_url: Option<&'a str>,
+    status: &'a str,
+    create_date: &'a str,
+}
+
+/// A job to enqueue, as built by the webhook receiver from an event payload.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JobRequestInfo {
+    pub kind: String,
+    pub source: String,
+    pub branch: String,
+    pub git_type: String,
+    pub repo_url: Option<String>,
+}
+
+/// Returns the number of inserted rows: 0 when the same job is already
+/// pending, so repeated events collapse into the one run.
+pub fn enqueue(conn: &mut DbConnection, job: &JobRequestInfo) -> QueryResult<usize> {
+    diesel::insert_into(collection_job::table)
+        .values(NewCollectionJob {
+            kind: &job.kind,
+            source: &job.source,
+            branch: &job.branch,
+            git_type: &job.git_type,
+            repo_url: job.repo_url.as_deref(),
+            status: STATUS_PENDING,
+            create_date: &get_sqlite_utc_now(),
+        })
+        .on_conflict_do_nothing()
+        .execute(conn)
+}
+
+pub fn get_by_id(conn: &mut DbConnection, id: &i64) -> Option<Model> {
+    collection_job::table
+        .filter(collection_job::id.eq(id))
+        .first::<Model>(conn)
+        .optional()
+        .expect("DB error in collection_job::get_by_id")
+}
+
+/// Pending jobs, oldest first.
+pub fn get_pending(conn: &mut DbConnection) -> Vec<Model> {
+    collection_job::table
+        .filter(collection_job::status.eq(STATUS_PENDING))
+        .order(collection_job::id.asc())
+        .load::<Model>(conn)
+        .expect("DB error in collection_job::get_pending")
+}
+
+/// Moves a pending job to running. `false` if another drain got it first.
+pub fn claim(conn: &mut DbConnection, id: &i64) -> QueryResult<bool> {
+    let updated = diesel::update(
+        collection_job::table.filter(
+            collection_job::id
+                .eq(id)
+                .and(collection_job::status.eq(STATUS_PENDING)),
+        ),
+    )
+    .set((
+        collection_job::status.eq(STATUS_RUNNING),
+        collection_job::start_date.eq(get_sqlite_utc_now()),
+    ))
+    .execute(conn)?;
+    Ok(updated == 1)
+}
+
+pub fn finish(conn: &mut DbConnection, id: &i64, error: Option<&str>) -> QueryResult<usize> {
+    let status = if error.is_some() {
+        STATUS_FAILED
+    } else {
+        STATUS_DONE
+    };
+    diesel::update(collection_job::table.filter(collection_job::id.eq(id)))
+        .set((
+            collection_job::status.eq(status),
+            collection_job::error.eq(error),
+            collection_job::end_date.eq(get_sqlite_utc_now()),
+        ))
+        .execute(conn)
+}
@@ -0,0 +1,166 @@
+// Copyright Alexandre D. Díaz
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::dialect;
+use crate::schema::collection_run;
+use crate::utils::date::get_sqlite_utc_now;
+use crate::DbConnection;
+
+pub const STATUS_RUNNING: &str = "running";
+pub const STATUS_DONE: &str = "done";
+pub const STATUS_FAILED: &str = "failed";
+
+pub const TRIGGER_MANUAL: &str = "manual";
+pub const TRIGGER_SCHEDULE: &str = "schedule";
+pub const TRIGGER_WEBHOOK: &str = "webhook";
+
+#[derive(Queryable, QueryableByName, Selectable, Debug, Deserialize, Serialize, Clone)]
+#[diesel(table_name = collection_run, check_for_backend(crate::DbBackend))]
+pub struct Model {
+    pub id: i64,
+    pub source: String,
+    pub org_name: String,
+    pub version_odoo: i32,
+    pub forge: String,
+    pub trigger: String,
+    pub status: String,
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub duration: Option<i64>,
+    pub repos_count: i32,
+    pub repos_failed: i32,
+    pub modules_added: i32,
+    pub modules_updated: i32,
+    pub modules_removed: i32,
+    pub api_calls: i64,
+    pub error: Option<String>,
+    pub modules_scanned: i32,
+    /// Security findings of the organization's modules once the run was done,
+    /// `None` until they're counted (see `collection_run_security::record`).
+    pub security_errors: Option<i32>,
+    pub security_warnings: Option<i32>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = collection_run)]
+struct NewCollectionRun<'a> {
+    source: &'a str,
+    org_name: &'a str,
+    version_odoo: i32,
+    forge: &'a str,
+    trigger: &'a str,
+    status: &'a str,
+    start_date: &'a str,
+}
+
+/// What a finished run did, summed over its repositories.
+#[derive(Debug, Default, Clone)]
+pub struct RunTotalsInfo {
+    pub repos_count: i32,
+    pub repos_failed: i32,
+    pub modules_added: i32,
+    pub modules_updated: i32,
+    pub modules_removed: i32,
+    pub modules_scanned: i32,
+    pub api_calls: i64,
+}
+
+/// Opens a run. A run of the same organization still marked as running was
+/// killed midway (the collector lock keeps two runs of an organization from
+/// overlapping), so it's closed as failed first.
+pub fn start(
+    conn: &mut DbConnection,
+    source: &str,
+    org_name: &str,
+    version_odoo: &u8,
+    forge: &str,
+    trigger: &str,
+) -> QueryResult<Model> {
+    let now = get_sqlite_utc_now();
+    diesel::update(
+        collection_run::table.filter(
+            collection_run::org_name
+                .eq(org_name)
+                .and(collection_run::status.eq(STATUS_RUNNING)),
+        ),
+    )
+    .set((
+        collection_run::status.eq(STATUS_FAILED),
+        collection_run::error.eq("Interrupted before finishing"),
+    ))
+    .execute(conn)?;
+    diesel::insert_into(collection_run::table)
+        .values(NewCollectionRun {
+            source,
+            org_name,
+            version_odoo: *version_odoo as i32,
+            forge,
+            trigger,
+            status: STATUS_RUNNING,
+            start_date: &now,
+        })
+        .execute(conn)?;
+    let id = crate::models::last_insert_id(conn);
+    Ok(get_by_id(conn, &id).expect("Just inserted collection_run"))
+}
+
+/// Closes a run: `failed` when `error` is set, `done` otherwise.
+pub fn finish(
+    conn: &mut DbConnection,
+    id: &i64,
+    duration: &i64,
+    totals: &RunTotalsInfo,
+    error: Option<&str>,
+) -> QueryResult<usize> {
+    let status = if error.is_some() {
+        STATUS_FAILED
+    } else {
+        STATUS_DONE
+    };
+    diesel::update(collection_run::table.filter(collection_run::id.eq(id)))
+        .set((
+            collection_run::status.eq(status),
+            collection_run::end_date.eq(get_sqlite_utc_now()),
+            collection_run::duration.eq(duration),
+            collection_run::repos_count.eq(totals.repos_count),
+            collection_run::repos_failed.eq(totals.repos_failed),
+            collection_run::modules_added.eq(totals.modules_added),
+            collection_run::modules_updated.eq(totals.modules_updated),
+            collection_run::modules_removed.eq(totals.modules_removed),
+            collection_run::modules_scanned.eq(totals.modules_scanned),
+            collection_run::api_calls.eq(totals.api_calls),
+            collection_run::error.eq(error),
+        ))
+        .execute(conn)
+}
+
+pub fn get_by_id(conn: &mut DbConnection, id: &i64) -> Option<Model> {
+    collection_run::table
+        .filter(collection_run::id.eq(id))
+        .first::<Model>(conn)
+        .optional()
+        .expect("DB error in collection_run::get_by_id")
+}
+
+/// Newest first.
+pub fn get_latest(conn: &mut DbConnection, limit: i64) -> Vec<Model> {
+    collection_run::table
+        .order(collection_run::id.desc())
+        .limit(limit)
+        .load::<Model>(conn)
+        .expect("DB error in collection_run::get_latest")
+}
+
+/// The last finished run of every organization and Odoo version.
+pub fn get_last_finished(conn: &mut DbConnection) -> Vec<Model> {
+    dialect::sql_query(
+        "SELECT * FROM collection_run WHERE id IN (\
+           SELECT MAX(id) FROM collection_run WHERE status != ? \
+           GROUP BY org_name, version_odoo \
+         ) ORDER BY org_name, version_odoo",
+    )
+    .bind::<diesel::sql_types::Text, _>(STATUS_RUNNING)
+    .load::<Model>(conn)
+    .expect("DB error in collection_run::get_last_finished")
+}
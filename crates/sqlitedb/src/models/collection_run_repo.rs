@@ -0,0 +1,137 @@
+// Copyright Alexandre D. Díaz
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::dialect;
+use crate::schema::collection_run_repo;
+use crate::DbConnection;
+
+pub const CLONE_OK: &str = "ok";
+// The repository has no branch for the collected version: expected for most
+// organization repositories on old versions, so it doesn't count as a failure.
+pub const CLONE_MISSING_BRANCH: &str = "missing_branch";
+pub const CLONE_FAILED: &str = "failed";
+
+pub const ANALYSIS_OK: &str = "ok";
+// Some modules were skipped (unreadable manifest, ...), the rest were saved.
+pub const ANALYSIS_PARTIAL: &str = "partial";
+pub const ANALYSIS_FAILED: &str = "failed";
+pub const ANALYSIS_SKIPPED: &str = "skipped";
+
+#[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
+#[diesel(table_name = collection_run_repo, check_for_backend(crate::DbBackend))]
+pub struct Model {
+    pub id: i64,
+    pub collection_run_id: i64,
+    pub org_name: String,
+    pub repo_name: String,
+    pub clone_status: String,
+    pub analysis_status: String,
+    pub modules_added: i32,
+    pub modules_updated: i32,
+    pub modules_removed: i32,
+    pub error: Option<String>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = collection_run_repo)]
+struct NewCollectionRunRepo<'a> {
+    collection_run_id: i64,
+    org_name: &'a str,
+    repo_name: &'a str,
+    clone_status: &'a str,
+    analysis_status: &'a str,
+    modules_added: i32,
+    modules_updated: i32,
+    modules_removed: i32,
+    error: Option<&'a str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RunRepoInfo {
+    pub org_name: String,
+    pub repo_name: String,
+    pub clone_status: String,
+    pub analysis_status: String,
+    pub modules_added: i32,
+    pub modules_updated: i32,
+    pub modules_removed: i32,
+    pub error: Option<String>,
+}
+
+impl RunRepoInfo {
+    pub fn is_failed(&self) -> bool {
+        self.clone_status == CLONE_FAILED || self.analysis_status == ANALYSIS_FAILED
+    }
+}
+
+pub fn add(conn: &mut DbConnection, run_id: &i64, info: &RunRepoInfo) -> QueryResult<usize> {
+    diesel::insert_into(collection_run_repo::table)
+        .values(NewCollectionRunRepo {
+            collection_run_id: *run_id,
+            org_name: &info.org_name,
+            repo_name: &info.repo_name,
+            clone_status: &info.clone_status,
+            analysis_status: &info.analysis_status,
+            modules_added: info.modules_added,
+            modules_updated: info.modules_updated,
+            modules_removed: info.modules_removed,
+            error: info.error.as_deref(),
+        })
+        .execute(conn)
+}
+
+/// Repositories with an error first, then by name.
+pub fn get_by_run_id(conn: &mut DbConnection, run_id: &i64) -> Vec<Model> {
+    collection_run_repo::table
+        .filter(collection_run_repo::collection_run_id.eq(run_id))
+        .order((
+            collection_run_repo::error.is_null(),
+            collection_run_repo::org_name,
+            collection_run_repo::repo_name,
+        ))
+        .load::<Model>(conn)
+        .expect("DB error in collection_run_repo::get_by_run_id")
+}
+
+#[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
+pub struct RepoCollectionInfo {
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub version_odoo: i32,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub collection_run_id: i64,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub end_date: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub clone_status: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub analysis_status: String,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub error: Option<String>,
+}
+
+/// How the last run of every Odoo version went for one repository: whether
+/// its branch was there, and when.
+pub fn get_last_by_repository(
+    conn: &mut DbConnection,
+    org_name: &str,
+    repo_name: &str,
+) -> Vec<RepoCollectionInfo> {
+    dialect::sql_query(
+        "SELECT cr.version_odoo, cr.id as collection_run_id, cr.end_date, \
+         crr.clone_status, crr.analysis_status, crr.error \
+         FROM collection_run_repo as crr \
+         INNER JOIN collection_run as cr ON cr.id = crr.collection_run_id \
+         WHERE crr.id IN (\
+           SELECT MAX(last_crr.id) FROM collection_run_repo as last_crr \
+           INNER JOIN collection_run as last_cr ON last_cr.id = last_crr.collection_run_id \
+           WHERE last_crr.org_name = ?1 AND last_crr.repo_name = ?2 \
+           GROUP BY last_cr.version_odoo \
+         ) \
+         ORDER BY cr.version_odoo DESC",
+    )
+    .bind::<diesel::sql_types::Text, _>(org_name)
+    .bind::<diesel::sql_types::Text, _>(repo_name)
+    .load::<RepoCollectionInfo>(conn)
+    .expect("DB error in collection_run_repo::get_last_by_repository")
+}
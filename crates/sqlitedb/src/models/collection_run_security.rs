@@ -0,0 +1,150 @@
+// Copyright Alexandre D. Díaz
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::module_security_warning::{SEVERITY_ERROR, SEVERITY_WARNING};
+use crate::dialect;
+use crate::schema::{collection_run, collection_run_security};
+use crate::DbConnection;
+
+#[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
+#[diesel(table_name = collection_run_security, check_for_backend(crate::DbBackend))]
+pub struct Model {
+    pub id: i64,
+    pub collection_run_id: i64,
+    pub severity: String,
+    pub code: String,
+    pub count: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = collection_run_security)]
+struct NewCollectionRunSecurity<'a> {
+    collection_run_id: i64,
+    severity: &'a str,
+    code: &'a str,
+    count: i32,
+}
+
+#[derive(QueryableByName, Debug)]
+struct CodeCountInfo {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    severity: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    code: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    count: i64,
+}
+
+/// The findings counted for one run, for the trend on /security.
+#[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
+pub struct SecurityTrendInfo {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub collection_run_id: i64,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub org_name: String,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub version_odoo: i32,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub end_date: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub errors: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub warnings: i64,
+}
+
+/// Counts, per severity and rule, the findings of the current version of
+/// every `org_name` module of `version_odoo`, and stores them against the
+/// run: findings are replaced on every analysis, so this is the only record
+/// of how they evolve.
+pub fn record(
+    conn: &mut DbConnection,
+    run_id: &i64,
+    org_name: &str,
+    version_odoo: &u8,
+) -> QueryResult<()> {
+    let counts = dialect::sql_query(
+        "SELECT msw.severity, msw.code, COUNT(*) as count \
+         FROM module_security_warning as msw \
+         INNER JOIN module_version as mv ON mv.id = msw.module_version_id \
+         INNER JOIN module as mod ON mod.id = msw.module_id AND mod.version_module = mv.version_module \
+         INNER JOIN gh_repository as gh_repo ON gh_repo.id = mod.gh_repository_id \
+         INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id \
+         WHERE gh_org.name = ? AND mod.version_odoo = ? \
+         GROUP BY msw.severity, msw.code \
+         ORDER BY msw.severity, msw.code",
+    )
+    .bind::<diesel::sql_types::Text, _>(org_name)
+    .bind::<diesel::sql_types::Integer, _>(*version_odoo as i32)
+    .load::<CodeCountInfo>(conn)?;
+
+    diesel::delete(
+        collection_run_security::table
+            .filter(collection_run_security::collection_run_id.eq(run_id)),
+    )
+    .execute(conn)?;
+    let new_rows: Vec<NewCollectionRunSecurity> = counts
+        .iter()
+        .map(|row| NewCollectionRunSecurity {
+            collection_run_id: *run_id,
+            severity: &row.severity,
+            code: &row.code,
+            count: row.count as i32,
+        })
+        .collect();
+    if !new_rows.is_empty() {
+        diesel::insert_into(collection_run_security::table)
+            .values(&new_rows)
+            .execute(conn)?;
+    }
+
+    let total = |severity: &str| -> i32 {
+        counts
+            .iter()
+            .filter(|row| row.severity == severity)
+            .map(|row| row.count as i32)
+            .sum()
+    };
+    diesel::update(collection_run::table.filter(collection_run::id.eq(run_id)))
+        .set((
+            collection_run::security_errors.eq(total(SEVERITY_ERROR)),
+            collection_run::security_warnings.eq(total(SEVERITY_WARNING)),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// The last `limit` runs that counted their findings, newest first. With
+/// `code`, only that rule's findings are summed.
+pub fn get_trend(
+    conn: &mut DbConnection,
+    version_odoo: Option<&u8>,
+    org_name: Option<&str>,
+    code: Option<&str>,
+    limit: i64,
+) -> Vec<SecurityTrendInfo> {
+    dialect::sql_query(
+        "SELECT cr.id as collection_run_id, cr.org_name, cr.version_odoo, cr.end_date, \
+         COALESCE(SUM(CASE WHEN crs.severity = ?1 THEN crs.count ELSE 0 END), 0) as errors, \
+         COALESCE(SUM(CASE WHEN crs.severity = ?2 THEN crs.count ELSE 0 END), 0) as warnings \
+         FROM collection_run as cr \
+         LEFT JOIN collection_run_security as crs ON crs.collection_run_id = cr.id \
+           AND (?3 IS NULL OR crs.code = ?3) \
+         WHERE cr.security_errors IS NOT NULL \
+           AND (?4 IS NULL OR cr.version_odoo = ?4) \
+           AND (?5 IS NULL OR cr.org_name = ?5) \
+         GROUP BY cr.id, cr.org_name, cr.version_odoo, cr.end_date \
+         ORDER BY cr.id DESC \
+         LIMIT ?6",
+    )
+    .bind::<diesel::sql_types::Text, _>(SEVERITY_ERROR)
+    .bind::<diesel::sql_types::Text, _>(SEVERITY_WARNING)
+    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(code)
+    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Integer>, _>(
+        version_odoo.map(|v| *v as i32),
+    )
+    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(org_name)
+    .bind::<diesel::sql_types::BigInt, _>(limit)
+    .load::<SecurityTrendInfo>(conn)
+    .expect("DB error in collection_run_security::get_trend")
+}
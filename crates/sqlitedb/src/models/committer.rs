@@ -1,15 +1,19 @@
 // Copyright Alexandre D. Díaz
 use diesel::prelude::*;
+use diesel::sql_types::BigInt;
 use serde::{Deserialize, Serialize};
 
-use crate::models::BOT_COMMITTERS;
+use crate::dialect;
+use crate::models::committer_email;
 use crate::schema::committer;
+use crate::DbConnection;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = committer, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = committer, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub name: String,
+    pub is_bot: bool,
 }
 
 #[derive(Insertable)]
@@ -18,7 +22,7 @@ struct NewCommitter<'a> {
     name: &'a str,
 }
 
-pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
+pub fn get_by_id(conn: &mut DbConnection, id: &i64) -> Option<Model> {
     committer::table
         .filter(committer::id.eq(id))
         .first::<Model>(conn)
@@ -26,7 +30,7 @@ pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
         .expect("DB error in committer::get_by_id")
 }
 
-pub fn get_by_name(conn: &mut SqliteConnection, name: &str) -> Option<Model> {
+pub fn get_by_name(conn: &mut DbConnection, name: &str) -> Option<Model> {
     committer::table
         .filter(committer::name.eq(name))
         .first::<Model>(conn)
@@ -44,18 +48,18 @@ pub struct GlobalRank {
     pub total_committers: i64,
 }
 
-pub fn get_global_rank_by_name(conn: &mut SqliteConnection, name: &str) -> Option<GlobalRank> {
-    diesel::sql_query(format!(
+pub fn get_global_rank_by_name(conn: &mut DbConnection, name: &str) -> Option<GlobalRank> {
+    dialect::sql_query(
         "SELECT rank, total_commits, total_committers FROM (\
            SELECT com.name as committer_name, SUM(mod_com.commits) as total_commits, \
                   RANK() OVER (ORDER BY SUM(mod_com.commits) DESC) as rank, \
                   COUNT(*) OVER () as total_committers \
            FROM module_committer as mod_com \
            INNER JOIN committer as com ON mod_com.committer_id = com.id \
-           WHERE com.name NOT IN ({BOT_COMMITTERS}) \
+           WHERE NOT com.is_bot \
            GROUP BY com.id \
-         ) WHERE committer_name = ?"
-    ))
+         ) as ranked WHERE committer_name = ?",
+    )
     .bind::<diesel::sql_types::Text, _>(name)
     .load::<GlobalRank>(conn)
     .expect("DB error in committer::get_global_rank_by_name")
@@ -77,19 +81,19 @@ pub struct GlobalRankEntry {
     pub modules_touched: i64,
 }
 
-pub fn rank_global(conn: &mut SqliteConnection, limit: i64) -> Vec<GlobalRankEntry> {
-    diesel::sql_query(format!(
+pub fn rank_global(conn: &mut DbConnection, limit: i64) -> Vec<GlobalRankEntry> {
+    dialect::sql_query(
         "SELECT com.name as name, SUM(mod_com.commits) as total_commits, \
                 RANK() OVER (ORDER BY SUM(mod_com.commits) DESC) as rank, \
                 COUNT(*) OVER () as total_committers, \
                 COUNT(DISTINCT mod_com.module_id) as modules_touched \
          FROM module_committer as mod_com \
          INNER JOIN committer as com ON mod_com.committer_id = com.id \
-         WHERE com.name NOT IN ({BOT_COMMITTERS}) \
+         WHERE NOT com.is_bot \
          GROUP BY com.id \
          ORDER BY total_commits DESC \
-         LIMIT ?"
-    ))
+         LIMIT ?",
+    )
     .bind::<diesel::sql_types::BigInt, _>(limit)
     .load::<GlobalRankEntry>(conn)
     .expect("DB error in committer::rank_global")
@@ -105,8 +109,8 @@ pub struct CommitterListInfo {
     pub modules_touched: i64,
 }
 
-pub fn list(conn: &mut SqliteConnection) -> Vec<CommitterListInfo> {
-    diesel::sql_query(
+pub fn list(conn: &mut DbConnection) -> Vec<CommitterListInfo> {
+    dialect::sql_query(
         "SELECT com.name as name, SUM(mc.commits) as total_commits, \
          COUNT(DISTINCT mc.module_id) as modules_touched \
          FROM committer as com \
@@ -118,7 +122,7 @@ pub fn list(conn: &mut SqliteConnection) -> Vec<CommitterListInfo> {
     .expect("DB error in committer::list")
 }
 
-pub fn add(conn: &mut SqliteConnection, name: &str) -> QueryResult<Model> {
+fn insert(conn: &mut DbConnection, name: &str) -> QueryResult<Model> {
     let inserted = diesel::insert_into(committer::table)
         .values(NewCommitter { name })
         .on_conflict(committer::name)
@@ -130,10 +134,160 @@ pub fn add(conn: &mut SqliteConnection, name: &str) -> QueryResult<Model> {
             .filter(committer::name.eq(name))
             .first::<Model>(conn)
     } else {
-        let id = crate::models::last_insert_rowid(conn);
+        let id = crate::models::last_insert_id(conn);
         Ok(Model {
             id,
             name: name.to_string(),
+            is_bot: false,
         })
     }
 }
+
+/// Moves everything recorded for `from_id` (emails, per-module and
+/// per-period activity, owned lines) onto `into_id` and drops `from_id`, in
+/// one transaction. What both committers have on the same module, period or
+/// version snapshot is summed.
+pub fn merge(conn: &mut DbConnection, from_id: &i64, into_id: &i64) -> QueryResult<()> {
+    if from_id == into_id {
+        return Ok(());
+    }
+    conn.transaction(|conn| merge_rows(conn, from_id, into_id))
+}
+
+fn merge_rows(conn: &mut DbConnection, from_id: &i64, into_id: &i64) -> QueryResult<()> {
+    committer_email::reassign(conn, from_id, into_id)?;
+    dialect::sql_query(
+        "UPDATE module_committer SET \
+         commits = commits + (SELECT f.commits FROM module_committer as f \
+           WHERE f.committer_id = ?1 AND f.module_id = module_committer.module_id), \
+         insertions = insertions + (SELECT f.insertions FROM module_committer as f \
+           WHERE f.committer_id = ?1 AND f.module_id = module_committer.module_id), \
+         deletions = deletions + (SELECT f.deletions FROM module_committer as f \
+           WHERE f.committer_id = ?1 AND f.module_id = module_committer.module_id) \
+         WHERE committer_id = ?2 AND module_id IN \
+           (SELECT module_id FROM module_committer WHERE committer_id = ?1)",
+    )
+    .bind::<BigInt, _>(from_id)
+    .bind::<BigInt, _>(into_id)
+    .execute(conn)?;
+    dialect::sql_query(
+        "DELETE FROM module_committer WHERE committer_id = ?1 AND module_id IN \
+         (SELECT module_id FROM module_committer WHERE committer_id = ?2)",
+    )
+    .bind::<BigInt, _>(from_id)
+    .bind::<BigInt, _>(into_id)
+    .execute(conn)?;
+    dialect::sql_query("UPDATE module_committer SET committer_id = ?2 WHERE committer_id = ?1")
+        .bind::<BigInt, _>(from_id)
+        .bind::<BigInt, _>(into_id)
+        .execute(conn)?;
+    dialect::sql_query(
+        "UPDATE module_committer_period SET \
+         commits = commits + (SELECT f.commits FROM module_committer_period as f \
+           WHERE f.committer_id = ?1 AND f.module_id = module_committer_period.module_id \
+           AND f.year = module_committer_period.year AND f.month = module_committer_period.month) \
+         WHERE committer_id = ?2 AND EXISTS (SELECT 1 FROM module_committer_period as f \
+           WHERE f.committer_id = ?1 AND f.module_id = module_committer_period.module_id \
+           AND f.year = module_committer_period.year AND f.month = module_committer_period.month)",
+    )
+    .bind::<BigInt, _>(from_id)
+    .bind::<BigInt, _>(into_id)
+    .execute(conn)?;
+    dialect::sql_query(
+        "DELETE FROM module_committer_period WHERE committer_id = ?1 AND EXISTS \
+         (SELECT 1 FROM module_committer_period as t WHERE t.committer_id = ?2 \
+           AND t.module_id = module_committer_period.module_id \
+           AND t.year = module_committer_period.year AND t.month = module_committer_period.month)",
+    )
+    .bind::<BigInt, _>(from_id)
+    .bind::<BigInt, _>(into_id)
+    .execute(conn)?;
+    dialect::sql_query(
+        "UPDATE module_committer_period SET committer_id = ?2 WHERE committer_id = ?1",
+    )
+    .bind::<BigInt, _>(from_id)
+    .bind::<BigInt, _>(into_id)
+    .execute(conn)?;
+    dialect::sql_query(
+        "UPDATE module_ownership SET \
+         lines = lines + (SELECT f.lines FROM module_ownership as f \
+           WHERE f.committer_id = ?1 AND f.module_version_id = module_ownership.module_version_id) \
+         WHERE committer_id = ?2 AND module_version_id IN \
+           (SELECT module_version_id FROM module_ownership WHERE committer_id = ?1)",
+    )
+    .bind::<BigInt, _>(from_id)
+    .bind::<BigInt, _>(into_id)
+    .execute(conn)?;
+    dialect::sql_query(
+        "DELETE FROM module_ownership WHERE committer_id = ?1 AND module_version_id IN \
+         (SELECT module_version_id FROM module_ownership WHERE committer_id = ?2)",
+    )
+    .bind::<BigInt, _>(from_id)
+    .bind::<BigInt, _>(into_id)
+    .execute(conn)?;
+    dialect::sql_query("UPDATE module_ownership SET committer_id = ?2 WHERE committer_id = ?1")
+        .bind::<BigInt, _>(from_id)
+        .bind::<BigInt, _>(into_id)
+        .execute(conn)?;
+    diesel::delete(committer::table.filter(committer::id.eq(from_id))).execute(conn)?;
+    Ok(())
+}
+
+/// Returns the committer for a (post-.mailmap) identity, merging the rows
+/// that turn out to be the same person: a committer already known by one of
+/// `emails` is that person even under another name. The incoming `name` is
+/// the canonical spelling (it's what the mailmaps resolved to), so the
+/// email's committer is renamed to it, or merged into the committer that
+/// already holds it.
+pub fn add(conn: &mut DbConnection, name: &str, emails: &[String]) -> QueryResult<Model> {
+    let by_email = committer_email::get_committer_id_by_emails(conn, emails);
+    let com = match (by_email, get_by_name(conn, name)) {
+        (Some(email_id), Some(by_name)) => {
+            merge(conn, &email_id, &by_name.id)?;
+            by_name
+        }
+        (Some(email_id), None) => {
+            diesel::update(committer::table.filter(committer::id.eq(email_id)))
+                .set(committer::name.eq(name))
+                .execute(conn)?;
+            committer::table
+                .filter(committer::id.eq(email_id))
+                .first::<Model>(conn)?
+        }
+        (None, Some(by_name)) => by_name,
+        (None, None) => insert(conn, name)?,
+    };
+    committer_email::add(conn, &com.id, emails)?;
+    Ok(com)
+}
+
+pub fn get_all(conn: &mut DbConnection) -> Vec<Model> {
+    committer::table
+        .order(committer::id.asc())
+        .load::<Model>(conn)
+        .expect("DB error in committer::get_all")
+}
+
+/// Flags exactly `bot_ids` as bots, clearing the flag everywhere else.
+/// Returns the number of committers whose flag changed.
+pub fn set_bots(conn: &mut DbConnection, bot_ids: &[i64]) -> QueryResult<usize> {
+    let flagged = diesel::update(
+        committer::table.filter(
+            committer::id
+                .eq_any(bot_ids)
+                .and(committer::is_bot.eq(false)),
+        ),
+    )
+    .set(committer::is_bot.eq(true))
+    .execute(conn)?;
+    let cleared = diesel::update(
+        committer::table.filter(
+            committer::id
+                .ne_all(bot_ids)
+                .and(committer::is_bot.eq(true)),
+        ),
+    )
+    .set(committer::is_bot.eq(false))
+    .execute(conn)?;
+    Ok(flagged + cleared)
+}
@@ -0,0 +1,91 @@
+// Copyright Alexandre D. Díaz
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::committer_email;
+use crate::utils::date::get_sqlite_utc_now;
+use crate::DbConnection;
+
+#[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
+#[diesel(table_name = committer_email, check_for_backend(crate::DbBackend))]
+pub struct Model {
+    pub id: i64,
+    pub committer_id: i64,
+    pub email: String,
+    pub create_date: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = committer_email)]
+struct NewCommitterEmail<'a> {
+    committer_id: i64,
+    email: &'a str,
+    create_date: &'a str,
+}
+
+/// Emails are compared case-insensitively (git doesn't, but people do mix
+/// `Foo@Bar.com` and `foo@bar.com` across machines).
+pub fn normalize(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// First committer owning any of `emails`, in the given order.
+pub fn get_committer_id_by_emails(conn: &mut DbConnection, emails: &[String]) -> Option<i64> {
+    for email in emails {
+        let committer_id = committer_email::table
+            .filter(committer_email::email.eq(normalize(email)))
+            .select(committer_email::committer_id)
+            .first::<i64>(conn)
+            .optional()
+            .expect("DB error in committer_email::get_committer_id_by_emails");
+        if committer_id.is_some() {
+            return committer_id;
+        }
+    }
+    None
+}
+
+pub fn get_by_committer_id(conn: &mut DbConnection, committer_id: &i64) -> Vec<Model> {
+    committer_email::table
+        .filter(committer_email::committer_id.eq(committer_id))
+        .order(committer_email::email.asc())
+        .load::<Model>(conn)
+        .expect("DB error in committer_email::get_by_committer_id")
+}
+
+/// Links `emails` to the committer. An email already owned by someone
+/// (including this committer) is left where it is.
+pub fn add(conn: &mut DbConnection, committer_id: &i64, emails: &[String]) -> QueryResult<usize> {
+    let create_date = get_sqlite_utc_now();
+    let emails: Vec<String> = emails
+        .iter()
+        .map(|email| normalize(email))
+        .filter(|email| !email.is_empty())
+        .collect();
+    let new_rows: Vec<NewCommitterEmail> = emails
+        .iter()
+        .map(|email| NewCommitterEmail {
+            committer_id: *committer_id,
+            email,
+            create_date: &create_date,
+        })
+        .collect();
+    if new_rows.is_empty() {
+        return Ok(0);
+    }
+    // One row at a time: diesel can't batch ON CONFLICT inserts on SQLite.
+    let mut inserted = 0;
+    for row in &new_rows {
+        inserted += diesel::insert_into(committer_email::table)
+            .values(row)
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+    }
+    Ok(inserted)
+}
+
+pub fn reassign(conn: &mut DbConnection, from_id: &i64, into_id: &i64) -> QueryResult<usize> {
+    diesel::update(committer_email::table.filter(committer_email::committer_id.eq(from_id)))
+        .set(committer_email::committer_id.eq(into_id))
+        .execute(conn)
+}
@@ -0,0 +1,80 @@
+// Copyright Alexandre D. Díaz
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::dataset_source;
+use crate::utils::date::get_sqlite_utc_now;
+use crate::DbConnection;
+
+/// Another instance whose dataset was merged in with `oghcollector import`.
+/// Repositories point at it through `gh_repository.dataset_source_id`.
+#[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
+#[diesel(table_name = dataset_source, check_for_backend(crate::DbBackend))]
+pub struct Model {
+    pub id: i64,
+    pub name: String,
+    pub format_version: i32,
+    pub export_date: String,
+    pub import_date: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = dataset_source)]
+struct NewDatasetSource<'a> {
+    name: &'a str,
+    format_version: i32,
+    export_date: &'a str,
+    import_date: &'a str,
+}
+
+pub fn get_by_id(conn: &mut DbConnection, id: &i64) -> Option<Model> {
+    dataset_source::table
+        .filter(dataset_source::id.eq(id))
+        .first::<Model>(conn)
+        .optional()
+        .expect("DB error in dataset_source::get_by_id")
+}
+
+pub fn get_by_name(conn: &mut DbConnection, name: &str) -> Option<Model> {
+    dataset_source::table
+        .filter(dataset_source::name.eq(name))
+        .first::<Model>(conn)
+        .optional()
+        .expect("DB error in dataset_source::get_by_name")
+}
+
+pub fn get_all(conn: &mut DbConnection) -> Vec<Model> {
+    dataset_source::table
+        .order(dataset_source::name.asc())
+        .load::<Model>(conn)
+        .expect("DB error in dataset_source::get_all")
+}
+
+/// Registers an import of `name`'s dataset, or refreshes the source's export
+/// and import dates when it was imported before.
+pub fn add(
+    conn: &mut DbConnection,
+    name: &str,
+    format_version: i32,
+    export_date: &str,
+) -> QueryResult<Model> {
+    let import_date = get_sqlite_utc_now();
+    diesel::insert_into(dataset_source::table)
+        .values(NewDatasetSource {
+            name,
+            format_version,
+            export_date,
+            import_date: &import_date,
+        })
+        .on_conflict(dataset_source::name)
+        .do_update()
+        .set((
+            dataset_source::format_version.eq(format_version),
+            dataset_source::export_date.eq(export_date),
+            dataset_source::import_date.eq(&import_date),
+        ))
+        .execute(conn)?;
+    dataset_source::table
+        .filter(dataset_source::name.eq(name))
+        .first::<Model>(conn)
+}
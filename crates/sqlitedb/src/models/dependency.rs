@@ -3,12 +3,14 @@ use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
+use crate::dialect;
 use crate::schema::dependency;
+use crate::DbConnection;
 
 use super::{dependency_module, module};
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = dependency, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = dependency, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub dependency_type_id: i64,
@@ -34,7 +36,7 @@ struct NewDependency<'a> {
     name: &'a str,
 }
 
-pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
+pub fn get_by_id(conn: &mut DbConnection, id: &i64) -> Option<Model> {
     dependency::table
         .filter(dependency::id.eq(id))
         .first::<Model>(conn)
@@ -42,7 +44,7 @@ pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
         .expect("DB error in dependency::get_by_id")
 }
 
-pub fn get_by_name(conn: &mut SqliteConnection, dep_type_id: &i64, name: &str) -> Option<Model> {
+pub fn get_by_name(conn: &mut DbConnection, dep_type_id: &i64, name: &str) -> Option<Model> {
     dependency::table
         .filter(
             dependency::dependency_type_id
@@ -55,11 +57,11 @@ pub fn get_by_name(conn: &mut SqliteConnection, dep_type_id: &i64, name: &str) -
 }
 
 pub fn get_module_external_dependency_names(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_id: &i64,
     dep_type: &str,
 ) -> Vec<String> {
-    diesel::sql_query(
+    dialect::sql_query(
         "SELECT dep.name \
          FROM dependency as dep \
          INNER JOIN dependency_module as dep_mod ON dep_mod.dependency_id = dep.id \
@@ -77,10 +79,10 @@ pub fn get_module_external_dependency_names(
 }
 
 pub fn get_module_dependency_info(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_id: &i64,
 ) -> Vec<DependencyModuleInfo> {
-    diesel::sql_query(
+    dialect::sql_query(
         "SELECT ghorg.name as org, ghrepo.name as repo, mod_dep.id as module_id, dep.name as module_name \
          FROM dependency as dep \
          INNER JOIN dependency_module as dep_mod ON dep_mod.dependency_id = dep.id \
@@ -107,7 +109,7 @@ pub struct FullDependencyInfo {
 }
 
 fn collect_full_dependency_info(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     mod_: &module::Model,
     info: &mut FullDependencyInfo,
     visited: &mut HashSet<i64>,
@@ -138,7 +140,7 @@ fn collect_full_dependency_info(
 }
 
 pub fn get_full_dependency_info(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     mod_: &module::Model,
 ) -> FullDependencyInfo {
     let mut info = FullDependencyInfo::default();
@@ -162,7 +164,7 @@ pub struct FullDependencyInfoWithUnresolved {
 }
 
 fn collect_full_dependency_info_with_unresolved(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     mod_: &module::Model,
     module_dep_type_id: &i64,
     result: &mut FullDependencyInfoWithUnresolved,
@@ -217,7 +219,7 @@ fn collect_full_dependency_info_with_unresolved(
 /// plan tool's "this jump is blocked by an unported dependency" signal,
 /// which a caller that only recurses into what resolves can never see.
 pub fn get_full_dependency_info_with_unresolved(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     mod_: &module::Model,
     module_dep_type_id: &i64,
 ) -> FullDependencyInfoWithUnresolved {
@@ -239,7 +241,7 @@ pub fn get_full_dependency_info_with_unresolved(
     result
 }
 
-pub fn add(conn: &mut SqliteConnection, dep_type_id: &i64, name: &str) -> QueryResult<Model> {
+pub fn add(conn: &mut DbConnection, dep_type_id: &i64, name: &str) -> QueryResult<Model> {
     let inserted = diesel::insert_into(dependency::table)
         .values(NewDependency {
             dependency_type_id: *dep_type_id,
@@ -258,7 +260,7 @@ pub fn add(conn: &mut SqliteConnection, dep_type_id: &i64, name: &str) -> QueryR
             )
             .first::<Model>(conn)
     } else {
-        let id = crate::models::last_insert_rowid(conn);
+        let id = crate::models::last_insert_id(conn);
         Ok(Model {
             id,
             dependency_type_id: *dep_type_id,
@@ -271,19 +273,14 @@ pub fn add(conn: &mut SqliteConnection, dep_type_id: &i64, name: &str) -> QueryR
 mod tests {
     use super::*;
     use crate::models::{dependency_module, dependency_type, module};
-    use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
     use std::collections::HashMap;
 
-    const MIGRATIONS: EmbeddedMigrations = embed_migrations!("../../migrations");
-
-    fn setup_db() -> SqliteConnection {
-        let mut conn = SqliteConnection::establish(":memory:").unwrap();
-        conn.run_pending_migrations(MIGRATIONS).unwrap();
-        conn
+    fn setup_db() -> DbConnection {
+        crate::establish_test_connection()
     }
 
     fn make_module(
-        conn: &mut SqliteConnection,
+        conn: &mut DbConnection,
         tech_name: &str,
         git_org: &str,
         git_repo: &str,
@@ -296,6 +293,7 @@ mod tests {
                 version_odoo,
                 name: tech_name.to_string(),
                 version_module: "1.0.0".to_string(),
+                summary: String::new(),
                 description: String::new(),
                 installation: String::new(),
                 usage: String::new(),
@@ -327,17 +325,12 @@ mod tests {
         .unwrap()
     }
 
-    fn link_module_dep(conn: &mut SqliteConnection, from: &module::Model, to: &module::Model) {
+    fn link_module_dep(conn: &mut DbConnection, from: &module::Model, to: &module::Model) {
         let dep_type = dependency_type::get_by_name(conn, "module").unwrap();
         dependency_module::add(conn, &dep_type.id, &to.technical_name, &from.id).unwrap();
     }
 
-    fn link_external_dep(
-        conn: &mut SqliteConnection,
-        dep_type: &str,
-        name: &str,
-        on: &module::Model,
-    ) {
+    fn link_external_dep(conn: &mut DbConnection, dep_type: &str, name: &str, on: &module::Model) {
         let dep_type = dependency_type::get_by_name(conn, dep_type).unwrap();
         dependency_module::add(conn, &dep_type.id, name, &on.id).unwrap();
     }
@@ -2,13 +2,15 @@
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::dialect;
 use crate::schema::dependency_module;
+use crate::DbConnection;
 
 use super::{dependency, module, system_event};
 use oghutils::version::odoo_version_u8_to_string;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = dependency_module, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = dependency_module, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub dependency_id: i64,
@@ -36,8 +38,8 @@ struct NewDependencyModule {
     module_id: i64,
 }
 
-pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<ModelFull> {
-    diesel::sql_query(
+pub fn get_by_id(conn: &mut DbConnection, id: &i64) -> Option<ModelFull> {
+    dialect::sql_query(
         "SELECT mod_dep.id, mod_dep.dependency_id, dep.name as dependency_name, \
          mod_dep.module_id, mod.technical_name as module_technical_name \
          FROM dependency_module as mod_dep \
@@ -52,7 +54,7 @@ pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<ModelFull> {
 }
 
 fn get_by_dependency_id_module_id(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     dep_id: &i64,
     mod_id: &i64,
 ) -> Option<Model> {
@@ -67,8 +69,8 @@ fn get_by_dependency_id_module_id(
         .expect("DB error in dependency_module::get_by_dependency_id_module_id")
 }
 
-pub fn get_names(conn: &mut SqliteConnection, module_id: &i64, dep_type_id: &i64) -> Vec<String> {
-    diesel::sql_query(
+pub fn get_names(conn: &mut DbConnection, module_id: &i64, dep_type_id: &i64) -> Vec<String> {
+    dialect::sql_query(
         "SELECT d.name \
          FROM dependency_module as dm \
          INNER JOIN dependency as d ON dm.dependency_id = d.id \
@@ -84,7 +86,7 @@ pub fn get_names(conn: &mut SqliteConnection, module_id: &i64, dep_type_id: &i64
 }
 
 pub fn add(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     dep_type_id: &i64,
     name: &str,
     module_id: &i64,
@@ -109,7 +111,7 @@ pub fn add(
             module_id: *module_id,
         })
         .execute(conn)?;
-    let new_id = crate::models::last_insert_rowid(conn);
+    let new_id = crate::models::last_insert_id(conn);
     let mod_info = module::get_by_id(conn, module_id).unwrap();
     let _ = system_event::register_new_dependency_module(
         conn,
@@ -127,13 +129,13 @@ pub fn add(
     })
 }
 
-pub fn delete_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> QueryResult<usize> {
+pub fn delete_by_module_id(conn: &mut DbConnection, module_id: &i64) -> QueryResult<usize> {
     diesel::delete(dependency_module::table.filter(dependency_module::module_id.eq(module_id)))
         .execute(conn)
 }
 
 pub fn delete_by_module_id_dependecy_id(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_id: &i64,
     dependency_id: &i64,
 ) -> QueryResult<usize> {
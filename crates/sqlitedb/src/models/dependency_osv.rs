@@ -2,7 +2,9 @@
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::dialect;
 use crate::schema::dependency_osv;
+use crate::DbConnection;
 
 use super::{dependency, dependency_module, module, system_event};
 use oghutils::version::odoo_version_u8_to_string;
@@ -53,11 +55,11 @@ struct NewDependencyOsv<'a> {
 }
 
 fn get_by_dep_mod_id_osv_id_impl(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     dep_mod_id: &i64,
     osv_id: &str,
 ) -> Option<Model> {
-    diesel::sql_query(
+    dialect::sql_query(
         "SELECT dep_o.id, dep_o.dependency_module_id, dep.name as dependency_name, \
          dep_o.osv_id, dep_o.details, dep_o.fixed_in \
          FROM dependency_osv as dep_o \
@@ -72,8 +74,48 @@ fn get_by_dep_mod_id_osv_id_impl(
     .expect("DB error in dependency_osv::get_by_dep_mod_id_osv_id")
 }
 
-pub fn get_osv_info(conn: &mut SqliteConnection) -> Vec<DependencyModuleOSVInfo> {
-    diesel::sql_query(
+/// Vulnerabilities found in the dependencies of one module.
+pub fn get_by_module_id(conn: &mut DbConnection, module_id: &i64) -> Vec<Model> {
+    dialect::sql_query(
+        "SELECT dep_o.id, dep_o.dependency_module_id, dep.name as dependency_name, \
+         dep_o.osv_id, dep_o.details, dep_o.fixed_in \
+         FROM dependency_osv as dep_o \
+         INNER JOIN dependency_module as dep_mod ON dep_mod.id = dep_o.dependency_module_id \
+         INNER JOIN dependency as dep ON dep.id = dep_mod.dependency_id \
+         WHERE dep_mod.module_id = ? \
+         ORDER BY dep.name, dep_o.osv_id",
+    )
+    .bind::<diesel::sql_types::BigInt, _>(module_id)
+    .load::<Model>(conn)
+    .expect("DB error in dependency_osv::get_by_module_id")
+}
+
+#[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
+pub struct OsvCountInfo {
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub version_odoo: i32,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub count: i64,
+}
+
+/// Distinct vulnerabilities in the dependencies of one repository's modules,
+/// by Odoo version.
+pub fn count_by_repository(conn: &mut DbConnection, gh_repository_id: &i64) -> Vec<OsvCountInfo> {
+    dialect::sql_query(
+        "SELECT mod.version_odoo, COUNT(DISTINCT dep_o.osv_id) as count \
+         FROM dependency_osv as dep_o \
+         INNER JOIN dependency_module as dep_mod ON dep_mod.id = dep_o.dependency_module_id \
+         INNER JOIN module as mod ON mod.id = dep_mod.module_id \
+         WHERE mod.gh_repository_id = ? \
+         GROUP BY mod.version_odoo",
+    )
+    .bind::<diesel::sql_types::BigInt, _>(gh_repository_id)
+    .load::<OsvCountInfo>(conn)
+    .expect("DB error in dependency_osv::count_by_repository")
+}
+
+pub fn get_osv_info(conn: &mut DbConnection) -> Vec<DependencyModuleOSVInfo> {
+    dialect::sql_query(
         "SELECT mod.version_odoo, mod.name as module_name, mod.technical_name as module_technical_name, \
          org.name as org_name, dep.name, dep_o.osv_id, dep_o.details, dep_o.fixed_in \
          FROM dependency_osv as dep_o \
@@ -88,7 +130,7 @@ pub fn get_osv_info(conn: &mut SqliteConnection) -> Vec<DependencyModuleOSVInfo>
 }
 
 pub fn add(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     dep_mod_id: &i64,
     osv_id: &str,
     details: &str,
@@ -109,7 +151,7 @@ pub fn add(
             fixed_in,
         })
         .execute(conn)?;
-    let new_id = crate::models::last_insert_rowid(conn);
+    let new_id = crate::models::last_insert_id(conn);
 
     if let Some(mod_info) = module::get_by_id(conn, &dep_mod.module_id) {
         let _ = system_event::register_new_osv_vulnerability(
@@ -131,3 +173,21 @@ pub fn add(
         fixed_in: fixed_in.to_string(),
     })
 }
+
+/// Drops the dependency's findings missing from `osv_ids`: used when they are
+/// mirrored from an imported dataset, where a finding the source no longer
+/// reports was fixed or withdrawn there.
+pub fn delete_missing(
+    conn: &mut DbConnection,
+    dep_mod_id: &i64,
+    osv_ids: &[&str],
+) -> QueryResult<usize> {
+    diesel::delete(
+        dependency_osv::table.filter(
+            dependency_osv::dependency_module_id
+                .eq(dep_mod_id)
+                .and(dependency_osv::osv_id.ne_all(osv_ids)),
+        ),
+    )
+    .execute(conn)
+}
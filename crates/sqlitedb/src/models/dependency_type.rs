@@ -3,9 +3,10 @@ use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::schema::dependency_type;
+use crate::DbConnection;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = dependency_type, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = dependency_type, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub name: String,
@@ -17,7 +18,7 @@ struct NewDependencyType<'a> {
     name: &'a str,
 }
 
-pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
+pub fn get_by_id(conn: &mut DbConnection, id: &i64) -> Option<Model> {
     dependency_type::table
         .filter(dependency_type::id.eq(id))
         .first::<Model>(conn)
@@ -25,7 +26,7 @@ pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
         .expect("DB error in dependency_type::get_by_id")
 }
 
-pub fn get_by_name(conn: &mut SqliteConnection, name: &str) -> Option<Model> {
+pub fn get_by_name(conn: &mut DbConnection, name: &str) -> Option<Model> {
     dependency_type::table
         .filter(dependency_type::name.eq(name))
         .first::<Model>(conn)
@@ -3,11 +3,12 @@ use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::schema::gh_organization;
+use crate::DbConnection;
 
 use super::system_event;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = gh_organization, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = gh_organization, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub name: String,
@@ -19,7 +20,7 @@ struct NewGhOrganization<'a> {
     name: &'a str,
 }
 
-pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
+pub fn get_by_id(conn: &mut DbConnection, id: &i64) -> Option<Model> {
     gh_organization::table
         .filter(gh_organization::id.eq(id))
         .first::<Model>(conn)
@@ -27,7 +28,7 @@ pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
         .expect("DB error in gh_organization::get_by_id")
 }
 
-pub fn get_by_name(conn: &mut SqliteConnection, name: &str) -> Option<Model> {
+pub fn get_by_name(conn: &mut DbConnection, name: &str) -> Option<Model> {
     gh_organization::table
         .filter(gh_organization::name.eq(name))
         .first::<Model>(conn)
@@ -35,14 +36,23 @@ pub fn get_by_name(conn: &mut SqliteConnection, name: &str) -> Option<Model> {
         .expect("DB error in gh_organization::get_by_name")
 }
 
-pub fn count(conn: &mut SqliteConnection) -> i64 {
+/// Every organization name, alphabetically.
+pub fn get_names(conn: &mut DbConnection) -> Vec<String> {
+    gh_organization::table
+        .select(gh_organization::name)
+        .order(gh_organization::name.asc())
+        .load::<String>(conn)
+        .expect("DB error in gh_organization::get_names")
+}
+
+pub fn count(conn: &mut DbConnection) -> i64 {
     gh_organization::table
         .count()
         .get_result(conn)
         .expect("DB error in gh_organization::count")
 }
 
-pub fn add(conn: &mut SqliteConnection, name: &str) -> QueryResult<Model> {
+pub fn add(conn: &mut DbConnection, name: &str) -> QueryResult<Model> {
     let inserted = diesel::insert_into(gh_organization::table)
         .values(NewGhOrganization { name })
         .on_conflict(gh_organization::name)
@@ -54,7 +64,7 @@ pub fn add(conn: &mut SqliteConnection, name: &str) -> QueryResult<Model> {
             .filter(gh_organization::name.eq(name))
             .first::<Model>(conn)
     } else {
-        let id = crate::models::last_insert_rowid(conn);
+        let id = crate::models::last_insert_id(conn);
         let _ = system_event::register_new_gh_organization(conn, name);
         Ok(Model {
             id,
@@ -2,19 +2,24 @@
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::dialect;
 use crate::schema::gh_repository;
 use crate::utils::date::get_sqlite_utc_now;
+use crate::DbConnection;
 
 use super::{gh_organization, system_event};
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = gh_repository, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = gh_repository, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub name: String,
     pub gh_organization_id: i64,
     pub create_date: String,
     pub update_date: String,
+    /// Dataset this repository was imported from (`oghcollector import`),
+    /// `None` when this instance collects it.
+    pub dataset_source_id: Option<i64>,
 }
 
 #[derive(Insertable)]
@@ -38,7 +43,7 @@ pub struct RepositoryInfo {
     pub version_odoo: i32,
 }
 
-pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
+pub fn get_by_id(conn: &mut DbConnection, id: &i64) -> Option<Model> {
     gh_repository::table
         .filter(gh_repository::id.eq(id))
         .first::<Model>(conn)
@@ -46,7 +51,14 @@ pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
         .expect("DB error in gh_repository::get_by_id")
 }
 
-pub fn get_by_name(conn: &mut SqliteConnection, gh_org_id: &i64, name: &str) -> Option<Model> {
+pub fn get_by_dataset_source_id(conn: &mut DbConnection, dataset_source_id: &i64) -> Vec<Model> {
+    gh_repository::table
+        .filter(gh_repository::dataset_source_id.eq(dataset_source_id))
+        .load::<Model>(conn)
+        .expect("DB error in gh_repository::get_by_dataset_source_id")
+}
+
+pub fn get_by_name(conn: &mut DbConnection, gh_org_id: &i64, name: &str) -> Option<Model> {
     gh_repository::table
         .filter(
             gh_repository::gh_organization_id
@@ -58,15 +70,15 @@ pub fn get_by_name(conn: &mut SqliteConnection, gh_org_id: &i64, name: &str) ->
         .expect("DB error in gh_repository::get_by_name")
 }
 
-pub fn get_info_by_name(conn: &mut SqliteConnection, repo_name: &str) -> Vec<RepositoryInfo> {
-    diesel::sql_query(
-        "SELECT gh_repo.name, gh_org.name as organization, count(mod.id) as num_modules, \
-         mod.version_odoo \
+pub fn get_info_by_name(conn: &mut DbConnection, repo_name: &str) -> Vec<RepositoryInfo> {
+    dialect::sql_query(
+        "SELECT gh_repo.name, gh_org.name as organization, \
+         CAST(count(mod.id) AS INTEGER) as num_modules, mod.version_odoo \
          FROM gh_repository as gh_repo \
          INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id \
          INNER JOIN module as mod ON mod.gh_repository_id = gh_repo.id \
          WHERE gh_repo.name = ? \
-         GROUP BY gh_org.id, mod.version_odoo",
+         GROUP BY gh_org.id, gh_repo.id, mod.version_odoo",
     )
     .bind::<diesel::sql_types::Text, _>(repo_name)
     .load::<RepositoryInfo>(conn)
@@ -77,23 +89,24 @@ pub fn get_info_by_name(conn: &mut SqliteConnection, repo_name: &str) -> Vec<Rep
 /// discovery (e.g. "spain" -> "l10n-spain") rather than an exact lookup.
 /// Grouped by repository id (not just org id) since several repositories
 /// across different organizations can match the same substring.
-pub fn search_by_name(conn: &mut SqliteConnection, name_substr: &str) -> Vec<RepositoryInfo> {
-    diesel::sql_query(
-        "SELECT gh_repo.name, gh_org.name as organization, count(mod.id) as num_modules, \
-         mod.version_odoo \
+pub fn search_by_name(conn: &mut DbConnection, name_substr: &str) -> Vec<RepositoryInfo> {
+    dialect::sql_query(format!(
+        "SELECT gh_repo.name, gh_org.name as organization, \
+         CAST(count(mod.id) AS INTEGER) as num_modules, mod.version_odoo \
          FROM gh_repository as gh_repo \
          INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id \
          INNER JOIN module as mod ON mod.gh_repository_id = gh_repo.id \
-         WHERE gh_repo.name LIKE ? \
-         GROUP BY gh_repo.id, mod.version_odoo \
+         WHERE gh_repo.name {ILIKE} ? \
+         GROUP BY gh_repo.id, gh_org.id, mod.version_odoo \
          ORDER BY gh_org.name, gh_repo.name",
-    )
+        ILIKE = dialect::ILIKE,
+    ))
     .bind::<diesel::sql_types::Text, _>(format!("%{name_substr}%"))
     .load::<RepositoryInfo>(conn)
     .expect("DB error in gh_repository::search_by_name")
 }
 
-pub fn add(conn: &mut SqliteConnection, gh_org_id: &i64, name: &str) -> QueryResult<Model> {
+pub fn add(conn: &mut DbConnection, gh_org_id: &i64, name: &str) -> QueryResult<Model> {
     let create_date = get_sqlite_utc_now();
     let inserted = diesel::insert_into(gh_repository::table)
         .values(NewGhRepository {
@@ -115,7 +128,7 @@ pub fn add(conn: &mut SqliteConnection, gh_org_id: &i64, name: &str) -> QueryRes
             )
             .first::<Model>(conn)
     } else {
-        let id = crate::models::last_insert_rowid(conn);
+        let id = crate::models::last_insert_id(conn);
         let org = gh_organization::get_by_id(conn, gh_org_id).unwrap();
         let _ = system_event::register_new_gh_repository(conn, &org.name, name);
         Ok(Model {
@@ -124,6 +137,19 @@ pub fn add(conn: &mut SqliteConnection, gh_org_id: &i64, name: &str) -> QueryRes
             gh_organization_id: *gh_org_id,
             create_date: create_date.clone(),
             update_date: create_date,
+            dataset_source_id: None,
         })
     }
 }
+
+/// Records where the repository's data comes from: a dataset source after an
+/// import, `None` once the collector takes it over.
+pub fn set_dataset_source(
+    conn: &mut DbConnection,
+    id: &i64,
+    dataset_source_id: Option<i64>,
+) -> QueryResult<usize> {
+    diesel::update(gh_repository::table.filter(gh_repository::id.eq(id)))
+        .set(gh_repository::dataset_source_id.eq(dataset_source_id))
+        .execute(conn)
+}
@@ -3,9 +3,10 @@ use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::schema::maintainer;
+use crate::DbConnection;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = maintainer, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = maintainer, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub name: String,
@@ -17,7 +18,7 @@ struct NewMaintainer<'a> {
     name: &'a str,
 }
 
-pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
+pub fn get_by_id(conn: &mut DbConnection, id: &i64) -> Option<Model> {
     maintainer::table
         .filter(maintainer::id.eq(id))
         .first::<Model>(conn)
@@ -25,7 +26,7 @@ pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
         .expect("DB error in maintainer::get_by_id")
 }
 
-pub fn get_by_name(conn: &mut SqliteConnection, name: &str) -> Option<Model> {
+pub fn get_by_name(conn: &mut DbConnection, name: &str) -> Option<Model> {
     maintainer::table
         .filter(maintainer::name.eq(name))
         .first::<Model>(conn)
@@ -33,7 +34,7 @@ pub fn get_by_name(conn: &mut SqliteConnection, name: &str) -> Option<Model> {
         .expect("DB error in maintainer::get_by_name")
 }
 
-pub fn add(conn: &mut SqliteConnection, name: &str) -> QueryResult<Model> {
+pub fn add(conn: &mut DbConnection, name: &str) -> QueryResult<Model> {
     let inserted = diesel::insert_into(maintainer::table)
         .values(NewMaintainer { name })
         .on_conflict(maintainer::name)
@@ -45,7 +46,7 @@ pub fn add(conn: &mut SqliteConnection, name: &str) -> QueryResult<Model> {
             .filter(maintainer::name.eq(name))
             .first::<Model>(conn)
     } else {
-        let id = crate::models::last_insert_rowid(conn);
+        let id = crate::models::last_insert_id(conn);
         Ok(Model {
             id,
             name: name.to_string(),
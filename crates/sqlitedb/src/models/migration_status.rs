@@ -0,0 +1,301 @@
+// Copyright Alexandre D. Díaz
+//! Port gaps between two consecutive Odoo versions: the modules a repository
+//! has for the previous version but its organization doesn't have (yet) for
+//! the next one, whether a migration PR is on its way, and how many modules
+//! wait on each of them.
+use std::collections::{BTreeMap, HashMap};
+
+use diesel::prelude::*;
+use oghutils::version::odoo_version_u8_to_string;
+use serde::{Deserialize, Serialize};
+
+use super::{dependency_type, gh_organization, module, module_lineage, pull_request};
+use crate::dialect;
+use crate::DbConnection;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortGapStatus {
+    /// At least one open migration PR targets the next version.
+    PullRequest,
+    NoPullRequest,
+    /// OpenUpgrade renamed it or merged it into another module.
+    Renamed,
+}
+
+/// Which gaps to list; the counts always cover all of them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortGapStatusFilter {
+    #[default]
+    All,
+    PullRequest,
+    NoPullRequest,
+    Renamed,
+}
+
+impl PortGapStatusFilter {
+    fn matches(&self, status: PortGapStatus) -> bool {
+        match self {
+            Self::All => true,
+            Self::PullRequest => status == PortGapStatus::PullRequest,
+            Self::NoPullRequest => status == PortGapStatus::NoPullRequest,
+            Self::Renamed => status == PortGapStatus::Renamed,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct MigrationStatusFilter {
+    /// The version ported to; the newest one collected when `None`.
+    pub version_odoo: Option<u8>,
+    pub org: Option<String>,
+    pub repo: Option<String>,
+    pub status: PortGapStatusFilter,
+}
+
+#[derive(QueryableByName, Debug)]
+struct PortCandidateInfo {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    technical_name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    version_module: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    org_name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    repo_name: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    gh_repository_id: i64,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    ported: i32,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    dependents: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PortGapPullRequest {
+    pub prid: i64,
+    pub repository: String,
+    pub title: String,
+    pub ci_status: Option<String>,
+    pub age_days: Option<i64>,
+    pub last_message_days: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PortGapModule {
+    pub technical_name: String,
+    pub name: String,
+    /// Its version in the previous Odoo version.
+    pub version_module: String,
+    pub status: PortGapStatus,
+    /// Modules of the previous version that depend on it, any organization.
+    pub dependents: i64,
+    /// What it became, for `renamed` gaps.
+    pub successor: Option<String>,
+    /// `renamed` or `merged`.
+    pub lineage_kind: Option<String>,
+    /// Oldest first.
+    pub pull_requests: Vec<PortGapPullRequest>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct RepositoryPortGap {
+    pub organization: String,
+    pub repository: String,
+    /// Installable modules in the previous version.
+    pub modules_from: usize,
+    /// Of those, the ones its organization has in the next version, in this
+    /// or another repository.
+    pub modules_ported: usize,
+    pub with_pull_request: usize,
+    pub without_pull_request: usize,
+    pub renamed: usize,
+    /// Most depended on first.
+    pub gaps: Vec<PortGapModule>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct MigrationStatus {
+    /// `None`, and nothing listed, until two versions are collected.
+    pub from_version: Option<String>,
+    pub to_version: Option<String>,
+    pub with_pull_request: usize,
+    pub without_pull_request: usize,
+    pub renamed: usize,
+    /// Repositories with every module ported.
+    pub repositories_complete: usize,
+    /// Only those with a gap matching the filter.
+    pub repositories: Vec<RepositoryPortGap>,
+    /// Versions that can be ported to (all but the oldest), newest first.
+    pub odoo_versions: Vec<String>,
+    pub organizations: Vec<String>,
+}
+
+fn get_port_candidates(
+    conn: &mut DbConnection,
+    from_version: &u8,
+    to_version: &u8,
+    org_name: Option<&str>,
+    repo_name: Option<&str>,
+) -> Vec<PortCandidateInfo> {
+    // Seeded by the migrations, see test_dependency_type_seeded.
+    let module_dep_type_id = dependency_type::get_by_name(conn, "module").unwrap().id;
+    dialect::sql_query(
+        "SELECT prev.technical_name, prev.name, prev.version_module, \
+         gh_org.name as org_name, gh_repo.name as repo_name, gh_repo.id as gh_repository_id, \
+         CASE WHEN EXISTS (SELECT 1 FROM module as cur \
+           INNER JOIN gh_repository as cur_repo ON cur_repo.id = cur.gh_repository_id \
+           WHERE cur.technical_name = prev.technical_name AND cur.version_odoo = ?2 \
+           AND cur_repo.gh_organization_id = gh_repo.gh_organization_id) \
+         THEN 1 ELSE 0 END as ported, \
+         (SELECT COUNT(DISTINCT dep_mod.technical_name) FROM dependency_module as dm \
+           INNER JOIN dependency as dep ON dep.id = dm.dependency_id \
+           INNER JOIN module as dep_mod ON dep_mod.id = dm.module_id \
+           WHERE dep.dependency_type_id = ?3 AND dep.name = prev.technical_name \
+           AND dep_mod.version_odoo = prev.version_odoo) as dependents \
+         FROM module as prev \
+         INNER JOIN gh_repository as gh_repo ON gh_repo.id = prev.gh_repository_id \
+         INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id \
+         WHERE prev.version_odoo = ?1 AND prev.installable \
+           AND (?4 IS NULL OR gh_org.name = ?4) \
+           AND (?5 IS NULL OR gh_repo.name = ?5) \
+         ORDER BY gh_org.name, gh_repo.name, dependents DESC, prev.technical_name",
+    )
+    .bind::<diesel::sql_types::Integer, _>(*from_version as i32)
+    .bind::<diesel::sql_types::Integer, _>(*to_version as i32)
+    .bind::<diesel::sql_types::BigInt, _>(module_dep_type_id)
+    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(org_name)
+    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(repo_name)
+    .load::<PortCandidateInfo>(conn)
+    .expect("DB error in migration_status::get_port_candidates")
+}
+
+pub fn build(conn: &mut DbConnection, filter: &MigrationStatusFilter) -> MigrationStatus {
+    let versions = module::get_odoo_versions(conn);
+    let mut status = MigrationStatus {
+        organizations: gh_organization::get_names(conn),
+        odoo_versions: versions
+            .iter()
+            .take(versions.len().saturating_sub(1))
+            .map(|version| odoo_version_u8_to_string(&(*version as u8)))
+            .collect(),
+        ..Default::default()
+    };
+    let Some(to_version) = filter
+        .version_odoo
+        .map(|version| version as i32)
+        .or(versions.first().copied())
+    else {
+        return status;
+    };
+    let Some(from_version) = versions.iter().copied().find(|v| *v < to_version) else {
+        return status;
+    };
+    let (from_u8, to_u8) = (from_version as u8, to_version as u8);
+    status.from_version = Some(odoo_version_u8_to_string(&from_u8));
+    status.to_version = Some(odoo_version_u8_to_string(&to_u8));
+
+    let candidates = get_port_candidates(
+        conn,
+        &from_u8,
+        &to_u8,
+        filter.org.as_deref(),
+        filter.repo.as_deref(),
+    );
+    // Keyed by repository too: the same technical name in another repository
+    // is another module. Apriori links are name-only (no repository).
+    let successions: HashMap<(Option<i64>, String), module_lineage::Model> =
+        module_lineage::get_successions_between(conn, &from_u8, &to_u8)
+            .into_iter()
+            .map(|lineage| {
+                (
+                    (
+                        lineage.predecessor_gh_repository_id,
+                        lineage.predecessor_technical_name.clone(),
+                    ),
+                    lineage,
+                )
+            })
+            .collect();
+    let mut pull_requests: HashMap<(String, String), Vec<PortGapPullRequest>> = HashMap::new();
+    for pr in pull_request::get_all(conn)
+        .into_iter()
+        .filter(|pr| pr.version_odoo == to_version)
+    {
+        pull_requests
+            .entry((pr.org_name, pr.module_technical_name))
+            .or_default()
+            .push(PortGapPullRequest {
+                prid: pr.prid,
+                repository: pr.repository_name,
+                title: pr.name,
+                ci_status: pr.ci_status,
+                age_days: pull_request::days_since(pr.created_at.as_deref()),
+                last_message_days: pull_request::days_since(pr.last_message_at.as_deref()),
+            });
+    }
+
+    let mut repositories: BTreeMap<(String, String), RepositoryPortGap> = BTreeMap::new();
+    for candidate in candidates {
+        let repo = repositories
+            .entry((candidate.org_name.clone(), candidate.repo_name.clone()))
+            .or_insert_with(|| RepositoryPortGap {
+                organization: candidate.org_name.clone(),
+                repository: candidate.repo_name.clone(),
+                ..Default::default()
+            });
+        repo.modules_from += 1;
+        if candidate.ported != 0 {
+            repo.modules_ported += 1;
+            continue;
+        }
+        let lineage = successions
+            .get(&(
+                Some(candidate.gh_repository_id),
+                candidate.technical_name.clone(),
+            ))
+            .or_else(|| successions.get(&(None, candidate.technical_name.clone())));
+        let mut prs = pull_requests
+            .remove(&(candidate.org_name, candidate.technical_name.clone()))
+            .unwrap_or_default();
+        prs.sort_by_key(|pr| pr.prid);
+        let gap_status = if lineage.is_some() {
+            repo.renamed += 1;
+            PortGapStatus::Renamed
+        } else if !prs.is_empty() {
+            repo.with_pull_request += 1;
+            PortGapStatus::PullRequest
+        } else {
+            repo.without_pull_request += 1;
+            PortGapStatus::NoPullRequest
+        };
+        if filter.status.matches(gap_status) {
+            repo.gaps.push(PortGapModule {
+                technical_name: candidate.technical_name,
+                name: candidate.name,
+                version_module: candidate.version_module,
+                status: gap_status,
+                dependents: candidate.dependents,
+                successor: lineage.map(|l| l.successor_technical_name.clone()),
+                lineage_kind: lineage.map(|l| l.kind.clone()),
+                pull_requests: prs,
+            });
+        }
+    }
+
+    for repo in repositories.into_values() {
+        status.with_pull_request += repo.with_pull_request;
+        status.without_pull_request += repo.without_pull_request;
+        status.renamed += repo.renamed;
+        if repo.modules_ported == repo.modules_from {
+            status.repositories_complete += 1;
+        }
+        if !repo.gaps.is_empty() {
+            status.repositories.push(repo);
+        }
+    }
+    status
+}
@@ -1,6 +1,15 @@
 // Copyright Alexandre D. Díaz
+pub mod atlas_export;
+pub mod atlas_graph;
 pub mod author;
+pub mod bot_account;
+pub mod collection_job;
+pub mod collection_run;
+pub mod collection_run_repo;
+pub mod collection_run_security;
 pub mod committer;
+pub mod committer_email;
+pub mod dataset_source;
 pub mod dependency;
 pub mod dependency_module;
 pub mod dependency_osv;
@@ -8,35 +17,44 @@ pub mod dependency_type;
 pub mod gh_organization;
 pub mod gh_repository;
 pub mod maintainer;
+pub mod migration_status;
+pub mod model_explorer;
 pub mod module;
 pub mod module_author;
 pub mod module_code_analysis;
 pub mod module_committer;
 pub mod module_committer_period;
 pub mod module_controller;
+pub mod module_erd;
+pub mod module_health;
+pub mod module_lineage;
 pub mod module_maintainer;
 pub mod module_model;
 pub mod module_model_field;
 pub mod module_model_method;
+pub mod module_ownership;
 pub mod module_record;
+pub mod module_search;
 pub mod module_security_warning;
+pub mod module_upgrade_coverage;
 pub mod module_version;
 pub mod module_view;
+pub mod open_pull_request;
 pub mod pull_request;
 pub mod pull_request_history;
+pub mod pull_request_review;
+pub mod repository_overview;
+pub mod route_catalog;
+pub mod security_overview;
 pub mod system_event;
 pub mod system_event_type;
 
 use diesel::r2d2::{ConnectionManager, PooledConnection};
-use diesel::sqlite::SqliteConnection;
 
-pub type Connection = PooledConnection<ConnectionManager<SqliteConnection>>;
+use crate::dialect;
+use crate::DbConnection;
 
-/// Bots/automation accounts excluded from committer rankings so they reflect
-/// human contributors. Single source of truth for `committer`, `module` and
-/// `module_committer_period`'s ranking queries.
-pub const BOT_COMMITTERS: &str = "'Odoo Translation Bot', 'OCA-git-bot', 'Weblate', 'oca-ci', \
-     'OCA Transbot', 'Launchpad Translations on behalf of openerp', 'oca-travis', 'GitHub'";
+pub type Connection = PooledConnection<ConnectionManager<DbConnection>>;
 
 // Shared helper types for sql_query results.
 #[derive(diesel::QueryableByName)]
@@ -51,36 +69,47 @@ struct LastIdRow {
     id: i64,
 }
 
-/// Returns the rowid of the most recently inserted row on this connection.
-pub(crate) fn last_insert_rowid(conn: &mut diesel::sqlite::SqliteConnection) -> i64 {
+#[cfg(not(feature = "postgres"))]
+const LAST_INSERT_ID: &str = "SELECT last_insert_rowid() as id";
+#[cfg(feature = "postgres")]
+const LAST_INSERT_ID: &str = "SELECT lastval() as id";
+
+/// Returns the id of the most recently inserted row on this connection.
+pub(crate) fn last_insert_id(conn: &mut DbConnection) -> i64 {
     use diesel::RunQueryDsl;
-    diesel::sql_query("SELECT last_insert_rowid() as id")
+    dialect::sql_query(LAST_INSERT_ID)
         .get_result::<LastIdRow>(conn)
-        .expect("Failed to get last_insert_rowid")
+        .expect("Failed to get last_insert_id")
         .id
 }
 
+/// A `LIKE` pattern matching values that contain `term` literally; use it
+/// with `ESCAPE '\'`.
+pub(crate) fn like_contains(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
 #[cfg(test)]
 mod tests {
-    use diesel::sqlite::SqliteConnection;
-    use diesel::Connection;
-    use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
-
-    const MIGRATIONS: EmbeddedMigrations = embed_migrations!("../../migrations");
+    use crate::DbConnection;
 
-    fn setup_db() -> SqliteConnection {
-        let mut conn = SqliteConnection::establish(":memory:").unwrap();
-        conn.run_pending_migrations(MIGRATIONS).unwrap();
-        conn
+    fn setup_db() -> DbConnection {
+        crate::establish_test_connection()
     }
 
     // Simulates a pre-existing database created by the old rusqlite-based
     // prepare_schema() + populate_basics(), which had no __diesel_migrations table.
     // Diesel migrations must apply cleanly on top of it.
+    #[cfg(not(feature = "postgres"))]
     #[test]
     fn test_migration_on_existing_db() {
         use diesel::connection::SimpleConnection;
-        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        use diesel::Connection;
+        let mut conn = diesel::sqlite::SqliteConnection::establish(":memory:").unwrap();
 
         // Reproduce exactly what the old prepare_schema() created (all CREATE TABLE IF NOT
         // EXISTS statements, plus the old seed data from populate_basics).
@@ -152,8 +181,7 @@ mod tests {
         .expect("Failed to create old schema");
 
         // Now run Diesel migrations on top — must not fail.
-        conn.run_pending_migrations(MIGRATIONS)
-            .expect("Migrations failed on existing DB");
+        crate::run_migrations(&mut conn).expect("Migrations failed on existing DB");
 
         // Verify the DB is functional after migration.
         let org = super::gh_organization::add(&mut conn, "TestOrg").unwrap();
@@ -183,7 +211,7 @@ mod tests {
     #[test]
     fn test_committer_add_and_get() {
         let mut conn = setup_db();
-        let com = super::committer::add(&mut conn, "Charlie").unwrap();
+        let com = super::committer::add(&mut conn, "Charlie", &[]).unwrap();
         assert_eq!(com.name, "Charlie");
         let found = super::committer::get_by_name(&mut conn, "Charlie");
         assert!(found.is_some());
@@ -231,6 +259,7 @@ mod tests {
             version_odoo: 16,
             name: "Test Module".to_string(),
             version_module: "16.0.1.0.0".to_string(),
+            summary: String::new(),
             description: "A test module".to_string(),
             installation: "pip install foo".to_string(),
             usage: "Go to Settings > Foo".to_string(),
@@ -290,6 +319,7 @@ mod tests {
             version_odoo: 17,
             name: "Dup Module".to_string(),
             version_module: "17.0.1.0.0".to_string(),
+            summary: String::new(),
             description: String::new(),
             installation: String::new(),
             usage: String::new(),
@@ -333,6 +363,7 @@ mod tests {
             version_odoo: 16,
             name: "Dep Test".to_string(),
             version_module: "16.0.1.0.0".to_string(),
+            summary: String::new(),
             description: String::new(),
             installation: String::new(),
             usage: String::new(),
@@ -465,6 +496,7 @@ mod tests {
             version_odoo: ver,
             name: name.to_string(),
             version_module: format!("{ver}.0.1.0.0"),
+            summary: String::new(),
             description: String::new(),
             installation: String::new(),
             usage: String::new(),
@@ -511,6 +543,7 @@ mod tests {
             version_odoo: 16,
             name: name.to_string(),
             version_module: "16.0.1.0.0".to_string(),
+            summary: String::new(),
             description: String::new(),
             installation: String::new(),
             usage: String::new(),
@@ -570,6 +603,7 @@ mod tests {
             version_odoo: 16,
             name: name.to_string(),
             version_module: "16.0.1.0.0".to_string(),
+            summary: String::new(),
             description: String::new(),
             installation: String::new(),
             usage: String::new(),
@@ -678,6 +712,8 @@ mod tests {
 
     #[test]
     fn test_pull_request_delete_outdated() {
+        use std::collections::HashMap;
+
         let mut conn = setup_db();
         let org = super::gh_organization::add(&mut conn, "PrOrg2").unwrap();
         let repo = super::gh_repository::add(&mut conn, &org.id, "pr-repo-2").unwrap();
@@ -690,9 +726,9 @@ mod tests {
             &mut conn, "mig 2", "mod_2", &2, &16u8, &repo.id, None, None, None,
         )
         .unwrap();
-        // Both have a known created_at, unlike pr1/pr2 - but only #3 (merged)
-        // should leave a pull_request_history trace once closed; #4 (closed
-        // without merge) must not, even though it has a created_at too.
+        // Both have a known created_at, unlike pr1/pr2 - #3 gets merged and #4
+        // closed without merge; both leave a pull_request_history trace, but
+        // only #3 must count toward the "avg. time open" stat.
         let _pr3 = super::pull_request::add(
             &mut conn,
             "mig 3",
@@ -719,24 +755,201 @@ mod tests {
         .unwrap();
 
         // Keep only #1, #2 must be removed since it's not in the "still open" list.
-        super::pull_request::delete_outdated(&mut conn, &repo.id, &16u8, &[1, 3, 4], &[]).unwrap();
+        super::pull_request::delete_outdated(
+            &mut conn,
+            &repo.id,
+            &16u8,
+            &[1, 3, 4],
+            &HashMap::new(),
+        )
+        .unwrap();
         assert!(super::pull_request::get_by_id(&mut conn, &pr1.id).is_some());
         assert!(super::pull_request::get_by_id(&mut conn, &_pr2.id).is_none());
 
         // Unlike module::delete_outdated, an empty list must clear everything left
         // (all migration PRs for this repo/version got merged or closed). #3 is
-        // reported merged, #1/#4 are not (closed-without-merge / no created_at).
-        super::pull_request::delete_outdated(&mut conn, &repo.id, &16u8, &[], &[3]).unwrap();
+        // reported merged, #4 closed, #1 has no created_at anywhere.
+        let closures = HashMap::from([
+            (
+                3,
+                super::pull_request_history::PullRequestClosureInfo {
+                    close_reason: super::pull_request_history::CLOSE_REASON_MERGED.to_string(),
+                    closed_at: Some("2024-01-03 00:00:00".to_string()),
+                    ..Default::default()
+                },
+            ),
+            (
+                4,
+                super::pull_request_history::PullRequestClosureInfo {
+                    close_reason: super::pull_request_history::CLOSE_REASON_CLOSED.to_string(),
+                    closed_at: Some("2024-01-02 00:00:00".to_string()),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        super::pull_request::delete_outdated(&mut conn, &repo.id, &16u8, &[], &closures).unwrap();
         assert!(super::pull_request::get_by_id(&mut conn, &pr1.id).is_none());
 
-        // Only the merged PR with a known created_at leaves a history row - this
-        // is what powers the "avg. time open" stat on the modules page, and the
-        // whole point of gating on merged_prids: rejections must not skew it,
-        // even when they have a created_at (#4) same as the merged one (#3).
+        // Rejections must not skew the "avg. time open" stat on the modules
+        // page, even when they have a created_at (#4) same as the merged one.
         let stats = super::pull_request_history::average_days_open_by_version(&mut conn);
         assert_eq!(stats.len(), 1);
         assert_eq!(stats[0].version_odoo, 16);
         assert_eq!(stats[0].closed_count, 1);
+        assert_eq!(stats[0].avg_days, 2.0);
+
+        // ...but they are still recorded with their reason for latency stats.
+        let latency = super::pull_request_history::latency_stats(
+            &mut conn,
+            super::pull_request_history::LatencyGroup::Version,
+        );
+        assert_eq!(latency.len(), 1);
+        assert_eq!(latency[0].closed_count, 2);
+        assert_eq!(latency[0].merged_count, 1);
+    }
+
+    #[test]
+    fn test_open_pull_request_replace_for_repository() {
+        use super::open_pull_request::OpenPullRequestInfo;
+
+        let mut conn = setup_db();
+        let org = super::gh_organization::add(&mut conn, "OprOrg").unwrap();
+        let repo = super::gh_repository::add(&mut conn, &org.id, "opr-repo").unwrap();
+
+        let fix_pr = OpenPullRequestInfo {
+            number: 10,
+            title: "[16.0][FIX] sale_commission: rounding".to_string(),
+            author: Some("jdoe".to_string()),
+            head_ref: "16.0-fix-sale_commission".to_string(),
+            is_draft: true,
+            labels: vec!["needs review".to_string()],
+            review_count: 2,
+            approval_count: 1,
+            ci_status: Some("failure".to_string()),
+            module_technical_names: vec![
+                "sale_commission".to_string(),
+                "sale_commission_product".to_string(),
+            ],
+            ..Default::default()
+        };
+        let other_pr = OpenPullRequestInfo {
+            number: 11,
+            title: "[16.0][IMP] other_module".to_string(),
+            module_technical_names: vec!["other_module".to_string()],
+            ..Default::default()
+        };
+        super::open_pull_request::replace_for_repository(
+            &mut conn,
+            &repo.id,
+            &16u8,
+            &[fix_pr.clone(), other_pr],
+        )
+        .unwrap();
+
+        // A PR touching several module folders shows up on each of them.
+        let found = super::open_pull_request::get_by_technical_name_organization_name(
+            &mut conn,
+            "sale_commission_product",
+            "OprOrg",
+            None,
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].prid, 10);
+        assert!(found[0].is_draft);
+        assert_eq!(found[0].labels_vec(), vec!["needs review".to_string()]);
+        assert_eq!(found[0].approval_count, 1);
+        assert_eq!(found[0].repository_name, "opr-repo");
+        assert_eq!(
+            super::open_pull_request::get_module_names(&mut conn, &found[0].id),
+            vec![
+                "sale_commission".to_string(),
+                "sale_commission_product".to_string()
+            ]
+        );
+        assert!(
+            super::open_pull_request::get_by_technical_name_organization_name(
+                &mut conn,
+                "sale_commission",
+                "OprOrg",
+                Some(&17u8),
+            )
+            .is_empty()
+        );
+
+        // Next run: #11 got merged/closed, only #10 is still open - the
+        // snapshot is replaced, with no leftover module links for #11.
+        super::open_pull_request::replace_for_repository(&mut conn, &repo.id, &16u8, &[fix_pr])
+            .unwrap();
+        assert!(
+            super::open_pull_request::get_by_technical_name_organization_name(
+                &mut conn,
+                "other_module",
+                "OprOrg",
+                None,
+            )
+            .is_empty()
+        );
+        assert_eq!(
+            super::open_pull_request::get_by_technical_name_organization_name(
+                &mut conn,
+                "sale_commission",
+                "OprOrg",
+                Some(&16u8),
+            )
+            .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_open_pull_request_retargeted_to_another_version() {
+        use super::open_pull_request::OpenPullRequestInfo;
+
+        let mut conn = setup_db();
+        let org = super::gh_organization::add(&mut conn, "OprOrg").unwrap();
+        let repo = super::gh_repository::add(&mut conn, &org.id, "opr-repo").unwrap();
+        let pr = OpenPullRequestInfo {
+            number: 20,
+            title: "[IMP] sale_commission: new rule".to_string(),
+            module_technical_names: vec!["sale_commission".to_string()],
+            ..Default::default()
+        };
+        super::open_pull_request::replace_for_repository(
+            &mut conn,
+            &repo.id,
+            &16u8,
+            std::slice::from_ref(&pr),
+        )
+        .unwrap();
+
+        // Its base branch moved from 16.0 to 17.0: the 17.0 scan runs first
+        // and takes it over instead of clashing with the 16.0 row.
+        super::open_pull_request::replace_for_repository(&mut conn, &repo.id, &17u8, &[pr])
+            .unwrap();
+        let found = super::open_pull_request::get_by_technical_name_organization_name(
+            &mut conn,
+            "sale_commission",
+            "OprOrg",
+            None,
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].version_odoo, 17);
+        assert_eq!(
+            super::open_pull_request::get_module_names(&mut conn, &found[0].id),
+            vec!["sale_commission".to_string()]
+        );
+
+        super::open_pull_request::replace_for_repository(&mut conn, &repo.id, &16u8, &[]).unwrap();
+        assert_eq!(
+            super::open_pull_request::get_by_technical_name_organization_name(
+                &mut conn,
+                "sale_commission",
+                "OprOrg",
+                Some(&17u8),
+            )
+            .len(),
+            1
+        );
     }
 
     #[test]
@@ -760,6 +973,7 @@ mod tests {
                 version_odoo: 16,
                 name: "Period Test".to_string(),
                 version_module: "16.0.1.0.0".to_string(),
+                summary: String::new(),
                 description: String::new(),
                 installation: String::new(),
                 usage: String::new(),
@@ -820,12 +1034,267 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_committer_identities_merged_by_email() {
+        let mut conn = setup_db();
+        use super::module::CommitterActivity;
+        use std::collections::HashMap;
+
+        let activity = |total: u32, email: &str| {
+            let mut periods = HashMap::new();
+            periods.insert((2024, 1), total);
+            CommitterActivity {
+                total,
+                periods,
+                emails: vec![email.to_string()],
+                ..Default::default()
+            }
+        };
+
+        // Same email under another spelling: one committer, renamed to the
+        // latest (mailmap-resolved) name.
+        let mut info = make_bare_module_info("identity_a");
+        info.committers
+            .insert("Pedro M. Baeza".to_string(), activity(2, "Pedro@Test.com"));
+        super::module::add(&mut conn, &info).unwrap();
+        let mut info = make_bare_module_info("identity_b");
+        info.committers
+            .insert("Pedro Baeza".to_string(), activity(3, "pedro@test.com"));
+        super::module::add(&mut conn, &info).unwrap();
+        assert!(super::committer::get_by_name(&mut conn, "Pedro M. Baeza").is_none());
+        let pedro = super::committer::get_by_name(&mut conn, "Pedro Baeza").unwrap();
+        let emails = super::committer_email::get_by_committer_id(&mut conn, &pedro.id);
+        assert_eq!(emails.len(), 1);
+        assert_eq!(emails[0].email, "pedro@test.com");
+        let list = super::committer::list(&mut conn);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].total_commits, 5);
+        assert_eq!(list[0].modules_touched, 2);
+
+        // Both spellings already stored as separate committers on the same
+        // module: the email's committer is merged into the named one and
+        // their activity summed.
+        let mut info = make_bare_module_info("identity_c");
+        info.committers
+            .insert("Ana".to_string(), activity(1, "ana@old.com"));
+        info.committers
+            .insert("Ana Lopez".to_string(), activity(4, "ana@test.com"));
+        super::module::add(&mut conn, &info).unwrap();
+        let ana_lopez = super::committer::get_by_name(&mut conn, "Ana Lopez").unwrap();
+        let ana = super::committer::add(&mut conn, "Ana", &["ana@test.com".to_string()]).unwrap();
+        assert!(super::committer::get_by_id(&mut conn, &ana_lopez.id).is_none());
+        let activity = super::module_committer::get_activity_by_committer_name(&mut conn, "Ana");
+        assert_eq!(activity.len(), 1);
+        assert_eq!(activity[0].commits, 5);
+        let periods =
+            super::module_committer_period::get_activity_by_committer_name(&mut conn, "Ana");
+        assert_eq!(periods.len(), 1);
+        assert_eq!(periods[0].commits, 5);
+        assert_eq!(
+            super::committer_email::get_by_committer_id(&mut conn, &ana.id).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_collection_job_queue_dedupes_pending() {
+        let mut conn = setup_db();
+        use super::collection_job::{self, JobRequestInfo};
+
+        let job = JobRequestInfo {
+            kind: collection_job::KIND_COLLECT.to_string(),
+            source: "OCA/web".to_string(),
+            branch: "16.0".to_string(),
+            git_type: "GH".to_string(),
+            repo_url: Some("https://github.com/OCA/web.git".to_string()),
+        };
+        assert_eq!(collection_job::enqueue(&mut conn, &job).unwrap(), 1);
+        // A second push before the drain collapses into the pending job.
+        assert_eq!(collection_job::enqueue(&mut conn, &job).unwrap(), 0);
+        let pr_job = JobRequestInfo {
+            kind: collection_job::KIND_PULL_REQUESTS.to_string(),
+            ..job.clone()
+        };
+        assert_eq!(collection_job::enqueue(&mut conn, &pr_job).unwrap(), 1);
+
+        let pending = collection_job::get_pending(&mut conn);
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].kind, collection_job::KIND_COLLECT);
+        let id = pending[0].id;
+        assert!(collection_job::claim(&mut conn, &id).unwrap());
+        assert!(!collection_job::claim(&mut conn, &id).unwrap());
+
+        // Once running, a new push may queue the next run.
+        assert_eq!(collection_job::enqueue(&mut conn, &job).unwrap(), 1);
+        collection_job::finish(&mut conn, &id, Some("clone failed")).unwrap();
+        let failed = collection_job::get_by_id(&mut conn, &id).unwrap();
+        assert_eq!(failed.status, collection_job::STATUS_FAILED);
+        assert_eq!(failed.error.as_deref(), Some("clone failed"));
+        assert!(failed.end_date.is_some());
+        assert_eq!(collection_job::get_pending(&mut conn).len(), 2);
+    }
+
+    #[test]
+    fn test_collection_run_history() {
+        let mut conn = setup_db();
+        use super::collection_run::{self, RunTotalsInfo};
+        use super::collection_run_repo::{self, RunRepoInfo};
+
+        let killed = collection_run::start(
+            &mut conn,
+            "OCA",
+            "OCA",
+            &16,
+            "github",
+            collection_run::TRIGGER_SCHEDULE,
+        )
+        .unwrap();
+        let run = collection_run::start(
+            &mut conn,
+            "OCA/web",
+            "OCA",
+            &17,
+            "github",
+            collection_run::TRIGGER_MANUAL,
+        )
+        .unwrap();
+        // The run left behind by a killed collector is closed on the next one.
+        let killed = collection_run::get_by_id(&mut conn, &killed.id).unwrap();
+        assert_eq!(killed.status, collection_run::STATUS_FAILED);
+        assert!(killed.error.is_some());
+        assert_eq!(run.status, collection_run::STATUS_RUNNING);
+
+        let repo = |name: &str, clone_status: &str, error: Option<&str>| RunRepoInfo {
+            org_name: "OCA".to_string(),
+            repo_name: name.to_string(),
+            clone_status: clone_status.to_string(),
+            analysis_status: collection_run_repo::ANALYSIS_OK.to_string(),
+            modules_added: 1,
+            modules_updated: 0,
+            modules_removed: 0,
+            error: error.map(str::to_string),
+        };
+        let repos = [
+            repo(
+                "account-financial-tools",
+                collection_run_repo::CLONE_OK,
+                None,
+            ),
+            repo(
+                "web",
+                collection_run_repo::CLONE_FAILED,
+                Some("fatal: timeout"),
+            ),
+        ];
+        for info in &repos {
+            collection_run_repo::add(&mut conn, &run.id, info).unwrap();
+        }
+        assert!(repos[1].is_failed() && !repos[0].is_failed());
+        collection_run::finish(
+            &mut conn,
+            &run.id,
+            &42,
+            &RunTotalsInfo {
+                repos_count: 2,
+                repos_failed: 1,
+                modules_added: 1,
+                modules_scanned: 12,
+                api_calls: 7,
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
+
+        let latest = collection_run::get_latest(&mut conn, 10);
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].id, run.id);
+        assert_eq!(latest[0].status, collection_run::STATUS_DONE);
+        assert_eq!(latest[0].duration, Some(42));
+        assert_eq!(latest[0].api_calls, 7);
+        let run_repos = collection_run_repo::get_by_run_id(&mut conn, &run.id);
+        assert_eq!(run_repos[0].repo_name, "web");
+        assert_eq!(run_repos[1].repo_name, "account-financial-tools");
+
+        // A run in progress doesn't hide the last finished one of its version.
+        collection_run::start(
+            &mut conn,
+            "OCA",
+            "OCA",
+            &17,
+            "github",
+            collection_run::TRIGGER_SCHEDULE,
+        )
+        .unwrap();
+        let last = collection_run::get_last_finished(&mut conn);
+        assert_eq!(
+            last.iter()
+                .map(|r| (r.version_odoo, r.id))
+                .collect::<Vec<_>>(),
+            vec![(16, killed.id), (17, run.id)]
+        );
+        assert_eq!(last[1].modules_scanned, 12);
+    }
+
+    #[test]
+    fn test_committer_bot_flag_honoured_by_rankings() {
+        let mut conn = setup_db();
+        use super::module::CommitterActivity;
+        use std::collections::HashMap;
+
+        let mut info = make_bare_module_info("bot_rank_test");
+        for (name, total) in [("renovate[bot]", 40), ("Eve", 3), ("Frank", 2)] {
+            let mut periods = HashMap::new();
+            periods.insert((2024, 5), total);
+            info.committers.insert(
+                name.to_string(),
+                CommitterActivity {
+                    total,
+                    periods,
+                    ..Default::default()
+                },
+            );
+        }
+        super::module::add(&mut conn, &info).unwrap();
+        assert_eq!(super::committer::rank_global(&mut conn, 10).len(), 3);
+
+        let bot = super::committer::get_by_name(&mut conn, "renovate[bot]").unwrap();
+        assert_eq!(super::committer::set_bots(&mut conn, &[bot.id]).unwrap(), 1);
+        assert!(
+            super::committer::get_by_id(&mut conn, &bot.id)
+                .unwrap()
+                .is_bot
+        );
+        let global = super::committer::rank_global(&mut conn, 10);
+        assert_eq!(global.len(), 2);
+        assert_eq!(global[0].name, "Eve");
+        assert!(super::committer::get_global_rank_by_name(&mut conn, "renovate[bot]").is_none());
+        let period = super::module_committer_period::rank_by_period(&mut conn, 2024, Some(5), 10);
+        assert_eq!(period.len(), 2);
+        assert!(super::module::rank_committer(&mut conn)
+            .iter()
+            .all(|r| r.committer_name != "renovate[bot]"));
+
+        // Re-syncing with no bots clears the flag again.
+        assert_eq!(super::committer::set_bots(&mut conn, &[]).unwrap(), 1);
+        assert_eq!(super::committer::rank_global(&mut conn, 10).len(), 3);
+
+        assert_eq!(super::bot_account::add(&mut conn, "Deployer").unwrap(), 1);
+        assert_eq!(super::bot_account::add(&mut conn, "Deployer").unwrap(), 0);
+        assert_eq!(super::bot_account::get_names(&mut conn), vec!["Deployer"]);
+        assert_eq!(
+            super::bot_account::remove(&mut conn, "Deployer").unwrap(),
+            1
+        );
+    }
+
     fn make_bare_module_info(name: &str) -> super::module::ManifestInfo {
         super::module::ManifestInfo {
             technical_name: name.to_string(),
             version_odoo: 16,
             name: name.to_string(),
             version_module: "16.0.1.0.0".to_string(),
+            summary: String::new(),
             description: String::new(),
             installation: String::new(),
             usage: String::new(),
@@ -855,6 +1324,134 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_module_lineage_moves_and_renames() {
+        let mut conn = setup_db();
+        let with_repo = |name: &str, repo: &str, version_odoo: u8| super::module::ManifestInfo {
+            git_repo: repo.to_string(),
+            version_odoo,
+            ..make_bare_module_info(name)
+        };
+
+        // Moved between versions: repo-a at 15.0, repo-b at 16.0.
+        super::module::add(&mut conn, &with_repo("mod_moved", "repo-a", 150)).unwrap();
+        let moved_v16 =
+            super::module::add(&mut conn, &with_repo("mod_moved", "repo-b", 160)).unwrap();
+        // Moved mid-version: both repos carry it at 16.0 until repo-a's
+        // row goes stale.
+        let stale = super::module::add(&mut conn, &with_repo("mod_mid", "repo-a", 160)).unwrap();
+        let mid_new = super::module::add(&mut conn, &with_repo("mod_mid", "repo-b", 160)).unwrap();
+        // Same name in another organization's repo is a fork, not a move.
+        super::module::add(
+            &mut conn,
+            &super::module::ManifestInfo {
+                git_org: "ForkOrg".to_string(),
+                ..with_repo("mod_moved", "fork-repo", 140)
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            super::module_lineage::detect_moves(&mut conn, &160u8).unwrap(),
+            1
+        );
+        // Re-detecting is a no-op.
+        assert_eq!(
+            super::module_lineage::detect_moves(&mut conn, &160u8).unwrap(),
+            0
+        );
+        let kept = super::module::add(&mut conn, &with_repo("mod_kept", "repo-a", 160)).unwrap();
+        super::module::delete_outdated(&mut conn, &stale.gh_repository_id, &160u8, &[kept.id])
+            .unwrap();
+        assert!(super::module::get_by_id(&mut conn, &stale.id).is_none());
+
+        let moved = super::module_lineage::get_latest_move(&mut conn, "mod_moved", &160u8).unwrap();
+        assert_eq!(
+            moved.successor_gh_repository_id,
+            Some(moved_v16.gh_repository_id)
+        );
+        assert!(super::module_lineage::get_latest_move(&mut conn, "mod_moved", &150u8).is_none());
+        let mid = super::module_lineage::get_latest_move(&mut conn, "mod_mid", &160u8).unwrap();
+        assert_eq!(
+            mid.predecessor_gh_repository_id,
+            Some(stale.gh_repository_id)
+        );
+        assert_eq!(
+            mid.successor_gh_repository_id,
+            Some(mid_new.gh_repository_id)
+        );
+
+        // apriori chain: a -> b at 15.0, b -> c at 17.0.
+        let to_pairs = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(a, b)| (a.to_string(), b.to_string()))
+                .collect()
+        };
+        super::module_lineage::import_apriori(&mut conn, &150u8, &to_pairs(&[("a", "b")]), &[])
+            .unwrap();
+        super::module_lineage::import_apriori(&mut conn, &170u8, &[], &to_pairs(&[("b", "c")]))
+            .unwrap();
+        assert!(super::module_lineage::resolve_successor(&mut conn, "a", &140u8).is_none());
+        let at_16 = super::module_lineage::resolve_successor(&mut conn, "a", &160u8).unwrap();
+        assert_eq!(at_16.successor_technical_name, "b");
+        assert_eq!(at_16.kind, super::module_lineage::KIND_RENAMED);
+        let at_17 = super::module_lineage::resolve_successor(&mut conn, "a", &170u8).unwrap();
+        assert_eq!(at_17.successor_technical_name, "c");
+        assert_eq!(at_17.kind, super::module_lineage::KIND_MERGED);
+        assert_eq!(
+            super::module_lineage::get_by_technical_name(&mut conn, "b").len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_module_lineage_same_name_in_two_organizations() {
+        use super::module_lineage::{add, KIND_MOVED, SOURCE_COLLECTOR};
+
+        let mut conn = setup_db();
+        let mut repo_id = |org: &str, repo: &str| {
+            let org = super::gh_organization::add(&mut conn, org).unwrap();
+            super::gh_repository::add(&mut conn, &org.id, repo)
+                .unwrap()
+                .id
+        };
+        let (oca_old, oca_new) = (repo_id("OCA", "oca-old"), repo_id("OCA", "oca-new"));
+        let (acme_old, acme_new) = (repo_id("Acme", "acme-old"), repo_id("Acme", "acme-new"));
+
+        let mut move_to = |from: &i64, to: &i64| {
+            add(
+                &mut conn,
+                KIND_MOVED,
+                "mod_shared",
+                Some(from),
+                "mod_shared",
+                Some(to),
+                &160u8,
+                SOURCE_COLLECTOR,
+            )
+            .unwrap()
+        };
+        assert_eq!(move_to(&oca_old, &oca_new), 1);
+        assert_eq!(move_to(&acme_old, &acme_new), 1);
+        assert_eq!(move_to(&acme_old, &acme_new), 0);
+
+        // Name-only apriori links still dedupe despite their NULL repositories.
+        let renamed = vec![("mod_shared".to_string(), "mod_renamed".to_string())];
+        assert_eq!(
+            super::module_lineage::import_apriori(&mut conn, &170u8, &renamed, &[]).unwrap(),
+            1
+        );
+        assert_eq!(
+            super::module_lineage::import_apriori(&mut conn, &170u8, &renamed, &[]).unwrap(),
+            0
+        );
+        assert_eq!(
+            super::module_lineage::get_by_technical_name(&mut conn, "mod_shared").len(),
+            3
+        );
+    }
+
     #[test]
     fn test_module_view_replace_for_module() {
         use super::module_code_analysis::ViewAnalysisInfo;
@@ -897,6 +1494,121 @@ mod tests {
         assert_eq!(found[0].view_type.as_deref(), Some("form"));
     }
 
+    #[test]
+    fn test_module_ownership_replace_and_bus_factor() {
+        use super::module_code_analysis::OwnershipAnalysisInfo;
+        let mut conn = setup_db();
+        let module =
+            super::module::add(&mut conn, &make_bare_module_info("ownership_test")).unwrap();
+        let module_version =
+            super::module_version::get_or_create(&mut conn, &module.id, &module.version_module)
+                .unwrap();
+        let owner = |name: &str, email: &str, lines: u32| OwnershipAnalysisInfo {
+            name: name.to_string(),
+            emails: vec![email.to_string()],
+            lines,
+        };
+
+        let owners = vec![
+            owner("Alice", "alice@test.com", 60),
+            owner("Bob", "bob@test.com", 30),
+            owner("OCA-git-bot", "bot@test.com", 500),
+            owner("Carol", "carol@test.com", 10),
+        ];
+        super::module_ownership::replace_for_module(
+            &mut conn,
+            &module.id,
+            &module_version.id,
+            &owners,
+        )
+        .unwrap();
+        let bot = super::committer::get_by_name(&mut conn, "OCA-git-bot").unwrap();
+        super::committer::set_bots(&mut conn, &[bot.id]).unwrap();
+        let found =
+            super::module_ownership::get_by_module_version_id(&mut conn, &module_version.id);
+        let names: Vec<&str> = found.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, vec!["Alice", "Bob", "Carol"]);
+        let lines: Vec<i32> = found.iter().map(|o| o.lines).collect();
+        assert_eq!(super::module_ownership::bus_factor(&lines), Some(1));
+        assert_eq!(super::module_ownership::bus_factor(&[40, 40, 20]), Some(2));
+        assert_eq!(super::module_ownership::bus_factor(&[]), None);
+
+        let bob = super::module_ownership::get_current_by_committer_name(&mut conn, "Bob");
+        assert_eq!(bob.len(), 1);
+        assert_eq!(bob[0].technical_name, "ownership_test");
+        assert_eq!(bob[0].lines, 30);
+        // The bot's 500 lines aren't part of the share.
+        assert_eq!(bob[0].total_lines, 100);
+
+        // Re-analyzing must replace, not accumulate.
+        super::module_ownership::replace_for_module(
+            &mut conn,
+            &module.id,
+            &module_version.id,
+            &[owner("Bob", "bob@test.com", 5)],
+        )
+        .unwrap();
+        let found =
+            super::module_ownership::get_by_module_version_id(&mut conn, &module_version.id);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].lines, 5);
+    }
+
+    #[test]
+    fn test_committer_merge_sums_ownership() {
+        use super::module_code_analysis::OwnershipAnalysisInfo;
+        let mut conn = setup_db();
+        let module =
+            super::module::add(&mut conn, &make_bare_module_info("ownership_merge")).unwrap();
+        let module_version =
+            super::module_version::get_or_create(&mut conn, &module.id, &module.version_module)
+                .unwrap();
+        let owner = |name: &str, email: &str, lines: u32| OwnershipAnalysisInfo {
+            name: name.to_string(),
+            emails: vec![email.to_string()],
+            lines,
+        };
+        super::module_ownership::replace_for_module(
+            &mut conn,
+            &module.id,
+            &module_version.id,
+            &[
+                owner("Carol", "carol@test.com", 40),
+                owner("Alice", "alice@test.com", 35),
+                owner("A. Smith", "asmith@test.com", 25),
+            ],
+        )
+        .unwrap();
+        let lines = |conn: &mut crate::DbConnection| -> Vec<i32> {
+            super::module_ownership::get_by_module_version_id(conn, &module_version.id)
+                .iter()
+                .map(|o| o.lines)
+                .collect()
+        };
+        assert_eq!(
+            super::module_ownership::bus_factor(&lines(&mut conn)),
+            Some(2)
+        );
+
+        let alice = super::committer::get_by_name(&mut conn, "Alice").unwrap();
+        let alias = super::committer::get_by_name(&mut conn, "A. Smith").unwrap();
+        super::committer::merge(&mut conn, &alias.id, &alice.id).unwrap();
+
+        let found =
+            super::module_ownership::get_by_module_version_id(&mut conn, &module_version.id);
+        let names: Vec<&str> = found.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, vec!["Alice", "Carol"]);
+        assert_eq!(found[0].lines, 60);
+        assert_eq!(
+            super::module_ownership::bus_factor(&lines(&mut conn)),
+            Some(1)
+        );
+        let alice_owns = super::module_ownership::get_current_by_committer_name(&mut conn, "Alice");
+        assert_eq!(alice_owns.len(), 1);
+        assert_eq!(alice_owns[0].lines, 60);
+        assert_eq!(alice_owns[0].total_lines, 100);
+    }
+
     #[test]
     fn test_module_record_replace_for_module() {
         use super::module_code_analysis::RecordAnalysisInfo;
@@ -949,18 +1661,545 @@ mod tests {
     }
 
     #[test]
-    fn test_module_model_replace_for_module_no_orphans() {
+    fn test_module_erd_build() {
+        use super::module_code_analysis::{FieldAnalysisInfo, ModelAnalysisInfo};
+        use super::module_erd::EntityKind;
+        let mut conn = setup_db();
+        let field = |name: &str, field_type: &str, relation: Option<&str>| FieldAnalysisInfo {
+            name: name.to_string(),
+            field_type: field_type.to_string(),
+            relation: relation.map(str::to_string),
+            attrs: None,
+        };
+        let model = |name: &str, is_new: bool, attrs, fields| ModelAnalysisInfo {
+            model_name: name.to_string(),
+            class_name: "Whatever".to_string(),
+            inherit_from: if is_new {
+                vec![]
+            } else {
+                vec![name.to_string()]
+            },
+            is_new_model: is_new,
+            docstring: None,
+            attrs,
+            fields,
+            methods: vec![],
+        };
+        let mut modules = Vec::new();
+        for (name, models) in [
+            (
+                "erd_a",
+                vec![
+                    model(
+                        "x.order",
+                        true,
+                        Some(serde_json::json!({"description": "Order"})),
+                        vec![
+                            field("partner_id", "Many2one", Some("res.partner")),
+                            field("line_ids", "One2many", Some("x.order.line")),
+                            field("name", "Char", None),
+                        ],
+                    ),
+                    model(
+                        "x.order.line",
+                        true,
+                        Some(
+                            serde_json::json!({"inherits_delegation": {"product.product": "product_id"}}),
+                        ),
+                        vec![field("order_id", "Many2one", Some("x.order"))],
+                    ),
+                ],
+            ),
+            (
+                "erd_b",
+                vec![
+                    model("x.order", false, None, vec![field("note", "Text", None)]),
+                    model(
+                        "x.order.line",
+                        false,
+                        None,
+                        vec![field("order_id", "Many2one", Some("x.order"))],
+                    ),
+                ],
+            ),
+        ] {
+            let module = super::module::add(&mut conn, &make_bare_module_info(name)).unwrap();
+            let module_version =
+                super::module_version::get_or_create(&mut conn, &module.id, &module.version_module)
+                    .unwrap();
+            super::module_model::replace_for_module(
+                &mut conn,
+                &module.id,
+                &module_version.id,
+                &models,
+            )
+            .unwrap();
+            modules.push(module);
+        }
+
+        let merged = super::module_erd::build(&mut conn, &modules, None, true);
+        let keys: Vec<&str> = merged.entities.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec!["x.order", "x.order.line", "res.partner", "product.product"]
+        );
+        let order = &merged.entities[0];
+        assert_eq!(order.kind, EntityKind::Defined);
+        assert_eq!(order.description.as_deref(), Some("Order"));
+        let fields: Vec<&str> = order.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(fields, vec!["line_ids", "name", "partner_id", "note"]);
+        // A field both modules declare is one field, added by both.
+        assert_eq!(merged.entities[1].fields[0].modules, vec!["erd_a", "erd_b"]);
+        assert_eq!(merged.entities[2].kind, EntityKind::Referenced);
+        let relations: Vec<(&str, &str, &str)> = merged
+            .relations
+            .iter()
+            .map(|r| {
+                (
+                    r.source.as_str(),
+                    r.target.as_str(),
+                    r.relation_type.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            relations,
+            vec![
+                ("x.order", "x.order.line", "One2many"),
+                ("x.order", "res.partner", "Many2one"),
+                ("x.order.line", "x.order", "Many2one"),
+                ("x.order.line", "product.product", "_inherits"),
+            ]
+        );
+
+        // Unmerged, each module gets its side of the model, and relations
+        // land on the side defining the target.
+        let split = super::module_erd::build(&mut conn, &modules, None, false);
+        let keys: Vec<&str> = split.entities.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "x.order@erd_a",
+                "x.order.line@erd_a",
+                "x.order@erd_b",
+                "x.order.line@erd_b",
+                "res.partner",
+                "product.product"
+            ]
+        );
+        assert_eq!(split.entities[2].kind, EntityKind::Extended);
+        assert!(split
+            .relations
+            .iter()
+            .any(|r| r.source == "x.order.line@erd_b" && r.target == "x.order@erd_a"));
+
+        let mermaid = super::module_erd::to_mermaid(&merged);
+        assert!(mermaid.starts_with("erDiagram\n"));
+        assert!(
+            mermaid.contains("    e0[\"x.order\"] {\n        One2many line_ids \"x.order.line\"\n")
+        );
+        assert!(mermaid.contains("    e2[\"res.partner\"]\n"));
+        assert!(mermaid.contains("    e0 }o--o| e2 : \"partner_id\""));
+        assert!(mermaid.contains("    e1 ||--|| e3 : \"product_id (_inherits)\""));
+        let svg = super::module_erd::to_svg(&split);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(">x.order (extended by erd_b)</text>"));
+        assert_eq!(svg.matches("marker-end=").count(), split.relations.len());
+    }
+
+    #[test]
+    fn test_model_explorer() {
         use super::module_code_analysis::{
             FieldAnalysisInfo, MethodAnalysisInfo, ModelAnalysisInfo,
         };
         let mut conn = setup_db();
-        let module = super::module::add(&mut conn, &make_bare_module_info("model_test")).unwrap();
-        let module_version =
-            super::module_version::get_or_create(&mut conn, &module.id, &module.version_module)
-                .unwrap();
-
-        let models_v1 = vec![ModelAnalysisInfo {
-            model_name: "res.partner".to_string(),
+        let field = |name: &str| FieldAnalysisInfo {
+            name: name.to_string(),
+            field_type: "Char".to_string(),
+            relation: None,
+            attrs: Some(serde_json::json!({"string": "VAT"})),
+        };
+        let method = |name: &str| MethodAnalysisInfo {
+            name: name.to_string(),
+            decorators: vec![],
+            signature: "(self)".to_string(),
+            docstring: None,
+        };
+        let model = |is_new: bool, fields, methods| ModelAnalysisInfo {
+            model_name: "x.thing".to_string(),
+            class_name: "XThing".to_string(),
+            inherit_from: if is_new {
+                vec![]
+            } else {
+                vec!["x.thing".to_string()]
+            },
+            is_new_model: is_new,
+            docstring: None,
+            attrs: None,
+            fields,
+            methods,
+        };
+        // Named so the extending module sorts first: definers must still lead.
+        for (name, models) in [
+            (
+                "expl_b",
+                vec![model(
+                    false,
+                    vec![field("vatxnumber")],
+                    vec![method("write")],
+                )],
+            ),
+            (
+                "expl_c",
+                vec![model(
+                    true,
+                    vec![field("vat_number")],
+                    vec![method("action_done"), method("_compute_total")],
+                )],
+            ),
+        ] {
+            let module = super::module::add(&mut conn, &make_bare_module_info(name)).unwrap();
+            let module_version =
+                super::module_version::get_or_create(&mut conn, &module.id, &module.version_module)
+                    .unwrap();
+            super::module_model::replace_for_module(
+                &mut conn,
+                &module.id,
+                &module_version.id,
+                &models,
+            )
+            .unwrap();
+            // An older snapshot of the same module must not show up.
+            let old_version =
+                super::module_version::get_or_create(&mut conn, &module.id, "16.0.0.1.0").unwrap();
+            super::module_model::replace_for_module(
+                &mut conn,
+                &module.id,
+                &old_version.id,
+                &[model(false, vec![field("vat_old")], vec![])],
+            )
+            .unwrap();
+        }
+
+        let info = super::model_explorer::get(&mut conn, &16, "x.thing").unwrap();
+        let names: Vec<&str> = info
+            .modules
+            .iter()
+            .map(|m| m.technical_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["expl_c", "expl_b"]);
+        assert!(info.modules[0].is_new_model);
+        assert_eq!(info.modules[0].fields.len(), 1);
+        assert_eq!(info.modules[0].fields[0].name, "vat_number");
+        // Private helpers are left out.
+        let methods: Vec<&str> = info.modules[0]
+            .methods
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect();
+        assert_eq!(methods, vec!["action_done"]);
+        assert_eq!(info.modules[1].inherit_from, vec!["x.thing".to_string()]);
+        assert!(super::model_explorer::get(&mut conn, &17, "x.thing").is_none());
+        assert!(super::model_explorer::get(&mut conn, &16, "x.other").is_none());
+
+        // `_` is a literal, not a LIKE wildcard.
+        let found = super::model_explorer::search_fields(&mut conn, "vat_number", None, 50);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].technical_name, "expl_c");
+        assert_eq!(found[0].model_name, "x.thing");
+        assert_eq!(
+            found[0].field.attrs,
+            Some(serde_json::json!({"string": "VAT"}))
+        );
+        let found = super::model_explorer::search_fields(&mut conn, "VAT", Some(&16), 50);
+        let names: Vec<&str> = found.iter().map(|f| f.field.name.as_str()).collect();
+        assert_eq!(names, vec!["vat_number", "vatxnumber"]);
+        assert!(super::model_explorer::search_fields(&mut conn, "vat", Some(&17), 50).is_empty());
+
+        let models = super::module_model::search_current_model_names(&mut conn, &16, "THING", 10);
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].model_name, "x.thing");
+        assert_eq!(models[0].num_modules, 2);
+    }
+
+    #[test]
+    fn test_route_catalog() {
+        use super::module_code_analysis::ControllerAnalysisInfo;
+        use super::route_catalog::RouteFilter;
+        let mut conn = setup_db();
+        let controller =
+            |class_name: &str, name: &str, route: &str, auth: &str| ControllerAnalysisInfo {
+                class_name: class_name.to_string(),
+                name: name.to_string(),
+                routes: vec![route.to_string()],
+                auth: Some(auth.to_string()),
+                http_type: "http".to_string(),
+                ..Default::default()
+            };
+        for (name, controllers) in [
+            (
+                "routes_a",
+                vec![
+                    ControllerAnalysisInfo {
+                        methods: vec!["GET".to_string()],
+                        uses_sudo: true,
+                        csrf: Some(false),
+                        ..controller("Shop", "shop", "/shop/<int:order_id>", "public")
+                    },
+                    ControllerAnalysisInfo {
+                        http_type: "json".to_string(),
+                        methods: vec!["GET".to_string()],
+                        checks_token_access: true,
+                        ..controller("Portal", "portal_data", "/my/data", "user")
+                    },
+                ],
+            ),
+            (
+                "routes_b",
+                vec![
+                    // Overrides routes_a's handler: same name, same path.
+                    controller("ShopExt", "shop", "/shop/<int:id>", "public"),
+                    // Different methods, so not competing with routes_a, but
+                    // routes_c takes every method.
+                    ControllerAnalysisInfo {
+                        methods: vec!["POST".to_string()],
+                        ..controller("Other", "portal_post", "/my/data", "user")
+                    },
+                ],
+            ),
+            (
+                "routes_c",
+                vec![controller("Mine", "my_data", "/my/data/", "user")],
+            ),
+        ] {
+            let module = super::module::add(&mut conn, &make_bare_module_info(name)).unwrap();
+            let module_version =
+                super::module_version::get_or_create(&mut conn, &module.id, &module.version_module)
+                    .unwrap();
+            super::module_controller::replace_for_module(
+                &mut conn,
+                &module.id,
+                &module_version.id,
+                &controllers,
+            )
+            .unwrap();
+        }
+
+        let catalog = super::route_catalog::build(&mut conn, &16, &RouteFilter::default());
+        assert_eq!(catalog.routes.len(), 5);
+        let collisions: Vec<(&str, bool, usize)> = catalog
+            .collisions
+            .iter()
+            .map(|c| (c.route.as_str(), c.conflicting, c.endpoints.len()))
+            .collect();
+        assert_eq!(
+            collisions,
+            vec![("/my/data", true, 3), ("/shop/<int>", false, 2)]
+        );
+        let token_checked: Vec<&str> = catalog
+            .routes
+            .iter()
+            .filter(|r| r.checks_token_access)
+            .map(|r| r.technical_name.as_str())
+            .collect();
+        assert_eq!(token_checked, vec!["routes_a"]);
+
+        let public_sudo = super::route_catalog::build(
+            &mut conn,
+            &16,
+            &RouteFilter {
+                public: true,
+                sudo: true,
+                csrf_off: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(public_sudo.routes.len(), 1);
+        assert_eq!(public_sudo.routes[0].handler, "Shop.shop");
+        assert!(public_sudo.routes[0].collides);
+        // Collisions are still computed on the whole set.
+        assert_eq!(public_sudo.collisions.len(), 2);
+
+        // Alone, routes_a collides with nobody.
+        let only_a = super::route_catalog::build(
+            &mut conn,
+            &16,
+            &RouteFilter {
+                modules: Some(vec!["routes_a".to_string()]),
+                ..Default::default()
+            },
+        );
+        assert_eq!(only_a.routes.len(), 2);
+        assert!(only_a.collisions.is_empty());
+        let json = super::route_catalog::build(
+            &mut conn,
+            &16,
+            &RouteFilter {
+                json: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(json.routes.len(), 1);
+        let csv = super::route_catalog::to_csv(&only_a);
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.contains("/shop/<int:order_id>,routes_a,"));
+    }
+
+    #[test]
+    fn test_security_overview() {
+        use super::collection_run::{self, RunTotalsInfo};
+        use super::module_security_warning::{
+            SecurityWarningInfo, SEVERITY_ERROR, SEVERITY_WARNING,
+        };
+        use super::security_overview::{SecurityFilter, SeverityFilter};
+        let mut conn = setup_db();
+        let finding = |severity: &str, code: &str, xml_id: &str| SecurityWarningInfo {
+            severity: severity.to_string(),
+            code: code.to_string(),
+            message: format!("{code} on {xml_id}"),
+            xml_id: Some(xml_id.to_string()),
+        };
+        let mut versions = Vec::new();
+        for (name, findings) in [
+            (
+                "sec_a",
+                vec![
+                    finding(SEVERITY_ERROR, "acl-global-read", "access_a_public"),
+                    finding(SEVERITY_WARNING, "route-user-csrf-off", "/sec_a/page"),
+                ],
+            ),
+            (
+                "sec_b",
+                vec![finding(SEVERITY_ERROR, "route-public-sudo", "/sec_b/hook")],
+            ),
+        ] {
+            let module = super::module::add(&mut conn, &make_bare_module_info(name)).unwrap();
+            let module_version =
+                super::module_version::get_or_create(&mut conn, &module.id, &module.version_module)
+                    .unwrap();
+            super::module_security_warning::replace_for_module(
+                &mut conn,
+                &module.id,
+                &module_version.id,
+                &findings,
+            )
+            .unwrap();
+            versions.push((module.id, module_version.id));
+        }
+        let collect = |conn: &mut DbConnection| {
+            let run = collection_run::start(
+                conn,
+                "AnalysisOrg",
+                "AnalysisOrg",
+                &16,
+                "github",
+                collection_run::TRIGGER_SCHEDULE,
+            )
+            .unwrap();
+            super::collection_run_security::record(conn, &run.id, "AnalysisOrg", &16).unwrap();
+            collection_run::finish(conn, &run.id, &1, &RunTotalsInfo::default(), None).unwrap();
+            collection_run::get_by_id(conn, &run.id).unwrap()
+        };
+        // Runs from before findings were counted stay out of the trend.
+        collection_run::start(
+            &mut conn,
+            "AnalysisOrg",
+            "AnalysisOrg",
+            &16,
+            "github",
+            collection_run::TRIGGER_MANUAL,
+        )
+        .unwrap();
+        let first = collect(&mut conn);
+        assert_eq!(first.security_errors, Some(2));
+        assert_eq!(first.security_warnings, Some(1));
+        // sec_b's route gets fixed.
+        super::module_security_warning::replace_for_module(
+            &mut conn,
+            &versions[1].0,
+            &versions[1].1,
+            &[],
+        )
+        .unwrap();
+        let second = collect(&mut conn);
+        assert_eq!(second.security_errors, Some(1));
+
+        // Minor findings are left out unless asked for.
+        let overview = super::security_overview::build(&mut conn, &SecurityFilter::default());
+        assert_eq!((overview.errors, overview.warnings), (1, 0));
+        let org = &overview.versions[0].organizations[0];
+        assert_eq!(org.organization, "AnalysisOrg");
+        assert_eq!(org.codes[0].code, "acl-global-read");
+        assert_eq!(org.codes[0].findings[0].technical_name, "sec_a");
+        assert_eq!(
+            overview.codes,
+            vec![
+                "acl-global-read".to_string(),
+                "route-user-csrf-off".to_string()
+            ]
+        );
+        assert_eq!(overview.trend.len(), 2);
+        assert_eq!(overview.trend[0].collection_run_id, second.id);
+        assert_eq!(overview.trend[0].errors_delta, Some(-1));
+        assert_eq!(overview.trend[0].warnings_delta, Some(0));
+        assert_eq!(overview.trend[1].errors_delta, None);
+
+        let minor = super::security_overview::build(
+            &mut conn,
+            &SecurityFilter {
+                severity: SeverityFilter::Warning,
+                ..Default::default()
+            },
+        );
+        assert_eq!((minor.errors, minor.warnings), (0, 1));
+        assert_eq!(
+            minor.versions[0].organizations[0].codes[0].findings[0]
+                .xml_id
+                .as_deref(),
+            Some("/sec_a/page")
+        );
+
+        // A rule's trend only sums that rule.
+        let by_code = super::security_overview::build(
+            &mut conn,
+            &SecurityFilter {
+                code: Some("route-public-sudo".to_string()),
+                severity: SeverityFilter::All,
+                ..Default::default()
+            },
+        );
+        assert!(by_code.versions.is_empty());
+        assert_eq!(
+            by_code
+                .trend
+                .iter()
+                .map(|point| point.errors)
+                .collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        let other_org = super::security_overview::build(
+            &mut conn,
+            &SecurityFilter {
+                org: Some("OCA".to_string()),
+                ..Default::default()
+            },
+        );
+        assert!(other_org.versions.is_empty() && other_org.trend.is_empty());
+    }
+
+    #[test]
+    fn test_module_model_replace_for_module_no_orphans() {
+        use super::module_code_analysis::{
+            FieldAnalysisInfo, MethodAnalysisInfo, ModelAnalysisInfo,
+        };
+        let mut conn = setup_db();
+        let module = super::module::add(&mut conn, &make_bare_module_info("model_test")).unwrap();
+        let module_version =
+            super::module_version::get_or_create(&mut conn, &module.id, &module.version_module)
+                .unwrap();
+
+        let models_v1 = vec![ModelAnalysisInfo {
+            model_name: "res.partner".to_string(),
             class_name: "ResPartner".to_string(),
             inherit_from: vec!["res.partner".to_string()],
             is_new_model: false,
@@ -1152,4 +2391,851 @@ mod tests {
         let current = super::module_version::resolve_current(&mut conn, &module_v2).unwrap();
         assert_eq!(current.id, module_version_v2.id);
     }
+
+    #[test]
+    fn test_module_version_prune_snapshots() {
+        use super::module_code_analysis::ViewAnalysisInfo;
+        let mut conn = setup_db();
+        let mut info = make_bare_module_info("pruned_module");
+        let mut version_ids = Vec::new();
+        for version in ["1.0.0", "1.0.1", "1.0.2"] {
+            info.version_module = version.to_string();
+            let module = super::module::add(&mut conn, &info).unwrap();
+            let module_version =
+                super::module_version::get_or_create(&mut conn, &module.id, version).unwrap();
+            let views = vec![ViewAnalysisInfo {
+                xml_id: format!("view_{version}"),
+                ..Default::default()
+            }];
+            super::module_view::replace_for_module(
+                &mut conn,
+                &module.id,
+                &module_version.id,
+                &views,
+            )
+            .unwrap();
+            version_ids.push(module_version.id);
+        }
+        // Re-collecting an older version after a revert makes it current again.
+        info.version_module = "1.0.0".to_string();
+        let module = super::module::add(&mut conn, &info).unwrap();
+
+        assert_eq!(
+            super::module_version::prune_snapshots(&mut conn, 1).unwrap(),
+            1
+        );
+        let kept: Vec<i64> = super::module_version::get_by_module_id(&mut conn, &module.id)
+            .iter()
+            .map(|v| v.id)
+            .collect();
+        assert_eq!(kept, vec![version_ids[0], version_ids[2]]);
+        assert!(
+            super::module_view::get_by_module_version_id(&mut conn, &version_ids[1]).is_empty()
+        );
+        assert_eq!(
+            super::module_view::get_by_module_version_id(&mut conn, &version_ids[0]).len(),
+            1
+        );
+
+        // Nothing left to prune.
+        assert_eq!(
+            super::module_version::prune_snapshots(&mut conn, 1).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_system_event_retention() {
+        use diesel::prelude::*;
+        let mut conn = setup_db();
+        for severity in ["info", "success", "warning", "error"] {
+            let event = super::system_event::add(&mut conn, "internal", severity, "old").unwrap();
+            diesel::update(
+                crate::schema::system_event::table
+                    .filter(crate::schema::system_event::id.eq(event.id)),
+            )
+            .set(crate::schema::system_event::date.eq("2020-01-01 00:00:00"))
+            .execute(&mut conn)
+            .unwrap();
+            super::system_event::add(&mut conn, "internal", severity, "new").unwrap();
+        }
+
+        let cutoff = "2021-01-01 00:00:00";
+        assert_eq!(
+            super::system_event::delete_info_before(&mut conn, cutoff).unwrap(),
+            2
+        );
+        let left = super::system_event::get_messages_page(&mut conn, i64::MAX, None, None, 10);
+        assert_eq!(left.len(), 6);
+        assert!(left
+            .iter()
+            .all(|e| e.message == "new" || e.severity == "warning" || e.severity == "error"));
+
+        assert_eq!(
+            super::system_event::delete_warnings_before(&mut conn, cutoff).unwrap(),
+            2
+        );
+        let left = super::system_event::get_messages_page(&mut conn, i64::MAX, None, None, 10);
+        assert!(left.iter().all(|e| e.message == "new"));
+        assert_eq!(left.len(), 4);
+    }
+
+    #[test]
+    fn test_atlas_graph_build_and_refresh() {
+        let mut conn = setup_db();
+        let module_type = super::dependency_type::get_by_name(&mut conn, "module").unwrap();
+        let python_type = super::dependency_type::get_by_name(&mut conn, "python").unwrap();
+
+        let mut core_info = make_bare_module_info("sale");
+        core_info.git_org = "odoo".to_string();
+        core_info.git_repo = "odoo".to_string();
+        core_info.application = true;
+        let core_sale = super::module::add(&mut conn, &core_info).unwrap();
+        // The OCA copy of a donated module must not shadow the core one.
+        let mut oca_info = make_bare_module_info("sale");
+        oca_info.git_org = "OCA".to_string();
+        oca_info.git_repo = "sale-workflow".to_string();
+        super::module::add(&mut conn, &oca_info).unwrap();
+        let mut ext_info = make_bare_module_info("sale_ext");
+        ext_info.git_org = "OCA".to_string();
+        ext_info.git_repo = "sale-workflow".to_string();
+        let ext = super::module::add(&mut conn, &ext_info).unwrap();
+        for dep in ["base", "sale", "missing_module"] {
+            super::dependency_module::add(&mut conn, &module_type.id, dep, &ext.id).unwrap();
+        }
+        super::dependency_module::add(&mut conn, &python_type.id, "requests", &ext.id).unwrap();
+        super::dependency_module::add(&mut conn, &module_type.id, "base", &core_sale.id).unwrap();
+
+        let graph = super::atlas_graph::build(&mut conn, &16);
+        let mut node_keys: Vec<&str> = graph.nodes.iter().map(|n| n.key.as_str()).collect();
+        node_keys.sort();
+        assert_eq!(
+            node_keys,
+            vec!["o_missing_module", "o_sale", "o_sale_ext", "p_requests"]
+        );
+        let sale = graph.nodes.iter().find(|n| n.key == "o_sale").unwrap();
+        assert_eq!(sale.attributes["repository"], "odoo");
+        assert_eq!(sale.attributes["size"], "12");
+        let mut edge_keys: Vec<&str> = graph.edges.iter().map(|e| e.key.as_str()).collect();
+        edge_keys.sort();
+        assert_eq!(
+            edge_keys,
+            vec![
+                "o_sale_ext__missing_module",
+                "o_sale_ext__sale",
+                "p_sale_ext__requests"
+            ]
+        );
+
+        assert!(super::atlas_graph::get_by_version_odoo(&mut conn, &16).is_none());
+        super::atlas_graph::refresh(&mut conn, &16).unwrap();
+        let stored = super::atlas_graph::get_by_version_odoo(&mut conn, &16).unwrap();
+        assert_eq!(stored.etag, super::atlas_graph::etag_for(&stored.data));
+        // Rebuilding an unchanged graph keeps its ETag.
+        super::atlas_graph::refresh(&mut conn, &16).unwrap();
+        let again = super::atlas_graph::get_by_version_odoo(&mut conn, &16).unwrap();
+        assert_eq!((again.id, again.etag), (stored.id, stored.etag));
+    }
+
+    #[test]
+    fn test_atlas_graph_filter_and_ego() {
+        use super::atlas_graph::GraphFilter;
+        let mut conn = setup_db();
+        let module_type = super::dependency_type::get_by_name(&mut conn, "module").unwrap();
+        // web_a -> web_b -> core_c, sale_d -> web_b
+        let mut ids = std::collections::HashMap::new();
+        for (name, org, repo, category) in [
+            ("web_a", "OCA", "web", "Web"),
+            ("web_b", "OCA", "web", "Web"),
+            ("core_c", "odoo", "odoo", "Hidden"),
+            ("sale_d", "OCA", "sale-workflow", "Sales"),
+        ] {
+            let mut info = make_bare_module_info(name);
+            info.git_org = org.to_string();
+            info.git_repo = repo.to_string();
+            info.category = category.to_string();
+            ids.insert(name, super::module::add(&mut conn, &info).unwrap().id);
+        }
+        for (module, dep) in [("web_a", "web_b"), ("web_b", "core_c"), ("sale_d", "web_b")] {
+            super::dependency_module::add(&mut conn, &module_type.id, dep, &ids[module]).unwrap();
+        }
+        let graph = super::atlas_graph::build(&mut conn, &16);
+        let keys = |graph: &super::atlas_graph::GraphInfo| {
+            let mut keys: Vec<String> = graph.nodes.iter().map(|n| n.key.clone()).collect();
+            keys.sort();
+            keys
+        };
+
+        // A repository keeps its modules and what they depend on.
+        let web = graph.clone().filter(&GraphFilter {
+            repository: Some("web".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(keys(&web), vec!["o_core_c", "o_web_a", "o_web_b"]);
+        assert_eq!(web.edges.len(), 2);
+        let sales = graph.clone().filter(&GraphFilter {
+            category: Some("Sales".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(keys(&sales), vec!["o_sale_d", "o_web_b"]);
+        let set = graph.clone().filter(&GraphFilter {
+            organization: Some("OCA".to_string()),
+            modules: Some(["web_a".to_string(), "core_c".to_string()].into()),
+            ..Default::default()
+        });
+        assert_eq!(keys(&set), vec!["o_web_a", "o_web_b"]);
+
+        // Ego graph of web_b: one hop each way.
+        let ego = graph.clone().ego("web_b", 1, 1);
+        assert_eq!(
+            keys(&ego),
+            vec!["o_core_c", "o_sale_d", "o_web_a", "o_web_b"]
+        );
+        assert_eq!(ego.edges.len(), 3);
+        let ego = graph.clone().ego("web_a", 1, 0);
+        assert_eq!(keys(&ego), vec!["o_web_a", "o_web_b"]);
+        let ego = graph.clone().ego("web_a", 2, 0);
+        assert_eq!(keys(&ego), vec!["o_core_c", "o_web_a", "o_web_b"]);
+        assert!(graph.ego("missing", 3, 3).nodes.is_empty());
+    }
+
+    #[test]
+    fn test_atlas_graph_closure_and_export() {
+        use super::atlas_export::{self, ExportFormat};
+        let mut conn = setup_db();
+        let module_type = super::dependency_type::get_by_name(&mut conn, "module").unwrap();
+        let python_type = super::dependency_type::get_by_name(&mut conn, "python").unwrap();
+        // web_a -> web_b -> core_c (-> python-dateutil), sale_d -> web_b
+        let mut ids = std::collections::HashMap::new();
+        for (name, org, repo) in [
+            ("web_a", "OCA", "web"),
+            ("web_b", "OCA", "web"),
+            ("core_c", "odoo", "odoo"),
+            ("sale_d", "OCA", "sale-workflow"),
+        ] {
+            let mut info = make_bare_module_info(name);
+            info.git_org = org.to_string();
+            info.git_repo = repo.to_string();
+            ids.insert(name, super::module::add(&mut conn, &info).unwrap().id);
+        }
+        for (module, dep) in [("web_a", "web_b"), ("web_b", "core_c"), ("sale_d", "web_b")] {
+            super::dependency_module::add(&mut conn, &module_type.id, dep, &ids[module]).unwrap();
+        }
+        super::dependency_module::add(
+            &mut conn,
+            &python_type.id,
+            "python-dateutil",
+            &ids["core_c"],
+        )
+        .unwrap();
+        let keys = |graph: &super::atlas_graph::GraphInfo| {
+            let mut keys: Vec<String> = graph.nodes.iter().map(|n| n.key.clone()).collect();
+            keys.sort();
+            keys
+        };
+
+        let closure = super::atlas_graph::closure(&mut conn, &16, &["web_a".to_string()]);
+        assert_eq!(
+            keys(&closure),
+            vec!["o_core_c", "o_web_a", "o_web_b", "p_python-dateutil"]
+        );
+        assert_eq!(closure.edges.len(), 3);
+        let set = super::atlas_graph::closure(
+            &mut conn,
+            &16,
+            &["web_a".to_string(), "sale_d".to_string()],
+        );
+        assert_eq!(set.nodes.len(), 5);
+        assert!(
+            super::atlas_graph::closure(&mut conn, &16, &["missing".to_string()])
+                .nodes
+                .is_empty()
+        );
+        let modules_only = closure.clone().without_external();
+        assert_eq!(keys(&modules_only), vec!["o_core_c", "o_web_a", "o_web_b"]);
+        assert_eq!(modules_only.edges.len(), 2);
+
+        let dot = atlas_export::render(&modules_only, "web_a (Odoo 16.0)", ExportFormat::Dot);
+        assert!(dot.starts_with("digraph \"web_a (Odoo 16.0)\" {"));
+        assert!(dot.contains("label=\"OCA/web\";"));
+        assert!(dot.contains("\"o_web_a\" -> \"o_web_b\";"));
+
+        // Mermaid ids are positional, the dash of the package name stays in
+        // its label.
+        let mermaid = atlas_export::to_mermaid(&closure, "web_a");
+        assert!(mermaid.contains("flowchart LR"));
+        assert!(mermaid.contains("subgraph g0[\"OCA/web\"]"));
+        assert!(mermaid.contains("([\"python-dateutil\"])"));
+        assert_eq!(mermaid.matches(" --> ").count(), 3);
+
+        let graphml = atlas_export::to_graphml(&closure, "web_a");
+        for key in [
+            "n_category",
+            "n_color",
+            "n_label",
+            "n_organization",
+            "n_repository",
+            "n_size",
+            "e_size",
+        ] {
+            assert!(graphml.contains(&format!("<key id=\"{key}\"")), "{key}");
+        }
+        assert!(graphml.contains("<node id=\"p_python-dateutil\">"));
+        assert_eq!(graphml.matches("<edge ").count(), 3);
+        assert_eq!(ExportFormat::from_name("mmd"), Some(ExportFormat::Mermaid));
+        assert_eq!(ExportFormat::from_name("png"), None);
+    }
+
+    #[test]
+    fn test_module_search_ranking_and_snippet() {
+        use super::module_code_analysis::{FieldAnalysisInfo, ModelAnalysisInfo};
+        let mut conn = setup_db();
+        let by_name = super::module::add(
+            &mut conn,
+            &super::module::ManifestInfo {
+                summary: "Classify sale orders by type".to_string(),
+                ..make_bare_module_info("sale_order_type")
+            },
+        )
+        .unwrap();
+        let by_docs = super::module::add(
+            &mut conn,
+            &super::module::ManifestInfo {
+                description: "Séquences <per> order type.".to_string(),
+                ..make_bare_module_info("sequence_extra")
+            },
+        )
+        .unwrap();
+        let by_field =
+            super::module::add(&mut conn, &make_bare_module_info("stock_extra")).unwrap();
+        let module_version =
+            super::module_version::get_or_create(&mut conn, &by_field.id, &by_field.version_module)
+                .unwrap();
+        super::module_model::replace_for_module(
+            &mut conn,
+            &by_field.id,
+            &module_version.id,
+            &[ModelAnalysisInfo {
+                model_name: "stock.picking".to_string(),
+                class_name: "StockPicking".to_string(),
+                fields: vec![FieldAnalysisInfo {
+                    name: "order_type_id".to_string(),
+                    field_type: "Many2one".to_string(),
+                    relation: None,
+                    attrs: Some(serde_json::json!({"string": "'Order Type'"})),
+                }],
+                ..Default::default()
+            }],
+        )
+        .unwrap();
+        for module in [&by_name, &by_docs, &by_field] {
+            super::module_search::refresh(&mut conn, &module.id).unwrap();
+        }
+
+        // The technical name outweighs the description and the fields.
+        let hits = super::module_search::search(&mut conn, "ORDER typ", Some(&16), 10);
+        let names: Vec<&str> = hits.iter().map(|h| h.technical_name.as_str()).collect();
+        assert_eq!(names[0], "sale_order_type");
+        assert_eq!(names.len(), 3);
+        let docs_hit = hits
+            .iter()
+            .find(|h| h.technical_name == "sequence_extra")
+            .unwrap();
+        assert!(docs_hit.snippet.starts_with("Séquences "));
+        assert!(docs_hit
+            .snippet
+            .contains("<mark>order</mark> <mark>type</mark>"));
+        // Diacritics are folded away.
+        assert_eq!(
+            super::module_search::search(&mut conn, "sequences", None, 10).len(),
+            1
+        );
+        // FTS5 syntax is just text.
+        assert!(super::module_search::search(&mut conn, "zzz OR order", None, 10).is_empty());
+        assert!(super::module_search::search(&mut conn, "- ()", None, 10).is_empty());
+
+        // search_modules only looks at the technical name.
+        let generic = super::module::get_generic_info(&mut conn, "order");
+        assert_eq!(generic.len(), 1);
+        assert_eq!(generic[0].technical_name, "sale_order_type");
+
+        let criteria = super::module::search_by_criteria(
+            &mut conn,
+            &16,
+            Some("order type"),
+            None,
+            None,
+            None,
+            super::module_health::HealthSort::Relevance,
+            10,
+        );
+        assert_eq!(criteria[0].technical_name, "sale_order_type");
+        assert!(criteria[0].snippet.as_deref().unwrap().contains("<mark>"));
+        let listing = super::module::search_by_criteria(
+            &mut conn,
+            &16,
+            None,
+            None,
+            None,
+            None,
+            super::module_health::HealthSort::Relevance,
+            10,
+        );
+        assert_eq!(listing.len(), 3);
+        assert!(listing.iter().all(|m| m.snippet.is_none()));
+
+        // Stale modules leave the index with them.
+        super::module::delete_outdated(&mut conn, &by_name.gh_repository_id, &16, &[by_name.id])
+            .unwrap();
+        assert_eq!(
+            super::module_search::search(&mut conn, "order", None, 10).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_repository_overview() {
+        use super::collection_run::{self, RunTotalsInfo};
+        use super::collection_run_repo::{self, RunRepoInfo};
+        use super::module::CommitterActivity;
+        use super::module_security_warning::{SecurityWarningInfo, SEVERITY_ERROR};
+        let mut conn = setup_db();
+        let activity = |total: u32| CommitterActivity {
+            total,
+            ..Default::default()
+        };
+
+        // Stored the way the collector does, 16.0 as 160.
+        let bare_module_info = |name: &str| super::module::ManifestInfo {
+            version_odoo: 160,
+            ..make_bare_module_info(name)
+        };
+        let mut info = bare_module_info("repo_a");
+        info.committers.insert("Ana".to_string(), activity(3));
+        info.committers.insert("Luis".to_string(), activity(1));
+        let repo_a_16 = super::module::add(&mut conn, &info).unwrap();
+        let mut info = bare_module_info("repo_a");
+        info.version_odoo = 170;
+        info.version_module = "17.0.1.0.0".to_string();
+        info.committers.insert("Ana".to_string(), activity(2));
+        super::module::add(&mut conn, &info).unwrap();
+        let mut info = bare_module_info("repo_b");
+        info.installable = false;
+        info.committers.insert("Luis".to_string(), activity(1));
+        super::module::add(&mut conn, &info).unwrap();
+        // Another repository of the same organization stays out.
+        let mut info = bare_module_info("elsewhere");
+        info.git_repo = "other-repo".to_string();
+        info.committers.insert("Luis".to_string(), activity(9));
+        super::module::add(&mut conn, &info).unwrap();
+
+        let module_version =
+            super::module_version::get_or_create(&mut conn, &repo_a_16.id, "16.0.1.0.0").unwrap();
+        super::module_security_warning::replace_for_module(
+            &mut conn,
+            &repo_a_16.id,
+            &module_version.id,
+            &[SecurityWarningInfo {
+                severity: SEVERITY_ERROR.to_string(),
+                code: "acl-global-read".to_string(),
+                message: "readable by everyone".to_string(),
+                xml_id: Some("access_repo_a_public".to_string()),
+            }],
+        )
+        .unwrap();
+        super::pull_request::add(
+            &mut conn,
+            "[18.0][MIG] repo_b",
+            "repo_b",
+            &7,
+            &180u8,
+            &repo_a_16.gh_repository_id,
+            Some("2024-01-01 00:00:00"),
+            Some("failure"),
+            None,
+        )
+        .unwrap();
+        let run = collection_run::start(
+            &mut conn,
+            "AnalysisOrg",
+            "AnalysisOrg",
+            &160,
+            "github",
+            collection_run::TRIGGER_SCHEDULE,
+        )
+        .unwrap();
+        collection_run_repo::add(
+            &mut conn,
+            &run.id,
+            &RunRepoInfo {
+                org_name: "AnalysisOrg".to_string(),
+                repo_name: "analysis-repo".to_string(),
+                clone_status: collection_run_repo::CLONE_OK.to_string(),
+                analysis_status: collection_run_repo::ANALYSIS_OK.to_string(),
+                modules_added: 2,
+                modules_updated: 0,
+                modules_removed: 0,
+                error: None,
+            },
+        )
+        .unwrap();
+        collection_run::finish(&mut conn, &run.id, &1, &RunTotalsInfo::default(), None).unwrap();
+
+        assert!(
+            super::repository_overview::get(&mut conn, "AnalysisOrg", "missing-repo").is_none()
+        );
+        let overview =
+            super::repository_overview::get(&mut conn, "AnalysisOrg", "analysis-repo").unwrap();
+        let versions: Vec<&str> = overview
+            .branches
+            .iter()
+            .map(|b| b.odoo_version.as_str())
+            .collect();
+        // 18.0 only has the migration PR.
+        assert_eq!(versions, vec!["18.0", "17.0", "16.0"]);
+        assert_eq!(overview.branches[2].num_modules, 2);
+        assert_eq!(overview.branches[2].security_errors, 1);
+        assert_eq!(
+            overview.branches[2]
+                .last_collection
+                .as_ref()
+                .map(|c| c.collection_run_id),
+            Some(run.id)
+        );
+        assert!(overview.branches[0].last_collection.is_none());
+        assert!(overview.last_collected.is_some());
+
+        let names: Vec<&str> = overview
+            .modules
+            .iter()
+            .map(|m| m.technical_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["repo_a", "repo_b"]);
+        let repo_a = &overview.modules[0];
+        assert_eq!(repo_a.ports[0].version_module, None);
+        assert_eq!(
+            repo_a.ports[1].version_module.as_deref(),
+            Some("17.0.1.0.0")
+        );
+        let repo_b = &overview.modules[1];
+        assert_eq!(repo_b.ports[0].pull_requests, vec![7]);
+        assert!(repo_b.ports[1].version_module.is_none());
+        assert!(!repo_b.ports[2].installable);
+        assert_eq!(overview.pull_requests.len(), 1);
+
+        let committers: Vec<(&str, i64, i64)> = overview
+            .committers
+            .iter()
+            .map(|c| (c.name.as_str(), c.total_commits, c.modules_touched))
+            .collect();
+        assert_eq!(committers, vec![("Ana", 5, 1), ("Luis", 2, 2)]);
+    }
+
+    #[test]
+    fn test_migration_status() {
+        use super::migration_status::{MigrationStatusFilter, PortGapStatus, PortGapStatusFilter};
+        let mut conn = setup_db();
+        let module_type = super::dependency_type::get_by_name(&mut conn, "module").unwrap();
+        let add_module = |conn: &mut DbConnection, name: &str, version_odoo: u8, repo: &str| {
+            let mut info = make_bare_module_info(name);
+            info.version_odoo = version_odoo;
+            info.version_module = format!("{}.1.0.0", version_odoo as f32 / 10.0);
+            info.git_repo = repo.to_string();
+            super::module::add(conn, &info).unwrap()
+        };
+
+        for name in ["gap_base", "gap_pr", "gap_old", "ported", "moved"] {
+            add_module(&mut conn, name, 160, "analysis-repo");
+        }
+        let mut info = make_bare_module_info("not_installable");
+        info.version_odoo = 160;
+        info.installable = false;
+        super::module::add(&mut conn, &info).unwrap();
+        // gap_base is what most others wait for.
+        for name in ["dep_one", "dep_two"] {
+            let dependent = add_module(&mut conn, name, 160, "other-repo");
+            super::dependency_module::add(&mut conn, &module_type.id, "gap_base", &dependent.id)
+                .unwrap();
+            add_module(&mut conn, name, 170, "other-repo");
+        }
+        let dependent = add_module(&mut conn, "dep_pr", 160, "other-repo");
+        super::dependency_module::add(&mut conn, &module_type.id, "gap_pr", &dependent.id).unwrap();
+        add_module(&mut conn, "dep_pr", 170, "other-repo");
+        add_module(&mut conn, "ported", 170, "analysis-repo");
+        // Ported, just to another repository of the organization.
+        add_module(&mut conn, "moved", 170, "other-repo");
+        let gap_new = add_module(&mut conn, "gap_new", 170, "analysis-repo");
+        super::module_lineage::import_apriori(
+            &mut conn,
+            &170u8,
+            &[("gap_old".to_string(), "gap_new".to_string())],
+            &[],
+        )
+        .unwrap();
+        // Renaming another repository's `gap_base` doesn't rename this one.
+        super::module_lineage::add(
+            &mut conn,
+            super::module_lineage::KIND_RENAMED,
+            "gap_base",
+            Some(&dependent.gh_repository_id),
+            "gap_base_new",
+            Some(&dependent.gh_repository_id),
+            &170u8,
+            super::module_lineage::SOURCE_COLLECTOR,
+        )
+        .unwrap();
+        for (prid, version_odoo) in [(12, 170u8), (3, 180u8)] {
+            super::pull_request::add(
+                &mut conn,
+                "[MIG] gap_pr",
+                "gap_pr",
+                &prid,
+                &version_odoo,
+                &gap_new.gh_repository_id,
+                Some("2024-01-01 00:00:00"),
+                Some("success"),
+                None,
+            )
+            .unwrap();
+        }
+
+        let status = super::migration_status::build(
+            &mut conn,
+            &MigrationStatusFilter {
+                version_odoo: Some(170),
+                ..Default::default()
+            },
+        );
+        assert_eq!(status.from_version.as_deref(), Some("16.0"));
+        assert_eq!(status.to_version.as_deref(), Some("17.0"));
+        assert_eq!(status.odoo_versions, vec!["17.0".to_string()]);
+        assert_eq!(
+            (
+                status.with_pull_request,
+                status.without_pull_request,
+                status.renamed
+            ),
+            (1, 1, 1)
+        );
+        // other-repo has everything at 17.0.
+        assert_eq!(status.repositories_complete, 1);
+        assert_eq!(status.repositories.len(), 1);
+        let repo = &status.repositories[0];
+        assert_eq!(repo.repository, "analysis-repo");
+        assert_eq!((repo.modules_from, repo.modules_ported), (5, 2));
+        let gaps: Vec<(&str, PortGapStatus, i64)> = repo
+            .gaps
+            .iter()
+            .map(|gap| (gap.technical_name.as_str(), gap.status, gap.dependents))
+            .collect();
+        assert_eq!(
+            gaps,
+            vec![
+                ("gap_base", PortGapStatus::NoPullRequest, 2),
+                ("gap_pr", PortGapStatus::PullRequest, 1),
+                ("gap_old", PortGapStatus::Renamed, 0),
+            ]
+        );
+        let prs: Vec<i64> = repo.gaps[1]
+            .pull_requests
+            .iter()
+            .map(|pr| pr.prid)
+            .collect();
+        assert_eq!(prs, vec![12]);
+        assert_eq!(repo.gaps[2].successor.as_deref(), Some("gap_new"));
+
+        let without_pr = super::migration_status::build(
+            &mut conn,
+            &MigrationStatusFilter {
+                status: PortGapStatusFilter::NoPullRequest,
+                ..Default::default()
+            },
+        );
+        assert_eq!(without_pr.to_version.as_deref(), Some("17.0"));
+        assert_eq!(without_pr.repositories[0].gaps.len(), 1);
+        assert_eq!(without_pr.with_pull_request, 1);
+
+        let other = super::migration_status::build(
+            &mut conn,
+            &MigrationStatusFilter {
+                repo: Some("other-repo".to_string()),
+                ..Default::default()
+            },
+        );
+        assert!(other.repositories.is_empty());
+        assert_eq!(other.repositories_complete, 1);
+    }
+
+    #[test]
+    fn test_module_health() {
+        use super::module_health::HealthSort;
+        use super::module_security_warning::{
+            SecurityWarningInfo, SEVERITY_ERROR, SEVERITY_WARNING,
+        };
+        use super::open_pull_request::OpenPullRequestInfo;
+
+        let mut conn = setup_db();
+        let healthy = super::module::add(
+            &mut conn,
+            &super::module::ManifestInfo {
+                version_odoo: 170,
+                version_module: "17.0.1.0.0".to_string(),
+                maintainer: "alice, bob".to_string(),
+                last_commit_date: chrono::Utc::now().format("%Y-%m-%d").to_string(),
+                ..make_bare_module_info("healthy")
+            },
+        )
+        .unwrap();
+        let version =
+            super::module_version::get_or_create(&mut conn, &healthy.id, "17.0.1.0.0").unwrap();
+        super::module_version::set_test_files(&mut conn, &version.id, Some(3)).unwrap();
+        // Never analyzed for tests: no module_version row at all.
+        super::module::add(
+            &mut conn,
+            &super::module::ManifestInfo {
+                version_odoo: 170,
+                version_module: "17.0.1.0.0".to_string(),
+                ..make_bare_module_info("unanalyzed")
+            },
+        )
+        .unwrap();
+        let weak = super::module::add(
+            &mut conn,
+            &super::module::ManifestInfo {
+                version_odoo: 160,
+                last_commit_date: "Thu, 1 Jan 2015 10:00:00 +0100".to_string(),
+                ..make_bare_module_info("weak")
+            },
+        )
+        .unwrap();
+        let version =
+            super::module_version::get_or_create(&mut conn, &weak.id, "16.0.1.0.0").unwrap();
+        super::module_version::set_test_files(&mut conn, &version.id, Some(0)).unwrap();
+        let warning = |severity: &str, code: &str| SecurityWarningInfo {
+            severity: severity.to_string(),
+            code: code.to_string(),
+            message: "finding".to_string(),
+            xml_id: None,
+        };
+        super::module_security_warning::replace_for_module(
+            &mut conn,
+            &weak.id,
+            &version.id,
+            &[
+                warning(SEVERITY_ERROR, "public_sudo"),
+                warning(SEVERITY_WARNING, "no_access"),
+                warning(SEVERITY_WARNING, "no_rule"),
+            ],
+        )
+        .unwrap();
+        let python = super::dependency_type::get_by_name(&mut conn, "python").unwrap();
+        let dep_mod =
+            super::dependency_module::add(&mut conn, &python.id, "requests", &weak.id).unwrap();
+        super::dependency_osv::add(&mut conn, &dep_mod.id, "PYSEC-1", "details", "2.1").unwrap();
+        super::open_pull_request::replace_for_repository(
+            &mut conn,
+            &weak.gh_repository_id,
+            &160u8,
+            &[
+                OpenPullRequestInfo {
+                    number: 1,
+                    ci_status: Some("failure".to_string()),
+                    module_technical_names: vec!["weak".to_string()],
+                    ..Default::default()
+                },
+                OpenPullRequestInfo {
+                    number: 2,
+                    ci_status: Some("success".to_string()),
+                    module_technical_names: vec!["weak".to_string()],
+                    ..Default::default()
+                },
+            ],
+        )
+        .unwrap();
+
+        let health = super::module_health::get_by_module_id(&mut conn, &healthy.id).unwrap();
+        assert_eq!(health.odoo_version, "17.0");
+        assert_eq!(health.score, super::module_health::MAX_SCORE);
+        assert!(health.components.iter().all(|c| c.points == c.max_points));
+
+        let health = super::module_health::get_by_module_id(&mut conn, &weak.id).unwrap();
+        let points: Vec<(&str, i32)> = health
+            .components
+            .iter()
+            .map(|c| (c.key.as_str(), c.points))
+            .collect();
+        assert_eq!(
+            points,
+            vec![
+                ("activity", 0),
+                ("maintainers", 0),
+                ("tests", 0),
+                ("ci", 5),
+                ("security", 8),
+                ("osv", 0),
+                // 16.0 is one version behind the newest collected.
+                ("latest_version", 7),
+            ]
+        );
+        assert_eq!(health.score, 20);
+
+        let all = super::module_health::get_all(&mut conn, Some(&170u8));
+        assert_eq!(all.len(), 2);
+        let unanalyzed = all
+            .values()
+            .find(|h| h.technical_name == "unanalyzed")
+            .unwrap();
+        let tests = unanalyzed
+            .components
+            .iter()
+            .find(|c| c.key == "tests")
+            .unwrap();
+        assert_eq!(tests.points, 0);
+        assert_eq!(tests.detail, "Not analyzed for tests yet");
+
+        // Searches read the scores the collector stored.
+        let unscored = super::module::search_by_criteria(
+            &mut conn,
+            &170u8,
+            None,
+            None,
+            None,
+            None,
+            HealthSort::Health,
+            10,
+        );
+        assert!(unscored.iter().all(|row| row.health.is_none()));
+        assert_eq!(super::module_health::refresh(&mut conn, &170u8).unwrap(), 2);
+        assert_eq!(super::module_health::refresh(&mut conn, &170u8).unwrap(), 2);
+        assert_eq!(super::module_health::get_scores(&mut conn).len(), 2);
+
+        let by_health = super::module::search_by_criteria(
+            &mut conn,
+            &170u8,
+            None,
+            None,
+            None,
+            None,
+            HealthSort::Health,
+            10,
+        );
+        let names: Vec<&str> = by_health
+            .iter()
+            .map(|row| row.technical_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["healthy", "unanalyzed"]);
+        assert_eq!(by_health[0].health, Some(100));
+        let strict = super::module::search_by_criteria(
+            &mut conn,
+            &170u8,
+            None,
+            None,
+            None,
+            Some(90),
+            HealthSort::Relevance,
+            10,
+        );
+        assert_eq!(strict.len(), 1);
+        assert_eq!(strict[0].technical_name, "healthy");
+    }
 }
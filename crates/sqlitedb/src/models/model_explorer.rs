@@ -0,0 +1,146 @@
+// Copyright Alexandre D. Díaz
+//! Model-centric view of the code analysis: for one Odoo model, every
+//! module defining or extending it (as of each module's current version)
+//! with the fields it adds and the public methods it overrides.
+use oghutils::version::odoo_version_u8_to_string;
+use serde::{Deserialize, Serialize};
+
+use super::{module_model, module_model_field, module_model_method};
+use crate::DbConnection;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ExplorerFieldInfo {
+    pub name: String,
+    pub field_type: String,
+    pub relation: Option<String>,
+    pub attrs: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ExplorerMethodInfo {
+    pub name: String,
+    pub decorators: Vec<String>,
+    pub signature: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ExplorerModuleInfo {
+    pub technical_name: String,
+    pub name: String,
+    pub organization: String,
+    pub repository: String,
+    pub class_name: String,
+    pub is_new_model: bool,
+    pub inherit_from: Vec<String>,
+    pub fields: Vec<ExplorerFieldInfo>,
+    pub methods: Vec<ExplorerMethodInfo>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ModelExplorerInfo {
+    pub model_name: String,
+    pub version_odoo: u8,
+    pub modules: Vec<ExplorerModuleInfo>,
+}
+
+/// A field found by name, wherever it is added.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct FieldMatchInfo {
+    pub odoo_version: String,
+    pub model_name: String,
+    pub technical_name: String,
+    pub organization: String,
+    pub repository: String,
+    #[serde(flatten)]
+    pub field: ExplorerFieldInfo,
+}
+
+/// Every module touching `model_name` at `version_odoo`, or `None` when no
+/// collected module does.
+pub fn get(
+    conn: &mut DbConnection,
+    version_odoo: &u8,
+    model_name: &str,
+) -> Option<ModelExplorerInfo> {
+    let usages = module_model::get_current_by_model_name(conn, version_odoo, model_name);
+    if usages.is_empty() {
+        return None;
+    }
+    let modules = usages
+        .into_iter()
+        .map(|usage| {
+            let fields = module_model_field::get_by_module_model_id(conn, &usage.module_model_id)
+                .into_iter()
+                .map(|field| ExplorerFieldInfo {
+                    attrs: field.attrs_value(),
+                    name: field.name,
+                    field_type: field.field_type,
+                    relation: field.relation,
+                })
+                .collect();
+            // Private helpers (`_compute_*`, `_onchange_*`, ...) are mostly
+            // noise here; what other modules care about is the public API.
+            let methods = module_model_method::get_by_module_model_id(conn, &usage.module_model_id)
+                .into_iter()
+                .filter(|method| !method.name.starts_with('_'))
+                .map(|method| ExplorerMethodInfo {
+                    decorators: method.decorators_vec(),
+                    name: method.name,
+                    signature: method.signature,
+                })
+                .collect();
+            ExplorerModuleInfo {
+                inherit_from: usage
+                    .inherit_from
+                    .as_deref()
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+                technical_name: usage.technical_name,
+                name: usage.module_name,
+                organization: usage.organization,
+                repository: usage.repository,
+                class_name: usage.class_name,
+                is_new_model: usage.is_new_model,
+                fields,
+                methods,
+            }
+        })
+        .collect();
+    Some(ModelExplorerInfo {
+        model_name: model_name.to_string(),
+        version_odoo: *version_odoo,
+        modules,
+    })
+}
+
+/// Fields named like `term` in the current version of any module, e.g. to
+/// find who adds `vat_number` anywhere.
+pub fn search_fields(
+    conn: &mut DbConnection,
+    term: &str,
+    version_odoo: Option<&u8>,
+    limit: i64,
+) -> Vec<FieldMatchInfo> {
+    module_model_field::search_current(conn, term, version_odoo, limit)
+        .into_iter()
+        .map(|found| FieldMatchInfo {
+            odoo_version: odoo_version_u8_to_string(&(found.version_odoo as u8)),
+            model_name: found.model_name,
+            technical_name: found.technical_name,
+            organization: found.organization,
+            repository: found.repository,
+            field: ExplorerFieldInfo {
+                attrs: found
+                    .attrs
+                    .and_then(|attrs| serde_json::from_str(&attrs).ok()),
+                name: found.name,
+                field_type: found.field_type,
+                relation: found.relation,
+            },
+        })
+        .collect()
+}
@@ -3,21 +3,25 @@ use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::dialect;
 use crate::schema::module;
 use crate::utils::date::get_sqlite_utc_now;
+use crate::DbConnection;
 
 use super::{
     author, gh_organization, gh_repository, maintainer, module_author,
-    module_code_analysis::ModuleAnalysisInfo, module_committer, module_committer_period,
-    module_maintainer, module_model, module_record, module_version, module_view, system_event,
-    BOT_COMMITTERS,
+    module_code_analysis::ModuleAnalysisInfo,
+    module_committer, module_committer_period,
+    module_health::{self, HealthSort},
+    module_lineage, module_maintainer, module_model, module_ownership, module_record,
+    module_search, module_version, module_view, system_event,
 };
 use oghutils::version::odoo_version_u8_to_string;
 
 use super::system_event::LogUpdateModuleInfo;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = module, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = module, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub technical_name: String,
@@ -43,6 +47,7 @@ pub struct Model {
     pub installation: Option<String>,
     pub usage: Option<String>,
     pub icon: Option<String>,
+    pub summary: Option<String>,
 }
 
 impl Model {
@@ -70,6 +75,9 @@ impl Model {
     pub fn icon_str(&self) -> &str {
         self.icon.as_deref().unwrap_or("")
     }
+    pub fn summary_str(&self) -> &str {
+        self.summary.as_deref().unwrap_or("")
+    }
 }
 
 /// A committer's activity within a module: total commit count plus a
@@ -81,6 +89,9 @@ pub struct CommitterActivity {
     pub insertions: u32,
     pub deletions: u32,
     pub periods: HashMap<(i32, i32), u32>,
+    /// Every email the commits were made with, so `committer::add` can tie
+    /// this identity to a committer already known under another name.
+    pub emails: Vec<String>,
 }
 
 #[derive(Clone, Default)]
@@ -89,6 +100,7 @@ pub struct ManifestInfo {
     pub version_odoo: u8,
     pub name: String,
     pub version_module: String,
+    pub summary: String,
     pub description: String,
     pub installation: String,
     pub usage: String,
@@ -162,6 +174,14 @@ pub struct ModuleCriteriaInfo {
     pub organization: String,
     #[diesel(sql_type = diesel::sql_types::Text)]
     pub repository: String,
+    /// HTML, with the matched words in `<mark>`. Only set when searching by
+    /// term.
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub snippet: Option<String>,
+    /// Health score as of the last collection of this version (see
+    /// `module_health::refresh`).
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Integer>)]
+    pub health: Option<i32>,
 }
 
 #[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
@@ -240,6 +260,8 @@ pub struct ModuleListInfo {
     pub description: Option<String>,
     pub category: Option<String>,
     pub org_name: String,
+    /// Any of its repositories, if it moved between versions.
+    pub repository: String,
     pub versions_odoo: Vec<i32>,
 }
 
@@ -256,6 +278,8 @@ struct ModuleListRow {
     #[diesel(sql_type = diesel::sql_types::Text)]
     org_name: String,
     #[diesel(sql_type = diesel::sql_types::Text)]
+    repository: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
     versions_str: String,
 }
 
@@ -285,9 +309,10 @@ struct NewModule<'a> {
     last_commit_name: &'a str,
     last_commit_date: &'a str,
     last_commit_partof: Option<&'a str>,
+    summary: Option<&'a str>,
 }
 
-pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
+pub fn get_by_id(conn: &mut DbConnection, id: &i64) -> Option<Model> {
     module::table
         .filter(module::id.eq(id))
         .first::<Model>(conn)
@@ -295,15 +320,32 @@ pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
         .expect("DB error in module::get_by_id")
 }
 
-pub fn get_by_odoo_version(conn: &mut SqliteConnection, version_odoo: &u8) -> Vec<Model> {
+pub fn get_by_odoo_version(conn: &mut DbConnection, version_odoo: &u8) -> Vec<Model> {
     module::table
         .filter(module::version_odoo.eq(*version_odoo as i32))
         .load::<Model>(conn)
         .expect("DB error in module::get_by_odoo_version")
 }
 
+/// Modules of the repositories this instance collects itself, leaving out
+/// those imported from another instance's dataset.
+pub fn get_collected(conn: &mut DbConnection) -> Vec<Model> {
+    use crate::schema::gh_repository;
+    module::table
+        .filter(
+            module::gh_repository_id.eq_any(
+                gh_repository::table
+                    .filter(gh_repository::dataset_source_id.is_null())
+                    .select(gh_repository::id),
+            ),
+        )
+        .order((module::gh_repository_id.asc(), module::technical_name.asc()))
+        .load::<Model>(conn)
+        .expect("DB error in module::get_collected")
+}
+
 pub fn get_by_technical_name(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     technical_name: &str,
     version_odoo: &u8,
     gh_repo_id: &i64,
@@ -321,7 +363,7 @@ pub fn get_by_technical_name(
 }
 
 pub fn get_by_technical_name_odoo_version(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     modules: &[String],
     version_odoo: &u8,
 ) -> Vec<Model> {
@@ -336,7 +378,7 @@ pub fn get_by_technical_name_odoo_version(
 }
 
 pub fn get_by_technical_name_odoo_version_organization_name_repository_name(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     technical_name: &str,
     version_odoo: &u8,
     org_name: &str,
@@ -361,7 +403,7 @@ pub fn get_by_technical_name_odoo_version_organization_name_repository_name(
 }
 
 pub fn get_by_technical_name_odoo_version_organization_name(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     technical_name: &str,
     version_odoo: &u8,
     org_name: &str,
@@ -390,7 +432,7 @@ pub fn get_by_technical_name_odoo_version_organization_name(
 /// technical_name substring would miss modules that don't follow the naming
 /// convention (e.g. `delivery_dhl_parcel` living in `OCA/l10n-spain`).
 pub fn get_by_organization_repository_name(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     org_name: &str,
     repo_name: &str,
 ) -> Vec<Model> {
@@ -411,7 +453,7 @@ pub fn get_by_organization_repository_name(
 }
 
 pub fn get_by_technical_name_organization_name(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     technical_name: &str,
     org_name: &str,
 ) -> Vec<Model> {
@@ -432,7 +474,7 @@ pub fn get_by_technical_name_organization_name(
 }
 
 pub fn get_by_technical_name_odoo_version_repository_name(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     technical_name: &str,
     version_odoo: &u8,
     repo_name: &str,
@@ -451,60 +493,84 @@ pub fn get_by_technical_name_odoo_version_repository_name(
         .expect("DB error in module::get_by_technical_name_odoo_version_repository_name")
 }
 
-pub fn get_generic_info(
-    conn: &mut SqliteConnection,
-    technical_name: &str,
-) -> Vec<ModuleGenericInfo> {
-    diesel::sql_query(
-        "SELECT mod.technical_name, GROUP_CONCAT(mod.version_odoo, ',') as versions, \
+pub fn get_generic_info(conn: &mut DbConnection, technical_name: &str) -> Vec<ModuleGenericInfo> {
+    let Some(query) = module_search::match_query(technical_name, &["technical_name"]) else {
+        return Vec::new();
+    };
+    dialect::sql_query(format!(
+        "SELECT mod.technical_name, {versions} as versions, \
          gh_org.name || '/' || gh_repo.name as src \
-         FROM module as mod \
+         FROM module_search \
+         INNER JOIN module as mod ON mod.id = {MODULE_ID} \
          INNER JOIN gh_repository as gh_repo ON gh_repo.id = mod.gh_repository_id \
          INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id \
-         WHERE mod.technical_name LIKE ? \
-         GROUP BY mod.technical_name, src",
-    )
-    .bind::<diesel::sql_types::Text, _>(format!("%{technical_name}%"))
+         WHERE {matches} \
+         GROUP BY mod.technical_name, src \
+         ORDER BY MIN({rank}), mod.technical_name",
+        versions = dialect::group_concat("mod.version_odoo", ","),
+        MODULE_ID = module_search::MODULE_ID,
+        matches = module_search::matches(1),
+        rank = module_search::plain_rank(1),
+    ))
+    .bind::<diesel::sql_types::Text, _>(query)
     .load::<ModuleGenericInfo>(conn)
     .expect("DB error in module::get_generic_info")
 }
 
 pub fn get_generic_info_by_odoo_version(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     technical_name: &str,
     version_odoo: &u8,
 ) -> Vec<ModuleGenericInfo> {
-    diesel::sql_query(
-        "SELECT mod.technical_name, GROUP_CONCAT(mod.version_odoo, ',') as versions, \
+    let Some(query) = module_search::match_query(technical_name, &["technical_name"]) else {
+        return Vec::new();
+    };
+    dialect::sql_query(format!(
+        "SELECT mod.technical_name, {versions} as versions, \
          gh_org.name || '/' || gh_repo.name as src \
-         FROM module as mod \
+         FROM module_search \
+         INNER JOIN module as mod ON mod.id = {MODULE_ID} \
          INNER JOIN gh_repository as gh_repo ON gh_repo.id = mod.gh_repository_id \
          INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id \
-         WHERE mod.technical_name LIKE ? AND mod.version_odoo = ? \
-         GROUP BY mod.technical_name, src",
-    )
-    .bind::<diesel::sql_types::Text, _>(format!("%{technical_name}%"))
+         WHERE {matches} AND mod.version_odoo = ?2 \
+         GROUP BY mod.technical_name, src \
+         ORDER BY MIN({rank}), mod.technical_name",
+        versions = dialect::group_concat("mod.version_odoo", ","),
+        MODULE_ID = module_search::MODULE_ID,
+        matches = module_search::matches(1),
+        rank = module_search::plain_rank(1),
+    ))
+    .bind::<diesel::sql_types::Text, _>(query)
     .bind::<diesel::sql_types::Integer, _>(*version_odoo as i32)
     .load::<ModuleGenericInfo>(conn)
     .expect("DB error in module::get_generic_info_by_odoo_version")
 }
 
 pub fn get_generic_info_by_odoo_version_installable(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     technical_name: &str,
     version_odoo: &u8,
     installable: &bool,
 ) -> Vec<ModuleGenericInfo> {
-    diesel::sql_query(
-        "SELECT mod.technical_name, GROUP_CONCAT(mod.version_odoo, ',') as versions, \
+    let Some(query) = module_search::match_query(technical_name, &["technical_name"]) else {
+        return Vec::new();
+    };
+    dialect::sql_query(format!(
+        "SELECT mod.technical_name, {versions} as versions, \
          gh_org.name || '/' || gh_repo.name as src \
-         FROM module as mod \
+         FROM module_search \
+         INNER JOIN module as mod ON mod.id = {MODULE_ID} \
          INNER JOIN gh_repository as gh_repo ON gh_repo.id = mod.gh_repository_id \
          INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id \
-         WHERE mod.technical_name LIKE ? AND mod.version_odoo = ? AND mod.installable = ? \
-         GROUP BY mod.technical_name, src",
-    )
-    .bind::<diesel::sql_types::Text, _>(format!("%{technical_name}%"))
+         WHERE {matches} AND mod.version_odoo = ?2 AND mod.installable = ?3 \
+         GROUP BY mod.technical_name, src \
+         ORDER BY MIN({rank}), mod.technical_name",
+        versions = dialect::group_concat("mod.version_odoo", ","),
+        MODULE_ID = module_search::MODULE_ID,
+        matches = module_search::matches(1),
+        rank = module_search::plain_rank(1),
+    ))
+    .bind::<diesel::sql_types::Text, _>(query)
     .bind::<diesel::sql_types::Integer, _>(*version_odoo as i32)
     .bind::<diesel::sql_types::Bool, _>(*installable)
     .load::<ModuleGenericInfo>(conn)
@@ -512,20 +578,29 @@ pub fn get_generic_info_by_odoo_version_installable(
 }
 
 pub fn get_generic_info_by_installable(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     technical_name: &str,
     installable: &bool,
 ) -> Vec<ModuleGenericInfo> {
-    diesel::sql_query(
-        "SELECT mod.technical_name, GROUP_CONCAT(mod.version_odoo, ',') as versions, \
+    let Some(query) = module_search::match_query(technical_name, &["technical_name"]) else {
+        return Vec::new();
+    };
+    dialect::sql_query(format!(
+        "SELECT mod.technical_name, {versions} as versions, \
          gh_org.name || '/' || gh_repo.name as src \
-         FROM module as mod \
+         FROM module_search \
+         INNER JOIN module as mod ON mod.id = {MODULE_ID} \
          INNER JOIN gh_repository as gh_repo ON gh_repo.id = mod.gh_repository_id \
          INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id \
-         WHERE mod.technical_name LIKE ? AND mod.installable = ? \
-         GROUP BY mod.technical_name, src",
-    )
-    .bind::<diesel::sql_types::Text, _>(format!("%{technical_name}%"))
+         WHERE {matches} AND mod.installable = ?2 \
+         GROUP BY mod.technical_name, src \
+         ORDER BY MIN({rank}), mod.technical_name",
+        versions = dialect::group_concat("mod.version_odoo", ","),
+        MODULE_ID = module_search::MODULE_ID,
+        matches = module_search::matches(1),
+        rank = module_search::plain_rank(1),
+    ))
+    .bind::<diesel::sql_types::Text, _>(query)
     .bind::<diesel::sql_types::Bool, _>(*installable)
     .load::<ModuleGenericInfo>(conn)
     .expect("DB error in module::get_generic_info_by_installable")
@@ -537,50 +612,93 @@ pub fn get_generic_info_by_installable(
 /// repository first). All filters are optional and combined with the
 /// `(? IS NULL OR ...)` idiom so this stays one query instead of the
 /// combinatorial set of hand-written variants used by get_generic_info*.
+/// A search term goes through the `module_search` full-text index: rows come
+/// best match first, with a highlighted snippet of what matched. Rows scoring
+/// below `min_health` are left out, and `HealthSort::Health` puts the
+/// healthiest first (equal scores keep the relevance order).
 #[allow(clippy::too_many_arguments)]
 pub fn search_by_criteria(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     version_odoo: &u8,
     search_term: Option<&str>,
     category: Option<&str>,
     depends_on: Option<&str>,
+    min_health: Option<i32>,
+    sort: HealthSort,
     limit: i64,
 ) -> Vec<ModuleCriteriaInfo> {
-    let search_pattern = search_term.map(|s| format!("%{s}%"));
-    diesel::sql_query(
+    let search_query = match search_term {
+        Some(term) => match module_search::match_query(term, &[]) {
+            Some(query) => Some(query),
+            None => return Vec::new(),
+        },
+        None => None,
+    };
+    // The index is only joined when searching: MATCH can't be skipped with
+    // the `? IS NULL` idiom like the other filters.
+    let (snippet, search_join, search_filter, order) = if search_query.is_some() {
+        (
+            module_search::snippet(2),
+            format!(
+                "INNER JOIN module_search ON {} = mod.id",
+                module_search::MODULE_ID
+            ),
+            module_search::matches(2),
+            format!("{}, mod.technical_name", module_search::rank(2)),
+        )
+    } else {
+        (
+            "NULL".to_string(),
+            String::new(),
+            "?2 IS NULL".to_string(),
+            "mod.technical_name".to_string(),
+        )
+    };
+    let order = match sort {
+        HealthSort::Relevance => order,
+        // Unscored rows last on both backends (NULLs sort first on PostgreSQL).
+        HealthSort::Health => format!("COALESCE(mhs.score, -1) DESC, {order}"),
+    };
+    dialect::sql_query(format!(
         "SELECT mod.technical_name, mod.name, mod.category, mod.installable, mod.application, \
-         gh_org.name as organization, gh_repo.name as repository \
+         gh_org.name as organization, gh_repo.name as repository, {snippet} as snippet, \
+         mhs.score as health \
          FROM module as mod \
+         {search_join} \
          INNER JOIN gh_repository as gh_repo ON gh_repo.id = mod.gh_repository_id \
          INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id \
-         WHERE mod.version_odoo = ? \
-           AND (? IS NULL OR mod.technical_name LIKE ? OR mod.name LIKE ? OR mod.description LIKE ?) \
-           AND (? IS NULL OR mod.category = ?) \
-           AND (? IS NULL OR mod.id IN ( \
+         LEFT JOIN module_health_score as mhs ON mhs.module_id = mod.id \
+         WHERE mod.version_odoo = ?1 \
+           AND {search_filter} \
+           AND (?3 IS NULL OR mod.category = ?3) \
+           AND (?4 IS NULL OR mod.id IN ( \
                  SELECT dep_mod.module_id FROM dependency_module as dep_mod \
                  INNER JOIN dependency as dep ON dep.id = dep_mod.dependency_id \
                  INNER JOIN dependency_type as dt ON dt.id = dep.dependency_type_id \
-                 WHERE dt.name = 'module' AND dep.name = ? \
+                 WHERE dt.name = 'module' AND dep.name = ?4 \
                )) \
-         ORDER BY mod.technical_name \
-         LIMIT ?",
-    )
+           AND (?6 IS NULL OR mhs.score >= ?6) \
+         ORDER BY {order} \
+         LIMIT ?5"
+    ))
     .bind::<diesel::sql_types::Integer, _>(*version_odoo as i32)
-    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(search_pattern.clone())
-    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(search_pattern.clone())
-    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(search_pattern.clone())
-    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(search_pattern)
-    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(category)
+    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(search_query)
     .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(category)
     .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(depends_on)
-    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(depends_on)
     .bind::<diesel::sql_types::BigInt, _>(limit)
+    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Integer>, _>(min_health)
     .load::<ModuleCriteriaInfo>(conn)
     .expect("DB error in module::search_by_criteria")
+    .into_iter()
+    .map(|row| ModuleCriteriaInfo {
+        snippet: row.snippet.as_deref().map(module_search::highlight),
+        ..row
+    })
+    .collect()
 }
 
-pub fn get_info(conn: &mut SqliteConnection, technical_name: &str) -> Vec<ModuleInfo> {
-    diesel::sql_query(
+pub fn get_info(conn: &mut DbConnection, technical_name: &str) -> Vec<ModuleInfo> {
+    dialect::sql_query(
         "SELECT mod.technical_name, mod.name, mod.version_odoo, gh_org.name as organization, \
          gh_rep.name as repository \
          FROM module as mod \
@@ -593,14 +711,14 @@ pub fn get_info(conn: &mut SqliteConnection, technical_name: &str) -> Vec<Module
     .expect("DB error in module::get_info")
 }
 
-pub fn count(conn: &mut SqliteConnection) -> Vec<ModuleCountInfo> {
-    diesel::sql_query("SELECT version_odoo, count(*) as count FROM module GROUP BY version_odoo")
+pub fn count(conn: &mut DbConnection) -> Vec<ModuleCountInfo> {
+    dialect::sql_query("SELECT version_odoo, count(*) as count FROM module GROUP BY version_odoo")
         .load::<ModuleCountInfo>(conn)
         .expect("DB error in module::count")
 }
 
-pub fn count_distinct(conn: &mut SqliteConnection) -> i64 {
-    diesel::sql_query("SELECT count(DISTINCT technical_name) as count FROM module")
+pub fn count_distinct(conn: &mut DbConnection) -> i64 {
+    dialect::sql_query("SELECT count(DISTINCT technical_name) as count FROM module")
         .load::<ModuleDistinctCountInfo>(conn)
         .expect("DB error in module::count_distinct")
         .first()
@@ -608,8 +726,8 @@ pub fn count_distinct(conn: &mut SqliteConnection) -> i64 {
         .unwrap_or(0)
 }
 
-pub fn count_organization(conn: &mut SqliteConnection) -> Vec<ModuleCountByOrganizationInfo> {
-    diesel::sql_query(
+pub fn count_organization(conn: &mut DbConnection) -> Vec<ModuleCountByOrganizationInfo> {
+    dialect::sql_query(
         "SELECT mod.version_odoo, count(*) as count, org.name as org_name \
          FROM module as mod \
          INNER JOIN gh_repository as repo ON mod.gh_repository_id = repo.id \
@@ -621,8 +739,8 @@ pub fn count_organization(conn: &mut SqliteConnection) -> Vec<ModuleCountByOrgan
     .expect("DB error in module::count_organization")
 }
 
-pub fn rank_contributor(conn: &mut SqliteConnection) -> Vec<ModuleRankContributorInfo> {
-    diesel::sql_query(
+pub fn rank_contributor(conn: &mut DbConnection) -> Vec<ModuleRankContributorInfo> {
+    dialect::sql_query(
         "SELECT * FROM (\
            SELECT mod.version_odoo, count(*) as count, au.name as contrib_name, \
                   RANK() OVER (PARTITION BY mod.version_odoo ORDER BY count(*) DESC) AS rank \
@@ -633,24 +751,24 @@ pub fn rank_contributor(conn: &mut SqliteConnection) -> Vec<ModuleRankContributo
                  AND au.name NOT LIKE 'Odoo %' \
            GROUP BY au.id, mod.version_odoo \
            ORDER BY count DESC \
-         ) WHERE rank <= 5 ORDER BY rank ASC",
+         ) as ranked WHERE rank <= 5 ORDER BY rank ASC",
     )
     .load::<ModuleRankContributorInfo>(conn)
     .expect("DB error in module::rank_contributor")
 }
 
-pub fn rank_committer(conn: &mut SqliteConnection) -> Vec<ModuleRankCommitterInfo> {
-    diesel::sql_query(format!(
+pub fn rank_committer(conn: &mut DbConnection) -> Vec<ModuleRankCommitterInfo> {
+    dialect::sql_query(
         "SELECT * FROM (\
            SELECT mod.version_odoo, SUM(mod_com.commits) as count, com.name as committer_name, \
                   RANK() OVER (PARTITION BY mod.version_odoo ORDER BY SUM(mod_com.commits) DESC) AS rank \
            FROM module as mod \
            INNER JOIN module_committer as mod_com ON mod.id = mod_com.module_id \
            INNER JOIN committer as com ON mod_com.committer_id = com.id \
-           WHERE com.name NOT IN ({BOT_COMMITTERS}) \
+           WHERE NOT com.is_bot \
            GROUP BY com.id, mod.version_odoo \
-         ) WHERE rank <= 5 ORDER BY rank ASC"
-    ))
+         ) as ranked WHERE rank <= 5 ORDER BY rank ASC",
+    )
     .load::<ModuleRankCommitterInfo>(conn)
     .expect("DB error in module::rank_committer")
 }
@@ -667,15 +785,15 @@ pub struct ModuleFunFactInfo {
 
 /// The single module (one technical_name/repo row, at the given Odoo
 /// version) with the most commits recorded across its committers.
-pub fn most_changed(conn: &mut SqliteConnection, version_odoo: &u8) -> Option<ModuleFunFactInfo> {
-    diesel::sql_query(
+pub fn most_changed(conn: &mut DbConnection, version_odoo: &u8) -> Option<ModuleFunFactInfo> {
+    dialect::sql_query(
         "SELECT mod.technical_name, gh_org.name as organization, SUM(mod_com.commits) as value \
          FROM module_committer as mod_com \
          INNER JOIN module as mod ON mod_com.module_id = mod.id \
          INNER JOIN gh_repository as gh_repo ON mod.gh_repository_id = gh_repo.id \
          INNER JOIN gh_organization as gh_org ON gh_repo.gh_organization_id = gh_org.id \
          WHERE mod.version_odoo = ? \
-         GROUP BY mod.id \
+         GROUP BY mod.id, gh_org.id \
          ORDER BY value DESC LIMIT 1",
     )
     .bind::<diesel::sql_types::Integer, _>(*version_odoo as i32)
@@ -686,8 +804,8 @@ pub fn most_changed(conn: &mut SqliteConnection, version_odoo: &u8) -> Option<Mo
 }
 
 /// The single module with the largest folder size, at the given Odoo version.
-pub fn largest_module(conn: &mut SqliteConnection, version_odoo: &u8) -> Option<ModuleFunFactInfo> {
-    diesel::sql_query(
+pub fn largest_module(conn: &mut DbConnection, version_odoo: &u8) -> Option<ModuleFunFactInfo> {
+    dialect::sql_query(
         "SELECT mod.technical_name, gh_org.name as organization, mod.folder_size as value \
          FROM module as mod \
          INNER JOIN gh_repository as gh_repo ON mod.gh_repository_id = gh_repo.id \
@@ -703,11 +821,8 @@ pub fn largest_module(conn: &mut SqliteConnection, version_odoo: &u8) -> Option<
 }
 
 /// The most recently added module, at the given Odoo version.
-pub fn newest_module(
-    conn: &mut SqliteConnection,
-    version_odoo: &u8,
-) -> Option<ModuleLastCreatedInfo> {
-    diesel::sql_query(
+pub fn newest_module(conn: &mut DbConnection, version_odoo: &u8) -> Option<ModuleLastCreatedInfo> {
+    dialect::sql_query(
         "SELECT mod.id, mod.version_odoo, mod.technical_name, mod.create_date, \
          gh_org.name as org_name \
          FROM module as mod \
@@ -723,9 +838,10 @@ pub fn newest_module(
     .next()
 }
 
-pub fn get_latest_modules_created(conn: &mut SqliteConnection) -> Vec<ModuleLastCreatedInfo> {
-    diesel::sql_query(
-        "SELECT mod.id, mod.version_odoo, mod.technical_name, date(mod.create_date) as create_date, \
+pub fn get_latest_modules_created(conn: &mut DbConnection) -> Vec<ModuleLastCreatedInfo> {
+    dialect::sql_query(
+        "SELECT mod.id, mod.version_odoo, mod.technical_name, \
+         SUBSTR(mod.create_date, 1, 10) as create_date, \
          gh_org.name as org_name \
          FROM module as mod \
          INNER JOIN gh_repository AS gh_repo ON mod.gh_repository_id = gh_repo.id \
@@ -736,17 +852,18 @@ pub fn get_latest_modules_created(conn: &mut SqliteConnection) -> Vec<ModuleLast
     .expect("DB error in module::get_latest_modules_created")
 }
 
-pub fn list(conn: &mut SqliteConnection) -> Vec<ModuleListInfo> {
-    diesel::sql_query(
+pub fn list(conn: &mut DbConnection) -> Vec<ModuleListInfo> {
+    dialect::sql_query(format!(
         "SELECT mod.technical_name, MAX(mod.name) as name, MAX(mod.description) as description, \
-         MAX(mod.category) as category, gh_org.name as org_name, \
-         GROUP_CONCAT(mod.version_odoo) as versions_str \
+         MAX(mod.category) as category, gh_org.name as org_name, MAX(gh_repo.name) as repository, \
+         {versions} as versions_str \
          FROM module as mod \
          INNER JOIN gh_repository AS gh_repo ON mod.gh_repository_id = gh_repo.id \
          INNER JOIN gh_organization as gh_org ON gh_repo.gh_organization_id = gh_org.id \
          GROUP BY gh_org.name, mod.technical_name \
          ORDER BY mod.technical_name, gh_org.name",
-    )
+        versions = dialect::group_concat("mod.version_odoo", ","),
+    ))
     .load::<ModuleListRow>(conn)
     .expect("DB error in module::list")
     .into_iter()
@@ -756,6 +873,7 @@ pub fn list(conn: &mut SqliteConnection) -> Vec<ModuleListInfo> {
         description: row.description,
         category: row.category,
         org_name: row.org_name,
+        repository: row.repository,
         versions_odoo: row
             .versions_str
             .split(',')
@@ -769,11 +887,7 @@ pub fn list(conn: &mut SqliteConnection) -> Vec<ModuleListInfo> {
 // first - icons are practically never version-specific, same as name/
 // description in `list` above). Empty string means "no icon file", stored
 // that way rather than NULL, so it's filtered out like NULL.
-pub fn get_icon(
-    conn: &mut SqliteConnection,
-    org_name: &str,
-    technical_name: &str,
-) -> Option<String> {
+pub fn get_icon(conn: &mut DbConnection, org_name: &str, technical_name: &str) -> Option<String> {
     use crate::schema::{gh_organization, gh_repository};
     module::table
         .inner_join(gh_repository::table.on(gh_repository::id.eq(module::gh_repository_id)))
@@ -794,7 +908,7 @@ pub fn get_icon(
         .flatten()
 }
 
-pub fn get_odoo_versions(conn: &mut SqliteConnection) -> Vec<i32> {
+pub fn get_odoo_versions(conn: &mut DbConnection) -> Vec<i32> {
     module::table
         .select(module::version_odoo)
         .distinct()
@@ -804,7 +918,7 @@ pub fn get_odoo_versions(conn: &mut SqliteConnection) -> Vec<i32> {
 }
 
 pub fn get_module_repository(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     version_odoo: &u8,
     modules: &[String],
 ) -> Vec<ModuleRepositoryInfo> {
@@ -850,7 +964,7 @@ pub fn get_module_repository(
 /// simply never tracked. Prefers the most recent version's repo/org when a
 /// technical_name moved between repos across versions.
 pub fn get_repository_org_by_technical_names(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     modules: &[String],
 ) -> Vec<ModuleRepositoryInfo> {
     use crate::schema::{gh_organization, gh_repository};
@@ -890,8 +1004,10 @@ pub fn get_repository_org_by_technical_names(
 /// module_version rows for these modules (and their module_view/module_model/
 /// module_record snapshots) are deleted by hand first, otherwise they'd be
 /// left orphaned forever instead of just for one run's worth of stale data.
+/// A module that vanished because it now lives in another repository of the
+/// same organization gets a `module_lineage` move recorded first.
 pub fn delete_outdated(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     gh_repo_id: &i64,
     version_odoo: &u8,
     module_ids: &[i64],
@@ -908,21 +1024,69 @@ pub fn delete_outdated(
         )
         .select(module::id)
         .load(conn)?;
+    delete_stale(conn, &stale_ids)
+}
+
+/// Drops the repo's modules of every version missing from `module_ids`: used
+/// when the repo is mirrored from an imported dataset, where a module (or a
+/// whole version) the source no longer exports is gone there. Unlike
+/// `delete_outdated`, an empty list empties the repo.
+pub fn delete_missing(
+    conn: &mut DbConnection,
+    gh_repo_id: &i64,
+    module_ids: &[i64],
+) -> QueryResult<usize> {
+    let stale_ids: Vec<i64> = module::table
+        .filter(
+            module::gh_repository_id
+                .eq(gh_repo_id)
+                .and(module::id.ne_all(module_ids)),
+        )
+        .select(module::id)
+        .load(conn)?;
+    delete_stale(conn, &stale_ids)
+}
 
-    for stale_id in &stale_ids {
+fn delete_stale(conn: &mut DbConnection, stale_ids: &[i64]) -> QueryResult<usize> {
+    for stale_id in stale_ids {
+        module_lineage::register_move_from_stale(conn, stale_id)?;
         module_model::delete_by_module_id(conn, stale_id)?;
         module_view::delete_by_module_id(conn, stale_id)?;
         module_record::delete_by_module_id(conn, stale_id)?;
+        module_ownership::delete_by_module_id(conn, stale_id)?;
         module_version::delete_by_module_id(conn, stale_id)?;
+        module_search::delete_by_module_id(conn, stale_id)?;
+        module_health::delete_by_module_id(conn, stale_id)?;
     }
 
-    diesel::delete(module::table.filter(module::id.eq_any(&stale_ids))).execute(conn)
+    diesel::delete(module::table.filter(module::id.eq_any(stale_ids))).execute(conn)
 }
 
-pub fn add(conn: &mut SqliteConnection, module_info: &ManifestInfo) -> QueryResult<Model> {
+/// Overrides the dates `add` stamps on a module, for data that was first
+/// seen somewhere else (an imported dataset keeps its source's dates).
+pub fn set_dates(
+    conn: &mut DbConnection,
+    id: &i64,
+    create_date: &str,
+    update_date: &str,
+) -> QueryResult<usize> {
+    diesel::update(module::table.filter(module::id.eq(id)))
+        .set((
+            module::create_date.eq(create_date),
+            module::update_date.eq(update_date),
+        ))
+        .execute(conn)
+}
+
+pub fn add(conn: &mut DbConnection, module_info: &ManifestInfo) -> QueryResult<Model> {
     let gh_org = gh_organization::add(conn, module_info.git_org.as_str())?;
     let gh_repo = gh_repository::add(conn, &gh_org.id, module_info.git_repo.as_str())?;
 
+    let summary = if module_info.summary.is_empty() {
+        None
+    } else {
+        Some(module_info.summary.as_str())
+    };
     let description = if module_info.description.is_empty() {
         None
     } else {
@@ -998,9 +1162,10 @@ pub fn add(conn: &mut SqliteConnection, module_info: &ManifestInfo) -> QueryResu
                 last_commit_name: &module_info.last_commit_name,
                 last_commit_date: &module_info.last_commit_date,
                 last_commit_partof,
+                summary,
             })
             .execute(conn)?;
-        let new_id = crate::models::last_insert_rowid(conn);
+        let new_id = crate::models::last_insert_id(conn);
         let new_module = Model {
             id: new_id,
             technical_name: module_info.technical_name.clone(),
@@ -1026,6 +1191,7 @@ pub fn add(conn: &mut SqliteConnection, module_info: &ManifestInfo) -> QueryResu
             last_commit_name: module_info.last_commit_name.clone(),
             last_commit_date: module_info.last_commit_date.clone(),
             last_commit_partof: last_commit_partof.map(|s| s.to_string()),
+            summary: summary.map(|s| s.to_string()),
         };
 
         for item in module_info
@@ -1164,6 +1330,13 @@ pub fn add(conn: &mut SqliteConnection, module_info: &ManifestInfo) -> QueryResu
             &module_info.version_module,
         ));
     }
+    if existing_module.summary_str() != module_info.summary {
+        changes.push((
+            "Summary",
+            existing_module.summary_str(),
+            &module_info.summary,
+        ));
+    }
     let existing_desc = existing_module.description_str().to_string();
     if existing_desc != module_info.description {
         changes.push(("Description", &existing_desc, &module_info.description));
@@ -1273,6 +1446,7 @@ pub fn add(conn: &mut SqliteConnection, module_info: &ManifestInfo) -> QueryResu
             module::last_commit_name.eq(commit_name),
             module::last_commit_date.eq(commit_date),
             module::last_commit_partof.eq(commit_partof),
+            module::summary.eq(summary),
         ))
         .execute(conn)?;
 
@@ -2,13 +2,15 @@
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::dialect;
 use crate::schema::module_author;
+use crate::DbConnection;
 
 use super::{author, module, system_event};
 use oghutils::version::odoo_version_u8_to_string;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = module_author, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = module_author, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub module_id: i64,
@@ -30,7 +32,7 @@ struct NewModuleAuthor {
     author_id: i64,
 }
 
-pub fn get_by_id(conn: &mut SqliteConnection, module_id: &i64, author_id: &i64) -> Option<Model> {
+pub fn get_by_id(conn: &mut DbConnection, module_id: &i64, author_id: &i64) -> Option<Model> {
     module_author::table
         .filter(
             module_author::module_id
@@ -42,7 +44,7 @@ pub fn get_by_id(conn: &mut SqliteConnection, module_id: &i64, author_id: &i64)
         .expect("DB error in module_author::get_by_id")
 }
 
-pub fn get_by_name(conn: &mut SqliteConnection, module_id: &i64, name: &str) -> Option<Model> {
+pub fn get_by_name(conn: &mut DbConnection, module_id: &i64, name: &str) -> Option<Model> {
     if let Some(author) = author::get_by_name(conn, name) {
         module_author::table
             .filter(
@@ -58,22 +60,22 @@ pub fn get_by_name(conn: &mut SqliteConnection, module_id: &i64, name: &str) ->
     }
 }
 
-pub fn get_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Vec<Model> {
+pub fn get_by_module_id(conn: &mut DbConnection, module_id: &i64) -> Vec<Model> {
     module_author::table
         .filter(module_author::module_id.eq(module_id))
         .load::<Model>(conn)
         .expect("DB error in module_author::get_by_module_id")
 }
 
-pub fn get_names_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Vec<String> {
+pub fn get_names_by_module_id(conn: &mut DbConnection, module_id: &i64) -> Vec<String> {
     get_by_module_id(conn, module_id)
         .into_iter()
         .filter_map(|ma| author::get_by_id(conn, &ma.author_id).map(|a| a.name))
         .collect()
 }
 
-pub fn get_top_names(conn: &mut SqliteConnection, limit: &u8) -> Vec<TopAuthorJSON> {
-    diesel::sql_query(
+pub fn get_top_names(conn: &mut DbConnection, limit: &u8) -> Vec<TopAuthorJSON> {
+    dialect::sql_query(
         "SELECT author_id, count(*) as count FROM module_author GROUP BY author_id ORDER BY count DESC LIMIT ?",
     )
     .bind::<diesel::sql_types::Integer, _>(*limit as i32)
@@ -81,7 +83,7 @@ pub fn get_top_names(conn: &mut SqliteConnection, limit: &u8) -> Vec<TopAuthorJS
     .expect("DB error in module_author::get_top_names")
 }
 
-pub fn add(conn: &mut SqliteConnection, module_id: &i64, name: &str) -> QueryResult<Model> {
+pub fn add(conn: &mut DbConnection, module_id: &i64, name: &str) -> QueryResult<Model> {
     let author_rec = author::add(conn, name)?;
     if let Some(existing) = get_by_id(conn, module_id, &author_rec.id) {
         return Ok(existing);
@@ -93,7 +95,7 @@ pub fn add(conn: &mut SqliteConnection, module_id: &i64, name: &str) -> QueryRes
             author_id: author_rec.id,
         })
         .execute(conn)?;
-    let new_id = crate::models::last_insert_rowid(conn);
+    let new_id = crate::models::last_insert_id(conn);
     let mod_info = module::get_by_id(conn, module_id).unwrap();
     let _ = system_event::register_new_module_author(
         conn,
@@ -109,13 +111,13 @@ pub fn add(conn: &mut SqliteConnection, module_id: &i64, name: &str) -> QueryRes
     })
 }
 
-pub fn delete_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> QueryResult<usize> {
+pub fn delete_by_module_id(conn: &mut DbConnection, module_id: &i64) -> QueryResult<usize> {
     diesel::delete(module_author::table.filter(module_author::module_id.eq(module_id)))
         .execute(conn)
 }
 
 pub fn delete_by_module_id_author_id(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_id: &i64,
     author_id: &i64,
 ) -> QueryResult<usize> {
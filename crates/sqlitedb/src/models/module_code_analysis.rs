@@ -103,6 +103,16 @@ pub struct ControllerAnalysisInfo {
     pub docstring: Option<String>,
 }
 
+// Surviving lines of the module's tree last touched by one identity (git
+// blame, after .mailmap resolution). Filled in by the collector itself, not
+// the Python script, hence the serde default.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct OwnershipAnalysisInfo {
+    pub name: String,
+    pub emails: Vec<String>,
+    pub lines: u32,
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct ModuleAnalysisInfo {
     pub views: Vec<ViewAnalysisInfo>,
@@ -111,4 +121,10 @@ pub struct ModuleAnalysisInfo {
     pub records: Vec<RecordAnalysisInfo>,
     #[serde(default)]
     pub controllers: Vec<ControllerAnalysisInfo>,
+    #[serde(default)]
+    pub ownership: Vec<OwnershipAnalysisInfo>,
+    /// `test_*.py` files in the module's `tests/` folder, counted by the
+    /// collector; `None` when it wasn't looked at.
+    #[serde(default)]
+    pub test_files: Option<usize>,
 }
@@ -2,14 +2,16 @@
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::dialect;
 use crate::schema::module_committer;
+use crate::DbConnection;
 
 use super::module::CommitterActivity;
 use super::{committer, module, system_event};
 use oghutils::version::odoo_version_u8_to_string;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = module_committer, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = module_committer, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub module_id: i64,
@@ -29,11 +31,7 @@ struct NewModuleCommitter {
     deletions: i32,
 }
 
-pub fn get_by_id(
-    conn: &mut SqliteConnection,
-    module_id: &i64,
-    committer_id: &i64,
-) -> Option<Model> {
+pub fn get_by_id(conn: &mut DbConnection, module_id: &i64, committer_id: &i64) -> Option<Model> {
     module_committer::table
         .filter(
             module_committer::module_id
@@ -45,7 +43,7 @@ pub fn get_by_id(
         .expect("DB error in module_committer::get_by_id")
 }
 
-pub fn get_by_name(conn: &mut SqliteConnection, module_id: &i64, name: &str) -> Option<Model> {
+pub fn get_by_name(conn: &mut DbConnection, module_id: &i64, name: &str) -> Option<Model> {
     if let Some(com) = committer::get_by_name(conn, name) {
         module_committer::table
             .filter(
@@ -61,14 +59,14 @@ pub fn get_by_name(conn: &mut SqliteConnection, module_id: &i64, name: &str) ->
     }
 }
 
-pub fn get_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Vec<Model> {
+pub fn get_by_module_id(conn: &mut DbConnection, module_id: &i64) -> Vec<Model> {
     module_committer::table
         .filter(module_committer::module_id.eq(module_id))
         .load::<Model>(conn)
         .expect("DB error in module_committer::get_by_module_id")
 }
 
-pub fn get_names_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Vec<String> {
+pub fn get_names_by_module_id(conn: &mut DbConnection, module_id: &i64) -> Vec<String> {
     get_by_module_id(conn, module_id)
         .into_iter()
         .filter_map(|mc| committer::get_by_id(conn, &mc.committer_id).map(|c| c.name))
@@ -96,10 +94,10 @@ pub struct CommitterModuleActivity {
 }
 
 pub fn get_activity_by_committer_name(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     committer_name: &str,
 ) -> Vec<CommitterModuleActivity> {
-    diesel::sql_query(
+    dialect::sql_query(
         "SELECT mod.technical_name, mod.name, mod.version_odoo, \
          gh_org.name as organization, gh_repo.name as repository, mod_com.commits as commits, \
          mod_com.insertions as insertions, mod_com.deletions as deletions \
@@ -116,13 +114,47 @@ pub fn get_activity_by_committer_name(
     .expect("DB error in module_committer::get_activity_by_committer_name")
 }
 
+#[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
+pub struct RepositoryCommitterInfo {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub name: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub total_commits: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub modules_touched: i64,
+}
+
+/// Who committed most to the modules of one repository, over every Odoo
+/// version, bots left out (same sum as `committer::rank_global`).
+pub fn rank_by_repository(
+    conn: &mut DbConnection,
+    gh_repository_id: &i64,
+    limit: i64,
+) -> Vec<RepositoryCommitterInfo> {
+    dialect::sql_query(
+        "SELECT com.name as name, SUM(mod_com.commits) as total_commits, \
+         COUNT(DISTINCT mod.technical_name) as modules_touched \
+         FROM module_committer as mod_com \
+         INNER JOIN committer as com ON mod_com.committer_id = com.id \
+         INNER JOIN module as mod ON mod_com.module_id = mod.id \
+         WHERE mod.gh_repository_id = ? AND NOT com.is_bot \
+         GROUP BY com.id, com.name \
+         ORDER BY total_commits DESC, com.name \
+         LIMIT ?",
+    )
+    .bind::<diesel::sql_types::BigInt, _>(gh_repository_id)
+    .bind::<diesel::sql_types::BigInt, _>(limit)
+    .load::<RepositoryCommitterInfo>(conn)
+    .expect("DB error in module_committer::rank_by_repository")
+}
+
 pub fn add(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_id: &i64,
     name: &str,
     activity: &CommitterActivity,
 ) -> QueryResult<Model> {
-    let com = committer::add(conn, name)?;
+    let com = committer::add(conn, name, &activity.emails)?;
     let commits_i32 = activity.total as i32;
     let insertions_i32 = activity.insertions as i32;
     let deletions_i32 = activity.deletions as i32;
@@ -165,7 +197,7 @@ pub fn add(
             deletions: deletions_i32,
         })
         .execute(conn)?;
-    let new_id = crate::models::last_insert_rowid(conn);
+    let new_id = crate::models::last_insert_id(conn);
     let mod_info = module::get_by_id(conn, module_id).unwrap();
     let _ = system_event::register_new_module_committer(
         conn,
@@ -4,11 +4,12 @@ use std::collections::HashMap;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::models::BOT_COMMITTERS;
+use crate::dialect;
 use crate::schema::module_committer_period;
+use crate::DbConnection;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = module_committer_period, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = module_committer_period, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub module_id: i64,
@@ -33,7 +34,7 @@ struct NewModuleCommitterPeriod {
 /// delete+insert is simpler than diffing and keeps this in sync with
 /// `module_committer::add`, which is called with the same data.
 pub fn replace_for_committer(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_id: &i64,
     committer_id: &i64,
     periods: &HashMap<(i32, i32), u32>,
@@ -87,10 +88,10 @@ pub struct PeriodActivity {
 /// that period. Ordered chronologically so callers can take the first/last
 /// row for "first module touched"/"last seen" trivia.
 pub fn get_activity_by_committer_name(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     committer_name: &str,
 ) -> Vec<PeriodActivity> {
-    diesel::sql_query(
+    dialect::sql_query(
         "SELECT mod.technical_name, mod.name, gh_org.name as organization, \
          mcp.year as year, mcp.month as month, mcp.commits as commits \
          FROM module_committer_period as mcp \
@@ -123,41 +124,41 @@ pub struct PeriodRankEntry {
 /// Ranks committers by commits within a period. `month` is only applied when
 /// `year` is also set (a lone month with no year would be meaningless).
 pub fn rank_by_period(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     year: i32,
     month: Option<i32>,
     limit: i64,
 ) -> Vec<PeriodRankEntry> {
     match month {
-        Some(m) => diesel::sql_query(format!(
+        Some(m) => dialect::sql_query(
             "SELECT com.name as name, SUM(mcp.commits) as total_commits, \
                     RANK() OVER (ORDER BY SUM(mcp.commits) DESC) as rank, \
                     COUNT(*) OVER () as total_committers, \
                     COUNT(DISTINCT mcp.module_id) as modules_touched \
              FROM module_committer_period as mcp \
              INNER JOIN committer as com ON mcp.committer_id = com.id \
-             WHERE com.name NOT IN ({BOT_COMMITTERS}) AND mcp.year = ? AND mcp.month = ? \
+             WHERE NOT com.is_bot AND mcp.year = ? AND mcp.month = ? \
              GROUP BY com.id \
              ORDER BY total_commits DESC \
-             LIMIT ?"
-        ))
+             LIMIT ?",
+        )
         .bind::<diesel::sql_types::Integer, _>(year)
         .bind::<diesel::sql_types::Integer, _>(m)
         .bind::<diesel::sql_types::BigInt, _>(limit)
         .load::<PeriodRankEntry>(conn)
         .expect("DB error in module_committer_period::rank_by_period"),
-        None => diesel::sql_query(format!(
+        None => dialect::sql_query(
             "SELECT com.name as name, SUM(mcp.commits) as total_commits, \
                     RANK() OVER (ORDER BY SUM(mcp.commits) DESC) as rank, \
                     COUNT(*) OVER () as total_committers, \
                     COUNT(DISTINCT mcp.module_id) as modules_touched \
              FROM module_committer_period as mcp \
              INNER JOIN committer as com ON mcp.committer_id = com.id \
-             WHERE com.name NOT IN ({BOT_COMMITTERS}) AND mcp.year = ? \
+             WHERE NOT com.is_bot AND mcp.year = ? \
              GROUP BY com.id \
              ORDER BY total_commits DESC \
-             LIMIT ?"
-        ))
+             LIMIT ?",
+        )
         .bind::<diesel::sql_types::Integer, _>(year)
         .bind::<diesel::sql_types::BigInt, _>(limit)
         .load::<PeriodRankEntry>(conn)
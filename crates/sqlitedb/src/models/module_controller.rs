@@ -2,12 +2,14 @@
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::dialect;
 use crate::schema::module_controller;
+use crate::DbConnection;
 
 use super::module_code_analysis::ControllerAnalysisInfo;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = module_controller, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = module_controller, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub module_id: i64,
@@ -23,6 +25,7 @@ pub struct Model {
     pub signature: String,
     pub docstring: Option<String>,
     pub module_version_id: i64,
+    pub checks_token_access: bool,
 }
 
 impl Model {
@@ -40,6 +43,72 @@ impl Model {
     }
 }
 
+/// A controller of a module's current version, with where it comes from.
+#[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
+pub struct CurrentControllerInfo {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub technical_name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub organization: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub repository: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub class_name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub routes: String,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub auth: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub http_type: String,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub methods: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Bool>)]
+    pub csrf: Option<bool>,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    pub website: bool,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    pub uses_sudo: bool,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    pub checks_token_access: bool,
+}
+
+impl CurrentControllerInfo {
+    pub fn routes_vec(&self) -> Vec<String> {
+        serde_json::from_str(&self.routes).unwrap_or_default()
+    }
+
+    pub fn methods_vec(&self) -> Vec<String> {
+        self.methods
+            .as_deref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Every controller of the current version of the modules of `version_odoo`.
+pub fn get_current_by_odoo_version(
+    conn: &mut DbConnection,
+    version_odoo: &u8,
+) -> Vec<CurrentControllerInfo> {
+    dialect::sql_query(
+        "SELECT mod.technical_name, gh_org.name as organization, gh_repo.name as repository, \
+         mc.class_name, mc.name, mc.routes, mc.auth, mc.http_type, mc.methods, mc.csrf, \
+         mc.website, mc.uses_sudo, mc.checks_token_access \
+         FROM module_controller as mc \
+         INNER JOIN module_version as mv ON mv.id = mc.module_version_id \
+         INNER JOIN module as mod ON mod.id = mv.module_id AND mod.version_module = mv.version_module \
+         INNER JOIN gh_repository as gh_repo ON gh_repo.id = mod.gh_repository_id \
+         INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id \
+         WHERE mod.version_odoo = ? \
+         ORDER BY mod.technical_name, gh_org.name, mc.class_name, mc.name",
+    )
+    .bind::<diesel::sql_types::Integer, _>(*version_odoo as i32)
+    .load::<CurrentControllerInfo>(conn)
+    .expect("DB error in module_controller::get_current_by_odoo_version")
+}
+
 #[derive(Insertable)]
 #[diesel(table_name = module_controller)]
 struct NewModuleController<'a> {
@@ -56,13 +125,11 @@ struct NewModuleController<'a> {
     signature: &'a str,
     docstring: Option<&'a str>,
     module_version_id: i64,
+    checks_token_access: bool,
 }
 
 /// Controllers for one specific version snapshot.
-pub fn get_by_module_version_id(
-    conn: &mut SqliteConnection,
-    module_version_id: &i64,
-) -> Vec<Model> {
+pub fn get_by_module_version_id(conn: &mut DbConnection, module_version_id: &i64) -> Vec<Model> {
     module_controller::table
         .filter(module_controller::module_version_id.eq(module_version_id))
         .order((
@@ -73,18 +140,26 @@ pub fn get_by_module_version_id(
         .expect("DB error in module_controller::get_by_module_version_id")
 }
 
+/// Drops the controllers of one version snapshot (see `module_version::prune_snapshots`).
+pub fn delete_by_module_version_id(
+    conn: &mut DbConnection,
+    module_version_id: &i64,
+) -> QueryResult<usize> {
+    diesel::delete(
+        module_controller::table.filter(module_controller::module_version_id.eq(module_version_id)),
+    )
+    .execute(conn)
+}
+
 /// Replaces every controller row for this version snapshot (delete+insert,
 /// scoped to `module_version_id` - mirrors module_record).
 pub fn replace_for_module(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_id: &i64,
     module_version_id: &i64,
     controllers: &[ControllerAnalysisInfo],
 ) -> QueryResult<()> {
-    diesel::delete(
-        module_controller::table.filter(module_controller::module_version_id.eq(module_version_id)),
-    )
-    .execute(conn)?;
+    delete_by_module_version_id(conn, module_version_id)?;
 
     let routes_json: Vec<String> = controllers
         .iter()
@@ -117,6 +192,7 @@ pub fn replace_for_module(
             signature: c.signature.as_str(),
             docstring: c.docstring.as_deref(),
             module_version_id: *module_version_id,
+            checks_token_access: c.checks_token_access,
         })
         .collect();
 
@@ -0,0 +1,518 @@
+// Copyright Alexandre D. Díaz
+//! Entity-relationship diagram of the Odoo models of a module set, from the
+//! code analysis: the models the modules define as entities, the core
+//! models they extend or point to as stubs, and an edge per relational
+//! field or `_inherits` delegation. Rendered as Mermaid or as a standalone
+//! SVG.
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::utils::escape::{mermaid_label, xml_escape};
+use crate::DbConnection;
+
+use super::{module, module_model, module_model_field, module_version};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    /// Defined (`_name`) by a module of the set.
+    Defined,
+    /// Only extended (`_inherit`) by the set.
+    Extended,
+    /// Not touched by the set, only pointed to by one of its fields.
+    Referenced,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ErdField {
+    pub name: String,
+    pub field_type: String,
+    pub relation: Option<String>,
+    /// Modules of the set adding the field.
+    pub modules: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ErdEntity {
+    pub key: String,
+    pub model_name: String,
+    /// The module whose side of the model this is, unless merged.
+    pub module: Option<String>,
+    pub kind: EntityKind,
+    pub description: Option<String>,
+    pub fields: Vec<ErdField>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ErdRelation {
+    pub source: String,
+    pub target: String,
+    pub field: String,
+    /// `Many2one`, `One2many`, `Many2many` or `_inherits`.
+    pub relation_type: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ErdInfo {
+    pub entities: Vec<ErdEntity>,
+    pub relations: Vec<ErdRelation>,
+}
+
+const RELATIONAL_FIELD_TYPES: [&str; 3] = ["Many2one", "One2many", "Many2many"];
+
+#[derive(Default)]
+struct ErdBuilder {
+    erd: ErdInfo,
+    entity_index: HashMap<String, usize>,
+    relation_keys: HashSet<(String, String, String)>,
+    // Fields delegating to a parent model, by entity key.
+    delegations: Vec<(String, String, String)>,
+}
+
+impl ErdBuilder {
+    fn entity(
+        &mut self,
+        key: String,
+        model_name: &str,
+        module: Option<&str>,
+        kind: EntityKind,
+    ) -> &mut ErdEntity {
+        let idx = *self.entity_index.entry(key.clone()).or_insert_with(|| {
+            self.erd.entities.push(ErdEntity {
+                key,
+                model_name: model_name.to_string(),
+                module: module.map(str::to_string),
+                kind,
+                description: None,
+                fields: Vec::new(),
+            });
+            self.erd.entities.len() - 1
+        });
+        let entity = &mut self.erd.entities[idx];
+        // Defined anywhere in the set wins over extended.
+        if kind == EntityKind::Defined {
+            entity.kind = kind;
+        }
+        entity
+    }
+
+    // The entity a relation to `model_name` lands on: the side of the set
+    // defining it, else any side touching it, else a stub.
+    fn target(&mut self, model_name: &str) -> String {
+        let touching = self
+            .erd
+            .entities
+            .iter()
+            .filter(|entity| entity.model_name == model_name);
+        let target = touching
+            .clone()
+            .find(|entity| entity.kind == EntityKind::Defined)
+            .or_else(|| touching.clone().next())
+            .map(|entity| entity.key.clone());
+        match target {
+            Some(key) => key,
+            None => {
+                let key = model_name.to_string();
+                self.entity(key.clone(), model_name, None, EntityKind::Referenced);
+                key
+            }
+        }
+    }
+
+    fn add_relation(&mut self, source: &str, model_name: &str, field: &str, relation_type: &str) {
+        let target = self.target(model_name);
+        if self
+            .relation_keys
+            .insert((source.to_string(), target.clone(), field.to_string()))
+        {
+            self.erd.relations.push(ErdRelation {
+                source: source.to_string(),
+                target,
+                field: field.to_string(),
+                relation_type: relation_type.to_string(),
+            });
+        }
+    }
+}
+
+/// Builds the diagram of `modules` at their current version, or at
+/// `version_module` for those that have it. With `merge`, every module
+/// adding fields to the same model shares one entity; otherwise each gets
+/// its own side of it.
+pub fn build(
+    conn: &mut DbConnection,
+    modules: &[module::Model],
+    version_module: Option<&str>,
+    merge: bool,
+) -> ErdInfo {
+    let mut builder = ErdBuilder::default();
+    for module in modules {
+        let snapshot = match version_module {
+            Some(v) => module_version::get_by_module_id_version_module(conn, &module.id, v)
+                .or_else(|| module_version::resolve_current(conn, module)),
+            None => module_version::resolve_current(conn, module),
+        };
+        let Some(snapshot) = snapshot else {
+            continue;
+        };
+        let technical_name = module.technical_name.as_str();
+        for model in module_model::get_by_module_version_id(conn, &snapshot.id) {
+            let (key, side) = if merge {
+                (model.model_name.clone(), None)
+            } else {
+                (
+                    format!("{}@{technical_name}", model.model_name),
+                    Some(technical_name),
+                )
+            };
+            let kind = if model.is_new_model {
+                EntityKind::Defined
+            } else {
+                EntityKind::Extended
+            };
+            let attrs = model.attrs_value();
+            let entity = builder.entity(key.clone(), &model.model_name, side, kind);
+            if entity.description.is_none() {
+                entity.description = attrs
+                    .as_ref()
+                    .and_then(|attrs| attrs["description"].as_str())
+                    .map(str::to_string);
+            }
+            for field in module_model_field::get_by_module_model_id(conn, &model.id) {
+                match entity.fields.iter_mut().find(|f| f.name == field.name) {
+                    Some(existing) => {
+                        if !existing.modules.iter().any(|m| m == technical_name) {
+                            existing.modules.push(technical_name.to_string());
+                        }
+                    }
+                    None => entity.fields.push(ErdField {
+                        name: field.name,
+                        field_type: field.field_type,
+                        relation: field.relation,
+                        modules: vec![technical_name.to_string()],
+                    }),
+                }
+            }
+            let delegation = attrs
+                .as_ref()
+                .and_then(|attrs| attrs["inherits_delegation"].as_object().cloned())
+                .unwrap_or_default();
+            for (parent, field) in delegation {
+                if let Some(field) = field.as_str() {
+                    builder
+                        .delegations
+                        .push((key.clone(), parent, field.to_string()));
+                }
+            }
+        }
+    }
+
+    // Relations last, so they land on entities of the set whenever the
+    // set has the target model, whatever the order it came in.
+    let mut relations = Vec::new();
+    for entity in &builder.erd.entities {
+        for field in &entity.fields {
+            if let Some(relation) = &field.relation {
+                if RELATIONAL_FIELD_TYPES.contains(&field.field_type.as_str()) {
+                    relations.push((
+                        entity.key.clone(),
+                        relation.clone(),
+                        field.name.clone(),
+                        field.field_type.clone(),
+                    ));
+                }
+            }
+        }
+    }
+    for (source, parent, field) in std::mem::take(&mut builder.delegations) {
+        relations.push((source, parent, field, "_inherits".to_string()));
+    }
+    for (source, model_name, field, relation_type) in relations {
+        builder.add_relation(&source, &model_name, &field, &relation_type);
+    }
+    builder.erd
+}
+
+fn entity_title(entity: &ErdEntity) -> String {
+    match (&entity.module, entity.kind) {
+        (Some(module), EntityKind::Extended) => {
+            format!("{} (extended by {module})", entity.model_name)
+        }
+        (Some(module), _) => format!("{} ({module})", entity.model_name),
+        (None, EntityKind::Extended) => format!("{} (extended)", entity.model_name),
+        (None, _) => entity.model_name.clone(),
+    }
+}
+
+/// A Mermaid `erDiagram`. Entities get positional ids, model names having
+/// dots Mermaid doesn't allow in one.
+pub fn to_mermaid(erd: &ErdInfo) -> String {
+    let ids: HashMap<&str, String> = erd
+        .entities
+        .iter()
+        .enumerate()
+        .map(|(idx, entity)| (entity.key.as_str(), format!("e{idx}")))
+        .collect();
+    let mut out = String::from("erDiagram\n");
+    for (idx, entity) in erd.entities.iter().enumerate() {
+        let title = mermaid_label(&entity_title(entity));
+        if entity.fields.is_empty() {
+            let _ = writeln!(out, "    e{idx}[\"{title}\"]");
+            continue;
+        }
+        let _ = writeln!(out, "    e{idx}[\"{title}\"] {{");
+        for field in &entity.fields {
+            let key = if field.field_type == "Many2one" {
+                " FK"
+            } else {
+                ""
+            };
+            match &field.relation {
+                Some(relation) => {
+                    let _ = writeln!(
+                        out,
+                        "        {} {}{key} \"{}\"",
+                        field.field_type,
+                        field.name,
+                        mermaid_label(relation)
+                    );
+                }
+                None => {
+                    let _ = writeln!(out, "        {} {}", field.field_type, field.name);
+                }
+            }
+        }
+        out.push_str("    }\n");
+    }
+    for relation in &erd.relations {
+        let (Some(source), Some(target)) = (
+            ids.get(relation.source.as_str()),
+            ids.get(relation.target.as_str()),
+        ) else {
+            continue;
+        };
+        let (cardinality, label) = match relation.relation_type.as_str() {
+            "Many2one" => ("}o--o|", relation.field.clone()),
+            "One2many" => ("|o--o{", relation.field.clone()),
+            "Many2many" => ("}o--o{", relation.field.clone()),
+            _ => ("||--||", format!("{} (_inherits)", relation.field)),
+        };
+        let _ = writeln!(
+            out,
+            "    {source} {cardinality} {target} : \"{}\"",
+            mermaid_label(&label)
+        );
+    }
+    out
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        return s.to_string();
+    }
+    let mut out: String = s.chars().take(max - 1).collect();
+    out.push('…');
+    out
+}
+
+const BOX_WIDTH: f64 = 260.0;
+const HEADER_HEIGHT: f64 = 26.0;
+const ROW_HEIGHT: f64 = 18.0;
+const MAX_ROWS: usize = 14;
+const GAP_X: f64 = 90.0;
+const GAP_Y: f64 = 70.0;
+const MARGIN: f64 = 20.0;
+
+fn box_height(entity: &ErdEntity) -> f64 {
+    let rows = entity.fields.len().min(MAX_ROWS + 1);
+    HEADER_HEIGHT + rows as f64 * ROW_HEIGHT + if rows > 0 { 8.0 } else { 0.0 }
+}
+
+// Where the segment from the center of a box towards (dx, dy) leaves it.
+fn border_point(cx: f64, cy: f64, half_h: f64, dx: f64, dy: f64) -> (f64, f64) {
+    let half_w = BOX_WIDTH / 2.0;
+    let tx = if dx != 0.0 {
+        half_w / dx.abs()
+    } else {
+        f64::INFINITY
+    };
+    let ty = if dy != 0.0 {
+        half_h / dy.abs()
+    } else {
+        f64::INFINITY
+    };
+    let t = tx.min(ty).min(1.0);
+    (cx + dx * t, cy + dy * t)
+}
+
+/// A standalone SVG: entities on a grid, relations as arrows from the
+/// model holding the field, labelled with it. Long field lists are cut
+/// at `MAX_ROWS`.
+pub fn to_svg(erd: &ErdInfo) -> String {
+    let columns = (erd.entities.len() as f64).sqrt().ceil().max(1.0) as usize;
+    let row_heights: Vec<f64> = erd
+        .entities
+        .chunks(columns)
+        .map(|row| row.iter().map(box_height).fold(0.0, f64::max))
+        .collect();
+    let mut positions: HashMap<&str, (f64, f64, f64)> = HashMap::new();
+    let mut y = MARGIN;
+    for (row, entities) in erd.entities.chunks(columns).enumerate() {
+        for (column, entity) in entities.iter().enumerate() {
+            let x = MARGIN + column as f64 * (BOX_WIDTH + GAP_X);
+            positions.insert(&entity.key, (x, y, box_height(entity)));
+        }
+        y += row_heights[row] + GAP_Y;
+    }
+    let width = MARGIN * 2.0 + columns as f64 * BOX_WIDTH + (columns - 1) as f64 * GAP_X;
+    let height = (y - GAP_Y + MARGIN).max(MARGIN * 2.0);
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\" font-family=\"sans-serif\" font-size=\"12\">"
+    );
+    out.push_str(
+        "  <defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" \
+         markerWidth=\"8\" markerHeight=\"8\" orient=\"auto-start-reverse\">\
+         <path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"#6c757d\"/></marker></defs>\n",
+    );
+    let _ = writeln!(
+        out,
+        "  <rect width=\"{width}\" height=\"{height}\" fill=\"#ffffff\"/>"
+    );
+
+    // Relations between the same two entities, either way, are spread
+    // apart so they don't draw over each other.
+    let pair = |relation: &ErdRelation| {
+        let mut pair = [relation.source.clone(), relation.target.clone()];
+        pair.sort();
+        pair
+    };
+    let mut pair_counts: HashMap<[String; 2], usize> = HashMap::new();
+    for relation in &erd.relations {
+        *pair_counts.entry(pair(relation)).or_default() += 1;
+    }
+    let mut pair_seen: HashMap<[String; 2], usize> = HashMap::new();
+
+    for relation in &erd.relations {
+        let (Some(&(sx, sy, sh)), Some(&(tx, ty, th))) = (
+            positions.get(relation.source.as_str()),
+            positions.get(relation.target.as_str()),
+        ) else {
+            continue;
+        };
+        let (scx, scy) = (sx + BOX_WIDTH / 2.0, sy + sh / 2.0);
+        let (tcx, tcy) = (tx + BOX_WIDTH / 2.0, ty + th / 2.0);
+        let label = if relation.relation_type == "_inherits" {
+            format!("{} (_inherits)", relation.field)
+        } else {
+            format!("{} ({})", relation.field, relation.relation_type)
+        };
+        if relation.source == relation.target {
+            // A model pointing to itself, e.g. parent_id.
+            let _ = writeln!(
+                out,
+                "  <path d=\"M {x1} {y1} C {x2} {y1}, {x2} {y2}, {x1} {y2}\" fill=\"none\" \
+                 stroke=\"#6c757d\" marker-end=\"url(#arrow)\"/>",
+                x1 = sx + BOX_WIDTH,
+                x2 = sx + BOX_WIDTH + 40.0,
+                y1 = sy + 8.0,
+                y2 = sy + HEADER_HEIGHT - 4.0,
+            );
+            continue;
+        }
+        let key = pair(relation);
+        let nth = pair_seen.entry(key.clone()).or_default();
+        let shift = (*nth as f64 - (pair_counts[&key] - 1) as f64 / 2.0) * 16.0;
+        *nth += 1;
+        // Perpendicular to the canonical direction of the pair, so both
+        // ways get the same normal.
+        let (dx, dy) = if relation.source == key[0] {
+            (tcx - scx, tcy - scy)
+        } else {
+            (scx - tcx, scy - tcy)
+        };
+        let length = dx.hypot(dy).max(1.0);
+        let (ox, oy) = (-dy / length * shift, dx / length * shift);
+        let (x1, y1) = border_point(scx + ox, scy + oy, sh / 2.0, tcx - scx, tcy - scy);
+        let (x2, y2) = border_point(tcx + ox, tcy + oy, th / 2.0, scx - tcx, scy - tcy);
+        let dash = if relation.relation_type == "_inherits" {
+            " stroke-dasharray=\"6 3\""
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            out,
+            "  <line x1=\"{x1:.1}\" y1=\"{y1:.1}\" x2=\"{x2:.1}\" y2=\"{y2:.1}\" \
+             stroke=\"#6c757d\"{dash} marker-end=\"url(#arrow)\"/>"
+        );
+        let _ = writeln!(
+            out,
+            "  <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" font-size=\"10\" \
+             fill=\"#495057\">{}</text>",
+            (x1 + x2) / 2.0,
+            (y1 + y2) / 2.0 - 3.0,
+            xml_escape(&label)
+        );
+    }
+
+    for entity in &erd.entities {
+        let (x, y, h) = positions[entity.key.as_str()];
+        let (color, dash) = match entity.kind {
+            EntityKind::Defined => ("#017E84", ""),
+            EntityKind::Extended => ("#E46E78", ""),
+            EntityKind::Referenced => ("#6c757d", " stroke-dasharray=\"4 3\""),
+        };
+        let _ = writeln!(out, "  <g>");
+        if let Some(description) = &entity.description {
+            let _ = writeln!(out, "    <title>{}</title>", xml_escape(description));
+        }
+        let _ = writeln!(
+            out,
+            "    <rect x=\"{x}\" y=\"{y}\" width=\"{BOX_WIDTH}\" height=\"{h}\" rx=\"4\" \
+             fill=\"#ffffff\" stroke=\"{color}\"{dash}/>"
+        );
+        let _ = writeln!(
+            out,
+            "    <rect x=\"{x}\" y=\"{y}\" width=\"{BOX_WIDTH}\" height=\"{HEADER_HEIGHT}\" \
+             rx=\"4\" fill=\"{color}\"/>"
+        );
+        let _ = writeln!(
+            out,
+            "    <text x=\"{}\" y=\"{}\" font-weight=\"bold\" fill=\"#ffffff\">{}</text>",
+            x + 8.0,
+            y + 17.0,
+            xml_escape(&truncate(&entity_title(entity), 38))
+        );
+        for (row, field) in entity.fields.iter().take(MAX_ROWS).enumerate() {
+            let text = match &field.relation {
+                Some(relation) => {
+                    format!("{}: {} → {relation}", field.name, field.field_type)
+                }
+                None => format!("{}: {}", field.name, field.field_type),
+            };
+            let _ = writeln!(
+                out,
+                "    <text x=\"{}\" y=\"{}\" fill=\"#212529\">{}</text>",
+                x + 8.0,
+                y + HEADER_HEIGHT + (row as f64 + 1.0) * ROW_HEIGHT - 4.0,
+                xml_escape(&truncate(&text, 40))
+            );
+        }
+        if entity.fields.len() > MAX_ROWS {
+            let _ = writeln!(
+                out,
+                "    <text x=\"{}\" y=\"{}\" fill=\"#6c757d\" font-style=\"italic\">… {} more</text>",
+                x + 8.0,
+                y + HEADER_HEIGHT + (MAX_ROWS as f64 + 1.0) * ROW_HEIGHT - 4.0,
+                entity.fields.len() - MAX_ROWS
+            );
+        }
+        let _ = writeln!(out, "  </g>");
+    }
+    out.push_str("</svg>\n");
+    out
+}
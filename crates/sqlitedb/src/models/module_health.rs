@@ -0,0 +1,420 @@
+// Copyright Alexandre D. Díaz
+//! A composite "should we depend on it?" score for a module in one Odoo
+//! version. It's out of 100 and kept transparent: the score is only the sum of
+//! its components, each telling the points it got, the most it could get and
+//! why.
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use oghutils::version::odoo_version_u8_to_string;
+use serde::{Deserialize, Serialize};
+
+use super::module;
+use super::module_security_warning::SEVERITY_ERROR;
+use crate::dialect;
+use crate::schema::module_health_score;
+use crate::utils::date::get_sqlite_utc_now;
+use crate::DbConnection;
+
+pub const MAX_SCORE: i32 = 100;
+
+const CI_FAILURE: &str = "failure";
+
+#[derive(QueryableByName, Debug)]
+struct HealthSignalsInfo {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    module_id: i64,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    technical_name: String,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    version_odoo: i32,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    org_name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    repo_name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    last_commit_date: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    maintainers: i64,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Integer>)]
+    test_files: Option<i32>,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    open_pull_requests: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    failing_pull_requests: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    security_errors: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    security_warnings: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    osv_count: i64,
+    /// The newest Odoo version its organization has it installable in.
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Integer>)]
+    newest_version: Option<i32>,
+}
+
+/// One term of the score.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HealthComponent {
+    /// `activity`, `maintainers`, `tests`, `ci`, `security`, `osv` or
+    /// `latest_version`.
+    pub key: String,
+    pub label: String,
+    pub points: i32,
+    pub max_points: i32,
+    pub detail: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ModuleHealth {
+    pub technical_name: String,
+    pub organization: String,
+    pub repository: String,
+    pub odoo_version: String,
+    /// Sum of the components' points, out of `MAX_SCORE`.
+    pub score: i32,
+    pub components: Vec<HealthComponent>,
+}
+
+/// How `module::search_by_criteria` orders its results.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthSort {
+    /// Best match first when searching by term, by technical name otherwise.
+    #[default]
+    Relevance,
+    Health,
+}
+
+#[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
+pub struct ModuleScoreInfo {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub technical_name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub organization: String,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub version_odoo: i32,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub score: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = module_health_score)]
+struct NewModuleHealthScore<'a> {
+    module_id: i64,
+    version_odoo: i32,
+    score: i32,
+    update_date: &'a str,
+}
+
+fn get_signals(
+    conn: &mut DbConnection,
+    module_id: Option<&i64>,
+    version_odoo: Option<&u8>,
+) -> Vec<HealthSignalsInfo> {
+    dialect::sql_query(
+        "SELECT mod.id as module_id, mod.technical_name, mod.version_odoo, \
+         gh_org.name as org_name, gh_repo.name as repo_name, mod.last_commit_date, \
+         (SELECT COUNT(*) FROM module_maintainer as mm WHERE mm.module_id = mod.id) as maintainers, \
+         (SELECT mv.test_files FROM module_version as mv \
+           WHERE mv.module_id = mod.id AND mv.version_module = mod.version_module) as test_files, \
+         COALESCE(prs.open_count, 0) as open_pull_requests, \
+         COALESCE(prs.failing_count, 0) as failing_pull_requests, \
+         (SELECT COUNT(*) FROM module_security_warning as msw \
+           INNER JOIN module_version as mv ON mv.id = msw.module_version_id \
+           WHERE msw.module_id = mod.id AND mv.version_module = mod.version_module \
+           AND msw.severity = ?2) as security_errors, \
+         (SELECT COUNT(*) FROM module_security_warning as msw \
+           INNER JOIN module_version as mv ON mv.id = msw.module_version_id \
+           WHERE msw.module_id = mod.id AND mv.version_module = mod.version_module \
+           AND msw.severity <> ?2) as security_warnings, \
+         (SELECT COUNT(DISTINCT dep_o.osv_id) FROM dependency_osv as dep_o \
+           INNER JOIN dependency_module as dep_mod ON dep_mod.id = dep_o.dependency_module_id \
+           WHERE dep_mod.module_id = mod.id) as osv_count, \
+         (SELECT MAX(newer.version_odoo) FROM module as newer \
+           INNER JOIN gh_repository as newer_repo ON newer_repo.id = newer.gh_repository_id \
+           WHERE newer.technical_name = mod.technical_name AND newer.installable \
+           AND newer_repo.gh_organization_id = gh_repo.gh_organization_id) as newest_version \
+         FROM module as mod \
+         INNER JOIN gh_repository as gh_repo ON gh_repo.id = mod.gh_repository_id \
+         INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id \
+         LEFT JOIN (SELECT opr_repo.gh_organization_id, opr.version_odoo, oprm.module_technical_name, \
+           COUNT(DISTINCT opr.id) as open_count, \
+           COUNT(DISTINCT CASE WHEN opr.ci_status = ?1 THEN opr.id END) as failing_count \
+           FROM open_pull_request as opr \
+           INNER JOIN open_pull_request_module as oprm ON oprm.open_pull_request_id = opr.id \
+           INNER JOIN gh_repository as opr_repo ON opr_repo.id = opr.gh_repository_id \
+           GROUP BY opr_repo.gh_organization_id, opr.version_odoo, oprm.module_technical_name) as prs \
+           ON prs.gh_organization_id = gh_repo.gh_organization_id \
+           AND prs.version_odoo = mod.version_odoo \
+           AND prs.module_technical_name = mod.technical_name \
+         WHERE (?3 IS NULL OR mod.id = ?3) AND (?4 IS NULL OR mod.version_odoo = ?4) \
+         ORDER BY gh_org.name, gh_repo.name, mod.technical_name, mod.version_odoo",
+    )
+    .bind::<diesel::sql_types::Text, _>(CI_FAILURE)
+    .bind::<diesel::sql_types::Text, _>(SEVERITY_ERROR)
+    .bind::<diesel::sql_types::Nullable<diesel::sql_types::BigInt>, _>(module_id)
+    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Integer>, _>(
+        version_odoo.map(|version| *version as i32),
+    )
+    .load::<HealthSignalsInfo>(conn)
+    .expect("DB error in module_health::get_signals")
+}
+
+/// Days since `date`, as git (`%aD`, RFC 2822) or the dataset/test helpers
+/// (`%Y-%m-%d[ %H:%M:%S]`) write it.
+fn days_since(date: &str) -> Option<i64> {
+    let date = date.trim();
+    let datetime = DateTime::parse_from_rfc2822(date)
+        .map(|dt| dt.naive_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S"))
+        .or_else(|_| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").map(|d| d.and_hms_opt(0, 0, 0).unwrap())
+        })
+        .ok()?;
+    Some((Utc::now().naive_utc() - datetime).num_days().max(0))
+}
+
+fn component(
+    key: &str,
+    label: &str,
+    points: i32,
+    max_points: i32,
+    detail: String,
+) -> HealthComponent {
+    HealthComponent {
+        key: key.to_string(),
+        label: label.to_string(),
+        points,
+        max_points,
+        detail,
+    }
+}
+
+fn plural(count: i64, one: &str, many: &str) -> String {
+    format!("{count} {}", if count == 1 { one } else { many })
+}
+
+/// `odoo_versions` is every collected version, newest first.
+fn score(signals: HealthSignalsInfo, odoo_versions: &[i32]) -> ModuleHealth {
+    let activity = match days_since(&signals.last_commit_date) {
+        Some(days) => component(
+            "activity",
+            "Recent commits",
+            match days {
+                0..=180 => 20,
+                181..=365 => 15,
+                366..=730 => 8,
+                _ => 0,
+            },
+            20,
+            format!("Last commit {} ago", plural(days, "day", "days")),
+        ),
+        None => component("activity", "Recent commits", 0, 20, "No commit date".into()),
+    };
+    let maintainers = component(
+        "maintainers",
+        "Maintainers",
+        match signals.maintainers {
+            0 => 0,
+            1 => 10,
+            _ => 15,
+        },
+        15,
+        plural(signals.maintainers, "maintainer", "maintainers") + " in the manifest",
+    );
+    let tests = match signals.test_files {
+        Some(0) => component("tests", "Tests", 0, 15, "No test_*.py file".into()),
+        Some(count) => component(
+            "tests",
+            "Tests",
+            15,
+            15,
+            plural(count as i64, "test file", "test files"),
+        ),
+        None => component("tests", "Tests", 0, 15, "Not analyzed for tests yet".into()),
+    };
+    let ci = if signals.open_pull_requests == 0 {
+        component(
+            "ci",
+            "CI of open PRs",
+            10,
+            10,
+            "No open pull request".into(),
+        )
+    } else {
+        let passing = signals.open_pull_requests - signals.failing_pull_requests;
+        component(
+            "ci",
+            "CI of open PRs",
+            (10.0 * passing as f64 / signals.open_pull_requests as f64).round() as i32,
+            10,
+            format!(
+                "{} of {} failing",
+                signals.failing_pull_requests,
+                plural(
+                    signals.open_pull_requests,
+                    "open pull request",
+                    "open pull requests"
+                ),
+            ),
+        )
+    };
+    let security = component(
+        "security",
+        "Security checks",
+        (15 - 5 * signals.security_errors - signals.security_warnings).max(0) as i32,
+        15,
+        format!(
+            "{}, {}",
+            plural(signals.security_errors, "error", "errors"),
+            plural(signals.security_warnings, "minor finding", "minor findings"),
+        ),
+    );
+    let osv = if signals.osv_count == 0 {
+        component(
+            "osv",
+            "Python dependencies",
+            10,
+            10,
+            "No known vulnerability".into(),
+        )
+    } else {
+        component(
+            "osv",
+            "Python dependencies",
+            0,
+            10,
+            plural(
+                signals.osv_count,
+                "known vulnerability",
+                "known vulnerabilities",
+            ),
+        )
+    };
+    let latest = odoo_versions
+        .first()
+        .copied()
+        .unwrap_or(signals.version_odoo);
+    let latest_version = match signals.newest_version {
+        Some(newest) if newest >= latest => component(
+            "latest_version",
+            "Latest Odoo version",
+            15,
+            15,
+            format!(
+                "Available in {}",
+                odoo_version_u8_to_string(&(newest as u8))
+            ),
+        ),
+        Some(newest) => component(
+            "latest_version",
+            "Latest Odoo version",
+            if odoo_versions.get(1) == Some(&newest) {
+                7
+            } else {
+                0
+            },
+            15,
+            format!(
+                "Newest in {}, {} is out",
+                odoo_version_u8_to_string(&(newest as u8)),
+                odoo_version_u8_to_string(&(latest as u8)),
+            ),
+        ),
+        None => component(
+            "latest_version",
+            "Latest Odoo version",
+            0,
+            15,
+            "Not installable in any version".into(),
+        ),
+    };
+
+    let components = vec![
+        activity,
+        maintainers,
+        tests,
+        ci,
+        security,
+        osv,
+        latest_version,
+    ];
+    ModuleHealth {
+        technical_name: signals.technical_name,
+        organization: signals.org_name,
+        repository: signals.repo_name,
+        odoo_version: odoo_version_u8_to_string(&(signals.version_odoo as u8)),
+        score: components.iter().map(|c| c.points).sum(),
+        components,
+    }
+}
+
+pub fn get_by_module_id(conn: &mut DbConnection, module_id: &i64) -> Option<ModuleHealth> {
+    let odoo_versions = module::get_odoo_versions(conn);
+    get_signals(conn, Some(module_id), None)
+        .into_iter()
+        .next()
+        .map(|signals| score(signals, &odoo_versions))
+}
+
+/// Every module's health, of one Odoo version or all of them, keyed by
+/// module id.
+pub fn get_all(conn: &mut DbConnection, version_odoo: Option<&u8>) -> HashMap<i64, ModuleHealth> {
+    let odoo_versions = module::get_odoo_versions(conn);
+    get_signals(conn, None, version_odoo)
+        .into_iter()
+        .map(|signals| (signals.module_id, score(signals, &odoo_versions)))
+        .collect()
+}
+
+/// Stores the score of every module of `version_odoo`, replacing the
+/// previous ones. The collector calls it at the end of every run of that
+/// version, so module lists and searches only read a column.
+pub fn refresh(conn: &mut DbConnection, version_odoo: &u8) -> QueryResult<usize> {
+    let update_date = get_sqlite_utc_now();
+    let scores = get_all(conn, Some(version_odoo));
+    let new_rows: Vec<NewModuleHealthScore> = scores
+        .iter()
+        .map(|(module_id, health)| NewModuleHealthScore {
+            module_id: *module_id,
+            version_odoo: *version_odoo as i32,
+            score: health.score,
+            update_date: &update_date,
+        })
+        .collect();
+    conn.transaction(|conn| {
+        diesel::delete(
+            module_health_score::table
+                .filter(module_health_score::version_odoo.eq(*version_odoo as i32)),
+        )
+        .execute(conn)?;
+        let mut count = 0;
+        // Keeps each statement under SQLite's bound parameters limit.
+        for chunk in new_rows.chunks(1000) {
+            count += diesel::insert_into(module_health_score::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        Ok(count)
+    })
+}
+
+pub fn delete_by_module_id(conn: &mut DbConnection, module_id: &i64) -> QueryResult<usize> {
+    diesel::delete(module_health_score::table.filter(module_health_score::module_id.eq(module_id)))
+        .execute(conn)
+}
+
+/// The stored scores (see `refresh`) of every module.
+pub fn get_scores(conn: &mut DbConnection) -> Vec<ModuleScoreInfo> {
+    dialect::sql_query(
+        "SELECT mod.technical_name, gh_org.name as organization, mhs.version_odoo, mhs.score \
+         FROM module_health_score as mhs \
+         INNER JOIN module as mod ON mod.id = mhs.module_id \
+         INNER JOIN gh_repository as gh_repo ON gh_repo.id = mod.gh_repository_id \
+         INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id",
+    )
+    .load::<ModuleScoreInfo>(conn)
+    .expect("DB error in module_health::get_scores")
+}
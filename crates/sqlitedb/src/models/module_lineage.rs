@@ -0,0 +1,265 @@
+// Copyright Alexandre D. Díaz
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer, Text};
+use serde::{Deserialize, Serialize};
+
+use crate::dialect;
+use crate::schema::module_lineage;
+use crate::utils::date::get_sqlite_utc_now;
+use crate::DbConnection;
+
+pub const KIND_MOVED: &str = "moved";
+pub const KIND_RENAMED: &str = "renamed";
+pub const KIND_MERGED: &str = "merged";
+
+pub const SOURCE_COLLECTOR: &str = "collector";
+pub const SOURCE_APRIORI: &str = "apriori";
+
+// Guards `resolve_successor` against rename cycles in hand-edited apriori
+// files; real OpenUpgrade chains are one or two hops long.
+const MAX_RESOLVE_HOPS: usize = 8;
+
+#[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
+#[diesel(table_name = module_lineage, check_for_backend(crate::DbBackend))]
+pub struct Model {
+    pub id: i64,
+    pub kind: String,
+    pub predecessor_technical_name: String,
+    pub predecessor_gh_repository_id: Option<i64>,
+    pub successor_technical_name: String,
+    pub successor_gh_repository_id: Option<i64>,
+    pub version_odoo: i32,
+    pub source: String,
+    pub create_date: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = module_lineage)]
+struct NewModuleLineage<'a> {
+    kind: &'a str,
+    predecessor_technical_name: &'a str,
+    predecessor_gh_repository_id: Option<i64>,
+    successor_technical_name: &'a str,
+    successor_gh_repository_id: Option<i64>,
+    version_odoo: i32,
+    source: &'a str,
+    create_date: &'a str,
+}
+
+/// Links `predecessor` to `successor` from `version_odoo` on. A link that
+/// is already known (same kind, names, repositories and version) is left
+/// untouched, so re-importing an apriori file or re-detecting a move is a
+/// no-op. Returns the number of inserted rows (0 or 1).
+#[allow(clippy::too_many_arguments)]
+pub fn add(
+    conn: &mut DbConnection,
+    kind: &str,
+    predecessor_technical_name: &str,
+    predecessor_gh_repository_id: Option<&i64>,
+    successor_technical_name: &str,
+    successor_gh_repository_id: Option<&i64>,
+    version_odoo: &u8,
+    source: &str,
+) -> QueryResult<usize> {
+    let create_date = get_sqlite_utc_now();
+    diesel::insert_into(module_lineage::table)
+        .values(NewModuleLineage {
+            kind,
+            predecessor_technical_name,
+            predecessor_gh_repository_id: predecessor_gh_repository_id.copied(),
+            successor_technical_name,
+            successor_gh_repository_id: successor_gh_repository_id.copied(),
+            version_odoo: *version_odoo as i32,
+            source,
+            create_date: &create_date,
+        })
+        // `uniq_module_lineage` is an expression index, which can't be named
+        // as a conflict target.
+        .on_conflict_do_nothing()
+        .execute(conn)
+}
+
+/// Imports OpenUpgrade's `renamed_modules`/`merged_modules` (old name, new
+/// name) pairs for the Odoo version their `apriori.py` belongs to.
+pub fn import_apriori(
+    conn: &mut DbConnection,
+    version_odoo: &u8,
+    renamed_modules: &[(String, String)],
+    merged_modules: &[(String, String)],
+) -> QueryResult<usize> {
+    let mut inserted = 0;
+    for (kind, pairs) in [
+        (KIND_RENAMED, renamed_modules),
+        (KIND_MERGED, merged_modules),
+    ] {
+        for (old_name, new_name) in pairs {
+            inserted += add(
+                conn,
+                kind,
+                old_name,
+                None,
+                new_name,
+                None,
+                version_odoo,
+                SOURCE_APRIORI,
+            )?;
+        }
+    }
+    Ok(inserted)
+}
+
+/// Records a move for a module row about to be deleted by
+/// `module::delete_outdated` when the same technical_name now lives, at the
+/// same version, in another repository of the same organization (OCA
+/// moving a module between repos mid-version).
+pub fn register_move_from_stale(
+    conn: &mut DbConnection,
+    stale_module_id: &i64,
+) -> QueryResult<usize> {
+    dialect::sql_query(
+        "INSERT INTO module_lineage (kind, predecessor_technical_name, \
+         predecessor_gh_repository_id, successor_technical_name, successor_gh_repository_id, \
+         version_odoo, source, create_date) \
+         SELECT ?, old.technical_name, old.gh_repository_id, new.technical_name, \
+         new.gh_repository_id, new.version_odoo, ?, ? \
+         FROM module as old \
+         INNER JOIN module as new ON new.technical_name = old.technical_name \
+         AND new.version_odoo = old.version_odoo AND new.gh_repository_id != old.gh_repository_id \
+         INNER JOIN gh_repository as old_repo ON old.gh_repository_id = old_repo.id \
+         INNER JOIN gh_repository as new_repo ON new.gh_repository_id = new_repo.id \
+         WHERE old.id = ? AND new_repo.gh_organization_id = old_repo.gh_organization_id \
+         LIMIT 1 \
+         ON CONFLICT DO NOTHING",
+    )
+    .bind::<Text, _>(KIND_MOVED)
+    .bind::<Text, _>(SOURCE_COLLECTOR)
+    .bind::<Text, _>(get_sqlite_utc_now())
+    .bind::<BigInt, _>(stale_module_id)
+    .execute(conn)
+}
+
+/// Records moves across versions: a module present at `version_odoo` whose
+/// closest previous version lives in another repository of the same
+/// organization, and that repository doesn't carry it at `version_odoo`
+/// anymore. Called once per collector run, after `module::delete_outdated`.
+pub fn detect_moves(conn: &mut DbConnection, version_odoo: &u8) -> QueryResult<usize> {
+    dialect::sql_query(
+        "INSERT INTO module_lineage (kind, predecessor_technical_name, \
+         predecessor_gh_repository_id, successor_technical_name, successor_gh_repository_id, \
+         version_odoo, source, create_date) \
+         SELECT ?, prev.technical_name, prev.gh_repository_id, cur.technical_name, \
+         cur.gh_repository_id, cur.version_odoo, ?, ? \
+         FROM module as cur \
+         INNER JOIN module as prev ON prev.technical_name = cur.technical_name \
+         AND prev.gh_repository_id != cur.gh_repository_id \
+         AND prev.version_odoo = (SELECT MAX(p.version_odoo) FROM module as p \
+         WHERE p.technical_name = cur.technical_name AND p.version_odoo < cur.version_odoo) \
+         INNER JOIN gh_repository as prev_repo ON prev.gh_repository_id = prev_repo.id \
+         INNER JOIN gh_repository as cur_repo ON cur.gh_repository_id = cur_repo.id \
+         WHERE cur.version_odoo = ? \
+         AND prev_repo.gh_organization_id = cur_repo.gh_organization_id \
+         AND NOT EXISTS (SELECT 1 FROM module as s WHERE s.technical_name = cur.technical_name \
+         AND s.version_odoo = cur.version_odoo AND s.gh_repository_id = prev.gh_repository_id) \
+         ON CONFLICT DO NOTHING",
+    )
+    .bind::<Text, _>(KIND_MOVED)
+    .bind::<Text, _>(SOURCE_COLLECTOR)
+    .bind::<Text, _>(get_sqlite_utc_now())
+    .bind::<Integer, _>(*version_odoo as i32)
+    .execute(conn)
+}
+
+/// Every link where `technical_name` is the predecessor or the successor,
+/// oldest version first.
+pub fn get_by_technical_name(conn: &mut DbConnection, technical_name: &str) -> Vec<Model> {
+    module_lineage::table
+        .filter(
+            module_lineage::predecessor_technical_name
+                .eq(technical_name)
+                .or(module_lineage::successor_technical_name.eq(technical_name)),
+        )
+        .order((module_lineage::version_odoo.asc(), module_lineage::id.asc()))
+        .load::<Model>(conn)
+        .expect("DB error in module_lineage::get_by_technical_name")
+}
+
+/// The rename/merge that ends the chain starting at `technical_name`, taking
+/// only links effective at `version_odoo` or earlier into account: with
+/// `a -> b` at 15.0 and `b -> c` at 17.0, `a` resolves to the `b -> c` link
+/// at 17.0 and to the `a -> b` link at 16.0. `None` if it was never renamed.
+pub fn resolve_successor(
+    conn: &mut DbConnection,
+    technical_name: &str,
+    version_odoo: &u8,
+) -> Option<Model> {
+    let mut current = technical_name.to_string();
+    let mut last_hop: Option<Model> = None;
+    let mut seen: Vec<String> = vec![current.clone()];
+    for _ in 0..MAX_RESOLVE_HOPS {
+        let hop = module_lineage::table
+            .filter(
+                module_lineage::predecessor_technical_name
+                    .eq(&current)
+                    .and(module_lineage::kind.eq_any([KIND_RENAMED, KIND_MERGED]))
+                    .and(module_lineage::version_odoo.le(*version_odoo as i32)),
+            )
+            .order((
+                module_lineage::version_odoo.desc(),
+                module_lineage::id.desc(),
+            ))
+            .first::<Model>(conn)
+            .optional()
+            .expect("DB error in module_lineage::resolve_successor");
+        let Some(hop) = hop else {
+            break;
+        };
+        if seen.contains(&hop.successor_technical_name) {
+            break;
+        }
+        current = hop.successor_technical_name.clone();
+        seen.push(current.clone());
+        last_hop = Some(hop);
+    }
+    last_hop
+}
+
+/// The renames and merges that took effect after `after_version` and up to
+/// `version_odoo`: what changed names in the jump between the two.
+pub fn get_successions_between(
+    conn: &mut DbConnection,
+    after_version: &u8,
+    version_odoo: &u8,
+) -> Vec<Model> {
+    module_lineage::table
+        .filter(
+            module_lineage::kind
+                .eq_any([KIND_RENAMED, KIND_MERGED])
+                .and(module_lineage::version_odoo.gt(*after_version as i32))
+                .and(module_lineage::version_odoo.le(*version_odoo as i32)),
+        )
+        .order((module_lineage::version_odoo.asc(), module_lineage::id.asc()))
+        .load::<Model>(conn)
+        .expect("DB error in module_lineage::get_successions_between")
+}
+
+/// Latest repository move of `technical_name` effective at `version_odoo`.
+pub fn get_latest_move(
+    conn: &mut DbConnection,
+    technical_name: &str,
+    version_odoo: &u8,
+) -> Option<Model> {
+    module_lineage::table
+        .filter(
+            module_lineage::predecessor_technical_name
+                .eq(technical_name)
+                .and(module_lineage::kind.eq(KIND_MOVED))
+                .and(module_lineage::version_odoo.le(*version_odoo as i32)),
+        )
+        .order((
+            module_lineage::version_odoo.desc(),
+            module_lineage::id.desc(),
+        ))
+        .first::<Model>(conn)
+        .optional()
+        .expect("DB error in module_lineage::get_latest_move")
+}
@@ -3,12 +3,13 @@ use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::schema::module_maintainer;
+use crate::DbConnection;
 
 use super::{maintainer, module, system_event};
 use oghutils::version::odoo_version_u8_to_string;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = module_maintainer, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = module_maintainer, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub module_id: i64,
@@ -22,11 +23,7 @@ struct NewModuleMaintainer {
     maintainer_id: i64,
 }
 
-pub fn get_by_id(
-    conn: &mut SqliteConnection,
-    module_id: &i64,
-    maintainer_id: &i64,
-) -> Option<Model> {
+pub fn get_by_id(conn: &mut DbConnection, module_id: &i64, maintainer_id: &i64) -> Option<Model> {
     module_maintainer::table
         .filter(
             module_maintainer::module_id
@@ -38,7 +35,7 @@ pub fn get_by_id(
         .expect("DB error in module_maintainer::get_by_id")
 }
 
-pub fn get_by_name(conn: &mut SqliteConnection, module_id: &i64, name: &str) -> Option<Model> {
+pub fn get_by_name(conn: &mut DbConnection, module_id: &i64, name: &str) -> Option<Model> {
     if let Some(maint) = maintainer::get_by_name(conn, name) {
         module_maintainer::table
             .filter(
@@ -54,21 +51,21 @@ pub fn get_by_name(conn: &mut SqliteConnection, module_id: &i64, name: &str) ->
     }
 }
 
-pub fn get_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Vec<Model> {
+pub fn get_by_module_id(conn: &mut DbConnection, module_id: &i64) -> Vec<Model> {
     module_maintainer::table
         .filter(module_maintainer::module_id.eq(module_id))
         .load::<Model>(conn)
         .expect("DB error in module_maintainer::get_by_module_id")
 }
 
-pub fn get_names_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Vec<String> {
+pub fn get_names_by_module_id(conn: &mut DbConnection, module_id: &i64) -> Vec<String> {
     get_by_module_id(conn, module_id)
         .into_iter()
         .filter_map(|mm| maintainer::get_by_id(conn, &mm.maintainer_id).map(|m| m.name))
         .collect()
 }
 
-pub fn add(conn: &mut SqliteConnection, module_id: &i64, name: &str) -> QueryResult<Model> {
+pub fn add(conn: &mut DbConnection, module_id: &i64, name: &str) -> QueryResult<Model> {
     let maint = maintainer::add(conn, name)?;
     if let Some(existing) = get_by_id(conn, module_id, &maint.id) {
         return Ok(existing);
@@ -80,7 +77,7 @@ pub fn add(conn: &mut SqliteConnection, module_id: &i64, name: &str) -> QueryRes
             maintainer_id: maint.id,
         })
         .execute(conn)?;
-    let new_id = crate::models::last_insert_rowid(conn);
+    let new_id = crate::models::last_insert_id(conn);
     let mod_info = module::get_by_id(conn, module_id).unwrap();
     let _ = system_event::register_new_module_maintainer(
         conn,
@@ -96,13 +93,13 @@ pub fn add(conn: &mut SqliteConnection, module_id: &i64, name: &str) -> QueryRes
     })
 }
 
-pub fn delete_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> QueryResult<usize> {
+pub fn delete_by_module_id(conn: &mut DbConnection, module_id: &i64) -> QueryResult<usize> {
     diesel::delete(module_maintainer::table.filter(module_maintainer::module_id.eq(module_id)))
         .execute(conn)
 }
 
 pub fn delete_by_module_id_maintainer_id(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_id: &i64,
     maintainer_id: &i64,
 ) -> QueryResult<usize> {
@@ -2,13 +2,15 @@
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::dialect;
 use crate::schema::{module_model, module_model_field, module_model_method};
+use crate::DbConnection;
 
 use super::module_code_analysis::ModelAnalysisInfo;
 use super::{module_model_field as field_ops, module_model_method as method_ops};
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = module_model, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = module_model, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub module_id: i64,
@@ -33,6 +35,91 @@ impl Model {
     }
 }
 
+/// One module touching a model, as of the module's current version.
+#[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
+pub struct ModelUsageInfo {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub module_model_id: i64,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub technical_name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub module_name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub organization: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub repository: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub class_name: String,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    pub is_new_model: bool,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub inherit_from: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub attrs: Option<String>,
+}
+
+#[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
+pub struct ModelNameCountInfo {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub model_name: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub num_modules: i64,
+}
+
+// Restricts a query on `mm` (module_model) to the snapshot of each module's
+// current version, the one `module.version_module` points to.
+pub(crate) const CURRENT_SNAPSHOT_JOIN: &str = "\
+    INNER JOIN module_version as mv ON mv.id = mm.module_version_id \
+    INNER JOIN module as mod ON mod.id = mv.module_id AND mod.version_module = mv.version_module \
+    INNER JOIN gh_repository as gh_repo ON gh_repo.id = mod.gh_repository_id \
+    INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id";
+
+/// Every module defining or extending `model_name` at `version_odoo`, those
+/// defining it first.
+pub fn get_current_by_model_name(
+    conn: &mut DbConnection,
+    version_odoo: &u8,
+    model_name: &str,
+) -> Vec<ModelUsageInfo> {
+    dialect::sql_query(format!(
+        "SELECT mm.id as module_model_id, mod.technical_name, mod.name as module_name, \
+         gh_org.name as organization, gh_repo.name as repository, mm.class_name, \
+         mm.is_new_model, mm.inherit_from, mm.attrs \
+         FROM module_model as mm \
+         {CURRENT_SNAPSHOT_JOIN} \
+         WHERE mod.version_odoo = ? AND mm.model_name = ? \
+         ORDER BY mm.is_new_model DESC, mod.technical_name, mm.id"
+    ))
+    .bind::<diesel::sql_types::Integer, _>(*version_odoo as i32)
+    .bind::<diesel::sql_types::Text, _>(model_name)
+    .load::<ModelUsageInfo>(conn)
+    .expect("DB error in module_model::get_current_by_model_name")
+}
+
+/// Model names at `version_odoo` containing `term`, with how many modules
+/// touch each, the most touched first.
+pub fn search_current_model_names(
+    conn: &mut DbConnection,
+    version_odoo: &u8,
+    term: &str,
+    limit: i64,
+) -> Vec<ModelNameCountInfo> {
+    dialect::sql_query(format!(
+        "SELECT mm.model_name, COUNT(DISTINCT mod.id) as num_modules \
+         FROM module_model as mm \
+         {CURRENT_SNAPSHOT_JOIN} \
+         WHERE mod.version_odoo = ? AND LOWER(mm.model_name) LIKE LOWER(?) ESCAPE '\\' \
+         GROUP BY mm.model_name \
+         ORDER BY num_modules DESC, mm.model_name \
+         LIMIT ?"
+    ))
+    .bind::<diesel::sql_types::Integer, _>(*version_odoo as i32)
+    .bind::<diesel::sql_types::Text, _>(crate::models::like_contains(term))
+    .bind::<diesel::sql_types::BigInt, _>(limit)
+    .load::<ModelNameCountInfo>(conn)
+    .expect("DB error in module_model::search_current_model_names")
+}
+
 #[derive(Insertable)]
 #[diesel(table_name = module_model)]
 struct NewModuleModel<'a> {
@@ -48,7 +135,7 @@ struct NewModuleModel<'a> {
 
 /// All models ever recorded for this module, across every historical version
 /// (uses the denormalized `module_id` column, no join through module_version).
-pub fn get_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Vec<Model> {
+pub fn get_by_module_id(conn: &mut DbConnection, module_id: &i64) -> Vec<Model> {
     module_model::table
         .filter(module_model::module_id.eq(module_id))
         .order(module_model::model_name.asc())
@@ -58,10 +145,7 @@ pub fn get_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Vec<Mod
 
 /// Models for one specific version snapshot - what callers resolving "latest"
 /// or a historical `version_module` actually want.
-pub fn get_by_module_version_id(
-    conn: &mut SqliteConnection,
-    module_version_id: &i64,
-) -> Vec<Model> {
+pub fn get_by_module_version_id(conn: &mut DbConnection, module_version_id: &i64) -> Vec<Model> {
     module_model::table
         .filter(module_model::module_version_id.eq(module_version_id))
         .order(module_model::model_name.asc())
@@ -72,7 +156,7 @@ pub fn get_by_module_version_id(
 /// Deletes every model (and their fields/methods) for this module, across all
 /// versions - used when the module itself is being removed entirely, not on
 /// routine re-analysis (see `replace_for_module`, which is scoped tighter).
-pub fn delete_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> QueryResult<()> {
+pub fn delete_by_module_id(conn: &mut DbConnection, module_id: &i64) -> QueryResult<()> {
     let existing_ids: Vec<i64> = module_model::table
         .filter(module_model::module_id.eq(module_id))
         .select(module_model::id)
@@ -96,18 +180,11 @@ pub fn delete_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Quer
     Ok(())
 }
 
-/// Replaces every model (and their fields/methods) for this version snapshot
-/// with `models`. FK enforcement is off (see lib.rs), so children must be
-/// deleted by hand before the parent rows disappear - otherwise re-analyzing
-/// a module leaves module_model_field/module_model_method rows pointing at
-/// dead ids. Scoped to `module_version_id`, not `module_id`: filtering either
-/// the lookup or the deletes by `module_id` here would wipe every historical
-/// version's snapshot on every run, defeating the point of `module_version`.
-pub fn replace_for_module(
-    conn: &mut SqliteConnection,
-    module_id: &i64,
+/// Deletes the models (and their fields/methods) of one version snapshot.
+/// Shared by `replace_for_module` and `module_version::prune_snapshots`.
+pub fn delete_by_module_version_id(
+    conn: &mut DbConnection,
     module_version_id: &i64,
-    models: &[ModelAnalysisInfo],
 ) -> QueryResult<()> {
     let existing_ids: Vec<i64> = module_model::table
         .filter(module_model::module_version_id.eq(module_version_id))
@@ -131,6 +208,24 @@ pub fn replace_for_module(
         .execute(conn)?;
     }
 
+    Ok(())
+}
+
+/// Replaces every model (and their fields/methods) for this version snapshot
+/// with `models`. FK enforcement is off (see lib.rs), so children must be
+/// deleted by hand before the parent rows disappear - otherwise re-analyzing
+/// a module leaves module_model_field/module_model_method rows pointing at
+/// dead ids. Scoped to `module_version_id`, not `module_id`: filtering either
+/// the lookup or the deletes by `module_id` here would wipe every historical
+/// version's snapshot on every run, defeating the point of `module_version`.
+pub fn replace_for_module(
+    conn: &mut DbConnection,
+    module_id: &i64,
+    module_version_id: &i64,
+    models: &[ModelAnalysisInfo],
+) -> QueryResult<()> {
+    delete_by_module_version_id(conn, module_version_id)?;
+
     for model_info in models {
         let inherit_from = if model_info.inherit_from.is_empty() {
             None
@@ -150,7 +245,7 @@ pub fn replace_for_module(
                 module_version_id: *module_version_id,
             })
             .execute(conn)?;
-        let module_model_id = crate::models::last_insert_rowid(conn);
+        let module_model_id = crate::models::last_insert_id(conn);
 
         field_ops::add_many(conn, &module_model_id, &model_info.fields)?;
         method_ops::add_many(conn, &module_model_id, &model_info.methods)?;
@@ -2,12 +2,14 @@
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::dialect;
 use crate::schema::module_model_field;
+use crate::DbConnection;
 
 use super::module_code_analysis::FieldAnalysisInfo;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = module_model_field, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = module_model_field, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub module_model_id: i64,
@@ -28,6 +30,61 @@ impl Model {
     }
 }
 
+/// A field some module adds to a model, as of the module's current version.
+#[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
+pub struct FieldUsageInfo {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub field_type: String,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub relation: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub attrs: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub model_name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub technical_name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub organization: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub repository: String,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub version_odoo: i32,
+}
+
+/// Fields whose name contains `term` (case-insensitive), across every Odoo
+/// version unless `version_odoo` is given. Exact matches come first, then
+/// the newest versions.
+pub fn search_current(
+    conn: &mut DbConnection,
+    term: &str,
+    version_odoo: Option<&u8>,
+    limit: i64,
+) -> Vec<FieldUsageInfo> {
+    dialect::sql_query(format!(
+        "SELECT f.name, f.field_type, f.relation, f.attrs, mm.model_name, mod.technical_name, \
+         gh_org.name as organization, gh_repo.name as repository, mod.version_odoo \
+         FROM module_model_field as f \
+         INNER JOIN module_model as mm ON mm.id = f.module_model_id \
+         {} \
+         WHERE LOWER(f.name) LIKE LOWER(?1) ESCAPE '\\' \
+           AND (?2 IS NULL OR mod.version_odoo = ?2) \
+         ORDER BY CASE WHEN LOWER(f.name) = LOWER(?3) THEN 0 ELSE 1 END, mod.version_odoo DESC, \
+         f.name, mm.model_name, mod.technical_name \
+         LIMIT ?4",
+        super::module_model::CURRENT_SNAPSHOT_JOIN
+    ))
+    .bind::<diesel::sql_types::Text, _>(crate::models::like_contains(term))
+    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Integer>, _>(
+        version_odoo.map(|v| *v as i32),
+    )
+    .bind::<diesel::sql_types::Text, _>(term)
+    .bind::<diesel::sql_types::BigInt, _>(limit)
+    .load::<FieldUsageInfo>(conn)
+    .expect("DB error in module_model_field::search_current")
+}
+
 #[derive(Insertable)]
 #[diesel(table_name = module_model_field)]
 struct NewModuleModelField<'a> {
@@ -38,7 +95,7 @@ struct NewModuleModelField<'a> {
     attrs: Option<&'a str>,
 }
 
-pub fn get_by_module_model_id(conn: &mut SqliteConnection, module_model_id: &i64) -> Vec<Model> {
+pub fn get_by_module_model_id(conn: &mut DbConnection, module_model_id: &i64) -> Vec<Model> {
     module_model_field::table
         .filter(module_model_field::module_model_id.eq(module_model_id))
         .order(module_model_field::name.asc())
@@ -47,7 +104,7 @@ pub fn get_by_module_model_id(conn: &mut SqliteConnection, module_model_id: &i64
 }
 
 pub fn add_many(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_model_id: &i64,
     fields: &[FieldAnalysisInfo],
 ) -> QueryResult<()> {
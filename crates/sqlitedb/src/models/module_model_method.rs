@@ -3,11 +3,12 @@ use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::schema::module_model_method;
+use crate::DbConnection;
 
 use super::module_code_analysis::MethodAnalysisInfo;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = module_model_method, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = module_model_method, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub module_model_id: i64,
@@ -39,7 +40,7 @@ struct NewModuleModelMethod<'a> {
     docstring: Option<&'a str>,
 }
 
-pub fn get_by_module_model_id(conn: &mut SqliteConnection, module_model_id: &i64) -> Vec<Model> {
+pub fn get_by_module_model_id(conn: &mut DbConnection, module_model_id: &i64) -> Vec<Model> {
     module_model_method::table
         .filter(module_model_method::module_model_id.eq(module_model_id))
         .order(module_model_method::name.asc())
@@ -48,7 +49,7 @@ pub fn get_by_module_model_id(conn: &mut SqliteConnection, module_model_id: &i64
 }
 
 pub fn add_many(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_model_id: &i64,
     methods: &[MethodAnalysisInfo],
 ) -> QueryResult<()> {
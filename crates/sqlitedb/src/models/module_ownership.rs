@@ -0,0 +1,175 @@
+// Copyright Alexandre D. Díaz
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::dialect;
+use crate::schema::module_ownership;
+use crate::DbConnection;
+
+use super::committer;
+use super::module_code_analysis::OwnershipAnalysisInfo;
+
+// Share of the live lines the top owners must hold together for the
+// bus factor: the fewest people who'd take over half the code with them.
+const BUS_FACTOR_SHARE: f64 = 0.5;
+
+#[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
+#[diesel(table_name = module_ownership, check_for_backend(crate::DbBackend))]
+pub struct Model {
+    pub id: i64,
+    pub module_id: i64,
+    pub committer_id: i64,
+    pub lines: i32,
+    pub module_version_id: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = module_ownership)]
+struct NewModuleOwnership {
+    module_id: i64,
+    committer_id: i64,
+    lines: i32,
+    module_version_id: i64,
+}
+
+#[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
+pub struct OwnerInfo {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub name: String,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub lines: i32,
+}
+
+pub fn delete_by_module_id(conn: &mut DbConnection, module_id: &i64) -> QueryResult<usize> {
+    diesel::delete(module_ownership::table.filter(module_ownership::module_id.eq(module_id)))
+        .execute(conn)
+}
+
+/// Drops the owners of one version snapshot (see `module_version::prune_snapshots`).
+pub fn delete_by_module_version_id(
+    conn: &mut DbConnection,
+    module_version_id: &i64,
+) -> QueryResult<usize> {
+    diesel::delete(
+        module_ownership::table.filter(module_ownership::module_version_id.eq(module_version_id)),
+    )
+    .execute(conn)
+}
+
+/// Replaces every ownership row for this version snapshot. Owners are
+/// resolved through `committer::add`, so blame identities land on the same
+/// committer rows as the commit stats.
+pub fn replace_for_module(
+    conn: &mut DbConnection,
+    module_id: &i64,
+    module_version_id: &i64,
+    owners: &[OwnershipAnalysisInfo],
+) -> QueryResult<()> {
+    delete_by_module_version_id(conn, module_version_id)?;
+
+    let mut new_rows: Vec<NewModuleOwnership> = Vec::new();
+    for owner in owners.iter().filter(|o| o.lines > 0) {
+        let com = committer::add(conn, &owner.name, &owner.emails)?;
+        // Two identities can collapse into one committer (e.g. a global
+        // mailmap entry added after the stats were collected).
+        if let Some(row) = new_rows.iter_mut().find(|r| r.committer_id == com.id) {
+            row.lines += owner.lines as i32;
+            continue;
+        }
+        new_rows.push(NewModuleOwnership {
+            module_id: *module_id,
+            committer_id: com.id,
+            lines: owner.lines as i32,
+            module_version_id: *module_version_id,
+        });
+    }
+
+    if !new_rows.is_empty() {
+        diesel::insert_into(module_ownership::table)
+            .values(&new_rows)
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+/// Owners of one version snapshot, largest first. Bots are left out, like
+/// in the committer rankings.
+pub fn get_by_module_version_id(
+    conn: &mut DbConnection,
+    module_version_id: &i64,
+) -> Vec<OwnerInfo> {
+    dialect::sql_query(
+        "SELECT com.name as name, own.lines as lines \
+         FROM module_ownership as own \
+         INNER JOIN committer as com ON own.committer_id = com.id \
+         WHERE own.module_version_id = ? AND NOT com.is_bot \
+         ORDER BY own.lines DESC, com.name ASC",
+    )
+    .bind::<diesel::sql_types::BigInt, _>(module_version_id)
+    .load::<OwnerInfo>(conn)
+    .expect("DB error in module_ownership::get_by_module_version_id")
+}
+
+/// Fewest owners holding more than half of the live lines together, from
+/// `lines` sorted largest first (as `get_by_module_version_id` returns
+/// them). `None` when there is nothing to own.
+pub fn bus_factor(lines: &[i32]) -> Option<usize> {
+    let total: i64 = lines.iter().map(|l| *l as i64).sum();
+    if total <= 0 {
+        return None;
+    }
+    let mut owned = 0i64;
+    for (index, l) in lines.iter().enumerate() {
+        owned += *l as i64;
+        if owned as f64 > total as f64 * BUS_FACTOR_SHARE {
+            return Some(index + 1);
+        }
+    }
+    Some(lines.len())
+}
+
+#[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
+pub struct CommitterOwnershipInfo {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub technical_name: String,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub version_odoo: i32,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub organization: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub repository: String,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub lines: i32,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub total_lines: i64,
+}
+
+/// Lines a committer owns in every module's *current* snapshot (mirrors
+/// module_version::resolve_current, joined in SQL), with the snapshot's
+/// total live lines so callers can derive the share. Bots' lines stay out of
+/// the total, as they stay out of `get_by_module_version_id`.
+pub fn get_current_by_committer_name(
+    conn: &mut DbConnection,
+    committer_name: &str,
+) -> Vec<CommitterOwnershipInfo> {
+    dialect::sql_query(
+        "SELECT mod.technical_name, mod.version_odoo, gh_org.name as organization, \
+         gh_repo.name as repository, own.lines as lines, \
+         (SELECT SUM(t.lines) FROM module_ownership as t \
+           INNER JOIN committer as t_com ON t_com.id = t.committer_id \
+           WHERE t.module_version_id = own.module_version_id \
+           AND (NOT t_com.is_bot OR t.committer_id = own.committer_id)) as total_lines \
+         FROM module_ownership as own \
+         INNER JOIN committer as com ON own.committer_id = com.id \
+         INNER JOIN module_version as mv ON mv.id = own.module_version_id \
+         INNER JOIN module as mod ON mod.id = own.module_id AND mod.version_module = mv.version_module \
+         INNER JOIN gh_repository as gh_repo ON gh_repo.id = mod.gh_repository_id \
+         INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id \
+         WHERE com.name = ? \
+         ORDER BY mod.version_odoo DESC, own.lines DESC",
+    )
+    .bind::<diesel::sql_types::Text, _>(committer_name)
+    .load::<CommitterOwnershipInfo>(conn)
+    .expect("DB error in module_ownership::get_current_by_committer_name")
+}
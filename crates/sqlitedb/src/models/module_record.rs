@@ -3,11 +3,12 @@ use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::schema::module_record;
+use crate::DbConnection;
 
 use super::module_code_analysis::RecordAnalysisInfo;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = module_record, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = module_record, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub module_id: i64,
@@ -44,7 +45,7 @@ struct NewModuleRecord<'a> {
 /// All records ever recorded for this module, across every historical
 /// version (uses the denormalized `module_id` column, no join through
 /// module_version).
-pub fn get_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Vec<Model> {
+pub fn get_by_module_id(conn: &mut DbConnection, module_id: &i64) -> Vec<Model> {
     module_record::table
         .filter(module_record::module_id.eq(module_id))
         .order((module_record::model.asc(), module_record::xml_id.asc()))
@@ -54,10 +55,7 @@ pub fn get_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Vec<Mod
 
 /// Records for one specific version snapshot - what callers resolving
 /// "latest" or a historical `version_module` actually want.
-pub fn get_by_module_version_id(
-    conn: &mut SqliteConnection,
-    module_version_id: &i64,
-) -> Vec<Model> {
+pub fn get_by_module_version_id(conn: &mut DbConnection, module_version_id: &i64) -> Vec<Model> {
     module_record::table
         .filter(module_record::module_version_id.eq(module_version_id))
         .order((module_record::model.asc(), module_record::xml_id.asc()))
@@ -65,26 +63,34 @@ pub fn get_by_module_version_id(
         .expect("DB error in module_record::get_by_module_version_id")
 }
 
-pub fn delete_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> QueryResult<usize> {
+pub fn delete_by_module_id(conn: &mut DbConnection, module_id: &i64) -> QueryResult<usize> {
     diesel::delete(module_record::table.filter(module_record::module_id.eq(module_id)))
         .execute(conn)
 }
 
+/// Drops the records of one version snapshot (see `module_version::prune_snapshots`).
+pub fn delete_by_module_version_id(
+    conn: &mut DbConnection,
+    module_version_id: &i64,
+) -> QueryResult<usize> {
+    diesel::delete(
+        module_record::table.filter(module_record::module_version_id.eq(module_version_id)),
+    )
+    .execute(conn)
+}
+
 /// Replaces every record row for this version snapshot with `records`. The
 /// collector recomputes the full list from the module's XML/CSV files on
 /// every run, so delete+insert is simpler than diffing (mirrors
 /// module_view) - but scoped to `module_version_id`, not `module_id`, so
 /// re-analyzing the current version never touches older versions' snapshots.
 pub fn replace_for_module(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_id: &i64,
     module_version_id: &i64,
     records: &[RecordAnalysisInfo],
 ) -> QueryResult<()> {
-    diesel::delete(
-        module_record::table.filter(module_record::module_version_id.eq(module_version_id)),
-    )
-    .execute(conn)?;
+    delete_by_module_version_id(conn, module_version_id)?;
 
     let fields_json: Vec<Option<String>> = records
         .iter()
@@ -0,0 +1,296 @@
+// Copyright Alexandre D. Díaz
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::dialect;
+use crate::DbConnection;
+
+// The index is FTS5's `module_search` virtual table on SQLite and a tsvector
+// column on PostgreSQL (see the migrations). The fragments below hide which;
+// the ones needing the MATCH expression from `match_query` take the number
+// of its `?N` parameter.
+
+const MARK_START: char = '\u{2}';
+const MARK_END: char = '\u{3}';
+
+/// The indexed module's id, to join `module_search` with `module`.
+#[cfg(not(feature = "postgres"))]
+pub const MODULE_ID: &str = "module_search.rowid";
+#[cfg(feature = "postgres")]
+pub const MODULE_ID: &str = "module_search.module_id";
+
+/// WHERE condition keeping the modules that match.
+pub fn matches(param: usize) -> String {
+    if cfg!(feature = "postgres") {
+        format!("module_search.document @@ to_tsquery('module_search', ?{param})")
+    } else {
+        format!("module_search MATCH ?{param}")
+    }
+}
+
+/// Weighted score, lower is better. BM25 on SQLite, in `module_search`'s
+/// column order: technical_name, name, summary, description, docs, models,
+/// fields, views. PostgreSQL weighs the same groups through `document`.
+/// Not usable inside aggregates on SQLite: see `plain_rank`.
+pub fn rank(param: usize) -> String {
+    if cfg!(feature = "postgres") {
+        format!(
+            "-ts_rank('{{0.1, 0.2, 0.4, 1.0}}', module_search.document, \
+             to_tsquery('module_search', ?{param}))"
+        )
+    } else {
+        "bm25(module_search, 10.0, 5.0, 4.0, 1.0, 1.0, 2.0, 1.0, 1.0)".to_string()
+    }
+}
+
+/// Unweighted score, lower is better, for `MIN()` and friends.
+pub fn plain_rank(param: usize) -> String {
+    if cfg!(feature = "postgres") {
+        format!("-ts_rank(module_search.document, to_tsquery('module_search', ?{param}))")
+    } else {
+        "module_search.rank".to_string()
+    }
+}
+
+/// Best-matching fragment of what was indexed. Matches are wrapped in
+/// control characters rather than HTML, so the text can be escaped before
+/// `highlight` turns them into `<mark>`.
+pub fn snippet(param: usize) -> String {
+    if cfg!(feature = "postgres") {
+        format!(
+            "ts_headline('module_search', {content}, to_tsquery('module_search', ?{param}), \
+             'StartSel=' || chr(2) || ', StopSel=' || chr(3) || \
+             ', MaxWords=16, MinWords=8, MaxFragments=1, FragmentDelimiter=…')",
+            content = words("module_search.content"),
+        )
+    } else {
+        "snippet(module_search, -1, char(2), char(3), '…', 16)".to_string()
+    }
+}
+
+// PostgreSQL's parser reads `sale.order` or `static/src` as a single host or
+// file token; split them so their words can be searched for.
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+fn words(expr: &str) -> String {
+    format!(r"regexp_replace({expr}, '(\w)[./](?=\w)', '\1 ', 'g')")
+}
+
+// Same row the migration filled the index with: the module plus the code
+// analysis of its current version only, so older versions' leftovers don't
+// make a module match.
+#[cfg(not(feature = "postgres"))]
+fn index_rows() -> String {
+    "INSERT INTO module_search(rowid, technical_name, name, summary, \
+     description, docs, models, fields, views) \
+     SELECT mod.id, mod.technical_name, mod.name, mod.summary, mod.description, \
+     COALESCE(mod.installation, '') || ' ' || COALESCE(mod.usage, ''), \
+     (SELECT GROUP_CONCAT(DISTINCT mm.model_name) \
+      FROM module_model as mm \
+      INNER JOIN module_version as mv ON mv.id = mm.module_version_id \
+      WHERE mm.module_id = mod.id AND mv.version_module = mod.version_module), \
+     (SELECT GROUP_CONCAT(mmf.name || ' ' || COALESCE( \
+             CASE WHEN json_valid(mmf.attrs) THEN json_extract(mmf.attrs, '$.string') END, ''), ' ') \
+      FROM module_model_field as mmf \
+      INNER JOIN module_model as mm ON mm.id = mmf.module_model_id \
+      INNER JOIN module_version as mv ON mv.id = mm.module_version_id \
+      WHERE mm.module_id = mod.id AND mv.version_module = mod.version_module), \
+     (SELECT GROUP_CONCAT(COALESCE(mvw.name, '') || ' ' || mvw.xml_id, ' ') \
+      FROM module_view as mvw \
+      INNER JOIN module_version as mv ON mv.id = mvw.module_version_id \
+      WHERE mvw.module_id = mod.id AND mv.version_module = mod.version_module) \
+     FROM module as mod \
+     WHERE mod.id = ?"
+        .to_string()
+}
+
+// The same columns, weighted A (technical name), B (name and summary),
+// C (models) and D (the rest). `content`, which snippets are cut from,
+// leaves the names out: they're shown next to the snippet anyway. Field
+// labels are taken from the JSON attrs with a regex, as PostgreSQL 15 can't
+// test whether text is valid JSON.
+#[cfg(feature = "postgres")]
+fn index_rows() -> String {
+    let vector = |expr: &str, weight: char| {
+        format!(
+            "setweight(to_tsvector('module_search', {}), '{weight}')",
+            words(&format!("COALESCE({expr}, '')"))
+        )
+    };
+    format!(
+        "INSERT INTO module_search(module_id, content, document) \
+         SELECT doc.id, \
+         concat_ws(' ', doc.summary, doc.description, doc.docs, doc.models, doc.fields, \
+                   doc.views), \
+         {technical_name} || {name} || {models} || {rest} \
+         FROM (SELECT mod.id, mod.technical_name, mod.name, mod.summary, mod.description, \
+           concat_ws(' ', mod.installation, mod.usage) as docs, \
+           (SELECT STRING_AGG(DISTINCT mm.model_name, ',') \
+            FROM module_model as mm \
+            INNER JOIN module_version as mv ON mv.id = mm.module_version_id \
+            WHERE mm.module_id = mod.id AND mv.version_module = mod.version_module) as models, \
+           (SELECT STRING_AGG(mmf.name || ' ' || COALESCE( \
+                   substring(mmf.attrs from '\"string\"\\s*:\\s*\"((?:[^\"\\\\]|\\\\.)*)\"'), ''), ' ') \
+            FROM module_model_field as mmf \
+            INNER JOIN module_model as mm ON mm.id = mmf.module_model_id \
+            INNER JOIN module_version as mv ON mv.id = mm.module_version_id \
+            WHERE mm.module_id = mod.id AND mv.version_module = mod.version_module) as fields, \
+           (SELECT STRING_AGG(COALESCE(mvw.name, '') || ' ' || mvw.xml_id, ' ') \
+            FROM module_view as mvw \
+            INNER JOIN module_version as mv ON mv.id = mvw.module_version_id \
+            WHERE mvw.module_id = mod.id AND mv.version_module = mod.version_module) as views \
+           FROM module as mod \
+           WHERE mod.id = ?) as doc",
+        technical_name = vector("doc.technical_name", 'A'),
+        name = vector("concat_ws(' ', doc.name, doc.summary)", 'B'),
+        models = vector("doc.models", 'C'),
+        rest = vector(
+            "concat_ws(' ', doc.description, doc.docs, doc.fields, doc.views)",
+            'D'
+        ),
+    )
+}
+
+#[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
+pub struct ModuleSearchInfo {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub technical_name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub name: String,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub version_odoo: i32,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub organization: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub repository: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub snippet: String,
+}
+
+/// Rebuilds the module's index row from what's stored now. Called once the
+/// collector is done saving the module and its code analysis.
+pub fn refresh(conn: &mut DbConnection, module_id: &i64) -> QueryResult<usize> {
+    delete_by_module_id(conn, module_id)?;
+    dialect::sql_query(index_rows())
+        .bind::<diesel::sql_types::BigInt, _>(module_id)
+        .execute(conn)
+}
+
+pub fn delete_by_module_id(conn: &mut DbConnection, module_id: &i64) -> QueryResult<usize> {
+    dialect::sql_query(format!("DELETE FROM module_search WHERE {MODULE_ID} = ?"))
+        .bind::<diesel::sql_types::BigInt, _>(module_id)
+        .execute(conn)
+}
+
+/// Turns user input into a MATCH expression for `matches`: every word must
+/// match the start of a token, in any of `columns` (all of them when
+/// empty). Words are quoted, so FTS5 operators typed by the user are just
+/// text. `None` when nothing searchable is left.
+#[cfg(not(feature = "postgres"))]
+pub fn match_query(term: &str, columns: &[&str]) -> Option<String> {
+    let words: Vec<String> = term
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    let words = words.join(" ");
+    if columns.is_empty() {
+        Some(words)
+    } else {
+        Some(format!("{{{}}} : ({words})", columns.join(" ")))
+    }
+}
+
+/// Turns user input into a tsquery for `matches`: every word must match the
+/// start of a token, in any of `columns` (all of them when empty). Only the
+/// alphanumeric runs of a word are kept, as consecutive prefixes, so
+/// tsquery operators typed by the user are dropped. `None` when nothing
+/// searchable is left.
+#[cfg(feature = "postgres")]
+pub fn match_query(term: &str, columns: &[&str]) -> Option<String> {
+    // The weight each column was indexed with (see `index_rows`).
+    let weights: String = columns
+        .iter()
+        .map(|column| match *column {
+            "technical_name" => 'A',
+            "name" | "summary" => 'B',
+            "models" => 'C',
+            _ => 'D',
+        })
+        .collect();
+    let words: Vec<String> = term
+        .split_whitespace()
+        .filter_map(|word| {
+            let parts: Vec<String> = word
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|part| !part.is_empty())
+                .map(|part| format!("{part}:*{weights}"))
+                .collect();
+            (!parts.is_empty()).then(|| parts.join(" <-> "))
+        })
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    Some(words.join(" & "))
+}
+
+/// HTML-escapes a `snippet` and turns its match markers into `<mark>`.
+pub fn highlight(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len());
+    for c in snippet.chars() {
+        match c {
+            MARK_START => html.push_str("<mark>"),
+            MARK_END => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            _ => html.push(c),
+        }
+    }
+    html
+}
+
+/// Full-text search over every indexed column, best match first, one row
+/// per module and Odoo version.
+pub fn search(
+    conn: &mut DbConnection,
+    term: &str,
+    version_odoo: Option<&u8>,
+    limit: i64,
+) -> Vec<ModuleSearchInfo> {
+    let Some(query) = match_query(term, &[]) else {
+        return Vec::new();
+    };
+    dialect::sql_query(format!(
+        "SELECT mod.technical_name, mod.name, mod.version_odoo, \
+         gh_org.name as organization, gh_repo.name as repository, {snippet} as snippet \
+         FROM module_search \
+         INNER JOIN module as mod ON mod.id = {MODULE_ID} \
+         INNER JOIN gh_repository as gh_repo ON gh_repo.id = mod.gh_repository_id \
+         INNER JOIN gh_organization as gh_org ON gh_org.id = gh_repo.gh_organization_id \
+         WHERE {matches} AND (?2 IS NULL OR mod.version_odoo = ?2) \
+         ORDER BY {rank}, mod.technical_name, mod.version_odoo DESC \
+         LIMIT ?3",
+        snippet = snippet(1),
+        matches = matches(1),
+        rank = rank(1),
+    ))
+    .bind::<diesel::sql_types::Text, _>(query)
+    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Integer>, _>(
+        version_odoo.map(|v| *v as i32),
+    )
+    .bind::<diesel::sql_types::BigInt, _>(limit)
+    .load::<ModuleSearchInfo>(conn)
+    .expect("DB error in module_search::search")
+    .into_iter()
+    .map(|row| ModuleSearchInfo {
+        snippet: highlight(&row.snippet),
+        ..row
+    })
+    .collect()
+}
@@ -2,16 +2,18 @@
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::dialect;
 use crate::schema::module_security_warning;
+use crate::DbConnection;
 
 // Severity values, matching system_event's string convention. "error" is
-// grave (surfaced on the module detail page); "warning" only goes to the
-// system_event log.
+// grave (surfaced on the module detail page); "warning" is minor, listed on
+// /security when asked for and logged as a system_event.
 pub const SEVERITY_ERROR: &str = "error";
 pub const SEVERITY_WARNING: &str = "warning";
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = module_security_warning, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = module_security_warning, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub module_id: i64,
@@ -63,11 +65,11 @@ pub struct ModuleSecurityWarningFullInfo {
 
 /// Every warning for every module's *current* snapshot (mirrors
 /// module_version::resolve_current, joined in SQL to avoid an N+1 query per
-/// module) - for the site-wide modules overview page. Unlike the module
+/// module) - for the site-wide modules overview and /security. Unlike the module
 /// detail page, this includes "warning" (minor) severity too, not just
 /// "error", since the whole point of this list is "by severity".
-pub fn get_all_current(conn: &mut SqliteConnection) -> Vec<ModuleSecurityWarningFullInfo> {
-    diesel::sql_query(
+pub fn get_all_current(conn: &mut DbConnection) -> Vec<ModuleSecurityWarningFullInfo> {
+    dialect::sql_query(
         "SELECT msw.severity, msw.code, msw.message, msw.xml_id, \
          mod.version_odoo, mod.technical_name, gh_org.name as org_name \
          FROM module_security_warning as msw \
@@ -81,12 +83,38 @@ pub fn get_all_current(conn: &mut SqliteConnection) -> Vec<ModuleSecurityWarning
     .expect("DB error in module_security_warning::get_all_current")
 }
 
+#[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
+pub struct SecurityCountInfo {
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub version_odoo: i32,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub severity: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub count: i64,
+}
+
+/// Findings of the current snapshot of one repository's modules, counted by
+/// Odoo version and severity.
+pub fn count_current_by_repository(
+    conn: &mut DbConnection,
+    gh_repository_id: &i64,
+) -> Vec<SecurityCountInfo> {
+    dialect::sql_query(
+        "SELECT mod.version_odoo, msw.severity, COUNT(*) as count \
+         FROM module_security_warning as msw \
+         INNER JOIN module_version as mv ON mv.id = msw.module_version_id \
+         INNER JOIN module as mod ON mod.id = msw.module_id AND mod.version_module = mv.version_module \
+         WHERE mod.gh_repository_id = ? \
+         GROUP BY mod.version_odoo, msw.severity",
+    )
+    .bind::<diesel::sql_types::BigInt, _>(gh_repository_id)
+    .load::<SecurityCountInfo>(conn)
+    .expect("DB error in module_security_warning::count_current_by_repository")
+}
+
 /// Warnings for one specific version snapshot - what the module detail page
 /// and API resolve to.
-pub fn get_by_module_version_id(
-    conn: &mut SqliteConnection,
-    module_version_id: &i64,
-) -> Vec<Model> {
+pub fn get_by_module_version_id(conn: &mut DbConnection, module_version_id: &i64) -> Vec<Model> {
     module_security_warning::table
         .filter(module_security_warning::module_version_id.eq(module_version_id))
         .order((
@@ -97,22 +125,30 @@ pub fn get_by_module_version_id(
         .expect("DB error in module_security_warning::get_by_module_version_id")
 }
 
+/// Drops the warnings of one version snapshot (see `module_version::prune_snapshots`).
+pub fn delete_by_module_version_id(
+    conn: &mut DbConnection,
+    module_version_id: &i64,
+) -> QueryResult<usize> {
+    diesel::delete(
+        module_security_warning::table
+            .filter(module_security_warning::module_version_id.eq(module_version_id)),
+    )
+    .execute(conn)
+}
+
 /// Replaces every warning row for this version snapshot. The collector
 /// recomputes the full list on every run, so delete+insert is simpler than
 /// diffing - scoped to `module_version_id`, not `module_id`, so re-analyzing
 /// the current version never touches older versions' snapshots (mirrors
 /// module_record).
 pub fn replace_for_module(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_id: &i64,
     module_version_id: &i64,
     warnings: &[SecurityWarningInfo],
 ) -> QueryResult<()> {
-    diesel::delete(
-        module_security_warning::table
-            .filter(module_security_warning::module_version_id.eq(module_version_id)),
-    )
-    .execute(conn)?;
+    delete_by_module_version_id(conn, module_version_id)?;
 
     let new_rows: Vec<NewModuleSecurityWarning> = warnings
         .iter()
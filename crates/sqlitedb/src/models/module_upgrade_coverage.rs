@@ -0,0 +1,133 @@
+// Copyright Alexandre D. Díaz
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::module_upgrade_coverage;
+use crate::utils::date::get_sqlite_utc_now;
+use crate::DbConnection;
+
+// Statuses OpenUpgrade uses for a module whose migration needs no further
+// work; anything else (empty, 'Partial', 'TODO', ...) is not covered yet.
+const COVERED_STATUS_PREFIXES: [&str; 3] = ["done", "nothing to do", "no db layout changes"];
+
+#[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
+#[diesel(table_name = module_upgrade_coverage, check_for_backend(crate::DbBackend))]
+pub struct Model {
+    pub id: i64,
+    pub technical_name: String,
+    pub version_odoo: i32,
+    pub status: String,
+    pub note: Option<String>,
+    pub is_new: bool,
+    pub is_removed: bool,
+    pub update_date: String,
+}
+
+impl Model {
+    pub fn is_covered(&self) -> bool {
+        is_covered_status(&self.status)
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = module_upgrade_coverage)]
+struct NewModuleUpgradeCoverage<'a> {
+    technical_name: &'a str,
+    version_odoo: i32,
+    status: &'a str,
+    note: Option<&'a str>,
+    is_new: bool,
+    is_removed: bool,
+    update_date: &'a str,
+}
+
+/// One row of an OpenUpgrade coverage table, as parsed by the collector.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UpgradeCoverageInfo {
+    pub technical_name: String,
+    pub status: String,
+    pub note: Option<String>,
+    pub is_new: bool,
+    pub is_removed: bool,
+}
+
+pub fn is_covered_status(status: &str) -> bool {
+    let status = status.trim().to_lowercase();
+    COVERED_STATUS_PREFIXES
+        .iter()
+        .any(|prefix| status.starts_with(prefix))
+}
+
+/// Replaces the coverage of one target version with a freshly parsed
+/// coverage table (delete+insert, like module_record).
+pub fn replace_for_version(
+    conn: &mut DbConnection,
+    version_odoo: &u8,
+    rows: &[UpgradeCoverageInfo],
+) -> QueryResult<usize> {
+    diesel::delete(
+        module_upgrade_coverage::table
+            .filter(module_upgrade_coverage::version_odoo.eq(*version_odoo as i32)),
+    )
+    .execute(conn)?;
+    if rows.is_empty() {
+        return Ok(0);
+    }
+    let update_date = get_sqlite_utc_now();
+    let new_rows: Vec<NewModuleUpgradeCoverage> = rows
+        .iter()
+        .map(|row| NewModuleUpgradeCoverage {
+            technical_name: &row.technical_name,
+            version_odoo: *version_odoo as i32,
+            status: &row.status,
+            note: row.note.as_deref(),
+            is_new: row.is_new,
+            is_removed: row.is_removed,
+            update_date: &update_date,
+        })
+        .collect();
+    // One row at a time: diesel can't batch ON CONFLICT inserts on SQLite.
+    let mut inserted = 0;
+    for row in &new_rows {
+        inserted += diesel::insert_into(module_upgrade_coverage::table)
+            .values(row)
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+    }
+    Ok(inserted)
+}
+
+pub fn get_by_technical_name_version(
+    conn: &mut DbConnection,
+    technical_name: &str,
+    version_odoo: &u8,
+) -> Option<Model> {
+    module_upgrade_coverage::table
+        .filter(
+            module_upgrade_coverage::technical_name
+                .eq(technical_name)
+                .and(module_upgrade_coverage::version_odoo.eq(*version_odoo as i32)),
+        )
+        .first::<Model>(conn)
+        .optional()
+        .expect("DB error in module_upgrade_coverage::get_by_technical_name_version")
+}
+
+pub fn get_by_technical_names_version(
+    conn: &mut DbConnection,
+    technical_names: &[String],
+    version_odoo: &u8,
+) -> Vec<Model> {
+    if technical_names.is_empty() {
+        return vec![];
+    }
+    module_upgrade_coverage::table
+        .filter(
+            module_upgrade_coverage::technical_name
+                .eq_any(technical_names)
+                .and(module_upgrade_coverage::version_odoo.eq(*version_odoo as i32)),
+        )
+        .order(module_upgrade_coverage::technical_name.asc())
+        .load::<Model>(conn)
+        .expect("DB error in module_upgrade_coverage::get_by_technical_names_version")
+}
@@ -1,20 +1,27 @@
 // Copyright Alexandre D. Díaz
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-use crate::schema::module_version;
+use crate::schema::{module, module_version};
 use crate::utils::date::get_sqlite_utc_now;
+use crate::DbConnection;
 
-use super::module;
+use super::{
+    module_controller, module_model, module_ownership, module_record, module_security_warning,
+    module_view,
+};
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = module_version, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = module_version, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub module_id: i64,
     pub version_module: String,
     pub create_date: String,
     pub update_date: String,
+    /// `test_*.py` files in its `tests/` folder; `None` until analyzed.
+    pub test_files: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -26,7 +33,7 @@ struct NewModuleVersion<'a> {
     update_date: &'a str,
 }
 
-pub fn get_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Vec<Model> {
+pub fn get_by_module_id(conn: &mut DbConnection, module_id: &i64) -> Vec<Model> {
     module_version::table
         .filter(module_version::module_id.eq(module_id))
         .order(module_version::id.asc())
@@ -35,7 +42,7 @@ pub fn get_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Vec<Mod
 }
 
 pub fn get_by_module_id_version_module(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_id: &i64,
     version_module: &str,
 ) -> Option<Model> {
@@ -53,7 +60,7 @@ pub fn get_by_module_id_version_module(
 /// The module_version row matching `module.version_module` - i.e. the latest
 /// one, since `module.version_module` is always kept as the current version
 /// by `module::add`. This is what "default to latest" resolves to everywhere.
-pub fn resolve_current(conn: &mut SqliteConnection, module: &module::Model) -> Option<Model> {
+pub fn resolve_current(conn: &mut DbConnection, module: &super::module::Model) -> Option<Model> {
     get_by_module_id_version_module(conn, &module.id, &module.version_module)
 }
 
@@ -62,7 +69,7 @@ pub fn resolve_current(conn: &mut SqliteConnection, module: &module::Model) -> O
 /// Called once per collector run so re-analyzing the same version in place
 /// (no manifest version bump) still refreshes `update_date`.
 pub fn get_or_create(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_id: &i64,
     version_module: &str,
 ) -> QueryResult<Model> {
@@ -86,17 +93,64 @@ pub fn get_or_create(
             update_date: &now,
         })
         .execute(conn)?;
-    let new_id = crate::models::last_insert_rowid(conn);
+    let new_id = crate::models::last_insert_id(conn);
     Ok(Model {
         id: new_id,
         module_id: *module_id,
         version_module: version_module.to_string(),
         create_date: now.clone(),
         update_date: now,
+        test_files: None,
     })
 }
 
-pub fn delete_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> QueryResult<usize> {
+pub fn set_test_files(
+    conn: &mut DbConnection,
+    module_version_id: &i64,
+    test_files: Option<i32>,
+) -> QueryResult<usize> {
+    diesel::update(module_version::table.filter(module_version::id.eq(module_version_id)))
+        .set(module_version::test_files.eq(test_files))
+        .execute(conn)
+}
+
+pub fn delete_by_module_id(conn: &mut DbConnection, module_id: &i64) -> QueryResult<usize> {
     diesel::delete(module_version::table.filter(module_version::module_id.eq(module_id)))
         .execute(conn)
 }
+
+/// Keeps the analysis of the `keep` newest versions of every module (plus the
+/// current one, whatever its age) and deletes the older snapshots: their
+/// views, models, records, controllers, security warnings and ownership go,
+/// and so does the module_version row, so the history only lists versions
+/// that can still be browsed. Returns how many snapshots were dropped.
+pub fn prune_snapshots(conn: &mut DbConnection, keep: usize) -> QueryResult<usize> {
+    let current: HashMap<i64, String> = module::table
+        .select((module::id, module::version_module))
+        .load::<(i64, String)>(conn)?
+        .into_iter()
+        .collect();
+    let versions = module_version::table
+        .order((module_version::module_id.asc(), module_version::id.desc()))
+        .load::<Model>(conn)?;
+
+    let mut pruned = 0;
+    let mut seen: HashMap<i64, usize> = HashMap::new();
+    for version in versions {
+        let newer = seen.entry(version.module_id).or_default();
+        *newer += 1;
+        if *newer <= keep || current.get(&version.module_id) == Some(&version.version_module) {
+            continue;
+        }
+        module_view::delete_by_module_version_id(conn, &version.id)?;
+        module_model::delete_by_module_version_id(conn, &version.id)?;
+        module_record::delete_by_module_version_id(conn, &version.id)?;
+        module_controller::delete_by_module_version_id(conn, &version.id)?;
+        module_security_warning::delete_by_module_version_id(conn, &version.id)?;
+        module_ownership::delete_by_module_version_id(conn, &version.id)?;
+        diesel::delete(module_version::table.filter(module_version::id.eq(version.id)))
+            .execute(conn)?;
+        pruned += 1;
+    }
+    Ok(pruned)
+}
@@ -3,11 +3,12 @@ use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::schema::module_view;
+use crate::DbConnection;
 
 use super::module_code_analysis::ViewAnalysisInfo;
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = module_view, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = module_view, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub module_id: i64,
@@ -33,7 +34,7 @@ struct NewModuleView<'a> {
 
 /// All views ever recorded for this module, across every historical version
 /// (uses the denormalized `module_id` column, no join through module_version).
-pub fn get_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Vec<Model> {
+pub fn get_by_module_id(conn: &mut DbConnection, module_id: &i64) -> Vec<Model> {
     module_view::table
         .filter(module_view::module_id.eq(module_id))
         .order(module_view::xml_id.asc())
@@ -43,10 +44,7 @@ pub fn get_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Vec<Mod
 
 /// Views for one specific version snapshot - what callers resolving "latest"
 /// or a historical `version_module` actually want.
-pub fn get_by_module_version_id(
-    conn: &mut SqliteConnection,
-    module_version_id: &i64,
-) -> Vec<Model> {
+pub fn get_by_module_version_id(conn: &mut DbConnection, module_version_id: &i64) -> Vec<Model> {
     module_view::table
         .filter(module_view::module_version_id.eq(module_version_id))
         .order(module_view::xml_id.asc())
@@ -54,10 +52,19 @@ pub fn get_by_module_version_id(
         .expect("DB error in module_view::get_by_module_version_id")
 }
 
-pub fn delete_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> QueryResult<usize> {
+pub fn delete_by_module_id(conn: &mut DbConnection, module_id: &i64) -> QueryResult<usize> {
     diesel::delete(module_view::table.filter(module_view::module_id.eq(module_id))).execute(conn)
 }
 
+/// Drops the views of one version snapshot (see `module_version::prune_snapshots`).
+pub fn delete_by_module_version_id(
+    conn: &mut DbConnection,
+    module_version_id: &i64,
+) -> QueryResult<usize> {
+    diesel::delete(module_view::table.filter(module_view::module_version_id.eq(module_version_id)))
+        .execute(conn)
+}
+
 /// Replaces every view row for this version snapshot with `views`. The
 /// collector recomputes the full list from the module's XML files on every
 /// run, so delete+insert is simpler than diffing (mirrors
@@ -65,13 +72,12 @@ pub fn delete_by_module_id(conn: &mut SqliteConnection, module_id: &i64) -> Quer
 /// `module_id`, so re-analyzing the current version never touches older
 /// versions' snapshots.
 pub fn replace_for_module(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_id: &i64,
     module_version_id: &i64,
     views: &[ViewAnalysisInfo],
 ) -> QueryResult<()> {
-    diesel::delete(module_view::table.filter(module_view::module_version_id.eq(module_version_id)))
-        .execute(conn)?;
+    delete_by_module_version_id(conn, module_version_id)?;
 
     let new_rows: Vec<NewModuleView> = views
         .iter()
@@ -0,0 +1,233 @@
+// Copyright Alexandre D. Díaz
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::dialect;
+use crate::schema::{open_pull_request, open_pull_request_module};
+use crate::DbConnection;
+
+#[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
+#[diesel(table_name = open_pull_request, check_for_backend(crate::DbBackend))]
+pub struct Model {
+    pub id: i64,
+    pub gh_repository_id: i64,
+    pub version_odoo: i32,
+    pub prid: i64,
+    pub name: String,
+    pub author: Option<String>,
+    pub head_ref: String,
+    pub is_draft: bool,
+    pub labels: String,
+    pub review_count: i32,
+    pub approval_count: i32,
+    pub ci_status: Option<String>,
+    pub created_at: Option<String>,
+    pub last_message_at: Option<String>,
+}
+
+impl Model {
+    /// `labels` is stored as JSON array text (like module_controller.routes);
+    /// parse it back for API responses.
+    pub fn labels_vec(&self) -> Vec<String> {
+        serde_json::from_str(&self.labels).unwrap_or_default()
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = open_pull_request)]
+struct NewOpenPullRequest<'a> {
+    gh_repository_id: i64,
+    version_odoo: i32,
+    prid: i64,
+    name: &'a str,
+    author: Option<&'a str>,
+    head_ref: &'a str,
+    is_draft: bool,
+    labels: &'a str,
+    review_count: i32,
+    approval_count: i32,
+    ci_status: Option<&'a str>,
+    created_at: Option<&'a str>,
+    last_message_at: Option<&'a str>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = open_pull_request_module)]
+struct NewOpenPullRequestModule<'a> {
+    open_pull_request_id: i64,
+    module_technical_name: &'a str,
+}
+
+/// One open PR/MR as fetched by the collector's git clients, ready to be
+/// stored. Dates use the sqlite text format (see `utils::date`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct OpenPullRequestInfo {
+    pub number: i64,
+    pub title: String,
+    pub author: Option<String>,
+    pub head_ref: String,
+    pub created_at: Option<String>,
+    pub last_message_at: Option<String>,
+    pub is_draft: bool,
+    pub labels: Vec<String>,
+    pub review_count: i32,
+    pub approval_count: i32,
+    pub ci_status: Option<String>,
+    /// Top-level module folders the PR/MR changes (see
+    /// `gitclient::extract_module_folders` in the collector).
+    pub module_technical_names: Vec<String>,
+}
+
+#[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
+pub struct OpenPullRequestFullInfo {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub id: i64,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub name: String,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub version_odoo: i32,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub prid: i64,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub author: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub head_ref: String,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    pub is_draft: bool,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub labels: String,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub review_count: i32,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub approval_count: i32,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub ci_status: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub created_at: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub last_message_at: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub repository_name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub org_name: String,
+}
+
+impl OpenPullRequestFullInfo {
+    pub fn labels_vec(&self) -> Vec<String> {
+        serde_json::from_str(&self.labels).unwrap_or_default()
+    }
+}
+
+/// Open PRs/MRs changing a module's folder in one organization, newest
+/// Odoo version first. `version_odoo` narrows it down to one version tab.
+pub fn get_by_technical_name_organization_name(
+    conn: &mut DbConnection,
+    technical_name: &str,
+    org_name: &str,
+    version_odoo: Option<&u8>,
+) -> Vec<OpenPullRequestFullInfo> {
+    dialect::sql_query(
+        "SELECT opr.id, opr.name, opr.version_odoo, opr.prid, opr.author, opr.head_ref, \
+         opr.is_draft, opr.labels, opr.review_count, opr.approval_count, opr.ci_status, \
+         opr.created_at, opr.last_message_at, \
+         gh_repo.name as repository_name, gh_org.name as org_name \
+         FROM open_pull_request as opr \
+         INNER JOIN open_pull_request_module as oprm ON oprm.open_pull_request_id = opr.id \
+         INNER JOIN gh_repository as gh_repo ON opr.gh_repository_id = gh_repo.id \
+         INNER JOIN gh_organization as gh_org ON gh_repo.gh_organization_id = gh_org.id \
+         WHERE oprm.module_technical_name = ? AND gh_org.name = ? \
+         AND (? IS NULL OR opr.version_odoo = ?) \
+         ORDER BY opr.version_odoo DESC, opr.prid DESC",
+    )
+    .bind::<diesel::sql_types::Text, _>(technical_name)
+    .bind::<diesel::sql_types::Text, _>(org_name)
+    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Integer>, _>(
+        version_odoo.map(|v| *v as i32),
+    )
+    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Integer>, _>(
+        version_odoo.map(|v| *v as i32),
+    )
+    .load::<OpenPullRequestFullInfo>(conn)
+    .expect("DB error in open_pull_request::get_by_technical_name_organization_name")
+}
+
+/// Module folders one open PR/MR changes.
+pub fn get_module_names(conn: &mut DbConnection, open_pull_request_id: &i64) -> Vec<String> {
+    open_pull_request_module::table
+        .filter(open_pull_request_module::open_pull_request_id.eq(open_pull_request_id))
+        .select(open_pull_request_module::module_technical_name)
+        .order(open_pull_request_module::module_technical_name.asc())
+        .load::<String>(conn)
+        .expect("DB error in open_pull_request::get_module_names")
+}
+
+/// Replaces the open PR/MR snapshot of one repo/version with what the
+/// provider reports right now (delete+insert, like module_record): PRs not
+/// in `prs` were closed or merged since the last run and just go away -
+/// migration PRs keep their own lifecycle in `pull_request`. A PR stored
+/// under another version was retargeted to this branch and moves here.
+pub fn replace_for_repository(
+    conn: &mut DbConnection,
+    gh_repo_id: &i64,
+    version_odoo: &u8,
+    prs: &[OpenPullRequestInfo],
+) -> QueryResult<()> {
+    let prids: Vec<i64> = prs.iter().map(|pr| pr.number).collect();
+    // Foreign keys aren't enforced on this connection, so the module links
+    // are removed by hand instead of relying on ON DELETE CASCADE.
+    let old_ids: Vec<i64> = open_pull_request::table
+        .filter(
+            open_pull_request::gh_repository_id.eq(gh_repo_id).and(
+                open_pull_request::version_odoo
+                    .eq(*version_odoo as i32)
+                    .or(open_pull_request::prid.eq_any(&prids)),
+            ),
+        )
+        .select(open_pull_request::id)
+        .load::<i64>(conn)?;
+    if !old_ids.is_empty() {
+        diesel::delete(
+            open_pull_request_module::table
+                .filter(open_pull_request_module::open_pull_request_id.eq_any(&old_ids)),
+        )
+        .execute(conn)?;
+        diesel::delete(open_pull_request::table.filter(open_pull_request::id.eq_any(&old_ids)))
+            .execute(conn)?;
+    }
+
+    for pr in prs {
+        let labels = serde_json::to_string(&pr.labels).unwrap_or_else(|_| "[]".to_string());
+        diesel::insert_into(open_pull_request::table)
+            .values(NewOpenPullRequest {
+                gh_repository_id: *gh_repo_id,
+                version_odoo: *version_odoo as i32,
+                prid: pr.number,
+                name: &pr.title,
+                author: pr.author.as_deref(),
+                head_ref: &pr.head_ref,
+                is_draft: pr.is_draft,
+                labels: &labels,
+                review_count: pr.review_count,
+                approval_count: pr.approval_count,
+                ci_status: pr.ci_status.as_deref(),
+                created_at: pr.created_at.as_deref(),
+                last_message_at: pr.last_message_at.as_deref(),
+            })
+            .execute(conn)?;
+        let open_pull_request_id = crate::models::last_insert_id(conn);
+        let new_modules: Vec<NewOpenPullRequestModule> = pr
+            .module_technical_names
+            .iter()
+            .map(|name| NewOpenPullRequestModule {
+                open_pull_request_id,
+                module_technical_name: name,
+            })
+            .collect();
+        if !new_modules.is_empty() {
+            diesel::insert_into(open_pull_request_module::table)
+                .values(&new_modules)
+                .execute(conn)?;
+        }
+    }
+    Ok(())
+}
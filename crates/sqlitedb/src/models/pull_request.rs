@@ -1,15 +1,20 @@
 // Copyright Alexandre D. Díaz
+use std::collections::HashMap;
+
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::dialect;
 use crate::schema::pull_request;
 use crate::utils::date::get_sqlite_utc_now;
+use crate::DbConnection;
 
+use super::pull_request_history::PullRequestClosureInfo;
 use super::{gh_repository, pull_request_history, system_event};
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = pull_request, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = pull_request, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub name: String,
@@ -60,8 +65,8 @@ pub struct PullRequestFullInfo {
 /// Every open migration PR/MR tracked, across all orgs/repos - for the
 /// site-wide modules overview page (unlike the other getters here, which
 /// scope to one module or one org).
-pub fn get_all(conn: &mut SqliteConnection) -> Vec<PullRequestFullInfo> {
-    diesel::sql_query(
+pub fn get_all(conn: &mut DbConnection) -> Vec<PullRequestFullInfo> {
+    dialect::sql_query(
         "SELECT pr.name, pr.version_odoo, pr.module_technical_name, pr.prid, \
          gh_repo.name as repository_name, gh_org.name as org_name, \
          pr.created_at, pr.ci_status, pr.last_message_at \
@@ -83,7 +88,7 @@ pub fn days_since(date: Option<&str>) -> Option<i64> {
     Some((chrono::Utc::now().naive_utc() - dt).num_days())
 }
 
-pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
+pub fn get_by_id(conn: &mut DbConnection, id: &i64) -> Option<Model> {
     pull_request::table
         .filter(pull_request::id.eq(id))
         .first::<Model>(conn)
@@ -91,11 +96,19 @@ pub fn get_by_id(conn: &mut SqliteConnection, id: &i64) -> Option<Model> {
         .expect("DB error in pull_request::get_by_id")
 }
 
+pub fn get_by_gh_repository_id(conn: &mut DbConnection, gh_repo_id: &i64) -> Vec<Model> {
+    pull_request::table
+        .filter(pull_request::gh_repository_id.eq(gh_repo_id))
+        .order(pull_request::prid.asc())
+        .load::<Model>(conn)
+        .expect("DB error in pull_request::get_by_gh_repository_id")
+}
+
 /// Batch lookup used by the migration plan tool: finds open migration PRs for a
 /// set of modules at a given target version, regardless of organization (the
 /// caller doesn't know in advance which org/repo a not-yet-merged module lives in).
 pub fn get_by_technical_names_odoo_version(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     technical_names: &[String],
     version_odoo: &u8,
 ) -> Vec<Model> {
@@ -117,7 +130,7 @@ pub fn get_by_technical_names_odoo_version(
 /// a module can be "registered" this way even if it has never actually
 /// merged in any version yet (a first-time port with only an open PR so far).
 pub fn get_repository_org_by_technical_names(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     technical_names: &[String],
 ) -> Vec<(String, String, String)> {
     use crate::schema::{gh_organization, gh_repository};
@@ -140,7 +153,7 @@ pub fn get_repository_org_by_technical_names(
 }
 
 pub fn get_by_technical_name_organization_name(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     technical_name: &str,
     org_name: &str,
 ) -> Vec<Model> {
@@ -165,7 +178,7 @@ pub fn get_by_technical_name_organization_name(
 /// `ci_status` is expected to change across collector runs as checks complete).
 #[allow(clippy::too_many_arguments)]
 pub fn add(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     name: &str,
     module_technical_name: &str,
     prid: &i64,
@@ -237,7 +250,7 @@ pub fn add(
 /// a read-only lookup so the caller (which has provider API access, unlike
 /// this crate) can check each one's merged status first.
 pub fn find_outdated(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     gh_repo_id: &i64,
     version_odoo: &u8,
     prids: &[i64],
@@ -255,20 +268,21 @@ pub fn find_outdated(
 }
 
 /// Removes PRs that are no longer open (merged/closed/renamed away from the
-/// migration convention) for a given repo/version. `merged_prids` (a subset of
-/// the outdated set, resolved by the caller via the provider API) gates which
-/// closures get a `pull_request_history` row - only merged PRs should count
-/// toward the "avg. time open before close" stat, not rejections.
+/// migration convention) for a given repo/version. `closures` (keyed by PR
+/// number, resolved by the caller via the provider API) gives each removed
+/// PR its real close date, close reason and reviews for its
+/// `pull_request_history` row; a PR missing from it (lookup failed) is still
+/// recorded, with `CLOSE_REASON_UNKNOWN` and the detection time as close date.
 ///
 /// Unlike `module::delete_outdated`, an empty `prids` list is a valid terminal
 /// state here (every previously open migration PR got merged or closed) and
 /// must still clear out the stale rows rather than skip the delete.
 pub fn delete_outdated(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     gh_repo_id: &i64,
     version_odoo: &u8,
     prids: &[i64],
-    merged_prids: &[i64],
+    closures: &HashMap<i64, PullRequestClosureInfo>,
 ) -> QueryResult<usize> {
     let removed = find_outdated(conn, gh_repo_id, version_odoo, prids)?;
 
@@ -279,7 +293,7 @@ pub fn delete_outdated(
     let repo_name = gh_repository::get_by_id(conn, gh_repo_id)
         .map(|r| r.name)
         .unwrap_or_default();
-    let closed_at = get_sqlite_utc_now();
+    let detected_at = get_sqlite_utc_now();
     for pr in &removed {
         let _ = system_event::register_closed_migration_pr(
             conn,
@@ -289,29 +303,58 @@ pub fn delete_outdated(
             &repo_name,
             version_odoo,
         );
-        // Only merged rows with a known open date are useful for the
-        // acceptance-time stat; PRs collected before the `created_at`
-        // migration don't have one, and closed-without-merge PRs would
-        // skew it toward rejections instead of real acceptance time.
-        if let Some(created_at) = &pr.created_at {
-            if merged_prids.contains(&pr.prid) {
-                let _ = pull_request_history::add(
-                    conn,
-                    &pr.module_technical_name,
-                    pr.version_odoo,
-                    *gh_repo_id,
-                    pr.prid,
-                    created_at,
-                    &closed_at,
-                );
-            }
-        }
+        let closure = closures.get(&pr.prid);
+        // The provider's own creation date wins over the stored one (missing
+        // for PRs collected before the `created_at` migration); with neither,
+        // no latency can be computed and the closure is only logged.
+        let Some(created_at) = closure
+            .and_then(|c| c.created_at.as_deref())
+            .or(pr.created_at.as_deref())
+        else {
+            continue;
+        };
+        let close_reason = closure
+            .map(|c| c.close_reason.as_str())
+            .unwrap_or(pull_request_history::CLOSE_REASON_UNKNOWN);
+        let closed_at = closure
+            .and_then(|c| c.closed_at.as_deref())
+            .unwrap_or(&detected_at);
+        let reviews = closure.map(|c| c.reviews.as_slice()).unwrap_or_default();
+        let _ = pull_request_history::add(
+            conn,
+            &pr.module_technical_name,
+            pr.version_odoo,
+            *gh_repo_id,
+            pr.prid,
+            created_at,
+            closed_at,
+            close_reason,
+            reviews,
+        );
     }
 
     let removed_ids: Vec<i64> = removed.iter().map(|pr| pr.id).collect();
     diesel::delete(pull_request::table.filter(pull_request::id.eq_any(removed_ids))).execute(conn)
 }
 
+/// Drops the repo's PRs missing from `prids`, without the `pull_request_history`
+/// bookkeeping of `delete_outdated`: used when the repo's PRs are mirrored
+/// from an imported dataset, which carries its own history rows.
+pub fn delete_missing(
+    conn: &mut DbConnection,
+    gh_repo_id: &i64,
+    prids: &[i64],
+) -> QueryResult<usize> {
+    diesel::delete(
+        pull_request::table.filter(
+            pull_request::gh_repository_id
+                .eq(gh_repo_id)
+                .and(pull_request::prid.ne_all(prids)),
+        ),
+    )
+    .execute(conn)
+}
+
 #[cfg(test)]
 mod tests {
     use super::days_since;
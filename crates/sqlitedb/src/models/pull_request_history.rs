@@ -1,11 +1,25 @@
 // Copyright Alexandre D. Díaz
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use oghutils::version::odoo_version_u8_to_string;
+
+use crate::dialect;
 use crate::schema::pull_request_history;
+use crate::DbConnection;
+
+use super::pull_request_review::{self, PullRequestReviewInfo, STATE_APPROVED};
+
+pub const CLOSE_REASON_MERGED: &str = "merged";
+pub const CLOSE_REASON_CLOSED: &str = "closed";
+pub const CLOSE_REASON_RETARGETED: &str = "retargeted";
+pub const CLOSE_REASON_UNKNOWN: &str = "unknown";
 
 #[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
-#[diesel(table_name = pull_request_history, check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(table_name = pull_request_history, check_for_backend(crate::DbBackend))]
 pub struct Model {
     pub id: i64,
     pub module_technical_name: String,
@@ -14,6 +28,9 @@ pub struct Model {
     pub prid: i64,
     pub created_at: String,
     pub closed_at: String,
+    pub close_reason: String,
+    pub first_review_at: Option<String>,
+    pub approved_at: Option<String>,
 }
 
 #[derive(Insertable)]
@@ -25,24 +42,54 @@ struct NewPullRequestHistory<'a> {
     prid: i64,
     created_at: &'a str,
     closed_at: &'a str,
+    close_reason: &'a str,
+    first_review_at: Option<&'a str>,
+    approved_at: Option<&'a str>,
 }
 
-/// Records a migration PR/MR that was merged, called from
-/// `pull_request::delete_outdated` right before the live row is removed - only
-/// for PRs the caller has confirmed were merged (closed-without-merge PRs are
-/// dropped there, never reaching this table).
-/// `closed_at` is the collector's detection time, not the provider's real
-/// merge timestamp - see the migration's comment for why.
+/// What the provider reports about a migration PR/MR that left the open
+/// list, as fetched by the collector's git clients right before
+/// `pull_request::delete_outdated`. `close_reason` is one of the
+/// `CLOSE_REASON_*` constants; `closed_at` is the provider's `merged_at` for
+/// merges and `closed_at` otherwise (`None` while still open).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PullRequestClosureInfo {
+    pub close_reason: String,
+    pub created_at: Option<String>,
+    pub closed_at: Option<String>,
+    pub reviews: Vec<PullRequestReviewInfo>,
+}
+
+/// Earliest review of any kind, and earliest approval, from a PR/MR's
+/// reviews (dates in sqlite text format compare chronologically as strings).
+fn first_review_and_approval(reviews: &[PullRequestReviewInfo]) -> (Option<&str>, Option<&str>) {
+    let first_review_at = reviews.iter().map(|r| r.submitted_at.as_str()).min();
+    let approved_at = reviews
+        .iter()
+        .filter(|r| r.state == STATE_APPROVED)
+        .map(|r| r.submitted_at.as_str())
+        .min();
+    (first_review_at, approved_at)
+}
+
+/// Records a migration PR/MR that stopped being open, called from
+/// `pull_request::delete_outdated` right before the live row is removed -
+/// merges and every other closure alike, told apart by `close_reason`.
+/// `closed_at` is the provider's real merge/close date whenever the
+/// collector could fetch it (its detection time otherwise).
 #[allow(clippy::too_many_arguments)]
 pub fn add(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_technical_name: &str,
     version_odoo: i32,
     gh_repository_id: i64,
     prid: i64,
     created_at: &str,
     closed_at: &str,
+    close_reason: &str,
+    reviews: &[PullRequestReviewInfo],
 ) -> QueryResult<Model> {
+    let (first_review_at, approved_at) = first_review_and_approval(reviews);
     diesel::insert_into(pull_request_history::table)
         .values(NewPullRequestHistory {
             module_technical_name,
@@ -51,20 +98,54 @@ pub fn add(
             prid,
             created_at,
             closed_at,
+            close_reason,
+            first_review_at,
+            approved_at,
         })
         .execute(conn)?;
+    let id = crate::models::last_insert_id(conn);
+    pull_request_review::add_many(conn, &id, reviews)?;
 
     Ok(Model {
-        id: crate::models::last_insert_rowid(conn),
+        id,
         module_technical_name: module_technical_name.to_string(),
         version_odoo,
         gh_repository_id,
         prid,
         created_at: created_at.to_string(),
         closed_at: closed_at.to_string(),
+        close_reason: close_reason.to_string(),
+        first_review_at: first_review_at.map(str::to_string),
+        approved_at: approved_at.map(str::to_string),
     })
 }
 
+pub fn get_by_gh_repository_id(conn: &mut DbConnection, gh_repo_id: &i64) -> Vec<Model> {
+    pull_request_history::table
+        .filter(pull_request_history::gh_repository_id.eq(gh_repo_id))
+        .order((
+            pull_request_history::prid.asc(),
+            pull_request_history::closed_at.asc(),
+        ))
+        .load::<Model>(conn)
+        .expect("DB error in pull_request_history::get_by_gh_repository_id")
+}
+
+/// Whether this closure of the PR is already recorded (a PR reopened and
+/// closed again has one row per closure).
+pub fn exists(conn: &mut DbConnection, gh_repo_id: &i64, prid: &i64, closed_at: &str) -> bool {
+    diesel::select(diesel::dsl::exists(
+        pull_request_history::table.filter(
+            pull_request_history::gh_repository_id
+                .eq(gh_repo_id)
+                .and(pull_request_history::prid.eq(prid))
+                .and(pull_request_history::closed_at.eq(closed_at)),
+        ),
+    ))
+    .get_result(conn)
+    .expect("DB error in pull_request_history::exists")
+}
+
 #[derive(QueryableByName, Debug, Deserialize, Serialize, Clone)]
 pub struct AcceptanceStatsInfo {
     #[diesel(sql_type = diesel::sql_types::Integer)]
@@ -76,35 +157,195 @@ pub struct AcceptanceStatsInfo {
 }
 
 /// Average days a migration PR/MR stayed open before being merged, per Odoo
-/// version - the closest proxy for "acceptance time" this table can give
-/// (see module doc: `closed_at` is a detection time, not the provider's exact
-/// merge timestamp). Rows are merged-only, so `closed_count` here means
-/// merged PRs, not every closure.
-pub fn average_days_open_by_version(conn: &mut SqliteConnection) -> Vec<AcceptanceStatsInfo> {
-    diesel::sql_query(
-        "SELECT version_odoo, \
-         AVG(julianday(closed_at) - julianday(created_at)) as avg_days, \
-         COUNT(*) as closed_count \
+/// version - the "acceptance time". Only merged rows count, so
+/// `closed_count` here means merged PRs, not every closure.
+pub fn average_days_open_by_version(conn: &mut DbConnection) -> Vec<AcceptanceStatsInfo> {
+    dialect::sql_query(format!(
+        "SELECT version_odoo, AVG({days}) as avg_days, COUNT(*) as closed_count \
          FROM pull_request_history \
+         WHERE close_reason = ? \
          GROUP BY version_odoo",
-    )
+        days = dialect::days_between("created_at", "closed_at"),
+    ))
+    .bind::<diesel::sql_types::Text, _>(CLOSE_REASON_MERGED)
     .load::<AcceptanceStatsInfo>(conn)
     .expect("DB error in pull_request_history::average_days_open_by_version")
 }
 
+/// How review latency stats are grouped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatencyGroup {
+    /// "organization/repository"
+    Repository,
+    /// Odoo version, e.g. "16.0"
+    Version,
+    /// Reviewer login: latencies measured to *their* first review/approval.
+    Reviewer,
+}
+
+impl LatencyGroup {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "repository" => Some(Self::Repository),
+            "version" => Some(Self::Version),
+            "reviewer" => Some(Self::Reviewer),
+            _ => None,
+        }
+    }
+}
+
+#[derive(QueryableByName, Debug)]
+struct LatencyRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    key: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    created_at: String,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    first_review_at: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    approved_at: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    closed_at: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    close_reason: String,
+}
+
+/// Median/90th percentile review latencies for one group. Time to first
+/// review and to approval are in hours (reviews tend to land within days),
+/// time to merge in days; `None` when no PR in the group has that event.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LatencyStatsInfo {
+    pub key: String,
+    pub closed_count: usize,
+    pub merged_count: usize,
+    pub first_review_p50_hours: Option<f64>,
+    pub first_review_p90_hours: Option<f64>,
+    pub approval_p50_hours: Option<f64>,
+    pub approval_p90_hours: Option<f64>,
+    pub merge_p50_days: Option<f64>,
+    pub merge_p90_days: Option<f64>,
+}
+
+fn hours_between(from: &str, to: &str) -> Option<f64> {
+    let from = NaiveDateTime::parse_from_str(from, "%Y-%m-%d %H:%M:%S").ok()?;
+    let to = NaiveDateTime::parse_from_str(to, "%Y-%m-%d %H:%M:%S").ok()?;
+    Some((to - from).num_seconds() as f64 / 3600.0)
+}
+
+/// Linear-interpolated percentile (`p` in 0..=100) of an ascending slice.
+pub fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p / 100.0).clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+/// Review latency percentiles over every recorded PR/MR closure, grouped by
+/// repository, Odoo version or reviewer. SQLite has no percentile function,
+/// so rows are aggregated here.
+pub fn latency_stats(conn: &mut DbConnection, group: LatencyGroup) -> Vec<LatencyStatsInfo> {
+    let query = match group {
+        LatencyGroup::Repository => {
+            "SELECT gh_org.name || '/' || gh_repo.name as key, h.created_at, \
+             h.first_review_at, h.approved_at, h.closed_at, h.close_reason \
+             FROM pull_request_history as h \
+             INNER JOIN gh_repository as gh_repo ON h.gh_repository_id = gh_repo.id \
+             INNER JOIN gh_organization as gh_org ON gh_repo.gh_organization_id = gh_org.id"
+        }
+        LatencyGroup::Version => {
+            "SELECT CAST(h.version_odoo AS TEXT) as key, h.created_at, \
+             h.first_review_at, h.approved_at, h.closed_at, h.close_reason \
+             FROM pull_request_history as h"
+        }
+        LatencyGroup::Reviewer => {
+            "SELECT r.reviewer as key, h.created_at, \
+             MIN(r.submitted_at) as first_review_at, \
+             MIN(CASE WHEN r.state = 'approved' THEN r.submitted_at END) as approved_at, \
+             h.closed_at, h.close_reason \
+             FROM pull_request_review as r \
+             INNER JOIN pull_request_history as h ON r.pull_request_history_id = h.id \
+             GROUP BY r.reviewer, h.id"
+        }
+    };
+    let rows = dialect::sql_query(query)
+        .load::<LatencyRow>(conn)
+        .expect("DB error in pull_request_history::latency_stats");
+
+    #[derive(Default)]
+    struct Samples {
+        closed_count: usize,
+        merged_count: usize,
+        first_review: Vec<f64>,
+        approval: Vec<f64>,
+        merge: Vec<f64>,
+    }
+    let mut by_key: BTreeMap<String, Samples> = BTreeMap::new();
+    for row in rows {
+        let samples = by_key.entry(row.key).or_default();
+        samples.closed_count += 1;
+        if let Some(h) = row
+            .first_review_at
+            .as_deref()
+            .and_then(|at| hours_between(&row.created_at, at))
+        {
+            samples.first_review.push(h);
+        }
+        if let Some(h) = row
+            .approved_at
+            .as_deref()
+            .and_then(|at| hours_between(&row.created_at, at))
+        {
+            samples.approval.push(h);
+        }
+        if row.close_reason == CLOSE_REASON_MERGED {
+            samples.merged_count += 1;
+            if let Some(h) = hours_between(&row.created_at, &row.closed_at) {
+                samples.merge.push(h / 24.0);
+            }
+        }
+    }
+
+    by_key
+        .into_iter()
+        .map(|(key, mut samples)| {
+            for values in [
+                &mut samples.first_review,
+                &mut samples.approval,
+                &mut samples.merge,
+            ] {
+                values.sort_by(|a, b| a.total_cmp(b));
+            }
+            let key = match group {
+                LatencyGroup::Version => key
+                    .parse::<u8>()
+                    .map(|v| odoo_version_u8_to_string(&v))
+                    .unwrap_or(key),
+                _ => key,
+            };
+            LatencyStatsInfo {
+                key,
+                closed_count: samples.closed_count,
+                merged_count: samples.merged_count,
+                first_review_p50_hours: percentile(&samples.first_review, 50.0),
+                first_review_p90_hours: percentile(&samples.first_review, 90.0),
+                approval_p50_hours: percentile(&samples.approval, 50.0),
+                approval_p90_hours: percentile(&samples.approval, 90.0),
+                merge_p50_days: percentile(&samples.merge, 50.0),
+                merge_p90_days: percentile(&samples.merge, 90.0),
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use diesel::sqlite::SqliteConnection;
-    use diesel::Connection;
-    use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 
-    const MIGRATIONS: EmbeddedMigrations = embed_migrations!("../../migrations");
-
-    fn setup_db() -> SqliteConnection {
-        let mut conn = SqliteConnection::establish(":memory:").unwrap();
-        conn.run_pending_migrations(MIGRATIONS).unwrap();
-        conn
+    fn setup_db() -> DbConnection {
+        crate::establish_test_connection()
     }
 
     #[test]
@@ -121,6 +362,8 @@ mod tests {
             1,
             "2024-01-01 00:00:00",
             "2024-01-03 00:00:00",
+            CLOSE_REASON_MERGED,
+            &[],
         )
         .unwrap();
         add(
@@ -131,6 +374,8 @@ mod tests {
             2,
             "2024-01-01 00:00:00",
             "2024-01-05 00:00:00",
+            CLOSE_REASON_MERGED,
+            &[],
         )
         .unwrap();
         add(
@@ -141,6 +386,22 @@ mod tests {
             3,
             "2024-01-01 00:00:00",
             "2024-01-11 00:00:00",
+            CLOSE_REASON_MERGED,
+            &[],
+        )
+        .unwrap();
+
+        // Closed without merge: must not count toward the acceptance time.
+        add(
+            &mut conn,
+            "mod_d",
+            16,
+            repo.id,
+            4,
+            "2024-01-01 00:00:00",
+            "2024-03-01 00:00:00",
+            CLOSE_REASON_CLOSED,
+            &[],
         )
         .unwrap();
 
@@ -155,4 +416,104 @@ mod tests {
         assert_eq!(stats[1].closed_count, 1);
         assert!((stats[1].avg_days - 10.0).abs() < 0.01);
     }
+
+    #[test]
+    fn test_percentile_interpolates() {
+        assert_eq!(percentile(&[], 50.0), None);
+        assert_eq!(percentile(&[4.0], 90.0), Some(4.0));
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0], 50.0), Some(2.5));
+        let p90 = percentile(
+            &[
+                0.0, 10.0, 20.0, 30.0, 40.0, 50.0, 60.0, 70.0, 80.0, 90.0, 100.0,
+            ],
+            90.0,
+        );
+        assert_eq!(p90, Some(90.0));
+    }
+
+    #[test]
+    fn test_latency_stats_by_version_repository_and_reviewer() {
+        let mut conn = setup_db();
+        let org = crate::models::gh_organization::add(&mut conn, "LatOrg").unwrap();
+        let repo = crate::models::gh_repository::add(&mut conn, &org.id, "lat-repo").unwrap();
+        let review = |reviewer: &str, state: &str, at: &str| PullRequestReviewInfo {
+            reviewer: reviewer.to_string(),
+            state: state.to_string(),
+            submitted_at: at.to_string(),
+        };
+
+        // Merged after 4 days: alice comments after 2h, approves after 10h;
+        // bob approves after 20h.
+        let merged = add(
+            &mut conn,
+            "mod_a",
+            160,
+            repo.id,
+            1,
+            "2024-01-01 00:00:00",
+            "2024-01-05 00:00:00",
+            CLOSE_REASON_MERGED,
+            &[
+                review(
+                    "alice",
+                    pull_request_review::STATE_COMMENTED,
+                    "2024-01-01 02:00:00",
+                ),
+                review("alice", STATE_APPROVED, "2024-01-01 10:00:00"),
+                review("bob", STATE_APPROVED, "2024-01-01 20:00:00"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            merged.first_review_at.as_deref(),
+            Some("2024-01-01 02:00:00")
+        );
+        assert_eq!(merged.approved_at.as_deref(), Some("2024-01-01 10:00:00"));
+        assert_eq!(
+            pull_request_review::get_by_pull_request_history_id(&mut conn, &merged.id).len(),
+            3
+        );
+
+        // Closed unmerged: bob requested changes after 6h.
+        add(
+            &mut conn,
+            "mod_b",
+            160,
+            repo.id,
+            2,
+            "2024-01-01 00:00:00",
+            "2024-02-01 00:00:00",
+            CLOSE_REASON_CLOSED,
+            &[review(
+                "bob",
+                pull_request_review::STATE_CHANGES_REQUESTED,
+                "2024-01-01 06:00:00",
+            )],
+        )
+        .unwrap();
+
+        let by_version = latency_stats(&mut conn, LatencyGroup::Version);
+        assert_eq!(by_version.len(), 1);
+        assert_eq!(by_version[0].key, "16.0");
+        assert_eq!(by_version[0].closed_count, 2);
+        assert_eq!(by_version[0].merged_count, 1);
+        assert_eq!(by_version[0].first_review_p50_hours, Some(4.0));
+        assert_eq!(by_version[0].approval_p50_hours, Some(10.0));
+        // Only the merged PR counts toward time to merge.
+        assert_eq!(by_version[0].merge_p50_days, Some(4.0));
+
+        let by_repo = latency_stats(&mut conn, LatencyGroup::Repository);
+        assert_eq!(by_repo.len(), 1);
+        assert_eq!(by_repo[0].key, "LatOrg/lat-repo");
+
+        let by_reviewer = latency_stats(&mut conn, LatencyGroup::Reviewer);
+        assert_eq!(by_reviewer.len(), 2);
+        assert_eq!(by_reviewer[0].key, "alice");
+        assert_eq!(by_reviewer[0].first_review_p50_hours, Some(2.0));
+        assert_eq!(by_reviewer[0].approval_p50_hours, Some(10.0));
+        assert_eq!(by_reviewer[1].key, "bob");
+        assert_eq!(by_reviewer[1].closed_count, 2);
+        assert_eq!(by_reviewer[1].first_review_p50_hours, Some(13.0));
+        assert_eq!(by_reviewer[1].approval_p50_hours, Some(20.0));
+    }
 }
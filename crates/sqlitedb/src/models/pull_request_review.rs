@@ -0,0 +1,72 @@
+// Copyright Alexandre D. Díaz
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::pull_request_review;
+use crate::DbConnection;
+
+pub const STATE_APPROVED: &str = "approved";
+pub const STATE_CHANGES_REQUESTED: &str = "changes_requested";
+pub const STATE_COMMENTED: &str = "commented";
+
+#[derive(Queryable, Selectable, Debug, Deserialize, Serialize, Clone)]
+#[diesel(table_name = pull_request_review, check_for_backend(crate::DbBackend))]
+pub struct Model {
+    pub id: i64,
+    pub pull_request_history_id: i64,
+    pub reviewer: String,
+    pub state: String,
+    pub submitted_at: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = pull_request_review)]
+struct NewPullRequestReview<'a> {
+    pull_request_history_id: i64,
+    reviewer: &'a str,
+    state: &'a str,
+    submitted_at: &'a str,
+}
+
+/// One review as reported by the provider, normalized by the collector's
+/// git clients (`state` is one of the `STATE_*` constants, `submitted_at`
+/// uses the sqlite text format).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PullRequestReviewInfo {
+    pub reviewer: String,
+    pub state: String,
+    pub submitted_at: String,
+}
+
+pub fn get_by_pull_request_history_id(
+    conn: &mut DbConnection,
+    pull_request_history_id: &i64,
+) -> Vec<Model> {
+    pull_request_review::table
+        .filter(pull_request_review::pull_request_history_id.eq(pull_request_history_id))
+        .order(pull_request_review::submitted_at.asc())
+        .load::<Model>(conn)
+        .expect("DB error in pull_request_review::get_by_pull_request_history_id")
+}
+
+pub fn add_many(
+    conn: &mut DbConnection,
+    pull_request_history_id: &i64,
+    reviews: &[PullRequestReviewInfo],
+) -> QueryResult<usize> {
+    if reviews.is_empty() {
+        return Ok(0);
+    }
+    let new_rows: Vec<NewPullRequestReview> = reviews
+        .iter()
+        .map(|r| NewPullRequestReview {
+            pull_request_history_id: *pull_request_history_id,
+            reviewer: &r.reviewer,
+            state: &r.state,
+            submitted_at: &r.submitted_at,
+        })
+        .collect();
+    diesel::insert_into(pull_request_review::table)
+        .values(&new_rows)
+        .execute(conn)
+}
@@ -0,0 +1,155 @@
+// Copyright Alexandre D. Díaz
+//! One repository at a glance: its Odoo version branches, which modules are
+//! ported to which, the migration PRs still open, who commits to it and what
+//! the security checks and OSV found.
+use std::collections::{BTreeMap, BTreeSet};
+
+use oghutils::version::odoo_version_u8_to_string;
+use serde::{Deserialize, Serialize};
+
+use super::module_security_warning::SEVERITY_ERROR;
+use super::{
+    collection_run_repo, dependency_osv, gh_organization, gh_repository, module, module_committer,
+    module_security_warning, pull_request,
+};
+use crate::DbConnection;
+
+const TOP_COMMITTERS: i64 = 10;
+
+/// One Odoo version of the repository, as far as it's known: from its
+/// modules, its migration PRs or the collections that looked for it.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct RepositoryBranchInfo {
+    pub odoo_version: String,
+    pub num_modules: usize,
+    pub security_errors: i64,
+    pub security_warnings: i64,
+    pub osv_count: i64,
+    pub last_collection: Option<collection_run_repo::RepoCollectionInfo>,
+}
+
+/// One cell of the port matrix.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct RepositoryModulePort {
+    /// `version_module` when the module is merged for that Odoo version.
+    pub version_module: Option<String>,
+    pub installable: bool,
+    /// Open migration PRs targeting that version.
+    pub pull_requests: Vec<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RepositoryModuleInfo {
+    pub technical_name: String,
+    pub name: String,
+    /// Same order as `RepositoryOverview::branches`.
+    pub ports: Vec<RepositoryModulePort>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RepositoryOverview {
+    pub organization: String,
+    pub name: String,
+    /// Newest Odoo version first.
+    pub branches: Vec<RepositoryBranchInfo>,
+    pub modules: Vec<RepositoryModuleInfo>,
+    /// Newest first.
+    pub pull_requests: Vec<pull_request::Model>,
+    pub committers: Vec<module_committer::RepositoryCommitterInfo>,
+    /// When a collection last went through the repository, any version.
+    pub last_collected: Option<String>,
+}
+
+/// `None` when the repository isn't tracked.
+pub fn get(conn: &mut DbConnection, org_name: &str, repo_name: &str) -> Option<RepositoryOverview> {
+    let org = gh_organization::get_by_name(conn, org_name)?;
+    let repo = gh_repository::get_by_name(conn, &org.id, repo_name)?;
+    let modules = module::get_by_organization_repository_name(conn, org_name, repo_name);
+    let mut pull_requests = pull_request::get_by_gh_repository_id(conn, &repo.id);
+    pull_requests.reverse();
+    let collections = collection_run_repo::get_last_by_repository(conn, org_name, repo_name);
+
+    let mut branches: BTreeMap<i32, RepositoryBranchInfo> = BTreeMap::new();
+    let versions: BTreeSet<i32> = modules
+        .iter()
+        .map(|m| m.version_odoo)
+        .chain(pull_requests.iter().map(|pr| pr.version_odoo))
+        .chain(collections.iter().map(|c| c.version_odoo))
+        .collect();
+    for version_odoo in &versions {
+        branches.insert(
+            *version_odoo,
+            RepositoryBranchInfo {
+                odoo_version: odoo_version_u8_to_string(&(*version_odoo as u8)),
+                ..Default::default()
+            },
+        );
+    }
+    for m in &modules {
+        if let Some(branch) = branches.get_mut(&m.version_odoo) {
+            branch.num_modules += 1;
+        }
+    }
+    for count in module_security_warning::count_current_by_repository(conn, &repo.id) {
+        if let Some(branch) = branches.get_mut(&count.version_odoo) {
+            if count.severity == SEVERITY_ERROR {
+                branch.security_errors += count.count;
+            } else {
+                branch.security_warnings += count.count;
+            }
+        }
+    }
+    for count in dependency_osv::count_by_repository(conn, &repo.id) {
+        if let Some(branch) = branches.get_mut(&count.version_odoo) {
+            branch.osv_count = count.count;
+        }
+    }
+    let last_collected = collections.iter().filter_map(|c| c.end_date.clone()).max();
+    for collection in collections {
+        if let Some(branch) = branches.get_mut(&collection.version_odoo) {
+            branch.last_collection = Some(collection);
+        }
+    }
+
+    // Newest version first, columns and cells alike.
+    let columns: Vec<i32> = versions.iter().rev().copied().collect();
+    let mut rows: BTreeMap<String, RepositoryModuleInfo> = BTreeMap::new();
+    let new_row = |technical_name: &str, name: &str| RepositoryModuleInfo {
+        technical_name: technical_name.to_string(),
+        name: name.to_string(),
+        ports: vec![RepositoryModulePort::default(); columns.len()],
+    };
+    for m in &modules {
+        let column = columns.iter().position(|v| *v == m.version_odoo).unwrap();
+        let row = rows
+            .entry(m.technical_name.clone())
+            .or_insert_with(|| new_row(&m.technical_name, &m.name));
+        // The newest version's name wins.
+        if row.ports[..column]
+            .iter()
+            .all(|p| p.version_module.is_none())
+        {
+            row.name = m.name.clone();
+        }
+        row.ports[column].version_module = Some(m.version_module.clone());
+        row.ports[column].installable = m.installable;
+    }
+    for pr in &pull_requests {
+        let column = columns.iter().position(|v| *v == pr.version_odoo).unwrap();
+        rows.entry(pr.module_technical_name.clone())
+            .or_insert_with(|| new_row(&pr.module_technical_name, &pr.module_technical_name))
+            .ports[column]
+            .pull_requests
+            .push(pr.prid);
+    }
+
+    Some(RepositoryOverview {
+        organization: org.name,
+        name: repo.name,
+        branches: branches.into_values().rev().collect(),
+        modules: rows.into_values().collect(),
+        pull_requests,
+        committers: module_committer::rank_by_repository(conn, &repo.id, TOP_COMMITTERS),
+        last_collected,
+    })
+}
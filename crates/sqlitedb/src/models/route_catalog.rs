@@ -0,0 +1,274 @@
+// Copyright Alexandre D. Díaz
+//! Every HTTP endpoint the collected modules of one Odoo version expose, one
+//! entry per route path, and the paths several modules register. Built from
+//! `module_controller` for each module's current version; overrides of an
+//! inherited route without a path of their own are left out.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use super::module_controller;
+use crate::DbConnection;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RouteEntry {
+    pub route: String,
+    pub technical_name: String,
+    pub organization: String,
+    pub repository: String,
+    /// `Class.method` of the handler.
+    pub handler: String,
+    pub auth: Option<String>,
+    pub http_type: String,
+    /// Empty means every method.
+    pub methods: Vec<String>,
+    pub csrf: Option<bool>,
+    pub website: bool,
+    pub uses_sudo: bool,
+    pub checks_token_access: bool,
+    /// Some other module registers the same path.
+    pub collides: bool,
+}
+
+impl RouteEntry {
+    /// Reachable without a logged-in user.
+    pub fn is_public(&self) -> bool {
+        matches!(self.auth.as_deref(), Some("public" | "none"))
+    }
+
+    /// CSRF protection only applies to `http` routes, and is on by default.
+    pub fn csrf_disabled(&self) -> bool {
+        self.csrf == Some(false)
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Clone)]
+pub struct RouteFilter {
+    /// Only `auth="public"` and `auth="none"` routes.
+    #[serde(default)]
+    pub public: bool,
+    #[serde(default)]
+    pub csrf_off: bool,
+    #[serde(default)]
+    pub sudo: bool,
+    #[serde(default)]
+    pub json: bool,
+    /// Only routes of these technical names.
+    pub modules: Option<Vec<String>>,
+    pub org: Option<String>,
+}
+
+impl RouteFilter {
+    fn matches(&self, entry: &RouteEntry) -> bool {
+        (!self.public || entry.is_public())
+            && (!self.csrf_off || entry.csrf_disabled())
+            && (!self.sudo || entry.uses_sudo)
+            && (!self.json || entry.http_type == "json")
+            && self
+                .modules
+                .as_ref()
+                .is_none_or(|names| names.contains(&entry.technical_name))
+            && self
+                .org
+                .as_ref()
+                .is_none_or(|org| *org == entry.organization)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CollisionEndpoint {
+    pub technical_name: String,
+    pub organization: String,
+    pub handler: String,
+}
+
+/// A path (ignoring the names of its placeholders) several modules register
+/// for overlapping HTTP methods.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RouteCollision {
+    pub route: String,
+    /// The handlers have different method names, so this isn't one module
+    /// overriding another's controller but two endpoints competing for the
+    /// path: whichever module loads last wins.
+    pub conflicting: bool,
+    pub endpoints: Vec<CollisionEndpoint>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct RouteCatalog {
+    pub routes: Vec<RouteEntry>,
+    pub collisions: Vec<RouteCollision>,
+}
+
+// `/shop/<int:order_id>` and `/shop/<int:id>` are the same werkzeug rule.
+fn normalize_route(route: &str) -> String {
+    let mut normalized = String::with_capacity(route.len());
+    let mut rest = route;
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        let placeholder = &rest[start + 1..start + len];
+        normalized.push_str(&rest[..start]);
+        normalized.push('<');
+        if let Some((converter, _)) = placeholder.rsplit_once(':') {
+            normalized.push_str(converter);
+        }
+        normalized.push('>');
+        rest = &rest[start + len + 1..];
+    }
+    normalized.push_str(rest);
+    let trimmed = normalized.trim_end_matches('/');
+    if trimmed.is_empty() {
+        "/".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+fn methods_overlap(a: &[String], b: &[String]) -> bool {
+    a.is_empty()
+        || b.is_empty()
+        || a.iter()
+            .any(|method| b.iter().any(|other| other.eq_ignore_ascii_case(method)))
+}
+
+fn find_collisions(routes: &mut [RouteEntry]) -> Vec<RouteCollision> {
+    let mut by_route: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (idx, entry) in routes.iter().enumerate() {
+        by_route
+            .entry(normalize_route(&entry.route))
+            .or_default()
+            .push(idx);
+    }
+    let mut collisions = Vec::new();
+    for (route, indexes) in by_route {
+        let mut colliding = BTreeSet::new();
+        for (pos, a) in indexes.iter().enumerate() {
+            for b in &indexes[pos + 1..] {
+                let (entry_a, entry_b) = (&routes[*a], &routes[*b]);
+                let same_module = entry_a.technical_name == entry_b.technical_name
+                    && entry_a.organization == entry_b.organization;
+                if !same_module && methods_overlap(&entry_a.methods, &entry_b.methods) {
+                    colliding.insert(*a);
+                    colliding.insert(*b);
+                }
+            }
+        }
+        if colliding.is_empty() {
+            continue;
+        }
+        let handler_names: BTreeSet<&str> = colliding
+            .iter()
+            .map(|idx| {
+                let handler = routes[*idx].handler.as_str();
+                handler.rsplit_once('.').map_or(handler, |(_, name)| name)
+            })
+            .collect();
+        let conflicting = handler_names.len() > 1;
+        let endpoints = colliding
+            .iter()
+            .map(|idx| {
+                let entry = &mut routes[*idx];
+                entry.collides = true;
+                CollisionEndpoint {
+                    technical_name: entry.technical_name.clone(),
+                    organization: entry.organization.clone(),
+                    handler: entry.handler.clone(),
+                }
+            })
+            .collect();
+        collisions.push(RouteCollision {
+            route,
+            conflicting,
+            endpoints,
+        });
+    }
+    collisions
+}
+
+/// The routes of `version_odoo` matching `filter`, sorted by path. Collisions
+/// are looked for among the routes of the module set (`filter.modules` and
+/// `filter.org`) regardless of the other filters, so a public route clashing
+/// with a private one still shows up.
+pub fn build(conn: &mut DbConnection, version_odoo: &u8, filter: &RouteFilter) -> RouteCatalog {
+    let scope = RouteFilter {
+        modules: filter.modules.clone(),
+        org: filter.org.clone(),
+        ..Default::default()
+    };
+    let mut routes: Vec<RouteEntry> =
+        module_controller::get_current_by_odoo_version(conn, version_odoo)
+            .into_iter()
+            .flat_map(|controller| {
+                let methods = controller.methods_vec();
+                controller
+                    .routes_vec()
+                    .into_iter()
+                    .map(|route| RouteEntry {
+                        route,
+                        technical_name: controller.technical_name.clone(),
+                        organization: controller.organization.clone(),
+                        repository: controller.repository.clone(),
+                        handler: format!("{}.{}", controller.class_name, controller.name),
+                        auth: controller.auth.clone(),
+                        http_type: controller.http_type.clone(),
+                        methods: methods.clone(),
+                        csrf: controller.csrf,
+                        website: controller.website,
+                        uses_sudo: controller.uses_sudo,
+                        checks_token_access: controller.checks_token_access,
+                        collides: false,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|entry| scope.matches(entry))
+            .collect();
+    routes.sort_by(|a, b| {
+        (&a.route, &a.technical_name, &a.handler).cmp(&(&b.route, &b.technical_name, &b.handler))
+    });
+    let collisions = find_collisions(&mut routes);
+    routes.retain(|entry| filter.matches(entry));
+    RouteCatalog { routes, collisions }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One line per route, for spreadsheets.
+pub fn to_csv(catalog: &RouteCatalog) -> String {
+    let mut out = String::from(
+        "route,module,organization,repository,handler,auth,type,methods,csrf,website,sudo,token_check,collides\n",
+    );
+    for entry in &catalog.routes {
+        let csrf = match entry.csrf {
+            Some(false) => "off",
+            Some(true) => "on",
+            None => "default",
+        };
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            csv_field(&entry.route),
+            csv_field(&entry.technical_name),
+            csv_field(&entry.organization),
+            csv_field(&entry.repository),
+            csv_field(&entry.handler),
+            csv_field(entry.auth.as_deref().unwrap_or_default()),
+            csv_field(&entry.http_type),
+            csv_field(&entry.methods.join(" ")),
+            csrf,
+            entry.website,
+            entry.uses_sudo,
+            entry.checks_token_access,
+            entry.collides,
+        );
+    }
+    out
+}
@@ -0,0 +1,231 @@
+// Copyright Alexandre D. Díaz
+//! The security findings of every module's current version, grouped by Odoo
+//! version, organization and rule, along with how their counts moved over
+//! the last collection runs.
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use oghutils::version::odoo_version_u8_to_string;
+use serde::{Deserialize, Serialize};
+
+use super::collection_run_security;
+use super::module_security_warning::{self, SEVERITY_ERROR, SEVERITY_WARNING};
+use crate::DbConnection;
+
+const TREND_LIMIT: i64 = 30;
+
+/// Which findings to list. Minor ones (`warning`) are left out by default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeverityFilter {
+    #[default]
+    Error,
+    Warning,
+    All,
+}
+
+impl SeverityFilter {
+    fn matches(&self, severity: &str) -> bool {
+        match self {
+            Self::Error => severity == SEVERITY_ERROR,
+            Self::Warning => severity == SEVERITY_WARNING,
+            Self::All => true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SecurityFilter {
+    pub version_odoo: Option<u8>,
+    pub org: Option<String>,
+    pub code: Option<String>,
+    pub severity: SeverityFilter,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SecurityFindingEntry {
+    pub severity: String,
+    pub message: String,
+    /// The record (without module prefix) or the route the finding is about.
+    pub xml_id: Option<String>,
+    pub technical_name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct SecurityCodeGroup {
+    pub code: String,
+    pub errors: usize,
+    pub warnings: usize,
+    pub findings: Vec<SecurityFindingEntry>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct SecurityOrgGroup {
+    pub organization: String,
+    pub errors: usize,
+    pub warnings: usize,
+    pub codes: Vec<SecurityCodeGroup>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct SecurityVersionGroup {
+    pub odoo_version: String,
+    pub errors: usize,
+    pub warnings: usize,
+    pub organizations: Vec<SecurityOrgGroup>,
+}
+
+/// One run's counts, and how they changed since the previous run of the same
+/// organization and version (`None` for the oldest one listed).
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SecurityTrendPoint {
+    pub collection_run_id: i64,
+    pub organization: String,
+    pub odoo_version: String,
+    pub end_date: Option<String>,
+    pub errors: i64,
+    pub warnings: i64,
+    pub errors_delta: Option<i64>,
+    pub warnings_delta: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct SecurityOverview {
+    pub errors: usize,
+    pub warnings: usize,
+    pub versions: Vec<SecurityVersionGroup>,
+    /// Every value found, whatever the filter, to pick from.
+    pub odoo_versions: Vec<String>,
+    pub organizations: Vec<String>,
+    pub codes: Vec<String>,
+    /// Newest first.
+    pub trend: Vec<SecurityTrendPoint>,
+}
+
+fn trend_points(infos: Vec<collection_run_security::SecurityTrendInfo>) -> Vec<SecurityTrendPoint> {
+    let mut previous: HashMap<(String, i32), (i64, i64)> = HashMap::new();
+    let mut points: Vec<SecurityTrendPoint> = infos
+        .into_iter()
+        .rev()
+        .map(|info| {
+            let key = (info.org_name.clone(), info.version_odoo);
+            let last = previous.insert(key, (info.errors, info.warnings));
+            SecurityTrendPoint {
+                collection_run_id: info.collection_run_id,
+                odoo_version: odoo_version_u8_to_string(&(info.version_odoo as u8)),
+                organization: info.org_name,
+                end_date: info.end_date,
+                errors: info.errors,
+                warnings: info.warnings,
+                errors_delta: last.map(|(errors, _)| info.errors - errors),
+                warnings_delta: last.map(|(_, warnings)| info.warnings - warnings),
+            }
+        })
+        .collect();
+    points.reverse();
+    points
+}
+
+pub fn build(conn: &mut DbConnection, filter: &SecurityFilter) -> SecurityOverview {
+    let findings = module_security_warning::get_all_current(conn);
+    let mut overview = SecurityOverview {
+        odoo_versions: findings
+            .iter()
+            .map(|finding| finding.version_odoo)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .rev()
+            .map(|version| odoo_version_u8_to_string(&(version as u8)))
+            .collect(),
+        organizations: findings
+            .iter()
+            .map(|finding| finding.org_name.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect(),
+        codes: findings
+            .iter()
+            .map(|finding| finding.code.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+
+    // version (newest first) -> organization -> code
+    let mut grouped: BTreeMap<
+        std::cmp::Reverse<i32>,
+        BTreeMap<String, BTreeMap<String, SecurityCodeGroup>>,
+    > = BTreeMap::new();
+    for finding in findings.into_iter().filter(|finding| {
+        filter.severity.matches(&finding.severity)
+            && filter
+                .version_odoo
+                .is_none_or(|version| version as i32 == finding.version_odoo)
+            && filter
+                .org
+                .as_ref()
+                .is_none_or(|org| *org == finding.org_name)
+            && filter
+                .code
+                .as_ref()
+                .is_none_or(|code| *code == finding.code)
+    }) {
+        let group = grouped
+            .entry(std::cmp::Reverse(finding.version_odoo))
+            .or_default()
+            .entry(finding.org_name)
+            .or_default()
+            .entry(finding.code.clone())
+            .or_insert_with(|| SecurityCodeGroup {
+                code: finding.code,
+                ..Default::default()
+            });
+        if finding.severity == SEVERITY_ERROR {
+            group.errors += 1;
+        } else {
+            group.warnings += 1;
+        }
+        group.findings.push(SecurityFindingEntry {
+            severity: finding.severity,
+            message: finding.message,
+            xml_id: finding.xml_id,
+            technical_name: finding.technical_name,
+        });
+    }
+
+    for (std::cmp::Reverse(version_odoo), orgs) in grouped {
+        let mut version_group = SecurityVersionGroup {
+            odoo_version: odoo_version_u8_to_string(&(version_odoo as u8)),
+            ..Default::default()
+        };
+        for (organization, codes) in orgs {
+            let mut org_group = SecurityOrgGroup {
+                organization,
+                ..Default::default()
+            };
+            for mut code_group in codes.into_values() {
+                code_group.findings.sort_by(|a, b| {
+                    (&a.technical_name, &a.xml_id).cmp(&(&b.technical_name, &b.xml_id))
+                });
+                org_group.errors += code_group.errors;
+                org_group.warnings += code_group.warnings;
+                org_group.codes.push(code_group);
+            }
+            version_group.errors += org_group.errors;
+            version_group.warnings += org_group.warnings;
+            version_group.organizations.push(org_group);
+        }
+        overview.errors += version_group.errors;
+        overview.warnings += version_group.warnings;
+        overview.versions.push(version_group);
+    }
+
+    overview.trend = trend_points(collection_run_security::get_trend(
+        conn,
+        filter.version_odoo.as_ref(),
+        filter.org.as_deref(),
+        filter.code.as_deref(),
+        TREND_LIMIT,
+    ));
+    overview
+}
@@ -2,8 +2,10 @@
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::dialect;
 use crate::schema::system_event;
 use crate::utils::date::get_sqlite_utc_now;
+use crate::DbConnection;
 use oghutils::version::odoo_version_u8_to_string;
 
 use super::system_event_type;
@@ -64,7 +66,7 @@ struct NewSystemEvent<'a> {
 /// `id` of the last row received). `date_from`/`date_to` are an inclusive
 /// `YYYY-MM-DD` day range, either end independently optional (open-ended).
 pub fn get_messages_page(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     before_id: i64,
     date_from: Option<&str>,
     date_to: Option<&str>,
@@ -78,8 +80,8 @@ pub fn get_messages_page(
     const ERR: &str = "DB error in system_event::get_messages_page";
 
     match (date_from, date_to) {
-        (Some(from), Some(to)) => diesel::sql_query(format!(
-            "{SELECT}WHERE se.id < ? AND date(se.date) >= date(?) AND date(se.date) <= date(?) \
+        (Some(from), Some(to)) => dialect::sql_query(format!(
+            "{SELECT}WHERE se.id < ? AND SUBSTR(se.date, 1, 10) >= ? AND SUBSTR(se.date, 1, 10) <= ? \
              ORDER BY se.id DESC LIMIT ?"
         ))
         .bind::<diesel::sql_types::BigInt, _>(before_id)
@@ -88,23 +90,23 @@ pub fn get_messages_page(
         .bind::<diesel::sql_types::BigInt, _>(limit)
         .load::<Model>(conn)
         .expect(ERR),
-        (Some(from), None) => diesel::sql_query(format!(
-            "{SELECT}WHERE se.id < ? AND date(se.date) >= date(?) ORDER BY se.id DESC LIMIT ?"
+        (Some(from), None) => dialect::sql_query(format!(
+            "{SELECT}WHERE se.id < ? AND SUBSTR(se.date, 1, 10) >= ? ORDER BY se.id DESC LIMIT ?"
         ))
         .bind::<diesel::sql_types::BigInt, _>(before_id)
         .bind::<diesel::sql_types::Text, _>(from)
         .bind::<diesel::sql_types::BigInt, _>(limit)
         .load::<Model>(conn)
         .expect(ERR),
-        (None, Some(to)) => diesel::sql_query(format!(
-            "{SELECT}WHERE se.id < ? AND date(se.date) <= date(?) ORDER BY se.id DESC LIMIT ?"
+        (None, Some(to)) => dialect::sql_query(format!(
+            "{SELECT}WHERE se.id < ? AND SUBSTR(se.date, 1, 10) <= ? ORDER BY se.id DESC LIMIT ?"
         ))
         .bind::<diesel::sql_types::BigInt, _>(before_id)
         .bind::<diesel::sql_types::Text, _>(to)
         .bind::<diesel::sql_types::BigInt, _>(limit)
         .load::<Model>(conn)
         .expect(ERR),
-        (None, None) => diesel::sql_query(format!(
+        (None, None) => dialect::sql_query(format!(
             "{SELECT}WHERE se.id < ? ORDER BY se.id DESC LIMIT ?"
         ))
         .bind::<diesel::sql_types::BigInt, _>(before_id)
@@ -119,7 +121,7 @@ pub fn get_messages_page(
 /// action never requires a migration. `message` must be plain text: it is
 /// rendered auto-escaped by the template, not as raw HTML.
 pub fn add(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     event_type_name: &str,
     severity: &str,
     message: &str,
@@ -136,7 +138,7 @@ pub fn add(
             is_html: false,
         })
         .execute(conn)?;
-    let id = crate::models::last_insert_rowid(conn);
+    let id = crate::models::last_insert_id(conn);
 
     Ok(Model {
         id,
@@ -149,8 +151,31 @@ pub fn add(
     })
 }
 
+/// Deletes the info and success events logged before `before` (a date in the
+/// sqlite text format, see `utils::date`). Returns how many were removed.
+pub fn delete_info_before(conn: &mut DbConnection, before: &str) -> QueryResult<usize> {
+    delete_before(conn, &[SEVERITY_INFO, SEVERITY_SUCCESS], before)
+}
+
+/// Same as `delete_info_before` for warnings and errors, which retention
+/// policies usually keep around for longer.
+pub fn delete_warnings_before(conn: &mut DbConnection, before: &str) -> QueryResult<usize> {
+    delete_before(conn, &[SEVERITY_WARNING, SEVERITY_ERROR], before)
+}
+
+fn delete_before(conn: &mut DbConnection, severities: &[&str], before: &str) -> QueryResult<usize> {
+    diesel::delete(
+        system_event::table.filter(
+            system_event::severity
+                .eq_any(severities)
+                .and(system_event::date.lt(before)),
+        ),
+    )
+    .execute(conn)
+}
+
 pub fn register_started_task_collector(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     source: &str,
     odoo_version: &str,
 ) -> QueryResult<Model> {
@@ -159,7 +184,7 @@ pub fn register_started_task_collector(
 }
 
 pub fn register_new_dependency_module(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     dep_name: &str,
     module_technical_name: &str,
     module_name: &str,
@@ -171,16 +196,13 @@ pub fn register_new_dependency_module(
     add(conn, "dependency", SEVERITY_SUCCESS, &msg)
 }
 
-pub fn register_new_gh_organization(
-    conn: &mut SqliteConnection,
-    org_name: &str,
-) -> QueryResult<Model> {
+pub fn register_new_gh_organization(conn: &mut DbConnection, org_name: &str) -> QueryResult<Model> {
     let msg = format!("New organization '{org_name}' added");
     add(conn, "organization", SEVERITY_SUCCESS, &msg)
 }
 
 pub fn register_new_gh_repository(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     org_name: &str,
     repo_name: &str,
 ) -> QueryResult<Model> {
@@ -189,7 +211,7 @@ pub fn register_new_gh_repository(
 }
 
 pub fn register_new_module_author(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     author_name: &str,
     module_technical_name: &str,
     module_name: &str,
@@ -202,7 +224,7 @@ pub fn register_new_module_author(
 }
 
 pub fn register_new_module_maintainer(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     maintainer_name: &str,
     module_technical_name: &str,
     module_name: &str,
@@ -215,7 +237,7 @@ pub fn register_new_module_maintainer(
 }
 
 pub fn register_new_module_committer(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     committer_name: &str,
     module_technical_name: &str,
     module_name: &str,
@@ -228,7 +250,7 @@ pub fn register_new_module_committer(
 }
 
 pub fn register_new_module(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_technical_name: &str,
     module_name: &str,
     module_version: &str,
@@ -243,7 +265,7 @@ pub fn register_new_module(
 }
 
 pub fn register_delete_module_author(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     author_name: &str,
     module_technical_name: &str,
     module_name: &str,
@@ -256,7 +278,7 @@ pub fn register_delete_module_author(
 }
 
 pub fn register_delete_module_dependency(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     dep_name: &str,
     dep_type: &str,
     module_technical_name: &str,
@@ -270,7 +292,7 @@ pub fn register_delete_module_dependency(
 }
 
 pub fn register_delete_module_maintainer(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     maintainer_name: &str,
     module_technical_name: &str,
     module_name: &str,
@@ -283,7 +305,7 @@ pub fn register_delete_module_maintainer(
 }
 
 pub fn register_update_module(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_info: &LogUpdateModuleInfo,
 ) -> QueryResult<Model> {
     let changes = module_info
@@ -316,7 +338,7 @@ pub fn register_update_module(
 }
 
 pub fn register_finished_task_collector(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     scan_seconds: &str,
     number_modules: &str,
     org_name: &str,
@@ -328,8 +350,42 @@ pub fn register_finished_task_collector(
     add(conn, "collector", SEVERITY_SUCCESS, &msg)
 }
 
+/// Outcome of a run started by `oghcollector daemon`.
+pub fn register_scheduled_task_collector(
+    conn: &mut DbConnection,
+    source: &str,
+    odoo_version: &str,
+    scan_seconds: &str,
+    error: Option<&str>,
+) -> QueryResult<Model> {
+    match error {
+        None => {
+            let msg = format!(
+                "Scheduled scan of '{source}' [{odoo_version}] completed in {scan_seconds} seconds"
+            );
+            add(conn, "scheduler", SEVERITY_SUCCESS, &msg)
+        }
+        Some(error) => {
+            let msg = format!(
+                "Scheduled scan of '{source}' [{odoo_version}] failed after {scan_seconds} seconds: {error}"
+            );
+            add(conn, "scheduler", SEVERITY_ERROR, &msg)
+        }
+    }
+}
+
+pub fn register_skipped_task_collector(
+    conn: &mut DbConnection,
+    source: &str,
+    odoo_version: &str,
+    reason: &str,
+) -> QueryResult<Model> {
+    let msg = format!("Scheduled scan of '{source}' [{odoo_version}] skipped: {reason}");
+    add(conn, "scheduler", SEVERITY_WARNING, &msg)
+}
+
 pub fn register_problem_module_version(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_technical_name: &str,
     module_name: &str,
     repo_name: &str,
@@ -343,7 +399,7 @@ pub fn register_problem_module_version(
 }
 
 pub fn register_new_migration_pr(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_technical_name: &str,
     pr_name: &str,
     prid: &i64,
@@ -358,7 +414,7 @@ pub fn register_new_migration_pr(
 }
 
 pub fn register_closed_migration_pr(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_technical_name: &str,
     pr_name: &str,
     prid: &i64,
@@ -375,7 +431,7 @@ pub fn register_closed_migration_pr(
 /// Non-grave security finding from the collector's static analysis (grave
 /// ones go to module_security_warning and the module detail page instead).
 pub fn register_security_warning(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     module_technical_name: &str,
     module_name: &str,
     module_version_odoo: &str,
@@ -390,7 +446,7 @@ pub fn register_security_warning(
 }
 
 pub fn register_new_osv_vulnerability(
-    conn: &mut SqliteConnection,
+    conn: &mut DbConnection,
     dep_name: &str,
     osv_id: &str,
     module_technical_name: &str,
@@ -1,5 +1,15 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    atlas_graph (id) {
+        id -> BigInt,
+        version_odoo -> Integer,
+        data -> Text,
+        etag -> Text,
+        update_date -> Text,
+    }
+}
+
 diesel::table! {
     author (id) {
         id -> BigInt,
@@ -7,10 +17,104 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    bot_account (id) {
+        id -> BigInt,
+        name -> Text,
+        create_date -> Text,
+    }
+}
+
+diesel::table! {
+    collection_job (id) {
+        id -> BigInt,
+        kind -> Text,
+        source -> Text,
+        branch -> Text,
+        git_type -> Text,
+        repo_url -> Nullable<Text>,
+        status -> Text,
+        error -> Nullable<Text>,
+        create_date -> Text,
+        start_date -> Nullable<Text>,
+        end_date -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    collection_run (id) {
+        id -> BigInt,
+        source -> Text,
+        org_name -> Text,
+        version_odoo -> Integer,
+        forge -> Text,
+        trigger -> Text,
+        status -> Text,
+        start_date -> Text,
+        end_date -> Nullable<Text>,
+        duration -> Nullable<BigInt>,
+        repos_count -> Integer,
+        repos_failed -> Integer,
+        modules_added -> Integer,
+        modules_updated -> Integer,
+        modules_removed -> Integer,
+        api_calls -> BigInt,
+        error -> Nullable<Text>,
+        modules_scanned -> Integer,
+        security_errors -> Nullable<Integer>,
+        security_warnings -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    collection_run_security (id) {
+        id -> BigInt,
+        collection_run_id -> BigInt,
+        severity -> Text,
+        code -> Text,
+        count -> Integer,
+    }
+}
+
+diesel::table! {
+    collection_run_repo (id) {
+        id -> BigInt,
+        collection_run_id -> BigInt,
+        org_name -> Text,
+        repo_name -> Text,
+        clone_status -> Text,
+        analysis_status -> Text,
+        modules_added -> Integer,
+        modules_updated -> Integer,
+        modules_removed -> Integer,
+        error -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     committer (id) {
         id -> BigInt,
         name -> Text,
+        is_bot -> Bool,
+    }
+}
+
+diesel::table! {
+    committer_email (id) {
+        id -> BigInt,
+        committer_id -> BigInt,
+        email -> Text,
+        create_date -> Text,
+    }
+}
+
+diesel::table! {
+    dataset_source (id) {
+        id -> BigInt,
+        name -> Text,
+        format_version -> Integer,
+        export_date -> Text,
+        import_date -> Text,
     }
 }
 
@@ -61,6 +165,7 @@ diesel::table! {
         gh_organization_id -> BigInt,
         create_date -> Text,
         update_date -> Text,
+        dataset_source_id -> Nullable<BigInt>,
     }
 }
 
@@ -97,6 +202,7 @@ diesel::table! {
         installation -> Nullable<Text>,
         usage -> Nullable<Text>,
         icon -> Nullable<Text>,
+        summary -> Nullable<Text>,
     }
 }
 
@@ -146,6 +252,31 @@ diesel::table! {
         signature -> Text,
         docstring -> Nullable<Text>,
         module_version_id -> BigInt,
+        checks_token_access -> Bool,
+    }
+}
+
+diesel::table! {
+    module_lineage (id) {
+        id -> BigInt,
+        kind -> Text,
+        predecessor_technical_name -> Text,
+        predecessor_gh_repository_id -> Nullable<BigInt>,
+        successor_technical_name -> Text,
+        successor_gh_repository_id -> Nullable<BigInt>,
+        version_odoo -> Integer,
+        source -> Text,
+        create_date -> Text,
+    }
+}
+
+diesel::table! {
+    module_health_score (id) {
+        id -> BigInt,
+        module_id -> BigInt,
+        version_odoo -> Integer,
+        score -> Integer,
+        update_date -> Text,
     }
 }
 
@@ -193,6 +324,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    module_ownership (id) {
+        id -> BigInt,
+        module_id -> BigInt,
+        committer_id -> BigInt,
+        lines -> Integer,
+        module_version_id -> BigInt,
+    }
+}
+
 diesel::table! {
     module_record (id) {
         id -> BigInt,
@@ -217,6 +358,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    module_upgrade_coverage (id) {
+        id -> BigInt,
+        technical_name -> Text,
+        version_odoo -> Integer,
+        status -> Text,
+        note -> Nullable<Text>,
+        is_new -> Bool,
+        is_removed -> Bool,
+        update_date -> Text,
+    }
+}
+
 diesel::table! {
     module_version (id) {
         id -> BigInt,
@@ -224,6 +378,7 @@ diesel::table! {
         version_module -> Text,
         create_date -> Text,
         update_date -> Text,
+        test_files -> Nullable<Integer>,
     }
 }
 
@@ -240,6 +395,33 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    open_pull_request (id) {
+        id -> BigInt,
+        gh_repository_id -> BigInt,
+        version_odoo -> Integer,
+        prid -> BigInt,
+        name -> Text,
+        author -> Nullable<Text>,
+        head_ref -> Text,
+        is_draft -> Bool,
+        labels -> Text,
+        review_count -> Integer,
+        approval_count -> Integer,
+        ci_status -> Nullable<Text>,
+        created_at -> Nullable<Text>,
+        last_message_at -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    open_pull_request_module (id) {
+        id -> BigInt,
+        open_pull_request_id -> BigInt,
+        module_technical_name -> Text,
+    }
+}
+
 diesel::table! {
     pull_request (id) {
         id -> BigInt,
@@ -263,6 +445,19 @@ diesel::table! {
         prid -> BigInt,
         created_at -> Text,
         closed_at -> Text,
+        close_reason -> Text,
+        first_review_at -> Nullable<Text>,
+        approved_at -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    pull_request_review (id) {
+        id -> BigInt,
+        pull_request_history_id -> BigInt,
+        reviewer -> Text,
+        state -> Text,
+        submitted_at -> Text,
     }
 }
 
@@ -284,15 +479,29 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(collection_run_repo -> collection_run (collection_run_id));
+diesel::joinable!(collection_run_security -> collection_run (collection_run_id));
+diesel::joinable!(committer_email -> committer (committer_id));
 diesel::joinable!(module_controller -> module_version (module_version_id));
 diesel::joinable!(module_model -> module_version (module_version_id));
+diesel::joinable!(module_ownership -> module_version (module_version_id));
 diesel::joinable!(module_record -> module_version (module_version_id));
 diesel::joinable!(module_security_warning -> module_version (module_version_id));
 diesel::joinable!(module_view -> module_version (module_version_id));
+diesel::joinable!(open_pull_request_module -> open_pull_request (open_pull_request_id));
+diesel::joinable!(pull_request_review -> pull_request_history (pull_request_history_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    atlas_graph,
     author,
+    bot_account,
+    collection_job,
+    collection_run,
+    collection_run_repo,
+    collection_run_security,
     committer,
+    committer_email,
+    dataset_source,
     dependency,
     dependency_module,
     dependency_osv,
@@ -305,16 +514,23 @@ diesel::allow_tables_to_appear_in_same_query!(
     module_committer,
     module_committer_period,
     module_controller,
+    module_lineage,
+    module_health_score,
     module_maintainer,
     module_model,
     module_model_field,
     module_model_method,
+    module_ownership,
     module_record,
     module_security_warning,
+    module_upgrade_coverage,
     module_version,
     module_view,
+    open_pull_request,
+    open_pull_request_module,
     pull_request,
     pull_request_history,
+    pull_request_review,
     system_event,
     system_event_type,
 );
@@ -0,0 +1,13 @@
+// Copyright Alexandre D. Díaz
+
+// Mermaid has no escape for `"` inside a quoted string, only entity codes.
+pub fn mermaid_label(s: &str) -> String {
+    s.replace('"', "#quot;")
+}
+
+pub fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,2 +1,3 @@
 // Copyright Alexandre D. Díaz
 pub mod date;
+pub mod escape;
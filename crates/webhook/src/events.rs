@@ -0,0 +1,168 @@
+// Copyright Alexandre D. Díaz
+use regex::Regex;
+use serde_json::Value;
+use std::sync::LazyLock;
+
+use sqlitedb::models::collection_job::{JobRequestInfo, KIND_COLLECT, KIND_PULL_REQUESTS};
+
+// Only Odoo version branches ("16.0", "17.0", ...) are collected.
+static VERSION_BRANCH_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d+\.\d+$").unwrap());
+
+const GITLAB_ORIGIN: &str = "https://gitlab.com";
+
+fn version_branch(git_ref: &str) -> Option<String> {
+    let branch = git_ref.strip_prefix("refs/heads/").unwrap_or(git_ref);
+    VERSION_BRANCH_RE
+        .is_match(branch)
+        .then(|| branch.to_string())
+}
+
+// "https://git.example.com/group/project" -> "https://git.example.com"
+fn url_origin(url: &str) -> Option<&str> {
+    let host_start = url.find("://")? + 3;
+    Some(match url[host_start..].find('/') {
+        Some(pos) => &url[..host_start + pos],
+        None => url,
+    })
+}
+
+/// Maps a GitHub event (`X-GitHub-Event`) to the job it should enqueue, if any.
+pub fn parse_github(event: &str, payload: &Value) -> Option<JobRequestInfo> {
+    let (kind, branch) = match event {
+        // Branch deletions have nothing left to collect.
+        "push" if payload["deleted"].as_bool() != Some(true) => {
+            (KIND_COLLECT, version_branch(payload["ref"].as_str()?)?)
+        }
+        "pull_request" => (
+            KIND_PULL_REQUESTS,
+            version_branch(payload["pull_request"]["base"]["ref"].as_str()?)?,
+        ),
+        _ => return None,
+    };
+    Some(JobRequestInfo {
+        kind: kind.to_string(),
+        source: payload["repository"]["full_name"].as_str()?.to_string(),
+        branch,
+        git_type: "GH".to_string(),
+        repo_url: payload["repository"]["clone_url"]
+            .as_str()
+            .map(str::to_string),
+    })
+}
+
+/// Maps a GitLab event (`X-Gitlab-Event`) to the job it should enqueue, if any.
+/// Self-hosted instances get their API URL in the job's `git_type`.
+pub fn parse_gitlab(event: &str, payload: &Value) -> Option<JobRequestInfo> {
+    let (kind, branch) = match event {
+        "Push Hook"
+            if payload["after"]
+                .as_str()
+                .is_some_and(|sha| !sha.trim_matches('0').is_empty()) =>
+        {
+            (KIND_COLLECT, version_branch(payload["ref"].as_str()?)?)
+        }
+        "Merge Request Hook" => (
+            KIND_PULL_REQUESTS,
+            version_branch(payload["object_attributes"]["target_branch"].as_str()?)?,
+        ),
+        _ => return None,
+    };
+    let project = &payload["project"];
+    let origin = url_origin(project["web_url"].as_str()?)?;
+    let git_type = if origin == GITLAB_ORIGIN {
+        "GL".to_string()
+    } else {
+        format!("GL:{origin}/api/v4/")
+    };
+    Some(JobRequestInfo {
+        kind: kind.to_string(),
+        source: project["path_with_namespace"].as_str()?.to_string(),
+        branch,
+        git_type,
+        repo_url: project["git_http_url"].as_str().map(str::to_string),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_github() {
+        let push = json!({
+            "ref": "refs/heads/16.0",
+            "deleted": false,
+            "repository": {
+                "full_name": "OCA/web",
+                "clone_url": "https://github.com/OCA/web.git",
+            },
+        });
+        assert_eq!(
+            parse_github("push", &push),
+            Some(JobRequestInfo {
+                kind: KIND_COLLECT.to_string(),
+                source: "OCA/web".to_string(),
+                branch: "16.0".to_string(),
+                git_type: "GH".to_string(),
+                repo_url: Some("https://github.com/OCA/web.git".to_string()),
+            })
+        );
+
+        let mut feature_push = push.clone();
+        feature_push["ref"] = json!("refs/heads/16.0-mig-web_foo");
+        assert_eq!(parse_github("push", &feature_push), None);
+        let mut deleted_push = push.clone();
+        deleted_push["deleted"] = json!(true);
+        assert_eq!(parse_github("push", &deleted_push), None);
+        assert_eq!(parse_github("issues", &push), None);
+
+        let pull_request = json!({
+            "action": "opened",
+            "pull_request": {"base": {"ref": "17.0"}},
+            "repository": {"full_name": "OCA/web"},
+        });
+        let job = parse_github("pull_request", &pull_request).unwrap();
+        assert_eq!(job.kind, KIND_PULL_REQUESTS);
+        assert_eq!(job.branch, "17.0");
+        assert_eq!(job.repo_url, None);
+    }
+
+    #[test]
+    fn test_parse_gitlab() {
+        let push = json!({
+            "ref": "refs/heads/15.0",
+            "after": "da1560886d4f094c3e6c9ef40349f7d38b5d27d7",
+            "project": {
+                "path_with_namespace": "acme/odoo-addons",
+                "web_url": "https://git.acme.test/acme/odoo-addons",
+                "git_http_url": "https://git.acme.test/acme/odoo-addons.git",
+            },
+        });
+        let job = parse_gitlab("Push Hook", &push).unwrap();
+        assert_eq!(job.kind, KIND_COLLECT);
+        assert_eq!(job.source, "acme/odoo-addons");
+        assert_eq!(job.branch, "15.0");
+        assert_eq!(job.git_type, "GL:https://git.acme.test/api/v4/");
+        assert_eq!(
+            job.repo_url.as_deref(),
+            Some("https://git.acme.test/acme/odoo-addons.git")
+        );
+
+        let mut deleted_push = push.clone();
+        deleted_push["after"] = json!("0000000000000000000000000000000000000000");
+        assert_eq!(parse_gitlab("Push Hook", &deleted_push), None);
+
+        let merge_request = json!({
+            "object_attributes": {"target_branch": "16.0"},
+            "project": {
+                "path_with_namespace": "acme/odoo-addons",
+                "web_url": "https://gitlab.com/acme/odoo-addons",
+            },
+        });
+        let job = parse_gitlab("Merge Request Hook", &merge_request).unwrap();
+        assert_eq!(job.kind, KIND_PULL_REQUESTS);
+        assert_eq!(job.git_type, "GL");
+        assert_eq!(parse_gitlab("Tag Push Hook", &merge_request), None);
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright Alexandre D. Díaz
+mod events;
+mod signature;
+
+use actix_web::{middleware::Logger, post, web, App, HttpRequest, HttpResponse, HttpServer};
+use serde_json::Value;
+use std::fs;
+
+use sqlitedb::models::collection_job::{self, JobRequestInfo};
+use sqlitedb::Pool;
+
+struct WebhookSecrets {
+    github: Option<String>,
+    gitlab: Option<String>,
+}
+
+// Same lookup order as the collector's API tokens: docker secret first, then env.
+fn read_secret(secret_name: &str, env_var: &str) -> Option<String> {
+    fs::read_to_string(format!("/run/secrets/{secret_name}"))
+        .ok()
+        .or_else(|| std::env::var(env_var).ok())
+        .map(|secret| secret.trim().to_string())
+        .filter(|secret| !secret.is_empty())
+}
+
+fn resolve_db_path() -> String {
+    std::env::var("OGHCOLLECTOR_DB_PATH").unwrap_or_else(|_| "data/data.db".to_string())
+}
+
+fn resolve_bind_addr() -> String {
+    std::env::var("OGHCOLLECTOR_WEBHOOK_BIND_ADDR").unwrap_or_else(|_| "0.0.0.0:8082".to_string())
+}
+
+fn get_header<'a>(req: &'a HttpRequest, name: &str) -> &'a str {
+    req.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+}
+
+async fn enqueue(pool: web::Data<Pool>, job: Option<JobRequestInfo>) -> HttpResponse {
+    let Some(job) = job else {
+        return HttpResponse::Ok().body("ignored");
+    };
+    let res = web::block(move || {
+        let mut conn = pool.get().unwrap();
+        collection_job::enqueue(&mut conn, &job).map(|inserted| (job, inserted))
+    })
+    .await;
+    match res {
+        Ok(Ok((job, inserted))) => {
+            log::info!(
+                "Queued {} job for '{}' {} ({})",
+                job.kind,
+                job.source,
+                job.branch,
+                if inserted > 0 {
+                    "new"
+                } else {
+                    "already pending"
+                }
+            );
+            HttpResponse::Accepted().body("queued")
+        }
+        Ok(Err(err)) => {
+            log::error!("Can't enqueue the job: {err}");
+            HttpResponse::InternalServerError().finish()
+        }
+        Err(err) => {
+            log::error!("Can't enqueue the job: {err}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[post("/github")]
+async fn route_github(
+    pool: web::Data<Pool>,
+    secrets: web::Data<WebhookSecrets>,
+    req: HttpRequest,
+    body: web::Bytes,
+) -> HttpResponse {
+    let Some(secret) = &secrets.github else {
+        return HttpResponse::NotFound().finish();
+    };
+    if !signature::verify_github(secret, &body, get_header(&req, "X-Hub-Signature-256")) {
+        return HttpResponse::Unauthorized().finish();
+    }
+    let Ok(payload) = serde_json::from_slice::<Value>(&body) else {
+        return HttpResponse::BadRequest().finish();
+    };
+    let job = events::parse_github(get_header(&req, "X-GitHub-Event"), &payload);
+    enqueue(pool, job).await
+}
+
+#[post("/gitlab")]
+async fn route_gitlab(
+    pool: web::Data<Pool>,
+    secrets: web::Data<WebhookSecrets>,
+    req: HttpRequest,
+    body: web::Bytes,
+) -> HttpResponse {
+    let Some(token) = &secrets.gitlab else {
+        return HttpResponse::NotFound().finish();
+    };
+    if !signature::verify_gitlab(token, get_header(&req, "X-Gitlab-Token")) {
+        return HttpResponse::Unauthorized().finish();
+    }
+    let Ok(payload) = serde_json::from_slice::<Value>(&body) else {
+        return HttpResponse::BadRequest().finish();
+    };
+    let job = events::parse_gitlab(get_header(&req, "X-Gitlab-Event"), &payload);
+    enqueue(pool, job).await
+}
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+
+    let secrets = web::Data::new(WebhookSecrets {
+        github: read_secret("webhook_gh_secret", "OGHCOLLECTOR_WEBHOOK_GH_SECRET"),
+        gitlab: read_secret("webhook_gl_token", "OGHCOLLECTOR_WEBHOOK_GL_TOKEN"),
+    });
+    // An endpoint without its secret stays disabled instead of accepting
+    // unauthenticated payloads.
+    if secrets.github.is_none() && secrets.gitlab.is_none() {
+        log::warn!(
+            "Neither OGHCOLLECTOR_WEBHOOK_GH_SECRET nor OGHCOLLECTOR_WEBHOOK_GL_TOKEN are set - \
+             every endpoint is disabled."
+        );
+    }
+
+    let db_path = resolve_db_path();
+    if cfg!(feature = "postgres") {
+        log::info!("using PostgreSQL DB from OGHCOLLECTOR_DATABASE_URL");
+    } else {
+        log::info!("using SQLite DB at {db_path}");
+    }
+    let pool = sqlitedb::new_write_pool(&sqlitedb::database_url(&db_path)?);
+
+    let bind_addr = resolve_bind_addr();
+    log::info!("Webhook endpoints listening on http://{bind_addr}/github and /gitlab");
+    HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(secrets.clone())
+            .wrap(Logger::default())
+            .service(route_github)
+            .service(route_gitlab)
+    })
+    .bind(bind_addr)?
+    .run()
+    .await
+}
@@ -0,0 +1,63 @@
+// Copyright Alexandre D. Díaz
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Checks GitHub's `X-Hub-Signature-256` header (`sha256=<hex HMAC of the raw body>`).
+pub fn verify_github(secret: &str, body: &[u8], header: &str) -> bool {
+    let Some(signature) = header.strip_prefix("sha256=").and_then(decode_hex) else {
+        return false;
+    };
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// GitLab doesn't sign payloads: `X-Gitlab-Token` carries the configured secret as is.
+pub fn verify_gitlab(token: &str, header: &str) -> bool {
+    constant_time_eq(token.as_bytes(), header.as_bytes())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_github() {
+        // Example from GitHub's "Validating webhook deliveries" docs.
+        let secret = "It's a Secret to Everybody";
+        let header = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+        assert!(verify_github(secret, b"Hello, World!", header));
+        assert!(!verify_github(secret, b"Hello, World?", header));
+        assert!(!verify_github("other secret", b"Hello, World!", header));
+        assert!(!verify_github(secret, b"Hello, World!", &header[7..]));
+        assert!(!verify_github(secret, b"Hello, World!", "sha256=zz"));
+    }
+
+    #[test]
+    fn test_verify_gitlab() {
+        assert!(verify_gitlab("s3cr3t", "s3cr3t"));
+        assert!(!verify_gitlab("s3cr3t", "s3cr3"));
+        assert!(!verify_gitlab("s3cr3t", "S3CR3T"));
+        assert!(!verify_gitlab("s3cr3t", ""));
+    }
+}
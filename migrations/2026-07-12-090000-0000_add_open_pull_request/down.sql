@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS open_pull_request_module;
+DROP TABLE IF EXISTS open_pull_request;
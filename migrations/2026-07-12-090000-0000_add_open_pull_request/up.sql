@@ -0,0 +1,44 @@
+-- Every open PR/MR against a tracked version branch, not only the ones
+-- following the OCA migration naming convention (those stay in
+-- `pull_request`, which carries the migration-specific lifecycle). This is
+-- a plain snapshot: the collector replaces a repo/version's rows on every
+-- run, so closed PRs simply disappear. `labels` is JSON array text (like
+-- module_controller.routes). `review_count` is the number of submitted
+-- reviews on GitHub and of discussion notes on GitLab, which has no review
+-- objects; `approval_count` is the number of distinct approvers.
+CREATE TABLE IF NOT EXISTS open_pull_request (
+    id integer primary key autoincrement,
+    gh_repository_id integer not null references gh_repository(id),
+    version_odoo integer not null,
+    prid integer not null,
+    name text not null,
+    author text,
+    head_ref text not null default '',
+    is_draft boolean not null default 0,
+    labels text not null default '[]',
+    review_count integer not null default 0,
+    approval_count integer not null default 0,
+    ci_status text,
+    created_at text,
+    last_message_at text,
+    CONSTRAINT fk_gh_repository
+        FOREIGN KEY (gh_repository_id)
+        REFERENCES gh_repository(id)
+        ON DELETE CASCADE,
+    UNIQUE (gh_repository_id, prid)
+);
+CREATE INDEX IF NOT EXISTS idx_open_pull_request_version_odoo ON open_pull_request(version_odoo);
+
+-- Module folders a PR/MR changes, from the provider's PR files API (a PR
+-- can touch several modules at once, and a module can have several PRs).
+CREATE TABLE IF NOT EXISTS open_pull_request_module (
+    id integer primary key autoincrement,
+    open_pull_request_id integer not null references open_pull_request(id),
+    module_technical_name text not null,
+    CONSTRAINT fk_open_pull_request
+        FOREIGN KEY (open_pull_request_id)
+        REFERENCES open_pull_request(id)
+        ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_open_pull_request_module_technical_name ON open_pull_request_module(module_technical_name);
+CREATE INDEX IF NOT EXISTS idx_open_pull_request_module_open_pull_request_id ON open_pull_request_module(open_pull_request_id);
@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS pull_request_review;
+DROP INDEX IF EXISTS idx_pull_request_history_gh_repository_id;
+DELETE FROM pull_request_history WHERE close_reason != 'merged';
+ALTER TABLE pull_request_history DROP COLUMN approved_at;
+ALTER TABLE pull_request_history DROP COLUMN first_review_at;
+ALTER TABLE pull_request_history DROP COLUMN close_reason;
@@ -0,0 +1,29 @@
+-- Real PR/MR lifecycle instead of detection times: `closed_at` now holds
+-- the provider's `merged_at`/`closed_at` (the collector's detection time is
+-- only a fallback when the lookup fails), and every closure gets a row, not
+-- only merges - `close_reason` tells them apart ('merged', 'closed' without
+-- merge, 'retargeted' when the PR is still open but left the tracked
+-- branch/naming convention, 'unknown' when the provider couldn't be
+-- queried). Rows from before this migration were all merges.
+ALTER TABLE pull_request_history ADD COLUMN close_reason text not null default 'merged';
+ALTER TABLE pull_request_history ADD COLUMN first_review_at text;
+ALTER TABLE pull_request_history ADD COLUMN approved_at text;
+CREATE INDEX IF NOT EXISTS idx_pull_request_history_gh_repository_id ON pull_request_history(gh_repository_id);
+
+-- Reviews a closed PR/MR got (author's own excluded), for per-reviewer
+-- latency stats. `state` is normalized across providers: 'approved',
+-- 'changes_requested' or 'commented' (GitLab approvals come from its
+-- "approved this merge request" system notes, comments from user notes).
+CREATE TABLE IF NOT EXISTS pull_request_review (
+    id integer primary key autoincrement,
+    pull_request_history_id integer not null references pull_request_history(id),
+    reviewer text not null,
+    state text not null,
+    submitted_at text not null,
+    CONSTRAINT fk_pull_request_history
+        FOREIGN KEY (pull_request_history_id)
+        REFERENCES pull_request_history(id)
+        ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_pull_request_review_pull_request_history_id ON pull_request_review(pull_request_history_id);
+CREATE INDEX IF NOT EXISTS idx_pull_request_review_reviewer ON pull_request_review(reviewer);
@@ -0,0 +1,29 @@
+-- Links a module to the one that took its place, so a module OCA moved to
+-- another repository, or that got renamed/merged into another one across
+-- Odoo versions, isn't just seen as one module deleted by
+-- `module::delete_outdated` and an unrelated new one created.
+-- `kind` is 'moved' (same technical_name, another repository of the same
+-- organization), 'renamed' or 'merged' (OpenUpgrade's apriori.py
+-- renamed_modules/merged_modules). `version_odoo` is the first version where
+-- the successor applies. Repository ids are only known for detected moves;
+-- apriori entries are name-only. `source` is 'collector' or 'apriori'.
+-- Two organizations can move or rename the same technical name in the same
+-- Odoo version, so the repositories are part of what makes a link unique;
+-- NULLs never collide in a UNIQUE key, hence the COALESCE in an index
+-- instead of a table constraint.
+CREATE TABLE IF NOT EXISTS module_lineage (
+    id integer primary key autoincrement,
+    kind text not null,
+    predecessor_technical_name text not null,
+    predecessor_gh_repository_id integer references gh_repository(id),
+    successor_technical_name text not null,
+    successor_gh_repository_id integer references gh_repository(id),
+    version_odoo integer not null,
+    source text not null,
+    create_date text not null
+);
+CREATE UNIQUE INDEX IF NOT EXISTS uniq_module_lineage ON module_lineage(kind,
+    predecessor_technical_name, COALESCE(predecessor_gh_repository_id, 0),
+    successor_technical_name, COALESCE(successor_gh_repository_id, 0), version_odoo);
+CREATE INDEX IF NOT EXISTS idx_module_lineage_predecessor_technical_name ON module_lineage(predecessor_technical_name);
+CREATE INDEX IF NOT EXISTS idx_module_lineage_successor_technical_name ON module_lineage(successor_technical_name);
@@ -0,0 +1,18 @@
+-- OpenUpgrade's per-version coverage tables (`docsource/modules*.rst` in
+-- the OCA/OpenUpgrade checkout), keyed by module and *target* version: a
+-- row at 170 tells whether OpenUpgrade migrates that module from 16.0 to
+-- 17.0. `status` is the table's raw text ('Done', 'Nothing to do', 'Partial',
+-- ... or empty when not covered yet); `is_new`/`is_removed` mirror the
+-- |new|/|del| markers. Replaced wholesale per version on every import.
+CREATE TABLE IF NOT EXISTS module_upgrade_coverage (
+    id integer primary key autoincrement,
+    technical_name text not null,
+    version_odoo integer not null,
+    status text not null default '',
+    note text,
+    is_new boolean not null default 0,
+    is_removed boolean not null default 0,
+    update_date text not null,
+    UNIQUE (technical_name, version_odoo)
+);
+CREATE INDEX IF NOT EXISTS idx_module_upgrade_coverage_version_odoo ON module_upgrade_coverage(version_odoo);
@@ -0,0 +1,15 @@
+-- Emails seen for each committer (git author email after .mailmap
+-- resolution). A committer is looked up by email before by name, so the
+-- same person showing up under two spellings of their name across
+-- repositories keeps a single `committer` row.
+CREATE TABLE IF NOT EXISTS committer_email (
+    id integer primary key autoincrement,
+    committer_id integer not null references committer(id),
+    email text not null unique,
+    create_date text not null,
+    CONSTRAINT fk_committer
+        FOREIGN KEY (committer_id)
+        REFERENCES committer(id)
+        ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_committer_email_committer_id ON committer_email(committer_id);
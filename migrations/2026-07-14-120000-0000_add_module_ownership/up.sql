@@ -0,0 +1,26 @@
+-- Surviving-lines ownership of a module version snapshot: how many lines of
+-- its current tree (`git blame`, i18n folders excluded) each committer last
+-- touched. Replaced per module_version_id whenever the module is
+-- re-analyzed, like module_record.
+CREATE TABLE IF NOT EXISTS module_ownership (
+    id integer primary key autoincrement,
+    module_id integer not null references module(id),
+    committer_id integer not null references committer(id),
+    lines integer not null,
+    module_version_id integer not null references module_version(id),
+    CONSTRAINT fk_module
+        FOREIGN KEY (module_id)
+        REFERENCES module(id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_committer
+        FOREIGN KEY (committer_id)
+        REFERENCES committer(id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_module_version
+        FOREIGN KEY (module_version_id)
+        REFERENCES module_version(id)
+        ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX IF NOT EXISTS uniq_module_ownership ON module_ownership(module_version_id, committer_id);
+CREATE INDEX IF NOT EXISTS idx_module_ownership_module_id ON module_ownership(module_id);
+CREATE INDEX IF NOT EXISTS idx_module_ownership_committer_id ON module_ownership(committer_id);
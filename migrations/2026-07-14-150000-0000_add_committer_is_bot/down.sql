@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS bot_account;
+
+DROP INDEX IF EXISTS idx_committer_is_bot;
+ALTER TABLE committer DROP COLUMN is_bot;
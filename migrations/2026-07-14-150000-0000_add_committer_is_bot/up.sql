@@ -0,0 +1,20 @@
+-- Bot/automation accounts are flagged on the committer itself, so every
+-- ranking filters on `is_bot` instead of a hardcoded name list. The
+-- collector recomputes the flag on every run from its rules file, GitHub
+-- `[bot]` suffixes and `bot_account`, the list admins edit with
+-- `oghcollector bots add|remove`.
+ALTER TABLE committer ADD COLUMN is_bot boolean not null default 0;
+CREATE INDEX IF NOT EXISTS idx_committer_is_bot ON committer(is_bot);
+
+CREATE TABLE IF NOT EXISTS bot_account (
+    id integer primary key autoincrement,
+    name text not null unique,
+    create_date text not null
+);
+
+-- Same accounts the rankings used to exclude, until the next collector run.
+UPDATE committer SET is_bot = 1
+WHERE name IN ('Odoo Translation Bot', 'OCA-git-bot', 'Weblate', 'oca-ci',
+               'OCA Transbot', 'Launchpad Translations on behalf of openerp',
+               'oca-travis', 'GitHub')
+   OR name LIKE '%[bot]';
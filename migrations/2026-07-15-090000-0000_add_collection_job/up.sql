@@ -0,0 +1,21 @@
+-- Targeted re-collections requested by the webhook receiver (oghwebhook)
+-- and drained by `oghcollector queue`. `kind` is 'collect' (repo-mode
+-- collection of `source` at `branch`) or 'pull_requests' (migration PR
+-- state only); `git_type` is the collector's `[git_type]` argument and
+-- `repo_url` the clone URL from the payload. Only one pending job per
+-- (kind, source, branch): bursts of pushes collapse into a single run.
+CREATE TABLE IF NOT EXISTS collection_job (
+    id integer primary key autoincrement,
+    kind text not null,
+    source text not null,
+    branch text not null,
+    git_type text not null,
+    repo_url text,
+    status text not null default 'pending',
+    error text,
+    create_date text not null,
+    start_date text,
+    end_date text
+);
+CREATE UNIQUE INDEX IF NOT EXISTS uniq_collection_job_pending ON collection_job(kind, source, branch) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS idx_collection_job_status ON collection_job(status);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS collection_run_repo;
+DROP TABLE IF EXISTS collection_run;
@@ -0,0 +1,41 @@
+-- One row per collector run (`oghcollector <origin> <version>`), whatever
+-- started it: `trigger` is 'manual', 'schedule' (oghcollector daemon) or
+-- 'webhook' (oghcollector queue). Runs left 'running' by a killed process are
+-- closed as 'failed' when the next run of the same organization starts.
+CREATE TABLE IF NOT EXISTS collection_run (
+    id integer primary key autoincrement,
+    source text not null,
+    org_name text not null,
+    version_odoo integer not null,
+    forge text not null,
+    trigger text not null,
+    status text not null default 'running',
+    start_date text not null,
+    end_date text,
+    duration integer,
+    repos_count integer not null default 0,
+    repos_failed integer not null default 0,
+    modules_added integer not null default 0,
+    modules_updated integer not null default 0,
+    modules_removed integer not null default 0,
+    api_calls integer not null default 0,
+    error text
+);
+CREATE INDEX IF NOT EXISTS idx_collection_run_org_name_status ON collection_run(org_name, status);
+
+-- Outcome of each repository of a run: whether it could be cloned/updated and
+-- analyzed, what changed in its modules and why it failed, if it did.
+CREATE TABLE IF NOT EXISTS collection_run_repo (
+    id integer primary key autoincrement,
+    collection_run_id integer not null,
+    org_name text not null,
+    repo_name text not null,
+    clone_status text not null,
+    analysis_status text not null,
+    modules_added integer not null default 0,
+    modules_updated integer not null default 0,
+    modules_removed integer not null default 0,
+    error text,
+    FOREIGN KEY(collection_run_id) REFERENCES collection_run(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_collection_run_repo_collection_run_id ON collection_run_repo(collection_run_id);
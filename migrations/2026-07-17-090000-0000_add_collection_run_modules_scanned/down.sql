@@ -0,0 +1 @@
+ALTER TABLE collection_run DROP COLUMN modules_scanned;
@@ -0,0 +1,3 @@
+-- Manifests the run analyzed, changed or not: the size of what a collection
+-- covers, next to the `modules_added`/`updated`/`removed` deltas.
+ALTER TABLE collection_run ADD COLUMN modules_scanned integer not null default 0;
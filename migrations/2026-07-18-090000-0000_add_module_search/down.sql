@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS module_search;
+ALTER TABLE module DROP COLUMN summary;
@@ -0,0 +1,38 @@
+-- The manifest's one-line `summary`, shown in search results and indexed
+-- next to the name.
+ALTER TABLE module ADD COLUMN summary text;
+
+-- Full-text index over a module and the code analysis of its current
+-- version, keyed by `rowid = module.id`. Kept in sync by the collector
+-- (see models::module_search::refresh), not by triggers: a single module
+-- save touches several tables, so the row is rebuilt once at the end.
+CREATE VIRTUAL TABLE IF NOT EXISTS module_search USING fts5(
+    technical_name,
+    name,
+    summary,
+    description,
+    docs,
+    models,
+    fields,
+    views,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO module_search(rowid, technical_name, name, summary, description, docs, models, fields, views)
+SELECT mod.id, mod.technical_name, mod.name, mod.summary, mod.description,
+    COALESCE(mod.installation, '') || ' ' || COALESCE(mod.usage, ''),
+    (SELECT GROUP_CONCAT(DISTINCT mm.model_name)
+     FROM module_model as mm
+     INNER JOIN module_version as mv ON mv.id = mm.module_version_id
+     WHERE mm.module_id = mod.id AND mv.version_module = mod.version_module),
+    (SELECT GROUP_CONCAT(mmf.name || ' ' || COALESCE(
+            CASE WHEN json_valid(mmf.attrs) THEN json_extract(mmf.attrs, '$.string') END, ''), ' ')
+     FROM module_model_field as mmf
+     INNER JOIN module_model as mm ON mm.id = mmf.module_model_id
+     INNER JOIN module_version as mv ON mv.id = mm.module_version_id
+     WHERE mm.module_id = mod.id AND mv.version_module = mod.version_module),
+    (SELECT GROUP_CONCAT(COALESCE(mvw.name, '') || ' ' || mvw.xml_id, ' ')
+     FROM module_view as mvw
+     INNER JOIN module_version as mv ON mv.id = mvw.module_version_id
+     WHERE mvw.module_id = mod.id AND mv.version_module = mod.version_module)
+FROM module as mod;
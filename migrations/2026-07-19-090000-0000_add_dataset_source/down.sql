@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS gh_repository_old (
+    id integer primary key autoincrement,
+    name text unique not null,
+    gh_organization_id integer not null references gh_organization(id),
+    create_date text not null,
+    update_date text not null,
+    CONSTRAINT fk_gh_organization
+        FOREIGN KEY (gh_organization_id)
+        REFERENCES gh_organization(id)
+        ON DELETE CASCADE
+);
+INSERT INTO gh_repository_old (id, name, gh_organization_id, create_date, update_date)
+SELECT id, name, gh_organization_id, create_date, update_date FROM gh_repository;
+DROP TABLE gh_repository;
+ALTER TABLE gh_repository_old RENAME TO gh_repository;
+CREATE UNIQUE INDEX IF NOT EXISTS uniq_name_gh_organization_id ON gh_repository(name, gh_organization_id);
+
+DROP TABLE IF EXISTS dataset_source;
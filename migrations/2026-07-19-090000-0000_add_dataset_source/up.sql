@@ -0,0 +1,33 @@
+-- Instances whose dataset was merged in with `oghcollector import`, one row
+-- per source name: re-importing a newer export of the same source updates it.
+CREATE TABLE IF NOT EXISTS dataset_source (
+    id integer primary key autoincrement,
+    name text not null unique,
+    format_version integer not null,
+    export_date text not null,
+    import_date text not null
+);
+
+-- Repository names are only unique per organization (a customer's `web` and
+-- OCA's `web` must live side by side once datasets are merged), and each
+-- repository remembers which dataset it came from: NULL means this instance
+-- collects it. SQLite can't drop a column constraint in place, so the table
+-- is rebuilt; ids are kept, so nothing referencing it has to change.
+CREATE TABLE IF NOT EXISTS gh_repository_new (
+    id integer primary key autoincrement,
+    name text not null,
+    gh_organization_id integer not null references gh_organization(id),
+    create_date text not null,
+    update_date text not null,
+    dataset_source_id integer references dataset_source(id) on delete set null,
+    CONSTRAINT fk_gh_organization
+        FOREIGN KEY (gh_organization_id)
+        REFERENCES gh_organization(id)
+        ON DELETE CASCADE
+);
+INSERT INTO gh_repository_new (id, name, gh_organization_id, create_date, update_date)
+SELECT id, name, gh_organization_id, create_date, update_date FROM gh_repository;
+DROP TABLE gh_repository;
+ALTER TABLE gh_repository_new RENAME TO gh_repository;
+CREATE UNIQUE INDEX IF NOT EXISTS uniq_name_gh_organization_id ON gh_repository(name, gh_organization_id);
+CREATE INDEX IF NOT EXISTS idx_gh_repository_dataset_source_id ON gh_repository(dataset_source_id);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS atlas_graph;
@@ -0,0 +1,11 @@
+-- The Atlas dependency graph of each Odoo version, as the JSON the page
+-- loads. Rebuilt by the collector at the end of every run (see
+-- models::atlas_graph::refresh) instead of on every request; `etag` is the
+-- hash of `data`, so unchanged graphs are answered with a 304.
+CREATE TABLE IF NOT EXISTS atlas_graph (
+    id integer primary key autoincrement,
+    version_odoo integer not null unique,
+    data text not null,
+    etag text not null,
+    update_date text not null
+);
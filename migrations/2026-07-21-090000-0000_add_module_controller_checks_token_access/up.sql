@@ -0,0 +1,5 @@
+-- Whether the handler validates record access itself (the portal
+-- `_document_check_access` pattern), so a public route using sudo can be
+-- told apart from one guarded by an access token. Controllers collected
+-- before this column existed read as unchecked until their next scan.
+ALTER TABLE module_controller ADD COLUMN checks_token_access boolean not null default 0;
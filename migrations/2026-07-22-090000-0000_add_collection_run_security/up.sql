@@ -0,0 +1,15 @@
+-- Security findings of the organization and Odoo version a run collected,
+-- counted per rule as they stood when the run finished: what /security draws
+-- its trends from. `security_errors`/`security_warnings` are the totals, NULL
+-- for runs from before findings were counted.
+ALTER TABLE collection_run ADD COLUMN security_errors integer;
+ALTER TABLE collection_run ADD COLUMN security_warnings integer;
+CREATE TABLE IF NOT EXISTS collection_run_security (
+    id integer primary key autoincrement,
+    collection_run_id integer not null,
+    severity text not null,
+    code text not null,
+    count integer not null,
+    FOREIGN KEY(collection_run_id) REFERENCES collection_run(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_collection_run_security_collection_run_id ON collection_run_security(collection_run_id);
@@ -0,0 +1,3 @@
+-- `test_*.py` files under the version's `tests/` folder, as counted by the
+-- analyzer; NULL for snapshots analyzed before it counted them.
+ALTER TABLE module_version ADD COLUMN test_files integer;
@@ -0,0 +1,11 @@
+-- Each module's health score (see models::module_health), stored by the
+-- collector at the end of every run of its version instead of computed on
+-- every module list or search request.
+CREATE TABLE IF NOT EXISTS module_health_score (
+    id integer primary key autoincrement,
+    module_id integer not null unique references module(id),
+    version_odoo integer not null,
+    score integer not null,
+    update_date text not null
+);
+CREATE INDEX IF NOT EXISTS idx_module_health_score_version_odoo ON module_health_score(version_odoo);
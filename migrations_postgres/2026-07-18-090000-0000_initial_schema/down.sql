@@ -0,0 +1,39 @@
+DROP TABLE IF EXISTS module_search CASCADE;
+DROP TABLE IF EXISTS collection_run_repo CASCADE;
+DROP TABLE IF EXISTS collection_run CASCADE;
+DROP TABLE IF EXISTS collection_job CASCADE;
+DROP TABLE IF EXISTS bot_account CASCADE;
+DROP TABLE IF EXISTS module_ownership CASCADE;
+DROP TABLE IF EXISTS committer_email CASCADE;
+DROP TABLE IF EXISTS module_upgrade_coverage CASCADE;
+DROP TABLE IF EXISTS module_lineage CASCADE;
+DROP TABLE IF EXISTS pull_request_review CASCADE;
+DROP TABLE IF EXISTS open_pull_request_module CASCADE;
+DROP TABLE IF EXISTS open_pull_request CASCADE;
+DROP TABLE IF EXISTS pull_request_history CASCADE;
+DROP TABLE IF EXISTS module_controller CASCADE;
+DROP TABLE IF EXISTS module_security_warning CASCADE;
+DROP TABLE IF EXISTS module_record CASCADE;
+DROP TABLE IF EXISTS module_model_method CASCADE;
+DROP TABLE IF EXISTS module_model_field CASCADE;
+DROP TABLE IF EXISTS module_model CASCADE;
+DROP TABLE IF EXISTS module_view CASCADE;
+DROP TABLE IF EXISTS module_version CASCADE;
+DROP TABLE IF EXISTS module_committer_period CASCADE;
+DROP TABLE IF EXISTS pull_request CASCADE;
+DROP TABLE IF EXISTS system_event CASCADE;
+DROP TABLE IF EXISTS system_event_type CASCADE;
+DROP TABLE IF EXISTS module_maintainer CASCADE;
+DROP TABLE IF EXISTS module_committer CASCADE;
+DROP TABLE IF EXISTS module_author CASCADE;
+DROP TABLE IF EXISTS maintainer CASCADE;
+DROP TABLE IF EXISTS dependency_osv CASCADE;
+DROP TABLE IF EXISTS dependency_module CASCADE;
+DROP TABLE IF EXISTS dependency CASCADE;
+DROP TABLE IF EXISTS dependency_type CASCADE;
+DROP TABLE IF EXISTS module CASCADE;
+DROP TABLE IF EXISTS gh_repository CASCADE;
+DROP TABLE IF EXISTS gh_organization CASCADE;
+DROP TABLE IF EXISTS committer CASCADE;
+DROP TABLE IF EXISTS author CASCADE;
+DROP TEXT SEARCH CONFIGURATION IF EXISTS module_search;
@@ -0,0 +1,474 @@
+-- PostgreSQL counterpart of every SQLite migration up to
+-- 2026-07-18-090000-0000_add_module_search, squashed: there are no older
+-- PostgreSQL databases to upgrade. Later schema changes get a migration in
+-- both `migrations/` and `migrations_postgres/`.
+--
+-- Same tables, columns and indexes; only the types and defaults are spelled
+-- the PostgreSQL way. Foreign keys are enforced here (SQLite connections don't
+-- turn them on): they cascade, or set NULL where the reference is optional.
+
+CREATE TABLE author (
+    id bigserial primary key,
+    name text not null unique
+);
+
+CREATE TABLE committer (
+    id bigserial primary key,
+    name text not null unique,
+    is_bot boolean not null default false
+);
+
+CREATE TABLE gh_organization (
+    id bigserial primary key,
+    name text not null unique
+);
+
+CREATE TABLE gh_repository (
+    id bigserial primary key,
+    name text unique not null,
+    gh_organization_id bigint not null,
+    create_date text not null,
+    update_date text not null,
+    CONSTRAINT fk_gh_organization FOREIGN KEY (gh_organization_id) REFERENCES gh_organization(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uniq_name_gh_organization_id ON gh_repository(name, gh_organization_id);
+
+CREATE TABLE module (
+    id bigserial primary key,
+    technical_name text not null,
+    version_odoo integer not null,
+    name text not null,
+    version_module text not null,
+    description text,
+    website text,
+    license text default 'LGPL-3',
+    category text default 'Uncategorized',
+    auto_install boolean not null default false,
+    application boolean not null default false,
+    installable boolean not null default true,
+    gh_repository_id bigint not null,
+    create_date text not null,
+    update_date text not null,
+    folder_size bigint not null,
+    last_commit_hash text not null,
+    last_commit_author text not null,
+    last_commit_name text not null,
+    last_commit_date text not null,
+    last_commit_partof text,
+    installation text,
+    usage text,
+    icon text,
+    summary text,
+    CONSTRAINT fk_gh_repository FOREIGN KEY (gh_repository_id) REFERENCES gh_repository(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uniq_tech_name_ver_odoo_gh_repository_id ON module(technical_name, version_odoo, gh_repository_id);
+
+CREATE TABLE dependency_type (
+    id bigserial primary key,
+    name text not null unique
+);
+INSERT INTO dependency_type(name) VALUES ('module'), ('python'), ('bin');
+
+CREATE TABLE dependency (
+    id bigserial primary key,
+    dependency_type_id bigint not null,
+    name text not null,
+    CONSTRAINT fk_dependency_type FOREIGN KEY (dependency_type_id) REFERENCES dependency_type(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uniq_dep_type_name ON dependency(dependency_type_id, name);
+
+CREATE TABLE dependency_module (
+    id bigserial primary key,
+    dependency_id bigint not null,
+    module_id bigint not null,
+    CONSTRAINT fk_dependency FOREIGN KEY (dependency_id) REFERENCES dependency(id) ON DELETE CASCADE,
+    CONSTRAINT fk_module FOREIGN KEY (module_id) REFERENCES module(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uniq_dep_module ON dependency_module(dependency_id, module_id);
+
+CREATE TABLE dependency_osv (
+    id bigserial primary key,
+    dependency_module_id bigint not null,
+    osv_id text not null,
+    details text not null,
+    fixed_in text not null,
+    CONSTRAINT fk_dependency_module_osv FOREIGN KEY (dependency_module_id) REFERENCES dependency_module(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uniq_dep_dep_mod_id_osv_id ON dependency_osv(dependency_module_id, osv_id);
+
+CREATE TABLE maintainer (
+    id bigserial primary key,
+    name text not null unique
+);
+
+CREATE TABLE module_author (
+    id bigserial primary key,
+    module_id bigint not null,
+    author_id bigint not null,
+    CONSTRAINT fk_module FOREIGN KEY (module_id) REFERENCES module(id) ON DELETE CASCADE,
+    CONSTRAINT fk_author FOREIGN KEY (author_id) REFERENCES author(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uniq_module_author ON module_author(module_id, author_id);
+
+CREATE TABLE module_committer (
+    id bigserial primary key,
+    module_id bigint not null,
+    committer_id bigint not null,
+    commits integer not null,
+    insertions integer not null default 0,
+    deletions integer not null default 0,
+    CONSTRAINT fk_module FOREIGN KEY (module_id) REFERENCES module(id) ON DELETE CASCADE,
+    CONSTRAINT fk_committer FOREIGN KEY (committer_id) REFERENCES committer(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uniq_module_committer ON module_committer(module_id, committer_id);
+
+CREATE TABLE module_maintainer (
+    id bigserial primary key,
+    module_id bigint not null,
+    maintainer_id bigint not null,
+    CONSTRAINT fk_module FOREIGN KEY (module_id) REFERENCES module(id) ON DELETE CASCADE,
+    CONSTRAINT fk_maintainer FOREIGN KEY (maintainer_id) REFERENCES maintainer(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uniq_module_maintainer ON module_maintainer(module_id, maintainer_id);
+
+CREATE TABLE system_event_type (
+    id bigserial primary key,
+    name text not null unique
+);
+INSERT INTO system_event_type(name) VALUES
+    ('issue'), ('internal'), ('module'), ('maintainer'),
+    ('committer'), ('dependency'), ('author'), ('repository'), ('organization');
+
+CREATE TABLE system_event (
+    id bigserial primary key,
+    message text not null,
+    date text not null,
+    event_type_id bigint not null,
+    severity text not null default 'info',
+    is_html boolean not null default true,
+    CONSTRAINT fk_event_type FOREIGN KEY (event_type_id) REFERENCES system_event_type(id) ON DELETE CASCADE
+);
+
+CREATE TABLE pull_request (
+    id bigserial primary key,
+    name text not null,
+    version_odoo integer not null,
+    module_technical_name text not null,
+    prid bigint not null,
+    gh_repository_id bigint not null,
+    created_at text,
+    ci_status text,
+    last_message_at text,
+    CONSTRAINT fk_gh_repository FOREIGN KEY (gh_repository_id) REFERENCES gh_repository(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uniq_gh_repository_id_prid ON pull_request(gh_repository_id, prid);
+
+CREATE TABLE module_committer_period (
+    id bigserial primary key,
+    module_id bigint not null,
+    committer_id bigint not null,
+    year integer not null,
+    month integer not null,
+    commits integer not null,
+    CONSTRAINT fk_module FOREIGN KEY (module_id) REFERENCES module(id) ON DELETE CASCADE,
+    CONSTRAINT fk_committer FOREIGN KEY (committer_id) REFERENCES committer(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uniq_module_committer_period ON module_committer_period(module_id, committer_id, year, month);
+CREATE INDEX idx_module_committer_period_year_month ON module_committer_period(year, month);
+
+CREATE TABLE module_version (
+    id bigserial primary key,
+    module_id bigint not null,
+    version_module text not null,
+    create_date text not null,
+    update_date text not null,
+    CONSTRAINT fk_module FOREIGN KEY (module_id) REFERENCES module(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_module_version_module_id ON module_version(module_id);
+CREATE UNIQUE INDEX idx_module_version_module_id_version_module ON module_version(module_id, version_module);
+
+CREATE TABLE module_view (
+    id bigserial primary key,
+    module_id bigint not null,
+    xml_id text not null,
+    name text,
+    model text,
+    inherit_xml_id text,
+    view_type text,
+    module_version_id bigint not null references module_version(id) on delete cascade,
+    CONSTRAINT fk_module FOREIGN KEY (module_id) REFERENCES module(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_module_view_module_id ON module_view(module_id);
+
+CREATE TABLE module_model (
+    id bigserial primary key,
+    module_id bigint not null,
+    model_name text not null,
+    class_name text not null,
+    inherit_from text,
+    is_new_model boolean not null default false,
+    docstring text,
+    attrs text,
+    module_version_id bigint not null references module_version(id) on delete cascade,
+    CONSTRAINT fk_module FOREIGN KEY (module_id) REFERENCES module(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_module_model_module_id ON module_model(module_id);
+CREATE INDEX idx_module_model_model_name ON module_model(model_name);
+
+CREATE TABLE module_model_field (
+    id bigserial primary key,
+    module_model_id bigint not null,
+    name text not null,
+    field_type text not null,
+    relation text,
+    attrs text,
+    CONSTRAINT fk_module_model FOREIGN KEY (module_model_id) REFERENCES module_model(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_module_model_field_module_model_id ON module_model_field(module_model_id);
+
+CREATE TABLE module_model_method (
+    id bigserial primary key,
+    module_model_id bigint not null,
+    name text not null,
+    decorators text,
+    signature text not null default '',
+    docstring text,
+    CONSTRAINT fk_module_model FOREIGN KEY (module_model_id) REFERENCES module_model(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_module_model_method_module_model_id ON module_model_method(module_model_id);
+CREATE INDEX idx_module_view_module_version_id ON module_view(module_version_id);
+CREATE INDEX idx_module_model_module_version_id ON module_model(module_version_id);
+
+CREATE TABLE module_record (
+    id bigserial primary key,
+    module_id bigint not null,
+    xml_id text not null,
+    model text not null,
+    noupdate boolean not null default false,
+    fields text,
+    module_version_id bigint not null references module_version(id) on delete cascade,
+    CONSTRAINT fk_module FOREIGN KEY (module_id) REFERENCES module(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_module_record_module_id ON module_record(module_id);
+CREATE INDEX idx_module_record_module_version_id ON module_record(module_version_id);
+CREATE INDEX idx_module_record_model ON module_record(model);
+
+CREATE TABLE module_security_warning (
+    id bigserial primary key,
+    module_id bigint not null,
+    severity text not null,
+    code text not null,
+    message text not null,
+    xml_id text,
+    module_version_id bigint not null references module_version(id) on delete cascade,
+    CONSTRAINT fk_module FOREIGN KEY (module_id) REFERENCES module(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_module_security_warning_module_id ON module_security_warning(module_id);
+CREATE INDEX idx_module_security_warning_module_version_id ON module_security_warning(module_version_id);
+
+CREATE TABLE module_controller (
+    id bigserial primary key,
+    module_id bigint not null,
+    class_name text not null,
+    name text not null,
+    routes text not null,
+    auth text,
+    http_type text not null,
+    methods text,
+    csrf boolean,
+    website boolean not null default false,
+    uses_sudo boolean not null default false,
+    signature text not null default '',
+    docstring text,
+    module_version_id bigint not null references module_version(id) on delete cascade,
+    CONSTRAINT fk_module FOREIGN KEY (module_id) REFERENCES module(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_module_controller_module_id ON module_controller(module_id);
+CREATE INDEX idx_module_controller_module_version_id ON module_controller(module_version_id);
+
+CREATE TABLE pull_request_history (
+    id bigserial primary key,
+    module_technical_name text not null,
+    version_odoo integer not null,
+    gh_repository_id bigint not null,
+    prid bigint not null,
+    created_at text not null,
+    closed_at text not null,
+    close_reason text not null default 'merged',
+    first_review_at text,
+    approved_at text,
+    CONSTRAINT fk_gh_repository FOREIGN KEY (gh_repository_id) REFERENCES gh_repository(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_pull_request_history_version_odoo ON pull_request_history(version_odoo);
+
+CREATE TABLE open_pull_request (
+    id bigserial primary key,
+    gh_repository_id bigint not null,
+    version_odoo integer not null,
+    prid bigint not null,
+    name text not null,
+    author text,
+    head_ref text not null default '',
+    is_draft boolean not null default false,
+    labels text not null default '[]',
+    review_count integer not null default 0,
+    approval_count integer not null default 0,
+    ci_status text,
+    created_at text,
+    last_message_at text,
+    CONSTRAINT fk_gh_repository FOREIGN KEY (gh_repository_id) REFERENCES gh_repository(id) ON DELETE CASCADE,
+    UNIQUE (gh_repository_id, prid)
+);
+CREATE INDEX idx_open_pull_request_version_odoo ON open_pull_request(version_odoo);
+
+CREATE TABLE open_pull_request_module (
+    id bigserial primary key,
+    open_pull_request_id bigint not null,
+    module_technical_name text not null,
+    CONSTRAINT fk_open_pull_request FOREIGN KEY (open_pull_request_id) REFERENCES open_pull_request(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_open_pull_request_module_technical_name ON open_pull_request_module(module_technical_name);
+CREATE INDEX idx_open_pull_request_module_open_pull_request_id ON open_pull_request_module(open_pull_request_id);
+CREATE INDEX idx_pull_request_history_gh_repository_id ON pull_request_history(gh_repository_id);
+
+CREATE TABLE pull_request_review (
+    id bigserial primary key,
+    pull_request_history_id bigint not null,
+    reviewer text not null,
+    state text not null,
+    submitted_at text not null,
+    CONSTRAINT fk_pull_request_history FOREIGN KEY (pull_request_history_id) REFERENCES pull_request_history(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_pull_request_review_pull_request_history_id ON pull_request_review(pull_request_history_id);
+CREATE INDEX idx_pull_request_review_reviewer ON pull_request_review(reviewer);
+
+CREATE TABLE module_lineage (
+    id bigserial primary key,
+    kind text not null,
+    predecessor_technical_name text not null,
+    predecessor_gh_repository_id bigint references gh_repository(id) on delete set null,
+    successor_technical_name text not null,
+    successor_gh_repository_id bigint references gh_repository(id) on delete set null,
+    version_odoo integer not null,
+    source text not null,
+    create_date text not null
+);
+CREATE UNIQUE INDEX uniq_module_lineage ON module_lineage(kind,
+    predecessor_technical_name, COALESCE(predecessor_gh_repository_id, 0),
+    successor_technical_name, COALESCE(successor_gh_repository_id, 0), version_odoo);
+CREATE INDEX idx_module_lineage_predecessor_technical_name ON module_lineage(predecessor_technical_name);
+CREATE INDEX idx_module_lineage_successor_technical_name ON module_lineage(successor_technical_name);
+
+CREATE TABLE module_upgrade_coverage (
+    id bigserial primary key,
+    technical_name text not null,
+    version_odoo integer not null,
+    status text not null default '',
+    note text,
+    is_new boolean not null default false,
+    is_removed boolean not null default false,
+    update_date text not null,
+    UNIQUE (technical_name, version_odoo)
+);
+CREATE INDEX idx_module_upgrade_coverage_version_odoo ON module_upgrade_coverage(version_odoo);
+
+CREATE TABLE committer_email (
+    id bigserial primary key,
+    committer_id bigint not null,
+    email text not null unique,
+    create_date text not null,
+    CONSTRAINT fk_committer FOREIGN KEY (committer_id) REFERENCES committer(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_committer_email_committer_id ON committer_email(committer_id);
+
+CREATE TABLE module_ownership (
+    id bigserial primary key,
+    module_id bigint not null,
+    committer_id bigint not null,
+    lines integer not null,
+    module_version_id bigint not null,
+    CONSTRAINT fk_module FOREIGN KEY (module_id) REFERENCES module(id) ON DELETE CASCADE,
+    CONSTRAINT fk_committer FOREIGN KEY (committer_id) REFERENCES committer(id) ON DELETE CASCADE,
+    CONSTRAINT fk_module_version FOREIGN KEY (module_version_id) REFERENCES module_version(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uniq_module_ownership ON module_ownership(module_version_id, committer_id);
+CREATE INDEX idx_module_ownership_module_id ON module_ownership(module_id);
+CREATE INDEX idx_module_ownership_committer_id ON module_ownership(committer_id);
+CREATE INDEX idx_committer_is_bot ON committer(is_bot);
+
+CREATE TABLE bot_account (
+    id bigserial primary key,
+    name text not null unique,
+    create_date text not null
+);
+
+CREATE TABLE collection_job (
+    id bigserial primary key,
+    kind text not null,
+    source text not null,
+    branch text not null,
+    git_type text not null,
+    repo_url text,
+    status text not null default 'pending',
+    error text,
+    create_date text not null,
+    start_date text,
+    end_date text
+);
+CREATE UNIQUE INDEX uniq_collection_job_pending ON collection_job(kind, source, branch) WHERE status = 'pending';
+CREATE INDEX idx_collection_job_status ON collection_job(status);
+
+CREATE TABLE collection_run (
+    id bigserial primary key,
+    source text not null,
+    org_name text not null,
+    version_odoo integer not null,
+    forge text not null,
+    trigger text not null,
+    status text not null default 'running',
+    start_date text not null,
+    end_date text,
+    duration bigint,
+    repos_count integer not null default 0,
+    repos_failed integer not null default 0,
+    modules_added integer not null default 0,
+    modules_updated integer not null default 0,
+    modules_removed integer not null default 0,
+    api_calls bigint not null default 0,
+    error text,
+    modules_scanned integer not null default 0
+);
+CREATE INDEX idx_collection_run_org_name_status ON collection_run(org_name, status);
+
+CREATE TABLE collection_run_repo (
+    id bigserial primary key,
+    collection_run_id bigint not null,
+    org_name text not null,
+    repo_name text not null,
+    clone_status text not null,
+    analysis_status text not null,
+    modules_added integer not null default 0,
+    modules_updated integer not null default 0,
+    modules_removed integer not null default 0,
+    error text,
+    FOREIGN KEY(collection_run_id) REFERENCES collection_run(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_collection_run_repo_collection_run_id ON collection_run_repo(collection_run_id);
+
+-- Full-text index, the counterpart of SQLite's FTS5 `module_search`. Words are
+-- folded to lowercase and unaccented; `document` weighs the technical name
+-- (A) over name and summary (B), model names (C) and the rest (D), and
+-- `content` is what `ts_headline` cuts the snippet from. Kept in sync by the
+-- collector (see models::module_search::refresh).
+CREATE EXTENSION IF NOT EXISTS unaccent;
+CREATE TEXT SEARCH CONFIGURATION module_search (COPY = simple);
+ALTER TEXT SEARCH CONFIGURATION module_search
+    ALTER MAPPING FOR asciiword, asciihword, hword_asciipart, word, hword, hword_part
+    WITH unaccent, simple;
+
+CREATE TABLE module_search (
+    module_id bigint primary key references module(id) on delete cascade,
+    content text not null,
+    document tsvector not null
+);
+CREATE INDEX idx_module_search_document ON module_search USING gin(document);
@@ -0,0 +1,3 @@
+ALTER TABLE gh_repository DROP COLUMN dataset_source_id;
+ALTER TABLE gh_repository ADD CONSTRAINT gh_repository_name_key UNIQUE (name);
+DROP TABLE IF EXISTS dataset_source;
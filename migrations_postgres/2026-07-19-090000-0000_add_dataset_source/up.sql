@@ -0,0 +1,13 @@
+-- See migrations/2026-07-19-090000-0000_add_dataset_source.
+CREATE TABLE dataset_source (
+    id bigserial primary key,
+    name text not null unique,
+    format_version integer not null,
+    export_date text not null,
+    import_date text not null
+);
+
+ALTER TABLE gh_repository DROP CONSTRAINT gh_repository_name_key;
+ALTER TABLE gh_repository
+    ADD COLUMN dataset_source_id bigint REFERENCES dataset_source(id) ON DELETE SET NULL;
+CREATE INDEX idx_gh_repository_dataset_source_id ON gh_repository(dataset_source_id);
@@ -0,0 +1,8 @@
+-- See migrations/2026-07-20-090000-0000_add_atlas_graph.
+CREATE TABLE atlas_graph (
+    id bigserial primary key,
+    version_odoo integer not null unique,
+    data text not null,
+    etag text not null,
+    update_date text not null
+);
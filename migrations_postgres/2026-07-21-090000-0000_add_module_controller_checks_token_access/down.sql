@@ -0,0 +1 @@
+ALTER TABLE module_controller DROP COLUMN checks_token_access;
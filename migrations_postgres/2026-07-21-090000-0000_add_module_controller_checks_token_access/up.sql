@@ -0,0 +1,2 @@
+-- See migrations/2026-07-21-090000-0000_add_module_controller_checks_token_access.
+ALTER TABLE module_controller ADD COLUMN checks_token_access boolean not null default false;
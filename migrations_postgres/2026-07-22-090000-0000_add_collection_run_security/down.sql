@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS collection_run_security;
+ALTER TABLE collection_run DROP COLUMN security_warnings;
+ALTER TABLE collection_run DROP COLUMN security_errors;
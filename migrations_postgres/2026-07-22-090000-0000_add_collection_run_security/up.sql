@@ -0,0 +1,12 @@
+-- See migrations/2026-07-22-090000-0000_add_collection_run_security.
+ALTER TABLE collection_run ADD COLUMN security_errors integer;
+ALTER TABLE collection_run ADD COLUMN security_warnings integer;
+CREATE TABLE collection_run_security (
+    id bigserial primary key,
+    collection_run_id bigint not null,
+    severity text not null,
+    code text not null,
+    count integer not null,
+    FOREIGN KEY(collection_run_id) REFERENCES collection_run(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_collection_run_security_collection_run_id ON collection_run_security(collection_run_id);
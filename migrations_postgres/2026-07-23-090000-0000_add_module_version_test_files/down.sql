@@ -0,0 +1 @@
+ALTER TABLE module_version DROP COLUMN test_files;
@@ -0,0 +1,2 @@
+-- See migrations/2026-07-23-090000-0000_add_module_version_test_files.
+ALTER TABLE module_version ADD COLUMN test_files integer;
@@ -0,0 +1,9 @@
+-- See migrations/2026-07-23-120000-0000_add_module_health_score.
+CREATE TABLE module_health_score (
+    id bigserial primary key,
+    module_id bigint not null unique references module(id) on delete cascade,
+    version_odoo integer not null,
+    score integer not null,
+    update_date text not null
+);
+CREATE INDEX idx_module_health_score_version_odoo ON module_health_score(version_odoo);
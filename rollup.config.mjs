@@ -30,6 +30,11 @@ export default [
       'web/js/pages/pack.mjs',
       'web/js/pages/committer.mjs',
       'web/js/pages/committers.mjs',
+      'web/js/pages/models.mjs',
+      'web/js/pages/route_catalog.mjs',
+      'web/js/pages/security.mjs',
+      'web/js/pages/repository.mjs',
+      'web/js/pages/migration_status.mjs',
     ],
     output: {
       sourcemap: (!is_production && 'inline') || false,
@@ -17,6 +17,13 @@ class SigmaAtlas extends Component {
   #el_mod_dep_graph = null;
   #el_mod_dep_control = null;
   #el_mod_dep_search_dependencies = null;
+  #el_filters = null;
+  #el_filters_modules = null;
+  // Everything in the page's query string but the version: org, repo,
+  // category, modules (a pack or the favorites), focus, depth and
+  // reverse_depth, passed through to /atlas/data.
+  #filters = new URLSearchParams();
+  #facets_version = null;
 
   onSetup() {
     Component.disableShadow();
@@ -39,6 +46,14 @@ class SigmaAtlas extends Component {
           click: this.onClickControl,
         },
       },
+      sigma_atlas_mod_dep_filters: {
+        mode: 'id',
+        events: {
+          change: this.onChangeFilters,
+          submit: this.onSubmitFilters,
+          reset: this.onResetFilters,
+        },
+      },
     });
     Component.useFetchData({
       odoo_versions: {
@@ -61,12 +76,23 @@ class SigmaAtlas extends Component {
     this.#el_mod_dep_search_dependencies = this.queryId(
       'sigma_atlas_mod_dep_search_dependencies',
     );
+    this.#el_filters = this.queryId('sigma_atlas_mod_dep_filters');
+    this.#el_filters_modules = this.queryId(
+      'sigma_atlas_mod_dep_filters_modules',
+    );
+    this.#filters = new URLSearchParams(window.location.search);
+    this.mirlo.state.odoo_version = this.#filters.get('version');
+    this.#filters.delete('version');
+    this.#syncFiltersForm();
     this.#update();
   }
 
   onStart() {
     super.onStart();
     this.#fillOdooVersionsSearchOptions();
+    if (this.mirlo.state.odoo_version) {
+      this.#el_search_select_ver.value = this.mirlo.state.odoo_version;
+    }
     this.queryId('sigma_atlas_mod_dep_search_input').focus();
   }
 
@@ -101,10 +127,13 @@ class SigmaAtlas extends Component {
 
     this.mirlo.state.odoo_version =
       this.#el_search_select_ver.value ||
+      this.mirlo.state.odoo_version ||
       this.getFetchData('odoo_versions')[0].value;
+    const query = this.#filters.toString();
     const data = await getService('requests').getJSON(
-      `/atlas/data/${this.mirlo.state.odoo_version}`,
+      `/atlas/data/${this.mirlo.state.odoo_version}${query ? `?${query}` : ''}`,
     );
+    this.#fillFilterOptions();
 
     // FIXME: Due to incosistences with forceAtlas need reconstruct the graph node.
     this.killGraph();
@@ -148,6 +177,9 @@ class SigmaAtlas extends Component {
     this.#fa2_layout.start();
     this.#fillDependecySearchOptions();
     this.#el_mod_dep_control.textContent = '⏹️';
+    const focus = this.#filters.get('focus');
+    this.mirlo.state.selectedNode =
+      focus && graph.hasNode(`o_${focus}`) ? `o_${focus}` : undefined;
 
     if (!this.#timer) {
       this.#timer = setTimeout(
@@ -182,6 +214,90 @@ class SigmaAtlas extends Component {
       .forEach(option => this.#el_search_select_ver.add(option));
   }
 
+  async #fillFilterOptions() {
+    if (this.#facets_version === this.mirlo.state.odoo_version) {
+      return;
+    }
+    this.#facets_version = this.mirlo.state.odoo_version;
+    const facets = await getService('requests').getJSON(
+      `/atlas/facets/${this.mirlo.state.odoo_version}`,
+    );
+    for (const [name, values] of [
+      ['org', facets.organizations],
+      ['repo', facets.repositories],
+      ['category', facets.categories],
+    ]) {
+      const select = this.#el_filters.elements[name];
+      const current = this.#filters.get(name) || '';
+      select.replaceChildren(select.options[0]);
+      values
+        .map(value => new Option(value))
+        .forEach(option => select.add(option));
+      // Keep a filter coming from the URL even if this version lacks it.
+      if (current && !values.includes(current)) {
+        select.add(new Option(current));
+      }
+      select.value = current;
+    }
+  }
+
+  #syncFiltersForm() {
+    const form = this.#el_filters.elements;
+    for (const name of ['org', 'repo', 'category', 'focus']) {
+      if (form[name].tagName === 'SELECT' && this.#filters.has(name)) {
+        form[name].add(new Option(this.#filters.get(name)));
+      }
+      form[name].value = this.#filters.get(name) || '';
+    }
+    form.depth.value = this.#filters.get('depth') || '1';
+    form.reverse_depth.value = this.#filters.get('reverse_depth') || '1';
+    const modules = (this.#filters.get('modules') || '')
+      .split(',')
+      .filter(Boolean);
+    this.#el_filters_modules.hidden = modules.length === 0;
+    this.#el_filters_modules.textContent = `${modules.length} selected modules`;
+  }
+
+  #applyFilters() {
+    const params = new URLSearchParams(this.#filters);
+    params.set('version', this.mirlo.state.odoo_version);
+    window.history.replaceState(
+      null,
+      '',
+      `${window.location.pathname}?${params}`,
+    );
+    this.#update();
+  }
+
+  onChangeFilters() {
+    const form = this.#el_filters.elements;
+    const modules = this.#filters.get('modules');
+    this.#filters = new URLSearchParams();
+    for (const name of ['org', 'repo', 'category', 'focus']) {
+      if (form[name].value) {
+        this.#filters.set(name, form[name].value.replace(/^o_/, ''));
+      }
+    }
+    if (modules) {
+      this.#filters.set('modules', modules);
+    }
+    if (this.#filters.has('focus')) {
+      this.#filters.set('depth', form.depth.value || '1');
+      this.#filters.set('reverse_depth', form.reverse_depth.value || '1');
+    }
+    this.#applyFilters();
+  }
+
+  onSubmitFilters(ev) {
+    ev.preventDefault();
+  }
+
+  onResetFilters() {
+    this.#filters = new URLSearchParams();
+    this.#el_filters_modules.hidden = true;
+    this.#applyFilters();
+  }
+
   setHoveredNode(node) {
     if (node) {
       this.mirlo.state.hoveredNode = node;
@@ -306,7 +422,8 @@ class SigmaAtlas extends Component {
 
   onChangeSearchVersion(ev) {
     if (ev.target.value !== this.mirlo.state.odoo_version) {
-      this.#update();
+      this.mirlo.state.odoo_version = ev.target.value;
+      this.#applyFilters();
     }
   }
 }
@@ -236,6 +236,27 @@ class DoodbaMigrationPlan extends Component {
       card.appendChild(pending_info);
     }
 
+    if (step.renamed.length > 0) {
+      const renamed_info = document.createElement('div');
+      renamed_info.classList.add('migration-step-renamed');
+      renamed_info.textContent = `🔀 Renamed/merged: ${step.renamed
+        .map(r => `${r.technical_name} → ${r.successor} (${r.kind})`)
+        .join(', ')}`;
+      card.appendChild(renamed_info);
+    }
+
+    if (step.upgrade_coverage.length > 0) {
+      const coverage_info = document.createElement('div');
+      coverage_info.classList.add('migration-step-coverage');
+      coverage_info.textContent = `🛠 OpenUpgrade: ${step.upgrade_coverage
+        .map(
+          c =>
+            `${c.technical_name} ${c.covered ? '✅' : '⚠️'} ${c.status || 'not covered'}`,
+        )
+        .join(', ')}`;
+      card.appendChild(coverage_info);
+    }
+
     if (step.missing.length > 0) {
       const missing_info = document.createElement('div');
       missing_info.classList.add('migration-step-missing');
@@ -1,11 +1,15 @@
 // Copyright Alexandre D. Díaz
-import {registerComponent} from 'mirlo';
+import {getService, registerComponent} from 'mirlo';
 import SearchDropdown from './search-dropdown.mjs';
 import '@scss/components/module-search.scss';
 
 class ModuleSearch extends SearchDropdown {
   #el_field = null;
   #el_version = null;
+  #el_health = null;
+  // org/technical_name -> {odoo version: health score}, for the full-text
+  // results too, which come without it.
+  #health_by_module = new Map();
 
   get searchEndpoint() {
     return '/common/odoo/module/list';
@@ -15,8 +19,13 @@ class ModuleSearch extends SearchDropdown {
     await super.onWillStart(...arguments);
     this.#el_field = this.queryId('field');
     this.#el_version = this.queryId('version');
+    this.#el_health = this.queryId('health');
     const versions = new Set();
     for (const module of this.getFetchData('records')) {
+      this.#health_by_module.set(
+        `${module.org_name}/${module.technical_name}`,
+        module.health,
+      );
       for (const version of module.versions) {
         versions.add(version);
       }
@@ -36,6 +45,7 @@ class ModuleSearch extends SearchDropdown {
       ...super.getEventDefs(),
       field: {mode: 'id', events: {change: this.onChangeFilters}},
       version: {mode: 'id', events: {change: this.onChangeFilters}},
+      health: {mode: 'id', events: {change: this.onChangeFilters}},
     };
   }
 
@@ -54,9 +64,53 @@ class ModuleSearch extends SearchDropdown {
       : q;
   }
 
+  // Full text is ranked server-side over the whole index (summary, docs,
+  // models, fields, views...), which the bulk list doesn't carry.
+  searchRemote(query) {
+    if (this.#el_field.value !== 'fulltext') {
+      return null;
+    }
+    const params = new URLSearchParams({
+      term: query,
+      odoo_version: this.#el_version.value,
+    });
+    return getService('requests')
+      .getJSON(`/common/odoo/module/search?${params}`)
+      .then(modules => modules.filter(module => this.#healthMatches(module)));
+  }
+
   recordMatchesFilters(module) {
     const version = this.#el_version.value;
-    return version === '' || module.versions.includes(version);
+    return (
+      (version === '' || module.versions.includes(version)) &&
+      this.#healthMatches(module)
+    );
+  }
+
+  // The score of the selected Odoo version, or of the newest one the module
+  // has when all of them are listed.
+  #healthScore(module) {
+    const health =
+      this.#health_by_module.get(
+        `${module.org_name}/${module.technical_name}`,
+      ) ?? {};
+    const version =
+      this.#el_version.value ||
+      Object.keys(health).sort((a, b) => parseFloat(b) - parseFloat(a))[0];
+    return health[version];
+  }
+
+  #healthMatches(module) {
+    const min_score = parseInt(this.#el_health.value, 10);
+    return Number.isNaN(min_score) || this.#healthScore(module) >= min_score;
+  }
+
+  sortResults(modules) {
+    if (this.#el_health.value === '') {
+      return modules;
+    }
+    const score = module => this.#healthScore(module) ?? -1;
+    return [...modules].sort((a, b) => score(b) - score(a));
   }
 
   searchKey(module) {
@@ -72,8 +126,24 @@ class ModuleSearch extends SearchDropdown {
     const el_text = document.createElement('div');
     el_text.classList.add('item-text');
     el_text.innerHTML = `<div>${module.technical_name}</div><div class="info">${module.org_name.toUpperCase()}: ${module.versions.join(' - ')}</div>`;
+    const score = this.#healthScore(module);
+    if (score !== undefined) {
+      const el_health = document.createElement('span');
+      el_health.classList.add('item-health');
+      el_health.title = 'Health score';
+      el_health.textContent = ` · health ${score}`;
+      el_text.querySelector('.info').appendChild(el_health);
+    }
     const field = this.#el_field.value;
-    if (field !== 'technical_name') {
+    if (field === 'fulltext') {
+      if (module.snippet) {
+        const el_snippet = document.createElement('div');
+        el_snippet.classList.add('snippet');
+        // Escaped by the server, only the <mark> highlights are markup.
+        el_snippet.innerHTML = module.snippet;
+        el_text.appendChild(el_snippet);
+      }
+    } else if (field !== 'technical_name') {
       const snippet = module[field]?.replace(/\s+/g, ' ').trim();
       if (snippet) {
         const el_snippet = document.createElement('div');
@@ -95,6 +165,14 @@ class ModuleSearch extends SearchDropdown {
     item.appendChild(el_icon);
 
     item_container.appendChild(item);
+
+    // Beside the result rather than inside it: links can't nest.
+    const el_repo = document.createElement('a');
+    el_repo.classList.add('item-repo');
+    el_repo.href = `/repo/${encodeURIComponent(module.org_name)}/${encodeURIComponent(module.repository)}`;
+    el_repo.textContent = module.repository;
+    el_repo.title = `${module.org_name}/${module.repository}`;
+    item_container.appendChild(el_repo);
     return item_container;
   }
 }
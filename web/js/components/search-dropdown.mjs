@@ -9,7 +9,8 @@ const DEBOUNCE_MS = 150;
 //   get searchEndpoint() -> URL of the full record list
 //   searchKey(record)    -> string the query is matched against
 //   createResultItem(record) -> <li> element for one result
-// and may override normalizeQuery(query).
+// and may override normalizeQuery(query), searchRemote(query) and
+// sortResults(records).
 export default class SearchDropdown extends Component {
   #el_search_results = null;
   #active_index = -1;
@@ -19,6 +20,7 @@ export default class SearchDropdown extends Component {
   #search_index = null;
   #prev_query = null;
   #prev_matches = null;
+  #search_seq = 0;
 
   onSetup() {
     Component.useEvents(this.getEventDefs());
@@ -57,6 +59,20 @@ export default class SearchDropdown extends Component {
     return true;
   }
 
+  // Subclasses return a promise of records to have the server search
+  // instead (e.g. ranked full-text search), or null to filter the fetched
+  // list client-side.
+  searchRemote() {
+    return null;
+  }
+
+  // Subclasses return the matches in another order (e.g. by a score picked
+  // in a filter control) - as a new array, the given one is reused for the
+  // next keystroke.
+  sortResults(records) {
+    return records;
+  }
+
   // Re-runs the current search text against the current filters/field. Call
   // after changing a filter control so results reflect it immediately.
   refreshResults() {
@@ -66,9 +82,10 @@ export default class SearchDropdown extends Component {
     this.#active_index = -1;
     const query = this.normalizeQuery(this.queryId('search').value);
     if (query === '') {
+      this.#search_seq += 1;
       this.#fillResults();
     } else {
-      this.#fillResults(this.#filterResults(query));
+      this.#search(query);
     }
   }
 
@@ -98,11 +115,13 @@ export default class SearchDropdown extends Component {
     if (query === '') {
       this.#prev_query = null;
       this.#prev_matches = null;
+      this.#search_seq += 1;
       this.#fillResults();
     } else {
-      this.#debounce_timer = setTimeout(() => {
-        this.#fillResults(this.#filterResults(query));
-      }, DEBOUNCE_MS);
+      this.#debounce_timer = setTimeout(
+        () => this.#search(query),
+        DEBOUNCE_MS,
+      );
     }
   }
 
@@ -178,6 +197,25 @@ export default class SearchDropdown extends Component {
     }
   }
 
+  async #search(query) {
+    const seq = ++this.#search_seq;
+    const remote = this.searchRemote(query);
+    if (remote === null) {
+      this.#fillResults(this.sortResults(this.#filterResults(query)));
+      return;
+    }
+    let results = [];
+    try {
+      results = await remote;
+    } catch {
+      // Same as no match: the dropdown just stays closed.
+    }
+    // Responses can arrive out of order: only the latest query may fill.
+    if (seq === this.#search_seq) {
+      this.#fillResults(this.sortResults(results));
+    }
+  }
+
   #filterResults(query) {
     if (this.#search_index === null) {
       // Lowercase every key once instead of on every keystroke.
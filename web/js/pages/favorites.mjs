@@ -1,6 +1,7 @@
 // Copyright 2026 Alexandre D. Díaz
 import '@scss/pages/favorites.scss';
 import {
+  atlasUrlForModules,
   deletePack,
   encodePackForShare,
   getFavorites,
@@ -59,6 +60,9 @@ function renderFavorites() {
   const favorites = getFavorites();
   list.textContent = '';
   empty.classList.toggle('d-none', favorites.length > 0);
+  const atlas_link = document.getElementById('favorites_atlas_link');
+  atlas_link.classList.toggle('d-none', favorites.length === 0);
+  atlas_link.href = atlasUrlForModules(favorites);
   for (const mod of favorites) {
     list.appendChild(
       createModuleTile(mod, removed => {
@@ -133,6 +137,13 @@ function createPackCard(pack) {
       window.open(`/pack?d=${encodePackForShare(pack)}`, '_blank', 'noopener');
     });
     actions.appendChild(share_btn);
+
+    const atlas_link = document.createElement('a');
+    atlas_link.className = 'btn btn-sm btn-outline-secondary';
+    atlas_link.textContent = 'Atlas';
+    atlas_link.title = 'View these modules and their dependencies in the Atlas';
+    atlas_link.href = atlasUrlForModules(pack.modules, version);
+    actions.appendChild(atlas_link);
   }
 
   // Rename/delete are infrequent - tuck them behind a kebab menu so the
@@ -0,0 +1,11 @@
+// Copyright 2026 Alexandre D. Díaz
+import '@scss/pages/migration_status.scss';
+
+// Selects and radios apply right away; the repository box waits for Enter
+// (the form's own submit), typing shouldn't reload the page on every key.
+const toolbar = document.getElementById('toolbar');
+toolbar?.addEventListener('change', ev => {
+  if (ev.target.name !== 'repo') {
+    toolbar.requestSubmit();
+  }
+});
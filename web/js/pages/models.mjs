@@ -0,0 +1,17 @@
+// Copyright 2026 Alexandre D. Díaz
+import '@scss/pages/models.scss';
+
+const search_input = document.getElementById('model_modules_search');
+if (search_input) {
+  search_input.addEventListener('input', () => {
+    const term = search_input.value.trim().toLowerCase();
+    document
+      .querySelectorAll('#model_modules .model-module[data-search]')
+      .forEach(section => {
+        section.classList.toggle(
+          'd-none',
+          term !== '' && !section.dataset.search.includes(term),
+        );
+      });
+  });
+}
@@ -19,9 +19,9 @@ bindSearchModal('module_search', 'mirlo-module-search');
 // own `.module-version-select` after this script has already run. Re-fetches
 // just this pane instead of reloading the page - a full-page reload has
 // nowhere to send the selection: only the active Odoo-version tab is
-// server-rendered (see pages/module.html), and there's no tab activated
-// from the URL, so a reload on any other tab would silently drop which
-// version was picked.
+// server-rendered (see pages/module.html), and the URL can only pick the
+// tab (see below), not the version in it, so a reload would silently drop
+// which version was picked.
 document
   .getElementById('versions-tabContent')
   ?.addEventListener('change', ev => {
@@ -39,6 +39,17 @@ document
 // versions doesn't ship every version's full code analysis on initial load.
 initLazyTabPanes('versions-tab');
 
+// `#version-X` opens that Odoo version's tab (e.g. links from /security).
+// Waits for `load` so bootstrap (main.mjs, also async) handles the click.
+window.addEventListener('load', () => {
+  const match = window.location.hash.match(/^#(version-.+)$/);
+  if (match) {
+    document
+      .getElementById(`${decodeURIComponent(match[1])}-tab`)
+      ?.click();
+  }
+});
+
 // Packs are built for one Odoo version (see favorites-store.mjs), so a new
 // one needs a version at creation time - use whichever version tab is
 // currently active on this page. Returns null on the PR tabs (id="pr-N",
@@ -3,8 +3,11 @@ import '@scss/pages/pack.scss';
 import QRCode from 'qrcode';
 import {
   addToPack,
+  atlasUrlForModules,
   createPack,
   decodeSharedPack,
+  erdUrlForModules,
+  routesUrlForModules,
 } from '@app/utils/favorites-store';
 import {exportPackZip} from '@app/utils/pack-export';
 import {createModuleTileLink} from '@app/utils/module-tile';
@@ -94,6 +97,22 @@ async function refresh(shared, odoo_version) {
   document.getElementById('pack_stat_size_note').textContent = odoo_version
     ? `For Odoo ${odoo_version}`
     : 'Latest tracked version per module';
+  document.getElementById('pack_atlas_link').href = atlasUrlForModules(
+    shared.modules,
+    odoo_version,
+  );
+  const erd_url = erdUrlForModules(shared.modules, odoo_version);
+  const erd_link = document.getElementById('pack_erd_link');
+  erd_link.classList.toggle('d-none', !erd_url);
+  if (erd_url) {
+    erd_link.href = erd_url;
+  }
+  const routes_url = routesUrlForModules(shared.modules, odoo_version);
+  const routes_link = document.getElementById('pack_routes_link');
+  routes_link.classList.toggle('d-none', !routes_url);
+  if (routes_url) {
+    routes_link.href = routes_url;
+  }
   return info_by_key;
 }
 
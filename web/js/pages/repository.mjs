@@ -0,0 +1,17 @@
+// Copyright 2026 Alexandre D. Díaz
+import '@scss/pages/repository.scss';
+
+const search_input = document.getElementById('repo_ports_search');
+if (search_input) {
+  search_input.addEventListener('input', () => {
+    const term = search_input.value.trim().toLowerCase();
+    document
+      .querySelectorAll('#repo_ports_table tbody tr[data-search]')
+      .forEach(row => {
+        row.classList.toggle(
+          'd-none',
+          term !== '' && !row.dataset.search.includes(term),
+        );
+      });
+  });
+}
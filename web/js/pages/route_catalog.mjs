@@ -0,0 +1,17 @@
+// Copyright 2026 Alexandre D. Díaz
+import '@scss/pages/route_catalog.scss';
+
+const search_input = document.getElementById('route_catalog_search');
+if (search_input) {
+  search_input.addEventListener('input', () => {
+    const term = search_input.value.trim().toLowerCase();
+    document
+      .querySelectorAll('#route_catalog_table tbody tr[data-search]')
+      .forEach(row => {
+        row.classList.toggle(
+          'd-none',
+          term !== '' && !row.dataset.search.includes(term),
+        );
+      });
+  });
+}
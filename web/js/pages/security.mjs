@@ -0,0 +1,7 @@
+// Copyright 2026 Alexandre D. Díaz
+import '@scss/pages/security.scss';
+
+// Every filter applies as soon as it changes; the submit button is only
+// there without JavaScript.
+const toolbar = document.getElementById('toolbar');
+toolbar?.addEventListener('change', () => toolbar.requestSubmit());
@@ -110,6 +110,42 @@ export function removeFromPack(id, mod) {
   }
 }
 
+// The Atlas restricted to these modules and what they depend on. No version
+// means the Atlas' default (the latest one).
+export function atlasUrlForModules(modules, odoo_version) {
+  const params = new URLSearchParams();
+  if (odoo_version) {
+    params.set('version', odoo_version);
+  }
+  params.set('modules', modules.map(mod => mod.technical_name).join(','));
+  return `/atlas?${params}`;
+}
+
+// The ER diagram of these modules' models, every module's fields merged into
+// one entity per model. Unlike the Atlas it needs a version: null without.
+export function erdUrlForModules(modules, odoo_version) {
+  if (!odoo_version) {
+    return null;
+  }
+  const params = new URLSearchParams({
+    modules: modules.map(mod => mod.technical_name).join(','),
+    merge: 'true',
+  });
+  return `/erd/${encodeURIComponent(odoo_version)}/svg?${params}`;
+}
+
+// The HTTP routes these modules expose, for a security review of the pack.
+// Like the ER diagram it needs a version.
+export function routesUrlForModules(modules, odoo_version) {
+  if (!odoo_version) {
+    return null;
+  }
+  const params = new URLSearchParams({
+    modules: modules.map(mod => mod.technical_name).join(','),
+  });
+  return `/routes/${encodeURIComponent(odoo_version)}?${params}`;
+}
+
 // Packs are shared as a self-contained URL (no server-side pack storage
 // exists - see the module comment at the top of this file), so the ?d=
 // param must carry the whole pack. Keep it minimal: name + [org,